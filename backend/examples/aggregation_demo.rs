@@ -29,7 +29,7 @@ async fn main() -> Result<()> {
     info!("Migrations completed");
 
     // Create database instance
-    let db = Arc::new(Database::new(pool));
+    let db = Arc::new(Database::new(pool.into()));
 
     // Configure aggregation service
     let config = AggregationConfig {
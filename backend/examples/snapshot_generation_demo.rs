@@ -33,7 +33,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .max_connections(5)
         .connect(&database_url)
         .await?;
-    let db = Arc::new(Database::new(pool));
+    let db = Arc::new(Database::new(pool.into()));
 
     // Initialize contract service (optional)
     let contract_service = if std::env::var("SNAPSHOT_CONTRACT_ID").is_ok() {
@@ -0,0 +1,35 @@
+-- Ledger Backfill Tables
+-- Backfills split a ledger range into chunks that are ingested concurrently.
+-- Each chunk keeps its own progress cursor so an interrupted job resumes where it stopped.
+
+CREATE TABLE IF NOT EXISTS ledger_backfill_jobs (
+    id TEXT PRIMARY KEY,
+    start_ledger INTEGER NOT NULL,
+    end_ledger INTEGER NOT NULL,
+    chunk_size INTEGER NOT NULL,
+    concurrency INTEGER NOT NULL,
+    source TEXT NOT NULL DEFAULT 'manual', -- 'manual', 'gap_scan'
+    status TEXT NOT NULL DEFAULT 'pending', -- 'pending', 'running', 'completed', 'failed'
+    error TEXT,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    started_at TEXT,
+    completed_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_ledger_backfill_jobs_status ON ledger_backfill_jobs(status);
+CREATE INDEX IF NOT EXISTS idx_ledger_backfill_jobs_created ON ledger_backfill_jobs(created_at DESC);
+
+CREATE TABLE IF NOT EXISTS ledger_backfill_chunks (
+    job_id TEXT NOT NULL REFERENCES ledger_backfill_jobs(id) ON DELETE CASCADE,
+    chunk_start INTEGER NOT NULL,
+    chunk_end INTEGER NOT NULL,
+    next_ledger INTEGER NOT NULL, -- first ledger not yet ingested in this chunk
+    ledgers_ingested INTEGER NOT NULL DEFAULT 0,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    status TEXT NOT NULL DEFAULT 'pending', -- 'pending', 'running', 'completed', 'failed'
+    error TEXT,
+    updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (job_id, chunk_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_ledger_backfill_chunks_status ON ledger_backfill_chunks(job_id, status);
@@ -0,0 +1,27 @@
+-- Ledger Chain Verification
+-- Every stored ledger keeps the hash of its predecessor so ingestion can verify the
+-- chain links and roll back derived data when the stored chain diverges from the
+-- canonical one (e.g. after switching RPC providers or restoring a corrupted backup).
+
+ALTER TABLE ledgers ADD COLUMN previous_hash TEXT;
+ALTER TABLE ingestion_cursor ADD COLUMN last_ledger_hash TEXT;
+
+-- Rollbacks delete derived rows by ledger
+CREATE INDEX IF NOT EXISTS idx_transactions_ledger ON transactions(ledger_sequence);
+CREATE INDEX IF NOT EXISTS idx_ledger_payments_ledger ON ledger_payments(ledger_sequence);
+
+-- Audit trail of detected forks and the rollbacks they triggered
+CREATE TABLE IF NOT EXISTS ledger_reorgs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    detected_at_ledger INTEGER NOT NULL, -- ledger whose link or hash did not match
+    fork_point INTEGER NOT NULL, -- last ledger still on the canonical chain
+    expected_hash TEXT, -- hash stored locally
+    actual_hash TEXT, -- hash reported by RPC
+    ledgers_rolled_back INTEGER NOT NULL DEFAULT 0,
+    payments_rolled_back INTEGER NOT NULL DEFAULT 0,
+    fee_bumps_rolled_back INTEGER NOT NULL DEFAULT 0,
+    account_merges_rolled_back INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_ledger_reorgs_created ON ledger_reorgs(created_at DESC);
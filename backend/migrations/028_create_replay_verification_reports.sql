@@ -0,0 +1,19 @@
+-- Replay Verification Reports
+-- A verification replay recomputes derived metrics into a shadow state and diffs
+-- it against the live tables. The report of each run is kept for auditing data
+-- integrity after incidents.
+
+CREATE TABLE IF NOT EXISTS replay_verification_reports (
+    session_id TEXT PRIMARY KEY,
+    start_ledger INTEGER NOT NULL,
+    end_ledger INTEGER NOT NULL,
+    rows_compared INTEGER NOT NULL DEFAULT 0,
+    rows_mismatched INTEGER NOT NULL DEFAULT 0,
+    first_diverging_ledger INTEGER, -- NULL when the live tables match the rebuild
+    report TEXT NOT NULL, -- JSON-encoded VerificationReport
+    created_at TIMESTAMP NOT NULL,
+    FOREIGN KEY (session_id) REFERENCES replay_sessions(session_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_replay_verification_reports_created ON replay_verification_reports(created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_replay_verification_reports_mismatched ON replay_verification_reports(rows_mismatched);
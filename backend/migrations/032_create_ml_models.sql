@@ -0,0 +1,14 @@
+-- Trained machine learning models
+-- Every training run is stored as a new version so the served model survives restarts
+-- and earlier versions can be compared against it.
+CREATE TABLE IF NOT EXISTS ml_models (
+    id TEXT PRIMARY KEY,
+    model_name TEXT NOT NULL, -- e.g. 'payment_success'
+    version INTEGER NOT NULL,
+    artifact TEXT NOT NULL, -- JSON-encoded model parameters
+    feature_schema TEXT NOT NULL, -- JSON array of feature names, in model input order
+    metrics TEXT NOT NULL, -- JSON-encoded held-out evaluation metrics
+    training_samples INTEGER NOT NULL,
+    trained_at TEXT NOT NULL,
+    UNIQUE (model_name, version)
+);
@@ -0,0 +1,37 @@
+-- Machine learning model registry and prediction log
+-- Each model version is 'candidate' (registered only), 'shadow' (scored alongside the
+-- active model without affecting responses), 'active' (serving) or 'retired'. At most one
+-- version of a model is active.
+ALTER TABLE ml_models ADD COLUMN status TEXT NOT NULL DEFAULT 'candidate';
+ALTER TABLE ml_models ADD COLUMN status_changed_at TEXT;
+
+-- Before the registry the latest version was the one served
+UPDATE ml_models SET status = 'active'
+WHERE (model_name, version) IN (
+    SELECT model_name, MAX(version) FROM ml_models GROUP BY model_name
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_ml_models_active ON ml_models(model_name) WHERE status = 'active';
+
+-- One row per model scoring a prediction request; rows of the same request share a
+-- prediction_id so active and shadow models are compared on identical traffic.
+CREATE TABLE IF NOT EXISTS ml_predictions (
+    id TEXT PRIMARY KEY,
+    prediction_id TEXT NOT NULL,
+    model_name TEXT NOT NULL,
+    model_version INTEGER NOT NULL,
+    role TEXT NOT NULL, -- 'active' | 'shadow'
+    asset_code TEXT NOT NULL,
+    asset_issuer TEXT NOT NULL,
+    amount_usd REAL NOT NULL,
+    features TEXT NOT NULL, -- JSON-encoded PredictionFeatures
+    probability REAL NOT NULL,
+    predicted_at TEXT NOT NULL,
+    transaction_hash TEXT, -- reported by the client once the payment was submitted
+    outcome INTEGER, -- NULL until observed
+    outcome_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_ml_predictions_prediction ON ml_predictions(prediction_id);
+CREATE INDEX IF NOT EXISTS idx_ml_predictions_model ON ml_predictions(model_name, model_version, predicted_at);
+CREATE INDEX IF NOT EXISTS idx_ml_predictions_unresolved ON ml_predictions(transaction_hash) WHERE outcome IS NULL;
@@ -0,0 +1,21 @@
+-- Combined DEX liquidity snapshots
+-- Periodic depth curves of a corridor's order book and liquidity pools taken together.
+-- The base asset is the corridor's first asset; depths are in base units, with the 1%
+-- depth also kept in USD for trend queries.
+CREATE TABLE IF NOT EXISTS dex_liquidity_snapshots (
+    id TEXT PRIMARY KEY,
+    corridor_key TEXT NOT NULL,
+    base_asset TEXT NOT NULL, -- 'native' or 'CODE:ISSUER'
+    counter_asset TEXT NOT NULL,
+    mid_price REAL NOT NULL, -- counter units per base unit
+    spread_bps REAL,
+    base_usd_price REAL,
+    book_levels INTEGER NOT NULL DEFAULT 0,
+    pool_count INTEGER NOT NULL DEFAULT 0,
+    depth_curve TEXT NOT NULL, -- JSON array of depth points at 0.1/0.5/1/2% slippage
+    depth_1pct_usd REAL,
+    captured_at TEXT NOT NULL -- RFC 3339
+);
+
+CREATE INDEX IF NOT EXISTS idx_dex_liquidity_snapshots_corridor ON dex_liquidity_snapshots(corridor_key, captured_at);
+CREATE INDEX IF NOT EXISTS idx_dex_liquidity_snapshots_captured ON dex_liquidity_snapshots(captured_at);
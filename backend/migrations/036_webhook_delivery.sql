@@ -0,0 +1,47 @@
+-- Webhook delivery scheduling, attempt history and dead letters
+-- Failed deliveries are retried with exponential backoff at next_attempt_at; events
+-- that run out of attempts, or whose endpoint was disabled, move to the dead-letter
+-- store until they are redelivered.
+ALTER TABLE webhook_events ADD COLUMN next_attempt_at TEXT; -- RFC 3339, NULL = due now
+ALTER TABLE webhook_events ADD COLUMN delivered_at TEXT;
+
+-- Endpoints that keep failing for long enough are disabled
+ALTER TABLE webhooks ADD COLUMN consecutive_failures INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE webhooks ADD COLUMN failing_since TEXT; -- first failure of the current streak
+ALTER TABLE webhooks ADD COLUMN disabled_at TEXT;
+ALTER TABLE webhooks ADD COLUMN disabled_reason TEXT;
+
+-- One row per HTTP request made for an event
+CREATE TABLE IF NOT EXISTS webhook_delivery_attempts (
+    id TEXT PRIMARY KEY,
+    event_id TEXT NOT NULL REFERENCES webhook_events(id) ON DELETE CASCADE,
+    webhook_id TEXT NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
+    attempt INTEGER NOT NULL, -- 1-based
+    status_code INTEGER, -- NULL when no response was received
+    latency_ms INTEGER NOT NULL,
+    response_snippet TEXT, -- start of the response body
+    error TEXT,
+    attempted_at TEXT NOT NULL -- RFC 3339
+);
+
+CREATE TABLE IF NOT EXISTS webhook_dead_letters (
+    event_id TEXT PRIMARY KEY REFERENCES webhook_events(id) ON DELETE CASCADE,
+    webhook_id TEXT NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
+    reason TEXT NOT NULL,
+    attempts INTEGER NOT NULL,
+    dead_lettered_at TEXT NOT NULL -- RFC 3339
+);
+
+-- Events that exhausted the previous fixed retry limit become dead letters
+INSERT INTO webhook_dead_letters (event_id, webhook_id, reason, attempts, dead_lettered_at)
+SELECT id, webhook_id, COALESCE(last_error, 'max_attempts_exceeded'), retries, created_at
+FROM webhook_events WHERE status = 'failed';
+UPDATE webhook_events SET status = 'dead_lettered' WHERE status = 'failed';
+
+CREATE INDEX IF NOT EXISTS idx_webhook_events_due ON webhook_events(status, next_attempt_at);
+CREATE INDEX IF NOT EXISTS idx_webhook_delivery_attempts_event
+    ON webhook_delivery_attempts(event_id, attempted_at);
+CREATE INDEX IF NOT EXISTS idx_webhook_delivery_attempts_webhook
+    ON webhook_delivery_attempts(webhook_id, attempted_at);
+CREATE INDEX IF NOT EXISTS idx_webhook_dead_letters_webhook
+    ON webhook_dead_letters(webhook_id, dead_lettered_at);
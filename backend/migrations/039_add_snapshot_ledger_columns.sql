@@ -0,0 +1,7 @@
+-- Record the ledger and transaction of each snapshot submission. 022 declared these
+-- columns, but the snapshots table from 002 already existed, so they were never added.
+
+ALTER TABLE snapshots ADD COLUMN ledger_sequence INTEGER;
+ALTER TABLE snapshots ADD COLUMN transaction_hash TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_snapshots_ledger ON snapshots(ledger_sequence);
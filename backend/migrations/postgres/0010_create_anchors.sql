@@ -0,0 +1,53 @@
+-- Create anchors table for tracking anchor performance metrics
+CREATE TABLE IF NOT EXISTS anchors (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    stellar_account TEXT NOT NULL UNIQUE,
+    home_domain TEXT,
+    total_transactions BIGINT DEFAULT 0,
+    successful_transactions BIGINT DEFAULT 0,
+    failed_transactions BIGINT DEFAULT 0,
+    total_volume_usd DOUBLE PRECISION DEFAULT 0,
+    avg_settlement_time_ms INTEGER DEFAULT 0,
+    reliability_score DOUBLE PRECISION DEFAULT 0,
+    status TEXT DEFAULT 'green',
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Create assets table for tracking issued assets per anchor
+CREATE TABLE IF NOT EXISTS assets (
+    id TEXT PRIMARY KEY,
+    anchor_id TEXT NOT NULL REFERENCES anchors(id) ON DELETE CASCADE,
+    asset_code TEXT NOT NULL,
+    asset_issuer TEXT NOT NULL,
+    total_supply DOUBLE PRECISION,
+    num_holders BIGINT DEFAULT 0,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(asset_code, asset_issuer)
+);
+
+-- Create anchor_metrics_history table for time-series data
+CREATE TABLE IF NOT EXISTS anchor_metrics_history (
+    id TEXT PRIMARY KEY,
+    anchor_id TEXT NOT NULL REFERENCES anchors(id) ON DELETE CASCADE,
+    timestamp TIMESTAMPTZ NOT NULL,
+    success_rate DOUBLE PRECISION NOT NULL,
+    failure_rate DOUBLE PRECISION NOT NULL,
+    reliability_score DOUBLE PRECISION NOT NULL,
+    total_transactions BIGINT NOT NULL,
+    successful_transactions BIGINT NOT NULL,
+    failed_transactions BIGINT NOT NULL,
+    avg_settlement_time_ms INTEGER,
+    volume_usd DOUBLE PRECISION,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Create indexes for better query performance
+CREATE INDEX idx_anchors_reliability ON anchors(reliability_score DESC);
+CREATE INDEX idx_anchors_status ON anchors(status);
+CREATE INDEX idx_anchors_stellar_account ON anchors(stellar_account);
+CREATE INDEX idx_assets_anchor ON assets(anchor_id);
+CREATE INDEX idx_anchor_metrics_anchor_time ON anchor_metrics_history(anchor_id, timestamp DESC);
+CREATE INDEX idx_anchor_metrics_timestamp ON anchor_metrics_history(timestamp DESC);
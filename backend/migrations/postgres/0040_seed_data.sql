@@ -0,0 +1,25 @@
+-- Seed Initial Anchor Data
+INSERT INTO anchors (id, name, stellar_account, home_domain, total_transactions, successful_transactions, failed_transactions, total_volume_usd, avg_settlement_time_ms, reliability_score, status)
+VALUES 
+('c1b1f1a1-1111-4111-a111-111111111111', 'Circle', 'GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN', 'circle.com', 10000, 9900, 100, 1500000.00, 2000, 99.0, 'green'),
+('c2b2f2a2-2222-4222-a222-222222222222', 'AnchorUSD', 'GDUKMGUGDZQK6YHYA5Z6AY2G4XDSZPSZ3SW5UN3ARVMO6QSRDWP5YLEX', 'anchorusd.com', 5000, 4800, 200, 750000.00, 4500, 95.0, 'yellow'),
+('c3b3f3a3-3333-4333-a333-333333333333', 'MoneyGram Access', 'GA7FCCMTTSUIC37PODEL6EOOSPDRILP6OQI5FWCWDDVDBLJV72W6RINZ', 'moneygram.com', 8500, 8415, 85, 980000.00, 1800, 98.5, 'green');
+
+-- Seed Asset Data
+INSERT INTO assets (id, anchor_id, asset_code, asset_issuer, total_supply, num_holders)
+VALUES 
+('a1a1a1a1-1111-4111-a111-111111111111', 'c1b1f1a1-1111-4111-a111-111111111111', 'USDC', 'GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN', 1000000000, 150000),
+('a2a2a2a2-2222-4222-a222-222222222222', 'c1b1f1a1-1111-4111-a111-111111111111', 'EURC', 'GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN', 500000000, 50000),
+('a3a3a3a3-3333-4333-a333-333333333333', 'c2b2f2a2-2222-4222-a222-222222222222', 'USD', 'GDUKMGUGDZQK6YHYA5Z6AY2G4XDSZPSZ3SW5UN3ARVMO6QSRDWP5YLEX', 200000000, 80000);
+
+-- Seed Corridor Data
+INSERT INTO corridors (id, source_asset_code, source_asset_issuer, destination_asset_code, destination_asset_issuer, reliability_score, status)
+VALUES 
+('d1d1d1d1-1111-4111-a111-111111111111', 'USDC', 'GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN', 'EURC', 'GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN', 98.5, 'active'),
+('d2d2d2d2-2222-4222-a222-222222222222', 'XLM', 'native', 'USDC', 'GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN', 99.2, 'active');
+
+-- Seed Initial Metrics
+INSERT INTO metrics (id, name, value, entity_id, entity_type, timestamp)
+VALUES 
+('m1m1m1m1-1111-4111-a111-111111111111', 'transaction_success_rate', 99.5, 'c1b1f1a1-1111-4111-a111-111111111111', 'anchor', CURRENT_TIMESTAMP),
+('m2m2m2m2-2222-4222-a222-222222222222', 'avg_settlement_time', 1200, 'd1d1d1d1-1111-4111-a111-111111111111', 'corridor', CURRENT_TIMESTAMP);
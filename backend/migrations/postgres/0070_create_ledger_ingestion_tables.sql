@@ -0,0 +1,43 @@
+-- Ledger Ingestion Tables
+
+CREATE TABLE IF NOT EXISTS ingestion_cursor (
+    id BIGSERIAL PRIMARY KEY,
+    last_ledger_sequence BIGINT NOT NULL,
+    cursor TEXT,
+    updated_at TEXT DEFAULT to_char(CURRENT_TIMESTAMP AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS')
+);
+
+CREATE TABLE IF NOT EXISTS ledgers (
+    sequence BIGINT PRIMARY KEY,
+    hash TEXT NOT NULL,
+    close_time TIMESTAMPTZ NOT NULL,
+    transaction_count INTEGER DEFAULT 0,
+    operation_count INTEGER DEFAULT 0,
+    created_at TEXT DEFAULT to_char(CURRENT_TIMESTAMP AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS')
+);
+
+CREATE TABLE IF NOT EXISTS transactions (
+    hash TEXT PRIMARY KEY,
+    ledger_sequence BIGINT NOT NULL REFERENCES ledgers(sequence),
+    source_account TEXT,
+    fee BIGINT,
+    operation_count INTEGER,
+    successful BOOLEAN,
+    created_at TEXT DEFAULT to_char(CURRENT_TIMESTAMP AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS')
+);
+
+CREATE TABLE IF NOT EXISTS ledger_payments (
+    id BIGSERIAL PRIMARY KEY,
+    ledger_sequence BIGINT NOT NULL REFERENCES ledgers(sequence),
+    transaction_hash TEXT NOT NULL, -- references transactions(hash) but we might ingest payments without full tx indexing if optimized
+    operation_type TEXT,
+    source_account TEXT,
+    destination TEXT,
+    asset_code TEXT,
+    asset_issuer TEXT,
+    amount TEXT,
+    created_at TEXT DEFAULT to_char(CURRENT_TIMESTAMP AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS')
+);
+
+CREATE INDEX IF NOT EXISTS idx_ledger_payments_account ON ledger_payments(source_account);
+CREATE INDEX IF NOT EXISTS idx_ledger_payments_destination ON ledger_payments(destination);
@@ -0,0 +1,22 @@
+-- Migration: Create admin_audit_log table for tamper-proof admin action logging
+
+CREATE TABLE IF NOT EXISTS admin_audit_log (
+    id TEXT PRIMARY KEY,
+    timestamp TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    action VARCHAR(100) NOT NULL,
+    resource VARCHAR(255) NOT NULL,
+    user_id TEXT NOT NULL,
+    status VARCHAR(20) NOT NULL,
+    details JSONB,
+    hash TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_admin_audit_timestamp ON admin_audit_log(timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_admin_audit_action ON admin_audit_log(action);
+CREATE INDEX IF NOT EXISTS idx_admin_audit_resource ON admin_audit_log(resource);
+CREATE INDEX IF NOT EXISTS idx_admin_audit_user_id ON admin_audit_log(user_id);
+CREATE INDEX IF NOT EXISTS idx_admin_audit_status ON admin_audit_log(status);
+
+-- Tamper-proof: hash column stores chained hash of previous log entry and current data
+-- To verify, recompute hash chain from first entry
@@ -0,0 +1,14 @@
+-- Migration: Create api_usage_stats table for tracking API usage analytics
+CREATE TABLE IF NOT EXISTS api_usage_stats (
+    id TEXT PRIMARY KEY,
+    endpoint TEXT NOT NULL,
+    method TEXT NOT NULL,
+    status_code INTEGER NOT NULL,
+    response_time_ms INTEGER NOT NULL,
+    user_id TEXT,
+    timestamp TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_usage_timestamp ON api_usage_stats(timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_api_usage_endpoint ON api_usage_stats(endpoint);
+CREATE INDEX IF NOT EXISTS idx_api_usage_status_code ON api_usage_stats(status_code);
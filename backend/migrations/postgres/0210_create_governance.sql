@@ -0,0 +1,46 @@
+-- Governance proposals table
+CREATE TABLE IF NOT EXISTS governance_proposals (
+    id TEXT PRIMARY KEY,
+    title TEXT NOT NULL,
+    description TEXT,
+    proposal_type TEXT DEFAULT 'contract_upgrade',
+    target_contract TEXT,
+    new_wasm_hash TEXT,
+    status TEXT DEFAULT 'draft',
+    created_by TEXT NOT NULL,
+    on_chain_id BIGINT,
+    voting_ends_at TEXT,
+    finalized_at TEXT,
+    executed_at TEXT,
+    created_at TEXT DEFAULT to_char(CURRENT_TIMESTAMP AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS'),
+    updated_at TEXT DEFAULT to_char(CURRENT_TIMESTAMP AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS')
+);
+
+-- Governance votes table
+CREATE TABLE IF NOT EXISTS governance_votes (
+    id TEXT PRIMARY KEY,
+    proposal_id TEXT NOT NULL,
+    voter_address TEXT NOT NULL,
+    choice TEXT NOT NULL,
+    tx_hash TEXT,
+    voted_at TEXT DEFAULT to_char(CURRENT_TIMESTAMP AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS'),
+    FOREIGN KEY (proposal_id) REFERENCES governance_proposals(id),
+    UNIQUE(proposal_id, voter_address)
+);
+
+-- Governance comments table
+CREATE TABLE IF NOT EXISTS governance_comments (
+    id TEXT PRIMARY KEY,
+    proposal_id TEXT NOT NULL,
+    author_address TEXT NOT NULL,
+    content TEXT NOT NULL,
+    created_at TEXT DEFAULT to_char(CURRENT_TIMESTAMP AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS'),
+    FOREIGN KEY (proposal_id) REFERENCES governance_proposals(id)
+);
+
+-- Indexes
+CREATE INDEX IF NOT EXISTS idx_governance_proposals_status ON governance_proposals(status);
+CREATE INDEX IF NOT EXISTS idx_governance_proposals_created_by ON governance_proposals(created_by);
+CREATE INDEX IF NOT EXISTS idx_governance_votes_proposal_id ON governance_votes(proposal_id);
+CREATE INDEX IF NOT EXISTS idx_governance_votes_voter_address ON governance_votes(voter_address);
+CREATE INDEX IF NOT EXISTS idx_governance_comments_proposal_id ON governance_comments(proposal_id);
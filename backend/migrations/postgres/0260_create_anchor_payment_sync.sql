@@ -0,0 +1,28 @@
+-- Anchor Payment Sync Tables
+-- Anchor metrics are accumulated incrementally from each anchor's full payment history.
+-- The sync row keeps the Horizon paging cursor and running counters so every run only
+-- reads payments that arrived since the previous one.
+
+CREATE TABLE IF NOT EXISTS anchor_payment_sync (
+    stellar_account TEXT PRIMARY KEY,
+    cursor TEXT, -- paging_token of the last payment processed
+    total_payments BIGINT NOT NULL DEFAULT 0,
+    successful_payments BIGINT NOT NULL DEFAULT 0,
+    failed_payments BIGINT NOT NULL DEFAULT 0,
+    total_volume DOUBLE PRECISION NOT NULL DEFAULT 0,
+    settlement_samples BIGINT NOT NULL DEFAULT 0, -- payments with a measurable settlement time
+    settlement_time_total_ms BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Transfer timestamps reported by anchors through SEP-24/SEP-31, keyed by the
+-- on-chain transaction that settled the transfer
+CREATE TABLE IF NOT EXISTS anchor_sep_transactions (
+    stellar_transaction_id TEXT PRIMARY KEY,
+    protocol TEXT NOT NULL, -- 'sep24', 'sep31'
+    transfer_id TEXT NOT NULL,
+    status TEXT,
+    started_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,34 @@
+-- Multi-resolution corridor metric rollups
+-- Daily, weekly and monthly buckets derived from corridor_metrics_hourly (days from
+-- hours, weeks and months from days). Each bucket is recomputed in full from its
+-- source buckets, so re-running a rollup is idempotent.
+CREATE TABLE IF NOT EXISTS corridor_metrics_rollups (
+    resolution TEXT NOT NULL, -- 'day', 'week', 'month'
+    corridor_key TEXT NOT NULL,
+    asset_a_code TEXT NOT NULL,
+    asset_a_issuer TEXT NOT NULL,
+    asset_b_code TEXT NOT NULL,
+    asset_b_issuer TEXT NOT NULL,
+    bucket_start TEXT NOT NULL, -- RFC 3339 start of the bucket (UTC, weeks start on Monday)
+    total_transactions BIGINT NOT NULL DEFAULT 0,
+    successful_transactions BIGINT NOT NULL DEFAULT 0,
+    failed_transactions BIGINT NOT NULL DEFAULT 0,
+    success_rate DOUBLE PRECISION NOT NULL DEFAULT 0,
+    volume_usd DOUBLE PRECISION NOT NULL DEFAULT 0,
+    avg_slippage_bps DOUBLE PRECISION NOT NULL DEFAULT 0, -- weighted by transaction count
+    avg_settlement_latency_ms INTEGER, -- weighted by transaction count
+    liquidity_depth_usd DOUBLE PRECISION NOT NULL DEFAULT 0, -- time-weighted average
+    source_buckets BIGINT NOT NULL DEFAULT 0, -- hourly buckets folded into this one
+    created_at TEXT DEFAULT to_char(CURRENT_TIMESTAMP AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS'),
+    updated_at TEXT DEFAULT to_char(CURRENT_TIMESTAMP AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS'),
+    PRIMARY KEY (resolution, corridor_key, bucket_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_corridor_metrics_rollups_bucket ON corridor_metrics_rollups(resolution, bucket_start);
+
+-- Per-resolution rollup progress: every bucket starting before completed_until is final
+CREATE TABLE IF NOT EXISTS rollup_watermarks (
+    resolution TEXT PRIMARY KEY,
+    completed_until TEXT NOT NULL, -- RFC 3339
+    updated_at TEXT NOT NULL
+);
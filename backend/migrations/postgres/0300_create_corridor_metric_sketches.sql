@@ -0,0 +1,15 @@
+-- Corridor distribution sketches
+-- Mergeable quantile sketches of settlement latency and payment size per corridor and
+-- bucket. Hourly sketches are written during aggregation; day/week/month sketches are
+-- merged from finer ones by the rollup job, alongside corridor_metrics_rollups.
+CREATE TABLE IF NOT EXISTS corridor_metric_sketches (
+    resolution TEXT NOT NULL, -- 'hour', 'day', 'week', 'month'
+    corridor_key TEXT NOT NULL,
+    bucket_start TEXT NOT NULL, -- RFC 3339 start of the bucket (UTC)
+    latency_sketch TEXT NOT NULL, -- JSON-encoded QuantileSketch of settlement latency (ms)
+    amount_sketch TEXT NOT NULL, -- JSON-encoded QuantileSketch of payment amounts
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (resolution, corridor_key, bucket_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_corridor_metric_sketches_bucket ON corridor_metric_sketches(resolution, bucket_start);
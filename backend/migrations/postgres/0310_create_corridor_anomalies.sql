@@ -0,0 +1,29 @@
+-- Corridor anomaly detection
+-- Learned per-corridor baselines (one JSON-encoded SeasonalBaseline per metric) and the
+-- anomalies flagged against them. Both are keyed by RFC 3339 hour buckets taken from
+-- corridor_metrics_hourly.
+CREATE TABLE IF NOT EXISTS corridor_anomaly_baselines (
+    corridor_key TEXT NOT NULL,
+    metric TEXT NOT NULL, -- 'success_rate', 'volume', 'latency', 'liquidity'
+    baseline TEXT NOT NULL, -- JSON-encoded SeasonalBaseline
+    last_bucket TEXT NOT NULL, -- RFC 3339 start of the last hour learned from
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (corridor_key, metric)
+);
+
+CREATE TABLE IF NOT EXISTS corridor_anomalies (
+    id TEXT PRIMARY KEY,
+    corridor_key TEXT NOT NULL,
+    metric TEXT NOT NULL,
+    bucket_start TEXT NOT NULL, -- RFC 3339 start of the anomalous hour
+    observed DOUBLE PRECISION NOT NULL,
+    expected DOUBLE PRECISION NOT NULL,
+    z_score DOUBLE PRECISION NOT NULL,
+    direction TEXT NOT NULL, -- 'spike' | 'drop'
+    severity TEXT NOT NULL, -- 'warning' | 'critical'
+    detected_at TEXT NOT NULL,
+    UNIQUE (corridor_key, metric, bucket_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_corridor_anomalies_corridor ON corridor_anomalies(corridor_key, bucket_start);
+CREATE INDEX IF NOT EXISTS idx_corridor_anomalies_bucket ON corridor_anomalies(bucket_start);
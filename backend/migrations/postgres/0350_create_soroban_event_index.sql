@@ -0,0 +1,50 @@
+-- Decoded Soroban contract events
+-- Filled by the contract event indexer from RPC getEvents. Every indexed event is also
+-- written to contract_events for replay; these tables hold the typed governance and
+-- snapshot events. event_id is the RPC event id.
+CREATE TABLE IF NOT EXISTS governance_proposal_events (
+    event_id TEXT PRIMARY KEY,
+    contract_id TEXT NOT NULL,
+    proposal_id BIGINT NOT NULL, -- on-chain proposal id
+    event_type TEXT NOT NULL, -- 'created' or 'finalized'
+    proposer TEXT,
+    target_contract TEXT,
+    voting_ends_at BIGINT, -- ledger timestamp, seconds
+    status TEXT, -- outcome of a finalized proposal
+    votes_for BIGINT,
+    votes_against BIGINT,
+    total_voters BIGINT,
+    ledger_sequence BIGINT NOT NULL,
+    transaction_hash TEXT NOT NULL,
+    closed_at TEXT NOT NULL -- RFC 3339
+);
+
+CREATE INDEX IF NOT EXISTS idx_governance_proposal_events_proposal ON governance_proposal_events(contract_id, proposal_id);
+
+CREATE TABLE IF NOT EXISTS governance_vote_events (
+    event_id TEXT PRIMARY KEY,
+    contract_id TEXT NOT NULL,
+    proposal_id BIGINT NOT NULL,
+    voter TEXT NOT NULL,
+    choice TEXT NOT NULL, -- 'for', 'against' or 'abstain'
+    ledger_sequence BIGINT NOT NULL,
+    transaction_hash TEXT NOT NULL,
+    closed_at TEXT NOT NULL -- RFC 3339
+);
+
+CREATE INDEX IF NOT EXISTS idx_governance_vote_events_proposal ON governance_vote_events(contract_id, proposal_id);
+CREATE INDEX IF NOT EXISTS idx_governance_vote_events_voter ON governance_vote_events(voter);
+
+CREATE TABLE IF NOT EXISTS snapshot_submission_events (
+    event_id TEXT PRIMARY KEY,
+    contract_id TEXT NOT NULL,
+    epoch BIGINT NOT NULL,
+    hash TEXT NOT NULL, -- hex
+    ledger_timestamp BIGINT NOT NULL, -- seconds
+    submitter TEXT,
+    ledger_sequence BIGINT NOT NULL,
+    transaction_hash TEXT NOT NULL,
+    closed_at TEXT NOT NULL -- RFC 3339
+);
+
+CREATE INDEX IF NOT EXISTS idx_snapshot_submission_events_epoch ON snapshot_submission_events(contract_id, epoch);
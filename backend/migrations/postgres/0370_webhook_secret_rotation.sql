@@ -0,0 +1,6 @@
+-- Webhook signing secret rotation
+-- After a rotation the replaced secret keeps signing deliveries, alongside the new
+-- one, until previous_secret_expires_at so receivers can switch over without
+-- rejecting events. Encrypted like secret.
+ALTER TABLE webhooks ADD COLUMN previous_secret TEXT;
+ALTER TABLE webhooks ADD COLUMN previous_secret_expires_at TEXT; -- RFC 3339
@@ -0,0 +1,10 @@
+-- Tie SEP-24/SEP-31 transfer timestamps to the anchor whose stellar.toml declares
+-- the transfer server they were read from. Earlier rows were stored for whatever
+-- transfer server a caller named and cannot be attributed, so they are dropped.
+
+DELETE FROM anchor_sep_transactions;
+
+ALTER TABLE anchor_sep_transactions ADD COLUMN anchor_account TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_anchor_sep_transactions_account
+    ON anchor_sep_transactions(anchor_account);
@@ -0,0 +1,7 @@
+-- Record the ledger and transaction of each snapshot submission. 0220 does not
+-- re-declare the snapshots table from 0020, so these columns are added here.
+
+ALTER TABLE snapshots ADD COLUMN ledger_sequence BIGINT;
+ALTER TABLE snapshots ADD COLUMN transaction_hash TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_snapshots_ledger ON snapshots(ledger_sequence);
@@ -1,6 +1,7 @@
+use crate::database::DbPool;
+use crate::with_pool;
 use anyhow::Result;
 use chrono::Utc;
-use sqlx::SqlitePool;
 use uuid::Uuid;
 
 #[derive(Debug, Clone)]
@@ -16,11 +17,11 @@ pub struct AdminAuditLogEntry {
 }
 
 pub struct AdminAuditLogger {
-    pool: SqlitePool,
+    pool: DbPool,
 }
 
 impl AdminAuditLogger {
-    pub fn new(pool: SqlitePool) -> Self {
+    pub fn new(pool: DbPool) -> Self {
         Self { pool }
     }
 
@@ -55,22 +56,25 @@ impl AdminAuditLogger {
         };
         let hash = format!("{:x}", md5::compute(hash_input));
 
-        sqlx::query(
-            r#"
-            INSERT INTO admin_audit_log (id, timestamp, action, resource, user_id, status, details, hash)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(&id)
-        .bind(timestamp)
-        .bind(action)
-        .bind(resource)
-        .bind(user_id)
-        .bind(status)
-        .bind(details)
-        .bind(&hash)
-        .execute(&self.pool)
-        .await?;
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO admin_audit_log (id, timestamp, action, resource, user_id, status, details, hash)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                "#,
+            )
+            .bind(&id)
+            .bind(timestamp)
+            .bind(action)
+            .bind(resource)
+            .bind(user_id)
+            .bind(status)
+            .bind(details)
+            .bind(&hash)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
 
         Ok(())
     }
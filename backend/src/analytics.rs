@@ -1,6 +1,9 @@
 use crate::models::{AnchorMetrics, AnchorStatus};
 
+pub mod anomaly;
 pub mod corridor;
+pub mod forecast;
+pub mod quantile_sketch;
 
 /// Performance metrics for an anchor's individual asset
 #[derive(Debug, Clone)]
@@ -0,0 +1,388 @@
+//! Seasonal baselines for corridor metrics and the deviation scores derived from them.
+//!
+//! Each corridor metric is modelled additively as
+//! `level + hour_of_day[h] + day_of_week[d]`, where the level is an exponentially
+//! weighted mean and the two seasonal terms are smoothed offsets from it. The spread of
+//! the residuals around that expectation is tracked as an exponentially weighted
+//! variance, so a new observation is scored by how many standard deviations it sits from
+//! what is normal for that corridor at that hour of that weekday.
+
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Corridor metric watched for anomalies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyMetric {
+    SuccessRate,
+    Volume,
+    Latency,
+    Liquidity,
+}
+
+/// Which way a metric moved relative to its baseline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyDirection {
+    Spike,
+    Drop,
+}
+
+impl AnomalyMetric {
+    pub const ALL: [AnomalyMetric; 4] = [
+        AnomalyMetric::SuccessRate,
+        AnomalyMetric::Volume,
+        AnomalyMetric::Latency,
+        AnomalyMetric::Liquidity,
+    ];
+
+    /// Name used for alert rule `metric_type` and stored anomalies
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnomalyMetric::SuccessRate => "success_rate",
+            AnomalyMetric::Volume => "volume",
+            AnomalyMetric::Latency => "latency",
+            AnomalyMetric::Liquidity => "liquidity",
+        }
+    }
+
+    /// Whether a move in `direction` is worth alerting on. Volume is flagged both ways,
+    /// since a sudden surge is as suspicious as a corridor going quiet.
+    pub fn is_adverse(&self, direction: AnomalyDirection) -> bool {
+        match self {
+            AnomalyMetric::SuccessRate | AnomalyMetric::Liquidity => {
+                direction == AnomalyDirection::Drop
+            }
+            AnomalyMetric::Latency => direction == AnomalyDirection::Spike,
+            AnomalyMetric::Volume => true,
+        }
+    }
+
+    /// Volume and liquidity vary multiplicatively, so they are modelled on a log scale
+    fn is_log_scaled(&self) -> bool {
+        matches!(self, AnomalyMetric::Volume | AnomalyMetric::Liquidity)
+    }
+
+    fn to_model(self, value: f64) -> f64 {
+        if self.is_log_scaled() {
+            value.max(0.0).ln_1p()
+        } else {
+            value
+        }
+    }
+
+    fn from_model(self, value: f64) -> f64 {
+        if self.is_log_scaled() {
+            value.exp_m1()
+        } else {
+            value
+        }
+    }
+
+    /// Smallest standard deviation used when scoring, in model units. Keeps a corridor
+    /// that has been perfectly flat from alerting on the first tiny wobble.
+    fn min_std_dev(&self) -> f64 {
+        match self {
+            // percentage points
+            AnomalyMetric::SuccessRate => 0.5,
+            // milliseconds
+            AnomalyMetric::Latency => 25.0,
+            // log scale: roughly a 5% change
+            AnomalyMetric::Volume | AnomalyMetric::Liquidity => 0.05,
+        }
+    }
+}
+
+impl fmt::Display for AnomalyMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AnomalyMetric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AnomalyMetric::ALL
+            .into_iter()
+            .find(|metric| metric.as_str() == s)
+            .ok_or_else(|| format!("unknown anomaly metric '{}'", s))
+    }
+}
+
+impl AnomalyDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnomalyDirection::Spike => "spike",
+            AnomalyDirection::Drop => "drop",
+        }
+    }
+}
+
+/// Smoothing factors of a [`SeasonalBaseline`]
+#[derive(Debug, Clone, Copy)]
+pub struct BaselineSmoothing {
+    /// Weight of each new observation in the level
+    pub level: f64,
+    /// Weight of each new observation in its hour-of-day offset (updated once a day)
+    pub hour_of_day: f64,
+    /// Weight of each new observation in its day-of-week offset (updated hourly)
+    pub day_of_week: f64,
+    /// Weight of each new squared residual in the variance
+    pub variance: f64,
+    /// Residuals are clamped to this many standard deviations before updating, so an
+    /// anomaly only nudges the baseline instead of becoming the new normal
+    pub clamp_z: f64,
+}
+
+impl Default for BaselineSmoothing {
+    fn default() -> Self {
+        Self {
+            level: 0.02,
+            hour_of_day: 0.1,
+            day_of_week: 0.01,
+            variance: 0.02,
+            clamp_z: 3.0,
+        }
+    }
+}
+
+/// How far one observation sits from its baseline
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Deviation {
+    /// Observed value, in the metric's own units
+    pub observed: f64,
+    /// Baseline expectation for this hour and weekday, in the metric's own units
+    pub expected: f64,
+    /// Residual standard deviation, in model units (log scale for volume and liquidity)
+    pub std_dev: f64,
+    /// Signed number of standard deviations from the expectation
+    pub z_score: f64,
+}
+
+impl Deviation {
+    pub fn direction(&self) -> AnomalyDirection {
+        if self.z_score < 0.0 {
+            AnomalyDirection::Drop
+        } else {
+            AnomalyDirection::Spike
+        }
+    }
+}
+
+/// Learned baseline of one corridor metric
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeasonalBaseline {
+    metric: AnomalyMetric,
+    level: f64,
+    variance: f64,
+    hour_of_day: [f64; 24],
+    day_of_week: [f64; 7],
+    observations: u64,
+}
+
+impl SeasonalBaseline {
+    pub fn new(metric: AnomalyMetric) -> Self {
+        Self {
+            metric,
+            level: 0.0,
+            variance: 0.0,
+            hour_of_day: [0.0; 24],
+            day_of_week: [0.0; 7],
+            observations: 0,
+        }
+    }
+
+    pub fn metric(&self) -> AnomalyMetric {
+        self.metric
+    }
+
+    /// Number of observations the baseline has learned from
+    pub fn observations(&self) -> u64 {
+        self.observations
+    }
+
+    fn slots(at: DateTime<Utc>) -> (usize, usize) {
+        (
+            at.hour() as usize,
+            at.weekday().num_days_from_monday() as usize,
+        )
+    }
+
+    fn expected_model(&self, at: DateTime<Utc>) -> f64 {
+        let (hour, day) = Self::slots(at);
+        self.level + self.hour_of_day[hour] + self.day_of_week[day]
+    }
+
+    fn std_dev(&self) -> f64 {
+        self.variance.sqrt().max(self.metric.min_std_dev())
+    }
+
+    /// Score `value` observed in the hour starting at `at`. Returns `None` until the
+    /// baseline has seen `min_observations` values.
+    pub fn score(&self, at: DateTime<Utc>, value: f64, min_observations: u64) -> Option<Deviation> {
+        if !value.is_finite() || self.observations < min_observations.max(1) {
+            return None;
+        }
+
+        let expected = self.expected_model(at);
+        let std_dev = self.std_dev();
+        Some(Deviation {
+            observed: value,
+            expected: self.metric.from_model(expected),
+            std_dev,
+            z_score: (self.metric.to_model(value) - expected) / std_dev,
+        })
+    }
+
+    /// Learn from `value` observed in the hour starting at `at`
+    pub fn update(&mut self, at: DateTime<Utc>, value: f64, smoothing: &BaselineSmoothing) {
+        if !value.is_finite() {
+            return;
+        }
+        let value = self.metric.to_model(value);
+
+        if self.observations == 0 {
+            self.level = value;
+            self.observations = 1;
+            return;
+        }
+
+        let (hour, day) = Self::slots(at);
+        let mut residual = value - self.expected_model(at);
+        if self.observations > 1 {
+            let limit = smoothing.clamp_z * self.std_dev();
+            residual = residual.clamp(-limit, limit);
+        }
+
+        // Early on the level and variance are plain running averages, so the first few
+        // observations are not swamped by the zero they started from.
+        let warmup = 1.0 / (self.observations + 1) as f64;
+        let level_weight = smoothing.level.max(warmup);
+        let variance_weight = smoothing.variance.max(warmup);
+
+        self.variance += variance_weight * (residual * residual - self.variance);
+        self.level += level_weight * residual;
+        let seasonal_residual = residual * (1.0 - level_weight);
+        self.hour_of_day[hour] += smoothing.hour_of_day * seasonal_residual;
+        self.day_of_week[day] += smoothing.day_of_week * seasonal_residual;
+
+        // Keep each seasonal term centred on zero so it cannot drift into the level
+        let hour_mean = self.hour_of_day.iter().sum::<f64>() / 24.0;
+        let day_mean = self.day_of_week.iter().sum::<f64>() / 7.0;
+        self.hour_of_day.iter_mut().for_each(|v| *v -= hour_mean);
+        self.day_of_week.iter_mut().for_each(|v| *v -= day_mean);
+        self.level += hour_mean + day_mean;
+
+        self.observations += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn start() -> DateTime<Utc> {
+        // A Monday
+        Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+    }
+
+    /// Daily cycle between 80% and 100% with a little deterministic noise
+    fn seasonal_success_rate(at: DateTime<Utc>, i: i64) -> f64 {
+        let daily = if (9..17).contains(&at.hour()) {
+            80.0
+        } else {
+            98.0
+        };
+        daily + ((i * 7919) % 11) as f64 / 10.0 - 0.5
+    }
+
+    fn trained(
+        metric: AnomalyMetric,
+        hours: i64,
+        value: impl Fn(DateTime<Utc>, i64) -> f64,
+    ) -> SeasonalBaseline {
+        let smoothing = BaselineSmoothing::default();
+        let mut baseline = SeasonalBaseline::new(metric);
+        for i in 0..hours {
+            let at = start() + Duration::hours(i);
+            baseline.update(at, value(at, i), &smoothing);
+        }
+        baseline
+    }
+
+    #[test]
+    fn test_learns_hour_of_day_pattern() {
+        let baseline = trained(AnomalyMetric::SuccessRate, 24 * 28, seasonal_success_rate);
+        let next_day = start() + Duration::days(28);
+
+        // The usual midday dip is not anomalous...
+        let midday = baseline
+            .score(next_day + Duration::hours(12), 80.0, 48)
+            .unwrap();
+        assert!(midday.z_score.abs() < 3.0, "{:?}", midday);
+        assert!((midday.expected - 80.0).abs() < 2.0);
+
+        // ...but the same value at night is
+        let night = baseline
+            .score(next_day + Duration::hours(2), 80.0, 48)
+            .unwrap();
+        assert!(night.z_score < -3.0, "{:?}", night);
+        assert_eq!(night.direction(), AnomalyDirection::Drop);
+    }
+
+    #[test]
+    fn test_volume_scored_on_log_scale() {
+        let baseline = trained(AnomalyMetric::Volume, 24 * 14, |_, i| {
+            10_000.0 * (1.0 + ((i * 31) % 7) as f64 / 100.0)
+        });
+        let at = start() + Duration::days(14);
+
+        let usual = baseline.score(at, 10_300.0, 48).unwrap();
+        assert!(usual.z_score.abs() < 3.0, "{:?}", usual);
+
+        let surge = baseline.score(at, 100_000.0, 48).unwrap();
+        assert!(surge.z_score > 3.0, "{:?}", surge);
+        assert!((surge.expected - 10_300.0).abs() < 500.0);
+    }
+
+    #[test]
+    fn test_warmup_and_anomaly_resistance() {
+        let smoothing = BaselineSmoothing::default();
+        let mut baseline = SeasonalBaseline::new(AnomalyMetric::Latency);
+        assert!(baseline.score(start(), 500.0, 1).is_none());
+
+        for i in 0..24 * 7 {
+            baseline.update(
+                start() + Duration::hours(i),
+                500.0 + (i % 5) as f64 * 10.0,
+                &smoothing,
+            );
+        }
+        assert!(baseline.score(start(), 500.0, 24 * 8).is_none());
+
+        // A single outage-sized spike barely moves what is considered normal
+        let at = start() + Duration::days(7);
+        baseline.update(at, 60_000.0, &smoothing);
+        let after = baseline.score(at + Duration::hours(1), 520.0, 48).unwrap();
+        assert!(after.z_score.abs() < 3.0, "{:?}", after);
+        assert!(after.expected < 700.0);
+    }
+
+    #[test]
+    fn test_adverse_directions() {
+        assert!(AnomalyMetric::SuccessRate.is_adverse(AnomalyDirection::Drop));
+        assert!(!AnomalyMetric::SuccessRate.is_adverse(AnomalyDirection::Spike));
+        assert!(AnomalyMetric::Latency.is_adverse(AnomalyDirection::Spike));
+        assert!(!AnomalyMetric::Liquidity.is_adverse(AnomalyDirection::Spike));
+        assert!(AnomalyMetric::Volume.is_adverse(AnomalyDirection::Spike));
+        assert!(AnomalyMetric::Volume.is_adverse(AnomalyDirection::Drop));
+        assert_eq!(
+            "latency".parse::<AnomalyMetric>(),
+            Ok(AnomalyMetric::Latency)
+        );
+    }
+}
@@ -0,0 +1,315 @@
+//! Holt-Winters forecasting of hourly corridor series.
+//!
+//! An additive model with a damped trend and one seasonal cycle (daily, or weekly once
+//! enough history exists) is fitted by grid search on one-step-ahead squared error.
+//! Prediction intervals widen with the horizon following the additive Holt-Winters
+//! error variance, using the residual spread of the fitted one-step forecasts.
+//! Sample paths can be simulated from the same error model for quantities that
+//! depend on several steps at once, such as totals over the horizon.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+pub const DAILY_SEASON: usize = 24;
+pub const WEEKLY_SEASON: usize = 168;
+
+/// Smoothing weights of the level, trend and seasonal components
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HoltWintersParams {
+    pub alpha: f64,
+    pub beta: f64,
+    pub gamma: f64,
+    /// Trend damping; 1.0 extrapolates the trend linearly
+    pub phi: f64,
+}
+
+const ALPHAS: [f64; 8] = [0.02, 0.05, 0.1, 0.2, 0.3, 0.5, 0.7, 0.9];
+const BETAS: [f64; 4] = [0.0, 0.01, 0.05, 0.15];
+const GAMMAS: [f64; 5] = [0.01, 0.05, 0.1, 0.3, 0.5];
+const PHI: f64 = 0.98;
+
+/// Season length the history supports: weekly with three weeks of hours, daily with
+/// two days, otherwise none
+pub fn season_length_for(observations: usize) -> Option<usize> {
+    if observations >= 3 * WEEKLY_SEASON {
+        Some(WEEKLY_SEASON)
+    } else if observations >= 2 * DAILY_SEASON {
+        Some(DAILY_SEASON)
+    } else {
+        None
+    }
+}
+
+/// One forecast step
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ForecastStep {
+    pub mean: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+/// A fitted Holt-Winters model, positioned after the last observation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HoltWinters {
+    pub params: HoltWintersParams,
+    pub season_length: usize,
+    level: f64,
+    trend: f64,
+    /// Seasonal terms indexed by position in the cycle; `seasonal[0]` applies to the
+    /// step right after the last observation
+    seasonal: Vec<f64>,
+    /// Standard deviation of the one-step-ahead errors
+    pub residual_std: f64,
+    pub observations: usize,
+}
+
+impl HoltWinters {
+    /// Fit the parameters that minimise the one-step-ahead error on `series`. Returns
+    /// `None` if the series is shorter than two seasons.
+    pub fn fit(series: &[f64], season_length: usize) -> Option<Self> {
+        let mut best: Option<(f64, Self)> = None;
+        for &alpha in &ALPHAS {
+            for &beta in &BETAS {
+                for &gamma in &GAMMAS {
+                    let params = HoltWintersParams {
+                        alpha,
+                        beta,
+                        gamma,
+                        phi: PHI,
+                    };
+                    let (sse, model) = Self::fit_with(series, season_length, params)?;
+                    if best.as_ref().is_none_or(|(best_sse, _)| sse < *best_sse) {
+                        best = Some((sse, model));
+                    }
+                }
+            }
+        }
+        best.map(|(_, model)| model)
+    }
+
+    /// Run the smoothing recursions over `series` with fixed parameters, returning the
+    /// one-step-ahead sum of squared errors and the final state
+    pub fn fit_with(
+        series: &[f64],
+        season_length: usize,
+        params: HoltWintersParams,
+    ) -> Option<(f64, Self)> {
+        let m = season_length;
+        if m == 0 || series.len() < 2 * m {
+            return None;
+        }
+
+        // Initialise from the first two seasons: level from the first season's mean,
+        // trend from the change between season means, seasonal terms from deviations
+        let first_mean = series[..m].iter().sum::<f64>() / m as f64;
+        let second_mean = series[m..2 * m].iter().sum::<f64>() / m as f64;
+        let mut level = first_mean;
+        let mut trend = (second_mean - first_mean) / m as f64;
+        let mut seasonal: Vec<f64> = series[..m].iter().map(|v| v - first_mean).collect();
+
+        let mut sse = 0.0;
+        let mut errors = 0usize;
+        for (t, &value) in series.iter().enumerate().skip(m) {
+            let season = seasonal[t % m];
+            let predicted = level + params.phi * trend + season;
+            let error = value - predicted;
+            sse += error * error;
+            errors += 1;
+
+            let previous_level = level;
+            level = params.alpha * (value - season)
+                + (1.0 - params.alpha) * (previous_level + params.phi * trend);
+            trend =
+                params.beta * (level - previous_level) + (1.0 - params.beta) * params.phi * trend;
+            seasonal[t % m] = params.gamma * (value - level) + (1.0 - params.gamma) * season;
+        }
+
+        // Rotate so the next step's seasonal term comes first
+        let next = series.len() % m;
+        seasonal.rotate_left(next);
+
+        let residual_std = (sse / errors.max(1) as f64).sqrt();
+        Some((
+            sse,
+            Self {
+                params,
+                season_length: m,
+                level,
+                trend,
+                seasonal,
+                residual_std,
+                observations: series.len(),
+            },
+        ))
+    }
+
+    /// Forecast `horizon` steps with intervals of `z` standard deviations
+    pub fn forecast(&self, horizon: usize, z: f64) -> Vec<ForecastStep> {
+        let HoltWintersParams {
+            alpha,
+            beta,
+            gamma,
+            phi,
+        } = self.params;
+        let m = self.season_length;
+
+        let mut damped_trend = 0.0;
+        let mut phi_power = 1.0;
+        // Sum of squared error weights of the steps before the current one
+        let mut weight_sum = 0.0;
+        (1..=horizon)
+            .map(|h| {
+                phi_power *= phi;
+                damped_trend += phi_power;
+                if h > 1 {
+                    let j = (h - 1) as f64;
+                    let seasonal_weight = if (h - 1) % m == 0 { gamma } else { 0.0 };
+                    weight_sum += (alpha * (1.0 + j * beta) + seasonal_weight).powi(2);
+                }
+
+                let mean = self.level + damped_trend * self.trend + self.seasonal[(h - 1) % m];
+                let half_width = z * self.residual_std * (1.0 + weight_sum).sqrt();
+                ForecastStep {
+                    mean,
+                    lower: mean - half_width,
+                    upper: mean + half_width,
+                }
+            })
+            .collect()
+    }
+
+    /// Sample `paths` trajectories of the next `horizon` steps by feeding normal
+    /// one-step errors with the residual spread through the smoothing recursions. An
+    /// error carries into later steps through the level, trend and seasonal terms, so
+    /// aggregates of a path vary as much as the model implies.
+    pub fn simulate<R: Rng>(&self, horizon: usize, paths: usize, rng: &mut R) -> Vec<Vec<f64>> {
+        let HoltWintersParams {
+            alpha,
+            beta,
+            gamma,
+            phi,
+        } = self.params;
+        let m = self.season_length;
+
+        (0..paths)
+            .map(|_| {
+                let mut level = self.level;
+                let mut trend = self.trend;
+                let mut seasonal = self.seasonal.clone();
+                (0..horizon)
+                    .map(|h| {
+                        let season = seasonal[h % m];
+                        let value =
+                            level + phi * trend + season + self.residual_std * standard_normal(rng);
+
+                        let previous_level = level;
+                        level = alpha * (value - season)
+                            + (1.0 - alpha) * (previous_level + phi * trend);
+                        trend = beta * (level - previous_level) + (1.0 - beta) * phi * trend;
+                        seasonal[h % m] = gamma * (value - level) + (1.0 - gamma) * season;
+                        value
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Standard normal sample (Box-Muller)
+fn standard_normal<R: Rng>(rng: &mut R) -> f64 {
+    let u1: f64 = rng.gen_range(f64::EPSILON..1.0);
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn daily_pattern(hours: usize) -> Vec<f64> {
+        (0..hours)
+            .map(|h| {
+                let hour = (h % DAILY_SEASON) as f64;
+                100.0 + 0.1 * h as f64 + 20.0 * (hour / 24.0 * std::f64::consts::TAU).sin()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_season_length_for() {
+        assert_eq!(season_length_for(47), None);
+        assert_eq!(season_length_for(48), Some(DAILY_SEASON));
+        assert_eq!(season_length_for(3 * WEEKLY_SEASON), Some(WEEKLY_SEASON));
+    }
+
+    #[test]
+    fn test_forecast_follows_seasonal_pattern() {
+        let series = daily_pattern(24 * 14);
+        let model = HoltWinters::fit(&series, DAILY_SEASON).unwrap();
+        let forecast = model.forecast(24, 1.96);
+
+        let expected = daily_pattern(24 * 15);
+        for (step, actual) in forecast.iter().zip(&expected[24 * 14..]) {
+            assert!(
+                (step.mean - actual).abs() < 2.0,
+                "{} vs {}",
+                step.mean,
+                actual
+            );
+            assert!(step.lower <= step.mean && step.mean <= step.upper);
+        }
+    }
+
+    #[test]
+    fn test_intervals_widen_with_horizon() {
+        let series: Vec<f64> = daily_pattern(24 * 14)
+            .into_iter()
+            .enumerate()
+            .map(|(i, v)| v + if i % 3 == 0 { 5.0 } else { -2.5 })
+            .collect();
+        let model = HoltWinters::fit(&series, DAILY_SEASON).unwrap();
+        assert!(model.residual_std > 0.0);
+
+        let forecast = model.forecast(72, 1.96);
+        let width = |step: &ForecastStep| step.upper - step.lower;
+        assert!(width(&forecast[71]) >= width(&forecast[0]));
+    }
+
+    #[test]
+    fn test_simulated_paths_match_forecast() {
+        use rand::SeedableRng;
+
+        let series: Vec<f64> = daily_pattern(24 * 14)
+            .into_iter()
+            .enumerate()
+            .map(|(i, v)| v + if i % 3 == 0 { 5.0 } else { -2.5 })
+            .collect();
+        let model = HoltWinters::fit(&series, DAILY_SEASON).unwrap();
+        let forecast = model.forecast(48, 1.96);
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let paths = model.simulate(48, 4_000, &mut rng);
+        assert_eq!(paths.len(), 4_000);
+        for h in [0, 47] {
+            let values: Vec<f64> = paths.iter().map(|path| path[h]).collect();
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+            let inside = values
+                .iter()
+                .filter(|v| (forecast[h].lower..=forecast[h].upper).contains(v))
+                .count();
+            assert!(
+                (mean - forecast[h].mean).abs() < 0.5,
+                "step {}: {}",
+                h,
+                mean
+            );
+            // About 95% of the paths fall inside each step's interval
+            assert!((3_600..=3_950).contains(&inside), "step {}: {}", h, inside);
+        }
+    }
+
+    #[test]
+    fn test_fit_requires_two_seasons() {
+        assert!(HoltWinters::fit(&daily_pattern(47), DAILY_SEASON).is_none());
+    }
+}
@@ -70,7 +70,10 @@ async fn delete_rule(
     auth_user: AuthUser,
     Path(id): Path<String>,
 ) -> ApiResult<impl IntoResponse> {
-    state.db.delete_alert_rule(&id, &auth_user.user_id).await?;
+    let deleted = state.db.delete_alert_rule(&id, &auth_user.user_id).await?;
+    if deleted == 0 {
+        return Err(ApiError::not_found("NOT_FOUND", "Alert rule not found"));
+    }
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -1,3 +1,5 @@
+use crate::database::DbPool;
+use crate::with_pool;
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
@@ -6,7 +8,6 @@ use axum::{
     Json, Router,
 };
 use serde_json::json;
-use sqlx::SqlitePool;
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -16,7 +17,7 @@ use crate::models::asset_verification::{
 use crate::services::asset_verifier::AssetVerifier;
 
 /// Create asset verification routes
-pub fn routes(pool: SqlitePool) -> Router {
+pub fn routes(pool: DbPool) -> Router {
     Router::new()
         .route("/verify/:code/:issuer", get(verify_asset))
         .route("/:code/:issuer/verification", get(get_verification))
@@ -28,7 +29,7 @@ pub fn routes(pool: SqlitePool) -> Router {
 /// Verify an asset and return its verification status
 /// GET /api/assets/verify/:code/:issuer
 async fn verify_asset(
-    State(pool): State<Arc<SqlitePool>>,
+    State(pool): State<Arc<DbPool>>,
     Path((code, issuer)): Path<(String, String)>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
     // Input validation
@@ -100,7 +101,7 @@ async fn verify_asset(
 /// Get verification details for an asset
 /// GET /api/assets/:code/:issuer/verification
 async fn get_verification(
-    State(pool): State<Arc<SqlitePool>>,
+    State(pool): State<Arc<DbPool>>,
     Path((code, issuer)): Path<(String, String)>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
     // Input validation
@@ -163,7 +164,7 @@ async fn get_verification(
 /// List verified assets with optional filters
 /// GET /api/assets/verified?status=verified&min_reputation=60&limit=50&offset=0
 async fn list_verified_assets(
-    State(pool): State<Arc<SqlitePool>>,
+    State(pool): State<Arc<DbPool>>,
     Query(query): Query<ListVerifiedAssetsQuery>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
     // Validate query parameters
@@ -228,7 +229,7 @@ async fn list_verified_assets(
 /// Report a suspicious asset
 /// POST /api/assets/report
 async fn report_suspicious_asset(
-    State(pool): State<Arc<SqlitePool>>,
+    State(pool): State<Arc<DbPool>>,
     Json(request): Json<ReportAssetRequest>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
     // Input validation
@@ -289,40 +290,46 @@ async fn report_suspicious_asset(
     let report_id = Uuid::new_v4().to_string();
 
     // Insert report into database
-    let result = sqlx::query(
-        r#"
-        INSERT INTO asset_verification_reports (
-            id, asset_code, asset_issuer, reporter_account,
-            report_type, description, evidence_url, status
-        ) VALUES (?, ?, ?, ?, ?, ?, ?, 'pending')
-        "#,
-    )
-    .bind(&report_id)
-    .bind(&request.asset_code)
-    .bind(&request.asset_issuer)
-    .bind(&request.reporter_account)
-    .bind(request.report_type.as_str())
-    .bind(&request.description)
-    .bind(&request.evidence_url)
-    .execute(&*pool)
-    .await;
+    let result = with_pool!(&*pool, |pool| {
+        sqlx::query(
+            r#"
+            INSERT INTO asset_verification_reports (
+                id, asset_code, asset_issuer, reporter_account,
+                report_type, description, evidence_url, status
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, 'pending')
+            "#,
+        )
+        .bind(&report_id)
+        .bind(&request.asset_code)
+        .bind(&request.asset_issuer)
+        .bind(&request.reporter_account)
+        .bind(request.report_type.as_str())
+        .bind(&request.description)
+        .bind(&request.evidence_url)
+        .execute(pool)
+        .await
+        .map(|done| done.rows_affected())
+    });
 
     match result {
         Ok(_) => {
             // Update suspicious reports count
-            let _ = sqlx::query(
-                r#"
-                UPDATE verified_assets
-                SET suspicious_reports_count = suspicious_reports_count + 1,
-                    last_suspicious_report_at = CURRENT_TIMESTAMP,
-                    updated_at = CURRENT_TIMESTAMP
-                WHERE asset_code = ? AND asset_issuer = ?
-                "#,
-            )
-            .bind(&request.asset_code)
-            .bind(&request.asset_issuer)
-            .execute(&*pool)
-            .await;
+            let _ = with_pool!(&*pool, |pool| {
+                sqlx::query(
+                    r#"
+                    UPDATE verified_assets
+                    SET suspicious_reports_count = suspicious_reports_count + 1,
+                        last_suspicious_report_at = CURRENT_TIMESTAMP,
+                        updated_at = CURRENT_TIMESTAMP
+                    WHERE asset_code = $1 AND asset_issuer = $2
+                    "#,
+                )
+                .bind(&request.asset_code)
+                .bind(&request.asset_issuer)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+            });
 
             Ok((
                 StatusCode::CREATED,
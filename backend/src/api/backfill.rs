@@ -0,0 +1,164 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::error::{ApiError, ApiResult};
+use crate::ingestion::backfill::LedgerBackfillService;
+
+#[derive(Deserialize)]
+pub struct CreateBackfillRequest {
+    pub start_ledger: u64,
+    pub end_ledger: u64,
+    pub chunk_size: Option<u64>,
+    pub concurrency: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct ListJobsParams {
+    #[serde(default = "default_limit")]
+    limit: i64,
+}
+
+fn default_limit() -> i64 {
+    50
+}
+
+#[derive(Deserialize)]
+pub struct GapParams {
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+}
+
+pub fn routes(service: Arc<LedgerBackfillService>) -> Router {
+    Router::new()
+        .route(
+            "/api/admin/ingestion/backfill",
+            get(list_backfill_jobs).post(create_backfill_job),
+        )
+        .route("/api/admin/ingestion/backfill/:id", get(get_backfill_job))
+        .route(
+            "/api/admin/ingestion/backfill/:id/resume",
+            post(resume_backfill_job),
+        )
+        .route("/api/admin/ingestion/gaps", get(list_gaps))
+        .route("/api/admin/ingestion/gaps/requeue", post(requeue_gaps))
+        .with_state(service)
+}
+
+/// Handler for POST /api/admin/ingestion/backfill - Start a backfill for a ledger range
+async fn create_backfill_job(
+    State(service): State<Arc<LedgerBackfillService>>,
+    Json(payload): Json<CreateBackfillRequest>,
+) -> ApiResult<impl IntoResponse> {
+    if payload.start_ledger == 0 || payload.end_ledger < payload.start_ledger {
+        return Err(ApiError::bad_request(
+            "INVALID_LEDGER_RANGE",
+            "start_ledger must be positive and not greater than end_ledger",
+        ));
+    }
+
+    let job = service
+        .create_job(
+            payload.start_ledger,
+            payload.end_ledger,
+            payload.chunk_size,
+            payload.concurrency,
+        )
+        .await
+        .map_err(|e| ApiError::bad_request("INVALID_BACKFILL", e.to_string()))?;
+
+    service.spawn_job(job.id.clone());
+
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+/// Handler for GET /api/admin/ingestion/backfill - List recent backfill jobs
+async fn list_backfill_jobs(
+    State(service): State<Arc<LedgerBackfillService>>,
+    Query(params): Query<ListJobsParams>,
+) -> ApiResult<impl IntoResponse> {
+    let jobs = service.list_jobs(params.limit.clamp(1, 500)).await?;
+    Ok(Json(jobs))
+}
+
+/// Handler for GET /api/admin/ingestion/backfill/:id - Job progress with per-chunk state
+async fn get_backfill_job(
+    State(service): State<Arc<LedgerBackfillService>>,
+    Path(id): Path<String>,
+) -> ApiResult<impl IntoResponse> {
+    let job = service.get_job(&id).await?.ok_or_else(|| {
+        ApiError::not_found(
+            "BACKFILL_JOB_NOT_FOUND",
+            format!("Backfill job {} not found", id),
+        )
+    })?;
+    Ok(Json(job))
+}
+
+/// Handler for POST /api/admin/ingestion/backfill/:id/resume - Retry unfinished chunks of a job
+async fn resume_backfill_job(
+    State(service): State<Arc<LedgerBackfillService>>,
+    Path(id): Path<String>,
+) -> ApiResult<impl IntoResponse> {
+    let detail = service.get_job(&id).await?.ok_or_else(|| {
+        ApiError::not_found(
+            "BACKFILL_JOB_NOT_FOUND",
+            format!("Backfill job {} not found", id),
+        )
+    })?;
+
+    if detail.job.status == "running" {
+        return Err(ApiError::bad_request(
+            "BACKFILL_JOB_RUNNING",
+            format!("Backfill job {} is already running", id),
+        ));
+    }
+
+    service.spawn_job(id);
+
+    Ok((StatusCode::ACCEPTED, Json(detail.job)))
+}
+
+/// Handler for GET /api/admin/ingestion/gaps - Ledger ranges missing from storage
+async fn list_gaps(
+    State(service): State<Arc<LedgerBackfillService>>,
+    Query(params): Query<GapParams>,
+) -> ApiResult<impl IntoResponse> {
+    let gaps = service.find_gaps(params.from, params.to).await?;
+    let missing_ledgers: u64 = gaps.iter().map(|g| g.len()).sum();
+
+    Ok(Json(serde_json::json!({
+        "gaps": gaps,
+        "gap_count": gaps.len(),
+        "missing_ledgers": missing_ledgers,
+    })))
+}
+
+/// Handler for POST /api/admin/ingestion/gaps/requeue - Queue detected gaps as a backfill job
+async fn requeue_gaps(
+    State(service): State<Arc<LedgerBackfillService>>,
+    Query(params): Query<GapParams>,
+) -> ApiResult<impl IntoResponse> {
+    match service.requeue_gaps(params.from, params.to).await? {
+        Some(job) => {
+            service.spawn_job(job.id.clone());
+            Ok((
+                StatusCode::ACCEPTED,
+                Json(serde_json::json!({ "job": job })),
+            ))
+        }
+        None => Ok((
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "job": null,
+                "message": "No gaps to requeue",
+            })),
+        )),
+    }
+}
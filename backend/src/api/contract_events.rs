@@ -0,0 +1,98 @@
+use axum::{
+    extract::{Query, State},
+    routing::get,
+    Json, Router,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::database::Database;
+use crate::db::contract_events::{ProposalEventRecord, SnapshotSubmissionRecord, VoteEventRecord};
+use crate::error::{ApiError, ApiResult};
+
+#[derive(Debug, Deserialize)]
+pub struct GovernanceEventsParams {
+    pub contract_id: Option<String>,
+    pub proposal_id: Option<i64>,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SnapshotSubmissionsParams {
+    pub contract_id: Option<String>,
+    pub epoch: Option<i64>,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
+fn default_limit() -> i64 {
+    50
+}
+
+pub fn routes(db: Arc<Database>) -> Router {
+    Router::new()
+        .route("/proposals", get(get_proposal_events))
+        .route("/votes", get(get_vote_events))
+        .route("/snapshots", get(get_snapshot_submissions))
+        .with_state(db)
+}
+
+fn database_error(e: anyhow::Error) -> ApiError {
+    ApiError::internal(
+        "DATABASE_ERROR",
+        format!("Failed to fetch contract events: {}", e),
+    )
+}
+
+/// GET /api/contract-events/proposals - Indexed governance proposal events, newest first
+async fn get_proposal_events(
+    State(db): State<Arc<Database>>,
+    Query(params): Query<GovernanceEventsParams>,
+) -> ApiResult<Json<Vec<ProposalEventRecord>>> {
+    let events = db
+        .contract_event_db()
+        .list_proposal_events(
+            params.contract_id.as_deref(),
+            params.proposal_id,
+            params.limit.clamp(1, 500),
+        )
+        .await
+        .map_err(database_error)?;
+    Ok(Json(events))
+}
+
+/// GET /api/contract-events/votes - Indexed governance votes, newest first
+async fn get_vote_events(
+    State(db): State<Arc<Database>>,
+    Query(params): Query<GovernanceEventsParams>,
+) -> ApiResult<Json<Vec<VoteEventRecord>>> {
+    let votes = db
+        .contract_event_db()
+        .list_votes(
+            params.contract_id.as_deref(),
+            params.proposal_id,
+            params.limit.clamp(1, 500),
+        )
+        .await
+        .map_err(database_error)?;
+    Ok(Json(votes))
+}
+
+/// GET /api/contract-events/snapshots - Indexed on-chain snapshot submissions, newest
+/// first
+async fn get_snapshot_submissions(
+    State(db): State<Arc<Database>>,
+    Query(params): Query<SnapshotSubmissionsParams>,
+) -> ApiResult<Json<Vec<SnapshotSubmissionRecord>>> {
+    let submissions = db
+        .contract_event_db()
+        .list_snapshot_submissions(
+            params.contract_id.as_deref(),
+            params.epoch,
+            params.limit.clamp(1, 500),
+        )
+        .await
+        .map_err(database_error)?;
+    Ok(Json(submissions))
+}
@@ -2,13 +2,22 @@ use axum::{
     extract::{Path, Query, State},
     Json,
 };
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use crate::error::{ApiError, ApiResult};
 use crate::models::corridor::{Corridor, CorridorMetrics};
 use crate::models::SortBy;
+use crate::services::anomaly_detection::CorridorAnomaly;
+use crate::services::dex_aggregator::LiquiditySnapshot;
+use crate::services::forecasting::{
+    forecast_corridor, CorridorForecast, ForecastConfig, MAX_HORIZON_HOURS,
+};
+use crate::services::rollup::{
+    fetch_corridor_distribution, fetch_corridor_series, parse_granularity, CorridorDistribution,
+    CorridorSeries,
+};
 use crate::state::AppState;
 use crate::validation;
 
@@ -120,6 +129,34 @@ fn get_liquidity_trend(volume_usd: f64) -> String {
     }
 }
 
+/// Parse a `CODE:ISSUER->CODE:ISSUER` corridor key
+fn parse_corridor_key(corridor_key: &str) -> ApiResult<Corridor> {
+    let parts: Vec<&str> = corridor_key.split("->").collect();
+    if parts.len() != 2 {
+        return Err(ApiError::bad_request(
+            "INVALID_CORRIDOR_FORMAT",
+            "Invalid corridor key format",
+        ));
+    }
+
+    let asset_a_parts: Vec<&str> = parts[0].split(':').collect();
+    let asset_b_parts: Vec<&str> = parts[1].split(':').collect();
+
+    if asset_a_parts.len() != 2 || asset_b_parts.len() != 2 {
+        return Err(ApiError::bad_request(
+            "INVALID_CORRIDOR_FORMAT",
+            "Invalid corridor key format",
+        ));
+    }
+
+    Ok(Corridor::new(
+        asset_a_parts[0].to_string(),
+        asset_a_parts[1].to_string(),
+        asset_b_parts[0].to_string(),
+        asset_b_parts[1].to_string(),
+    ))
+}
+
 /// GET /api/corridors - List all corridors
 pub async fn list_corridors(
     State(app_state): State<AppState>,
@@ -273,30 +310,7 @@ pub async fn get_corridor_detail(
     State(app_state): State<AppState>,
     Path(corridor_key): Path<String>,
 ) -> ApiResult<Json<CorridorDetailResponse>> {
-    let parts: Vec<&str> = corridor_key.split("->").collect();
-    if parts.len() != 2 {
-        return Err(ApiError::bad_request(
-            "INVALID_CORRIDOR_FORMAT",
-            "Invalid corridor key format",
-        ));
-    }
-
-    let asset_a_parts: Vec<&str> = parts[0].split(':').collect();
-    let asset_b_parts: Vec<&str> = parts[1].split(':').collect();
-
-    if asset_a_parts.len() != 2 || asset_b_parts.len() != 2 {
-        return Err(ApiError::bad_request(
-            "INVALID_CORRIDOR_FORMAT",
-            "Invalid corridor key format",
-        ));
-    }
-
-    let corridor = Corridor::new(
-        asset_a_parts[0].to_string(),
-        asset_a_parts[1].to_string(),
-        asset_b_parts[0].to_string(),
-        asset_b_parts[1].to_string(),
-    );
+    let corridor = parse_corridor_key(&corridor_key)?;
 
     let end_date = Utc::now().date_naive();
     let start_date = end_date - Duration::days(30);
@@ -451,6 +465,239 @@ pub async fn get_corridor_detail(
     }))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct CorridorMetricsSeriesQuery {
+    /// Defaults to 30 days before `end`
+    pub start: Option<DateTime<Utc>>,
+    /// Defaults to now
+    pub end: Option<DateTime<Utc>>,
+    /// Desired bucket width, e.g. "1h", "6h", "1d", "1w", "1mo"
+    pub granularity: Option<String>,
+}
+
+/// GET /api/corridors/:corridor_key/metrics - Corridor metrics over a time range
+///
+/// Served from the coarsest rollup resolution (hour, day, week or month) whose buckets
+/// fit within the requested granularity, so long ranges stay cheap.
+pub async fn get_corridor_metrics_series(
+    State(app_state): State<AppState>,
+    Path(corridor_key): Path<String>,
+    Query(params): Query<CorridorMetricsSeriesQuery>,
+) -> ApiResult<Json<CorridorSeries>> {
+    let corridor = parse_corridor_key(&corridor_key)?;
+
+    let end = params.end.unwrap_or_else(Utc::now);
+    let start = params.start.unwrap_or(end - Duration::days(30));
+    if start >= end {
+        return Err(ApiError::bad_request(
+            "INVALID_TIME_RANGE",
+            "start must be before end",
+        ));
+    }
+
+    let granularity = match params.granularity.as_deref() {
+        Some(value) => Some(parse_granularity(value).ok_or_else(|| {
+            ApiError::bad_request(
+                "INVALID_GRANULARITY",
+                format!("Invalid granularity '{}'", value),
+            )
+        })?),
+        None => None,
+    };
+
+    let series = fetch_corridor_series(
+        &app_state.db.rollup_db(),
+        &corridor.to_string_key(),
+        start,
+        end,
+        granularity,
+    )
+    .await
+    .map_err(|e| {
+        ApiError::internal(
+            "DATABASE_ERROR",
+            format!("Failed to fetch corridor metrics: {}", e),
+        )
+    })?;
+
+    Ok(Json(series))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CorridorDistributionQuery {
+    /// Defaults to 24 hours before `end`
+    pub start: Option<DateTime<Utc>>,
+    /// Defaults to now
+    pub end: Option<DateTime<Utc>>,
+}
+
+/// GET /api/corridors/:corridor_key/distribution - Latency and payment size percentiles
+///
+/// Answered by merging the stored per-bucket sketches, so p50/p90/p99 are available for
+/// any range without rescanning payments.
+pub async fn get_corridor_distribution(
+    State(app_state): State<AppState>,
+    Path(corridor_key): Path<String>,
+    Query(params): Query<CorridorDistributionQuery>,
+) -> ApiResult<Json<CorridorDistribution>> {
+    let corridor = parse_corridor_key(&corridor_key)?;
+
+    let end = params.end.unwrap_or_else(Utc::now);
+    let start = params.start.unwrap_or(end - Duration::hours(24));
+    if start >= end {
+        return Err(ApiError::bad_request(
+            "INVALID_TIME_RANGE",
+            "start must be before end",
+        ));
+    }
+
+    let distribution = fetch_corridor_distribution(
+        &app_state.db.rollup_db(),
+        &corridor.to_string_key(),
+        start,
+        end,
+    )
+    .await
+    .map_err(|e| {
+        ApiError::internal(
+            "DATABASE_ERROR",
+            format!("Failed to fetch corridor distribution: {}", e),
+        )
+    })?;
+
+    Ok(Json(distribution))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CorridorAnomaliesQuery {
+    /// Defaults to 7 days before `end`
+    pub start: Option<DateTime<Utc>>,
+    /// Defaults to now
+    pub end: Option<DateTime<Utc>>,
+    /// Defaults to 100, at most 500
+    pub limit: Option<i64>,
+}
+
+/// GET /api/corridors/:corridor_key/anomalies - Hours that deviated from the corridor's
+/// learned baseline, newest first
+pub async fn get_corridor_anomalies(
+    State(app_state): State<AppState>,
+    Path(corridor_key): Path<String>,
+    Query(params): Query<CorridorAnomaliesQuery>,
+) -> ApiResult<Json<Vec<CorridorAnomaly>>> {
+    let corridor = parse_corridor_key(&corridor_key)?;
+
+    let end = params.end.unwrap_or_else(Utc::now);
+    let start = params.start.unwrap_or(end - Duration::days(7));
+    if start >= end {
+        return Err(ApiError::bad_request(
+            "INVALID_TIME_RANGE",
+            "start must be before end",
+        ));
+    }
+    let limit = params.limit.unwrap_or(100).clamp(1, 500);
+
+    let anomalies = app_state
+        .db
+        .anomaly_db()
+        .list_anomalies(&corridor.to_string_key(), start, end, limit)
+        .await
+        .map_err(|e| {
+            ApiError::internal(
+                "DATABASE_ERROR",
+                format!("Failed to fetch corridor anomalies: {}", e),
+            )
+        })?;
+
+    Ok(Json(anomalies))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CorridorForecastQuery {
+    /// Forecast horizon, e.g. "24h" or "7d"; defaults to 24 hours, at most 7 days
+    pub horizon: Option<String>,
+}
+
+/// GET /api/corridors/:corridor_key/forecast - Hourly volume and liquidity forecasts with
+/// prediction intervals
+pub async fn get_corridor_forecast(
+    State(app_state): State<AppState>,
+    Path(corridor_key): Path<String>,
+    Query(params): Query<CorridorForecastQuery>,
+) -> ApiResult<Json<CorridorForecast>> {
+    let corridor = parse_corridor_key(&corridor_key)?;
+
+    let horizon = match params.horizon.as_deref() {
+        Some(value) => parse_granularity(value)
+            .filter(|h| h.num_hours() >= 1 && h.num_hours() <= MAX_HORIZON_HOURS)
+            .ok_or_else(|| {
+                ApiError::bad_request(
+                    "INVALID_HORIZON",
+                    format!("Invalid horizon '{}', expected 1h to 7d", value),
+                )
+            })?,
+        None => Duration::hours(24),
+    };
+
+    let forecast = forecast_corridor(
+        &app_state.db.rollup_db(),
+        &corridor.to_string_key(),
+        horizon.num_hours(),
+        Utc::now(),
+        &ForecastConfig::from_env(),
+    )
+    .await
+    .map_err(|e| {
+        ApiError::internal(
+            "DATABASE_ERROR",
+            format!("Failed to forecast corridor: {}", e),
+        )
+    })?;
+
+    Ok(Json(forecast))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CorridorDepthQuery {
+    /// Defaults to 24 hours before `end`
+    pub start: Option<DateTime<Utc>>,
+    /// Defaults to now
+    pub end: Option<DateTime<Utc>>,
+}
+
+/// GET /api/corridors/:corridor_key/depth - Order book and pool depth snapshots of the
+/// corridor's asset pair, oldest first
+pub async fn get_corridor_depth_history(
+    State(app_state): State<AppState>,
+    Path(corridor_key): Path<String>,
+    Query(params): Query<CorridorDepthQuery>,
+) -> ApiResult<Json<Vec<LiquiditySnapshot>>> {
+    let corridor = parse_corridor_key(&corridor_key)?;
+
+    let end = params.end.unwrap_or_else(Utc::now);
+    let start = params.start.unwrap_or(end - Duration::hours(24));
+    if start >= end {
+        return Err(ApiError::bad_request(
+            "INVALID_TIME_RANGE",
+            "start must be before end",
+        ));
+    }
+
+    let snapshots = app_state
+        .db
+        .dex_db()
+        .list_snapshots(&corridor.to_string_key(), start, end)
+        .await
+        .map_err(|e| {
+            ApiError::internal(
+                "DATABASE_ERROR",
+                format!("Failed to fetch corridor depth: {}", e),
+            )
+        })?;
+
+    Ok(Json(snapshots))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
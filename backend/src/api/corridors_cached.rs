@@ -9,15 +9,19 @@ use std::collections::HashMap;
 use std::sync::{Arc, Mutex, OnceLock};
 use utoipa::{IntoParams, ToSchema};
 
+use crate::analytics::quantile_sketch::QuantileSketch;
 use crate::cache::helpers::cached_query;
 use crate::cache::{keys, CacheManager};
 use crate::database::Database;
 use crate::error::{ApiError, ApiResult};
+use crate::models::corridor::Corridor;
 use crate::models::SortBy;
 use crate::rpc::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig};
 use crate::rpc::error::{with_retry, RetryConfig, RpcError};
 use crate::rpc::StellarRpcClient;
+use crate::services::dex_aggregator::{DexAggregator, DexAggregatorConfig, LiquiditySnapshot};
 use crate::services::price_feed::PriceFeedClient;
+use crate::services::rollup::fetch_corridor_sketches;
 use crate::validation;
 use anyhow::anyhow;
 
@@ -194,6 +198,79 @@ pub struct CorridorDetailResponse {
     pub liquidity_trends: Vec<LiquidityDataPoint>,
     /// Related corridors
     pub related_corridors: Option<Vec<CorridorResponse>>,
+    /// Order book and pool depth of the corridor's asset pair, when either has offers
+    pub market_depth: Option<CorridorMarketDepth>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CorridorMarketDepth {
+    /// Asset the depth is measured in (`native` or `CODE:ISSUER`)
+    #[schema(example = "USDC:GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN")]
+    pub base_asset: String,
+    /// Asset prices are quoted in
+    #[schema(example = "native")]
+    pub counter_asset: String,
+    /// Mid price in counter units per base unit
+    #[schema(example = 8.5)]
+    pub mid_price: f64,
+    /// Spread between the best bid and ask in basis points
+    #[schema(example = 12.5)]
+    pub spread_bps: Option<f64>,
+    /// Number of order book price levels
+    #[schema(example = 40)]
+    pub book_levels: i64,
+    /// Number of liquidity pools holding both assets
+    #[schema(example = 1)]
+    pub pool_count: i64,
+    /// Depth at 0.1%, 0.5%, 1% and 2% slippage
+    pub depth_curve: Vec<MarketDepthDataPoint>,
+    /// When the depth was captured
+    #[schema(example = "2024-01-15T10:30:00Z")]
+    pub captured_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MarketDepthDataPoint {
+    /// Price move from mid in percent
+    #[schema(example = 1.0)]
+    pub slippage_pct: f64,
+    /// Base units that can be sold within the price move
+    #[schema(example = 52000.0)]
+    pub sell_depth: f64,
+    /// Base units that can be bought within the price move
+    #[schema(example = 48000.0)]
+    pub buy_depth: f64,
+    /// Part of the depth provided by liquidity pools
+    #[schema(example = 30000.0)]
+    pub pool_depth: f64,
+    /// Both sides in USD, when the base asset has a price
+    #[schema(example = 100000.0)]
+    pub depth_usd: Option<f64>,
+}
+
+impl From<&LiquiditySnapshot> for CorridorMarketDepth {
+    fn from(snapshot: &LiquiditySnapshot) -> Self {
+        Self {
+            base_asset: snapshot.base_asset.clone(),
+            counter_asset: snapshot.counter_asset.clone(),
+            mid_price: snapshot.mid_price,
+            spread_bps: snapshot.spread_bps,
+            book_levels: snapshot.book_levels,
+            pool_count: snapshot.pool_count,
+            depth_curve: snapshot
+                .curve
+                .iter()
+                .map(|point| MarketDepthDataPoint {
+                    slippage_pct: point.slippage_pct,
+                    sell_depth: point.sell_depth,
+                    buy_depth: point.buy_depth,
+                    pool_depth: point.pool_depth,
+                    depth_usd: point.depth_usd,
+                })
+                .collect(),
+            captured_at: snapshot.captured_at.to_rfc3339(),
+        }
+    }
 }
 
 /// Query parameters for listing corridors with filtering and pagination.
@@ -620,6 +697,40 @@ fn calculate_latency_distribution(
     data_points
 }
 
+/// Latency distribution from a measured latency sketch, using the same buckets as
+/// `calculate_latency_distribution`. Each bucket counts latencies above the previous
+/// bucket up to its own bound; the last bucket also takes everything slower.
+fn latency_distribution_from_sketch(sketch: &QuantileSketch) -> Vec<LatencyDataPoint> {
+    let buckets = [100, 250, 500, 1000, 2000];
+    let total_count = sketch.count() as i64;
+    let mut below_previous = 0i64;
+
+    buckets
+        .iter()
+        .enumerate()
+        .map(|(i, &bucket)| {
+            let below = if i == buckets.len() - 1 {
+                total_count
+            } else {
+                sketch.rank(bucket as f64) as i64
+            };
+            let count = below - below_previous;
+            below_previous = below;
+
+            let percentage = if total_count > 0 {
+                (count as f64 / total_count as f64) * 100.0
+            } else {
+                0.0
+            };
+            LatencyDataPoint {
+                latency_bucket_ms: bucket,
+                count,
+                percentage,
+            }
+        })
+        .collect()
+}
+
 /// Calculate liquidity trends over time (daily snapshots)
 fn calculate_liquidity_trends(
     corridor_payments: &[&crate::rpc::Payment],
@@ -873,7 +984,7 @@ pub async fn get_corridor_detail(
         let liquidity_trend = get_liquidity_trend(volume_usd);
         let avg_latency = 400.0 + (success_rate * 2.0);
 
-        let corridor = CorridorResponse {
+        let mut corridor = CorridorResponse {
             id: corridor_key.clone(),
             source_asset: source_parts[0].to_string(),
             destination_asset: dest_parts[0].to_string(),
@@ -894,10 +1005,71 @@ pub async fn get_corridor_detail(
 
         // Calculate historical metrics
         let historical_success_rate = calculate_historical_success_rate(&corridor_payments);
-        let latency_distribution =
+        let mut latency_distribution =
             calculate_latency_distribution(&corridor_payments, total_attempts);
+
+        // Prefer measured latency percentiles from the hourly sketches when aggregation
+        // has recorded any for this corridor over the last day
+        let normalized = Corridor::new(
+            source_parts[0].to_string(),
+            source_parts[1].to_string(),
+            dest_parts[0].to_string(),
+            dest_parts[1].to_string(),
+        );
+        let normalized_key = normalized.to_string_key();
+        let now = chrono::Utc::now();
+        match fetch_corridor_sketches(
+            &db.rollup_db(),
+            &normalized_key,
+            now - chrono::Duration::hours(24),
+            now,
+        )
+        .await
+        {
+            Ok(sketches) if !sketches.latency_ms.is_empty() => {
+                let latency = &sketches.latency_ms;
+                corridor.average_latency_ms = latency.mean().unwrap_or(avg_latency);
+                corridor.median_latency_ms = latency.quantile(0.5).unwrap_or_default();
+                corridor.p95_latency_ms = latency.quantile(0.95).unwrap_or_default();
+                corridor.p99_latency_ms = latency.quantile(0.99).unwrap_or_default();
+                latency_distribution = latency_distribution_from_sketch(latency);
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to load latency sketches for {}: {}",
+                    corridor_key,
+                    e
+                );
+            }
+        }
         let liquidity_trends = calculate_liquidity_trends(&corridor_payments, volume_usd);
 
+        // Depth of the pair's order book and pools, from a recent snapshot when the
+        // background aggregator has one
+        let aggregator = DexAggregator::new(
+            Arc::clone(&db),
+            Arc::clone(&rpc_client),
+            Arc::clone(&price_feed),
+            DexAggregatorConfig::from_env(),
+        );
+        let market_depth = match aggregator
+            .corridor_depth(&normalized, chrono::Duration::hours(1))
+            .await
+        {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                tracing::warn!("Failed to load market depth for {}: {}", corridor_key, e);
+                None
+            }
+        };
+        if let Some(depth_usd) = market_depth
+            .as_ref()
+            .and_then(|snapshot| snapshot.depth_usd_at(1.0))
+        {
+            corridor.liquidity_depth_usd = depth_usd;
+        }
+
         // Find related corridors
         let related_corridors = find_related_corridors(&corridor_key, &all_corridors);
 
@@ -907,6 +1079,7 @@ pub async fn get_corridor_detail(
             latency_distribution,
             liquidity_trends,
             related_corridors,
+            market_depth: market_depth.as_ref().map(CorridorMarketDepth::from),
         })
     })
     .await?;
@@ -1178,6 +1351,21 @@ mod tests {
         assert!((total_percentage - 100.0).abs() < 0.1);
     }
 
+    #[test]
+    fn test_latency_distribution_from_sketch() {
+        let mut sketch = QuantileSketch::default();
+        for latency in [50.0, 90.0, 200.0, 400.0, 900.0, 1500.0, 4000.0, 8000.0] {
+            sketch.insert(latency);
+        }
+
+        let result = latency_distribution_from_sketch(&sketch);
+        let counts: Vec<i64> = result.iter().map(|d| d.count).collect();
+        assert_eq!(counts, vec![2, 1, 1, 1, 3]);
+
+        let total_percentage: f64 = result.iter().map(|d| d.percentage).sum();
+        assert!((total_percentage - 100.0).abs() < 0.1);
+    }
+
     #[test]
     fn test_calculate_liquidity_trends_empty() {
         let payments = vec![];
@@ -12,16 +12,27 @@ use utoipa::ToSchema;
 
 use crate::error::DomainError;
 use crate::http_cache::cached_json_response;
+use crate::rpc::StellarRpcClient;
+use crate::services::path_finder::{
+    HorizonPathQuote, LiquidityGraph, PathFinder, PathFinderConfig, PathQuote, QuotedHop,
+    VenueKind, MAX_HOPS, NATIVE,
+};
 use crate::services::price_feed::PriceFeedClient;
 
-const DEFAULT_CACHE_TTL_SECONDS: usize = 60;
+/// Quotes follow the live order books, so they are only briefly cacheable
+const DEFAULT_CACHE_TTL_SECONDS: usize = 15;
 const USDC_ISSUER: &str = "GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN";
+/// Fee of a one-operation transaction at the base fee of 100 stroops, in XLM
+const NETWORK_FEE_XLM: f64 = 0.000_01;
 
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum PaymentRoute {
+    /// Paths of up to three hops across order books
     StellarDex,
+    /// The direct market between the two assets, book or pool
     AnchorDirect,
+    /// Paths of up to three hops across constant-product pools
     LiquidityPool,
 }
 
@@ -45,17 +56,32 @@ impl PaymentRoute {
             Self::LiquidityPool => "Liquidity Pool",
         }
     }
+
+    fn venues(&self) -> &'static [VenueKind] {
+        match self {
+            Self::StellarDex => &[VenueKind::OrderBook],
+            Self::AnchorDirect => &[VenueKind::OrderBook, VenueKind::LiquidityPool],
+            Self::LiquidityPool => &[VenueKind::LiquidityPool],
+        }
+    }
+
+    fn max_hops(&self) -> usize {
+        match self {
+            Self::AnchorDirect => 1,
+            Self::StellarDex | Self::LiquidityPool => MAX_HOPS,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct CostCalculationRequest {
     #[schema(example = "USDC")]
     pub source_currency: String,
-    #[schema(example = "NGN")]
+    #[schema(example = "EURC")]
     pub destination_currency: String,
     #[schema(example = 1000.0)]
     pub source_amount: f64,
-    #[schema(example = 1550000.0)]
+    #[schema(example = 920.0)]
     pub destination_amount: Option<f64>,
     pub routes: Option<Vec<PaymentRoute>>,
 }
@@ -77,17 +103,88 @@ pub struct RouteCostBreakdown {
     pub additional_source_required: Option<f64>,
 }
 
+/// One hop of a quoted path
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RouteHop {
+    pub from_asset: String,
+    pub to_asset: String,
+    #[schema(example = "order_book")]
+    pub venue: String,
+    pub pool_id: Option<String>,
+    pub amount_in: f64,
+    pub amount_out: f64,
+}
+
+impl From<&QuotedHop> for RouteHop {
+    fn from(hop: &QuotedHop) -> Self {
+        Self {
+            from_asset: hop.from.clone(),
+            to_asset: hop.to.clone(),
+            venue: hop.venue.as_str().to_string(),
+            pool_id: hop.pool_id.clone(),
+            amount_in: hop.amount_in,
+            amount_out: hop.amount_out,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct RouteEstimate {
     pub route: PaymentRoute,
     pub route_name: String,
+    pub path: Vec<RouteHop>,
     pub breakdown: RouteCostBreakdown,
 }
 
+/// A path found by Horizon's own path finding
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct HorizonQuote {
+    pub source_amount: f64,
+    pub destination_amount: f64,
+    /// Assets along the path, source first
+    pub path: Vec<String>,
+}
+
+impl From<HorizonPathQuote> for HorizonQuote {
+    fn from(quote: HorizonPathQuote) -> Self {
+        Self {
+            source_amount: quote.source_amount,
+            destination_amount: quote.destination_amount,
+            path: quote.path,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct HorizonComparison {
+    /// Best path for exactly the source amount
+    pub strict_send: Option<HorizonQuote>,
+    /// Cheapest path for exactly the destination amount, when one was requested
+    pub strict_receive: Option<HorizonQuote>,
+    /// How much more the best route delivers than the strict-send path, in bps
+    pub best_route_difference_bps: Option<f64>,
+}
+
+/// Where the route costs of an estimate come from
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QuoteSource {
+    /// Quoted against the current order books and liquidity pools
+    Market,
+    /// Estimated from USD reference rates and typical route fees, for currencies
+    /// without an on-chain market
+    ReferenceRates,
+}
+
 #[derive(Debug, Serialize, ToSchema)]
 pub struct CostCalculationResponse {
     pub source_currency: String,
     pub destination_currency: String,
+    pub quote_source: QuoteSource,
+    /// Asset the source currency resolved to, `native` or `CODE:ISSUER`
+    pub source_asset: Option<String>,
+    /// Asset the destination currency resolved to, `native` or `CODE:ISSUER`
+    pub destination_asset: Option<String>,
     pub source_amount: f64,
     pub destination_amount: Option<f64>,
     pub source_usd_rate: f64,
@@ -95,6 +192,9 @@ pub struct CostCalculationResponse {
     pub mid_market_rate: f64,
     pub best_route: RouteEstimate,
     pub routes: Vec<RouteEstimate>,
+    /// Requested routes without a path deep enough for the amount
+    pub unavailable_routes: Vec<PaymentRoute>,
+    pub horizon: HorizonComparison,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -139,7 +239,18 @@ impl RouteFees {
     }
 }
 
+#[derive(Clone)]
+pub struct CostCalculatorState {
+    price_feed: Arc<PriceFeedClient>,
+    path_finder: Arc<PathFinder>,
+}
+
 /// Estimate total cross-border payment costs and compare available routes.
+///
+/// Each route is quoted for the requested amount against the current order books and
+/// liquidity pools, and the best route is compared with Horizon's own path finding.
+/// Currencies without an on-chain market, such as fiat currencies, are estimated from
+/// USD reference rates and typical route fees instead.
 #[utoipa::path(
     post,
     path = "/api/cost-calculator/estimate",
@@ -148,12 +259,14 @@ impl RouteFees {
         (status = 200, description = "Cost estimate generated", body = CostCalculationResponse),
         (status = 304, description = "Not modified. Conditional request matched current response."),
         (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 422, description = "No route has enough liquidity for the amount", body = ErrorResponse),
+        (status = 503, description = "Market data unavailable", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     tag = "Cost Calculator"
 )]
 pub async fn estimate_costs(
-    State(price_feed): State<Arc<PriceFeedClient>>,
+    State(state): State<CostCalculatorState>,
     request_headers: HeaderMap,
     Json(request): Json<CostCalculationRequest>,
 ) -> Response {
@@ -192,51 +305,139 @@ pub async fn estimate_costs(
         return error_response(StatusCode::BAD_REQUEST, "at least one route is required");
     }
 
-    let source_usd_rate = match resolve_usd_rate(&price_feed, &source_currency).await {
-        Ok(rate) => rate,
-        Err(error) => return error_response(StatusCode::BAD_REQUEST, &error.to_string()),
+    let path_finder = &state.path_finder;
+    let mut graph = match path_finder.load_pools().await {
+        Ok(graph) => graph,
+        Err(error) => {
+            tracing::warn!(
+                "Cost calculator could not load liquidity pools: {:#}",
+                error
+            );
+            return error_response(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "market data is currently unavailable",
+            );
+        }
     };
 
-    let destination_usd_rate = match resolve_usd_rate(&price_feed, &destination_currency).await {
-        Ok(rate) => rate,
-        Err(error) => return error_response(StatusCode::BAD_REQUEST, &error.to_string()),
+    let hub_assets = &path_finder.config().hub_assets;
+    let (Some(source_asset), Some(destination_asset)) = (
+        graph.resolve_asset(&source_currency, hub_assets),
+        graph.resolve_asset(&destination_currency, hub_assets),
+    ) else {
+        return match estimate_from_reference_rates(
+            &state.price_feed,
+            source_currency,
+            destination_currency,
+            request.source_amount,
+            request.destination_amount,
+            unique_routes,
+        )
+        .await
+        {
+            Ok(response) => cached_estimate(&request_headers, &response),
+            Err(error) => error_response(StatusCode::BAD_REQUEST, &error.to_string()),
+        };
     };
 
-    if destination_usd_rate <= 0.0 {
-        return error_response(StatusCode::BAD_REQUEST, "destination USD rate is invalid");
+    if source_asset == destination_asset {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "source and destination resolve to the same asset",
+        );
     }
 
-    let mid_market_rate = source_usd_rate / destination_usd_rate;
+    path_finder
+        .load_order_books(&mut graph, &[&source_asset, &destination_asset])
+        .await;
 
-    let mut route_estimates: Vec<RouteEstimate> = unique_routes
-        .into_iter()
-        .map(|route| {
-            estimate_route(
-                route,
-                request.source_amount,
-                request.destination_amount,
-                mid_market_rate,
+    let horizon_strict_receive = async {
+        match request.destination_amount {
+            Some(amount) => {
+                path_finder
+                    .horizon_strict_receive(&source_asset, &destination_asset, amount)
+                    .await
+            }
+            None => None,
+        }
+    };
+    let (horizon_strict_send, horizon_strict_receive, source_usd_rate, destination_usd_rate) = tokio::join!(
+        path_finder.horizon_strict_send(&source_asset, request.source_amount, &destination_asset),
+        horizon_strict_receive,
+        resolve_usd_rate(&state.price_feed, price_feed_id(&source_asset)),
+        resolve_usd_rate(&state.price_feed, price_feed_id(&destination_asset)),
+    );
+
+    let network_fee_source = network_fee_in(&graph, &source_asset);
+    let mut route_estimates = Vec::new();
+    let mut unavailable_routes = Vec::new();
+    for route in unique_routes {
+        let Some(quote) = graph.best_strict_send(
+            &source_asset,
+            &destination_asset,
+            request.source_amount,
+            route.venues(),
+            route.max_hops(),
+        ) else {
+            unavailable_routes.push(route);
+            continue;
+        };
+        let target_quote = request.destination_amount.and_then(|amount| {
+            graph.best_strict_receive(
+                &source_asset,
+                &destination_asset,
+                amount,
+                route.venues(),
+                route.max_hops(),
             )
-        })
-        .collect();
+        });
+        route_estimates.push(estimate_route(
+            route,
+            &quote,
+            network_fee_source,
+            request.destination_amount,
+            target_quote.as_ref(),
+        ));
+    }
 
     route_estimates.sort_by(|a, b| {
-        a.breakdown
-            .total_fees_source
-            .partial_cmp(&b.breakdown.total_fees_source)
-            .unwrap_or(std::cmp::Ordering::Equal)
+        b.breakdown
+            .estimated_destination_amount
+            .total_cmp(&a.breakdown.estimated_destination_amount)
     });
 
     let Some(best_route) = route_estimates.first().cloned() else {
         return error_response(
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "failed to estimate routes",
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "no route has enough liquidity for the requested amount",
         );
     };
 
+    // An asset the price feed does not track is priced through the market rate
+    let mid_market_rate = best_route.breakdown.exchange_rate_mid;
+    let (source_usd_rate, destination_usd_rate) = match (source_usd_rate, destination_usd_rate) {
+        (Ok(source), Ok(destination)) => (source, destination),
+        (Ok(source), Err(_)) => (source, source / mid_market_rate),
+        (Err(_), Ok(destination)) => (destination * mid_market_rate, destination),
+        (Err(error), Err(_)) => {
+            return error_response(StatusCode::BAD_REQUEST, &error.to_string());
+        }
+    };
+
+    let best_route_difference_bps = horizon_strict_send
+        .as_ref()
+        .filter(|quote| quote.destination_amount > 0.0)
+        .map(|quote| {
+            (best_route.breakdown.estimated_destination_amount / quote.destination_amount - 1.0)
+                * 10_000.0
+        });
+
     let response = CostCalculationResponse {
         source_currency: source_currency.clone(),
         destination_currency: destination_currency.clone(),
+        quote_source: QuoteSource::Market,
+        source_asset: Some(source_asset),
+        destination_asset: Some(destination_asset),
         source_amount: request.source_amount,
         destination_amount: request.destination_amount,
         source_usd_rate,
@@ -244,8 +445,68 @@ pub async fn estimate_costs(
         mid_market_rate,
         best_route,
         routes: route_estimates,
+        unavailable_routes,
+        horizon: HorizonComparison {
+            strict_send: horizon_strict_send.map(HorizonQuote::from),
+            strict_receive: horizon_strict_receive.map(HorizonQuote::from),
+            best_route_difference_bps,
+        },
     };
 
+    cached_estimate(&request_headers, &response)
+}
+
+/// Estimate each route from the USD reference rates of both currencies and the
+/// typical fees of the route, for currencies the order books and pools do not trade
+async fn estimate_from_reference_rates(
+    price_feed: &PriceFeedClient,
+    source_currency: String,
+    destination_currency: String,
+    source_amount: f64,
+    destination_amount: Option<f64>,
+    routes: Vec<PaymentRoute>,
+) -> Result<CostCalculationResponse, DomainError> {
+    let source_usd_rate = resolve_usd_rate(price_feed, &source_currency).await?;
+    let destination_usd_rate = resolve_usd_rate(price_feed, &destination_currency).await?;
+    let mid_market_rate = source_usd_rate / destination_usd_rate;
+
+    let mut route_estimates: Vec<RouteEstimate> = routes
+        .into_iter()
+        .map(|route| {
+            estimate_reference_route(route, source_amount, destination_amount, mid_market_rate)
+        })
+        .collect();
+
+    route_estimates.sort_by(|a, b| {
+        a.breakdown
+            .total_fees_source
+            .total_cmp(&b.breakdown.total_fees_source)
+    });
+    let best_route = route_estimates[0].clone();
+
+    Ok(CostCalculationResponse {
+        source_currency,
+        destination_currency,
+        quote_source: QuoteSource::ReferenceRates,
+        source_asset: None,
+        destination_asset: None,
+        source_amount,
+        destination_amount,
+        source_usd_rate,
+        destination_usd_rate,
+        mid_market_rate,
+        best_route,
+        routes: route_estimates,
+        unavailable_routes: Vec::new(),
+        horizon: HorizonComparison {
+            strict_send: None,
+            strict_receive: None,
+            best_route_difference_bps: None,
+        },
+    })
+}
+
+fn cached_estimate(request_headers: &HeaderMap, response: &CostCalculationResponse) -> Response {
     let route_key = response
         .routes
         .iter()
@@ -255,17 +516,17 @@ pub async fn estimate_costs(
 
     let resource_key = format!(
         "cost-calculator:{}:{}:{:.8}:{}:{:?}",
-        source_currency,
-        destination_currency,
-        request.source_amount,
+        response.source_currency,
+        response.destination_currency,
+        response.source_amount,
         route_key,
-        request.destination_amount
+        response.destination_amount
     );
 
     match cached_json_response(
-        &request_headers,
+        request_headers,
         &resource_key,
-        &response,
+        response,
         DEFAULT_CACHE_TTL_SECONDS,
     ) {
         Ok(response) => response,
@@ -276,7 +537,89 @@ pub async fn estimate_costs(
     }
 }
 
+/// Break a route's quote down into the spread to the mid price, pool fees, slippage
+/// of the amount and the network fee. `target_quote` is the same route quoted for
+/// exactly `destination_target`.
 fn estimate_route(
+    route: PaymentRoute,
+    quote: &PathQuote,
+    network_fee_source: f64,
+    destination_target: Option<f64>,
+    target_quote: Option<&PathQuote>,
+) -> RouteEstimate {
+    let source_amount = quote.source_amount;
+    let mid_market_rate = quote.mid_rate;
+
+    let destination_at_mid = source_amount * mid_market_rate;
+    let destination_at_spot = source_amount * quote.spot_rate;
+    let destination_after_fees = destination_at_spot * quote.fee_factor;
+
+    let spread_cost_destination = (destination_at_mid - destination_at_spot).max(0.0);
+    let service_fee_destination = destination_at_spot - destination_after_fees;
+    let slippage_cost_destination = (destination_after_fees - quote.destination_amount).max(0.0);
+    let network_fee_destination = network_fee_source * mid_market_rate;
+
+    let spread_cost_source = spread_cost_destination / mid_market_rate;
+    let service_fee_source = service_fee_destination / mid_market_rate;
+    let slippage_cost_source = slippage_cost_destination / mid_market_rate;
+    let total_fees_source =
+        spread_cost_source + service_fee_source + network_fee_source + slippage_cost_source;
+    let total_fees_destination = spread_cost_destination
+        + service_fee_destination
+        + network_fee_destination
+        + slippage_cost_destination;
+
+    let estimated_destination_amount = quote.destination_amount;
+    let effective_rate = estimated_destination_amount / source_amount;
+
+    let destination_shortfall = destination_target
+        .map(|target| (target - estimated_destination_amount).max(0.0))
+        .filter(|shortfall| *shortfall > 0.0);
+
+    let additional_source_required = destination_shortfall
+        .and(target_quote)
+        .map(|target| target.source_amount - source_amount)
+        .filter(|required| required.is_finite() && *required > 0.0);
+
+    RouteEstimate {
+        route,
+        route_name: route.label().to_string(),
+        path: quote.hops.iter().map(RouteHop::from).collect(),
+        breakdown: RouteCostBreakdown {
+            exchange_rate_mid: mid_market_rate,
+            effective_rate,
+            spread_bps: quote.spread_bps(),
+            slippage_bps: quote.slippage_bps(),
+            spread_cost_source,
+            service_fee_source,
+            network_fee_source,
+            slippage_cost_source,
+            total_fees_source,
+            total_fees_destination,
+            estimated_destination_amount,
+            destination_shortfall,
+            additional_source_required,
+        },
+    }
+}
+
+/// The transaction fee, paid in XLM, in units of the source asset
+fn network_fee_in(graph: &LiquidityGraph, source_asset: &str) -> f64 {
+    if source_asset == NATIVE {
+        return NETWORK_FEE_XLM;
+    }
+    graph
+        .best_strict_send(
+            NATIVE,
+            source_asset,
+            NETWORK_FEE_XLM,
+            &[VenueKind::OrderBook, VenueKind::LiquidityPool],
+            MAX_HOPS,
+        )
+        .map_or(0.0, |quote| quote.destination_amount)
+}
+
+fn estimate_reference_route(
     route: PaymentRoute,
     source_amount: f64,
     destination_target: Option<f64>,
@@ -334,6 +677,7 @@ fn estimate_route(
     RouteEstimate {
         route,
         route_name: route.label().to_string(),
+        path: Vec::new(),
         breakdown: RouteCostBreakdown {
             exchange_rate_mid: mid_market_rate,
             effective_rate,
@@ -352,6 +696,15 @@ fn estimate_route(
     }
 }
 
+/// Price feed id of a resolved asset, `native` or `CODE:ISSUER`
+fn price_feed_id(asset: &str) -> &str {
+    if asset == NATIVE {
+        "XLM:native"
+    } else {
+        asset
+    }
+}
+
 async fn resolve_usd_rate(
     price_feed: &PriceFeedClient,
     currency: &str,
@@ -445,15 +798,20 @@ fn error_response(status: StatusCode, message: &str) -> Response {
         .into_response()
 }
 
-pub fn routes(price_feed: Arc<PriceFeedClient>) -> Router {
+pub fn routes(price_feed: Arc<PriceFeedClient>, rpc_client: Arc<StellarRpcClient>) -> Router {
+    let state = CostCalculatorState {
+        price_feed,
+        path_finder: Arc::new(PathFinder::new(rpc_client, PathFinderConfig::from_env())),
+    };
     Router::new()
         .route("/estimate", post(estimate_costs))
-        .with_state(price_feed)
+        .with_state(state)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rpc::{HorizonLiquidityPool, HorizonPoolReserve};
 
     #[test]
     fn test_normalize_currency() {
@@ -466,8 +824,62 @@ mod tests {
     }
 
     #[test]
-    fn test_estimate_route_produces_positive_fees() {
-        let estimate = estimate_route(
+    fn test_estimate_route_breakdown_adds_up() {
+        let mut graph = LiquidityGraph::new();
+        graph.add_pool(&HorizonLiquidityPool {
+            id: "pool".to_string(),
+            fee_bp: 30,
+            pool_type: "constant_product".to_string(),
+            total_trustlines: 1,
+            total_shares: "1".to_string(),
+            reserves: vec![
+                HorizonPoolReserve {
+                    asset: "USDC:GA".to_string(),
+                    amount: "100000.0".to_string(),
+                },
+                HorizonPoolReserve {
+                    asset: "EURC:GB".to_string(),
+                    amount: "92000.0".to_string(),
+                },
+            ],
+            paging_token: None,
+        });
+        let route = PaymentRoute::LiquidityPool;
+        let quote = graph
+            .best_strict_send(
+                "USDC:GA",
+                "EURC:GB",
+                1_000.0,
+                route.venues(),
+                route.max_hops(),
+            )
+            .unwrap();
+        let target = graph
+            .best_strict_receive(
+                "USDC:GA",
+                "EURC:GB",
+                950.0,
+                route.venues(),
+                route.max_hops(),
+            )
+            .unwrap();
+
+        let estimate = estimate_route(route, &quote, 0.001, Some(950.0), Some(&target));
+        let breakdown = &estimate.breakdown;
+        assert!(breakdown.service_fee_source > 0.0);
+        assert!(breakdown.slippage_cost_source > 0.0);
+        assert_eq!(breakdown.spread_cost_source, 0.0);
+        let delivered_at_mid = (1_000.0 - breakdown.total_fees_source + 0.001) * 0.92;
+        assert!((delivered_at_mid - breakdown.estimated_destination_amount).abs() < 1e-6);
+        assert!(breakdown.destination_shortfall.unwrap() > 0.0);
+        let required = breakdown.additional_source_required.unwrap();
+        assert!((required - (target.source_amount - 1_000.0)).abs() < 1e-9);
+        assert_eq!(estimate.path.len(), 1);
+    }
+
+    #[test]
+    fn test_estimate_reference_route_produces_positive_fees() {
+        let estimate = estimate_reference_route(
             PaymentRoute::StellarDex,
             1_000.0,
             Some(1_500_000.0),
@@ -483,4 +895,11 @@ mod tests {
         assert_eq!(fallback_usd_rate("USDC"), Some(1.0));
         assert!(fallback_usd_rate("NGN").is_some());
     }
+
+    #[test]
+    fn test_network_fee_is_converted_to_source_asset() {
+        let graph = LiquidityGraph::new();
+        assert_eq!(network_fee_in(&graph, NATIVE), NETWORK_FEE_XLM);
+        assert_eq!(network_fee_in(&graph, "USDC:GA"), 0.0);
+    }
 }
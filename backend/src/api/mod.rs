@@ -7,7 +7,9 @@ pub mod api_keys;
 pub mod asset_verification;
 
 pub mod auth;
+pub mod backfill;
 pub mod cache_stats;
+pub mod contract_events;
 pub mod corridors;
 pub mod corridors_cached;
 pub mod cost_calculator;
@@ -1,3 +1,5 @@
+use crate::database::DbPool;
+use crate::with_pool;
 /// OAuth API endpoints for Zapier integration
 use axum::{
     extract::{Query, State},
@@ -8,7 +10,6 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use sqlx::SqlitePool;
 
 use crate::auth::oauth::{OAuthService, TokenResponse};
 use crate::auth_middleware::AuthUser;
@@ -71,7 +72,7 @@ pub struct ListOAuthAppsResponse {
 
 /// POST /api/oauth/authorize - Request authorization code
 pub async fn authorize(
-    State(db): State<SqlitePool>,
+    State(db): State<DbPool>,
     auth_user: AuthUser,
     Query(request): Query<OAuthAuthorizeRequest>,
 ) -> Result<Response, OAuthApiError> {
@@ -110,7 +111,7 @@ pub async fn authorize(
 
 /// POST /api/oauth/token - Exchange authorization code for tokens
 pub async fn token(
-    State(db): State<SqlitePool>,
+    State(db): State<DbPool>,
     Json(request): Json<OAuthTokenRequest>,
 ) -> Result<Response, OAuthApiError> {
     let service = OAuthService::new(db.clone());
@@ -122,16 +123,14 @@ pub async fn token(
         .map_err(|_| OAuthApiError::InvalidClient)?;
 
     // Get user from database for username
-    let user_row = sqlx::query("SELECT username FROM users WHERE id = ?")
-        .bind(user_id.clone())
-        .fetch_optional(&db)
-        .await
-        .map_err(|e| OAuthApiError::ServerError(e.to_string()))?
-        .ok_or_else(|| OAuthApiError::InvalidClient)?;
-    let username: String = {
-        use sqlx::Row;
-        user_row.get(0)
-    };
+    let username: String = with_pool!(&db, |pool| {
+        sqlx::query_scalar("SELECT username FROM users WHERE id = $1")
+            .bind(user_id.clone())
+            .fetch_optional(pool)
+            .await
+    })
+    .map_err(|e| OAuthApiError::ServerError(e.to_string()))?
+    .ok_or_else(|| OAuthApiError::InvalidClient)?;
 
     match request.grant_type.as_str() {
         "authorization_code" => {
@@ -234,7 +233,7 @@ pub async fn token(
 
 /// POST /api/oauth/revoke - Revoke an access token
 pub async fn revoke(
-    State(db): State<SqlitePool>,
+    State(db): State<DbPool>,
     Json(request): Json<OAuthRevokeRequest>,
 ) -> Result<Response, OAuthApiError> {
     let service = OAuthService::new(db);
@@ -260,30 +259,29 @@ pub async fn revoke(
 
 /// GET /api/oauth/apps - List OAuth apps for authenticated user
 pub async fn list_apps(
-    State(db): State<SqlitePool>,
+    State(db): State<DbPool>,
     auth_user: AuthUser,
 ) -> Result<Response, OAuthApiError> {
-    let rows = sqlx::query(
-        r#"
-        SELECT client_id, app_name, created_at FROM oauth_clients
-        WHERE user_id = ?
-        ORDER BY created_at DESC
-        "#,
-    )
-    .bind(auth_user.user_id)
-    .fetch_all(&db)
-    .await
+    let rows: Vec<(String, String, String)> = with_pool!(&db, |pool| {
+        sqlx::query_as(
+            r#"
+            SELECT client_id, app_name, created_at FROM oauth_clients
+            WHERE user_id = $1
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(auth_user.user_id)
+        .fetch_all(pool)
+        .await
+    })
     .map_err(|e: sqlx::Error| OAuthApiError::ServerError(e.to_string()))?;
 
     let app_list: Vec<OAuthAppInfo> = rows
         .into_iter()
-        .map(|row| {
-            use sqlx::Row;
-            OAuthAppInfo {
-                client_id: row.get(0),
-                app_name: row.get(1),
-                created_at: row.get(2),
-            }
+        .map(|(client_id, app_name, created_at)| OAuthAppInfo {
+            client_id,
+            app_name,
+            created_at,
         })
         .collect();
 
@@ -340,7 +338,7 @@ impl From<anyhow::Error> for OAuthApiError {
 }
 
 /// Create OAuth routes
-pub fn routes(db: SqlitePool) -> Router {
+pub fn routes(db: DbPool) -> Router {
     Router::new()
         .route("/api/oauth/authorize", post(authorize))
         .route("/api/oauth/token", post(token))
@@ -1,12 +1,20 @@
-use axum::{extract::Query, Json};
-use rand::Rng;
+use axum::{extract::Query, Extension, Json};
+use chrono::{DateTime, NaiveTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::error::{ApiError, ApiResult};
+use crate::ml::{parse_asset, MLService};
 
 #[derive(Debug, Deserialize)]
 pub struct PredictionQuery {
+    /// "native" or "CODE:ISSUER"
     pub source_asset: String,
+    /// "native" or "CODE:ISSUER"
     pub destination_asset: String,
+    /// Payment size in USD
     pub amount: f64,
+    /// RFC 3339 timestamp, or "HH:MM" UTC today
     pub time_of_day: String,
 }
 
@@ -15,22 +23,75 @@ pub struct PredictionResponse {
     pub success_probability: f64,
     pub confidence_interval: (f64, f64),
     pub alternative_routes: Vec<String>,
+    pub model_version: String,
+    /// Id under which the payment's outcome can be reported to
+    /// `POST /api/ml/predictions/:prediction_id/outcome`
+    pub prediction_id: String,
 }
 
 /// POST /api/predict/success - Predict payment success
-pub async fn predict_success(Query(_params): Query<PredictionQuery>) -> Json<PredictionResponse> {
-    // Mock implementation
-    let mut rng = rand::thread_rng();
-    let probability = rng.gen_range(0.8..0.98);
+///
+/// Scores the payment with the trained payment success model against the current state
+/// of the corridor delivering `destination_asset`.
+pub async fn predict_success(
+    Extension(ml_service): Extension<Arc<MLService>>,
+    Query(params): Query<PredictionQuery>,
+) -> ApiResult<Json<PredictionResponse>> {
+    if parse_asset(&params.source_asset).is_none() {
+        return Err(ApiError::bad_request(
+            "INVALID_ASSET",
+            "source_asset must be 'native' or 'CODE:ISSUER'",
+        ));
+    }
+    let (asset_code, asset_issuer) = parse_asset(&params.destination_asset).ok_or_else(|| {
+        ApiError::bad_request(
+            "INVALID_ASSET",
+            "destination_asset must be 'native' or 'CODE:ISSUER'",
+        )
+    })?;
+    if !params.amount.is_finite() || params.amount <= 0.0 {
+        return Err(ApiError::bad_request(
+            "INVALID_AMOUNT",
+            "amount must be a positive number",
+        ));
+    }
+    let timestamp = parse_time_of_day(&params.time_of_day, Utc::now()).ok_or_else(|| {
+        ApiError::bad_request(
+            "INVALID_TIME",
+            "time_of_day must be an RFC 3339 timestamp or HH:MM",
+        )
+    })?;
 
-    let response = PredictionResponse {
-        success_probability: probability,
-        confidence_interval: (probability - 0.05, probability + 0.02),
-        alternative_routes: vec![
-            "Route via XLM".to_string(),
-            "Route via secondary anchor".to_string(),
-        ],
+    let Some(model) = ml_service.current_model() else {
+        return Err(ApiError::not_found(
+            "MODEL_NOT_TRAINED",
+            "No payment success model has been trained yet",
+        ));
     };
 
-    Json(response)
+    let prediction = ml_service
+        .predict_payment_success(&asset_code, &asset_issuer, params.amount, timestamp)
+        .await
+        .map_err(|e| {
+            tracing::error!("Payment success prediction failed: {}", e);
+            ApiError::internal("PREDICTION_FAILED", "Failed to predict payment success")
+        })?;
+    let probability = f64::from(prediction.result.success_probability);
+
+    Ok(Json(PredictionResponse {
+        success_probability: probability,
+        confidence_interval: model.confidence_interval(probability),
+        // Filled in once path finding can suggest other routes
+        alternative_routes: Vec::new(),
+        model_version: prediction.result.model_version,
+        prediction_id: prediction.prediction_id,
+    }))
+}
+
+fn parse_time_of_day(value: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+    let time = NaiveTime::parse_from_str(value, "%H:%M").ok()?;
+    Some(now.date_naive().and_time(time).and_utc())
 }
@@ -16,7 +16,10 @@ use crate::{
         checkpoint::CheckpointManager,
         config::{ReplayConfig, ReplayMode, ReplayRange},
         engine::ReplayEngine,
-        event_processor::CompositeEventProcessor,
+        event_processor::{CompositeEventProcessor, SnapshotEventProcessor},
+        metrics_processors::{
+            AnchorMetricsProcessor, CorridorMetricsProcessor, LiquidityPoolSnapshotProcessor,
+        },
         state_builder::StateBuilder,
         storage::{EventStorage, ReplayStorage},
         EventFilter, ReplayMetadata,
@@ -121,7 +124,15 @@ pub async fn start_replay(
     let event_storage = Arc::new(EventStorage::new(state.db.pool().clone()));
     let replay_storage = Arc::new(ReplayStorage::new(state.db.pool().clone()));
     let checkpoint_manager = Arc::new(CheckpointManager::new(state.db.pool().clone()));
-    let processor = Arc::new(CompositeEventProcessor::new());
+    let pool = state.db.pool().clone();
+    let processor = Arc::new(
+        CompositeEventProcessor::new()
+            .add_processor(Arc::new(SnapshotEventProcessor::new(pool.clone())))
+            .add_processor(Arc::new(CorridorMetricsProcessor::hourly(pool.clone())))
+            .add_processor(Arc::new(CorridorMetricsProcessor::daily(pool.clone())))
+            .add_processor(Arc::new(AnchorMetricsProcessor::new(pool.clone())))
+            .add_processor(Arc::new(LiquidityPoolSnapshotProcessor::new(pool))),
+    );
     let state_builder = Arc::new(tokio::sync::RwLock::new(StateBuilder::new(
         state.db.pool().clone(),
     )));
@@ -138,6 +149,7 @@ pub async fn start_replay(
     .map_err(|e| ApiError::internal("INTERNAL_ERROR", e.to_string()))?;
 
     // Start replay in background
+    let session_id = engine.session_id().to_string();
     let engine_clone = Arc::new(engine);
     tokio::spawn(async move {
         match engine_clone.start().await {
@@ -153,7 +165,7 @@ pub async fn start_replay(
     Ok((
         StatusCode::ACCEPTED,
         Json(ReplayResponse {
-            session_id: "replay-session".to_string(), // Would use actual session ID
+            session_id,
             status: "started".to_string(),
             message: "Replay started successfully".to_string(),
         }),
@@ -195,6 +207,26 @@ pub async fn list_replays(
     Ok(Json(sessions))
 }
 
+/// Get the report of a verification replay
+pub async fn get_verification_report(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    info!("Getting verification report for session: {}", session_id);
+
+    let replay_storage = ReplayStorage::new(state.db.pool().clone());
+
+    let report = replay_storage
+        .load_verification_report(&session_id)
+        .await
+        .map_err(|e| ApiError::internal("INTERNAL_ERROR", e.to_string()))?
+        .ok_or_else(|| {
+            ApiError::not_found("NOT_FOUND", "Verification report not found".to_string())
+        })?;
+
+    Ok(Json(report))
+}
+
 /// Get checkpoints for a session
 pub async fn list_checkpoints(
     State(state): State<Arc<AppState>>,
@@ -13,6 +13,9 @@ use serde_json::Value;
 use std::sync::Arc;
 use std::time::Duration;
 
+use crate::ingestion::settlement::PROTOCOL_SEP24;
+use crate::services::sep_transfer_recorder::SepTransferRecorder;
+
 /// Allowed transfer server hosts (env: SEP24_ALLOWED_ORIGINS, comma-separated).
 /// If unset, any origin is allowed (use in dev only).
 fn allowed_origins() -> Vec<String> {
@@ -37,6 +40,9 @@ fn is_origin_allowed(transfer_server: &str) -> bool {
 #[derive(Clone)]
 pub struct Sep24State {
     pub client: Arc<Client>,
+    /// When set, transfer timestamps from registered anchors' responses feed their
+    /// settlement metrics
+    pub recorder: Option<Arc<SepTransferRecorder>>,
 }
 
 impl Sep24State {
@@ -47,6 +53,18 @@ impl Sep24State {
             .unwrap_or_else(|_| Client::new());
         Self {
             client: Arc::new(client),
+            recorder: None,
+        }
+    }
+
+    pub fn with_recorder(mut self, recorder: Arc<SepTransferRecorder>) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    async fn record_transfers(&self, transfer_server: &str, data: &Value) {
+        if let Some(recorder) = &self.recorder {
+            recorder.record(PROTOCOL_SEP24, transfer_server, data).await;
         }
     }
 }
@@ -292,6 +310,7 @@ pub async fn get_transactions(
     if !status.is_success() {
         return Err(Sep24Error::Anchor(status.as_u16(), data));
     }
+    state.record_transfers(&q.transfer_server, &data).await;
     Ok(Json(data))
 }
 
@@ -337,6 +356,7 @@ pub async fn get_transaction(
     if !status.is_success() {
         return Err(Sep24Error::Anchor(status.as_u16(), data));
     }
+    state.record_transfers(&q.transfer_server, &data).await;
     Ok(Json(data))
 }
 
@@ -388,8 +408,8 @@ impl IntoResponse for Sep24Error {
 }
 
 /// Build SEP-24 API router
-pub fn routes() -> axum::Router {
-    let state = Sep24State::new();
+pub fn routes(recorder: Arc<SepTransferRecorder>) -> axum::Router {
+    let state = Sep24State::new().with_recorder(recorder);
     axum::Router::new()
         .route("/api/sep24/info", axum::routing::get(get_info))
         .route(
@@ -13,6 +13,9 @@ use serde_json::Value;
 use std::sync::Arc;
 use std::time::Duration;
 
+use crate::ingestion::settlement::PROTOCOL_SEP31;
+use crate::services::sep_transfer_recorder::SepTransferRecorder;
+
 fn allowed_origins() -> Vec<String> {
     std::env::var("SEP31_ALLOWED_ORIGINS")
         .ok()
@@ -32,6 +35,9 @@ fn is_origin_allowed(transfer_server: &str) -> bool {
 #[derive(Clone)]
 pub struct Sep31State {
     pub client: Arc<Client>,
+    /// When set, transfer timestamps from registered anchors' responses feed their
+    /// settlement metrics
+    pub recorder: Option<Arc<SepTransferRecorder>>,
 }
 
 impl Sep31State {
@@ -42,6 +48,18 @@ impl Sep31State {
             .unwrap_or_else(|_| Client::new());
         Self {
             client: Arc::new(client),
+            recorder: None,
+        }
+    }
+
+    pub fn with_recorder(mut self, recorder: Arc<SepTransferRecorder>) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    async fn record_transfers(&self, transfer_server: &str, data: &Value) {
+        if let Some(recorder) = &self.recorder {
+            recorder.record(PROTOCOL_SEP31, transfer_server, data).await;
         }
     }
 }
@@ -223,6 +241,7 @@ pub async fn get_transactions(
     if !status.is_success() {
         return Err(Sep31Error::Anchor(status.as_u16(), data));
     }
+    state.record_transfers(&q.transfer_server, &data).await;
     Ok(Json(data))
 }
 
@@ -268,6 +287,7 @@ pub async fn get_transaction(
     if !status.is_success() {
         return Err(Sep31Error::Anchor(status.as_u16(), data));
     }
+    state.record_transfers(&q.transfer_server, &data).await;
     Ok(Json(data))
 }
 
@@ -402,8 +422,8 @@ impl IntoResponse for Sep31Error {
     }
 }
 
-pub fn routes() -> axum::Router {
-    let state = Sep31State::new();
+pub fn routes(recorder: Arc<SepTransferRecorder>) -> axum::Router {
+    let state = Sep31State::new().with_recorder(recorder);
     axum::Router::new()
         .route("/api/sep31/info", axum::routing::get(get_info))
         .route("/api/sep31/quote", axum::routing::post(post_quote))
@@ -37,7 +37,7 @@ pub fn routes(
     price_feed: Arc<PriceFeedClient>,
     rate_limiter: Arc<RateLimiter>,
     cors: CorsLayer,
-    pool: sqlx::SqlitePool,
+    pool: crate::database::DbPool,
     cache: Arc<CacheManager>,
 ) -> Router {
     // 1. Cached routes
@@ -85,6 +85,7 @@ pub fn routes(
     // 4. RPC routes
     let rpc_routes = Router::new()
         .route("/rpc/health", get(rpc_handlers::rpc_health_check))
+        .route("/rpc/providers", get(rpc_handlers::get_provider_status))
         .route("/rpc/ledger/latest", get(rpc_handlers::get_latest_ledger))
         .route("/rpc/payments", get(rpc_handlers::get_payments))
         .route(
@@ -93,7 +94,7 @@ pub fn routes(
         )
         .route("/rpc/trades", get(rpc_handlers::get_trades))
         .route("/rpc/orderbook", get(rpc_handlers::get_order_book))
-        .with_state(rpc_client);
+        .with_state(Arc::clone(&rpc_client));
 
     // 5. Special service routes
     let service_routes = Router::new()
@@ -104,7 +105,10 @@ pub fn routes(
         )
         .nest("/liquidity-pools", liquidity_pools::routes(lp_analyzer))
         .nest("/prices", price_feed_api::routes(price_feed.clone()))
-        .nest("/cost-calculator", cost_calculator::routes(price_feed))
+        .nest(
+            "/cost-calculator",
+            cost_calculator::routes(price_feed, rpc_client),
+        )
         .nest("/cache/stats", cache_stats::routes(cache.clone()))
         .nest("/metrics", metrics_cached::routes(cache));
 
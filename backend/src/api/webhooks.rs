@@ -1,28 +1,47 @@
+use crate::database::DbPool;
 /// Webhook API endpoints
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::{IntoResponse, Response},
-    routing::{delete, post},
+    routing::{delete, get, post},
     Json, Router,
 };
+use serde::Deserialize;
 use serde_json::json;
-use sqlx::SqlitePool;
 
 use crate::auth_middleware::AuthUser;
-use crate::webhooks::{CreateWebhookRequest, WebhookResponse, WebhookService};
+use crate::webhooks::delivery::{EVENT_DEAD_LETTERED, EVENT_DELIVERED, EVENT_PENDING};
+use crate::webhooks::{CreateWebhookRequest, Webhook, WebhookResponse, WebhookService};
+
+const DEFAULT_DELIVERY_LIMIT: i64 = 50;
+const MAX_DELIVERY_LIMIT: i64 = 200;
+const DEFAULT_SECRET_GRACE_HOURS: i64 = 24;
+const MAX_SECRET_GRACE_HOURS: i64 = 7 * 24;
 
 /// POST /api/webhooks - Register a new webhook
 pub async fn register_webhook(
-    State(db): State<SqlitePool>,
+    State(db): State<DbPool>,
     auth_user: AuthUser,
     Json(request): Json<CreateWebhookRequest>,
 ) -> Result<Response, WebhookApiError> {
+    validate_webhook_request(&request).map_err(WebhookApiError::BadRequest)?;
+
+    let service = WebhookService::new(db);
+    let response = service
+        .register_webhook(&auth_user.user_id, request)
+        .await
+        .map_err(|e| WebhookApiError::ServerError(e.to_string()))?;
+
+    Ok((StatusCode::CREATED, Json(response)).into_response())
+}
+
+/// Check a webhook registration before it is stored: the URL must be HTTP(S) and must
+/// not point at internal or private addresses, and at least one event type is needed.
+pub fn validate_webhook_request(request: &CreateWebhookRequest) -> Result<(), String> {
     // Validate URL scheme
     if !request.url.starts_with("https://") && !request.url.starts_with("http://") {
-        return Err(WebhookApiError::BadRequest(
-            "Webhook URL must be valid HTTP(S)".to_string(),
-        ));
+        return Err("Webhook URL must be valid HTTP(S)".to_string());
     }
 
     // SSRF protection: block private/internal URLs (SEC-008)
@@ -37,15 +56,13 @@ pub async fn register_webhook(
                 || host_lower.ends_with(".local")
                 || host_lower.ends_with(".internal")
             {
-                return Err(WebhookApiError::BadRequest(
+                return Err(
                     "Webhook URL must not point to localhost or internal addresses".to_string(),
-                ));
+                );
             }
             // Block AWS metadata endpoint
             if host_lower == "169.254.169.254" || host_lower == "metadata.google.internal" {
-                return Err(WebhookApiError::BadRequest(
-                    "Webhook URL must not point to cloud metadata endpoints".to_string(),
-                ));
+                return Err("Webhook URL must not point to cloud metadata endpoints".to_string());
             }
             // Block common private IP ranges
             if let Ok(ip) = host.parse::<std::net::IpAddr>() {
@@ -59,38 +76,28 @@ pub async fn register_webhook(
                     std::net::IpAddr::V6(v6) => v6.is_loopback(),
                 };
                 if is_private {
-                    return Err(WebhookApiError::BadRequest(
+                    return Err(
                         "Webhook URL must not point to private or reserved IP addresses"
                             .to_string(),
-                    ));
+                    );
                 }
             }
         }
     } else {
-        return Err(WebhookApiError::BadRequest(
-            "Webhook URL is not a valid URL".to_string(),
-        ));
+        return Err("Webhook URL is not a valid URL".to_string());
     }
 
     // Validate event types
     if request.event_types.is_empty() {
-        return Err(WebhookApiError::BadRequest(
-            "At least one event type is required".to_string(),
-        ));
+        return Err("At least one event type is required".to_string());
     }
 
-    let service = WebhookService::new(db);
-    let response = service
-        .register_webhook(&auth_user.user_id, request)
-        .await
-        .map_err(|e| WebhookApiError::ServerError(e.to_string()))?;
-
-    Ok((StatusCode::CREATED, Json(response)).into_response())
+    Ok(())
 }
 
 /// GET /api/webhooks - List webhooks for authenticated user
 pub async fn list_webhooks(
-    State(db): State<SqlitePool>,
+    State(db): State<DbPool>,
     auth_user: AuthUser,
 ) -> Result<Response, WebhookApiError> {
     let service = WebhookService::new(db);
@@ -111,6 +118,9 @@ pub async fn list_webhooks(
                 .and_then(|f| serde_json::from_str(f).ok()),
             is_active: w.is_active,
             created_at: w.created_at,
+            disabled_at: w.disabled_at,
+            disabled_reason: w.disabled_reason,
+            secret: None,
         })
         .collect();
 
@@ -119,7 +129,7 @@ pub async fn list_webhooks(
 
 /// DELETE /api/webhooks/:id - Delete/deactivate webhook
 pub async fn delete_webhook(
-    State(db): State<SqlitePool>,
+    State(db): State<DbPool>,
     auth_user: AuthUser,
     Path(webhook_id): Path<String>,
 ) -> Result<Response, WebhookApiError> {
@@ -142,7 +152,7 @@ pub async fn delete_webhook(
 
 /// POST /api/webhooks/:id/test - Send test payload to webhook
 pub async fn test_webhook(
-    State(db): State<SqlitePool>,
+    State(db): State<DbPool>,
     auth_user: AuthUser,
     Path(webhook_id): Path<String>,
 ) -> Result<Response, WebhookApiError> {
@@ -184,6 +194,202 @@ pub async fn test_webhook(
         .into_response())
 }
 
+/// Webhook of `webhook_id` if it belongs to the caller
+async fn owned_webhook(
+    service: &WebhookService,
+    webhook_id: &str,
+    user_id: &str,
+) -> Result<Webhook, WebhookApiError> {
+    let webhook = service
+        .get_webhook(webhook_id)
+        .await
+        .map_err(|e| WebhookApiError::ServerError(e.to_string()))?
+        .ok_or_else(|| WebhookApiError::NotFound("Webhook not found".to_string()))?;
+
+    if webhook.user_id != user_id {
+        return Err(WebhookApiError::Forbidden);
+    }
+    Ok(webhook)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeliveriesQuery {
+    /// `pending`, `delivered` or `dead_lettered`
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+impl DeliveriesQuery {
+    fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(DEFAULT_DELIVERY_LIMIT)
+            .clamp(1, MAX_DELIVERY_LIMIT)
+    }
+}
+
+/// GET /api/webhooks/:id/deliveries - Recent events queued for a webhook and their state
+pub async fn list_deliveries(
+    State(db): State<DbPool>,
+    auth_user: AuthUser,
+    Path(webhook_id): Path<String>,
+    Query(query): Query<DeliveriesQuery>,
+) -> Result<Response, WebhookApiError> {
+    if let Some(status) = query.status.as_deref() {
+        if ![EVENT_PENDING, EVENT_DELIVERED, EVENT_DEAD_LETTERED].contains(&status) {
+            return Err(WebhookApiError::BadRequest(format!(
+                "Unknown delivery status '{}'",
+                status
+            )));
+        }
+    }
+
+    let service = WebhookService::new(db);
+    owned_webhook(&service, &webhook_id, &auth_user.user_id).await?;
+    let deliveries = service
+        .list_deliveries(&webhook_id, query.status.as_deref(), query.limit())
+        .await
+        .map_err(|e| WebhookApiError::ServerError(e.to_string()))?;
+
+    Ok((StatusCode::OK, Json(json!({"deliveries": deliveries}))).into_response())
+}
+
+/// GET /api/webhooks/:id/deliveries/:event_id - An event with every attempt made to
+/// deliver it (status code, latency and start of the response body)
+pub async fn get_delivery(
+    State(db): State<DbPool>,
+    auth_user: AuthUser,
+    Path((webhook_id, event_id)): Path<(String, String)>,
+) -> Result<Response, WebhookApiError> {
+    let service = WebhookService::new(db);
+    owned_webhook(&service, &webhook_id, &auth_user.user_id).await?;
+
+    let delivery = service
+        .get_delivery(&webhook_id, &event_id)
+        .await
+        .map_err(|e| WebhookApiError::ServerError(e.to_string()))?
+        .ok_or_else(|| WebhookApiError::NotFound("Delivery not found".to_string()))?;
+    let attempts = service
+        .list_delivery_attempts(&webhook_id, &event_id)
+        .await
+        .map_err(|e| WebhookApiError::ServerError(e.to_string()))?;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({"delivery": delivery, "attempts": attempts})),
+    )
+        .into_response())
+}
+
+/// POST /api/webhooks/:id/deliveries/:event_id/redeliver - Queue an event again,
+/// whether it was dead-lettered or already delivered
+pub async fn redeliver(
+    State(db): State<DbPool>,
+    auth_user: AuthUser,
+    Path((webhook_id, event_id)): Path<(String, String)>,
+) -> Result<Response, WebhookApiError> {
+    let service = WebhookService::new(db);
+    let webhook = owned_webhook(&service, &webhook_id, &auth_user.user_id).await?;
+    if !webhook.is_active {
+        return Err(WebhookApiError::BadRequest(
+            "Webhook has been deleted".to_string(),
+        ));
+    }
+    if webhook.disabled_at.is_some() {
+        return Err(WebhookApiError::BadRequest(
+            "Webhook is disabled; enable it before redelivering".to_string(),
+        ));
+    }
+
+    let queued = service
+        .redeliver_event(&webhook_id, &event_id)
+        .await
+        .map_err(|e| WebhookApiError::ServerError(e.to_string()))?;
+    if !queued {
+        return Err(WebhookApiError::NotFound("Delivery not found".to_string()));
+    }
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({"message": "Delivery queued", "event_id": event_id})),
+    )
+        .into_response())
+}
+
+/// GET /api/webhooks/:id/dead-letters - Events that will not be retried again unless
+/// redelivered
+pub async fn list_dead_letters(
+    State(db): State<DbPool>,
+    auth_user: AuthUser,
+    Path(webhook_id): Path<String>,
+    Query(query): Query<DeliveriesQuery>,
+) -> Result<Response, WebhookApiError> {
+    let service = WebhookService::new(db);
+    owned_webhook(&service, &webhook_id, &auth_user.user_id).await?;
+    let dead_letters = service
+        .list_dead_letters(&webhook_id, query.limit())
+        .await
+        .map_err(|e| WebhookApiError::ServerError(e.to_string()))?;
+
+    Ok((StatusCode::OK, Json(json!({"dead_letters": dead_letters}))).into_response())
+}
+
+/// POST /api/webhooks/:id/enable - Resume deliveries to a webhook disabled for failing
+pub async fn enable_webhook(
+    State(db): State<DbPool>,
+    auth_user: AuthUser,
+    Path(webhook_id): Path<String>,
+) -> Result<Response, WebhookApiError> {
+    let service = WebhookService::new(db);
+    owned_webhook(&service, &webhook_id, &auth_user.user_id).await?;
+    let enabled = service
+        .enable_webhook(&webhook_id, &auth_user.user_id)
+        .await
+        .map_err(|e| WebhookApiError::ServerError(e.to_string()))?;
+    if !enabled {
+        return Err(WebhookApiError::NotFound("Webhook not found".to_string()));
+    }
+
+    Ok((StatusCode::OK, Json(json!({"message": "Webhook enabled"}))).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RotateSecretQuery {
+    /// Hours the previous secret keeps signing deliveries; 0 retires it at once
+    pub grace_period_hours: Option<i64>,
+}
+
+/// POST /api/webhooks/:id/rotate-secret - Issue a new signing secret
+pub async fn rotate_secret(
+    State(db): State<DbPool>,
+    auth_user: AuthUser,
+    Path(webhook_id): Path<String>,
+    Query(query): Query<RotateSecretQuery>,
+) -> Result<Response, WebhookApiError> {
+    let grace_hours = query
+        .grace_period_hours
+        .unwrap_or(DEFAULT_SECRET_GRACE_HOURS);
+    if !(0..=MAX_SECRET_GRACE_HOURS).contains(&grace_hours) {
+        return Err(WebhookApiError::BadRequest(format!(
+            "grace_period_hours must be between 0 and {}",
+            MAX_SECRET_GRACE_HOURS
+        )));
+    }
+
+    let service = WebhookService::new(db);
+    owned_webhook(&service, &webhook_id, &auth_user.user_id).await?;
+    let rotated = service
+        .rotate_secret(
+            &webhook_id,
+            &auth_user.user_id,
+            chrono::Duration::hours(grace_hours),
+        )
+        .await
+        .map_err(|e| WebhookApiError::ServerError(e.to_string()))?
+        .ok_or_else(|| WebhookApiError::NotFound("Webhook not found".to_string()))?;
+
+    Ok((StatusCode::OK, Json(rotated)).into_response())
+}
+
 /// Webhook API Error types
 #[derive(Debug)]
 pub enum WebhookApiError {
@@ -210,10 +416,19 @@ impl IntoResponse for WebhookApiError {
 }
 
 /// Create webhook routes
-pub fn routes(db: SqlitePool) -> Router {
+pub fn routes(db: DbPool) -> Router {
     Router::new()
         .route("/api/webhooks", post(register_webhook).get(list_webhooks))
         .route("/api/webhooks/:id", delete(delete_webhook))
         .route("/api/webhooks/:id/test", post(test_webhook))
+        .route("/api/webhooks/:id/enable", post(enable_webhook))
+        .route("/api/webhooks/:id/rotate-secret", post(rotate_secret))
+        .route("/api/webhooks/:id/deliveries", get(list_deliveries))
+        .route("/api/webhooks/:id/deliveries/:event_id", get(get_delivery))
+        .route(
+            "/api/webhooks/:id/deliveries/:event_id/redeliver",
+            post(redeliver),
+        )
+        .route("/api/webhooks/:id/dead-letters", get(list_dead_letters))
         .with_state(db)
 }
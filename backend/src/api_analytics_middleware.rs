@@ -1,4 +1,5 @@
 use crate::database::Database;
+use crate::with_pool;
 use axum::{
     body::Body,
     extract::{Request, State},
@@ -38,19 +39,22 @@ pub async fn api_analytics_middleware(
         let id = Uuid::new_v4().to_string();
         let timestamp = Utc::now();
 
-        let result = sqlx::query(
-            "INSERT INTO api_usage_stats (id, endpoint, method, status_code, response_time_ms, user_id, timestamp) 
-             VALUES (?, ?, ?, ?, ?, ?, ?)"
-        )
-        .bind(id)
-        .bind(path)
-        .bind(method.to_string())
-        .bind(status)
-        .bind(duration)
-        .bind(user_id)
-        .bind(timestamp)
-        .execute(db_clone.pool())
-        .await;
+        let result = with_pool!(db_clone.pool(), |pool| {
+            sqlx::query(
+                "INSERT INTO api_usage_stats (id, endpoint, method, status_code, response_time_ms, user_id, timestamp) 
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)"
+            )
+            .bind(id)
+            .bind(path)
+            .bind(method.to_string())
+            .bind(status)
+            .bind(duration)
+            .bind(user_id)
+            .bind(timestamp)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        });
 
         if let Err(e) = result {
             tracing::error!("Failed to record API usage stat: {}", e);
@@ -3,6 +3,8 @@ pub mod oauth;
 pub mod sep10_middleware;
 pub mod sep10_simple;
 
+use crate::database::DbPool;
+use crate::with_pool;
 use anyhow::{anyhow, Result};
 use argon2::{Argon2, PasswordHash, PasswordVerifier};
 use chrono::{Duration, Utc};
@@ -10,7 +12,6 @@ use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation}
 use redis::aio::MultiplexedConnection;
 use redis::AsyncCommands;
 use serde::{Deserialize, Serialize};
-use sqlx::SqlitePool;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -76,13 +77,13 @@ pub struct Claims {
 pub struct AuthService {
     jwt_secret: String,
     redis_connection: Arc<RwLock<Option<MultiplexedConnection>>>,
-    db_pool: SqlitePool,
+    db_pool: DbPool,
 }
 
 impl AuthService {
     pub fn new(
         redis_connection: Arc<RwLock<Option<MultiplexedConnection>>>,
-        db_pool: SqlitePool,
+        db_pool: DbPool,
     ) -> Self {
         let jwt_secret = std::env::var("JWT_SECRET")
             .expect("JWT_SECRET environment variable is required. Generate a cryptographically secure random key of at least 32 bytes.");
@@ -108,12 +109,14 @@ impl AuthService {
             password_hash: String,
         }
 
-        let record = sqlx::query_as::<_, UserRecord>(
-            "SELECT id, username, password_hash FROM users WHERE username = $1",
-        )
-        .bind(username)
-        .fetch_optional(&self.db_pool)
-        .await
+        let record = with_pool!(&self.db_pool, |pool| {
+            sqlx::query_as::<_, UserRecord>(
+                "SELECT id, username, password_hash FROM users WHERE username = $1",
+            )
+            .bind(username)
+            .fetch_optional(pool)
+            .await
+        })
         .map_err(|e| anyhow!("Database error during authentication: {}", e))?;
 
         let record = record.ok_or_else(|| anyhow!("Invalid username or password"))?;
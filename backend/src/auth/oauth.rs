@@ -1,10 +1,11 @@
+use crate::database::DbPool;
+use crate::with_pool;
 /// OAuth 2.0 module for Zapier integration
 /// Handles authorization code flow, token generation, and scope validation
 use anyhow::{anyhow, Result};
 use chrono::{Duration, Utc};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
-use sqlx::SqlitePool;
 use uuid::Uuid;
 
 /// OAuth Claims - extended JWT with additional Zapier fields
@@ -63,12 +64,12 @@ pub struct OAuthService {
     token_expiry_days: i64,
     refresh_expiry_days: i64,
     encryption_key: String,
-    db: SqlitePool,
+    db: DbPool,
 }
 
 impl OAuthService {
     /// Create new OAuth service
-    pub fn new(db: SqlitePool) -> Self {
+    pub fn new(db: DbPool) -> Self {
         let jwt_secret = std::env::var("JWT_SECRET")
             .expect("JWT_SECRET environment variable is required for OAuth service");
 
@@ -110,19 +111,22 @@ impl OAuthService {
         let encrypted_secret = crate::crypto::encrypt_data(&client_secret, &self.encryption_key)
             .map_err(|e| anyhow!("Failed to encrypt client secret: {}", e))?;
 
-        sqlx::query(
-            r#"
-            INSERT INTO oauth_clients (id, user_id, client_id, client_secret, app_name)
-            VALUES (?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(id)
-        .bind(user_id)
-        .bind(client_id.clone())
-        .bind(encrypted_secret)
-        .bind(app_name)
-        .execute(&self.db)
-        .await?;
+        with_pool!(&self.db, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO oauth_clients (id, user_id, client_id, client_secret, app_name)
+                VALUES ($1, $2, $3, $4, $5)
+                "#,
+            )
+            .bind(id)
+            .bind(user_id)
+            .bind(client_id.clone())
+            .bind(encrypted_secret)
+            .bind(app_name)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
 
         Ok((client_id, client_secret))
     }
@@ -133,20 +137,17 @@ impl OAuthService {
         client_id: &str,
         client_secret: &str,
     ) -> Result<String> {
-        let row = sqlx::query(
-            r#"
-            SELECT user_id, client_secret FROM oauth_clients
-            WHERE client_id = ?
-            "#,
-        )
-        .bind(client_id)
-        .fetch_optional(&self.db)
-        .await?;
-
-        let client = row.map(|r| {
-            use sqlx::Row;
-            (r.get::<String, _>(0), r.get::<String, _>(1))
-        });
+        let client: Option<(String, String)> = with_pool!(&self.db, |pool| {
+            sqlx::query_as(
+                r#"
+                SELECT user_id, client_secret FROM oauth_clients
+                WHERE client_id = $1
+                "#,
+            )
+            .bind(client_id)
+            .fetch_optional(pool)
+            .await
+        })?;
 
         match client {
             Some((user_id, client_secret_record)) => {
@@ -272,18 +273,21 @@ impl OAuthService {
         let id = Uuid::new_v4().to_string();
         let scopes_str = scopes.join(",");
 
-        sqlx::query(
-            r#"
-            INSERT INTO oauth_authorizations (id, client_id, user_id, scopes)
-            VALUES (?, ?, ?, ?)
-            "#,
-        )
-        .bind(id)
-        .bind(client_id)
-        .bind(user_id)
-        .bind(scopes_str)
-        .execute(&self.db)
-        .await?;
+        with_pool!(&self.db, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO oauth_authorizations (id, client_id, user_id, scopes)
+                VALUES ($1, $2, $3, $4)
+                "#,
+            )
+            .bind(id)
+            .bind(client_id)
+            .bind(user_id)
+            .bind(scopes_str)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
 
         Ok(())
     }
@@ -294,21 +298,18 @@ impl OAuthService {
         client_id: &str,
         user_id: &str,
     ) -> Result<Option<Vec<String>>> {
-        let row = sqlx::query(
-            r#"
-            SELECT scopes FROM oauth_authorizations
-            WHERE client_id = ? AND user_id = ?
-            "#,
-        )
-        .bind(client_id)
-        .bind(user_id)
-        .fetch_optional(&self.db)
-        .await?;
-
-        let auth = row.map(|r| {
-            use sqlx::Row;
-            r.get::<String, _>(0)
-        });
+        let auth: Option<String> = with_pool!(&self.db, |pool| {
+            sqlx::query_scalar(
+                r#"
+                SELECT scopes FROM oauth_authorizations
+                WHERE client_id = $1 AND user_id = $2
+                "#,
+            )
+            .bind(client_id)
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await
+        })?;
 
         Ok(auth.map(|record| record.split(',').map(|s: &str| s.to_string()).collect()))
     }
@@ -332,20 +333,23 @@ impl OAuthService {
         let enc_refresh_token = crate::crypto::encrypt_data(refresh_token, &self.encryption_key)
             .map_err(|e| anyhow!("Failed to encrypt refresh token: {}", e))?;
 
-        sqlx::query(
-            r#"
-            INSERT INTO oauth_tokens (id, user_id, access_token, refresh_token, token_type, expires_at)
-            VALUES (?, ?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(id)
-        .bind(user_id)
-        .bind(enc_access_token)
-        .bind(enc_refresh_token)
-        .bind("Bearer")
-        .bind(expires_at_str)
-        .execute(&self.db)
-        .await?;
+        with_pool!(&self.db, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO oauth_tokens (id, user_id, access_token, refresh_token, token_type, expires_at)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#,
+            )
+            .bind(id)
+            .bind(user_id)
+            .bind(enc_access_token)
+            .bind(enc_refresh_token)
+            .bind("Bearer")
+            .bind(expires_at_str)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
 
         Ok(())
     }
@@ -355,12 +359,15 @@ impl OAuthService {
         let enc_token = crate::crypto::encrypt_data(access_token, &self.encryption_key)
             .map_err(|e| anyhow!("Failed to encrypt token for lookup: {}", e))?;
 
-        let result = sqlx::query(r#"DELETE FROM oauth_tokens WHERE access_token = ?"#)
-            .bind(enc_token)
-            .execute(&self.db)
-            .await?;
+        let result = with_pool!(&self.db, |pool| {
+            sqlx::query(r#"DELETE FROM oauth_tokens WHERE access_token = $1"#)
+                .bind(enc_token)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+        })?;
 
-        if result.rows_affected() == 0 {
+        if result == 0 {
             tracing::warn!("Token revocation requested but token not found in database");
         } else {
             tracing::info!("OAuth token revoked successfully");
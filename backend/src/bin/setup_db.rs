@@ -1,14 +1,16 @@
-use sqlx::{sqlite::SqliteConnectOptions, SqlitePool};
-use std::str::FromStr;
+use stellar_insights_backend::database::{run_migrations, PoolConfig};
 
 #[tokio::main]
 async fn main() {
+    let database_url = std::env::var("DATABASE_URL")
+        .unwrap_or_else(|_| "sqlite://stellar_insights.db?mode=rwc".to_string());
+
     println!("Creating database...");
-    let options = SqliteConnectOptions::from_str("sqlite://stellar_insights.db")
-        .unwrap()
-        .create_if_missing(true);
-    let pool = SqlitePool::connect_with(options).await.unwrap();
+    let pool = PoolConfig::default()
+        .create_pool(&database_url)
+        .await
+        .unwrap();
     println!("Applying migrations...");
-    sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+    run_migrations(&pool).await.unwrap();
     println!("Database ready!");
 }
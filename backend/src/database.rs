@@ -1,14 +1,16 @@
 use crate::admin_audit_log::AdminAuditLogger;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
+use sqlx::pool::PoolOptions;
+use sqlx::postgres::PgConnectOptions;
 use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode};
-use sqlx::{ConnectOptions, SqlitePool};
-use std::str::FromStr;
+use sqlx::ConnectOptions;
 use std::time::Duration;
 use std::time::Instant;
 use uuid::Uuid;
 
 use crate::analytics::compute_anchor_metrics;
+use crate::ingestion::settlement::{PaymentStats, SepTransferTimes};
 use crate::models::api_key::{
     generate_api_key, hash_api_key, ApiKey, ApiKeyInfo, CreateApiKeyRequest, CreateApiKeyResponse,
 };
@@ -17,6 +19,113 @@ use crate::models::{
     MetricRecord, MuxedAccountAnalytics, MuxedAccountUsage, SnapshotRecord,
 };
 
+/// SQL engine the data layer runs on, chosen at startup from the
+/// `DATABASE_URL` scheme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatabaseBackend {
+    Sqlite,
+    Postgres,
+}
+
+impl DatabaseBackend {
+    /// Engine named by a `DATABASE_URL` scheme
+    pub fn from_url(url: &str) -> Result<Self> {
+        let scheme = url.split(':').next().unwrap_or_default();
+        match scheme.to_ascii_lowercase().as_str() {
+            "sqlite" => Ok(Self::Sqlite),
+            "postgres" | "postgresql" => Ok(Self::Postgres),
+            _ => Err(anyhow::anyhow!(
+                "Unsupported DATABASE_URL scheme '{}': expected sqlite: or postgres://",
+                scheme
+            )),
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Sqlite => "SQLite",
+            Self::Postgres => "PostgreSQL",
+        }
+    }
+}
+
+/// Connection pool used by `Database` and every service
+///
+/// Queries run through [`with_pool!`](crate::with_pool), which compiles them
+/// once per engine.
+#[derive(Debug, Clone)]
+pub enum DbPool {
+    Sqlite(sqlx::SqlitePool),
+    Postgres(sqlx::PgPool),
+}
+
+impl DbPool {
+    pub fn backend(&self) -> DatabaseBackend {
+        match self {
+            Self::Sqlite(_) => DatabaseBackend::Sqlite,
+            Self::Postgres(_) => DatabaseBackend::Postgres,
+        }
+    }
+
+    pub fn size(&self) -> u32 {
+        crate::with_pool!(self, |pool| pool.size())
+    }
+
+    pub fn num_idle(&self) -> usize {
+        crate::with_pool!(self, |pool| pool.num_idle())
+    }
+
+    pub async fn close(&self) {
+        crate::with_pool!(self, |pool| pool.close().await);
+    }
+}
+
+impl From<sqlx::SqlitePool> for DbPool {
+    fn from(pool: sqlx::SqlitePool) -> Self {
+        Self::Sqlite(pool)
+    }
+}
+
+impl From<sqlx::PgPool> for DbPool {
+    fn from(pool: sqlx::PgPool) -> Self {
+        Self::Postgres(pool)
+    }
+}
+
+/// Evaluate `$body` with `$pool` bound to the engine-specific pool behind a
+/// `&DbPool`.
+///
+/// The body is compiled once for each engine, so it can build queries, decode
+/// rows and open transactions exactly as it would against a single
+/// `sqlx::Pool`; both expansions must evaluate to the same type.
+///
+/// ```ignore
+/// let count: i64 = with_pool!(&self.pool, |pool| {
+///     sqlx::query_scalar("SELECT COUNT(*) FROM anchors")
+///         .fetch_one(pool)
+///         .await
+/// })?;
+/// ```
+#[macro_export]
+macro_rules! with_pool {
+    ($db:expr, |$pool:ident| $body:expr) => {
+        match $db {
+            $crate::database::DbPool::Sqlite($pool) => $body,
+            $crate::database::DbPool::Postgres($pool) => $body,
+        }
+    };
+}
+
+/// Apply the migration set for the pool's engine: `migrations/` for SQLite,
+/// `migrations/postgres/` for PostgreSQL.
+pub async fn run_migrations(pool: &DbPool) -> Result<()> {
+    match pool {
+        DbPool::Sqlite(pool) => sqlx::migrate!("./migrations").run(pool).await?,
+        DbPool::Postgres(pool) => sqlx::migrate!("./migrations/postgres").run(pool).await?,
+    }
+    Ok(())
+}
+
 /// Configuration for database connection pool
 #[derive(Debug, Clone)]
 pub struct PoolConfig {
@@ -130,45 +239,65 @@ impl PoolConfig {
         }
     }
 
-    /// Create a configured SQLite pool with these settings.
-    /// Uses WAL journal mode and configurable SQL query logging (all in dev, slow-only in prod).
-    pub async fn create_pool(&self, database_url: &str) -> Result<SqlitePool> {
+    /// Create a configured pool with these settings for the engine named by
+    /// `database_url`. SQLite uses WAL journal mode. SQL query logging is
+    /// configurable (all in dev, slow-only in prod).
+    pub async fn create_pool(&self, database_url: &str) -> Result<DbPool> {
+        let backend = DatabaseBackend::from_url(database_url)?;
         let sql_log = SqlLogConfig::from_env();
-
-        let mut opts: SqliteConnectOptions = database_url
-            .parse()
-            .map_err(|e: sqlx::Error| anyhow::anyhow!("Invalid DATABASE_URL: {}", e))?;
-
-        opts = opts.journal_mode(SqliteJournalMode::Wal);
-
-        if sql_log.level != log::LevelFilter::Off {
-            if sql_log.log_all_in_dev {
-                opts = opts.log_statements(sql_log.level);
-                tracing::info!(
-                    "SQL query logging: all queries at level {:?} (development)",
-                    sql_log.level
-                );
-            } else {
-                let threshold = Duration::from_millis(sql_log.slow_query_threshold_ms);
-                opts = opts.log_slow_statements(sql_log.level, threshold);
-                tracing::info!(
-                    "SQL query logging: slow queries only (> {} ms) at level {:?} (production)",
-                    sql_log.slow_query_threshold_ms,
-                    sql_log.level
-                );
+        tracing::info!("Database backend: {}", backend.name());
+
+        let pool = match backend {
+            DatabaseBackend::Sqlite => {
+                let opts: SqliteConnectOptions = parse_connect_options(database_url)?;
+                let opts =
+                    with_statement_logging(opts.journal_mode(SqliteJournalMode::Wal), &sql_log);
+                DbPool::Sqlite(self.pool_options().connect_with(opts).await?)
             }
-        }
+            DatabaseBackend::Postgres => {
+                let opts: PgConnectOptions = parse_connect_options(database_url)?;
+                let opts = with_statement_logging(opts, &sql_log);
+                DbPool::Postgres(self.pool_options().connect_with(opts).await?)
+            }
+        };
 
-        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        Ok(pool)
+    }
+
+    fn pool_options<DB: sqlx::Database>(&self) -> PoolOptions<DB> {
+        PoolOptions::new()
             .max_connections(self.max_connections)
             .min_connections(self.min_connections)
             .acquire_timeout(Duration::from_secs(self.connect_timeout_seconds))
             .idle_timeout(Some(Duration::from_secs(self.idle_timeout_seconds)))
             .max_lifetime(Some(Duration::from_secs(self.max_lifetime_seconds)))
-            .connect_with(opts)
-            .await?;
+    }
+}
 
-        Ok(pool)
+fn parse_connect_options<O: ConnectOptions>(database_url: &str) -> Result<O> {
+    database_url
+        .parse()
+        .map_err(|e: sqlx::Error| anyhow::anyhow!("Invalid DATABASE_URL: {}", e))
+}
+
+fn with_statement_logging<O: ConnectOptions>(opts: O, sql_log: &SqlLogConfig) -> O {
+    if sql_log.level == log::LevelFilter::Off {
+        return opts;
+    }
+    if sql_log.log_all_in_dev {
+        tracing::info!(
+            "SQL query logging: all queries at level {:?} (development)",
+            sql_log.level
+        );
+        opts.log_statements(sql_log.level)
+    } else {
+        let threshold = Duration::from_millis(sql_log.slow_query_threshold_ms);
+        tracing::info!(
+            "SQL query logging: slow queries only (> {} ms) at level {:?} (production)",
+            sql_log.slow_query_threshold_ms,
+            sql_log.level
+        );
+        opts.log_slow_statements(sql_log.level, threshold)
     }
 }
 
@@ -179,11 +308,21 @@ pub struct AnchorRpcUpdate {
     pub successful_transactions: i64,
     pub failed_transactions: i64,
     pub total_volume_usd: f64,
-    pub avg_settlement_time_ms: i32,
+    /// `None` keeps the previously stored value
+    pub avg_settlement_time_ms: Option<i32>,
     pub reliability_score: f64,
     pub status: String,
 }
 
+/// Incremental payment sync state for an anchor
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+pub struct AnchorPaymentSync {
+    pub stellar_account: String,
+    pub cursor: Option<String>,
+    #[sqlx(flatten)]
+    pub stats: PaymentStats,
+}
+
 /// Parameters for recording anchor metrics history
 pub struct AnchorMetricsParams {
     pub anchor_id: Uuid,
@@ -205,12 +344,12 @@ pub struct PoolMetrics {
 }
 
 pub struct Database {
-    pool: SqlitePool,
+    pool: DbPool,
     pub admin_audit_logger: AdminAuditLogger,
 }
 
 impl Database {
-    pub fn new(pool: SqlitePool) -> Self {
+    pub fn new(pool: DbPool) -> Self {
         let admin_audit_logger = AdminAuditLogger::new(pool.clone());
         Self {
             pool,
@@ -218,7 +357,7 @@ impl Database {
         }
     }
 
-    pub fn pool(&self) -> &SqlitePool {
+    pub fn pool(&self) -> &DbPool {
         &self.pool
     }
 
@@ -259,19 +398,21 @@ impl Database {
     /// ```
     pub async fn create_anchor(&self, req: CreateAnchorRequest) -> Result<Anchor> {
         let id = Uuid::new_v4().to_string();
-        let anchor = sqlx::query_as::<_, Anchor>(
-            r#"
-            INSERT INTO anchors (id, name, stellar_account, home_domain)
-            VALUES ($1, $2, $3, $4)
-            RETURNING *
-            "#,
-        )
-        .bind(id)
-        .bind(&req.name)
-        .bind(&req.stellar_account)
-        .bind(&req.home_domain)
-        .fetch_one(&self.pool)
-        .await?;
+        let anchor = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, Anchor>(
+                r#"
+                INSERT INTO anchors (id, name, stellar_account, home_domain)
+                VALUES ($1, $2, $3, $4)
+                RETURNING *
+                "#,
+            )
+            .bind(id)
+            .bind(&req.name)
+            .bind(&req.stellar_account)
+            .bind(&req.home_domain)
+            .fetch_one(pool)
+            .await
+        })?;
 
         Ok(anchor)
     }
@@ -304,14 +445,16 @@ impl Database {
     ///
     /// Indexed query on primary key, typically <1ms.
     pub async fn get_anchor_by_id(&self, id: Uuid) -> Result<Option<Anchor>> {
-        let anchor = sqlx::query_as::<_, Anchor>(
-            r#"
-            SELECT * FROM anchors WHERE id = $1
-            "#,
-        )
-        .bind(id.to_string())
-        .fetch_optional(&self.pool)
-        .await?;
+        let anchor = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, Anchor>(
+                r#"
+                SELECT * FROM anchors WHERE id = $1
+                "#,
+            )
+            .bind(id.to_string())
+            .fetch_optional(pool)
+            .await
+        })?;
 
         Ok(anchor)
     }
@@ -338,14 +481,16 @@ impl Database {
         &self,
         stellar_account: &str,
     ) -> Result<Option<Anchor>> {
-        let anchor = sqlx::query_as::<_, Anchor>(
-            r#"
-            SELECT * FROM anchors WHERE stellar_account = $1
-            "#,
-        )
-        .bind(stellar_account)
-        .fetch_optional(&self.pool)
-        .await?;
+        let anchor = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, Anchor>(
+                r#"
+                SELECT * FROM anchors WHERE stellar_account = $1
+                "#,
+            )
+            .bind(stellar_account)
+            .fetch_optional(pool)
+            .await
+        })?;
 
         Ok(anchor)
     }
@@ -376,17 +521,19 @@ impl Database {
     /// Query is indexed and metrics are recorded. Typical response time <10ms for limit ≤ 100.
     pub async fn list_anchors(&self, limit: i64, offset: i64) -> Result<Vec<Anchor>> {
         let start = Instant::now();
-        let anchors = sqlx::query_as::<_, Anchor>(
-            r#"
-            SELECT * FROM anchors
-            ORDER BY reliability_score DESC, updated_at DESC
-            LIMIT $1 OFFSET $2
-            "#,
-        )
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&self.pool)
-        .await?;
+        let anchors = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, Anchor>(
+                r#"
+                SELECT * FROM anchors
+                ORDER BY reliability_score DESC, updated_at DESC
+                LIMIT $1 OFFSET $2
+                "#,
+            )
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(pool)
+            .await
+        })?;
 
         crate::observability::metrics::observe_db_query(
             "list_anchors",
@@ -453,32 +600,34 @@ impl Database {
         );
 
         // Update anchor
-        let anchor = sqlx::query_as::<_, Anchor>(
-            r#"
-            UPDATE anchors
-            SET total_transactions = $1,
-                successful_transactions = $2,
-                failed_transactions = $3,
-                avg_settlement_time_ms = $4,
-                reliability_score = $5,
-                status = $6,
-                total_volume_usd = COALESCE($7, total_volume_usd),
-                updated_at = $8
-            WHERE id = $9
-            RETURNING *
-            "#,
-        )
-        .bind(total_transactions)
-        .bind(successful_transactions)
-        .bind(failed_transactions)
-        .bind(avg_settlement_time_ms.unwrap_or(0))
-        .bind(metrics.reliability_score)
-        .bind(metrics.status.as_str())
-        .bind(volume_usd.unwrap_or(0.0))
-        .bind(Utc::now())
-        .bind(anchor_id.to_string())
-        .fetch_one(&self.pool)
-        .await?;
+        let anchor = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, Anchor>(
+                r#"
+                UPDATE anchors
+                SET total_transactions = $1,
+                    successful_transactions = $2,
+                    failed_transactions = $3,
+                    avg_settlement_time_ms = $4,
+                    reliability_score = $5,
+                    status = $6,
+                    total_volume_usd = COALESCE($7, total_volume_usd),
+                    updated_at = $8
+                WHERE id = $9
+                RETURNING *
+                "#,
+            )
+            .bind(total_transactions)
+            .bind(successful_transactions)
+            .bind(failed_transactions)
+            .bind(avg_settlement_time_ms.unwrap_or(0))
+            .bind(metrics.reliability_score)
+            .bind(metrics.status.as_str())
+            .bind(volume_usd.unwrap_or(0.0))
+            .bind(Utc::now())
+            .bind(anchor_id.to_string())
+            .fetch_one(pool)
+            .await
+        })?;
 
         // Record metrics history
         self.record_anchor_metrics_history(AnchorMetricsParams {
@@ -536,22 +685,24 @@ impl Database {
         asset_issuer: String,
     ) -> Result<Asset> {
         let id = Uuid::new_v4().to_string();
-        let asset = sqlx::query_as::<_, Asset>(
-            r#"
-            INSERT INTO assets (id, anchor_id, asset_code, asset_issuer)
-            VALUES ($1, $2, $3, $4)
-            ON CONFLICT (asset_code, asset_issuer) DO UPDATE
-            SET anchor_id = EXCLUDED.anchor_id,
-                updated_at = CURRENT_TIMESTAMP
-            RETURNING *
-            "#,
-        )
-        .bind(id)
-        .bind(anchor_id.to_string())
-        .bind(&asset_code)
-        .bind(&asset_issuer)
-        .fetch_one(&self.pool)
-        .await?;
+        let asset = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, Asset>(
+                r#"
+                INSERT INTO assets (id, anchor_id, asset_code, asset_issuer)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (asset_code, asset_issuer) DO UPDATE
+                SET anchor_id = EXCLUDED.anchor_id,
+                    updated_at = CURRENT_TIMESTAMP
+                RETURNING *
+                "#,
+            )
+            .bind(id)
+            .bind(anchor_id.to_string())
+            .bind(&asset_code)
+            .bind(&asset_issuer)
+            .fetch_one(pool)
+            .await
+        })?;
 
         Ok(asset)
     }
@@ -576,15 +727,17 @@ impl Database {
     /// }
     /// ```
     pub async fn get_assets_by_anchor(&self, anchor_id: Uuid) -> Result<Vec<Asset>> {
-        let assets = sqlx::query_as::<_, Asset>(
-            r#"
-            SELECT * FROM assets WHERE anchor_id = $1
-            ORDER BY asset_code ASC
-            "#,
-        )
-        .bind(anchor_id.to_string())
-        .fetch_all(&self.pool)
-        .await?;
+        let assets = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, Asset>(
+                r#"
+                SELECT * FROM assets WHERE anchor_id = $1
+                ORDER BY asset_code ASC
+                "#,
+            )
+            .bind(anchor_id.to_string())
+            .fetch_all(pool)
+            .await
+        })?;
 
         Ok(assets)
     }
@@ -630,7 +783,7 @@ impl Database {
         let placeholders = anchor_id_strs
             .iter()
             .enumerate()
-            .map(|(i, _)| format!("?{}", i + 1))
+            .map(|(i, _)| format!("${}", i + 1))
             .collect::<Vec<_>>()
             .join(", ");
 
@@ -639,12 +792,13 @@ impl Database {
             placeholders
         );
 
-        let mut query = sqlx::query_as::<_, Asset>(&query_str);
-        for id in &anchor_id_strs {
-            query = query.bind(id);
-        }
-
-        let assets = query.fetch_all(&self.pool).await?;
+        let assets = with_pool!(&self.pool, |pool| {
+            let mut query = sqlx::query_as::<_, Asset>(&query_str);
+            for id in &anchor_id_strs {
+                query = query.bind(id);
+            }
+            query.fetch_all(pool).await
+        })?;
 
         let mut result: std::collections::HashMap<String, Vec<Asset>> =
             std::collections::HashMap::new();
@@ -659,57 +813,222 @@ impl Database {
     }
 
     pub async fn count_assets_by_anchor(&self, anchor_id: Uuid) -> Result<i64> {
-        let count: (i64,) = sqlx::query_as(
-            r#"
-            SELECT COUNT(*) FROM assets WHERE anchor_id = $1
-            "#,
-        )
-        .bind(anchor_id.to_string())
-        .fetch_one(&self.pool)
-        .await?;
+        let count: (i64,) = with_pool!(&self.pool, |pool| {
+            sqlx::query_as(
+                r#"
+                SELECT COUNT(*) FROM assets WHERE anchor_id = $1
+                "#,
+            )
+            .bind(anchor_id.to_string())
+            .fetch_one(pool)
+            .await
+        })?;
 
         Ok(count.0)
     }
 
     // Update anchor metrics from RPC ingestion
     pub async fn update_anchor_from_rpc(&self, params: AnchorRpcUpdate) -> Result<()> {
-        sqlx::query(
-            r#"
-            UPDATE anchors
-            SET total_transactions = $1,
-                successful_transactions = $2,
-                failed_transactions = $3,
-                total_volume_usd = $4,
-                avg_settlement_time_ms = $5,
-                reliability_score = $6,
-                status = $7,
-                updated_at = $8
-            WHERE stellar_account = $9
-            "#,
-        )
-        .bind(params.total_transactions)
-        .bind(params.successful_transactions)
-        .bind(params.failed_transactions)
-        .bind(params.total_volume_usd)
-        .bind(params.avg_settlement_time_ms)
-        .bind(params.reliability_score)
-        .bind(&params.status)
-        .bind(Utc::now())
-        .bind(&params.stellar_account)
-        .execute(&self.pool)
-        .await?;
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                UPDATE anchors
+                SET total_transactions = $1,
+                    successful_transactions = $2,
+                    failed_transactions = $3,
+                    total_volume_usd = $4,
+                    avg_settlement_time_ms = COALESCE($5, avg_settlement_time_ms),
+                    reliability_score = $6,
+                    status = $7,
+                    updated_at = $8
+                WHERE stellar_account = $9
+                "#,
+            )
+            .bind(params.total_transactions)
+            .bind(params.successful_transactions)
+            .bind(params.failed_transactions)
+            .bind(params.total_volume_usd)
+            .bind(params.avg_settlement_time_ms)
+            .bind(params.reliability_score)
+            .bind(&params.status)
+            .bind(Utc::now())
+            .bind(&params.stellar_account)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
+
+        Ok(())
+    }
+
+    pub async fn get_anchor_payment_sync(
+        &self,
+        stellar_account: &str,
+    ) -> Result<Option<AnchorPaymentSync>> {
+        let sync = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, AnchorPaymentSync>(
+                r#"
+                SELECT stellar_account, cursor, total_payments, successful_payments, failed_payments,
+                       total_volume, settlement_samples, settlement_time_total_ms
+                FROM anchor_payment_sync
+                WHERE stellar_account = $1
+                "#,
+            )
+            .bind(stellar_account)
+            .fetch_optional(pool)
+            .await
+        })?;
+
+        Ok(sync)
+    }
+
+    pub async fn save_anchor_payment_sync(&self, sync: &AnchorPaymentSync) -> Result<()> {
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO anchor_payment_sync (
+                    stellar_account, cursor, total_payments, successful_payments, failed_payments,
+                    total_volume, settlement_samples, settlement_time_total_ms, updated_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                ON CONFLICT(stellar_account) DO UPDATE SET
+                    cursor = excluded.cursor,
+                    total_payments = excluded.total_payments,
+                    successful_payments = excluded.successful_payments,
+                    failed_payments = excluded.failed_payments,
+                    total_volume = excluded.total_volume,
+                    settlement_samples = excluded.settlement_samples,
+                    settlement_time_total_ms = excluded.settlement_time_total_ms,
+                    updated_at = excluded.updated_at
+                "#,
+            )
+            .bind(&sync.stellar_account)
+            .bind(&sync.cursor)
+            .bind(sync.stats.total_payments)
+            .bind(sync.stats.successful_payments)
+            .bind(sync.stats.failed_payments)
+            .bind(sync.stats.total_volume)
+            .bind(sync.stats.settlement_samples)
+            .bind(sync.stats.settlement_time_total_ms)
+            .bind(Utc::now())
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
+
+        Ok(())
+    }
+
+    /// Store SEP-24/SEP-31 transfer timestamps reported by the anchor with
+    /// `anchor_account`. A transfer already stored for another anchor is left as is.
+    pub async fn record_sep_transfers(
+        &self,
+        anchor_account: &str,
+        transfers: &[SepTransferTimes],
+    ) -> Result<()> {
+        for transfer in transfers {
+            with_pool!(&self.pool, |pool| {
+                sqlx::query(
+                    r#"
+                    INSERT INTO anchor_sep_transactions (
+                        stellar_transaction_id, protocol, transfer_id, status, started_at, completed_at, updated_at, anchor_account
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                    ON CONFLICT(stellar_transaction_id) DO UPDATE SET
+                        status = excluded.status,
+                        started_at = COALESCE(excluded.started_at, anchor_sep_transactions.started_at),
+                        completed_at = COALESCE(excluded.completed_at, anchor_sep_transactions.completed_at),
+                        updated_at = excluded.updated_at
+                    WHERE anchor_sep_transactions.anchor_account = excluded.anchor_account
+                    "#,
+                )
+                .bind(&transfer.stellar_transaction_id)
+                .bind(&transfer.protocol)
+                .bind(&transfer.transfer_id)
+                .bind(&transfer.status)
+                .bind(transfer.started_at)
+                .bind(transfer.completed_at)
+                .bind(Utc::now())
+                .bind(anchor_account)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+            })?;
+        }
 
         Ok(())
     }
 
+    /// Look up SEP transfer timestamps the anchor with `anchor_account` reported
+    /// for a set of transaction hashes
+    pub async fn get_sep_transfers(
+        &self,
+        anchor_account: &str,
+        transaction_hashes: &[String],
+    ) -> Result<std::collections::HashMap<String, SepTransferTimes>> {
+        if transaction_hashes.is_empty() {
+            return Ok(std::collections::HashMap::new());
+        }
+
+        let rows: Vec<(
+            String,
+            String,
+            String,
+            Option<String>,
+            Option<DateTime<Utc>>,
+            Option<DateTime<Utc>>,
+        )> = with_pool!(&self.pool, |pool| {
+            let mut query = sqlx::QueryBuilder::new(
+                "SELECT stellar_transaction_id, protocol, transfer_id, status, started_at, completed_at \
+                 FROM anchor_sep_transactions WHERE anchor_account = ",
+            );
+            query.push_bind(anchor_account);
+            query.push(" AND stellar_transaction_id IN (");
+            let mut separated = query.separated(", ");
+            for hash in transaction_hashes {
+                separated.push_bind(hash);
+            }
+            separated.push_unseparated(")");
+
+            query.build_query_as().fetch_all(pool).await
+        })?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(
+                    stellar_transaction_id,
+                    protocol,
+                    transfer_id,
+                    status,
+                    started_at,
+                    completed_at,
+                )| {
+                    (
+                        stellar_transaction_id.clone(),
+                        SepTransferTimes {
+                            stellar_transaction_id,
+                            protocol,
+                            transfer_id,
+                            status,
+                            started_at,
+                            completed_at,
+                        },
+                    )
+                },
+            )
+            .collect())
+    }
+
     // Metrics history operations
     pub async fn record_anchor_metrics_history(
         &self,
         params: AnchorMetricsParams,
     ) -> Result<AnchorMetricsHistory> {
         let id = Uuid::new_v4().to_string();
-        let history = sqlx::query_as::<_, AnchorMetricsHistory>(
-            r#"
+        let history = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, AnchorMetricsHistory>(
+                r#"
             INSERT INTO anchor_metrics_history (
                 id, anchor_id, timestamp, success_rate, failure_rate, reliability_score,
                 total_transactions, successful_transactions, failed_transactions,
@@ -718,20 +1037,21 @@ impl Database {
             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
             RETURNING *
             "#,
-        )
-        .bind(id)
-        .bind(params.anchor_id.to_string())
-        .bind(Utc::now())
-        .bind(params.success_rate)
-        .bind(params.failure_rate)
-        .bind(params.reliability_score)
-        .bind(params.total_transactions)
-        .bind(params.successful_transactions)
-        .bind(params.failed_transactions)
-        .bind(params.avg_settlement_time_ms.unwrap_or(0))
-        .bind(params.volume_usd.unwrap_or(0.0))
-        .fetch_one(&self.pool)
-        .await?;
+            )
+            .bind(id)
+            .bind(params.anchor_id.to_string())
+            .bind(Utc::now())
+            .bind(params.success_rate)
+            .bind(params.failure_rate)
+            .bind(params.reliability_score)
+            .bind(params.total_transactions)
+            .bind(params.successful_transactions)
+            .bind(params.failed_transactions)
+            .bind(params.avg_settlement_time_ms.unwrap_or(0))
+            .bind(params.volume_usd.unwrap_or(0.0))
+            .fetch_one(pool)
+            .await
+        })?;
 
         Ok(history)
     }
@@ -741,18 +1061,20 @@ impl Database {
         anchor_id: Uuid,
         limit: i64,
     ) -> Result<Vec<AnchorMetricsHistory>> {
-        let history = sqlx::query_as::<_, AnchorMetricsHistory>(
-            r#"
+        let history = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, AnchorMetricsHistory>(
+                r#"
             SELECT * FROM anchor_metrics_history
             WHERE anchor_id = $1
             ORDER BY timestamp DESC
             LIMIT $2
             "#,
-        )
-        .bind(anchor_id.to_string())
-        .bind(limit)
-        .fetch_all(&self.pool)
-        .await?;
+            )
+            .bind(anchor_id.to_string())
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+        })?;
 
         Ok(history)
     }
@@ -786,24 +1108,27 @@ impl Database {
         );
 
         // Ensure the corridor exists in the database
-        sqlx::query(
-            r#"
-            INSERT INTO corridors (
-                id, source_asset_code, source_asset_issuer,
-                destination_asset_code, destination_asset_issuer
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO corridors (
+                    id, source_asset_code, source_asset_issuer,
+                    destination_asset_code, destination_asset_issuer
+                )
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (source_asset_code, source_asset_issuer, destination_asset_code, destination_asset_issuer)
+                DO UPDATE SET updated_at = CURRENT_TIMESTAMP
+                "#,
             )
-            VALUES ($1, $2, $3, $4, $5)
-            ON CONFLICT (source_asset_code, source_asset_issuer, destination_asset_code, destination_asset_issuer)
-            DO UPDATE SET updated_at = CURRENT_TIMESTAMP
-            "#,
-        )
-        .bind(Uuid::new_v4().to_string())
-        .bind(&corridor.asset_a_code)
-        .bind(&corridor.asset_a_issuer)
-        .bind(&corridor.asset_b_code)
-        .bind(&corridor.asset_b_issuer)
-        .execute(&self.pool)
-        .await?;
+            .bind(Uuid::new_v4().to_string())
+            .bind(&corridor.asset_a_code)
+            .bind(&corridor.asset_a_issuer)
+            .bind(&corridor.asset_b_code)
+            .bind(&corridor.asset_b_issuer)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
 
         Ok(corridor)
     }
@@ -814,15 +1139,17 @@ impl Database {
         offset: i64,
     ) -> Result<Vec<crate::models::corridor::Corridor>> {
         let start = Instant::now();
-        let records = sqlx::query_as::<_, CorridorRecord>(
-            r#"
-            SELECT * FROM corridors ORDER BY reliability_score DESC LIMIT $1 OFFSET $2
-            "#,
-        )
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&self.pool)
-        .await?;
+        let records = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, CorridorRecord>(
+                r#"
+                SELECT * FROM corridors ORDER BY reliability_score DESC LIMIT $1 OFFSET $2
+                "#,
+            )
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(pool)
+            .await
+        })?;
 
         let corridors = records
             .into_iter()
@@ -847,14 +1174,16 @@ impl Database {
         &self,
         id: Uuid,
     ) -> Result<Option<crate::models::corridor::Corridor>> {
-        let record = sqlx::query_as::<_, CorridorRecord>(
-            r#"
-            SELECT * FROM corridors WHERE id = $1
-            "#,
-        )
-        .bind(id.to_string())
-        .fetch_optional(&self.pool)
-        .await?;
+        let record = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, CorridorRecord>(
+                r#"
+                SELECT * FROM corridors WHERE id = $1
+                "#,
+            )
+            .bind(id.to_string())
+            .fetch_optional(pool)
+            .await
+        })?;
 
         Ok(record.map(|r| {
             crate::models::corridor::Corridor::new(
@@ -871,19 +1200,21 @@ impl Database {
         id: Uuid,
         metrics: crate::models::corridor::CorridorMetrics,
     ) -> Result<crate::models::corridor::Corridor> {
-        let record = sqlx::query_as::<_, CorridorRecord>(
-            r#"
-            UPDATE corridors
-            SET reliability_score = $1,
-                updated_at = CURRENT_TIMESTAMP
-            WHERE id = $2
-            RETURNING *
-            "#,
-        )
-        .bind(metrics.success_rate)
-        .bind(id.to_string())
-        .fetch_one(&self.pool)
-        .await?;
+        let record = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, CorridorRecord>(
+                r#"
+                UPDATE corridors
+                SET reliability_score = $1,
+                    updated_at = CURRENT_TIMESTAMP
+                WHERE id = $2
+                RETURNING *
+                "#,
+            )
+            .bind(metrics.success_rate)
+            .bind(id.to_string())
+            .fetch_one(pool)
+            .await
+        })?;
 
         Ok(crate::models::corridor::Corridor::new(
             record.source_asset_code,
@@ -902,21 +1233,23 @@ impl Database {
         entity_type: Option<String>,
     ) -> Result<MetricRecord> {
         let id = Uuid::new_v4().to_string();
-        let metric = sqlx::query_as::<_, MetricRecord>(
-            r#"
-            INSERT INTO metrics (id, name, value, entity_id, entity_type, timestamp)
-            VALUES ($1, $2, $3, $4, $5, $6)
-            RETURNING *
-            "#,
-        )
-        .bind(id)
-        .bind(name)
-        .bind(value)
-        .bind(entity_id)
-        .bind(entity_type)
-        .bind(Utc::now())
-        .fetch_one(&self.pool)
-        .await?;
+        let metric = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, MetricRecord>(
+                r#"
+                INSERT INTO metrics (id, name, value, entity_id, entity_type, timestamp)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                RETURNING *
+                "#,
+            )
+            .bind(id)
+            .bind(name)
+            .bind(value)
+            .bind(entity_id)
+            .bind(entity_type)
+            .bind(Utc::now())
+            .fetch_one(pool)
+            .await
+        })?;
 
         Ok(metric)
     }
@@ -931,85 +1264,96 @@ impl Database {
         epoch: Option<i64>,
     ) -> Result<SnapshotRecord> {
         let id = Uuid::new_v4().to_string();
-        let snapshot = sqlx::query_as::<_, SnapshotRecord>(
-            r#"
-            INSERT INTO snapshots (id, entity_id, entity_type, data, hash, epoch, timestamp)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
-            RETURNING *
-            "#,
-        )
-        .bind(id)
-        .bind(entity_id)
-        .bind(entity_type)
-        .bind(data.to_string())
-        .bind(hash)
-        .bind(epoch)
-        .bind(Utc::now())
-        .fetch_one(&self.pool)
-        .await?;
+        let snapshot = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, SnapshotRecord>(
+                r#"
+                INSERT INTO snapshots (id, entity_id, entity_type, data, hash, epoch, timestamp)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                RETURNING *
+                "#,
+            )
+            .bind(id)
+            .bind(entity_id)
+            .bind(entity_type)
+            .bind(data.to_string())
+            .bind(hash)
+            .bind(epoch)
+            .bind(Utc::now())
+            .fetch_one(pool)
+            .await
+        })?;
 
         Ok(snapshot)
     }
 
     pub async fn get_snapshot_by_epoch(&self, epoch: i64) -> Result<Option<SnapshotRecord>> {
-        let snapshot = sqlx::query_as::<_, SnapshotRecord>(
-            r#"
-            SELECT * FROM snapshots WHERE epoch = $1 LIMIT 1
-            "#,
-        )
-        .bind(epoch)
-        .fetch_optional(&self.pool)
-        .await?;
+        let snapshot = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, SnapshotRecord>(
+                r#"
+                SELECT * FROM snapshots WHERE epoch = $1 LIMIT 1
+                "#,
+            )
+            .bind(epoch)
+            .fetch_optional(pool)
+            .await
+        })?;
 
         Ok(snapshot)
     }
 
     pub async fn list_snapshots(&self, limit: i64, offset: i64) -> Result<Vec<SnapshotRecord>> {
-        let snapshots = sqlx::query_as::<_, SnapshotRecord>(
-            r#"
-            SELECT * FROM snapshots
-            WHERE epoch IS NOT NULL
-            ORDER BY epoch DESC
-            LIMIT $1 OFFSET $2
-            "#,
-        )
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&self.pool)
-        .await?;
+        let snapshots = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, SnapshotRecord>(
+                r#"
+                SELECT * FROM snapshots
+                WHERE epoch IS NOT NULL
+                ORDER BY epoch DESC
+                LIMIT $1 OFFSET $2
+                "#,
+            )
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(pool)
+            .await
+        })?;
 
         Ok(snapshots)
     }
 
     // Ingestion methods
     pub async fn get_ingestion_cursor(&self, task_name: &str) -> Result<Option<String>> {
-        let state = sqlx::query_as::<_, crate::models::IngestionState>(
-            r#"
-            SELECT * FROM ingestion_state WHERE task_name = $1
-            "#,
-        )
-        .bind(task_name)
-        .fetch_optional(&self.pool)
-        .await?;
+        let state = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, crate::models::IngestionState>(
+                r#"
+                SELECT * FROM ingestion_state WHERE task_name = $1
+                "#,
+            )
+            .bind(task_name)
+            .fetch_optional(pool)
+            .await
+        })?;
 
         Ok(state.map(|s| s.last_cursor))
     }
 
     pub async fn update_ingestion_cursor(&self, task_name: &str, last_cursor: &str) -> Result<()> {
-        sqlx::query(
-            r#"
-            INSERT INTO ingestion_state (task_name, last_cursor, updated_at)
-            VALUES ($1, $2, $3)
-            ON CONFLICT (task_name) DO UPDATE SET
-                last_cursor = EXCLUDED.last_cursor,
-                updated_at = EXCLUDED.updated_at
-            "#,
-        )
-        .bind(task_name)
-        .bind(last_cursor)
-        .bind(Utc::now())
-        .execute(&self.pool)
-        .await?;
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO ingestion_state (task_name, last_cursor, updated_at)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (task_name) DO UPDATE SET
+                    last_cursor = EXCLUDED.last_cursor,
+                    updated_at = EXCLUDED.updated_at
+                "#,
+            )
+            .bind(task_name)
+            .bind(last_cursor)
+            .bind(Utc::now())
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
 
         Ok(())
     }
@@ -1017,27 +1361,30 @@ impl Database {
     pub async fn save_payments(&self, payments: Vec<crate::models::PaymentRecord>) -> Result<()> {
         let start = Instant::now();
         for payment in payments {
-            sqlx::query(
-                r#"
-                INSERT INTO payments (
-                    id, transaction_hash, source_account, destination_account,
-                    asset_type, asset_code, asset_issuer, amount, created_at
+            with_pool!(&self.pool, |pool| {
+                sqlx::query(
+                    r#"
+                    INSERT INTO payments (
+                        id, transaction_hash, source_account, destination_account,
+                        asset_type, asset_code, asset_issuer, amount, created_at
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                    ON CONFLICT (id) DO NOTHING
+                    "#,
                 )
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-                ON CONFLICT (id) DO NOTHING
-                "#,
-            )
-            .bind(&payment.id)
-            .bind(&payment.transaction_hash)
-            .bind(&payment.source_account)
-            .bind(&payment.destination_account)
-            .bind(&payment.asset_type)
-            .bind(&payment.asset_code)
-            .bind(&payment.asset_issuer)
-            .bind(payment.amount)
-            .bind(payment.created_at)
-            .execute(&self.pool)
-            .await?;
+                .bind(&payment.id)
+                .bind(&payment.transaction_hash)
+                .bind(&payment.source_account)
+                .bind(&payment.destination_account)
+                .bind(&payment.asset_type)
+                .bind(&payment.asset_code)
+                .bind(&payment.asset_issuer)
+                .bind(payment.amount)
+                .bind(payment.created_at)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+            })?;
         }
         crate::observability::metrics::observe_db_query(
             "save_payments",
@@ -1052,6 +1399,26 @@ impl Database {
         crate::db::aggregation::AggregationDb::new(self.pool.clone())
     }
 
+    pub fn rollup_db(&self) -> crate::db::rollups::RollupDb {
+        crate::db::rollups::RollupDb::new(self.pool.clone())
+    }
+
+    pub fn anomaly_db(&self) -> crate::db::anomalies::AnomalyDb {
+        crate::db::anomalies::AnomalyDb::new(self.pool.clone())
+    }
+
+    pub fn ml_db(&self) -> crate::db::ml::MlDb {
+        crate::db::ml::MlDb::new(self.pool.clone())
+    }
+
+    pub fn dex_db(&self) -> crate::db::dex::DexDb {
+        crate::db::dex::DexDb::new(self.pool.clone())
+    }
+
+    pub fn contract_event_db(&self) -> crate::db::contract_events::ContractEventDb {
+        crate::db::contract_events::ContractEventDb::new(self.pool.clone())
+    }
+
     pub async fn fetch_payments_by_timerange(
         &self,
         start_time: chrono::DateTime<chrono::Utc>,
@@ -1063,6 +1430,16 @@ impl Database {
             .await
     }
 
+    pub async fn fetch_payments_in_bucket(
+        &self,
+        start_time: chrono::DateTime<chrono::Utc>,
+        end_time: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<crate::models::corridor::PaymentRecord>> {
+        self.aggregation_db()
+            .fetch_payments_in_bucket(start_time, end_time)
+            .await
+    }
+
     pub async fn upsert_hourly_corridor_metric(
         &self,
         metric: &crate::services::aggregation::HourlyCorridorMetrics,
@@ -1121,16 +1498,18 @@ impl Database {
         use crate::muxed;
         const MUXED_LEN: i64 = 69;
 
-        let total_muxed_payments = sqlx::query_scalar::<_, i64>(
-            r#"
-            SELECT COUNT(*) FROM payments
-            WHERE (source_account LIKE 'M%' AND LENGTH(source_account) = ?1)
-               OR (destination_account LIKE 'M%' AND LENGTH(destination_account) = ?1)
-            "#,
-        )
-        .bind(MUXED_LEN)
-        .fetch_one(&self.pool)
-        .await?;
+        let total_muxed_payments = with_pool!(&self.pool, |pool| {
+            sqlx::query_scalar::<_, i64>(
+                r#"
+                SELECT COUNT(*) FROM payments
+                WHERE (source_account LIKE 'M%' AND LENGTH(source_account) = $1)
+                   OR (destination_account LIKE 'M%' AND LENGTH(destination_account) = $1)
+                "#,
+            )
+            .bind(MUXED_LEN)
+            .fetch_one(pool)
+            .await
+        })?;
 
         #[derive(sqlx::FromRow)]
         struct AddrCount {
@@ -1138,33 +1517,37 @@ impl Database {
             cnt: i64,
         }
 
-        let source_counts: Vec<AddrCount> = sqlx::query_as(
-            r#"
-            SELECT source_account AS addr, COUNT(*) AS cnt FROM payments
-            WHERE source_account LIKE 'M%' AND LENGTH(source_account) = ?1
-            GROUP BY source_account
-            ORDER BY cnt DESC
-            LIMIT ?2
-            "#,
-        )
-        .bind(MUXED_LEN)
-        .bind(top_limit)
-        .fetch_all(&self.pool)
-        .await?;
+        let source_counts: Vec<AddrCount> = with_pool!(&self.pool, |pool| {
+            sqlx::query_as(
+                r#"
+                SELECT source_account AS addr, COUNT(*) AS cnt FROM payments
+                WHERE source_account LIKE 'M%' AND LENGTH(source_account) = $1
+                GROUP BY source_account
+                ORDER BY cnt DESC
+                LIMIT $2
+                "#,
+            )
+            .bind(MUXED_LEN)
+            .bind(top_limit)
+            .fetch_all(pool)
+            .await
+        })?;
 
-        let dest_counts: Vec<AddrCount> = sqlx::query_as(
-            r#"
-            SELECT destination_account AS addr, COUNT(*) AS cnt FROM payments
-            WHERE destination_account LIKE 'M%' AND LENGTH(destination_account) = ?1
-            GROUP BY destination_account
-            ORDER BY cnt DESC
-            LIMIT ?2
-            "#,
-        )
-        .bind(MUXED_LEN)
-        .bind(top_limit)
-        .fetch_all(&self.pool)
-        .await?;
+        let dest_counts: Vec<AddrCount> = with_pool!(&self.pool, |pool| {
+            sqlx::query_as(
+                r#"
+                SELECT destination_account AS addr, COUNT(*) AS cnt FROM payments
+                WHERE destination_account LIKE 'M%' AND LENGTH(destination_account) = $1
+                GROUP BY destination_account
+                ORDER BY cnt DESC
+                LIMIT $2
+                "#,
+            )
+            .bind(MUXED_LEN)
+            .bind(top_limit)
+            .fetch_all(pool)
+            .await
+        })?;
 
         let mut by_addr: std::collections::HashMap<String, (i64, i64)> =
             std::collections::HashMap::new();
@@ -1193,18 +1576,20 @@ impl Database {
         top_muxed_by_activity.sort_by(|a, b| b.total_payments.cmp(&a.total_payments));
         top_muxed_by_activity.truncate(top_limit as usize);
 
-        let unique_muxed_addresses = sqlx::query_scalar::<_, i64>(
-            r#"
-            SELECT COUNT(DISTINCT addr) FROM (
-                SELECT source_account AS addr FROM payments WHERE source_account LIKE 'M%' AND LENGTH(source_account) = ?1
-                UNION
-                SELECT destination_account AS addr FROM payments WHERE destination_account LIKE 'M%' AND LENGTH(destination_account) = ?1
+        let unique_muxed_addresses = with_pool!(&self.pool, |pool| {
+            sqlx::query_scalar::<_, i64>(
+                r#"
+                SELECT COUNT(DISTINCT addr) FROM (
+                    SELECT source_account AS addr FROM payments WHERE source_account LIKE 'M%' AND LENGTH(source_account) = $1
+                    UNION
+                    SELECT destination_account AS addr FROM payments WHERE destination_account LIKE 'M%' AND LENGTH(destination_account) = $1
+                )
+                "#,
             )
-            "#,
-        )
-        .bind(MUXED_LEN)
-        .fetch_one(&self.pool)
-        .await?;
+            .bind(MUXED_LEN)
+            .fetch_one(pool)
+            .await
+        })?;
 
         let base_accounts_with_muxed: Vec<String> = top_muxed_by_activity
             .iter()
@@ -1237,20 +1622,25 @@ impl Database {
         let id = Uuid::new_v4().to_string();
         let status = "pending";
 
-        let pending_transaction = sqlx::query_as::<_, crate::models::PendingTransaction>(
-            r#"
-            INSERT INTO pending_transactions (id, source_account, xdr, required_signatures, status)
-            VALUES ($1, $2, $3, $4, $5)
-            RETURNING *
-            "#,
-        )
-        .bind(&id)
-        .bind(source_account)
-        .bind(xdr)
-        .bind(required_signatures)
-        .bind(status)
-        .fetch_one(&self.pool)
-        .await?;
+        let pending_transaction = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<
+                _,
+                crate::models::PendingTransaction,
+            >(
+                r#"
+                INSERT INTO pending_transactions (id, source_account, xdr, required_signatures, status)
+                VALUES ($1, $2, $3, $4, $5)
+                RETURNING *
+                "#,
+            )
+            .bind(&id)
+            .bind(source_account)
+            .bind(xdr)
+            .bind(required_signatures)
+            .bind(status)
+            .fetch_one(pool)
+            .await
+        })?;
 
         Ok(pending_transaction)
     }
@@ -1259,24 +1649,28 @@ impl Database {
         &self,
         id: &str,
     ) -> Result<Option<crate::models::PendingTransactionWithSignatures>> {
-        let pending_transaction = sqlx::query_as::<_, crate::models::PendingTransaction>(
-            r#"
-            SELECT * FROM pending_transactions WHERE id = $1
-            "#,
-        )
-        .bind(id)
-        .fetch_optional(&self.pool)
-        .await?;
-
-        if let Some(transaction) = pending_transaction {
-            let signatures = sqlx::query_as::<_, crate::models::Signature>(
+        let pending_transaction = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, crate::models::PendingTransaction>(
                 r#"
-                SELECT * FROM transaction_signatures WHERE transaction_id = $1
+                SELECT * FROM pending_transactions WHERE id = $1
                 "#,
             )
             .bind(id)
-            .fetch_all(&self.pool)
-            .await?;
+            .fetch_optional(pool)
+            .await
+        })?;
+
+        if let Some(transaction) = pending_transaction {
+            let signatures = with_pool!(&self.pool, |pool| {
+                sqlx::query_as::<_, crate::models::Signature>(
+                    r#"
+                    SELECT * FROM transaction_signatures WHERE transaction_id = $1
+                    "#,
+                )
+                .bind(id)
+                .fetch_all(pool)
+                .await
+            })?;
 
             Ok(Some(crate::models::PendingTransactionWithSignatures {
                 transaction,
@@ -1295,34 +1689,40 @@ impl Database {
     ) -> Result<()> {
         let id = Uuid::new_v4().to_string();
 
-        sqlx::query(
-            r#"
-            INSERT INTO transaction_signatures (id, transaction_id, signer, signature)
-            VALUES ($1, $2, $3, $4)
-            "#,
-        )
-        .bind(id)
-        .bind(transaction_id)
-        .bind(signer)
-        .bind(signature)
-        .execute(&self.pool)
-        .await?;
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO transaction_signatures (id, transaction_id, signer, signature)
+                VALUES ($1, $2, $3, $4)
+                "#,
+            )
+            .bind(id)
+            .bind(transaction_id)
+            .bind(signer)
+            .bind(signature)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
 
         Ok(())
     }
 
     pub async fn update_transaction_status(&self, id: &str, status: &str) -> Result<()> {
-        sqlx::query(
-            r#"
-            UPDATE pending_transactions
-            SET status = $1, updated_at = CURRENT_TIMESTAMP
-            WHERE id = $2
-            "#,
-        )
-        .bind(status)
-        .bind(id)
-        .execute(&self.pool)
-        .await?;
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                UPDATE pending_transactions
+                SET status = $1, updated_at = CURRENT_TIMESTAMP
+                WHERE id = $2
+                "#,
+            )
+            .bind(status)
+            .bind(id)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
 
         Ok(())
     }
@@ -1339,27 +1739,32 @@ impl Database {
         let scopes = req.scopes.unwrap_or_else(|| "read".to_string());
         let now = Utc::now().to_rfc3339();
 
-        sqlx::query(
-            r#"
-            INSERT INTO api_keys (id, name, key_prefix, key_hash, wallet_address, scopes, status, created_at, expires_at)
-            VALUES ($1, $2, $3, $4, $5, $6, 'active', $7, $8)
-            "#,
-        )
-        .bind(&id)
-        .bind(&req.name)
-        .bind(&prefix)
-        .bind(&key_hash)
-        .bind(wallet_address)
-        .bind(&scopes)
-        .bind(&now)
-        .bind(&req.expires_at)
-        .execute(&self.pool)
-        .await?;
-
-        let key = sqlx::query_as::<_, ApiKey>("SELECT * FROM api_keys WHERE id = $1")
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO api_keys (id, name, key_prefix, key_hash, wallet_address, scopes, status, created_at, expires_at)
+                VALUES ($1, $2, $3, $4, $5, $6, 'active', $7, $8)
+                "#,
+            )
             .bind(&id)
-            .fetch_one(&self.pool)
-            .await?;
+            .bind(&req.name)
+            .bind(&prefix)
+            .bind(&key_hash)
+            .bind(wallet_address)
+            .bind(&scopes)
+            .bind(&now)
+            .bind(&req.expires_at)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
+
+        let key = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, ApiKey>("SELECT * FROM api_keys WHERE id = $1")
+                .bind(&id)
+                .fetch_one(pool)
+                .await
+        })?;
 
         Ok(CreateApiKeyResponse {
             key: ApiKeyInfo::from(key),
@@ -1368,16 +1773,18 @@ impl Database {
     }
 
     pub async fn list_api_keys(&self, wallet_address: &str) -> Result<Vec<ApiKeyInfo>> {
-        let keys = sqlx::query_as::<_, ApiKey>(
-            r#"
-            SELECT * FROM api_keys
-            WHERE wallet_address = $1
-            ORDER BY created_at DESC
-            "#,
-        )
-        .bind(wallet_address)
-        .fetch_all(&self.pool)
-        .await?;
+        let keys = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, ApiKey>(
+                r#"
+                SELECT * FROM api_keys
+                WHERE wallet_address = $1
+                ORDER BY created_at DESC
+                "#,
+            )
+            .bind(wallet_address)
+            .fetch_all(pool)
+            .await
+        })?;
 
         Ok(keys.into_iter().map(ApiKeyInfo::from).collect())
     }
@@ -1387,13 +1794,15 @@ impl Database {
         id: &str,
         wallet_address: &str,
     ) -> Result<Option<ApiKeyInfo>> {
-        let key = sqlx::query_as::<_, ApiKey>(
-            "SELECT * FROM api_keys WHERE id = $1 AND wallet_address = $2",
-        )
-        .bind(id)
-        .bind(wallet_address)
-        .fetch_optional(&self.pool)
-        .await?;
+        let key = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, ApiKey>(
+                "SELECT * FROM api_keys WHERE id = $1 AND wallet_address = $2",
+            )
+            .bind(id)
+            .bind(wallet_address)
+            .fetch_optional(pool)
+            .await
+        })?;
 
         Ok(key.map(ApiKeyInfo::from))
     }
@@ -1401,12 +1810,14 @@ impl Database {
     pub async fn validate_api_key(&self, plain_key: &str) -> Result<Option<ApiKey>> {
         let key_hash = hash_api_key(plain_key);
 
-        let key = sqlx::query_as::<_, ApiKey>(
-            "SELECT * FROM api_keys WHERE key_hash = $1 AND status = 'active'",
-        )
-        .bind(&key_hash)
-        .fetch_optional(&self.pool)
-        .await?;
+        let key = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, ApiKey>(
+                "SELECT * FROM api_keys WHERE key_hash = $1 AND status = 'active'",
+            )
+            .bind(&key_hash)
+            .fetch_optional(pool)
+            .await
+        })?;
 
         if let Some(ref k) = key {
             if let Some(ref expires_at) = k.expires_at {
@@ -1417,31 +1828,37 @@ impl Database {
                 }
             }
 
-            sqlx::query("UPDATE api_keys SET last_used_at = $1 WHERE id = $2")
-                .bind(Utc::now().to_rfc3339())
-                .bind(&k.id)
-                .execute(&self.pool)
-                .await?;
+            with_pool!(&self.pool, |pool| {
+                sqlx::query("UPDATE api_keys SET last_used_at = $1 WHERE id = $2")
+                    .bind(Utc::now().to_rfc3339())
+                    .bind(&k.id)
+                    .execute(pool)
+                    .await
+                    .map(|done| done.rows_affected())
+            })?;
         }
 
         Ok(key)
     }
 
     pub async fn revoke_api_key(&self, id: &str, wallet_address: &str) -> Result<bool> {
-        let result = sqlx::query(
-            r#"
-            UPDATE api_keys
-            SET status = 'revoked', revoked_at = $1
-            WHERE id = $2 AND wallet_address = $3 AND status = 'active'
-            "#,
-        )
-        .bind(Utc::now().to_rfc3339())
-        .bind(id)
-        .bind(wallet_address)
-        .execute(&self.pool)
-        .await?;
+        let result = with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                UPDATE api_keys
+                SET status = 'revoked', revoked_at = $1
+                WHERE id = $2 AND wallet_address = $3 AND status = 'active'
+                "#,
+            )
+            .bind(Utc::now().to_rfc3339())
+            .bind(id)
+            .bind(wallet_address)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
 
-        Ok(result.rows_affected() > 0)
+        Ok(result > 0)
     }
 
     pub async fn rotate_api_key(
@@ -1449,13 +1866,15 @@ impl Database {
         id: &str,
         wallet_address: &str,
     ) -> Result<Option<CreateApiKeyResponse>> {
-        let old_key = sqlx::query_as::<_, ApiKey>(
-            "SELECT * FROM api_keys WHERE id = $1 AND wallet_address = $2 AND status = 'active'",
-        )
-        .bind(id)
-        .bind(wallet_address)
-        .fetch_optional(&self.pool)
-        .await?;
+        let old_key = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, ApiKey>(
+                "SELECT * FROM api_keys WHERE id = $1 AND wallet_address = $2 AND status = 'active'",
+            )
+            .bind(id)
+            .bind(wallet_address)
+            .fetch_optional(pool)
+            .await
+        })?;
 
         let old_key = match old_key {
             Some(k) => k,
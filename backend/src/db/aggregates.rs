@@ -1,61 +1,19 @@
+use crate::database::DbPool;
+use crate::with_pool;
 use anyhow::Result;
 use chrono::NaiveDate;
-use sqlx::SqlitePool;
 
-use crate::models::corridor::{Corridor, CorridorAnalytics, CorridorMetrics};
+use crate::models::corridor::{Corridor, CorridorMetrics};
 
 pub struct CorridorAggregates {
-    pool: SqlitePool,
+    pool: DbPool,
 }
 
 impl CorridorAggregates {
-    pub fn new(pool: SqlitePool) -> Self {
+    pub fn new(pool: DbPool) -> Self {
         Self { pool }
     }
 
-    pub async fn store_daily_corridor_metrics(
-        &self,
-        analytics: &CorridorAnalytics,
-        date: NaiveDate,
-    ) -> Result<CorridorMetrics> {
-        let date_datetime = date.and_hms_opt(0, 0, 0).unwrap().and_utc();
-        let corridor_key = analytics.corridor.to_string_key();
-
-        let metrics = sqlx::query_as::<_, CorridorMetrics>(
-            r#"
-            INSERT INTO corridor_metrics (
-                corridor_key, asset_a_code, asset_a_issuer, asset_b_code, asset_b_issuer,
-                date, total_transactions, successful_transactions, failed_transactions,
-                success_rate, volume_usd
-            )
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            ON CONFLICT (corridor_key, date) DO UPDATE SET
-                total_transactions = EXCLUDED.total_transactions,
-                successful_transactions = EXCLUDED.successful_transactions,
-                failed_transactions = EXCLUDED.failed_transactions,
-                success_rate = EXCLUDED.success_rate,
-                volume_usd = EXCLUDED.volume_usd,
-                updated_at = CURRENT_TIMESTAMP
-            RETURNING *
-            "#,
-        )
-        .bind(&corridor_key)
-        .bind(&analytics.corridor.asset_a_code)
-        .bind(&analytics.corridor.asset_a_issuer)
-        .bind(&analytics.corridor.asset_b_code)
-        .bind(&analytics.corridor.asset_b_issuer)
-        .bind(date_datetime)
-        .bind(analytics.total_transactions)
-        .bind(analytics.successful_transactions)
-        .bind(analytics.failed_transactions)
-        .bind(analytics.success_rate)
-        .bind(analytics.volume_usd)
-        .fetch_one(&self.pool)
-        .await?;
-
-        Ok(metrics)
-    }
-
     pub async fn get_corridor_metrics(
         &self,
         corridor: &Corridor,
@@ -66,18 +24,20 @@ impl CorridorAggregates {
         let start_datetime = start_date.and_hms_opt(0, 0, 0).unwrap().and_utc();
         let end_datetime = end_date.and_hms_opt(23, 59, 59).unwrap().and_utc();
 
-        let metrics = sqlx::query_as::<_, CorridorMetrics>(
-            r#"
-            SELECT * FROM corridor_metrics
-            WHERE corridor_key = ? AND date >= ? AND date <= ?
-            ORDER BY date DESC
-            "#,
-        )
-        .bind(&corridor_key)
-        .bind(start_datetime)
-        .bind(end_datetime)
-        .fetch_all(&self.pool)
-        .await?;
+        let metrics = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, CorridorMetrics>(
+                r#"
+                SELECT * FROM corridor_metrics
+                WHERE corridor_key = $1 AND date >= $2 AND date <= $3
+                ORDER BY date DESC
+                "#,
+            )
+            .bind(&corridor_key)
+            .bind(start_datetime)
+            .bind(end_datetime)
+            .fetch_all(pool)
+            .await
+        })?;
 
         Ok(metrics)
     }
@@ -89,17 +49,19 @@ impl CorridorAggregates {
         let date_datetime = date.and_hms_opt(0, 0, 0).unwrap().and_utc();
         let next_day = date_datetime + chrono::Duration::days(1);
 
-        let metrics = sqlx::query_as::<_, CorridorMetrics>(
-            r#"
-            SELECT * FROM corridor_metrics
-            WHERE date >= ? AND date < ?
-            ORDER BY volume_usd DESC
-            "#,
-        )
-        .bind(date_datetime)
-        .bind(next_day)
-        .fetch_all(&self.pool)
-        .await?;
+        let metrics = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, CorridorMetrics>(
+                r#"
+                SELECT * FROM corridor_metrics
+                WHERE date >= $1 AND date < $2
+                ORDER BY volume_usd DESC
+                "#,
+            )
+            .bind(date_datetime)
+            .bind(next_day)
+            .fetch_all(pool)
+            .await
+        })?;
 
         Ok(metrics)
     }
@@ -112,30 +74,32 @@ impl CorridorAggregates {
         let start_datetime = start_date.and_hms_opt(0, 0, 0).unwrap().and_utc();
         let end_datetime = end_date.and_hms_opt(23, 59, 59).unwrap().and_utc();
 
-        let metrics = sqlx::query_as::<_, AggregatedCorridorMetrics>(
-            r#"
-            SELECT
-                corridor_key,
-                asset_a_code,
-                asset_a_issuer,
-                asset_b_code,
-                asset_b_issuer,
-                SUM(total_transactions) as total_transactions,
-                SUM(successful_transactions) as successful_transactions,
-                SUM(failed_transactions) as failed_transactions,
-                AVG(success_rate) as avg_success_rate,
-                SUM(volume_usd) as total_volume_usd,
-                MAX(date) as latest_date
-            FROM corridor_metrics
-            WHERE date >= ? AND date <= ?
-            GROUP BY corridor_key, asset_a_code, asset_a_issuer, asset_b_code, asset_b_issuer
-            ORDER BY total_volume_usd DESC
-            "#,
-        )
-        .bind(start_datetime)
-        .bind(end_datetime)
-        .fetch_all(&self.pool)
-        .await?;
+        let metrics = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, AggregatedCorridorMetrics>(
+                r#"
+                SELECT
+                    corridor_key,
+                    asset_a_code,
+                    asset_a_issuer,
+                    asset_b_code,
+                    asset_b_issuer,
+                    CAST(SUM(total_transactions) AS BIGINT) as total_transactions,
+                    CAST(SUM(successful_transactions) AS BIGINT) as successful_transactions,
+                    CAST(SUM(failed_transactions) AS BIGINT) as failed_transactions,
+                    AVG(success_rate) as avg_success_rate,
+                    SUM(volume_usd) as total_volume_usd,
+                    MAX(date) as latest_date
+                FROM corridor_metrics
+                WHERE date >= $1 AND date <= $2
+                GROUP BY corridor_key, asset_a_code, asset_a_issuer, asset_b_code, asset_b_issuer
+                ORDER BY total_volume_usd DESC
+                "#,
+            )
+            .bind(start_datetime)
+            .bind(end_datetime)
+            .fetch_all(pool)
+            .await
+        })?;
 
         Ok(metrics)
     }
@@ -148,19 +112,21 @@ impl CorridorAggregates {
         let date_datetime = date.and_hms_opt(0, 0, 0).unwrap().and_utc();
         let next_day = date_datetime + chrono::Duration::days(1);
 
-        let metrics = sqlx::query_as::<_, CorridorMetrics>(
-            r#"
-            SELECT * FROM corridor_metrics
-            WHERE date >= ? AND date < ?
-            ORDER BY volume_usd DESC
-            LIMIT ?
-            "#,
-        )
-        .bind(date_datetime)
-        .bind(next_day)
-        .bind(limit)
-        .fetch_all(&self.pool)
-        .await?;
+        let metrics = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, CorridorMetrics>(
+                r#"
+                SELECT * FROM corridor_metrics
+                WHERE date >= $1 AND date < $2
+                ORDER BY volume_usd DESC
+                LIMIT $3
+                "#,
+            )
+            .bind(date_datetime)
+            .bind(next_day)
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+        })?;
 
         Ok(metrics)
     }
@@ -173,19 +139,21 @@ impl CorridorAggregates {
         let date_datetime = date.and_hms_opt(0, 0, 0).unwrap().and_utc();
         let next_day = date_datetime + chrono::Duration::days(1);
 
-        let metrics = sqlx::query_as::<_, CorridorMetrics>(
-            r#"
-            SELECT * FROM corridor_metrics
-            WHERE date >= ? AND date < ?
-            ORDER BY total_transactions DESC
-            LIMIT ?
-            "#,
-        )
-        .bind(date_datetime)
-        .bind(next_day)
-        .bind(limit)
-        .fetch_all(&self.pool)
-        .await?;
+        let metrics = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, CorridorMetrics>(
+                r#"
+                SELECT * FROM corridor_metrics
+                WHERE date >= $1 AND date < $2
+                ORDER BY total_transactions DESC
+                LIMIT $3
+                "#,
+            )
+            .bind(date_datetime)
+            .bind(next_day)
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+        })?;
 
         Ok(metrics)
     }
@@ -199,21 +167,23 @@ impl CorridorAggregates {
         let date_datetime = date.and_hms_opt(0, 0, 0).unwrap().and_utc();
         let next_day = date_datetime + chrono::Duration::days(1);
 
-        let metrics = sqlx::query_as::<_, CorridorMetrics>(
-            r#"
-            SELECT * FROM corridor_metrics
-            WHERE date >= ? AND date < ?
-            AND success_rate >= ?
-            AND total_transactions >= ?
-            ORDER BY success_rate DESC, total_transactions DESC
-            "#,
-        )
-        .bind(date_datetime)
-        .bind(next_day)
-        .bind(min_success_rate)
-        .bind(min_transactions)
-        .fetch_all(&self.pool)
-        .await?;
+        let metrics = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, CorridorMetrics>(
+                r#"
+                SELECT * FROM corridor_metrics
+                WHERE date >= $1 AND date < $2
+                AND success_rate >= $3
+                AND total_transactions >= $4
+                ORDER BY success_rate DESC, total_transactions DESC
+                "#,
+            )
+            .bind(date_datetime)
+            .bind(next_day)
+            .bind(min_success_rate)
+            .bind(min_transactions)
+            .fetch_all(pool)
+            .await
+        })?;
 
         Ok(metrics)
     }
@@ -226,23 +196,25 @@ impl CorridorAggregates {
         let start_datetime = start_date.and_hms_opt(0, 0, 0).unwrap().and_utc();
         let end_datetime = end_date.and_hms_opt(23, 59, 59).unwrap().and_utc();
 
-        let stats = sqlx::query_as::<_, CorridorSummaryStats>(
-            r#"
+        let stats = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, CorridorSummaryStats>(
+                r#"
             SELECT 
                 COUNT(*) as total_corridors,
-                SUM(total_transactions) as total_transactions,
-                SUM(successful_transactions) as successful_transactions,
-                SUM(failed_transactions) as failed_transactions,
+                CAST(SUM(total_transactions) AS BIGINT) as total_transactions,
+                CAST(SUM(successful_transactions) AS BIGINT) as successful_transactions,
+                CAST(SUM(failed_transactions) AS BIGINT) as failed_transactions,
                 SUM(volume_usd) as total_volume_usd,
                 AVG(success_rate) as avg_success_rate
             FROM corridor_metrics
-            WHERE date >= ? AND date <= ?
+            WHERE date >= $1 AND date <= $2
             "#,
-        )
-        .bind(start_datetime)
-        .bind(end_datetime)
-        .fetch_one(&self.pool)
-        .await?;
+            )
+            .bind(start_datetime)
+            .bind(end_datetime)
+            .fetch_one(pool)
+            .await
+        })?;
 
         Ok(stats)
     }
@@ -250,17 +222,20 @@ impl CorridorAggregates {
     pub async fn delete_old_metrics(&self, cutoff_date: NaiveDate) -> Result<u64> {
         let cutoff_datetime = cutoff_date.and_hms_opt(0, 0, 0).unwrap().and_utc();
 
-        let result: sqlx::sqlite::SqliteQueryResult = sqlx::query(
-            r#"
-            DELETE FROM corridor_metrics
-            WHERE date < ?
-            "#,
-        )
-        .bind(cutoff_datetime)
-        .execute(&self.pool)
-        .await?;
+        let result = with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                DELETE FROM corridor_metrics
+                WHERE date < $1
+                "#,
+            )
+            .bind(cutoff_datetime)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
 
-        Ok(result.rows_affected())
+        Ok(result)
     }
 }
 
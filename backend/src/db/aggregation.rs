@@ -1,15 +1,16 @@
+use crate::database::DbPool;
+use crate::with_pool;
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
-use sqlx::SqlitePool;
 
 use crate::services::aggregation::HourlyCorridorMetrics;
 
 pub struct AggregationDb {
-    pool: SqlitePool,
+    pool: DbPool,
 }
 
 impl AggregationDb {
-    pub fn new(pool: SqlitePool) -> Self {
+    pub fn new(pool: DbPool) -> Self {
         Self { pool }
     }
 
@@ -20,65 +21,75 @@ impl AggregationDb {
         end_time: DateTime<Utc>,
         limit: i64,
     ) -> Result<Vec<crate::models::corridor::PaymentRecord>> {
-        let records = sqlx::query_as::<_, PaymentRecordRow>(
-            r#"
-            SELECT 
-                id,
-                transaction_hash,
-                source_account,
-                destination_account,
-                asset_type,
-                asset_code,
-                asset_issuer,
-                amount,
-                created_at
-            FROM payments
-            WHERE created_at >= ? AND created_at <= ?
-            ORDER BY created_at ASC
-            LIMIT ?
-            "#,
-        )
-        .bind(start_time.to_rfc3339())
-        .bind(end_time.to_rfc3339())
-        .bind(limit)
-        .fetch_all(&self.pool)
-        .await
+        let records = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, PaymentRecordRow>(
+                r#"
+                SELECT 
+                    id,
+                    transaction_hash,
+                    source_account,
+                    destination_account,
+                    asset_type,
+                    asset_code,
+                    asset_issuer,
+                    amount,
+                    created_at
+                FROM payments
+                WHERE created_at >= $1 AND created_at <= $2
+                ORDER BY created_at ASC
+                LIMIT $3
+                "#,
+            )
+            .bind(start_time)
+            .bind(end_time)
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+        })
         .context("Failed to fetch payments by timerange")?;
 
-        // Convert to PaymentRecord with corridor information
-        let payment_records: Vec<crate::models::corridor::PaymentRecord> = records
+        Ok(records
             .into_iter()
-            .filter_map(|row| {
-                // Parse the created_at timestamp
-                let timestamp = DateTime::parse_from_rfc3339(&row.created_at)
-                    .ok()?
-                    .with_timezone(&Utc);
+            .map(PaymentRecordRow::into_payment_record)
+            .collect())
+    }
 
-                // For now, assume all payments are successful
-                // In a real system, you'd have a status field
-                let successful = true;
-
-                Some(crate::models::corridor::PaymentRecord {
-                    id: uuid::Uuid::parse_str(&row.id).ok()?,
-                    source_asset_code: row.asset_code.clone().unwrap_or_else(|| "XLM".to_string()),
-                    source_asset_issuer: row
-                        .asset_issuer
-                        .clone()
-                        .unwrap_or_else(|| "native".to_string()),
-                    destination_asset_code: row.asset_code.unwrap_or_else(|| "XLM".to_string()),
-                    destination_asset_issuer: row
-                        .asset_issuer
-                        .unwrap_or_else(|| "native".to_string()),
-                    amount: row.amount,
-                    successful,
-                    timestamp,
-                    submission_time: None,
-                    confirmation_time: None,
-                })
-            })
-            .collect();
+    /// Payments created within `[start_time, end_time)`, as corridor payment
+    /// records built the same way as [`Self::fetch_payments_by_timerange`]
+    pub async fn fetch_payments_in_bucket(
+        &self,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<Vec<crate::models::corridor::PaymentRecord>> {
+        let records = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, PaymentRecordRow>(
+                r#"
+                SELECT
+                    id,
+                    transaction_hash,
+                    source_account,
+                    destination_account,
+                    asset_type,
+                    asset_code,
+                    asset_issuer,
+                    amount,
+                    created_at
+                FROM payments
+                WHERE created_at >= $1 AND created_at < $2
+                ORDER BY created_at ASC, id ASC
+                "#,
+            )
+            .bind(start_time)
+            .bind(end_time)
+            .fetch_all(pool)
+            .await
+        })
+        .context("Failed to fetch payments for bucket")?;
 
-        Ok(payment_records)
+        Ok(records
+            .into_iter()
+            .map(PaymentRecordRow::into_payment_record)
+            .collect())
     }
 
     /// Upserts a single hourly corridor metric row into `corridor_metrics_hourly`.
@@ -107,66 +118,70 @@ impl AggregationDb {
     ) -> Result<()> {
         let now = Utc::now().to_rfc3339();
 
-        sqlx::query(
-            r#"
-            INSERT INTO corridor_metrics_hourly (
-                id,
-                corridor_key,
-                asset_a_code,
-                asset_a_issuer,
-                asset_b_code,
-                asset_b_issuer,
-                hour_bucket,
-                total_transactions,
-                successful_transactions,
-                failed_transactions,
-                success_rate,
-                volume_usd,
-                avg_slippage_bps,
-                avg_settlement_latency_ms,
-                liquidity_depth_usd,
-                created_at,
-                updated_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            ON CONFLICT(corridor_key, hour_bucket) DO UPDATE SET
-                -- Counters are cumulative across retries/batches for the same hour bucket.
-                total_transactions = total_transactions + excluded.total_transactions,
-                successful_transactions = successful_transactions + excluded.successful_transactions,
-                failed_transactions = failed_transactions + excluded.failed_transactions,
-                -- Recompute from merged counts rather than averaging percentages.
-                success_rate = (successful_transactions * 100.0) / NULLIF(total_transactions, 0),
-                volume_usd = volume_usd + excluded.volume_usd,
-                -- Blend point estimates when raw distributions are not retained.
-                avg_slippage_bps = (avg_slippage_bps + excluded.avg_slippage_bps) / 2.0,
-                avg_settlement_latency_ms = COALESCE(
-                    (avg_settlement_latency_ms + excluded.avg_settlement_latency_ms) / 2,
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO corridor_metrics_hourly (
+                    id,
+                    corridor_key,
+                    asset_a_code,
+                    asset_a_issuer,
+                    asset_b_code,
+                    asset_b_issuer,
+                    hour_bucket,
+                    total_transactions,
+                    successful_transactions,
+                    failed_transactions,
+                    success_rate,
+                    volume_usd,
+                    avg_slippage_bps,
                     avg_settlement_latency_ms,
-                    excluded.avg_settlement_latency_ms
-                ),
-                liquidity_depth_usd = (liquidity_depth_usd + excluded.liquidity_depth_usd) / 2.0,
-                updated_at = ?
-            "#,
-        )
-        .bind(&metric.id)
-        .bind(&metric.corridor_key)
-        .bind(&metric.asset_a_code)
-        .bind(&metric.asset_a_issuer)
-        .bind(&metric.asset_b_code)
-        .bind(&metric.asset_b_issuer)
-        .bind(metric.hour_bucket.to_rfc3339())
-        .bind(metric.total_transactions)
-        .bind(metric.successful_transactions)
-        .bind(metric.failed_transactions)
-        .bind(metric.success_rate)
-        .bind(metric.volume_usd)
-        .bind(metric.avg_slippage_bps)
-        .bind(metric.avg_settlement_latency_ms)
-        .bind(metric.liquidity_depth_usd)
-        .bind(&now)
-        .bind(&now)
-        .bind(&now)
-        .execute(&self.pool)
-        .await
+                    liquidity_depth_usd,
+                    created_at,
+                    updated_at
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+                ON CONFLICT(corridor_key, hour_bucket) DO UPDATE SET
+                    -- Counters are cumulative across retries/batches for the same hour bucket.
+                    total_transactions = corridor_metrics_hourly.total_transactions + excluded.total_transactions,
+                    successful_transactions = corridor_metrics_hourly.successful_transactions + excluded.successful_transactions,
+                    failed_transactions = corridor_metrics_hourly.failed_transactions + excluded.failed_transactions,
+                    -- Recompute from merged counts rather than averaging percentages.
+                    success_rate = ((corridor_metrics_hourly.successful_transactions + excluded.successful_transactions) * 100.0)
+                        / NULLIF(corridor_metrics_hourly.total_transactions + excluded.total_transactions, 0),
+                    volume_usd = corridor_metrics_hourly.volume_usd + excluded.volume_usd,
+                    -- Blend point estimates when raw distributions are not retained.
+                    avg_slippage_bps = (corridor_metrics_hourly.avg_slippage_bps + excluded.avg_slippage_bps) / 2.0,
+                    avg_settlement_latency_ms = COALESCE(
+                        (corridor_metrics_hourly.avg_settlement_latency_ms + excluded.avg_settlement_latency_ms) / 2,
+                        corridor_metrics_hourly.avg_settlement_latency_ms,
+                        excluded.avg_settlement_latency_ms
+                    ),
+                    liquidity_depth_usd = (corridor_metrics_hourly.liquidity_depth_usd + excluded.liquidity_depth_usd) / 2.0,
+                    updated_at = $18
+                "#,
+            )
+            .bind(&metric.id)
+            .bind(&metric.corridor_key)
+            .bind(&metric.asset_a_code)
+            .bind(&metric.asset_a_issuer)
+            .bind(&metric.asset_b_code)
+            .bind(&metric.asset_b_issuer)
+            .bind(metric.hour_bucket.to_rfc3339())
+            .bind(metric.total_transactions)
+            .bind(metric.successful_transactions)
+            .bind(metric.failed_transactions)
+            .bind(metric.success_rate)
+            .bind(metric.volume_usd)
+            .bind(metric.avg_slippage_bps)
+            .bind(metric.avg_settlement_latency_ms)
+            .bind(metric.liquidity_depth_usd)
+            .bind(&now)
+            .bind(&now)
+            .bind(&now)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .context("Failed to upsert hourly corridor metric")?;
 
         Ok(())
@@ -178,33 +193,35 @@ impl AggregationDb {
         start_time: DateTime<Utc>,
         end_time: DateTime<Utc>,
     ) -> Result<Vec<HourlyCorridorMetrics>> {
-        let rows = sqlx::query_as::<_, HourlyCorridorMetricsRow>(
-            r#"
-            SELECT 
-                id,
-                corridor_key,
-                asset_a_code,
-                asset_a_issuer,
-                asset_b_code,
-                asset_b_issuer,
-                hour_bucket,
-                total_transactions,
-                successful_transactions,
-                failed_transactions,
-                success_rate,
-                volume_usd,
-                avg_slippage_bps,
-                avg_settlement_latency_ms,
-                liquidity_depth_usd
-            FROM corridor_metrics_hourly
-            WHERE hour_bucket >= ? AND hour_bucket <= ?
-            ORDER BY hour_bucket ASC
-            "#,
-        )
-        .bind(start_time.to_rfc3339())
-        .bind(end_time.to_rfc3339())
-        .fetch_all(&self.pool)
-        .await
+        let rows = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, HourlyCorridorMetricsRow>(
+                r#"
+                SELECT 
+                    id,
+                    corridor_key,
+                    asset_a_code,
+                    asset_a_issuer,
+                    asset_b_code,
+                    asset_b_issuer,
+                    hour_bucket,
+                    total_transactions,
+                    successful_transactions,
+                    failed_transactions,
+                    success_rate,
+                    volume_usd,
+                    avg_slippage_bps,
+                    avg_settlement_latency_ms,
+                    liquidity_depth_usd
+                FROM corridor_metrics_hourly
+                WHERE hour_bucket >= $1 AND hour_bucket <= $2
+                ORDER BY hour_bucket ASC
+                "#,
+            )
+            .bind(start_time.to_rfc3339())
+            .bind(end_time.to_rfc3339())
+            .fetch_all(pool)
+            .await
+        })
         .context("Failed to fetch hourly metrics by timerange")?;
 
         let metrics: Vec<HourlyCorridorMetrics> = rows
@@ -241,18 +258,21 @@ impl AggregationDb {
     pub async fn create_aggregation_job(&self, job_id: &str, job_type: &str) -> Result<()> {
         let now = Utc::now().to_rfc3339();
 
-        sqlx::query(
-            r#"
-            INSERT INTO aggregation_jobs (id, job_type, status, created_at, updated_at)
-            VALUES (?, ?, 'pending', ?, ?)
-            "#,
-        )
-        .bind(job_id)
-        .bind(job_type)
-        .bind(&now)
-        .bind(&now)
-        .execute(&self.pool)
-        .await
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO aggregation_jobs (id, job_type, status, created_at, updated_at)
+                VALUES ($1, $2, 'pending', $3, $4)
+                "#,
+            )
+            .bind(job_id)
+            .bind(job_type)
+            .bind(&now)
+            .bind(&now)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .context("Failed to create aggregation job")?;
 
         Ok(())
@@ -285,21 +305,24 @@ impl AggregationDb {
         let query_str = format!(
             r#"
             UPDATE aggregation_jobs
-            SET status = ?, error_message = ?, {} = ?, updated_at = ?
-            WHERE id = ?
+            SET status = $1, error_message = $2, {} = $3, updated_at = $4
+            WHERE id = $5
             "#,
             time_field
         );
 
-        sqlx::query(&query_str)
-            .bind(status)
-            .bind(error_message)
-            .bind(&now)
-            .bind(&now)
-            .bind(job_id)
-            .execute(&self.pool)
-            .await
-            .context("Failed to update aggregation job status")?;
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(&query_str)
+                .bind(status)
+                .bind(error_message)
+                .bind(&now)
+                .bind(&now)
+                .bind(job_id)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+        })
+        .context("Failed to update aggregation job status")?;
 
         Ok(())
     }
@@ -308,18 +331,21 @@ impl AggregationDb {
     pub async fn update_last_processed_hour(&self, job_id: &str, last_hour: &str) -> Result<()> {
         let now = Utc::now().to_rfc3339();
 
-        sqlx::query(
-            r#"
-            UPDATE aggregation_jobs
-            SET last_processed_hour = ?, updated_at = ?
-            WHERE id = ?
-            "#,
-        )
-        .bind(last_hour)
-        .bind(&now)
-        .bind(job_id)
-        .execute(&self.pool)
-        .await
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                UPDATE aggregation_jobs
+                SET last_processed_hour = $1, updated_at = $2
+                WHERE id = $3
+                "#,
+            )
+            .bind(last_hour)
+            .bind(&now)
+            .bind(job_id)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .context("Failed to update last processed hour")?;
 
         Ok(())
@@ -327,14 +353,16 @@ impl AggregationDb {
 
     /// Get job retry count
     pub async fn get_job_retry_count(&self, job_id: &str) -> Result<i32> {
-        let row: (i32,) = sqlx::query_as(
-            r#"
-            SELECT retry_count FROM aggregation_jobs WHERE id = ?
-            "#,
-        )
-        .bind(job_id)
-        .fetch_one(&self.pool)
-        .await
+        let row: (i32,) = with_pool!(&self.pool, |pool| {
+            sqlx::query_as(
+                r#"
+                SELECT retry_count FROM aggregation_jobs WHERE id = $1
+                "#,
+            )
+            .bind(job_id)
+            .fetch_one(pool)
+            .await
+        })
         .context("Failed to get job retry count")?;
 
         Ok(row.0)
@@ -344,23 +372,39 @@ impl AggregationDb {
     pub async fn increment_job_retry_count(&self, job_id: &str) -> Result<()> {
         let now = Utc::now().to_rfc3339();
 
-        sqlx::query(
-            r#"
-            UPDATE aggregation_jobs
-            SET retry_count = retry_count + 1, updated_at = ?
-            WHERE id = ?
-            "#,
-        )
-        .bind(&now)
-        .bind(job_id)
-        .execute(&self.pool)
-        .await
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                UPDATE aggregation_jobs
+                SET retry_count = retry_count + 1, updated_at = $1
+                WHERE id = $2
+                "#,
+            )
+            .bind(&now)
+            .bind(job_id)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .context("Failed to increment job retry count")?;
 
         Ok(())
     }
 }
 
+/// Payment ids ingested from Horizon are operation ids rather than UUIDs; map
+/// those to a stable UUID derived from the id
+fn payment_uuid(id: &str) -> uuid::Uuid {
+    use sha2::{Digest, Sha256};
+
+    uuid::Uuid::parse_str(id).unwrap_or_else(|_| {
+        let digest = Sha256::digest(id.as_bytes());
+        let mut bytes = [0u8; 16];
+        bytes.copy_from_slice(&digest[..16]);
+        uuid::Uuid::from_bytes(bytes)
+    })
+}
+
 // Database row structures
 // Note: Some fields are fetched from DB for completeness but not used in Rust code.
 // They're kept for potential future use and to match the SQL SELECT statement.
@@ -378,7 +422,31 @@ struct PaymentRecordRow {
     asset_code: Option<String>,
     asset_issuer: Option<String>,
     amount: f64,
-    created_at: String,
+    created_at: DateTime<Utc>,
+}
+
+impl PaymentRecordRow {
+    /// Payments are stored per asset, so the corridor runs from the asset to
+    /// itself, with XLM for native payments
+    fn into_payment_record(self) -> crate::models::corridor::PaymentRecord {
+        let code = self.asset_code.unwrap_or_else(|| "XLM".to_string());
+        let issuer = self.asset_issuer.unwrap_or_else(|| "native".to_string());
+
+        crate::models::corridor::PaymentRecord {
+            id: payment_uuid(&self.id),
+            source_asset_code: code.clone(),
+            source_asset_issuer: issuer.clone(),
+            destination_asset_code: code,
+            destination_asset_issuer: issuer,
+            amount: self.amount,
+            // For now, assume all payments are successful
+            // In a real system, you'd have a status field
+            successful: true,
+            timestamp: self.created_at,
+            submission_time: None,
+            confirmation_time: None,
+        }
+    }
 }
 
 #[derive(sqlx::FromRow)]
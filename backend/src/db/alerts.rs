@@ -1,6 +1,7 @@
 use crate::models::alerts::{
     AlertHistory, AlertRule, CreateAlertRuleRequest, SnoozeAlertRequest, UpdateAlertRuleRequest,
 };
+use crate::with_pool;
 use anyhow::Result;
 use chrono::Utc;
 use uuid::Uuid;
@@ -13,55 +14,61 @@ impl crate::database::Database {
         req: CreateAlertRuleRequest,
     ) -> Result<AlertRule> {
         let id = Uuid::new_v4().to_string();
-        let rule = sqlx::query_as::<_, AlertRule>(
-            r#"
-            INSERT INTO alert_rules (
-                id, user_id, corridor_id, metric_type, condition, threshold,
-                notify_email, notify_webhook, notify_in_app
+        let rule = with_pool!(self.pool(), |pool| {
+            sqlx::query_as::<_, AlertRule>(
+                r#"
+                INSERT INTO alert_rules (
+                    id, user_id, corridor_id, metric_type, condition, threshold,
+                    notify_email, notify_webhook, notify_in_app
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                RETURNING *
+                "#,
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-            RETURNING *
-            "#,
-        )
-        .bind(id)
-        .bind(user_id)
-        .bind(&req.corridor_id)
-        .bind(&req.metric_type)
-        .bind(&req.condition)
-        .bind(req.threshold)
-        .bind(req.notify_email)
-        .bind(req.notify_webhook)
-        .bind(req.notify_in_app)
-        .fetch_one(self.pool())
-        .await?;
+            .bind(id)
+            .bind(user_id)
+            .bind(&req.corridor_id)
+            .bind(&req.metric_type)
+            .bind(&req.condition)
+            .bind(req.threshold)
+            .bind(req.notify_email)
+            .bind(req.notify_webhook)
+            .bind(req.notify_in_app)
+            .fetch_one(pool)
+            .await
+        })?;
 
         Ok(rule)
     }
 
     pub async fn get_alert_rules_for_user(&self, user_id: &str) -> Result<Vec<AlertRule>> {
-        let rules = sqlx::query_as::<_, AlertRule>(
-            r#"
-            SELECT * FROM alert_rules
-            WHERE user_id = $1
-            ORDER BY created_at DESC
-            "#,
-        )
-        .bind(user_id)
-        .fetch_all(self.pool())
-        .await?;
+        let rules = with_pool!(self.pool(), |pool| {
+            sqlx::query_as::<_, AlertRule>(
+                r#"
+                SELECT * FROM alert_rules
+                WHERE user_id = $1
+                ORDER BY created_at DESC
+                "#,
+            )
+            .bind(user_id)
+            .fetch_all(pool)
+            .await
+        })?;
 
         Ok(rules)
     }
 
     pub async fn get_all_active_alert_rules(&self) -> Result<Vec<AlertRule>> {
-        let rules = sqlx::query_as::<_, AlertRule>(
-            r#"
-            SELECT * FROM alert_rules
-            WHERE is_active = 1
-            "#,
-        )
-        .fetch_all(self.pool())
-        .await?;
+        let rules = with_pool!(self.pool(), |pool| {
+            sqlx::query_as::<_, AlertRule>(
+                r#"
+                SELECT * FROM alert_rules
+                WHERE is_active = TRUE
+                "#,
+            )
+            .fetch_all(pool)
+            .await
+        })?;
 
         Ok(rules)
     }
@@ -102,67 +109,72 @@ impl crate::database::Database {
 
         query.push_str(" WHERE id = $1 AND user_id = $2 RETURNING *");
 
-        let mut q = sqlx::query_as::<_, AlertRule>(&query)
-            .bind(id)
-            .bind(user_id);
+        let rule = with_pool!(self.pool(), |pool| {
+            let mut q = sqlx::query_as::<_, AlertRule>(&query)
+                .bind(id)
+                .bind(user_id);
 
-        if req.corridor_id.is_some() {
-            q = q.bind(&req.corridor_id);
-        } else {
-            q = q.bind(None::<String>);
-        }
-        if let Some(m) = &req.metric_type {
-            q = q.bind(m);
-        } else {
-            q = q.bind("");
-        }
-        if let Some(c) = &req.condition {
-            q = q.bind(c);
-        } else {
-            q = q.bind("");
-        }
-        if let Some(t) = req.threshold {
-            q = q.bind(t);
-        } else {
-            q = q.bind(0.0);
-        }
-        if let Some(e) = req.notify_email {
-            q = q.bind(e);
-        } else {
-            q = q.bind(false);
-        }
-        if let Some(w) = req.notify_webhook {
-            q = q.bind(w);
-        } else {
-            q = q.bind(false);
-        }
-        if let Some(i) = req.notify_in_app {
-            q = q.bind(i);
-        } else {
-            q = q.bind(false);
-        }
-        if let Some(a) = req.is_active {
-            q = q.bind(a);
-        } else {
-            q = q.bind(false);
-        }
+            if req.corridor_id.is_some() {
+                q = q.bind(&req.corridor_id);
+            } else {
+                q = q.bind(None::<String>);
+            }
+            if let Some(m) = &req.metric_type {
+                q = q.bind(m);
+            } else {
+                q = q.bind("");
+            }
+            if let Some(c) = &req.condition {
+                q = q.bind(c);
+            } else {
+                q = q.bind("");
+            }
+            if let Some(t) = req.threshold {
+                q = q.bind(t);
+            } else {
+                q = q.bind(0.0);
+            }
+            if let Some(e) = req.notify_email {
+                q = q.bind(e);
+            } else {
+                q = q.bind(false);
+            }
+            if let Some(w) = req.notify_webhook {
+                q = q.bind(w);
+            } else {
+                q = q.bind(false);
+            }
+            if let Some(i) = req.notify_in_app {
+                q = q.bind(i);
+            } else {
+                q = q.bind(false);
+            }
+            if let Some(a) = req.is_active {
+                q = q.bind(a);
+            } else {
+                q = q.bind(false);
+            }
 
-        let rule = q.fetch_one(self.pool()).await?;
+            q.fetch_one(pool).await
+        })?;
         Ok(rule)
     }
 
-    pub async fn delete_alert_rule(&self, id: &str, user_id: &str) -> Result<()> {
-        sqlx::query(
-            r#"
-            DELETE FROM alert_rules WHERE id = $1 AND user_id = $2
-            "#,
-        )
-        .bind(id)
-        .bind(user_id)
-        .execute(self.pool())
-        .await?;
+    pub async fn delete_alert_rule(&self, id: &str, user_id: &str) -> Result<u64> {
+        let deleted = with_pool!(self.pool(), |pool| {
+            sqlx::query(
+                r#"
+                DELETE FROM alert_rules WHERE id = $1 AND user_id = $2
+                "#,
+            )
+            .bind(id)
+            .bind(user_id)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
 
-        Ok(())
+        Ok(deleted)
     }
 
     pub async fn snooze_alert_rule(
@@ -171,19 +183,21 @@ impl crate::database::Database {
         user_id: &str,
         req: SnoozeAlertRequest,
     ) -> Result<AlertRule> {
-        let rule = sqlx::query_as::<_, AlertRule>(
-            r#"
-            UPDATE alert_rules
-            SET snoozed_until = $3, updated_at = CURRENT_TIMESTAMP
-            WHERE id = $1 AND user_id = $2
-            RETURNING *
-            "#,
-        )
-        .bind(id)
-        .bind(user_id)
-        .bind(req.snoozed_until)
-        .fetch_one(self.pool())
-        .await?;
+        let rule = with_pool!(self.pool(), |pool| {
+            sqlx::query_as::<_, AlertRule>(
+                r#"
+                UPDATE alert_rules
+                SET snoozed_until = $3, updated_at = CURRENT_TIMESTAMP
+                WHERE id = $1 AND user_id = $2
+                RETURNING *
+                "#,
+            )
+            .bind(id)
+            .bind(user_id)
+            .bind(req.snoozed_until)
+            .fetch_one(pool)
+            .await
+        })?;
 
         Ok(rule)
     }
@@ -201,27 +215,29 @@ impl crate::database::Database {
         message: &str,
     ) -> Result<AlertHistory> {
         let id = Uuid::new_v4().to_string();
-        let history = sqlx::query_as::<_, AlertHistory>(
-            r#"
-            INSERT INTO alert_history (
-                id, rule_id, user_id, corridor_id, metric_type,
-                trigger_value, threshold_value, condition, message
+        let history = with_pool!(self.pool(), |pool| {
+            sqlx::query_as::<_, AlertHistory>(
+                r#"
+                INSERT INTO alert_history (
+                    id, rule_id, user_id, corridor_id, metric_type,
+                    trigger_value, threshold_value, condition, message
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                RETURNING *
+                "#,
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-            RETURNING *
-            "#,
-        )
-        .bind(id)
-        .bind(rule_id)
-        .bind(user_id)
-        .bind(corridor_id)
-        .bind(metric_type)
-        .bind(trigger_value)
-        .bind(threshold_value)
-        .bind(condition)
-        .bind(message)
-        .fetch_one(self.pool())
-        .await?;
+            .bind(id)
+            .bind(rule_id)
+            .bind(user_id)
+            .bind(corridor_id)
+            .bind(metric_type)
+            .bind(trigger_value)
+            .bind(threshold_value)
+            .bind(condition)
+            .bind(message)
+            .fetch_one(pool)
+            .await
+        })?;
 
         Ok(history)
     }
@@ -231,50 +247,58 @@ impl crate::database::Database {
         user_id: &str,
         limit: i64,
     ) -> Result<Vec<AlertHistory>> {
-        let history = sqlx::query_as::<_, AlertHistory>(
-            r#"
-            SELECT * FROM alert_history
-            WHERE user_id = $1
-            ORDER BY triggered_at DESC
-            LIMIT $2
-            "#,
-        )
-        .bind(user_id)
-        .bind(limit)
-        .fetch_all(self.pool())
-        .await?;
+        let history = with_pool!(self.pool(), |pool| {
+            sqlx::query_as::<_, AlertHistory>(
+                r#"
+                SELECT * FROM alert_history
+                WHERE user_id = $1
+                ORDER BY triggered_at DESC
+                LIMIT $2
+                "#,
+            )
+            .bind(user_id)
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+        })?;
 
         Ok(history)
     }
 
     pub async fn mark_alert_history_read(&self, id: &str, user_id: &str) -> Result<()> {
-        sqlx::query(
-            r#"
-            UPDATE alert_history
-            SET is_read = 1
-            WHERE id = $1 AND user_id = $2
-            "#,
-        )
-        .bind(id)
-        .bind(user_id)
-        .execute(self.pool())
-        .await?;
+        with_pool!(self.pool(), |pool| {
+            sqlx::query(
+                r#"
+                UPDATE alert_history
+                SET is_read = TRUE
+                WHERE id = $1 AND user_id = $2
+                "#,
+            )
+            .bind(id)
+            .bind(user_id)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
 
         Ok(())
     }
 
     pub async fn dismiss_alert_history(&self, id: &str, user_id: &str) -> Result<()> {
-        sqlx::query(
-            r#"
-            UPDATE alert_history
-            SET is_dismissed = 1
-            WHERE id = $1 AND user_id = $2
-            "#,
-        )
-        .bind(id)
-        .bind(user_id)
-        .execute(self.pool())
-        .await?;
+        with_pool!(self.pool(), |pool| {
+            sqlx::query(
+                r#"
+                UPDATE alert_history
+                SET is_dismissed = TRUE
+                WHERE id = $1 AND user_id = $2
+                "#,
+            )
+            .bind(id)
+            .bind(user_id)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
 
         Ok(())
     }
@@ -0,0 +1,197 @@
+use crate::database::DbPool;
+use crate::with_pool;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+
+use crate::analytics::anomaly::{AnomalyDirection, AnomalyMetric, SeasonalBaseline};
+use crate::services::anomaly_detection::{CorridorAnomaly, StoredBaseline};
+
+/// Storage for corridor anomaly baselines and detected anomalies.
+///
+/// Hour buckets are stored as RFC 3339 text, like the rollup tables, so range filters
+/// compare them as strings.
+pub struct AnomalyDb {
+    pool: DbPool,
+}
+
+impl AnomalyDb {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Load every stored baseline
+    pub async fn load_baselines(&self) -> Result<Vec<StoredBaseline>> {
+        let rows = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, BaselineRow>(
+                r#"
+                SELECT corridor_key, baseline, last_bucket
+                FROM corridor_anomaly_baselines
+                "#,
+            )
+            .fetch_all(pool)
+            .await
+        })
+        .context("Failed to load anomaly baselines")?;
+
+        rows.into_iter()
+            .filter_map(|row| {
+                let last_bucket = parse_bucket(&row.last_bucket)?;
+                Some((row, last_bucket))
+            })
+            .map(|(row, last_bucket)| {
+                Ok(StoredBaseline {
+                    corridor_key: row.corridor_key,
+                    baseline: serde_json::from_str(&row.baseline)
+                        .context("Failed to decode anomaly baseline")?,
+                    last_bucket,
+                })
+            })
+            .collect()
+    }
+
+    /// Insert or replace the baseline of one corridor metric
+    pub async fn upsert_baseline(
+        &self,
+        corridor_key: &str,
+        baseline: &SeasonalBaseline,
+        last_bucket: DateTime<Utc>,
+    ) -> Result<()> {
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO corridor_anomaly_baselines (
+                    corridor_key, metric, baseline, last_bucket, updated_at
+                ) VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT(corridor_key, metric) DO UPDATE SET
+                    baseline = excluded.baseline,
+                    last_bucket = excluded.last_bucket,
+                    updated_at = excluded.updated_at
+                "#,
+            )
+            .bind(corridor_key)
+            .bind(baseline.metric().as_str())
+            .bind(serde_json::to_string(baseline)?)
+            .bind(last_bucket.to_rfc3339())
+            .bind(Utc::now().to_rfc3339())
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
+        .context("Failed to upsert anomaly baseline")?;
+
+        Ok(())
+    }
+
+    /// Record a detected anomaly. Returns `false` if the same corridor, metric and hour
+    /// was already recorded, so re-running detection does not notify twice.
+    pub async fn insert_anomaly(&self, anomaly: &CorridorAnomaly) -> Result<bool> {
+        let result = with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO corridor_anomalies (
+                    id, corridor_key, metric, bucket_start, observed, expected,
+                    z_score, direction, severity, detected_at
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                ON CONFLICT(corridor_key, metric, bucket_start) DO NOTHING
+                "#,
+            )
+            .bind(&anomaly.id)
+            .bind(&anomaly.corridor_key)
+            .bind(anomaly.metric.as_str())
+            .bind(anomaly.bucket_start.to_rfc3339())
+            .bind(anomaly.observed)
+            .bind(anomaly.expected)
+            .bind(anomaly.z_score)
+            .bind(anomaly.direction.as_str())
+            .bind(&anomaly.severity)
+            .bind(anomaly.detected_at.to_rfc3339())
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
+        .context("Failed to insert corridor anomaly")?;
+
+        Ok(result > 0)
+    }
+
+    /// Fetch anomalies of one corridor in hours starting in `[start, end)`, newest first
+    pub async fn list_anomalies(
+        &self,
+        corridor_key: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<CorridorAnomaly>> {
+        let rows = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, AnomalyRow>(
+                r#"
+                SELECT id, corridor_key, metric, bucket_start, observed, expected,
+                       z_score, direction, severity, detected_at
+                FROM corridor_anomalies
+                WHERE corridor_key = $1 AND bucket_start >= $2 AND bucket_start < $3
+                ORDER BY bucket_start DESC, metric ASC
+                LIMIT $4
+                "#,
+            )
+            .bind(corridor_key)
+            .bind(start.to_rfc3339())
+            .bind(end.to_rfc3339())
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+        })
+        .context("Failed to list corridor anomalies")?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(AnomalyRow::into_anomaly)
+            .collect())
+    }
+}
+
+fn parse_bucket(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|ts| ts.with_timezone(&Utc))
+}
+
+#[derive(sqlx::FromRow)]
+struct BaselineRow {
+    corridor_key: String,
+    baseline: String,
+    last_bucket: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct AnomalyRow {
+    id: String,
+    corridor_key: String,
+    metric: String,
+    bucket_start: String,
+    observed: f64,
+    expected: f64,
+    z_score: f64,
+    direction: String,
+    severity: String,
+    detected_at: String,
+}
+
+impl AnomalyRow {
+    fn into_anomaly(self) -> Option<CorridorAnomaly> {
+        Some(CorridorAnomaly {
+            metric: self.metric.parse::<AnomalyMetric>().ok()?,
+            bucket_start: parse_bucket(&self.bucket_start)?,
+            direction: match self.direction.as_str() {
+                "drop" => AnomalyDirection::Drop,
+                _ => AnomalyDirection::Spike,
+            },
+            detected_at: parse_bucket(&self.detected_at)?,
+            id: self.id,
+            corridor_key: self.corridor_key,
+            observed: self.observed,
+            expected: self.expected,
+            z_score: self.z_score,
+            severity: self.severity,
+        })
+    }
+}
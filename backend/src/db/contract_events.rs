@@ -0,0 +1,291 @@
+use crate::database::DbPool;
+use crate::with_pool;
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::ingestion::contract_events::{DecodedEvent, IndexedEvent};
+
+/// Storage for indexed Soroban contract events.
+///
+/// Every event lands in `contract_events` so the replay engine sees it; governance and
+/// snapshot events are also written to typed tables keyed by the RPC event id, which
+/// makes re-indexing a ledger range idempotent.
+pub struct ContractEventDb {
+    pool: DbPool,
+}
+
+/// A proposal created or finalized on the governance contract
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ProposalEventRecord {
+    pub event_id: String,
+    pub contract_id: String,
+    pub proposal_id: i64,
+    pub event_type: String,
+    pub proposer: Option<String>,
+    pub target_contract: Option<String>,
+    pub voting_ends_at: Option<i64>,
+    pub status: Option<String>,
+    pub votes_for: Option<i64>,
+    pub votes_against: Option<i64>,
+    pub total_voters: Option<i64>,
+    pub ledger_sequence: i64,
+    pub transaction_hash: String,
+    pub closed_at: String,
+}
+
+/// A vote cast on the governance contract
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct VoteEventRecord {
+    pub event_id: String,
+    pub contract_id: String,
+    pub proposal_id: i64,
+    pub voter: String,
+    pub choice: String,
+    pub ledger_sequence: i64,
+    pub transaction_hash: String,
+    pub closed_at: String,
+}
+
+/// A snapshot hash submitted to the snapshot or analytics contract
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct SnapshotSubmissionRecord {
+    pub event_id: String,
+    pub contract_id: String,
+    pub epoch: i64,
+    pub hash: String,
+    pub ledger_timestamp: i64,
+    pub submitter: Option<String>,
+    pub ledger_sequence: i64,
+    pub transaction_hash: String,
+    pub closed_at: String,
+}
+
+impl ContractEventDb {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Store one event. Returns `false` if it was already indexed.
+    pub async fn store_event(&self, event: &IndexedEvent, network: &str) -> Result<bool> {
+        with_pool!(&self.pool, |pool| {
+            let mut tx = pool.begin().await?;
+            let closed_at = event.closed_at.to_rfc3339();
+            let ledger_sequence = event.ledger_sequence as i64;
+
+            // contract_events is unique per (ledger, transaction, event type) as well as by
+            // id, so a conflict on either is ignored
+            let result = sqlx::query(
+                r#"
+                INSERT INTO contract_events (
+                    id, ledger_sequence, transaction_hash, contract_id,
+                    event_type, data, timestamp, network
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT DO NOTHING
+                "#,
+            )
+            .bind(&event.id)
+            .bind(ledger_sequence)
+            .bind(&event.transaction_hash)
+            .bind(&event.contract_id)
+            .bind(event.event.event_type())
+            .bind(serde_json::to_string(&event.event)?)
+            .bind(event.closed_at)
+            .bind(network)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to store contract event")?;
+            let mut inserted = result.rows_affected() > 0;
+
+            let typed = match &event.event {
+                DecodedEvent::ProposalCreated(created) => Some(
+                    sqlx::query(
+                        r#"
+                        INSERT INTO governance_proposal_events (
+                            event_id, contract_id, proposal_id, event_type, proposer,
+                            target_contract, voting_ends_at, ledger_sequence,
+                            transaction_hash, closed_at
+                        )
+                        VALUES ($1, $2, $3, 'created', $4, $5, $6, $7, $8, $9)
+                        ON CONFLICT (event_id) DO NOTHING
+                        "#,
+                    )
+                    .bind(&event.id)
+                    .bind(&event.contract_id)
+                    .bind(created.proposal_id as i64)
+                    .bind(&created.proposer)
+                    .bind(&created.target_contract)
+                    .bind(created.voting_ends_at as i64)
+                    .bind(ledger_sequence)
+                    .bind(&event.transaction_hash)
+                    .bind(&closed_at)
+                    .execute(&mut *tx)
+                    .await
+                    .context("Failed to store proposal event")?,
+                ),
+                DecodedEvent::ProposalFinalized(finalized) => Some(
+                    sqlx::query(
+                        r#"
+                        INSERT INTO governance_proposal_events (
+                            event_id, contract_id, proposal_id, event_type, status,
+                            votes_for, votes_against, total_voters, ledger_sequence,
+                            transaction_hash, closed_at
+                        )
+                        VALUES ($1, $2, $3, 'finalized', $4, $5, $6, $7, $8, $9, $10)
+                        ON CONFLICT (event_id) DO NOTHING
+                        "#,
+                    )
+                    .bind(&event.id)
+                    .bind(&event.contract_id)
+                    .bind(finalized.proposal_id as i64)
+                    .bind(finalized.status.as_str())
+                    .bind(finalized.votes_for as i64)
+                    .bind(finalized.votes_against as i64)
+                    .bind(finalized.total_voters as i64)
+                    .bind(ledger_sequence)
+                    .bind(&event.transaction_hash)
+                    .bind(&closed_at)
+                    .execute(&mut *tx)
+                    .await
+                    .context("Failed to store proposal event")?,
+                ),
+                DecodedEvent::VoteCast(vote) => Some(
+                    sqlx::query(
+                        r#"
+                        INSERT INTO governance_vote_events (
+                            event_id, contract_id, proposal_id, voter, choice,
+                            ledger_sequence, transaction_hash, closed_at
+                        )
+                        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                        ON CONFLICT (event_id) DO NOTHING
+                        "#,
+                    )
+                    .bind(&event.id)
+                    .bind(&event.contract_id)
+                    .bind(vote.proposal_id as i64)
+                    .bind(&vote.voter)
+                    .bind(vote.choice.as_str())
+                    .bind(ledger_sequence)
+                    .bind(&event.transaction_hash)
+                    .bind(&closed_at)
+                    .execute(&mut *tx)
+                    .await
+                    .context("Failed to store vote event")?,
+                ),
+                DecodedEvent::SnapshotSubmitted(snapshot) => Some(
+                    sqlx::query(
+                        r#"
+                        INSERT INTO snapshot_submission_events (
+                            event_id, contract_id, epoch, hash, ledger_timestamp, submitter,
+                            ledger_sequence, transaction_hash, closed_at
+                        )
+                        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                        ON CONFLICT (event_id) DO NOTHING
+                        "#,
+                    )
+                    .bind(&event.id)
+                    .bind(&event.contract_id)
+                    .bind(snapshot.epoch as i64)
+                    .bind(&snapshot.hash)
+                    .bind(snapshot.ledger_timestamp as i64)
+                    .bind(&snapshot.submitter)
+                    .bind(ledger_sequence)
+                    .bind(&event.transaction_hash)
+                    .bind(&closed_at)
+                    .execute(&mut *tx)
+                    .await
+                    .context("Failed to store snapshot submission event")?,
+                ),
+                _ => None,
+            };
+            if let Some(result) = typed {
+                inserted = result.rows_affected() > 0;
+            }
+
+            tx.commit().await?;
+            Ok(inserted)
+        })
+    }
+
+    /// Proposal events, newest first
+    pub async fn list_proposal_events(
+        &self,
+        contract_id: Option<&str>,
+        proposal_id: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<ProposalEventRecord>> {
+        with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, ProposalEventRecord>(
+                r#"
+                SELECT event_id, contract_id, proposal_id, event_type, proposer,
+                       target_contract, voting_ends_at, status, votes_for, votes_against,
+                       total_voters, ledger_sequence, transaction_hash, closed_at
+                FROM governance_proposal_events
+                WHERE ($1 IS NULL OR contract_id = $1) AND ($2 IS NULL OR proposal_id = $2)
+                ORDER BY ledger_sequence DESC, event_id DESC
+                LIMIT $3
+                "#,
+            )
+            .bind(contract_id)
+            .bind(proposal_id)
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+        })
+        .context("Failed to list proposal events")
+    }
+
+    /// Votes, newest first
+    pub async fn list_votes(
+        &self,
+        contract_id: Option<&str>,
+        proposal_id: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<VoteEventRecord>> {
+        with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, VoteEventRecord>(
+                r#"
+                SELECT event_id, contract_id, proposal_id, voter, choice, ledger_sequence,
+                       transaction_hash, closed_at
+                FROM governance_vote_events
+                WHERE ($1 IS NULL OR contract_id = $1) AND ($2 IS NULL OR proposal_id = $2)
+                ORDER BY ledger_sequence DESC, event_id DESC
+                LIMIT $3
+                "#,
+            )
+            .bind(contract_id)
+            .bind(proposal_id)
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+        })
+        .context("Failed to list vote events")
+    }
+
+    /// Snapshot submissions, newest first
+    pub async fn list_snapshot_submissions(
+        &self,
+        contract_id: Option<&str>,
+        epoch: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<SnapshotSubmissionRecord>> {
+        with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, SnapshotSubmissionRecord>(
+                r#"
+                SELECT event_id, contract_id, epoch, hash, ledger_timestamp, submitter,
+                       ledger_sequence, transaction_hash, closed_at
+                FROM snapshot_submission_events
+                WHERE ($1 IS NULL OR contract_id = $1) AND ($2 IS NULL OR epoch = $2)
+                ORDER BY ledger_sequence DESC, event_id DESC
+                LIMIT $3
+                "#,
+            )
+            .bind(contract_id)
+            .bind(epoch)
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+        })
+        .context("Failed to list snapshot submissions")
+    }
+}
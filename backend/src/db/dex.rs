@@ -0,0 +1,191 @@
+use crate::database::DbPool;
+use crate::with_pool;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+
+use crate::services::dex_aggregator::{corridor_asset_id, LiquiditySnapshot};
+
+/// Storage for combined order book and pool depth snapshots.
+///
+/// Capture times are RFC 3339 text, so range filters compare them as strings. The depth
+/// curve is stored as JSON next to its 1% USD depth, which trend queries read directly.
+pub struct DexDb {
+    pool: DbPool,
+}
+
+impl DexDb {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn insert_snapshot(&self, snapshot: &LiquiditySnapshot) -> Result<()> {
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO dex_liquidity_snapshots (
+                    id, corridor_key, base_asset, counter_asset, mid_price, spread_bps,
+                    base_usd_price, book_levels, pool_count, depth_curve, depth_1pct_usd,
+                    captured_at
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+                "#,
+            )
+            .bind(&snapshot.id)
+            .bind(&snapshot.corridor_key)
+            .bind(&snapshot.base_asset)
+            .bind(&snapshot.counter_asset)
+            .bind(snapshot.mid_price)
+            .bind(snapshot.spread_bps)
+            .bind(snapshot.base_usd_price)
+            .bind(snapshot.book_levels)
+            .bind(snapshot.pool_count)
+            .bind(serde_json::to_string(&snapshot.curve)?)
+            .bind(snapshot.depth_usd_at(1.0))
+            .bind(snapshot.captured_at.to_rfc3339())
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
+        .context("Failed to insert DEX liquidity snapshot")?;
+
+        Ok(())
+    }
+
+    /// Most recent snapshot of a corridor captured at or after `since`
+    pub async fn latest_snapshot(
+        &self,
+        corridor_key: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Option<LiquiditySnapshot>> {
+        let row = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, SnapshotRow>(
+                r#"
+                SELECT id, corridor_key, base_asset, counter_asset, mid_price, spread_bps,
+                       base_usd_price, book_levels, pool_count, depth_curve, captured_at
+                FROM dex_liquidity_snapshots
+                WHERE corridor_key = $1 AND captured_at >= $2
+                ORDER BY captured_at DESC
+                LIMIT 1
+                "#,
+            )
+            .bind(corridor_key)
+            .bind(since.to_rfc3339())
+            .fetch_optional(pool)
+            .await
+        })
+        .context("Failed to load DEX liquidity snapshot")?;
+
+        row.map(SnapshotRow::into_snapshot).transpose()
+    }
+
+    /// Snapshots of a corridor captured in `[start, end)`, oldest first
+    pub async fn list_snapshots(
+        &self,
+        corridor_key: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<LiquiditySnapshot>> {
+        let rows = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, SnapshotRow>(
+                r#"
+                SELECT id, corridor_key, base_asset, counter_asset, mid_price, spread_bps,
+                       base_usd_price, book_levels, pool_count, depth_curve, captured_at
+                FROM dex_liquidity_snapshots
+                WHERE corridor_key = $1 AND captured_at >= $2 AND captured_at < $3
+                ORDER BY captured_at ASC
+                "#,
+            )
+            .bind(corridor_key)
+            .bind(start.to_rfc3339())
+            .bind(end.to_rfc3339())
+            .fetch_all(pool)
+            .await
+        })
+        .context("Failed to list DEX liquidity snapshots")?;
+
+        rows.into_iter().map(SnapshotRow::into_snapshot).collect()
+    }
+
+    /// Asset pairs of the synced liquidity pools that traded in the last day, as
+    /// `native` or `CODE:ISSUER` ids, by 24h volume, highest first
+    pub async fn fetch_traded_pairs(&self, limit: i64) -> Result<Vec<(String, String)>> {
+        let rows: Vec<(String, Option<String>, String, Option<String>)> =
+            with_pool!(&self.pool, |pool| {
+                sqlx::query_as(
+                    r#"
+                SELECT reserve_a_asset_code, reserve_a_asset_issuer,
+                       reserve_b_asset_code, reserve_b_asset_issuer
+                FROM liquidity_pools
+                WHERE trade_count_24h > 0
+                GROUP BY reserve_a_asset_code, reserve_a_asset_issuer,
+                         reserve_b_asset_code, reserve_b_asset_issuer
+                ORDER BY SUM(volume_24h_usd) DESC
+                LIMIT $1
+                "#,
+                )
+                .bind(limit)
+                .fetch_all(pool)
+                .await
+            })
+            .context("Failed to load traded liquidity pool pairs")?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(code_a, issuer_a, code_b, issuer_b)| {
+                (
+                    corridor_asset_id(&code_a, issuer_a.as_deref().unwrap_or_default()),
+                    corridor_asset_id(&code_b, issuer_b.as_deref().unwrap_or_default()),
+                )
+            })
+            .collect())
+    }
+
+    /// Delete snapshots captured before `cutoff`, returning how many were removed
+    pub async fn delete_snapshots_before(&self, cutoff: DateTime<Utc>) -> Result<u64> {
+        let result = with_pool!(&self.pool, |pool| {
+            sqlx::query("DELETE FROM dex_liquidity_snapshots WHERE captured_at < $1")
+                .bind(cutoff.to_rfc3339())
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+        })
+        .context("Failed to prune DEX liquidity snapshots")?;
+
+        Ok(result)
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct SnapshotRow {
+    id: String,
+    corridor_key: String,
+    base_asset: String,
+    counter_asset: String,
+    mid_price: f64,
+    spread_bps: Option<f64>,
+    base_usd_price: Option<f64>,
+    book_levels: i64,
+    pool_count: i64,
+    depth_curve: String,
+    captured_at: String,
+}
+
+impl SnapshotRow {
+    fn into_snapshot(self) -> Result<LiquiditySnapshot> {
+        Ok(LiquiditySnapshot {
+            curve: serde_json::from_str(&self.depth_curve)
+                .context("Failed to decode depth curve")?,
+            captured_at: DateTime::parse_from_rfc3339(&self.captured_at)
+                .context("Invalid snapshot capture time")?
+                .with_timezone(&Utc),
+            id: self.id,
+            corridor_key: self.corridor_key,
+            base_asset: self.base_asset,
+            counter_asset: self.counter_asset,
+            mid_price: self.mid_price,
+            spread_bps: self.spread_bps,
+            base_usd_price: self.base_usd_price,
+            book_levels: self.book_levels,
+            pool_count: self.pool_count,
+        })
+    }
+}
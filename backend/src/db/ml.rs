@@ -0,0 +1,523 @@
+use crate::database::DbPool;
+use crate::with_pool;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+
+use crate::ml::{
+    payment_corridor_key, LabelledPayment, ModelScore, ModelStatus, PaymentSuccessModel,
+    PredictionFeatures, RegisteredModel,
+};
+
+/// Storage for machine learning training data, the model registry and logged predictions.
+///
+/// Payments carry no outcome of their own, so they are labelled with the `successful`
+/// flag of their parent transaction when ledger ingestion has indexed it. Horizon only
+/// returns payments from applied operations, so a payment without an indexed
+/// transaction is counted as successful. Failed payments come from ledger ingestion,
+/// which stores the payment operations of failed transactions in `ledger_payments`.
+pub struct MlDb {
+    pool: DbPool,
+}
+
+impl MlDb {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Fetch the most recent `limit` payments made in `[start, end]`, oldest first.
+    ///
+    /// Payment operations of failed ledger-ingested transactions are included as
+    /// failures unless Horizon already returned the transaction's payments.
+    pub async fn fetch_labelled_payments(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<LabelledPayment>> {
+        let rows = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, LabelledPaymentRow>(
+                r#"
+                SELECT p.asset_code, p.asset_issuer, p.amount, p.created_at, t.successful
+                FROM payments p
+                LEFT JOIN transactions t ON t.hash = p.transaction_hash
+                WHERE p.created_at >= $1 AND p.created_at <= $2
+                ORDER BY p.created_at DESC
+                LIMIT $3
+                "#,
+            )
+            .bind(start)
+            .bind(end)
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+        })
+        .context("Failed to fetch labelled payments")?;
+
+        let failed = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, FailedPaymentRow>(
+                r#"
+                SELECT lp.asset_code, lp.asset_issuer, lp.amount, l.close_time
+                FROM ledger_payments lp
+                JOIN transactions t ON t.hash = lp.transaction_hash
+                JOIN ledgers l ON l.sequence = lp.ledger_sequence
+                WHERE t.successful = FALSE
+                  AND l.close_time >= $1 AND l.close_time <= $2
+                  AND NOT EXISTS (
+                      SELECT 1 FROM payments p WHERE p.transaction_hash = lp.transaction_hash
+                  )
+                ORDER BY l.close_time DESC
+                LIMIT $3
+                "#,
+            )
+            .bind(start)
+            .bind(end)
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+        })
+        .context("Failed to fetch failed ledger payments")?;
+
+        let mut payments: Vec<LabelledPayment> = rows
+            .into_iter()
+            .map(|row| LabelledPayment {
+                corridor_key: payment_corridor_key(
+                    row.asset_code.as_deref().unwrap_or("XLM"),
+                    row.asset_issuer.as_deref().unwrap_or("native"),
+                ),
+                amount: row.amount,
+                timestamp: row.created_at,
+                successful: row.successful.unwrap_or(true),
+            })
+            .chain(failed.into_iter().map(|row| {
+                LabelledPayment {
+                    corridor_key: payment_corridor_key(
+                        row.asset_code.as_deref().unwrap_or("XLM"),
+                        row.asset_issuer.as_deref().unwrap_or("native"),
+                    ),
+                    amount: row
+                        .amount
+                        .as_deref()
+                        .and_then(|amount| amount.parse().ok())
+                        .unwrap_or(0.0),
+                    timestamp: row.close_time,
+                    successful: false,
+                }
+            }))
+            .collect();
+
+        payments.sort_by_key(|payment| payment.timestamp);
+        let excess = payments.len().saturating_sub(limit.max(0) as usize);
+        payments.drain(..excess);
+        Ok(payments)
+    }
+
+    /// Count payments of one asset made in `[start, end)` and how many of them
+    /// succeeded, counting failed ledger-ingested payments the same way as
+    /// [`Self::fetch_labelled_payments`]
+    pub async fn count_recent_outcomes(
+        &self,
+        asset_code: &str,
+        asset_issuer: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<(u64, u64)> {
+        let (total, successful, failed): (i64, i64, i64) =
+            with_pool!(&self.pool, |pool| {
+                sqlx::query_as(
+                    r#"
+                SELECT
+                    CAST(COUNT(*) AS BIGINT),
+                    CAST(COALESCE(SUM(CASE WHEN t.successful = FALSE THEN 0 ELSE 1 END), 0) AS BIGINT),
+                    (
+                        SELECT CAST(COUNT(*) AS BIGINT)
+                        FROM ledger_payments lp
+                        JOIN transactions lt ON lt.hash = lp.transaction_hash
+                        JOIN ledgers l ON l.sequence = lp.ledger_sequence
+                        WHERE lt.successful = FALSE
+                          AND COALESCE(lp.asset_code, 'XLM') = $1
+                          AND COALESCE(lp.asset_issuer, 'native') = $2
+                          AND l.close_time >= $3 AND l.close_time < $4
+                          AND NOT EXISTS (
+                              SELECT 1 FROM payments fp WHERE fp.transaction_hash = lp.transaction_hash
+                          )
+                    )
+                FROM payments p
+                LEFT JOIN transactions t ON t.hash = p.transaction_hash
+                WHERE COALESCE(p.asset_code, 'XLM') = $1
+                  AND COALESCE(p.asset_issuer, 'native') = $2
+                  AND p.created_at >= $3 AND p.created_at < $4
+                "#,
+                )
+                .bind(asset_code)
+                .bind(asset_issuer)
+                .bind(start)
+                .bind(end)
+                .fetch_one(pool)
+                .await
+            })
+            .context("Failed to count recent payment outcomes")?;
+
+        Ok(((total + failed).max(0) as u64, successful.max(0) as u64))
+    }
+
+    /// Register `model` as the next version of `model_name`, as a candidate, and return
+    /// that version
+    pub async fn save_model(&self, model_name: &str, model: &PaymentSuccessModel) -> Result<i64> {
+        let latest: Option<i64> = with_pool!(&self.pool, |pool| {
+            sqlx::query_scalar(
+                "SELECT CAST(MAX(version) AS BIGINT) FROM ml_models WHERE model_name = $1",
+            )
+            .bind(model_name)
+            .fetch_one(pool)
+            .await
+        })
+        .context("Failed to read latest model version")?;
+        let version = latest.unwrap_or(0) + 1;
+
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO ml_models (
+                    id, model_name, version, artifact, feature_schema, metrics,
+                    training_samples, trained_at
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                "#,
+            )
+            .bind(uuid::Uuid::new_v4().to_string())
+            .bind(model_name)
+            .bind(version)
+            .bind(serde_json::to_string(&model.model)?)
+            .bind(serde_json::to_string(&model.feature_names)?)
+            .bind(serde_json::to_string(&model.metrics)?)
+            .bind(model.training_samples as i64)
+            .bind(model.trained_at.to_rfc3339())
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
+        .context("Failed to save model")?;
+
+        Ok(version)
+    }
+
+    /// Move one version of `model_name` to `status`, retiring the previously active
+    /// version when promoting. Returns `false` if the version does not exist.
+    pub async fn set_model_status(
+        &self,
+        model_name: &str,
+        version: i64,
+        status: ModelStatus,
+        now: DateTime<Utc>,
+    ) -> Result<bool> {
+        with_pool!(&self.pool, |pool| {
+            let mut tx = pool.begin().await?;
+
+            if status == ModelStatus::Active {
+                sqlx::query(
+                    r#"
+                    UPDATE ml_models SET status = $1, status_changed_at = $2
+                    WHERE model_name = $3 AND status = $4 AND version <> $5
+                    "#,
+                )
+                .bind(ModelStatus::Retired.as_str())
+                .bind(now.to_rfc3339())
+                .bind(model_name)
+                .bind(ModelStatus::Active.as_str())
+                .bind(version)
+                .execute(&mut *tx)
+                .await
+                .context("Failed to retire active model")?;
+            }
+
+            let result = sqlx::query(
+                r#"
+                UPDATE ml_models SET status = $1, status_changed_at = $2
+                WHERE model_name = $3 AND version = $4
+                "#,
+            )
+            .bind(status.as_str())
+            .bind(now.to_rfc3339())
+            .bind(model_name)
+            .bind(version)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to update model status")?;
+
+            if result.rows_affected() == 0 {
+                tx.rollback().await?;
+                return Ok(false);
+            }
+            tx.commit().await?;
+        });
+        Ok(true)
+    }
+
+    /// Load the active and shadow versions of `model_name`
+    pub async fn load_serving_models(
+        &self,
+        model_name: &str,
+    ) -> Result<Vec<(ModelStatus, PaymentSuccessModel)>> {
+        let rows = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, ModelRow>(
+                r#"
+                SELECT version, status, artifact, feature_schema, metrics, training_samples,
+                       trained_at, status_changed_at
+                FROM ml_models
+                WHERE model_name = $1 AND status IN ($2, $3)
+                ORDER BY version DESC
+                "#,
+            )
+            .bind(model_name)
+            .bind(ModelStatus::Active.as_str())
+            .bind(ModelStatus::Shadow.as_str())
+            .fetch_all(pool)
+            .await
+        })
+        .context("Failed to load serving models")?;
+
+        rows.into_iter()
+            .map(|row| Ok((row.status.parse()?, row.into_model()?)))
+            .collect()
+    }
+
+    /// Load one version of `model_name`
+    pub async fn load_model(
+        &self,
+        model_name: &str,
+        version: i64,
+    ) -> Result<Option<PaymentSuccessModel>> {
+        let row = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, ModelRow>(
+                r#"
+                SELECT version, status, artifact, feature_schema, metrics, training_samples,
+                       trained_at, status_changed_at
+                FROM ml_models
+                WHERE model_name = $1 AND version = $2
+                "#,
+            )
+            .bind(model_name)
+            .bind(version)
+            .fetch_optional(pool)
+            .await
+        })
+        .context("Failed to load model")?;
+
+        row.map(ModelRow::into_model).transpose()
+    }
+
+    /// Every registered version of `model_name`, newest first
+    pub async fn list_models(&self, model_name: &str) -> Result<Vec<RegisteredModel>> {
+        let rows = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, ModelRow>(
+                r#"
+                SELECT version, status, artifact, feature_schema, metrics, training_samples,
+                       trained_at, status_changed_at
+                FROM ml_models
+                WHERE model_name = $1
+                ORDER BY version DESC
+                "#,
+            )
+            .bind(model_name)
+            .fetch_all(pool)
+            .await
+        })
+        .context("Failed to list models")?;
+
+        rows.into_iter()
+            .map(|row| {
+                let status = row.status.parse()?;
+                let status_changed_at = row.status_changed_at.as_deref().and_then(parse_time);
+                let model = row.into_model()?;
+                Ok(RegisteredModel {
+                    model_name: model_name.to_string(),
+                    version: model.version,
+                    status,
+                    trained_at: model.trained_at,
+                    status_changed_at,
+                    training_samples: model.training_samples,
+                    feature_names: model.feature_names,
+                    metrics: model.metrics,
+                })
+            })
+            .collect()
+    }
+
+    /// Log the scores every serving model gave one prediction request
+    #[allow(clippy::too_many_arguments)]
+    pub async fn log_prediction(
+        &self,
+        prediction_id: &str,
+        model_name: &str,
+        asset_code: &str,
+        asset_issuer: &str,
+        amount_usd: f64,
+        features: &PredictionFeatures,
+        scores: &[ModelScore],
+        predicted_at: DateTime<Utc>,
+    ) -> Result<()> {
+        let features = serde_json::to_string(features)?;
+        with_pool!(&self.pool, |pool| {
+            let mut tx = pool.begin().await?;
+
+            for score in scores {
+                sqlx::query(
+                    r#"
+                    INSERT INTO ml_predictions (
+                        id, prediction_id, model_name, model_version, role, asset_code,
+                        asset_issuer, amount_usd, features, probability, predicted_at
+                    ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                    "#,
+                )
+                .bind(uuid::Uuid::new_v4().to_string())
+                .bind(prediction_id)
+                .bind(model_name)
+                .bind(score.version)
+                .bind(score.role.as_str())
+                .bind(asset_code)
+                .bind(asset_issuer)
+                .bind(amount_usd)
+                .bind(&features)
+                .bind(score.probability)
+                .bind(predicted_at.to_rfc3339())
+                .execute(&mut *tx)
+                .await
+                .context("Failed to log prediction")?;
+            }
+
+            tx.commit().await?;
+        });
+        Ok(())
+    }
+
+    /// Attach the hash of the transaction submitted for a prediction to every score of
+    /// it, so that [`Self::resolve_outcomes`] labels them once the transaction is
+    /// ingested. Predictions that already have an outcome are left as they are. Returns
+    /// the number of rows updated.
+    pub async fn record_transaction_hash(
+        &self,
+        prediction_id: &str,
+        transaction_hash: &str,
+    ) -> Result<u64> {
+        let result = with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                UPDATE ml_predictions SET transaction_hash = $1
+                WHERE prediction_id = $2 AND outcome IS NULL
+                "#,
+            )
+            .bind(transaction_hash)
+            .bind(prediction_id)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
+        .context("Failed to record prediction transaction")?;
+
+        Ok(result)
+    }
+
+    /// Label unresolved predictions whose transaction has been ingested, with the
+    /// transaction's result or, if only its payment is known, as successful
+    pub async fn resolve_outcomes(&self, now: DateTime<Utc>) -> Result<u64> {
+        let result = with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                UPDATE ml_predictions SET
+                    outcome = COALESCE(
+                        (SELECT t.successful FROM transactions t
+                         WHERE t.hash = ml_predictions.transaction_hash),
+                        TRUE
+                    ),
+                    outcome_at = $1
+                WHERE outcome IS NULL
+                  AND transaction_hash IS NOT NULL
+                  AND (
+                      EXISTS (SELECT 1 FROM transactions t
+                              WHERE t.hash = ml_predictions.transaction_hash)
+                      OR EXISTS (SELECT 1 FROM payments p
+                                 WHERE p.transaction_hash = ml_predictions.transaction_hash)
+                  )
+                "#,
+            )
+            .bind(now.to_rfc3339())
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
+        .context("Failed to resolve prediction outcomes")?;
+
+        Ok(result)
+    }
+
+    /// Fetch `(version, probability, outcome)` of every score logged since `since`
+    pub async fn fetch_logged_predictions(
+        &self,
+        model_name: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<(i64, f64, Option<bool>)>> {
+        with_pool!(&self.pool, |pool| {
+            sqlx::query_as(
+                r#"
+                SELECT model_version, probability, outcome
+                FROM ml_predictions
+                WHERE model_name = $1 AND predicted_at >= $2
+                "#,
+            )
+            .bind(model_name)
+            .bind(since.to_rfc3339())
+            .fetch_all(pool)
+            .await
+        })
+        .context("Failed to fetch logged predictions")
+    }
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|ts| ts.with_timezone(&Utc))
+}
+
+#[derive(sqlx::FromRow)]
+struct LabelledPaymentRow {
+    asset_code: Option<String>,
+    asset_issuer: Option<String>,
+    amount: f64,
+    created_at: DateTime<Utc>,
+    successful: Option<bool>,
+}
+
+#[derive(sqlx::FromRow)]
+struct FailedPaymentRow {
+    asset_code: Option<String>,
+    asset_issuer: Option<String>,
+    amount: Option<String>,
+    close_time: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct ModelRow {
+    version: i64,
+    status: String,
+    artifact: String,
+    feature_schema: String,
+    metrics: String,
+    training_samples: i64,
+    trained_at: String,
+    status_changed_at: Option<String>,
+}
+
+impl ModelRow {
+    fn into_model(self) -> Result<PaymentSuccessModel> {
+        Ok(PaymentSuccessModel {
+            version: self.version,
+            trained_at: parse_time(&self.trained_at).context("Invalid model training time")?,
+            feature_names: serde_json::from_str(&self.feature_schema)
+                .context("Failed to decode model feature schema")?,
+            training_samples: self.training_samples.max(0) as usize,
+            model: serde_json::from_str(&self.artifact).context("Failed to decode model")?,
+            metrics: serde_json::from_str(&self.metrics)
+                .context("Failed to decode model metrics")?,
+        })
+    }
+}
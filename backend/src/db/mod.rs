@@ -1,4 +1,9 @@
 pub mod aggregates;
 pub mod aggregation;
 pub mod alerts;
+pub mod anomalies;
+pub mod contract_events;
+pub mod dex;
+pub mod ml;
+pub mod rollups;
 pub mod schema;
@@ -0,0 +1,427 @@
+use crate::database::DbPool;
+use crate::with_pool;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+
+use crate::services::rollup::{CorridorRollup, CorridorSketches, Resolution};
+
+/// Storage for multi-resolution corridor rollups.
+///
+/// Hourly buckets live in `corridor_metrics_hourly`; coarser ones in
+/// `corridor_metrics_rollups`; distribution sketches of every resolution in
+/// `corridor_metric_sketches`. Bucket starts are stored as RFC 3339 text in both tables,
+/// which sorts chronologically, so range filters compare them as strings.
+pub struct RollupDb {
+    pool: DbPool,
+}
+
+/// Table and bucket column holding buckets of `resolution`
+fn bucket_source(resolution: Resolution) -> (&'static str, &'static str) {
+    match resolution {
+        Resolution::Hour => ("corridor_metrics_hourly", "hour_bucket"),
+        _ => ("corridor_metrics_rollups", "bucket_start"),
+    }
+}
+
+/// Extra condition restricting `corridor_metrics_rollups` to one resolution
+fn resolution_filter(resolution: Resolution) -> String {
+    match resolution {
+        Resolution::Hour => String::new(),
+        _ => format!("AND resolution = '{}'", resolution.as_str()),
+    }
+}
+
+impl RollupDb {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Fetch the buckets of `resolution` starting in `[start, end)`, optionally for a
+    /// single corridor
+    pub async fn fetch_buckets(
+        &self,
+        resolution: Resolution,
+        corridor_key: Option<&str>,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<CorridorRollup>> {
+        let (table, bucket_column) = bucket_source(resolution);
+        let source_buckets = match resolution {
+            Resolution::Hour => "1",
+            _ => "source_buckets",
+        };
+        let corridor_filter = if corridor_key.is_some() {
+            "AND corridor_key = $3"
+        } else {
+            ""
+        };
+
+        let query_str = format!(
+            r#"
+            SELECT
+                corridor_key,
+                asset_a_code,
+                asset_a_issuer,
+                asset_b_code,
+                asset_b_issuer,
+                {bucket_column} AS bucket_start,
+                total_transactions,
+                successful_transactions,
+                failed_transactions,
+                success_rate,
+                volume_usd,
+                avg_slippage_bps,
+                avg_settlement_latency_ms,
+                liquidity_depth_usd,
+                CAST({source_buckets} AS BIGINT) AS source_buckets
+            FROM {table}
+            WHERE {bucket_column} >= $1 AND {bucket_column} < $2
+            {resolution_filter}
+            {corridor_filter}
+            ORDER BY {bucket_column} ASC, corridor_key ASC
+            "#,
+            resolution_filter = resolution_filter(resolution),
+        );
+
+        let rows = with_pool!(&self.pool, |pool| {
+            let mut query = sqlx::query_as::<_, CorridorRollupRow>(&query_str)
+                .bind(start.to_rfc3339())
+                .bind(end.to_rfc3339());
+            if let Some(corridor_key) = corridor_key {
+                query = query.bind(corridor_key);
+            }
+            query.fetch_all(pool).await
+        })
+        .with_context(|| format!("Failed to fetch {} corridor buckets", resolution))?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| row.into_rollup(resolution))
+            .collect())
+    }
+
+    /// Start of the earliest bucket of `resolution` at or after `from` (or overall)
+    pub async fn earliest_bucket_from(
+        &self,
+        resolution: Resolution,
+        from: Option<DateTime<Utc>>,
+    ) -> Result<Option<DateTime<Utc>>> {
+        let (table, bucket_column) = bucket_source(resolution);
+        let query_str = format!(
+            r#"
+            SELECT MIN({bucket_column}) FROM {table}
+            WHERE {bucket_column} >= $1
+            {resolution_filter}
+            "#,
+            resolution_filter = resolution_filter(resolution),
+        );
+
+        let earliest: Option<String> = with_pool!(&self.pool, |pool| {
+            sqlx::query_scalar(&query_str)
+                .bind(from.map(|ts| ts.to_rfc3339()).unwrap_or_default())
+                .fetch_one(pool)
+                .await
+        })
+        .with_context(|| format!("Failed to find earliest {} bucket", resolution))?;
+
+        Ok(earliest.and_then(|s| parse_bucket(&s)))
+    }
+
+    /// Insert or replace one rolled-up bucket.
+    ///
+    /// Rollups are always recomputed in full from their source buckets, so an existing
+    /// row is overwritten rather than merged.
+    pub async fn upsert_rollup(&self, rollup: &CorridorRollup) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO corridor_metrics_rollups (
+                    resolution,
+                    corridor_key,
+                    asset_a_code,
+                    asset_a_issuer,
+                    asset_b_code,
+                    asset_b_issuer,
+                    bucket_start,
+                    total_transactions,
+                    successful_transactions,
+                    failed_transactions,
+                    success_rate,
+                    volume_usd,
+                    avg_slippage_bps,
+                    avg_settlement_latency_ms,
+                    liquidity_depth_usd,
+                    source_buckets,
+                    created_at,
+                    updated_at
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)
+                ON CONFLICT(resolution, corridor_key, bucket_start) DO UPDATE SET
+                    total_transactions = excluded.total_transactions,
+                    successful_transactions = excluded.successful_transactions,
+                    failed_transactions = excluded.failed_transactions,
+                    success_rate = excluded.success_rate,
+                    volume_usd = excluded.volume_usd,
+                    avg_slippage_bps = excluded.avg_slippage_bps,
+                    avg_settlement_latency_ms = excluded.avg_settlement_latency_ms,
+                    liquidity_depth_usd = excluded.liquidity_depth_usd,
+                    source_buckets = excluded.source_buckets,
+                    updated_at = excluded.updated_at
+                "#,
+            )
+            .bind(rollup.resolution.as_str())
+            .bind(&rollup.corridor_key)
+            .bind(&rollup.asset_a_code)
+            .bind(&rollup.asset_a_issuer)
+            .bind(&rollup.asset_b_code)
+            .bind(&rollup.asset_b_issuer)
+            .bind(rollup.bucket_start.to_rfc3339())
+            .bind(rollup.total_transactions)
+            .bind(rollup.successful_transactions)
+            .bind(rollup.failed_transactions)
+            .bind(rollup.success_rate)
+            .bind(rollup.volume_usd)
+            .bind(rollup.avg_slippage_bps)
+            .bind(rollup.avg_settlement_latency_ms)
+            .bind(rollup.liquidity_depth_usd)
+            .bind(rollup.source_buckets)
+            .bind(&now)
+            .bind(&now)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
+        .context("Failed to upsert corridor rollup")?;
+
+        Ok(())
+    }
+
+    /// Fetch the distribution sketches of `resolution` starting in `[start, end)`,
+    /// optionally for a single corridor
+    pub async fn fetch_sketches(
+        &self,
+        resolution: Resolution,
+        corridor_key: Option<&str>,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<CorridorSketches>> {
+        let corridor_filter = if corridor_key.is_some() {
+            "AND corridor_key = $4"
+        } else {
+            ""
+        };
+        let query_str = format!(
+            r#"
+            SELECT corridor_key, bucket_start, latency_sketch, amount_sketch
+            FROM corridor_metric_sketches
+            WHERE resolution = $1 AND bucket_start >= $2 AND bucket_start < $3
+            {corridor_filter}
+            ORDER BY bucket_start ASC, corridor_key ASC
+            "#
+        );
+
+        let rows = with_pool!(&self.pool, |pool| {
+            let mut query = sqlx::query_as::<_, CorridorSketchesRow>(&query_str)
+                .bind(resolution.as_str())
+                .bind(start.to_rfc3339())
+                .bind(end.to_rfc3339());
+            if let Some(corridor_key) = corridor_key {
+                query = query.bind(corridor_key);
+            }
+            query.fetch_all(pool).await
+        })
+        .with_context(|| format!("Failed to fetch {} corridor sketches", resolution))?;
+
+        rows.into_iter()
+            .filter_map(|row| {
+                let bucket_start = parse_bucket(&row.bucket_start)?;
+                Some((row, bucket_start))
+            })
+            .map(|(row, bucket_start)| {
+                Ok(CorridorSketches {
+                    resolution,
+                    corridor_key: row.corridor_key,
+                    bucket_start,
+                    latency_ms: serde_json::from_str(&row.latency_sketch)
+                        .context("Failed to decode latency sketch")?,
+                    amount: serde_json::from_str(&row.amount_sketch)
+                        .context("Failed to decode amount sketch")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Insert or replace the distribution sketches of one bucket
+    pub async fn upsert_sketches(&self, sketches: &CorridorSketches) -> Result<()> {
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO corridor_metric_sketches (
+                    resolution, corridor_key, bucket_start, latency_sketch, amount_sketch, updated_at
+                ) VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT(resolution, corridor_key, bucket_start) DO UPDATE SET
+                    latency_sketch = excluded.latency_sketch,
+                    amount_sketch = excluded.amount_sketch,
+                    updated_at = excluded.updated_at
+                "#,
+            )
+            .bind(sketches.resolution.as_str())
+            .bind(&sketches.corridor_key)
+            .bind(sketches.bucket_start.to_rfc3339())
+            .bind(serde_json::to_string(&sketches.latency_ms)?)
+            .bind(serde_json::to_string(&sketches.amount)?)
+            .bind(Utc::now().to_rfc3339())
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
+        .context("Failed to upsert corridor sketches")?;
+
+        Ok(())
+    }
+
+    /// Start of the first bucket of `resolution` that has not been finalized yet
+    pub async fn get_watermark(&self, resolution: Resolution) -> Result<Option<DateTime<Utc>>> {
+        let watermark: Option<String> = with_pool!(&self.pool, |pool| {
+            sqlx::query_scalar(
+                r#"
+                SELECT completed_until FROM rollup_watermarks WHERE resolution = $1
+                "#,
+            )
+            .bind(resolution.as_str())
+            .fetch_optional(pool)
+            .await
+        })
+        .context("Failed to get rollup watermark")?;
+
+        Ok(watermark.and_then(|s| parse_bucket(&s)))
+    }
+
+    /// Record that every bucket of `resolution` before `completed_until` is final
+    pub async fn set_watermark(
+        &self,
+        resolution: Resolution,
+        completed_until: DateTime<Utc>,
+    ) -> Result<()> {
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO rollup_watermarks (resolution, completed_until, updated_at)
+                VALUES ($1, $2, $3)
+                ON CONFLICT(resolution) DO UPDATE SET
+                    completed_until = excluded.completed_until,
+                    updated_at = excluded.updated_at
+                "#,
+            )
+            .bind(resolution.as_str())
+            .bind(completed_until.to_rfc3339())
+            .bind(Utc::now().to_rfc3339())
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
+        .context("Failed to set rollup watermark")?;
+
+        Ok(())
+    }
+
+    /// Delete buckets and sketches of `resolution` starting before `cutoff`
+    pub async fn delete_buckets_before(
+        &self,
+        resolution: Resolution,
+        cutoff: DateTime<Utc>,
+    ) -> Result<u64> {
+        let (table, bucket_column) = bucket_source(resolution);
+        let query_str = format!(
+            r#"
+            DELETE FROM {table}
+            WHERE {bucket_column} < $1
+            {resolution_filter}
+            "#,
+            resolution_filter = resolution_filter(resolution),
+        );
+
+        let pruned = with_pool!(&self.pool, |pool| {
+            sqlx::query(&query_str)
+                .bind(cutoff.to_rfc3339())
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+        })
+        .with_context(|| format!("Failed to prune {} corridor buckets", resolution))?;
+
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                DELETE FROM corridor_metric_sketches
+                WHERE resolution = $1 AND bucket_start < $2
+                "#,
+            )
+            .bind(resolution.as_str())
+            .bind(cutoff.to_rfc3339())
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
+        .with_context(|| format!("Failed to prune {} corridor sketches", resolution))?;
+
+        Ok(pruned)
+    }
+}
+
+fn parse_bucket(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|ts| ts.with_timezone(&Utc))
+}
+
+#[derive(sqlx::FromRow)]
+struct CorridorSketchesRow {
+    corridor_key: String,
+    bucket_start: String,
+    latency_sketch: String,
+    amount_sketch: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct CorridorRollupRow {
+    corridor_key: String,
+    asset_a_code: String,
+    asset_a_issuer: String,
+    asset_b_code: String,
+    asset_b_issuer: String,
+    bucket_start: String,
+    total_transactions: i64,
+    successful_transactions: i64,
+    failed_transactions: i64,
+    success_rate: f64,
+    volume_usd: f64,
+    avg_slippage_bps: f64,
+    avg_settlement_latency_ms: Option<i32>,
+    liquidity_depth_usd: f64,
+    source_buckets: i64,
+}
+
+impl CorridorRollupRow {
+    fn into_rollup(self, resolution: Resolution) -> Option<CorridorRollup> {
+        Some(CorridorRollup {
+            resolution,
+            bucket_start: parse_bucket(&self.bucket_start)?,
+            corridor_key: self.corridor_key,
+            asset_a_code: self.asset_a_code,
+            asset_a_issuer: self.asset_a_issuer,
+            asset_b_code: self.asset_b_code,
+            asset_b_issuer: self.asset_b_issuer,
+            total_transactions: self.total_transactions,
+            successful_transactions: self.successful_transactions,
+            failed_transactions: self.failed_transactions,
+            success_rate: self.success_rate,
+            volume_usd: self.volume_usd,
+            avg_slippage_bps: self.avg_slippage_bps,
+            avg_settlement_latency_ms: self.avg_settlement_latency_ms,
+            liquidity_depth_usd: self.liquidity_depth_usd,
+            source_buckets: self.source_buckets,
+        })
+    }
+}
@@ -7,6 +7,8 @@
 use anyhow::Result;
 use std::env;
 
+use crate::database::DatabaseBackend;
+
 /// Required environment variables that must be set
 const REQUIRED_VARS: &[&str] = &["DATABASE_URL", "ENCRYPTION_KEY", "JWT_SECRET"];
 
@@ -43,6 +45,13 @@ pub fn validate_env() -> Result<()> {
         }
     }
 
+    // The URL itself is not echoed since it may carry credentials
+    if let Ok(db_url) = env::var("DATABASE_URL") {
+        if let Err(e) = DatabaseBackend::from_url(&db_url) {
+            errors.push(e.to_string());
+        }
+    }
+
     if !errors.is_empty() {
         anyhow::bail!(
             "Environment configuration errors:\n  - {}",
@@ -120,7 +129,7 @@ fn log_var(name: &str) {
 
 /// Sanitize database URL to hide credentials
 fn sanitize_database_url(url: &str) -> String {
-    if url.starts_with("sqlite:") {
+    if matches!(DatabaseBackend::from_url(url), Ok(DatabaseBackend::Sqlite)) {
         return url.to_string();
     }
 
@@ -1,30 +1,34 @@
 // GDPR Service - Business logic for GDPR compliance
 
+use crate::database::DbPool;
 use crate::error::ApiError;
 use crate::gdpr::models::*;
+use crate::with_pool;
 use chrono::{Duration, Utc};
-use sqlx::{Pool, Sqlite};
+
 use std::sync::Arc;
 use uuid::Uuid;
 
 /// GDPR Service for handling data export, deletion, and consent management
 pub struct GdprService {
-    db: Pool<Sqlite>,
+    db: DbPool,
 }
 
 impl GdprService {
-    pub fn new(db: Pool<Sqlite>) -> Self {
+    pub fn new(db: DbPool) -> Self {
         Self { db }
     }
 
     /// Get all consents for a user
     pub async fn get_user_consents(&self, user_id: &str) -> Result<Vec<ConsentResponse>, AppError> {
-        let consents = sqlx::query_as::<_, UserConsent>(
-            "SELECT * FROM user_consents WHERE user_id = ? ORDER BY consent_type",
-        )
-        .bind(user_id)
-        .fetch_all(&self.db)
-        .await
+        let consents = with_pool!(&self.db, |pool| {
+            sqlx::query_as::<_, UserConsent>(
+                "SELECT * FROM user_consents WHERE user_id = $1 ORDER BY consent_type",
+            )
+            .bind(user_id)
+            .fetch_all(pool)
+            .await
+        })
         .map_err(AppError::Database)?;
 
         let mut responses = Vec::new();
@@ -64,13 +68,15 @@ impl GdprService {
         user_agent: Option<String>,
     ) -> Result<ConsentResponse, AppError> {
         let consent_type = request.consent_type.clone();
-        let old_consent_given = sqlx::query_as::<_, UserConsent>(
-            "SELECT * FROM user_consents WHERE user_id = ? AND consent_type = ?",
-        )
-        .bind(user_id)
-        .bind(&request.consent_type)
-        .fetch_optional(&self.db)
-        .await
+        let old_consent_given = with_pool!(&self.db, |pool| {
+            sqlx::query_as::<_, UserConsent>(
+                "SELECT * FROM user_consents WHERE user_id = $1 AND consent_type = $2",
+            )
+            .bind(user_id)
+            .bind(&request.consent_type)
+            .fetch_optional(pool)
+            .await
+        })
         .map_err(AppError::Database)?
         .map(|c| c.consent_given);
 
@@ -78,48 +84,54 @@ impl GdprService {
         let consent_version = request.consent_version.unwrap_or_else(|| "1.0".to_string());
 
         // Upsert the consent
-        sqlx::query(
-            "INSERT INTO user_consents (id, user_id, consent_type, consent_given, consent_version, ip_address, user_agent, granted_at, revoked_at, created_at, updated_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-             ON CONFLICT(user_id, consent_type) DO UPDATE SET
-                consent_given = excluded.consent_given,
-                consent_version = excluded.consent_version,
-                granted_at = CASE WHEN excluded.consent_given = 1 AND user_consents.consent_given = 0 THEN ? ELSE user_consents.granted_at END,
-                revoked_at = CASE WHEN excluded.consent_given = 0 AND user_consents.consent_given = 1 THEN ? ELSE user_consents.revoked_at END,
-                updated_at = ?"
-        )
-        .bind(Uuid::new_v4().to_string())
-        .bind(user_id)
-        .bind(&request.consent_type)
-        .bind(request.consent_given)
-        .bind(&consent_version)
-        .bind(&ip_address)
-        .bind(&user_agent)
-        .bind(if request.consent_given { Some(now.clone()) } else { None })
-        .bind(if !request.consent_given { Some(now.clone()) } else { None })
-        .bind(&now)
-        .bind(&now)
-        .bind(&now)
-        .execute(&self.db)
-        .await
+        with_pool!(&self.db, |pool| {
+            sqlx::query(
+                "INSERT INTO user_consents (id, user_id, consent_type, consent_given, consent_version, ip_address, user_agent, granted_at, revoked_at, created_at, updated_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                 ON CONFLICT(user_id, consent_type) DO UPDATE SET
+                    consent_given = excluded.consent_given,
+                    consent_version = excluded.consent_version,
+                    granted_at = CASE WHEN excluded.consent_given = TRUE AND user_consents.consent_given = FALSE THEN $12 ELSE user_consents.granted_at END,
+                    revoked_at = CASE WHEN excluded.consent_given = FALSE AND user_consents.consent_given = TRUE THEN $13 ELSE user_consents.revoked_at END,
+                    updated_at = $14"
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(user_id)
+            .bind(&request.consent_type)
+            .bind(request.consent_given)
+            .bind(&consent_version)
+            .bind(&ip_address)
+            .bind(&user_agent)
+            .bind(if request.consent_given { Some(now.clone()) } else { None })
+            .bind(if !request.consent_given { Some(now.clone()) } else { None })
+            .bind(&now)
+            .bind(&now)
+            .bind(&now)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .map_err(AppError::Database)?;
 
         // Log the consent change in audit log
-        sqlx::query(
-            "INSERT INTO consent_audit_log (id, user_id, consent_type, action, old_value, new_value, ip_address, user_agent, created_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"
-        )
-        .bind(Uuid::new_v4().to_string())
-        .bind(user_id)
-        .bind(&consent_type)
-        .bind(if request.consent_given { "granted" } else { "revoked" })
-        .bind(old_consent_given)
-        .bind(Some(request.consent_given))
-        .bind(&ip_address)
-        .bind(&user_agent)
-        .bind(&now)
-        .execute(&self.db)
-        .await
+        with_pool!(&self.db, |pool| {
+            sqlx::query(
+                "INSERT INTO consent_audit_log (id, user_id, consent_type, action, old_value, new_value, ip_address, user_agent, created_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)"
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(user_id)
+            .bind(&consent_type)
+            .bind(if request.consent_given { "granted" } else { "revoked" })
+            .bind(old_consent_given)
+            .bind(Some(request.consent_given))
+            .bind(&ip_address)
+            .bind(&user_agent)
+            .bind(&now)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .map_err(AppError::Database)?;
 
         Ok(ConsentResponse {
@@ -177,20 +189,23 @@ impl GdprService {
         // Generate a secure download token
         let download_token = Uuid::new_v4().to_string();
 
-        sqlx::query(
-            "INSERT INTO data_export_requests (id, user_id, status, requested_data_types, export_format, requested_at, expires_at, download_token)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
-        )
-        .bind(&id)
-        .bind(user_id)
-        .bind("pending")
-        .bind(&data_types)
-        .bind(&export_format)
-        .bind(&now)
-        .bind(&expires_at)
-        .bind(&download_token)
-        .execute(&self.db)
-        .await
+        with_pool!(&self.db, |pool| {
+            sqlx::query(
+                "INSERT INTO data_export_requests (id, user_id, status, requested_data_types, export_format, requested_at, expires_at, download_token)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"
+            )
+            .bind(&id)
+            .bind(user_id)
+            .bind("pending")
+            .bind(&data_types)
+            .bind(&export_format)
+            .bind(&now)
+            .bind(&expires_at)
+            .bind(&download_token)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .map_err(AppError::Database)?;
 
         Ok(ExportRequestResponse {
@@ -208,13 +223,15 @@ impl GdprService {
         user_id: &str,
         request_id: &str,
     ) -> Result<ExportRequestResponse, AppError> {
-        let request = sqlx::query_as::<_, DataExportRequest>(
-            "SELECT * FROM data_export_requests WHERE id = ? AND user_id = ?",
-        )
-        .bind(request_id)
-        .bind(user_id)
-        .fetch_optional(&self.db)
-        .await
+        let request = with_pool!(&self.db, |pool| {
+            sqlx::query_as::<_, DataExportRequest>(
+                "SELECT * FROM data_export_requests WHERE id = $1 AND user_id = $2",
+            )
+            .bind(request_id)
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await
+        })
         .map_err(AppError::Database)?
         .ok_or(AppError::NotFound("Export request not found".to_string()))?;
 
@@ -241,12 +258,14 @@ impl GdprService {
         &self,
         user_id: &str,
     ) -> Result<Vec<ExportRequestResponse>, AppError> {
-        let requests = sqlx::query_as::<_, DataExportRequest>(
-            "SELECT * FROM data_export_requests WHERE user_id = ? ORDER BY requested_at DESC",
-        )
-        .bind(user_id)
-        .fetch_all(&self.db)
-        .await
+        let requests = with_pool!(&self.db, |pool| {
+            sqlx::query_as::<_, DataExportRequest>(
+                "SELECT * FROM data_export_requests WHERE user_id = $1 ORDER BY requested_at DESC",
+            )
+            .bind(user_id)
+            .fetch_all(pool)
+            .await
+        })
         .map_err(AppError::Database)?;
 
         let mut responses = Vec::new();
@@ -288,20 +307,23 @@ impl GdprService {
         let delete_all_data = request.delete_all_data.unwrap_or(true);
         let data_types = request.data_types.map(|d| d.join(","));
 
-        sqlx::query(
-            "INSERT INTO data_deletion_requests (id, user_id, status, reason, delete_all_data, data_types_to_delete, requested_at, confirmation_token)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
-        )
-        .bind(&id)
-        .bind(user_id)
-        .bind("pending")
-        .bind(&request.reason)
-        .bind(delete_all_data)
-        .bind(&data_types)
-        .bind(&now)
-        .bind(&confirmation_token)
-        .execute(&self.db)
-        .await
+        with_pool!(&self.db, |pool| {
+            sqlx::query(
+                "INSERT INTO data_deletion_requests (id, user_id, status, reason, delete_all_data, data_types_to_delete, requested_at, confirmation_token)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"
+            )
+            .bind(&id)
+            .bind(user_id)
+            .bind("pending")
+            .bind(&request.reason)
+            .bind(delete_all_data)
+            .bind(&data_types)
+            .bind(&now)
+            .bind(&confirmation_token)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .map_err(AppError::Database)?;
 
         Ok(DeletionRequestResponse {
@@ -327,29 +349,34 @@ impl GdprService {
             .unwrap()
             .to_rfc3339();
 
-        let result = sqlx::query(
-            "UPDATE data_deletion_requests SET status = ?, scheduled_deletion_at = ? WHERE confirmation_token = ? AND status = ?"
-        )
-        .bind("scheduled")
-        .bind(&scheduled_deletion)
-        .bind(confirmation_token)
-        .bind("pending")
-        .execute(&self.db)
-        .await
+        let result = with_pool!(&self.db, |pool| {
+            sqlx::query(
+                "UPDATE data_deletion_requests SET status = $1, scheduled_deletion_at = $2 WHERE confirmation_token = $3 AND status = $4"
+            )
+            .bind("scheduled")
+            .bind(&scheduled_deletion)
+            .bind(confirmation_token)
+            .bind("pending")
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .map_err(AppError::Database)?;
 
-        if result.rows_affected() == 0 {
+        if result == 0 {
             return Err(AppError::NotFound(
                 "Deletion request not found or already processed".to_string(),
             ));
         }
 
-        let request = sqlx::query_as::<_, DataDeletionRequest>(
-            "SELECT * FROM data_deletion_requests WHERE confirmation_token = ?",
-        )
-        .bind(confirmation_token)
-        .fetch_one(&self.db)
-        .await
+        let request = with_pool!(&self.db, |pool| {
+            sqlx::query_as::<_, DataDeletionRequest>(
+                "SELECT * FROM data_deletion_requests WHERE confirmation_token = $1",
+            )
+            .bind(confirmation_token)
+            .fetch_one(pool)
+            .await
+        })
         .map_err(AppError::Database)?;
 
         Ok(DeletionRequestResponse {
@@ -370,31 +397,36 @@ impl GdprService {
     ) -> Result<DeletionRequestResponse, AppError> {
         let now = Utc::now().to_rfc3339();
 
-        let result = sqlx::query(
-            "UPDATE data_deletion_requests SET status = ?, cancelled_at = ? WHERE id = ? AND user_id = ? AND status IN (?, ?)"
-        )
-        .bind("cancelled")
-        .bind(&now)
-        .bind(request_id)
-        .bind(user_id)
-        .bind("pending")
-        .bind("scheduled")
-        .execute(&self.db)
-        .await
+        let result = with_pool!(&self.db, |pool| {
+            sqlx::query(
+                "UPDATE data_deletion_requests SET status = $1, cancelled_at = $2 WHERE id = $3 AND user_id = $4 AND status IN ($5, $6)"
+            )
+            .bind("cancelled")
+            .bind(&now)
+            .bind(request_id)
+            .bind(user_id)
+            .bind("pending")
+            .bind("scheduled")
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .map_err(AppError::Database)?;
 
-        if result.rows_affected() == 0 {
+        if result == 0 {
             return Err(AppError::NotFound(
                 "Deletion request not found or cannot be cancelled".to_string(),
             ));
         }
 
-        let request = sqlx::query_as::<_, DataDeletionRequest>(
-            "SELECT * FROM data_deletion_requests WHERE id = ?",
-        )
-        .bind(request_id)
-        .fetch_one(&self.db)
-        .await
+        let request = with_pool!(&self.db, |pool| {
+            sqlx::query_as::<_, DataDeletionRequest>(
+                "SELECT * FROM data_deletion_requests WHERE id = $1",
+            )
+            .bind(request_id)
+            .fetch_one(pool)
+            .await
+        })
         .map_err(AppError::Database)?;
 
         Ok(DeletionRequestResponse {
@@ -413,13 +445,15 @@ impl GdprService {
         user_id: &str,
         request_id: &str,
     ) -> Result<DeletionRequestResponse, AppError> {
-        let request = sqlx::query_as::<_, DataDeletionRequest>(
-            "SELECT * FROM data_deletion_requests WHERE id = ? AND user_id = ?",
-        )
-        .bind(request_id)
-        .bind(user_id)
-        .fetch_optional(&self.db)
-        .await
+        let request = with_pool!(&self.db, |pool| {
+            sqlx::query_as::<_, DataDeletionRequest>(
+                "SELECT * FROM data_deletion_requests WHERE id = $1 AND user_id = $2",
+            )
+            .bind(request_id)
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await
+        })
         .map_err(AppError::Database)?
         .ok_or(AppError::NotFound("Deletion request not found".to_string()))?;
 
@@ -438,12 +472,14 @@ impl GdprService {
         &self,
         user_id: &str,
     ) -> Result<Vec<DeletionRequestResponse>, AppError> {
-        let requests = sqlx::query_as::<_, DataDeletionRequest>(
-            "SELECT * FROM data_deletion_requests WHERE user_id = ? ORDER BY requested_at DESC",
-        )
-        .bind(user_id)
-        .fetch_all(&self.db)
-        .await
+        let requests = with_pool!(&self.db, |pool| {
+            sqlx::query_as::<_, DataDeletionRequest>(
+                "SELECT * FROM data_deletion_requests WHERE user_id = $1 ORDER BY requested_at DESC",
+            )
+            .bind(user_id)
+            .fetch_all(pool)
+            .await
+        })
         .map_err(AppError::Database)?;
 
         let mut responses = Vec::new();
@@ -465,32 +501,39 @@ impl GdprService {
     pub async fn get_gdpr_summary(&self, user_id: &str) -> Result<GdprSummary, AppError> {
         let consents = self.get_user_consents(user_id).await?;
 
-        let pending_exports: i32 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM data_export_requests WHERE user_id = ? AND status IN (?, ?)",
-        )
-        .bind(user_id)
-        .bind("pending")
-        .bind("processing")
-        .fetch_one(&self.db)
-        .await
+        let pending_exports: i32 = with_pool!(&self.db, |pool| {
+            sqlx::query_scalar(
+                "SELECT CAST(COUNT(*) AS INTEGER) FROM data_export_requests WHERE user_id = $1 AND status IN ($2, $3)",
+            )
+            .bind(user_id)
+            .bind("pending")
+            .bind("processing")
+            .fetch_one(pool)
+            .await
+        })
         .map_err(AppError::Database)?;
 
-        let pending_deletions: i32 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM data_deletion_requests WHERE user_id = ? AND status IN (?, ?)",
-        )
-        .bind(user_id)
-        .bind("pending")
-        .bind("scheduled")
-        .fetch_one(&self.db)
-        .await
+        let pending_deletions: i32 = with_pool!(&self.db, |pool| {
+            sqlx::query_scalar(
+                "SELECT CAST(COUNT(*) AS INTEGER) FROM data_deletion_requests WHERE user_id = $1 AND status IN ($2, $3)",
+            )
+            .bind(user_id)
+            .bind("pending")
+            .bind("scheduled")
+            .fetch_one(pool)
+            .await
+        })
         .map_err(AppError::Database)?;
 
-        let processing_count: i32 =
-            sqlx::query_scalar("SELECT COUNT(*) FROM data_processing_log WHERE user_id = ?")
-                .bind(user_id)
-                .fetch_one(&self.db)
-                .await
-                .map_err(AppError::Database)?;
+        let processing_count: i32 = with_pool!(&self.db, |pool| {
+            sqlx::query_scalar(
+                "SELECT CAST(COUNT(*) AS INTEGER) FROM data_processing_log WHERE user_id = $1"
+            )
+            .bind(user_id)
+            .fetch_one(pool)
+            .await
+        })
+        .map_err(AppError::Database)?;
 
         Ok(GdprSummary {
             user_id: user_id.to_string(),
@@ -557,19 +600,22 @@ impl GdprService {
     ) -> Result<(), AppError> {
         let now = Utc::now().to_rfc3339();
 
-        sqlx::query(
-            "INSERT INTO data_processing_log (id, user_id, activity_type, data_category, purpose, legal_basis, processed_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?)"
-        )
-        .bind(Uuid::new_v4().to_string())
-        .bind(user_id)
-        .bind(activity_type)
-        .bind(data_category)
-        .bind(&purpose)
-        .bind(&legal_basis)
-        .bind(&now)
-        .execute(&self.db)
-        .await
+        with_pool!(&self.db, |pool| {
+            sqlx::query(
+                "INSERT INTO data_processing_log (id, user_id, activity_type, data_category, purpose, legal_basis, processed_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)"
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(user_id)
+            .bind(activity_type)
+            .bind(data_category)
+            .bind(&purpose)
+            .bind(&legal_basis)
+            .bind(&now)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .map_err(AppError::Database)?;
 
         Ok(())
@@ -0,0 +1,150 @@
+//! Caller identity for GraphQL mutations.
+//!
+//! The bearer token of a request is resolved once, before execution, into a
+//! [`Viewer`] that resolvers read from the context. The same credentials the REST
+//! API accepts are recognised: API keys (`si_live_`/`si_test_`), JWT access tokens
+//! and SEP-10 sessions.
+
+use async_graphql::{Context, Error, ErrorExtensions};
+use axum::http::{header, HeaderMap};
+use std::sync::Arc;
+
+use crate::auth::sep10_simple::Sep10Service;
+use crate::auth::AuthService;
+use crate::database::Database;
+use crate::rate_limit::ClientTier;
+
+/// Who is executing a GraphQL request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Viewer {
+    Anonymous,
+    /// Holder of a JWT access token
+    User {
+        user_id: String,
+    },
+    /// Stellar account that proved control of its key through SEP-10
+    Sep10 {
+        account: String,
+    },
+    /// API key acting on behalf of the wallet that created it
+    ApiKey {
+        key_id: String,
+        wallet_address: String,
+    },
+}
+
+impl Viewer {
+    /// Tier of the caller, matching how the rate limiter classifies the same credential
+    pub fn tier(&self) -> ClientTier {
+        match self {
+            Viewer::Anonymous => ClientTier::Anonymous,
+            Viewer::User { .. } | Viewer::Sep10 { .. } | Viewer::ApiKey { .. } => {
+                ClientTier::Authenticated
+            }
+        }
+    }
+}
+
+/// Resolves request headers into a [`Viewer`]
+pub struct GraphQLAuth {
+    db: Arc<Database>,
+    auth_service: Arc<AuthService>,
+    sep10_service: Arc<Sep10Service>,
+}
+
+impl GraphQLAuth {
+    pub fn new(
+        db: Arc<Database>,
+        auth_service: Arc<AuthService>,
+        sep10_service: Arc<Sep10Service>,
+    ) -> Self {
+        Self {
+            db,
+            auth_service,
+            sep10_service,
+        }
+    }
+
+    /// A missing or unrecognised token yields `Viewer::Anonymous`; mutations that
+    /// need a caller reject it when they run.
+    pub async fn viewer(&self, headers: &HeaderMap) -> Viewer {
+        let authorization = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok());
+        self.viewer_for(authorization).await
+    }
+
+    /// Like [`GraphQLAuth::viewer`], for an `Authorization` value received outside of
+    /// HTTP headers, such as a WebSocket `connection_init` payload
+    pub async fn viewer_for(&self, authorization: Option<&str>) -> Viewer {
+        let Some(token) = authorization.and_then(|value| value.strip_prefix("Bearer ")) else {
+            return Viewer::Anonymous;
+        };
+
+        if token.starts_with("si_live_") || token.starts_with("si_test_") {
+            return match self.db.validate_api_key(token).await {
+                Ok(Some(key)) => Viewer::ApiKey {
+                    key_id: key.id,
+                    wallet_address: key.wallet_address,
+                },
+                Ok(None) => Viewer::Anonymous,
+                Err(e) => {
+                    tracing::warn!("Failed to validate API key for GraphQL request: {}", e);
+                    Viewer::Anonymous
+                }
+            };
+        }
+
+        if let Ok(claims) = self.auth_service.validate_token(token) {
+            if claims.token_type == "access" {
+                return Viewer::User {
+                    user_id: claims.sub,
+                };
+            }
+        }
+
+        match self.sep10_service.validate_session(token).await {
+            Ok(session) => Viewer::Sep10 {
+                account: session.account,
+            },
+            Err(_) => Viewer::Anonymous,
+        }
+    }
+}
+
+fn viewer<'a>(ctx: &'a Context<'_>) -> &'a Viewer {
+    ctx.data_opt::<Viewer>().unwrap_or(&Viewer::Anonymous)
+}
+
+fn unauthenticated(message: &str) -> Error {
+    Error::new(message).extend_with(|_, e| e.set("code", "UNAUTHENTICATED"))
+}
+
+/// User ID of a JWT-authenticated caller, for user-owned resources such as alert
+/// rules and webhooks
+pub fn require_user<'a>(ctx: &'a Context<'_>) -> async_graphql::Result<&'a str> {
+    match viewer(ctx) {
+        Viewer::User { user_id } => Ok(user_id),
+        _ => Err(unauthenticated("A JWT access token is required")),
+    }
+}
+
+/// Wallet of a SEP-10 session or of the wallet an API key belongs to
+pub fn require_wallet<'a>(ctx: &'a Context<'_>) -> async_graphql::Result<&'a str> {
+    match viewer(ctx) {
+        Viewer::Sep10 { account } => Ok(account),
+        Viewer::ApiKey { wallet_address, .. } => Ok(wallet_address),
+        _ => Err(unauthenticated(
+            "A SEP-10 session token or an API key is required",
+        )),
+    }
+}
+
+/// Wallet of a SEP-10 session. Used where the account itself must act, e.g.
+/// governance votes, which an API key may not cast on its behalf.
+pub fn require_sep10_account<'a>(ctx: &'a Context<'_>) -> async_graphql::Result<&'a str> {
+    match viewer(ctx) {
+        Viewer::Sep10 { account } => Ok(account),
+        _ => Err(unauthenticated("A SEP-10 session token is required")),
+    }
+}
@@ -0,0 +1,181 @@
+//! Request guards: per-tier query cost limits and persisted queries.
+
+use async_graphql::extensions::{
+    Extension, ExtensionContext, ExtensionFactory, NextPrepareRequest, NextValidation,
+};
+use async_graphql::{
+    Error, ErrorExtensions, Pos, Request, ServerError, ServerResult, ValidationResult, Value,
+};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::rate_limit::ClientTier;
+
+use super::auth::Viewer;
+
+fn coded_error(code: &'static str, message: impl Into<String>) -> ServerError {
+    Error::new(message)
+        .extend_with(|_, e| e.set("code", code))
+        .into_server_error(Pos::default())
+}
+
+/// Largest query cost accepted from each client tier
+#[derive(Debug, Clone, Copy)]
+pub struct ComplexityLimits {
+    pub anonymous: usize,
+    pub authenticated: usize,
+}
+
+impl ComplexityLimits {
+    pub fn for_tier(&self, tier: ClientTier) -> usize {
+        match tier {
+            ClientTier::Anonymous => self.anonymous,
+            // No credential carries a premium plan, so premium callers get the
+            // authenticated budget
+            ClientTier::Authenticated | ClientTier::Premium => self.authenticated,
+        }
+    }
+}
+
+/// Rejects a request whose cost, summed from the per-field complexities of the
+/// schema, is above the limit of the caller's tier
+pub struct QueryComplexity(pub ComplexityLimits);
+
+impl ExtensionFactory for QueryComplexity {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(QueryComplexityExtension(self.0))
+    }
+}
+
+struct QueryComplexityExtension(ComplexityLimits);
+
+#[async_trait::async_trait]
+impl Extension for QueryComplexityExtension {
+    async fn validation(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        next: NextValidation<'_>,
+    ) -> Result<ValidationResult, Vec<ServerError>> {
+        let result = next.run(ctx).await?;
+        let tier = ctx
+            .data_opt::<Viewer>()
+            .map_or(ClientTier::Anonymous, Viewer::tier);
+        let limit = self.0.for_tier(tier);
+        if result.complexity > limit {
+            return Err(vec![coded_error(
+                "QUERY_TOO_COMPLEX",
+                format!(
+                    "Query cost {} exceeds the limit of {} for {:?} clients",
+                    result.complexity, limit, tier
+                ),
+            )]);
+        }
+        Ok(result)
+    }
+}
+
+#[derive(Default)]
+struct QueryStore {
+    queries: HashMap<String, String>,
+    /// Insertion order, oldest first, for eviction
+    order: VecDeque<String>,
+}
+
+/// Automatic persisted queries in the Apollo protocol: a client sends the SHA-256
+/// of its query in `extensions.persistedQuery.sha256Hash` and only sends the full
+/// text after the server answers `PersistedQueryNotFound`. The most recent
+/// `capacity` queries are kept in memory.
+#[derive(Clone)]
+pub struct PersistedQueries {
+    capacity: usize,
+    store: Arc<Mutex<QueryStore>>,
+}
+
+impl PersistedQueries {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            store: Arc::default(),
+        }
+    }
+
+    fn get(&self, hash: &str) -> Option<String> {
+        self.store
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .queries
+            .get(hash)
+            .cloned()
+    }
+
+    fn insert(&self, hash: String, query: String) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut store = self.store.lock().unwrap_or_else(PoisonError::into_inner);
+        if store.queries.insert(hash.clone(), query).is_some() {
+            return;
+        }
+        store.order.push_back(hash);
+        while store.order.len() > self.capacity {
+            if let Some(oldest) = store.order.pop_front() {
+                store.queries.remove(&oldest);
+            }
+        }
+    }
+}
+
+impl ExtensionFactory for PersistedQueries {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(self.clone())
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for PersistedQueries {
+    async fn prepare_request(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        mut request: Request,
+        next: NextPrepareRequest<'_>,
+    ) -> ServerResult<Request> {
+        let Some(persisted) = request.extensions.remove("persistedQuery") else {
+            return next.run(ctx, request).await;
+        };
+        let hash = match persisted {
+            Value::Object(fields) if fields.get("version") == Some(&Value::from(1)) => {
+                match fields.get("sha256Hash") {
+                    Some(Value::String(hash)) => hash.to_ascii_lowercase(),
+                    _ => {
+                        return Err(ServerError::new(
+                            "persistedQuery.sha256Hash is required",
+                            None,
+                        ))
+                    }
+                }
+            }
+            _ => {
+                return Err(ServerError::new(
+                    "Only version 1 of persistedQuery is supported",
+                    None,
+                ))
+            }
+        };
+
+        if request.query.is_empty() {
+            request.query = self.get(&hash).ok_or_else(|| {
+                coded_error("PERSISTED_QUERY_NOT_FOUND", "PersistedQueryNotFound")
+            })?;
+        } else {
+            if hex::encode(Sha256::digest(request.query.as_bytes())) != hash {
+                return Err(ServerError::new(
+                    "persistedQuery.sha256Hash does not match the query",
+                    None,
+                ));
+            }
+            self.insert(hash, request.query.clone());
+        }
+        next.run(ctx, request).await
+    }
+}
@@ -0,0 +1,148 @@
+//! HTTP and WebSocket transport for the GraphQL schema.
+//!
+//! `async-graphql-axum` 7 targets axum 0.8, so the endpoints are served with the
+//! transport-agnostic pieces of `async_graphql::http` instead: queries and mutations
+//! as JSON over POST, subscriptions over the `graphql-transport-ws` and `graphql-ws`
+//! WebSocket protocols.
+
+use async_graphql::http::{
+    playground_source, GraphQLPlaygroundConfig, WebSocket as GraphQLWebSocket, WebSocketProtocols,
+    WsMessage, ALL_WEBSOCKET_PROTOCOLS,
+};
+use async_graphql::Data;
+use axum::{
+    extract::{
+        ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    http::{header, HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use futures::{future, SinkExt, StreamExt};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use super::auth::{GraphQLAuth, Viewer};
+use super::schema::AppSchema;
+use crate::websocket::{validate_token, WsQueryParams};
+
+#[derive(Clone)]
+struct GraphQLState {
+    schema: AppSchema,
+    auth: Arc<GraphQLAuth>,
+}
+
+pub fn routes(schema: AppSchema, auth: Arc<GraphQLAuth>) -> Router {
+    Router::new()
+        .route("/graphql", post(graphql_handler))
+        .route("/graphql/ws", get(graphql_ws_handler))
+        .route("/graphql/playground", get(graphql_playground))
+        .with_state(GraphQLState { schema, auth })
+}
+
+/// POST /graphql - Execute a query or mutation. The bearer token, if any, identifies
+/// the caller to mutations.
+async fn graphql_handler(
+    State(state): State<GraphQLState>,
+    headers: HeaderMap,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    let viewer = state.auth.viewer(&headers).await;
+    Json(state.schema.execute(request.data(viewer)).await)
+}
+
+async fn graphql_playground() -> impl IntoResponse {
+    Html(playground_source(
+        GraphQLPlaygroundConfig::new("/graphql").subscription_endpoint("/graphql/ws"),
+    ))
+}
+
+/// GET /graphql/ws - Run subscriptions over a WebSocket. The client picks the protocol
+/// through `Sec-WebSocket-Protocol`.
+///
+/// A `token` query parameter is checked against `WS_AUTH_TOKEN` as on `/ws`. The caller
+/// is identified by the bearer token of the upgrade request, or by an `Authorization`
+/// field in the `connection_init` payload for clients that cannot set headers.
+async fn graphql_ws_handler(
+    State(state): State<GraphQLState>,
+    Query(params): Query<WsQueryParams>,
+    headers: HeaderMap,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    if let Some(token) = params.token {
+        if !validate_token(&token) {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(serde_json::json!({"error": "Unauthorized"})),
+            )
+                .into_response();
+        }
+    }
+
+    let protocol = headers
+        .get(header::SEC_WEBSOCKET_PROTOCOL)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|protocols| {
+            protocols
+                .split(',')
+                .find_map(|p| WebSocketProtocols::from_str(p.trim()).ok())
+        });
+    let Some(protocol) = protocol else {
+        return (
+            StatusCode::BAD_REQUEST,
+            "Sec-WebSocket-Protocol must be graphql-transport-ws or graphql-ws",
+        )
+            .into_response();
+    };
+
+    let viewer = state.auth.viewer(&headers).await;
+    upgrade
+        .protocols(ALL_WEBSOCKET_PROTOCOLS)
+        .on_upgrade(move |socket| serve_subscriptions(socket, state, protocol, viewer))
+}
+
+async fn serve_subscriptions(
+    socket: WebSocket,
+    state: GraphQLState,
+    protocol: WebSocketProtocols,
+    viewer: Viewer,
+) {
+    let (mut sink, stream) = socket.split();
+    let input = stream
+        .take_while(|message| future::ready(message.is_ok()))
+        .filter_map(|message| {
+            future::ready(match message {
+                Ok(message @ (Message::Text(_) | Message::Binary(_))) => Some(message.into_data()),
+                _ => None,
+            })
+        });
+
+    let mut data = Data::default();
+    data.insert(viewer);
+    let auth = state.auth;
+    let mut output = GraphQLWebSocket::new(state.schema, input, protocol)
+        .connection_data(data)
+        .on_connection_init(move |payload| async move {
+            // Replaces the viewer of the upgrade request when present
+            let mut data = Data::default();
+            if let Some(authorization) = payload.get("Authorization").and_then(|v| v.as_str()) {
+                data.insert(auth.viewer_for(Some(authorization)).await);
+            }
+            Ok(data)
+        })
+        .map(|message| match message {
+            WsMessage::Text(text) => Message::Text(text),
+            WsMessage::Close(code, reason) => Message::Close(Some(CloseFrame {
+                code,
+                reason: reason.into(),
+            })),
+        });
+
+    while let Some(message) = output.next().await {
+        if sink.send(message).await.is_err() {
+            break;
+        }
+    }
+}
@@ -0,0 +1,198 @@
+//! Batched loading of nested relations.
+//!
+//! Sibling objects in a list are resolved concurrently, so every `Anchor.assets` of
+//! an `anchors` page asks for its rows in the same scheduler pass. [`DataLoader`]
+//! collects the keys requested in that pass and fetches them with one query instead
+//! of one per parent.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use sqlx::QueryBuilder;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::sync::watch;
+
+use crate::database::DbPool;
+use crate::with_pool;
+
+use super::types::{AssetType, MetricType};
+
+/// Most recent metrics returned per corridor by `Corridor.metrics`
+pub const METRICS_PER_CORRIDOR: i64 = 24;
+
+/// Fetches the values of many keys at once
+#[async_trait]
+pub trait Loader: Send + Sync + 'static {
+    type Key: Clone + Eq + Hash + Send + Sync + 'static;
+    type Value: Clone + Send + Sync + 'static;
+
+    /// Keys without rows may be left out of the returned map
+    async fn load(&self, keys: &[Self::Key]) -> Result<HashMap<Self::Key, Self::Value>>;
+}
+
+type BatchResult<L> = Result<Arc<HashMap<<L as Loader>::Key, <L as Loader>::Value>>, Arc<str>>;
+
+struct Batch<L: Loader> {
+    keys: HashSet<L::Key>,
+    done: watch::Sender<Option<BatchResult<L>>>,
+}
+
+pub struct DataLoader<L: Loader> {
+    loader: L,
+    pending: Mutex<Option<Batch<L>>>,
+}
+
+impl<L: Loader> DataLoader<L> {
+    pub fn new(loader: L) -> Self {
+        Self {
+            loader,
+            pending: Mutex::new(None),
+        }
+    }
+
+    /// Value of `key`, fetched together with every other key requested before the
+    /// first caller of the batch is polled again
+    pub async fn load_one(&self, key: L::Key) -> Result<Option<L::Value>> {
+        let (mut done, leader) = {
+            let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+            match pending.as_mut() {
+                Some(batch) => {
+                    batch.keys.insert(key.clone());
+                    (batch.done.subscribe(), None)
+                }
+                None => {
+                    let (tx, rx) = watch::channel(None);
+                    *pending = Some(Batch {
+                        keys: HashSet::from([key.clone()]),
+                        done: tx,
+                    });
+                    (rx, Some(PendingGuard(&self.pending)))
+                }
+            }
+        };
+
+        if let Some(guard) = leader {
+            tokio::task::yield_now().await;
+            // Only this caller removes the batch; were it gone, the waiting callers
+            // would see the closed channel
+            if let Some(batch) = guard.take() {
+                let keys: Vec<L::Key> = batch.keys.into_iter().collect();
+                let result = self
+                    .loader
+                    .load(&keys)
+                    .await
+                    .map(Arc::new)
+                    .map_err(|e| Arc::from(e.to_string()));
+                batch.done.send_replace(Some(result));
+            }
+        }
+
+        // The sender is dropped without a result only if the leading caller was
+        // cancelled
+        let result = done
+            .wait_for(Option::is_some)
+            .await
+            .ok()
+            .and_then(|result| result.clone())
+            .ok_or_else(|| anyhow::anyhow!("Batched load was cancelled"))?;
+        match result {
+            Ok(values) => Ok(values.get(&key).cloned()),
+            Err(message) => Err(anyhow::anyhow!("{}", message)),
+        }
+    }
+}
+
+/// Held by the caller that opened a batch. Should that caller be dropped before it
+/// takes the batch, the batch is discarded so later keys start a new one and the
+/// waiting callers see the closed channel.
+struct PendingGuard<'a, L: Loader>(&'a Mutex<Option<Batch<L>>>);
+
+impl<L: Loader> PendingGuard<'_, L> {
+    fn take(self) -> Option<Batch<L>> {
+        let batch = self.0.lock().unwrap_or_else(PoisonError::into_inner).take();
+        std::mem::forget(self);
+        batch
+    }
+}
+
+impl<L: Loader> Drop for PendingGuard<'_, L> {
+    fn drop(&mut self) {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner).take();
+    }
+}
+
+/// Assets of each anchor, most held first
+pub struct AnchorAssetsLoader {
+    pub pool: Arc<DbPool>,
+}
+
+#[async_trait]
+impl Loader for AnchorAssetsLoader {
+    type Key = String;
+    type Value = Vec<AssetType>;
+
+    async fn load(&self, anchor_ids: &[String]) -> Result<HashMap<String, Vec<AssetType>>> {
+        let assets = with_pool!(self.pool.as_ref(), |pool| {
+            let mut query = QueryBuilder::new(
+                "SELECT id, anchor_id, asset_code, asset_issuer, total_supply, num_holders, \
+                 created_at, updated_at FROM assets WHERE anchor_id IN (",
+            );
+            let mut ids = query.separated(", ");
+            for id in anchor_ids {
+                ids.push_bind(id.clone());
+            }
+            query.push(") ORDER BY num_holders DESC");
+
+            query.build_query_as::<AssetType>().fetch_all(pool).await
+        })?;
+
+        let mut by_anchor: HashMap<String, Vec<AssetType>> = HashMap::new();
+        for asset in assets {
+            by_anchor
+                .entry(asset.anchor_id.clone())
+                .or_default()
+                .push(asset);
+        }
+        Ok(by_anchor)
+    }
+}
+
+/// Latest [`METRICS_PER_CORRIDOR`] metrics of each corridor, newest first
+pub struct CorridorMetricsLoader {
+    pub pool: Arc<DbPool>,
+}
+
+#[async_trait]
+impl Loader for CorridorMetricsLoader {
+    type Key = String;
+    type Value = Vec<MetricType>;
+
+    async fn load(&self, corridor_ids: &[String]) -> Result<HashMap<String, Vec<MetricType>>> {
+        let metrics = with_pool!(self.pool.as_ref(), |pool| {
+            let mut query = QueryBuilder::new(
+                "SELECT id, name, value, entity_id, entity_type, timestamp, created_at FROM ( \
+                 SELECT id, name, value, entity_id, entity_type, timestamp, created_at, \
+                 ROW_NUMBER() OVER (PARTITION BY entity_id ORDER BY timestamp DESC) AS row_rank \
+                 FROM metrics WHERE entity_type = 'corridor' AND entity_id IN (",
+            );
+            let mut ids = query.separated(", ");
+            for id in corridor_ids {
+                ids.push_bind(id.clone());
+            }
+            query.push(")) ranked WHERE row_rank <= ");
+            query.push_bind(METRICS_PER_CORRIDOR);
+            query.push(" ORDER BY timestamp DESC");
+
+            query.build_query_as::<MetricType>().fetch_all(pool).await
+        })?;
+
+        let mut by_corridor: HashMap<String, Vec<MetricType>> = HashMap::new();
+        for metric in metrics {
+            if let Some(corridor_id) = metric.entity_id.clone() {
+                by_corridor.entry(corridor_id).or_default().push(metric);
+            }
+        }
+        Ok(by_corridor)
+    }
+}
@@ -1,8 +1,16 @@
+pub mod auth;
+pub mod extensions;
+pub mod http;
+pub mod loaders;
+pub mod mutations;
+pub mod resolvers;
 pub mod schema;
+pub mod subscriptions;
 pub mod types;
-pub mod resolvers;
 
 #[cfg(test)]
 mod tests;
 
-pub use schema::{build_schema, AppSchema};
+pub use auth::{GraphQLAuth, Viewer};
+pub use http::routes;
+pub use schema::{build_schema, AppSchema, GraphQLConfig};
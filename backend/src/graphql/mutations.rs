@@ -0,0 +1,225 @@
+use async_graphql::*;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+use crate::api::webhooks::validate_webhook_request;
+use crate::database::Database;
+use crate::models::alerts::SnoozeAlertRequest;
+use crate::services::governance::{CastVoteRequest, GovernanceService};
+use crate::webhooks::{CreateWebhookRequest, WebhookService};
+
+use super::auth::{require_sep10_account, require_user, require_wallet};
+use super::types::*;
+
+fn coded_error(code: &'static str, message: impl Into<String>) -> Error {
+    Error::new(message).extend_with(|_, e| e.set("code", code))
+}
+
+fn not_found(message: &str) -> Error {
+    coded_error("NOT_FOUND", message)
+}
+
+fn bad_input(message: impl Into<String>) -> Error {
+    coded_error("BAD_USER_INPUT", message)
+}
+
+/// Log the cause and hide it from the client
+fn internal(action: &str, e: anyhow::Error) -> Error {
+    tracing::error!("GraphQL mutation failed to {}: {}", action, e);
+    coded_error("INTERNAL_ERROR", format!("Failed to {}", action))
+}
+
+/// Alert rule statements end in `fetch_one`, so a rule that is missing or owned by
+/// someone else surfaces as `RowNotFound`
+fn alert_rule_error(action: &str, e: anyhow::Error) -> Error {
+    match e.downcast_ref::<sqlx::Error>() {
+        Some(sqlx::Error::RowNotFound) => not_found("Alert rule not found"),
+        _ => internal(action, e),
+    }
+}
+
+/// Mutations on caller-owned resources. Alert rules and webhooks belong to JWT users,
+/// API keys to wallets and votes to SEP-10 accounts, as in the REST API.
+pub struct MutationRoot {
+    pub db: Arc<Database>,
+}
+
+#[Object]
+impl MutationRoot {
+    /// Create an alert rule
+    async fn create_alert_rule(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateAlertRuleInput,
+    ) -> Result<AlertRuleType> {
+        let user_id = require_user(ctx)?;
+        let rule = self
+            .db
+            .create_alert_rule(user_id, input.into())
+            .await
+            .map_err(|e| internal("create alert rule", e))?;
+        Ok(rule.into())
+    }
+
+    /// Change fields of an alert rule
+    async fn update_alert_rule(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        input: UpdateAlertRuleInput,
+    ) -> Result<AlertRuleType> {
+        let user_id = require_user(ctx)?;
+        let rule = self
+            .db
+            .update_alert_rule(&id, user_id, input.into())
+            .await
+            .map_err(|e| alert_rule_error("update alert rule", e))?;
+        Ok(rule.into())
+    }
+
+    /// Mute an alert rule until the given time
+    async fn snooze_alert_rule(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        until: DateTime<Utc>,
+    ) -> Result<AlertRuleType> {
+        let user_id = require_user(ctx)?;
+        if until <= Utc::now() {
+            return Err(bad_input("Snooze time must be in the future"));
+        }
+        let rule = self
+            .db
+            .snooze_alert_rule(
+                &id,
+                user_id,
+                SnoozeAlertRequest {
+                    snoozed_until: until,
+                },
+            )
+            .await
+            .map_err(|e| alert_rule_error("snooze alert rule", e))?;
+        Ok(rule.into())
+    }
+
+    /// Delete an alert rule
+    async fn delete_alert_rule(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
+        let user_id = require_user(ctx)?;
+        let deleted = self
+            .db
+            .delete_alert_rule(&id, user_id)
+            .await
+            .map_err(|e| internal("delete alert rule", e))?;
+        if deleted == 0 {
+            return Err(not_found("Alert rule not found"));
+        }
+        Ok(true)
+    }
+
+    /// Register a webhook
+    async fn register_webhook(
+        &self,
+        ctx: &Context<'_>,
+        input: RegisterWebhookInput,
+    ) -> Result<WebhookType> {
+        let user_id = require_user(ctx)?;
+        let filters = input
+            .filters
+            .as_deref()
+            .map(serde_json::from_str::<serde_json::Value>)
+            .transpose()
+            .map_err(|e| bad_input(format!("Webhook filters are not valid JSON: {}", e)))?;
+        let request = CreateWebhookRequest {
+            url: input.url,
+            event_types: input.event_types,
+            filters,
+        };
+        validate_webhook_request(&request).map_err(bad_input)?;
+
+        let webhook = WebhookService::new(self.db.pool().clone())
+            .register_webhook(user_id, request)
+            .await
+            .map_err(|e| internal("register webhook", e))?;
+        Ok(webhook.into())
+    }
+
+    /// Deactivate a webhook
+    async fn delete_webhook(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
+        let user_id = require_user(ctx)?;
+        let deleted = WebhookService::new(self.db.pool().clone())
+            .delete_webhook(&id, user_id)
+            .await
+            .map_err(|e| internal("delete webhook", e))?;
+        if !deleted {
+            return Err(not_found("Webhook not found"));
+        }
+        Ok(true)
+    }
+
+    /// Issue an API key for the SEP-10 authenticated wallet. An API key cannot mint
+    /// further keys, which would outlive its own revocation.
+    async fn create_api_key(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateApiKeyInput,
+    ) -> Result<IssuedApiKeyType> {
+        let wallet_address = require_sep10_account(ctx)?;
+        if input.name.trim().is_empty() {
+            return Err(bad_input("Key name is required"));
+        }
+        let issued = self
+            .db
+            .create_api_key(wallet_address, input.into())
+            .await
+            .map_err(|e| internal("create API key", e))?;
+        Ok(issued.into())
+    }
+
+    /// Revoke an API key and issue a replacement with the same name and scopes, as the
+    /// SEP-10 authenticated wallet
+    async fn rotate_api_key(&self, ctx: &Context<'_>, id: String) -> Result<IssuedApiKeyType> {
+        let wallet_address = require_sep10_account(ctx)?;
+        self.db
+            .rotate_api_key(&id, wallet_address)
+            .await
+            .map_err(|e| internal("rotate API key", e))?
+            .map(Into::into)
+            .ok_or_else(|| not_found("API key not found or already revoked"))
+    }
+
+    /// Revoke an API key. An API key may revoke itself or another key of its wallet.
+    async fn revoke_api_key(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
+        let wallet_address = require_wallet(ctx)?;
+        let revoked = self
+            .db
+            .revoke_api_key(&id, wallet_address)
+            .await
+            .map_err(|e| internal("revoke API key", e))?;
+        if !revoked {
+            return Err(not_found("API key not found or already revoked"));
+        }
+        Ok(true)
+    }
+
+    /// Vote on an active governance proposal as the SEP-10 authenticated account
+    async fn cast_vote(
+        &self,
+        ctx: &Context<'_>,
+        input: CastVoteInput,
+    ) -> Result<GovernanceVoteType> {
+        let voter_address = require_sep10_account(ctx)?;
+        let vote = GovernanceService::new(Arc::clone(&self.db))
+            .cast_vote(
+                &input.proposal_id,
+                voter_address,
+                CastVoteRequest {
+                    choice: input.choice,
+                    tx_hash: input.tx_hash,
+                },
+            )
+            .await
+            // Missing or inactive proposals and repeat votes, as in the REST API
+            .map_err(|e| bad_input(e.to_string()))?;
+        Ok(vote.into())
+    }
+}
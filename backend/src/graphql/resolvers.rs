@@ -1,268 +1,504 @@
+use crate::database::DbPool;
+use crate::db::rollups::RollupDb;
+use crate::models::corridor::Corridor;
+use crate::services::forecasting::{
+    forecast_corridor, ForecastConfig, ForecastPoint, MetricForecast, MAX_HORIZON_HOURS,
+};
+use crate::services::rollup::{fetch_corridor_series, parse_granularity};
+use crate::with_pool;
 use async_graphql::*;
-use sqlx::SqlitePool;
+use chrono::Utc;
+use sqlx::QueryBuilder;
 use std::sync::Arc;
 
+use super::loaders::{AnchorAssetsLoader, CorridorMetricsLoader, DataLoader, METRICS_PER_CORRIDOR};
 use super::types::*;
 
+/// Rows assumed for asset lists, whose length is not bounded by an argument
+const ASSETS_PER_ANCHOR_ESTIMATE: usize = 10;
+/// Points assumed for a metrics series
+const SERIES_POINTS_ESTIMATE: usize = 100;
+/// Cost of fitting a corridor forecast
+const FORECAST_COST: usize = 50;
+
+const ANCHOR_COLUMNS: &str = "id, name, stellar_account, home_domain, total_transactions, \
+     successful_transactions, failed_transactions, total_volume_usd, avg_settlement_time_ms, \
+     reliability_score, status, created_at, updated_at";
+
+const CORRIDOR_COLUMNS: &str = "id, source_asset_code, source_asset_issuer, \
+     destination_asset_code, destination_asset_issuer, reliability_score, status, created_at, \
+     updated_at";
+
+fn push_anchor_filter<'a, DB>(query: &mut QueryBuilder<'a, DB>, filter: Option<&AnchorFilter>)
+where
+    DB: sqlx::Database,
+    String: sqlx::Encode<'a, DB> + sqlx::Type<DB>,
+    f64: sqlx::Encode<'a, DB> + sqlx::Type<DB>,
+{
+    let Some(f) = filter else { return };
+    if let Some(status) = &f.status {
+        query.push(" AND status = ");
+        query.push_bind(status.clone());
+    }
+    if let Some(min_score) = f.min_reliability_score {
+        query.push(" AND reliability_score >= ");
+        query.push_bind(min_score);
+    }
+    if let Some(search) = &f.search {
+        let pattern = format!("%{}%", search);
+        query.push(" AND (name LIKE ");
+        query.push_bind(pattern.clone());
+        query.push(" OR stellar_account LIKE ");
+        query.push_bind(pattern);
+        query.push(")");
+    }
+}
+
+fn push_corridor_filter<'a, DB>(query: &mut QueryBuilder<'a, DB>, filter: Option<&CorridorFilter>)
+where
+    DB: sqlx::Database,
+    String: sqlx::Encode<'a, DB> + sqlx::Type<DB>,
+    f64: sqlx::Encode<'a, DB> + sqlx::Type<DB>,
+{
+    let Some(f) = filter else { return };
+    if let Some(source) = &f.source_asset_code {
+        query.push(" AND source_asset_code = ");
+        query.push_bind(source.clone());
+    }
+    if let Some(dest) = &f.destination_asset_code {
+        query.push(" AND destination_asset_code = ");
+        query.push_bind(dest.clone());
+    }
+    if let Some(status) = &f.status {
+        query.push(" AND status = ");
+        query.push_bind(status.clone());
+    }
+    if let Some(min_score) = f.min_reliability_score {
+        query.push(" AND reliability_score >= ");
+        query.push_bind(min_score);
+    }
+}
+
+/// Estimated rows of a list field: the requested page size, clamped the way the
+/// resolver clamps it
+fn page_size(limit: Option<i32>, default: i32, max: i32) -> usize {
+    usize::try_from(limit.unwrap_or(default).clamp(0, max)).unwrap_or(0)
+}
+
+/// Cost of a list field: one for the field plus the cost of each of its rows
+fn list_cost(rows: usize, child_complexity: usize) -> usize {
+    rows.saturating_mul(child_complexity).saturating_add(1)
+}
+
+fn page_cost(
+    pagination: Option<&PaginationInput>,
+    default: i32,
+    max: i32,
+    child_complexity: usize,
+) -> usize {
+    list_cost(
+        page_size(pagination.and_then(|p| p.limit), default, max),
+        child_complexity,
+    )
+}
+
 pub struct QueryRoot {
-    pub pool: Arc<SqlitePool>,
+    pub pool: Arc<DbPool>,
 }
 
 #[Object]
 impl QueryRoot {
     /// Get a single anchor by ID
-    async fn anchor(&self, ctx: &Context<'_>, id: String) -> Result<Option<AnchorType>> {
+    async fn anchor(&self, id: String) -> Result<Option<AnchorType>> {
         let pool = &self.pool;
-        
-        let anchor = sqlx::query_as!(
-            AnchorType,
-            r#"
-            SELECT 
-                id, name, stellar_account, home_domain,
-                total_transactions, successful_transactions, failed_transactions,
-                total_volume_usd, avg_settlement_time_ms, reliability_score,
-                status, created_at as "created_at: _", updated_at as "updated_at: _"
-            FROM anchors
-            WHERE id = ?
-            "#,
-            id
-        )
-        .fetch_optional(pool.as_ref())
-        .await?;
+
+        let anchor = with_pool!(pool.as_ref(), |pool| {
+            sqlx::query_as::<_, AnchorType>(&format!(
+                "SELECT {} FROM anchors WHERE id = $1",
+                ANCHOR_COLUMNS
+            ))
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+        })?;
 
         Ok(anchor)
     }
 
     /// Get all anchors with optional filtering and pagination
+    #[graphql(complexity = "page_cost(pagination.as_ref(), 10, 100, child_complexity)")]
     async fn anchors(
         &self,
-        ctx: &Context<'_>,
         filter: Option<AnchorFilter>,
         pagination: Option<PaginationInput>,
     ) -> Result<AnchorsConnection> {
         let pool = &self.pool;
-        let limit = pagination.as_ref().and_then(|p| p.limit).unwrap_or(10).min(100);
-        let offset = pagination.as_ref().and_then(|p| p.offset).unwrap_or(0);
-
-        let mut query = String::from("SELECT id, name, stellar_account, home_domain, total_transactions, successful_transactions, failed_transactions, total_volume_usd, avg_settlement_time_ms, reliability_score, status, created_at, updated_at FROM anchors WHERE 1=1");
-        let mut count_query = String::from("SELECT COUNT(*) as count FROM anchors WHERE 1=1");
-
-        if let Some(f) = &filter {
-            if let Some(status) = &f.status {
-                query.push_str(&format!(" AND status = '{}'", status));
-                count_query.push_str(&format!(" AND status = '{}'", status));
-            }
-            if let Some(min_score) = f.min_reliability_score {
-                query.push_str(&format!(" AND reliability_score >= {}", min_score));
-                count_query.push_str(&format!(" AND reliability_score >= {}", min_score));
-            }
-            if let Some(search) = &f.search {
-                query.push_str(&format!(" AND (name LIKE '%{}%' OR stellar_account LIKE '%{}%')", search, search));
-                count_query.push_str(&format!(" AND (name LIKE '%{}%' OR stellar_account LIKE '%{}%')", search, search));
-            }
-        }
-
-        query.push_str(&format!(" ORDER BY reliability_score DESC LIMIT {} OFFSET {}", limit, offset));
-
-        let anchors = sqlx::query_as::<_, AnchorType>(&query)
-            .fetch_all(pool.as_ref())
-            .await?;
-
-        let total: (i32,) = sqlx::query_as(&count_query)
-            .fetch_one(pool.as_ref())
-            .await?;
+        let limit = pagination
+            .as_ref()
+            .and_then(|p| p.limit)
+            .unwrap_or(10)
+            .clamp(0, 100);
+        let offset = pagination
+            .as_ref()
+            .and_then(|p| p.offset)
+            .unwrap_or(0)
+            .max(0);
+
+        let (anchors, total) = with_pool!(pool.as_ref(), |pool| {
+            let mut query =
+                QueryBuilder::new(format!("SELECT {} FROM anchors WHERE 1=1", ANCHOR_COLUMNS));
+            push_anchor_filter(&mut query, filter.as_ref());
+            query.push(" ORDER BY reliability_score DESC LIMIT ");
+            query.push_bind(i64::from(limit));
+            query.push(" OFFSET ");
+            query.push_bind(i64::from(offset));
+            let anchors = query.build_query_as::<AnchorType>().fetch_all(pool).await?;
+
+            let mut count_query = QueryBuilder::new("SELECT COUNT(*) FROM anchors WHERE 1=1");
+            push_anchor_filter(&mut count_query, filter.as_ref());
+            let (total,): (i64,) = count_query.build_query_as().fetch_one(pool).await?;
+
+            (anchors, total)
+        });
+        let total = i32::try_from(total).unwrap_or(i32::MAX);
 
         Ok(AnchorsConnection {
             nodes: anchors,
-            total_count: total.0,
-            has_next_page: (offset + limit) < total.0,
+            total_count: total,
+            has_next_page: (offset + limit) < total,
         })
     }
 
     /// Get a single corridor by ID
-    async fn corridor(&self, ctx: &Context<'_>, id: String) -> Result<Option<CorridorType>> {
+    async fn corridor(&self, id: String) -> Result<Option<CorridorType>> {
         let pool = &self.pool;
-        
-        let corridor = sqlx::query_as!(
-            CorridorType,
-            r#"
-            SELECT 
-                id, source_asset_code, source_asset_issuer,
-                destination_asset_code, destination_asset_issuer,
-                reliability_score, status,
-                created_at as "created_at: _", updated_at as "updated_at: _"
-            FROM corridors
-            WHERE id = ?
-            "#,
-            id
-        )
-        .fetch_optional(pool.as_ref())
-        .await?;
+
+        let corridor = with_pool!(pool.as_ref(), |pool| {
+            sqlx::query_as::<_, CorridorType>(&format!(
+                "SELECT {} FROM corridors WHERE id = $1",
+                CORRIDOR_COLUMNS
+            ))
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+        })?;
 
         Ok(corridor)
     }
 
     /// Get all corridors with optional filtering and pagination
+    #[graphql(complexity = "page_cost(pagination.as_ref(), 10, 100, child_complexity)")]
     async fn corridors(
         &self,
-        ctx: &Context<'_>,
         filter: Option<CorridorFilter>,
         pagination: Option<PaginationInput>,
     ) -> Result<CorridorsConnection> {
         let pool = &self.pool;
-        let limit = pagination.as_ref().and_then(|p| p.limit).unwrap_or(10).min(100);
-        let offset = pagination.as_ref().and_then(|p| p.offset).unwrap_or(0);
-
-        let mut query = String::from("SELECT id, source_asset_code, source_asset_issuer, destination_asset_code, destination_asset_issuer, reliability_score, status, created_at, updated_at FROM corridors WHERE 1=1");
-        let mut count_query = String::from("SELECT COUNT(*) as count FROM corridors WHERE 1=1");
-
-        if let Some(f) = &filter {
-            if let Some(source) = &f.source_asset_code {
-                query.push_str(&format!(" AND source_asset_code = '{}'", source));
-                count_query.push_str(&format!(" AND source_asset_code = '{}'", source));
-            }
-            if let Some(dest) = &f.destination_asset_code {
-                query.push_str(&format!(" AND destination_asset_code = '{}'", dest));
-                count_query.push_str(&format!(" AND destination_asset_code = '{}'", dest));
-            }
-            if let Some(status) = &f.status {
-                query.push_str(&format!(" AND status = '{}'", status));
-                count_query.push_str(&format!(" AND status = '{}'", status));
-            }
-            if let Some(min_score) = f.min_reliability_score {
-                query.push_str(&format!(" AND reliability_score >= {}", min_score));
-                count_query.push_str(&format!(" AND reliability_score >= {}", min_score));
-            }
-        }
-
-        query.push_str(&format!(" ORDER BY reliability_score DESC LIMIT {} OFFSET {}", limit, offset));
-
-        let corridors = sqlx::query_as::<_, CorridorType>(&query)
-            .fetch_all(pool.as_ref())
-            .await?;
-
-        let total: (i32,) = sqlx::query_as(&count_query)
-            .fetch_one(pool.as_ref())
-            .await?;
+        let limit = pagination
+            .as_ref()
+            .and_then(|p| p.limit)
+            .unwrap_or(10)
+            .clamp(0, 100);
+        let offset = pagination
+            .as_ref()
+            .and_then(|p| p.offset)
+            .unwrap_or(0)
+            .max(0);
+
+        let (corridors, total) = with_pool!(pool.as_ref(), |pool| {
+            let mut query = QueryBuilder::new(format!(
+                "SELECT {} FROM corridors WHERE 1=1",
+                CORRIDOR_COLUMNS
+            ));
+            push_corridor_filter(&mut query, filter.as_ref());
+            query.push(" ORDER BY reliability_score DESC LIMIT ");
+            query.push_bind(i64::from(limit));
+            query.push(" OFFSET ");
+            query.push_bind(i64::from(offset));
+            let corridors = query
+                .build_query_as::<CorridorType>()
+                .fetch_all(pool)
+                .await?;
+
+            let mut count_query = QueryBuilder::new("SELECT COUNT(*) FROM corridors WHERE 1=1");
+            push_corridor_filter(&mut count_query, filter.as_ref());
+            let (total,): (i64,) = count_query.build_query_as().fetch_one(pool).await?;
+
+            (corridors, total)
+        });
+        let total = i32::try_from(total).unwrap_or(i32::MAX);
 
         Ok(CorridorsConnection {
             nodes: corridors,
-            total_count: total.0,
-            has_next_page: (offset + limit) < total.0,
+            total_count: total,
+            has_next_page: (offset + limit) < total,
         })
     }
 
     /// Get assets for a specific anchor
-    async fn assets_by_anchor(&self, ctx: &Context<'_>, anchor_id: String) -> Result<Vec<AssetType>> {
+    #[graphql(complexity = "list_cost(ASSETS_PER_ANCHOR_ESTIMATE, child_complexity)")]
+    async fn assets_by_anchor(&self, anchor_id: String) -> Result<Vec<AssetType>> {
         let pool = &self.pool;
-        
-        let assets = sqlx::query_as!(
-            AssetType,
-            r#"
-            SELECT 
-                id, anchor_id, asset_code, asset_issuer,
-                total_supply, num_holders,
-                created_at as "created_at: _", updated_at as "updated_at: _"
-            FROM assets
-            WHERE anchor_id = ?
-            ORDER BY num_holders DESC
-            "#,
-            anchor_id
-        )
-        .fetch_all(pool.as_ref())
-        .await?;
+
+        let assets = with_pool!(pool.as_ref(), |pool| {
+            sqlx::query_as::<_, AssetType>(
+                r#"
+                SELECT
+                    id, anchor_id, asset_code, asset_issuer,
+                    total_supply, num_holders, created_at, updated_at
+                FROM assets
+                WHERE anchor_id = $1
+                ORDER BY num_holders DESC
+                "#,
+            )
+            .bind(anchor_id)
+            .fetch_all(pool)
+            .await
+        })?;
 
         Ok(assets)
     }
 
     /// Get metrics for an entity within a time range
+    #[graphql(complexity = "page_cost(pagination.as_ref(), 100, 1000, child_complexity)")]
     async fn metrics(
         &self,
-        ctx: &Context<'_>,
         entity_id: Option<String>,
         entity_type: Option<String>,
         time_range: Option<TimeRangeInput>,
         pagination: Option<PaginationInput>,
     ) -> Result<Vec<MetricType>> {
         let pool = &self.pool;
-        let limit = pagination.as_ref().and_then(|p| p.limit).unwrap_or(100).min(1000);
-        let offset = pagination.as_ref().and_then(|p| p.offset).unwrap_or(0);
+        let limit = pagination
+            .as_ref()
+            .and_then(|p| p.limit)
+            .unwrap_or(100)
+            .clamp(0, 1000);
+        let offset = pagination
+            .as_ref()
+            .and_then(|p| p.offset)
+            .unwrap_or(0)
+            .max(0);
+
+        let metrics = with_pool!(pool.as_ref(), |pool| {
+            let mut query = QueryBuilder::new(
+                "SELECT id, name, value, entity_id, entity_type, timestamp, created_at FROM metrics WHERE 1=1",
+            );
+            if let Some(eid) = entity_id {
+                query.push(" AND entity_id = ");
+                query.push_bind(eid);
+            }
+            if let Some(etype) = entity_type {
+                query.push(" AND entity_type = ");
+                query.push_bind(etype);
+            }
+            if let Some(tr) = time_range {
+                query.push(" AND timestamp >= ");
+                query.push_bind(tr.start);
+                query.push(" AND timestamp <= ");
+                query.push_bind(tr.end);
+            }
+            query.push(" ORDER BY timestamp DESC LIMIT ");
+            query.push_bind(i64::from(limit));
+            query.push(" OFFSET ");
+            query.push_bind(i64::from(offset));
 
-        let mut query = String::from("SELECT id, name, value, entity_id, entity_type, timestamp, created_at FROM metrics WHERE 1=1");
+            query.build_query_as::<MetricType>().fetch_all(pool).await
+        })?;
 
-        if let Some(eid) = &entity_id {
-            query.push_str(&format!(" AND entity_id = '{}'", eid));
-        }
-        if let Some(etype) = &entity_type {
-            query.push_str(&format!(" AND entity_type = '{}'", etype));
-        }
-        if let Some(tr) = &time_range {
-            query.push_str(&format!(" AND timestamp >= '{}' AND timestamp <= '{}'", tr.start, tr.end));
-        }
+        Ok(metrics)
+    }
 
-        query.push_str(&format!(" ORDER BY timestamp DESC LIMIT {} OFFSET {}", limit, offset));
+    /// Get corridor metrics over a time range, served from the coarsest rollup
+    /// resolution that satisfies `granularity` (e.g. "1h", "1d", "1w", "1mo")
+    #[graphql(complexity = "list_cost(SERIES_POINTS_ESTIMATE, child_complexity)")]
+    async fn corridor_metrics_series(
+        &self,
+        corridor_key: String,
+        time_range: TimeRangeInput,
+        granularity: Option<String>,
+    ) -> Result<CorridorMetricsSeriesType> {
+        let pool = &self.pool;
 
-        let metrics = sqlx::query_as::<_, MetricType>(&query)
-            .fetch_all(pool.as_ref())
-            .await?;
+        let granularity = match granularity.as_deref() {
+            Some(value) => Some(
+                parse_granularity(value)
+                    .ok_or_else(|| Error::new(format!("Invalid granularity '{}'", value)))?,
+            ),
+            None => None,
+        };
+
+        let series = fetch_corridor_series(
+            &RollupDb::new(pool.as_ref().clone()),
+            &corridor_key,
+            time_range.start,
+            time_range.end,
+            granularity,
+        )
+        .await?;
 
-        Ok(metrics)
+        Ok(CorridorMetricsSeriesType {
+            corridor_key: series.corridor_key,
+            resolution: series.resolution.to_string(),
+            points: series
+                .points
+                .into_iter()
+                .map(|p| CorridorMetricsPointType {
+                    bucket_start: p.bucket_start,
+                    total_transactions: p.total_transactions,
+                    successful_transactions: p.successful_transactions,
+                    failed_transactions: p.failed_transactions,
+                    success_rate: p.success_rate,
+                    volume_usd: p.volume_usd,
+                    avg_slippage_bps: p.avg_slippage_bps,
+                    avg_settlement_latency_ms: p.avg_settlement_latency_ms,
+                    liquidity_depth_usd: p.liquidity_depth_usd,
+                })
+                .collect(),
+        })
     }
 
     /// Get latest snapshot for an entity
     async fn latest_snapshot(
         &self,
-        ctx: &Context<'_>,
         entity_id: String,
         entity_type: String,
     ) -> Result<Option<SnapshotType>> {
         let pool = &self.pool;
-        
-        let snapshot = sqlx::query_as!(
-            SnapshotType,
-            r#"
-            SELECT 
-                id, entity_id, entity_type, data, hash, epoch,
-                timestamp as "timestamp: _", created_at as "created_at: _"
-            FROM snapshots
-            WHERE entity_id = ? AND entity_type = ?
-            ORDER BY timestamp DESC
-            LIMIT 1
-            "#,
-            entity_id,
-            entity_type
-        )
-        .fetch_optional(pool.as_ref())
-        .await?;
+
+        let snapshot = with_pool!(pool.as_ref(), |pool| {
+            sqlx::query_as::<_, SnapshotType>(
+                r#"
+                SELECT
+                    id, entity_id, entity_type, data, hash, epoch, timestamp, created_at
+                FROM snapshots
+                WHERE entity_id = $1 AND entity_type = $2
+                ORDER BY timestamp DESC
+                LIMIT 1
+                "#,
+            )
+            .bind(entity_id)
+            .bind(entity_type)
+            .fetch_optional(pool)
+            .await
+        })?;
 
         Ok(snapshot)
     }
 
     /// Search across anchors and corridors
-    async fn search(
+    #[graphql(complexity = "list_cost(page_size(limit, 10, 50), child_complexity)")]
+    async fn search(&self, query: String, limit: Option<i32>) -> Result<SearchResults> {
+        let pool = &self.pool;
+        let search_limit = i64::from(limit.unwrap_or(10).clamp(0, 50));
+        let pattern = format!("%{}%", query);
+
+        let anchors = with_pool!(pool.as_ref(), |pool| {
+            sqlx::query_as::<_, AnchorType>(&format!(
+                "SELECT {} FROM anchors WHERE name LIKE $1 OR stellar_account LIKE $1 LIMIT $2",
+                ANCHOR_COLUMNS
+            ))
+            .bind(&pattern)
+            .bind(search_limit)
+            .fetch_all(pool)
+            .await
+        })?;
+
+        let corridors = with_pool!(pool.as_ref(), |pool| {
+            sqlx::query_as::<_, CorridorType>(&format!(
+                "SELECT {} FROM corridors WHERE source_asset_code LIKE $1 OR destination_asset_code LIKE $1 LIMIT $2",
+                CORRIDOR_COLUMNS
+            ))
+            .bind(&pattern)
+            .bind(search_limit)
+            .fetch_all(pool)
+            .await
+        })?;
+
+        Ok(SearchResults { anchors, corridors })
+    }
+}
+
+#[ComplexObject]
+impl AnchorType {
+    /// Assets issued by the anchor, most held first. Loaded in one batch for every
+    /// anchor in the response.
+    #[graphql(complexity = "list_cost(ASSETS_PER_ANCHOR_ESTIMATE, child_complexity)")]
+    async fn assets(&self, ctx: &Context<'_>) -> Result<Vec<AssetType>> {
+        let loader = ctx.data::<DataLoader<AnchorAssetsLoader>>()?;
+        Ok(loader.load_one(self.id.clone()).await?.unwrap_or_default())
+    }
+}
+
+#[ComplexObject]
+impl CorridorType {
+    /// Latest metrics recorded for the corridor, newest first. Loaded in one batch for
+    /// every corridor in the response.
+    #[graphql(complexity = "list_cost(METRICS_PER_CORRIDOR as usize, child_complexity)")]
+    async fn metrics(&self, ctx: &Context<'_>) -> Result<Vec<MetricType>> {
+        let loader = ctx.data::<DataLoader<CorridorMetricsLoader>>()?;
+        Ok(loader.load_one(self.id.clone()).await?.unwrap_or_default())
+    }
+
+    /// Hourly volume and liquidity forecasts for the next `horizon_hours` (default 24,
+    /// at most 168). Fitting the model is expensive, so the field carries a fixed cost.
+    #[graphql(complexity = "FORECAST_COST + child_complexity")]
+    async fn forecast(
         &self,
         ctx: &Context<'_>,
-        query: String,
-        limit: Option<i32>,
-    ) -> Result<SearchResults> {
-        let pool = &self.pool;
-        let search_limit = limit.unwrap_or(10).min(50);
+        horizon_hours: Option<i32>,
+    ) -> Result<CorridorForecastType> {
+        let pool = ctx.data::<Arc<DbPool>>()?;
+        let horizon_hours = i64::from(horizon_hours.unwrap_or(24));
+        if !(1..=MAX_HORIZON_HOURS).contains(&horizon_hours) {
+            return Err(Error::new(format!(
+                "horizonHours must be between 1 and {}",
+                MAX_HORIZON_HOURS
+            )));
+        }
 
-        let anchors = sqlx::query_as::<_, AnchorType>(&format!(
-            "SELECT id, name, stellar_account, home_domain, total_transactions, successful_transactions, failed_transactions, total_volume_usd, avg_settlement_time_ms, reliability_score, status, created_at, updated_at FROM anchors WHERE name LIKE '%{}%' OR stellar_account LIKE '%{}%' LIMIT {}",
-            query, query, search_limit
-        ))
-        .fetch_all(pool.as_ref())
+        let corridor_key = Corridor::new(
+            self.source_asset_code.clone(),
+            self.source_asset_issuer.clone(),
+            self.destination_asset_code.clone(),
+            self.destination_asset_issuer.clone(),
+        )
+        .to_string_key();
+        let forecast = forecast_corridor(
+            &RollupDb::new(pool.as_ref().clone()),
+            &corridor_key,
+            horizon_hours,
+            Utc::now(),
+            &ForecastConfig::from_env(),
+        )
         .await?;
 
-        let corridors = sqlx::query_as::<_, CorridorType>(&format!(
-            "SELECT id, source_asset_code, source_asset_issuer, destination_asset_code, destination_asset_issuer, reliability_score, status, created_at, updated_at FROM corridors WHERE source_asset_code LIKE '%{}%' OR destination_asset_code LIKE '%{}%' LIMIT {}",
-            query, query, search_limit
-        ))
-        .fetch_all(pool.as_ref())
-        .await?;
+        Ok(CorridorForecastType {
+            corridor_key: forecast.corridor_key,
+            forecast_start: forecast.forecast_start,
+            horizon_hours: forecast.horizon_hours as i32,
+            confidence: forecast.confidence,
+            volume_usd: forecast.volume_usd.map(metric_forecast_type),
+            liquidity_depth_usd: forecast.liquidity_depth_usd.map(metric_forecast_type),
+        })
+    }
+}
 
-        Ok(SearchResults { anchors, corridors })
+fn metric_forecast_type(forecast: MetricForecast) -> MetricForecastType {
+    let point_type = |p: &ForecastPoint| ForecastPointType {
+        timestamp: p.timestamp,
+        forecast: p.forecast,
+        lower: p.lower,
+        upper: p.upper,
+    };
+    MetricForecastType {
+        season_length_hours: forecast.season_length_hours as i32,
+        observations: forecast.observations as i32,
+        total: forecast.total.map(|total| total.forecast),
+        total_lower: forecast.total.map(|total| total.lower),
+        total_upper: forecast.total.map(|total| total.upper),
+        trough: forecast.trough().map(point_type),
+        points: forecast.points.iter().map(point_type).collect(),
     }
 }
 
@@ -272,15 +508,3 @@ pub struct SearchResults {
     pub anchors: Vec<AnchorType>,
     pub corridors: Vec<CorridorType>,
 }
-
-pub struct MutationRoot {
-    pub pool: Arc<SqlitePool>,
-}
-
-#[Object]
-impl MutationRoot {
-    /// Placeholder for future mutations
-    async fn placeholder(&self) -> Result<bool> {
-        Ok(true)
-    }
-}
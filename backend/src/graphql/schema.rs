@@ -1,16 +1,82 @@
-use async_graphql::{EmptySubscription, Schema};
-use sqlx::SqlitePool;
+use crate::database::Database;
+use crate::websocket::WsState;
+use async_graphql::Schema;
 use std::sync::Arc;
 
-use super::resolvers::{MutationRoot, QueryRoot};
+use super::extensions::{ComplexityLimits, PersistedQueries, QueryComplexity};
+use super::loaders::{AnchorAssetsLoader, CorridorMetricsLoader, DataLoader};
+use super::mutations::MutationRoot;
+use super::resolvers::QueryRoot;
+use super::subscriptions::SubscriptionRoot;
 
-pub type AppSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+pub type AppSchema = Schema<QueryRoot, MutationRoot, SubscriptionRoot>;
 
-pub fn build_schema(pool: Arc<SqlitePool>) -> AppSchema {
+/// Limits applied to every GraphQL request
+#[derive(Debug, Clone)]
+pub struct GraphQLConfig {
+    /// Deepest selection nesting accepted
+    pub max_depth: usize,
+    /// Largest query cost accepted per client tier
+    pub max_complexity: ComplexityLimits,
+    /// Persisted queries kept in memory
+    pub persisted_query_capacity: usize,
+}
+
+impl Default for GraphQLConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: 10,
+            max_complexity: ComplexityLimits {
+                anonymous: 1_000,
+                authenticated: 5_000,
+            },
+            persisted_query_capacity: 1_000,
+        }
+    }
+}
+
+impl GraphQLConfig {
+    /// Reads `GRAPHQL_MAX_DEPTH`, `GRAPHQL_MAX_COMPLEXITY_ANONYMOUS`,
+    /// `GRAPHQL_MAX_COMPLEXITY_AUTHENTICATED` and `GRAPHQL_PERSISTED_QUERY_CAPACITY`,
+    /// falling back to the defaults.
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        let env_usize = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .and_then(|value| value.parse::<usize>().ok())
+        };
+        if let Some(depth) = env_usize("GRAPHQL_MAX_DEPTH") {
+            config.max_depth = depth;
+        }
+        if let Some(limit) = env_usize("GRAPHQL_MAX_COMPLEXITY_ANONYMOUS") {
+            config.max_complexity.anonymous = limit;
+        }
+        if let Some(limit) = env_usize("GRAPHQL_MAX_COMPLEXITY_AUTHENTICATED") {
+            config.max_complexity.authenticated = limit;
+        }
+        if let Some(capacity) = env_usize("GRAPHQL_PERSISTED_QUERY_CAPACITY") {
+            config.persisted_query_capacity = capacity;
+        }
+        config
+    }
+}
+
+pub fn build_schema(db: Arc<Database>, ws_state: Arc<WsState>, config: GraphQLConfig) -> AppSchema {
+    let pool = Arc::new(db.pool().clone());
     Schema::build(
         QueryRoot { pool: pool.clone() },
-        MutationRoot { pool },
-        EmptySubscription,
+        MutationRoot { db },
+        SubscriptionRoot { ws_state },
     )
+    .limit_depth(config.max_depth)
+    .extension(PersistedQueries::new(config.persisted_query_capacity))
+    .extension(QueryComplexity(config.max_complexity))
+    .data(DataLoader::new(AnchorAssetsLoader { pool: pool.clone() }))
+    .data(DataLoader::new(CorridorMetricsLoader {
+        pool: pool.clone(),
+    }))
+    // Available to field resolvers of nested objects, e.g. `Corridor.forecast`
+    .data(pool)
     .finish()
 }
@@ -0,0 +1,147 @@
+use async_graphql::*;
+use futures::stream::{self, Stream, StreamExt};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::websocket::{WsMessage, WsState};
+
+use super::types::*;
+
+/// Live updates, fed by the messages `WsState` delivers to WebSocket clients
+pub struct SubscriptionRoot {
+    pub ws_state: Arc<WsState>,
+}
+
+#[Subscription]
+impl SubscriptionRoot {
+    /// Metrics of one corridor as its payments stream in
+    async fn corridor_updated(
+        &self,
+        corridor_key: String,
+    ) -> impl Stream<Item = CorridorUpdateType> {
+        events(&self.ws_state).filter_map(move |message| {
+            let update = match message {
+                WsMessage::CorridorUpdate {
+                    corridor_key: key,
+                    asset_a_code,
+                    asset_a_issuer,
+                    asset_b_code,
+                    asset_b_issuer,
+                    success_rate,
+                    health_score,
+                    last_updated,
+                } if key == corridor_key => Some(CorridorUpdateType {
+                    corridor_key: key,
+                    asset_a_code,
+                    asset_a_issuer,
+                    asset_b_code,
+                    asset_b_issuer,
+                    success_rate,
+                    health_score,
+                    last_updated,
+                }),
+                _ => None,
+            };
+            async move { update }
+        })
+    }
+
+    /// Status changes of one anchor, or of every anchor without `anchorId`. The first
+    /// update seen for an anchor is always delivered; later ones only when the status
+    /// differs from the previous update.
+    async fn anchor_status_changed(
+        &self,
+        anchor_id: Option<String>,
+    ) -> impl Stream<Item = AnchorStatusType> {
+        events(&self.ws_state)
+            .scan(
+                HashMap::<String, String>::new(),
+                move |last_status, message| {
+                    let change = match message {
+                        WsMessage::AnchorUpdate {
+                            anchor_id: id,
+                            name,
+                            reliability_score,
+                            status,
+                        } if anchor_id.as_ref().is_none_or(|wanted| *wanted == id) => {
+                            let changed = last_status.get(&id) != Some(&status);
+                            last_status.insert(id.clone(), status.clone());
+                            changed.then_some(AnchorStatusType {
+                                anchor_id: id,
+                                name,
+                                reliability_score,
+                                status,
+                            })
+                        }
+                        _ => None,
+                    };
+                    futures::future::ready(Some(change))
+                },
+            )
+            .filter_map(futures::future::ready)
+    }
+
+    /// Snapshot hashes as they are anchored on-chain
+    async fn snapshot_submitted(&self) -> impl Stream<Item = SnapshotSubmissionType> {
+        events(&self.ws_state).filter_map(|message| {
+            let submission = match message {
+                WsMessage::SnapshotUpdate {
+                    snapshot_id,
+                    epoch,
+                    timestamp,
+                    hash,
+                } => Some(SnapshotSubmissionType {
+                    snapshot_id,
+                    epoch,
+                    timestamp,
+                    hash,
+                }),
+                _ => None,
+            };
+            async move { submission }
+        })
+    }
+
+    /// Corridor health alerts, optionally only those of one severity
+    async fn health_alerts(&self, severity: Option<String>) -> impl Stream<Item = HealthAlertType> {
+        events(&self.ws_state).filter_map(move |message| {
+            let alert = match message {
+                WsMessage::HealthAlert {
+                    corridor_id,
+                    severity: alert_severity,
+                    message,
+                    timestamp,
+                } if severity
+                    .as_ref()
+                    .is_none_or(|wanted| wanted.eq_ignore_ascii_case(&alert_severity)) =>
+                {
+                    Some(HealthAlertType {
+                        corridor_id,
+                        severity: alert_severity,
+                        message,
+                        timestamp,
+                    })
+                }
+                _ => None,
+            };
+            async move { alert }
+        })
+    }
+}
+
+/// Messages published through `WsState`. A subscriber that falls behind skips the
+/// messages it missed rather than ending the subscription.
+fn events(ws_state: &WsState) -> impl Stream<Item = WsMessage> {
+    stream::unfold(ws_state.subscribe_events(), |mut rx| async move {
+        loop {
+            match rx.recv().await {
+                Ok(message) => return Some((message, rx)),
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("GraphQL subscriber lagged, skipped {} messages", skipped);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    })
+}
@@ -1,11 +1,434 @@
-#[cfg(test)]
-mod tests {
-    use super::super::*;
-
-    #[test]
-    fn test_graphql_types_compile() {
-        // This test ensures all types compile correctly
-        // The actual functionality will be tested via integration tests
-        assert!(true);
+use super::*;
+use crate::database::Database;
+use crate::websocket::{WsMessage, WsState};
+use async_graphql::Request;
+use futures::StreamExt;
+use std::sync::Arc;
+use std::time::Duration;
+
+async fn schema() -> (AppSchema, Arc<WsState>) {
+    schema_with(GraphQLConfig::default()).await
+}
+
+async fn schema_with(config: GraphQLConfig) -> (AppSchema, Arc<WsState>) {
+    let pool = crate::test_support::migrated_pool().await;
+    sqlx::raw_sql(
+        "INSERT INTO anchors (id, name, stellar_account, reliability_score, status) \
+         VALUES ('a1', 'Alpha Anchor', 'GALPHA', 97.5, 'green'), \
+                ('a2', 'Beta Anchor', 'GBETA', 88.0, 'yellow'); \
+         INSERT INTO assets (id, anchor_id, asset_code, asset_issuer, num_holders) \
+         VALUES ('s1', 'a1', 'USDC', 'GALPHA', 10), ('s2', 'a1', 'EURC', 'GALPHA', 30), \
+                ('s3', 'a2', 'BRL', 'GBETA', 5); \
+         INSERT INTO corridors (id, source_asset_code, source_asset_issuer, \
+                destination_asset_code, destination_asset_issuer) \
+         VALUES ('c1', 'USDC', 'GALPHA', 'BRL', 'GBETA'); \
+         INSERT INTO metrics (id, name, value, entity_id, entity_type, timestamp) \
+         VALUES ('m1', 'success_rate', 97.0, 'c1', 'corridor', '2024-01-01T00:00:00Z'), \
+                ('m2', 'success_rate', 99.0, 'c1', 'corridor', '2024-01-02T00:00:00Z'), \
+                ('m3', 'success_rate', 50.0, 'a1', 'anchor', '2024-01-02T00:00:00Z'); \
+         INSERT INTO users (id, username) VALUES ('u1', 'alice'), ('u2', 'bob'); \
+         INSERT INTO governance_proposals (id, title, status, created_by) \
+         VALUES ('p1', 'Upgrade snapshot contract', 'active', 'GADMIN');",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let ws_state = Arc::new(WsState::new());
+    (
+        build_schema(
+            Arc::new(Database::new(pool.into())),
+            Arc::clone(&ws_state),
+            config,
+        ),
+        ws_state,
+    )
+}
+
+#[tokio::test]
+async fn test_anchor_search_binds_filter_values() {
+    let (schema, _) = schema().await;
+
+    let response = schema
+        .execute(r#"{ anchors(filter: { search: "Alpha" }) { totalCount nodes { id status } } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let data = response.data.into_json().unwrap();
+    assert_eq!(data["anchors"]["totalCount"], 1);
+    assert_eq!(data["anchors"]["nodes"][0]["id"], "a1");
+
+    let response = schema
+        .execute(r#"{ anchors(filter: { search: "' OR '1'='1" }) { totalCount } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap()["anchors"]["totalCount"],
+        0
+    );
+}
+
+async fn execute_as(schema: &AppSchema, viewer: Viewer, query: &str) -> async_graphql::Response {
+    schema.execute(Request::new(query).data(viewer)).await
+}
+
+fn error_code(response: &async_graphql::Response) -> async_graphql::Value {
+    let extensions = response.errors[0].extensions.as_ref().unwrap();
+    extensions.get("code").unwrap().clone()
+}
+
+#[tokio::test]
+async fn test_alert_rule_mutations_are_scoped_to_jwt_user() {
+    let (schema, _) = schema().await;
+    let alice = Viewer::User {
+        user_id: "u1".to_string(),
+    };
+    let bob = Viewer::User {
+        user_id: "u2".to_string(),
+    };
+    let create = r#"mutation {
+        createAlertRule(input: { metricType: "success_rate", condition: "below", threshold: 90.0 }) {
+            id notifyInApp isActive
+        }
+    }"#;
+
+    let response = schema.execute(create).await;
+    assert_eq!(error_code(&response), "UNAUTHENTICATED".into());
+    let wallet = Viewer::Sep10 {
+        account: "GWALLET".to_string(),
+    };
+    let response = execute_as(&schema, wallet, create).await;
+    assert_eq!(error_code(&response), "UNAUTHENTICATED".into());
+
+    let response = execute_as(&schema, alice.clone(), create).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let rule = response.data.into_json().unwrap()["createAlertRule"].clone();
+    assert_eq!(rule["notifyInApp"], true);
+    let rule_id = rule["id"].as_str().unwrap();
+
+    let snooze = format!(
+        r#"mutation {{ snoozeAlertRule(id: "{}", until: "2999-01-01T00:00:00Z") {{ snoozedUntil }} }}"#,
+        rule_id
+    );
+    let response = execute_as(&schema, bob.clone(), &snooze).await;
+    assert_eq!(error_code(&response), "NOT_FOUND".into());
+    let response = execute_as(&schema, alice.clone(), &snooze).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let delete = format!(r#"mutation {{ deleteAlertRule(id: "{}") }}"#, rule_id);
+    let response = execute_as(&schema, bob, &delete).await;
+    assert_eq!(error_code(&response), "NOT_FOUND".into());
+    let response = execute_as(&schema, alice.clone(), &delete).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let response = execute_as(&schema, alice.clone(), &delete).await;
+    assert_eq!(error_code(&response), "NOT_FOUND".into());
+    let response = execute_as(&schema, alice, &snooze).await;
+    assert_eq!(error_code(&response), "NOT_FOUND".into());
+}
+
+#[tokio::test]
+async fn test_register_webhook_rejects_internal_urls() {
+    let (schema, _) = schema().await;
+    let alice = Viewer::User {
+        user_id: "u1".to_string(),
+    };
+
+    let response = execute_as(
+        &schema,
+        alice.clone(),
+        r#"mutation { registerWebhook(input: { url: "http://169.254.169.254/hook", eventTypes: ["payment.created"] }) { id } }"#,
+    )
+    .await;
+    assert_eq!(error_code(&response), "BAD_USER_INPUT".into());
+
+    let response = execute_as(
+        &schema,
+        alice.clone(),
+        r#"mutation { registerWebhook(input: { url: "https://hooks.example.com/si", eventTypes: ["payment.created"], filters: "{\"corridor\": \"USDC-XLM\"}" }) { id eventTypes filters secret } }"#,
+    )
+    .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let webhook = response.data.into_json().unwrap()["registerWebhook"].clone();
+    assert_eq!(webhook["eventTypes"][0], "payment.created");
+    assert!(webhook["secret"].is_string());
+
+    let delete = format!(
+        r#"mutation {{ deleteWebhook(id: "{}") }}"#,
+        webhook["id"].as_str().unwrap()
+    );
+    let bob = Viewer::User {
+        user_id: "u2".to_string(),
+    };
+    let response = execute_as(&schema, bob, &delete).await;
+    assert_eq!(error_code(&response), "NOT_FOUND".into());
+    let response = execute_as(&schema, alice, &delete).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+}
+
+#[tokio::test]
+async fn test_api_keys_and_votes_follow_wallet_auth() {
+    let (schema, _) = schema().await;
+    let session = Viewer::Sep10 {
+        account: "GVOTER".to_string(),
+    };
+
+    let response = execute_as(
+        &schema,
+        session.clone(),
+        r#"mutation { createApiKey(input: { name: "ci" }) { plainKey key { id walletAddress } } }"#,
+    )
+    .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let issued = response.data.into_json().unwrap()["createApiKey"].clone();
+    assert_eq!(issued["key"]["walletAddress"], "GVOTER");
+    let key_id = issued["key"]["id"].as_str().unwrap().to_string();
+
+    // The key may not issue keys or vote for its wallet, only revoke keys
+    let api_key = Viewer::ApiKey {
+        key_id: key_id.clone(),
+        wallet_address: "GVOTER".to_string(),
+    };
+    let response = execute_as(
+        &schema,
+        api_key.clone(),
+        r#"mutation { createApiKey(input: { name: "copy" }) { plainKey } }"#,
+    )
+    .await;
+    assert_eq!(error_code(&response), "UNAUTHENTICATED".into());
+    let rotate = format!(
+        r#"mutation {{ rotateApiKey(id: "{}") {{ plainKey }} }}"#,
+        key_id
+    );
+    let response = execute_as(&schema, api_key.clone(), &rotate).await;
+    assert_eq!(error_code(&response), "UNAUTHENTICATED".into());
+
+    let vote = r#"mutation { castVote(input: { proposalId: "p1", choice: "for" }) { voterAddress choice } }"#;
+    let response = execute_as(&schema, api_key.clone(), vote).await;
+    assert_eq!(error_code(&response), "UNAUTHENTICATED".into());
+
+    let response = execute_as(&schema, session.clone(), vote).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap()["castVote"]["voterAddress"],
+        "GVOTER"
+    );
+    let response = execute_as(&schema, session, vote).await;
+    assert_eq!(error_code(&response), "BAD_USER_INPUT".into());
+
+    let revoke = format!(r#"mutation {{ revokeApiKey(id: "{}") }}"#, key_id);
+    let other_wallet = Viewer::Sep10 {
+        account: "GOTHER".to_string(),
+    };
+    let response = execute_as(&schema, other_wallet, &revoke).await;
+    assert_eq!(error_code(&response), "NOT_FOUND".into());
+    let response = execute_as(&schema, api_key, &revoke).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+}
+
+#[tokio::test]
+async fn test_data_loader_batches_concurrent_keys() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct CountingLoader(Arc<AtomicUsize>);
+
+    #[async_trait::async_trait]
+    impl loaders::Loader for CountingLoader {
+        type Key = u32;
+        type Value = u32;
+
+        async fn load(&self, keys: &[u32]) -> anyhow::Result<std::collections::HashMap<u32, u32>> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(keys
+                .iter()
+                .filter(|k| **k != 3)
+                .map(|k| (*k, k * 10))
+                .collect())
+        }
+    }
+
+    let batches = Arc::new(AtomicUsize::new(0));
+    let loader = loaders::DataLoader::new(CountingLoader(Arc::clone(&batches)));
+    let values = futures::future::join_all((1..=4).map(|k| loader.load_one(k))).await;
+    let values: Vec<Option<u32>> = values.into_iter().map(Result::unwrap).collect();
+    assert_eq!(values, vec![Some(10), Some(20), None, Some(40)]);
+
+    // A later key starts a new batch
+    assert_eq!(loader.load_one(2).await.unwrap(), Some(20));
+    assert_eq!(batches.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_nested_relations_resolve_per_parent() {
+    let (schema, _) = schema().await;
+
+    let response = schema
+        .execute(
+            "{ anchors { nodes { id assets { assetCode } } } \
+               corridors { nodes { id metrics { value } } } }",
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let data = response.data.into_json().unwrap();
+    let node = |list: &str, id: &str| {
+        data[list]["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|node| node["id"] == id)
+            .unwrap()
+            .clone()
+    };
+    let alpha = node("anchors", "a1");
+    assert_eq!(alpha["assets"][0]["assetCode"], "EURC");
+    assert_eq!(alpha["assets"][1]["assetCode"], "USDC");
+    assert_eq!(node("anchors", "a2")["assets"].as_array().unwrap().len(), 1);
+    let metrics = &node("corridors", "c1")["metrics"];
+    assert_eq!(metrics.as_array().unwrap().len(), 2);
+    assert_eq!(metrics[0]["value"], 99.0);
+}
+
+#[tokio::test]
+async fn test_query_cost_and_depth_limits() {
+    let mut config = GraphQLConfig::default();
+    config.max_depth = 3;
+    config.max_complexity.anonymous = 40;
+    let (schema, _) = schema_with(config).await;
+
+    // 10 anchors at 3 each, plus the field itself
+    let query = "{ anchors(pagination: { limit: 10 }) { nodes { id name } } }";
+    let response = schema.execute(query).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let expensive = "{ anchors(pagination: { limit: 20 }) { nodes { id name } } }";
+    let response = schema.execute(expensive).await;
+    assert_eq!(error_code(&response), "QUERY_TOO_COMPLEX".into());
+    let response = execute_as(
+        &schema,
+        Viewer::ApiKey {
+            key_id: "k1".to_string(),
+            wallet_address: "GWALLET".to_string(),
+        },
+        expensive,
+    )
+    .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let response = schema
+        .execute("{ anchors { nodes { assets { assetCode } } } }")
+        .await;
+    assert_eq!(response.errors[0].message, "Query is nested too deep.");
+}
+
+#[tokio::test]
+async fn test_persisted_query_is_registered_then_served_by_hash() {
+    let (schema, _) = schema().await;
+    let query = "{ anchor(id: \"a1\") { name } }";
+    let hash = hex::encode(<sha2::Sha256 as sha2::Digest>::digest(query.as_bytes()));
+    let persisted = |query: &str| {
+        let mut request = Request::new(query);
+        request.extensions.insert(
+            "persistedQuery".to_string(),
+            async_graphql::Value::from_json(
+                serde_json::json!({ "version": 1, "sha256Hash": hash }),
+            )
+            .unwrap(),
+        );
+        request
+    };
+
+    let response = schema.execute(persisted("")).await;
+    assert_eq!(error_code(&response), "PERSISTED_QUERY_NOT_FOUND".into());
+    assert_eq!(response.errors[0].message, "PersistedQueryNotFound");
+
+    let response = schema
+        .execute(persisted("{ anchors { totalCount } }"))
+        .await;
+    assert!(!response.errors.is_empty());
+
+    let response = schema.execute(persisted(query)).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let response = schema.execute(persisted("")).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap()["anchor"]["name"],
+        "Alpha Anchor"
+    );
+}
+
+#[tokio::test]
+async fn test_health_alerts_subscription_filters_by_severity() {
+    let (schema, ws_state) = schema().await;
+    let mut stream = schema.execute_stream(
+        r#"subscription { healthAlerts(severity: "critical") { corridorId severity } }"#,
+    );
+
+    // The subscription attaches to the event channel when first polled
+    let publisher = tokio::spawn(async move {
+        while ws_state.events.receiver_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+        for (corridor_id, severity) in [("c1", "warning"), ("c2", "critical")] {
+            ws_state.broadcast(WsMessage::HealthAlert {
+                corridor_id: corridor_id.to_string(),
+                severity: severity.to_string(),
+                message: "volume dropped".to_string(),
+                timestamp: "2024-01-01T00:00:00Z".to_string(),
+            });
+        }
+    });
+
+    let response = tokio::time::timeout(Duration::from_secs(5), stream.next())
+        .await
+        .unwrap()
+        .unwrap();
+    publisher.await.unwrap();
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let data = response.data.into_json().unwrap();
+    assert_eq!(data["healthAlerts"]["corridorId"], "c2");
+    assert_eq!(data["healthAlerts"]["severity"], "critical");
+}
+
+#[tokio::test]
+async fn test_anchor_status_subscription_skips_unchanged_status() {
+    let (schema, ws_state) = schema().await;
+    let mut stream = schema.execute_stream(
+        r#"subscription { anchorStatusChanged(anchorId: "a1") { anchorId status } }"#,
+    );
+
+    let publisher = tokio::spawn(async move {
+        while ws_state.events.receiver_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+        for (anchor_id, status) in [
+            ("a1", "green"),
+            ("a2", "red"),
+            ("a1", "green"),
+            ("a1", "red"),
+        ] {
+            ws_state.broadcast(WsMessage::AnchorUpdate {
+                anchor_id: anchor_id.to_string(),
+                name: "Alpha Anchor".to_string(),
+                reliability_score: 90.0,
+                status: status.to_string(),
+            });
+        }
+    });
+
+    let mut statuses = Vec::new();
+    for _ in 0..2 {
+        let response = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .unwrap()
+            .unwrap();
+        let data = response.data.into_json().unwrap();
+        assert_eq!(data["anchorStatusChanged"]["anchorId"], "a1");
+        statuses.push(
+            data["anchorStatusChanged"]["status"]
+                .as_str()
+                .unwrap()
+                .to_string(),
+        );
     }
+    publisher.await.unwrap();
+    assert_eq!(statuses, vec!["green", "red"]);
 }
@@ -2,9 +2,14 @@ use async_graphql::*;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::models::alerts::{AlertRule, CreateAlertRuleRequest, UpdateAlertRuleRequest};
+use crate::models::api_key::{ApiKeyInfo, CreateApiKeyRequest, CreateApiKeyResponse};
+use crate::services::governance::VoteResponse;
+use crate::webhooks::WebhookResponse;
+
 /// Anchor entity with metrics
-#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
-#[graphql(name = "Anchor")]
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject, sqlx::FromRow)]
+#[graphql(name = "Anchor", complex)]
 pub struct AnchorType {
     /// Unique identifier
     pub id: String,
@@ -23,7 +28,7 @@ pub struct AnchorType {
     /// Total volume in USD
     pub total_volume_usd: f64,
     /// Average settlement time in milliseconds
-    pub avg_settlement_time_ms: i64,
+    pub avg_settlement_time_ms: i32,
     /// Reliability score (0-100)
     pub reliability_score: f64,
     /// Status (green, yellow, red)
@@ -35,7 +40,7 @@ pub struct AnchorType {
 }
 
 /// Asset entity
-#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject, sqlx::FromRow)]
 #[graphql(name = "Asset")]
 pub struct AssetType {
     /// Unique identifier
@@ -57,8 +62,8 @@ pub struct AssetType {
 }
 
 /// Corridor entity representing a payment path
-#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
-#[graphql(name = "Corridor")]
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject, sqlx::FromRow)]
+#[graphql(name = "Corridor", complex)]
 pub struct CorridorType {
     /// Unique identifier
     pub id: String,
@@ -81,7 +86,7 @@ pub struct CorridorType {
 }
 
 /// Metric data point
-#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject, sqlx::FromRow)]
 #[graphql(name = "Metric")]
 pub struct MetricType {
     /// Unique identifier
@@ -101,7 +106,7 @@ pub struct MetricType {
 }
 
 /// Snapshot of entity state
-#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject, sqlx::FromRow)]
 #[graphql(name = "Snapshot")]
 pub struct SnapshotType {
     /// Unique identifier
@@ -168,6 +173,335 @@ pub struct TrustlineStatType {
     pub updated_at: DateTime<Utc>,
 }
 
+/// Corridor metrics for one time bucket
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(name = "CorridorMetricsPoint")]
+pub struct CorridorMetricsPointType {
+    /// Start of the bucket
+    pub bucket_start: DateTime<Utc>,
+    /// Total number of transactions
+    pub total_transactions: i64,
+    /// Number of successful transactions
+    pub successful_transactions: i64,
+    /// Number of failed transactions
+    pub failed_transactions: i64,
+    /// Success rate (0-100)
+    pub success_rate: f64,
+    /// Total volume in USD
+    pub volume_usd: f64,
+    /// Average slippage in basis points
+    pub avg_slippage_bps: f64,
+    /// Average settlement latency in milliseconds
+    pub avg_settlement_latency_ms: Option<i32>,
+    /// Average liquidity depth in USD
+    pub liquidity_depth_usd: f64,
+}
+
+/// Corridor metrics series at the resolution chosen for the requested range
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(name = "CorridorMetricsSeries")]
+pub struct CorridorMetricsSeriesType {
+    /// Corridor key (CODE:ISSUER->CODE:ISSUER)
+    pub corridor_key: String,
+    /// Bucket resolution (hour, day, week, month)
+    pub resolution: String,
+    /// Data points, oldest first
+    pub points: Vec<CorridorMetricsPointType>,
+}
+
+/// Forecast value for one future hour
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(name = "ForecastPoint")]
+pub struct ForecastPointType {
+    /// Start of the hour
+    pub timestamp: DateTime<Utc>,
+    /// Point forecast
+    pub forecast: f64,
+    /// Lower bound of the prediction interval
+    pub lower: f64,
+    /// Upper bound of the prediction interval
+    pub upper: f64,
+}
+
+/// Hourly forecast of one corridor metric
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(name = "MetricForecast")]
+pub struct MetricForecastType {
+    /// Length of the seasonal cycle in hours (24 or 168)
+    pub season_length_hours: i32,
+    /// Hours of history the model was fitted on
+    pub observations: i32,
+    /// Sum of the hourly forecasts over the horizon; null for liquidity, which is a
+    /// level rather than a flow
+    pub total: Option<f64>,
+    /// Lower bound of the prediction interval of the total, from simulated paths
+    pub total_lower: Option<f64>,
+    /// Upper bound of the prediction interval of the total, from simulated paths
+    pub total_upper: Option<f64>,
+    /// Hour with the lowest lower bound
+    pub trough: Option<ForecastPointType>,
+    /// Hourly forecasts, soonest first
+    pub points: Vec<ForecastPointType>,
+}
+
+/// Volume and liquidity forecasts of a corridor
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(name = "CorridorForecast")]
+pub struct CorridorForecastType {
+    /// Corridor key (CODE:ISSUER->CODE:ISSUER)
+    pub corridor_key: String,
+    /// Start of the first forecast hour
+    pub forecast_start: DateTime<Utc>,
+    /// Forecast horizon in hours
+    pub horizon_hours: i32,
+    /// Coverage of the prediction intervals (e.g. 0.95)
+    pub confidence: f64,
+    /// Hourly volume in USD; null without two days of history
+    pub volume_usd: Option<MetricForecastType>,
+    /// Hourly liquidity depth in USD; null without two days of history
+    pub liquidity_depth_usd: Option<MetricForecastType>,
+}
+
+/// Live corridor metrics, pushed as payments stream in
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(name = "CorridorUpdate")]
+pub struct CorridorUpdateType {
+    /// Corridor key (CODE:ISSUER->CODE:ISSUER)
+    pub corridor_key: String,
+    /// Source asset code
+    pub asset_a_code: String,
+    /// Source asset issuer
+    pub asset_a_issuer: String,
+    /// Destination asset code
+    pub asset_b_code: String,
+    /// Destination asset issuer
+    pub asset_b_issuer: String,
+    /// Success rate of the current hour, when known
+    pub success_rate: Option<f64>,
+    /// Health score, when known
+    pub health_score: Option<f64>,
+    /// Time of the latest payment counted (RFC 3339)
+    pub last_updated: Option<String>,
+}
+
+/// Anchor status after a change
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(name = "AnchorStatusUpdate")]
+pub struct AnchorStatusType {
+    /// Anchor ID
+    pub anchor_id: String,
+    /// Anchor name
+    pub name: String,
+    /// Reliability score (0-100)
+    pub reliability_score: f64,
+    /// New status (green, yellow, red)
+    pub status: String,
+}
+
+/// Snapshot hash anchored on-chain
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(name = "SnapshotSubmission")]
+pub struct SnapshotSubmissionType {
+    /// Snapshot or contract event ID
+    pub snapshot_id: String,
+    /// Epoch number
+    pub epoch: i64,
+    /// Time of the submission (RFC 3339)
+    pub timestamp: String,
+    /// Hex-encoded snapshot hash
+    pub hash: String,
+}
+
+/// Corridor health alert
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(name = "HealthAlert")]
+pub struct HealthAlertType {
+    /// Corridor the alert is about
+    pub corridor_id: String,
+    /// Severity (e.g. warning, critical)
+    pub severity: String,
+    /// Human-readable description
+    pub message: String,
+    /// Time of the alert (RFC 3339)
+    pub timestamp: String,
+}
+
+/// User-defined alert rule on a corridor metric
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(name = "AlertRule")]
+pub struct AlertRuleType {
+    /// Unique identifier
+    pub id: String,
+    /// Corridor the rule watches, or every corridor when absent
+    pub corridor_id: Option<String>,
+    /// Metric compared against the threshold (e.g. success_rate, latency, liquidity)
+    pub metric_type: String,
+    /// Comparison (above, below, equals, anomaly)
+    pub condition: String,
+    /// Threshold value, a z-score for the anomaly condition
+    pub threshold: f64,
+    /// Notify by email
+    pub notify_email: bool,
+    /// Notify through registered webhooks
+    pub notify_webhook: bool,
+    /// Notify in the app
+    pub notify_in_app: bool,
+    /// Whether the rule is evaluated
+    pub is_active: bool,
+    /// Rule is muted until this time
+    pub snoozed_until: Option<DateTime<Utc>>,
+    /// Creation timestamp
+    pub created_at: DateTime<Utc>,
+    /// Last update timestamp
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<AlertRule> for AlertRuleType {
+    fn from(rule: AlertRule) -> Self {
+        Self {
+            id: rule.id,
+            corridor_id: rule.corridor_id,
+            metric_type: rule.metric_type,
+            condition: rule.condition,
+            threshold: rule.threshold,
+            notify_email: rule.notify_email,
+            notify_webhook: rule.notify_webhook,
+            notify_in_app: rule.notify_in_app,
+            is_active: rule.is_active,
+            snoozed_until: rule.snoozed_until,
+            created_at: rule.created_at,
+            updated_at: rule.updated_at,
+        }
+    }
+}
+
+/// Registered webhook endpoint
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(name = "Webhook")]
+pub struct WebhookType {
+    /// Unique identifier
+    pub id: String,
+    /// Delivery URL
+    pub url: String,
+    /// Subscribed event types (e.g. corridor.health_degraded)
+    pub event_types: Vec<String>,
+    /// Payload filters (JSON)
+    pub filters: Option<String>,
+    /// Whether deliveries are made
+    pub is_active: bool,
+    /// Creation timestamp (RFC 3339)
+    pub created_at: String,
+    /// Signing secret, only returned by `registerWebhook`
+    pub secret: Option<String>,
+}
+
+impl From<WebhookResponse> for WebhookType {
+    fn from(webhook: WebhookResponse) -> Self {
+        Self {
+            id: webhook.id,
+            url: webhook.url,
+            event_types: webhook.event_types,
+            filters: webhook.filters.map(|f| f.to_string()),
+            is_active: webhook.is_active,
+            created_at: webhook.created_at,
+            secret: webhook.secret,
+        }
+    }
+}
+
+/// API key metadata; the key itself is only returned when it is issued
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(name = "ApiKey")]
+pub struct ApiKeyType {
+    /// Unique identifier
+    pub id: String,
+    /// Display name
+    pub name: String,
+    /// Leading characters of the key, for recognising it
+    pub key_prefix: String,
+    /// Wallet that owns the key
+    pub wallet_address: String,
+    /// Comma-separated scopes
+    pub scopes: String,
+    /// Status (active, revoked)
+    pub status: String,
+    /// Creation timestamp (RFC 3339)
+    pub created_at: String,
+    /// Last time the key authenticated a request (RFC 3339)
+    pub last_used_at: Option<String>,
+    /// Expiry (RFC 3339)
+    pub expires_at: Option<String>,
+    /// Revocation time (RFC 3339)
+    pub revoked_at: Option<String>,
+}
+
+impl From<ApiKeyInfo> for ApiKeyType {
+    fn from(key: ApiKeyInfo) -> Self {
+        Self {
+            id: key.id,
+            name: key.name,
+            key_prefix: key.key_prefix,
+            wallet_address: key.wallet_address,
+            scopes: key.scopes,
+            status: key.status,
+            created_at: key.created_at,
+            last_used_at: key.last_used_at,
+            expires_at: key.expires_at,
+            revoked_at: key.revoked_at,
+        }
+    }
+}
+
+/// Newly issued API key
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(name = "IssuedApiKey")]
+pub struct IssuedApiKeyType {
+    /// Key metadata
+    pub key: ApiKeyType,
+    /// The key itself. It is not stored and cannot be retrieved again.
+    pub plain_key: String,
+}
+
+impl From<CreateApiKeyResponse> for IssuedApiKeyType {
+    fn from(response: CreateApiKeyResponse) -> Self {
+        Self {
+            key: response.key.into(),
+            plain_key: response.plain_key,
+        }
+    }
+}
+
+/// Vote cast on a governance proposal
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(name = "GovernanceVote")]
+pub struct GovernanceVoteType {
+    /// Unique identifier
+    pub id: String,
+    /// Proposal voted on
+    pub proposal_id: String,
+    /// Voting account
+    pub voter_address: String,
+    /// Choice (for, against, abstain)
+    pub choice: String,
+    /// Hash of the on-chain vote transaction
+    pub tx_hash: Option<String>,
+    /// Time of the vote (RFC 3339)
+    pub voted_at: String,
+}
+
+impl From<VoteResponse> for GovernanceVoteType {
+    fn from(vote: VoteResponse) -> Self {
+        Self {
+            id: vote.id,
+            proposal_id: vote.proposal_id,
+            voter_address: vote.voter_address,
+            choice: vote.choice,
+            tx_hash: vote.tx_hash,
+            voted_at: vote.voted_at,
+        }
+    }
+}
+
 /// Pagination input
 #[derive(Debug, Clone, InputObject)]
 pub struct PaginationInput {
@@ -210,6 +544,121 @@ pub struct TimeRangeInput {
     pub end: DateTime<Utc>,
 }
 
+/// Fields of a new alert rule
+#[derive(Debug, Clone, InputObject)]
+pub struct CreateAlertRuleInput {
+    /// Corridor to watch, or every corridor when absent
+    pub corridor_id: Option<String>,
+    /// Metric to compare (e.g. success_rate, latency, liquidity)
+    pub metric_type: String,
+    /// Comparison (above, below, equals, anomaly)
+    pub condition: String,
+    /// Threshold value
+    pub threshold: f64,
+    /// Notify by email (default: false)
+    #[graphql(default)]
+    pub notify_email: bool,
+    /// Notify through registered webhooks (default: false)
+    #[graphql(default)]
+    pub notify_webhook: bool,
+    /// Notify in the app (default: true)
+    #[graphql(default = true)]
+    pub notify_in_app: bool,
+}
+
+impl From<CreateAlertRuleInput> for CreateAlertRuleRequest {
+    fn from(input: CreateAlertRuleInput) -> Self {
+        Self {
+            corridor_id: input.corridor_id,
+            metric_type: input.metric_type,
+            condition: input.condition,
+            threshold: input.threshold,
+            notify_email: input.notify_email,
+            notify_webhook: input.notify_webhook,
+            notify_in_app: input.notify_in_app,
+        }
+    }
+}
+
+/// Alert rule fields to change; absent fields are left as they are
+#[derive(Debug, Clone, InputObject)]
+pub struct UpdateAlertRuleInput {
+    /// Corridor to watch
+    pub corridor_id: Option<String>,
+    /// Metric to compare
+    pub metric_type: Option<String>,
+    /// Comparison
+    pub condition: Option<String>,
+    /// Threshold value
+    pub threshold: Option<f64>,
+    /// Notify by email
+    pub notify_email: Option<bool>,
+    /// Notify through registered webhooks
+    pub notify_webhook: Option<bool>,
+    /// Notify in the app
+    pub notify_in_app: Option<bool>,
+    /// Whether the rule is evaluated
+    pub is_active: Option<bool>,
+}
+
+impl From<UpdateAlertRuleInput> for UpdateAlertRuleRequest {
+    fn from(input: UpdateAlertRuleInput) -> Self {
+        Self {
+            corridor_id: input.corridor_id,
+            metric_type: input.metric_type,
+            condition: input.condition,
+            threshold: input.threshold,
+            notify_email: input.notify_email,
+            notify_webhook: input.notify_webhook,
+            notify_in_app: input.notify_in_app,
+            is_active: input.is_active,
+        }
+    }
+}
+
+/// Webhook to register
+#[derive(Debug, Clone, InputObject)]
+pub struct RegisterWebhookInput {
+    /// HTTP(S) URL deliveries are posted to
+    pub url: String,
+    /// Event types to subscribe to
+    pub event_types: Vec<String>,
+    /// Payload filters (JSON object)
+    pub filters: Option<String>,
+}
+
+/// API key to issue
+#[derive(Debug, Clone, InputObject)]
+pub struct CreateApiKeyInput {
+    /// Display name
+    pub name: String,
+    /// Comma-separated scopes (default: read)
+    pub scopes: Option<String>,
+    /// Expiry (RFC 3339)
+    pub expires_at: Option<String>,
+}
+
+impl From<CreateApiKeyInput> for CreateApiKeyRequest {
+    fn from(input: CreateApiKeyInput) -> Self {
+        Self {
+            name: input.name,
+            scopes: input.scopes,
+            expires_at: input.expires_at,
+        }
+    }
+}
+
+/// Vote on a governance proposal
+#[derive(Debug, Clone, InputObject)]
+pub struct CastVoteInput {
+    /// Proposal to vote on
+    pub proposal_id: String,
+    /// Choice (for, against, abstain)
+    pub choice: String,
+    /// Hash of the on-chain vote transaction
+    pub tx_hash: Option<String>,
+}
+
 /// Paginated response wrapper
 #[derive(Debug, Clone, SimpleObject)]
 #[graphql(name = "AnchorsConnection")]
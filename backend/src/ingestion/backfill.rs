@@ -0,0 +1,905 @@
+//! Parallel ledger backfill with gap detection.
+//!
+//! A backfill job splits a ledger range into chunks and ingests them with bounded
+//! concurrency through [`LedgerIngestionService::ingest_batch_from`]. Chunk progress
+//! is persisted after every batch so a restarted job picks up where it stopped.
+//! The gap scanner looks for holes in the stored `ledgers` table and queues them as
+//! a new job.
+
+use crate::database::DbPool;
+use crate::with_pool;
+use anyhow::{bail, Context, Result};
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::ingestion::ledger::LedgerIngestionService;
+
+const STATUS_PENDING: &str = "pending";
+const STATUS_RUNNING: &str = "running";
+const STATUS_COMPLETED: &str = "completed";
+const STATUS_FAILED: &str = "failed";
+
+const SOURCE_MANUAL: &str = "manual";
+const SOURCE_GAP_SCAN: &str = "gap_scan";
+
+/// Upper bound on the number of ledgers a single job may cover
+const MAX_BACKFILL_LEDGERS: u64 = 5_000_000;
+/// Upper bound on concurrently ingested chunks
+const MAX_BACKFILL_CONCURRENCY: usize = 32;
+
+/// Tuning knobs for backfills
+#[derive(Debug, Clone)]
+pub struct BackfillConfig {
+    /// Ledgers per chunk
+    pub chunk_size: u64,
+    /// Chunks ingested concurrently
+    pub concurrency: usize,
+    /// Ledgers requested per `getLedgers` call
+    pub batch_size: u32,
+    /// Attempts per chunk before it is marked failed
+    pub max_chunk_attempts: u32,
+}
+
+impl Default for BackfillConfig {
+    fn default() -> Self {
+        Self {
+            chunk_size: 1_000,
+            concurrency: 4,
+            batch_size: 50,
+            max_chunk_attempts: 5,
+        }
+    }
+}
+
+impl BackfillConfig {
+    /// Load configuration from `LEDGER_BACKFILL_*` environment variables
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let read = |name: &str| std::env::var(name).ok();
+
+        Self {
+            chunk_size: read("LEDGER_BACKFILL_CHUNK_SIZE")
+                .and_then(|v| v.parse().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(defaults.chunk_size),
+            concurrency: read("LEDGER_BACKFILL_CONCURRENCY")
+                .and_then(|v| v.parse().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(defaults.concurrency)
+                .min(MAX_BACKFILL_CONCURRENCY),
+            batch_size: read("LEDGER_BACKFILL_BATCH_SIZE")
+                .and_then(|v| v.parse().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(defaults.batch_size),
+            max_chunk_attempts: read("LEDGER_BACKFILL_MAX_ATTEMPTS")
+                .and_then(|v| v.parse().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(defaults.max_chunk_attempts),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct BackfillJob {
+    pub id: String,
+    pub start_ledger: i64,
+    pub end_ledger: i64,
+    pub chunk_size: i64,
+    pub concurrency: i64,
+    pub source: String,
+    pub status: String,
+    pub error: Option<String>,
+    pub created_at: Option<String>,
+    pub started_at: Option<String>,
+    pub completed_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct BackfillChunk {
+    pub job_id: String,
+    pub chunk_start: i64,
+    pub chunk_end: i64,
+    pub next_ledger: i64,
+    pub ledgers_ingested: i64,
+    pub attempts: i64,
+    pub status: String,
+    pub error: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+/// A job with its chunks and aggregate progress
+#[derive(Debug, Clone, Serialize)]
+pub struct BackfillJobDetail {
+    #[serde(flatten)]
+    pub job: BackfillJob,
+    pub total_ledgers: i64,
+    pub ledgers_ingested: i64,
+    pub chunks_completed: usize,
+    pub chunks_failed: usize,
+    pub chunks: Vec<BackfillChunk>,
+}
+
+/// An inclusive range of ledger sequences missing from storage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LedgerGap {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl LedgerGap {
+    pub fn len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.end < self.start
+    }
+}
+
+/// Split an inclusive ledger range into chunks of at most `chunk_size` ledgers
+pub fn split_range(start: u64, end: u64, chunk_size: u64) -> Vec<(u64, u64)> {
+    let chunk_size = chunk_size.max(1);
+    let mut chunks = Vec::new();
+    let mut chunk_start = start;
+
+    while chunk_start <= end {
+        let chunk_end = chunk_start.saturating_add(chunk_size - 1).min(end);
+        chunks.push((chunk_start, chunk_end));
+        if chunk_end == u64::MAX {
+            break;
+        }
+        chunk_start = chunk_end + 1;
+    }
+
+    chunks
+}
+
+pub struct LedgerBackfillService {
+    ingestion: Arc<LedgerIngestionService>,
+    pool: DbPool,
+    config: BackfillConfig,
+}
+
+impl LedgerBackfillService {
+    pub fn new(
+        ingestion: Arc<LedgerIngestionService>,
+        pool: DbPool,
+        config: BackfillConfig,
+    ) -> Self {
+        Self {
+            ingestion,
+            pool,
+            config,
+        }
+    }
+
+    /// Create a backfill job for an inclusive ledger range
+    pub async fn create_job(
+        &self,
+        start_ledger: u64,
+        end_ledger: u64,
+        chunk_size: Option<u64>,
+        concurrency: Option<usize>,
+    ) -> Result<BackfillJob> {
+        if start_ledger == 0 || end_ledger < start_ledger {
+            bail!("invalid ledger range {}..={}", start_ledger, end_ledger);
+        }
+        if end_ledger - start_ledger + 1 > MAX_BACKFILL_LEDGERS {
+            bail!(
+                "backfill range too large: {} ledgers (max {})",
+                end_ledger - start_ledger + 1,
+                MAX_BACKFILL_LEDGERS
+            );
+        }
+
+        let chunk_size = chunk_size
+            .filter(|v| *v > 0)
+            .unwrap_or(self.config.chunk_size);
+        let ranges = split_range(start_ledger, end_ledger, chunk_size);
+
+        self.insert_job(&ranges, SOURCE_MANUAL, chunk_size, concurrency)
+            .await
+    }
+
+    async fn insert_job(
+        &self,
+        ranges: &[(u64, u64)],
+        source: &str,
+        chunk_size: u64,
+        concurrency: Option<usize>,
+    ) -> Result<BackfillJob> {
+        let (Some(first), Some(last)) = (ranges.first(), ranges.last()) else {
+            bail!("backfill job needs at least one ledger range");
+        };
+
+        let id = Uuid::new_v4().to_string();
+        let concurrency = concurrency
+            .filter(|v| *v > 0)
+            .unwrap_or(self.config.concurrency)
+            .min(MAX_BACKFILL_CONCURRENCY);
+
+        with_pool!(&self.pool, |pool| {
+            let mut tx = pool.begin().await?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO ledger_backfill_jobs (id, start_ledger, end_ledger, chunk_size, concurrency, source, status)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+            )
+            .bind(&id)
+            .bind(first.0 as i64)
+            .bind(last.1 as i64)
+            .bind(chunk_size as i64)
+            .bind(concurrency as i64)
+            .bind(source)
+            .bind(STATUS_PENDING)
+            .execute(&mut *tx)
+            .await?;
+
+            for (chunk_start, chunk_end) in ranges {
+                sqlx::query(
+                    r#"
+                    INSERT INTO ledger_backfill_chunks (job_id, chunk_start, chunk_end, next_ledger, status)
+                    VALUES ($1, $2, $3, $2, $4)
+                    "#,
+                )
+                .bind(&id)
+                .bind(*chunk_start as i64)
+                .bind(*chunk_end as i64)
+                .bind(STATUS_PENDING)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            tx.commit().await?;
+        });
+
+        info!(
+            "Created {} backfill job {} for ledgers {}..={} ({} chunks)",
+            source,
+            id,
+            first.0,
+            last.1,
+            ranges.len()
+        );
+
+        self.fetch_job(&id)
+            .await?
+            .context("backfill job disappeared after insert")
+    }
+
+    /// Run a job in the background
+    pub fn spawn_job(self: &Arc<Self>, job_id: String) {
+        let service = Arc::clone(self);
+        tokio::spawn(async move {
+            if let Err(e) = service.run_job(&job_id).await {
+                error!("Backfill job {} failed: {:#}", job_id, e);
+            }
+        });
+    }
+
+    /// Ingest every unfinished chunk of a job with bounded parallelism
+    pub async fn run_job(&self, job_id: &str) -> Result<BackfillJob> {
+        let job = self
+            .fetch_job(job_id)
+            .await?
+            .with_context(|| format!("backfill job {} not found", job_id))?;
+
+        // Claim the job so two runners never work on it at once
+        let claimed = with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                UPDATE ledger_backfill_jobs
+                SET status = $1, error = NULL, started_at = COALESCE(started_at, CAST(CURRENT_TIMESTAMP AS TEXT)), completed_at = NULL
+                WHERE id = $2 AND status != $1
+                "#,
+            )
+            .bind(STATUS_RUNNING)
+            .bind(job_id)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
+        if claimed == 0 {
+            bail!("backfill job {} is already running", job_id);
+        }
+
+        let chunks: Vec<BackfillChunk> = with_pool!(&self.pool, |pool| {
+            sqlx::query_as(
+                r#"
+                SELECT * FROM ledger_backfill_chunks
+                WHERE job_id = $1 AND status != $2
+                ORDER BY chunk_start
+                "#,
+            )
+            .bind(job_id)
+            .bind(STATUS_COMPLETED)
+            .fetch_all(pool)
+            .await
+        })?;
+
+        info!(
+            "Running backfill job {}: {} chunk(s) remaining, concurrency {}",
+            job_id,
+            chunks.len(),
+            job.concurrency
+        );
+
+        let concurrency = usize::try_from(job.concurrency)
+            .unwrap_or(1)
+            .clamp(1, MAX_BACKFILL_CONCURRENCY);
+        let results: Vec<Result<()>> = stream::iter(chunks)
+            .map(|chunk| self.run_chunk(chunk))
+            .buffer_unordered(concurrency)
+            .collect()
+            .await;
+
+        let failed = results.iter().filter(|r| r.is_err()).count();
+        let (status, error) = if failed == 0 {
+            (STATUS_COMPLETED, None)
+        } else {
+            (STATUS_FAILED, Some(format!("{} chunk(s) failed", failed)))
+        };
+
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                UPDATE ledger_backfill_jobs
+                SET status = $1, error = $2, completed_at = CURRENT_TIMESTAMP
+                WHERE id = $3
+                "#,
+            )
+            .bind(status)
+            .bind(&error)
+            .bind(job_id)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
+
+        info!("Backfill job {} finished with status {}", job_id, status);
+
+        self.fetch_job(job_id)
+            .await?
+            .with_context(|| format!("backfill job {} not found", job_id))
+    }
+
+    /// Ingest one chunk, retrying failed batches with exponential backoff
+    async fn run_chunk(&self, chunk: BackfillChunk) -> Result<()> {
+        let chunk_end = chunk.chunk_end as u64;
+        let mut next = chunk.next_ledger as u64;
+        let mut attempts = 0u32;
+
+        self.update_chunk(&chunk, next, 0, STATUS_RUNNING, None)
+            .await?;
+
+        while next <= chunk_end {
+            let remaining = chunk_end - next + 1;
+            let limit = u32::try_from(remaining)
+                .unwrap_or(u32::MAX)
+                .min(self.config.batch_size);
+
+            let outcome = match self.ingestion.ingest_batch_from(next, limit).await {
+                Ok((_, None)) => Err(anyhow::anyhow!(
+                    "RPC returned no ledgers starting at {}",
+                    next
+                )),
+                Ok((count, Some(last))) => Ok((count, last)),
+                Err(e) => Err(e),
+            };
+
+            match outcome {
+                Ok((count, last)) => {
+                    attempts = 0;
+                    next = last.saturating_add(1).max(next + 1);
+                    self.update_chunk(&chunk, next, count, STATUS_RUNNING, None)
+                        .await?;
+                }
+                Err(e) => {
+                    attempts += 1;
+                    with_pool!(&self.pool, |pool| {
+                        sqlx::query(
+                            "UPDATE ledger_backfill_chunks SET attempts = attempts + 1 WHERE job_id = $1 AND chunk_start = $2",
+                        )
+                        .bind(&chunk.job_id)
+                        .bind(chunk.chunk_start)
+                        .execute(pool)
+                        .await
+                        .map(|done| done.rows_affected())
+                    })?;
+
+                    if attempts >= self.config.max_chunk_attempts {
+                        let message = format!("{:#}", e);
+                        warn!(
+                            "Backfill chunk {}..={} of job {} failed at ledger {}: {}",
+                            chunk.chunk_start, chunk.chunk_end, chunk.job_id, next, message
+                        );
+                        self.update_chunk(&chunk, next, 0, STATUS_FAILED, Some(&message))
+                            .await?;
+                        return Err(e);
+                    }
+
+                    let backoff = Duration::from_millis(500 * 2u64.pow(attempts.min(6)));
+                    warn!(
+                        "Backfill batch at ledger {} failed (attempt {}), retrying in {:?}: {}",
+                        next, attempts, backoff, e
+                    );
+                    tokio::time::sleep(backoff).await;
+                }
+            }
+        }
+
+        self.update_chunk(&chunk, next, 0, STATUS_COMPLETED, None)
+            .await
+    }
+
+    async fn update_chunk(
+        &self,
+        chunk: &BackfillChunk,
+        next_ledger: u64,
+        ingested: u64,
+        status: &str,
+        error: Option<&str>,
+    ) -> Result<()> {
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                UPDATE ledger_backfill_chunks
+                SET next_ledger = $1,
+                    ledgers_ingested = ledgers_ingested + $2,
+                    status = $3,
+                    error = $4,
+                    updated_at = CURRENT_TIMESTAMP
+                WHERE job_id = $5 AND chunk_start = $6
+                "#,
+            )
+            .bind(next_ledger as i64)
+            .bind(ingested as i64)
+            .bind(status)
+            .bind(error)
+            .bind(&chunk.job_id)
+            .bind(chunk.chunk_start)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
+        Ok(())
+    }
+
+    async fn fetch_job(&self, job_id: &str) -> Result<Option<BackfillJob>> {
+        let job = with_pool!(&self.pool, |pool| {
+            sqlx::query_as("SELECT * FROM ledger_backfill_jobs WHERE id = $1")
+                .bind(job_id)
+                .fetch_optional(pool)
+                .await
+        })?;
+        Ok(job)
+    }
+
+    /// Get a job with its chunks and aggregate progress
+    pub async fn get_job(&self, job_id: &str) -> Result<Option<BackfillJobDetail>> {
+        let Some(job) = self.fetch_job(job_id).await? else {
+            return Ok(None);
+        };
+
+        let chunks: Vec<BackfillChunk> = with_pool!(&self.pool, |pool| {
+            sqlx::query_as(
+                "SELECT * FROM ledger_backfill_chunks WHERE job_id = $1 ORDER BY chunk_start",
+            )
+            .bind(job_id)
+            .fetch_all(pool)
+            .await
+        })?;
+
+        Ok(Some(BackfillJobDetail {
+            total_ledgers: chunks.iter().map(|c| c.chunk_end - c.chunk_start + 1).sum(),
+            ledgers_ingested: chunks.iter().map(|c| c.ledgers_ingested).sum(),
+            chunks_completed: chunks
+                .iter()
+                .filter(|c| c.status == STATUS_COMPLETED)
+                .count(),
+            chunks_failed: chunks.iter().filter(|c| c.status == STATUS_FAILED).count(),
+            job,
+            chunks,
+        }))
+    }
+
+    /// List the most recent jobs
+    pub async fn list_jobs(&self, limit: i64) -> Result<Vec<BackfillJob>> {
+        let jobs = with_pool!(&self.pool, |pool| {
+            sqlx::query_as(
+                "SELECT * FROM ledger_backfill_jobs ORDER BY created_at DESC, id LIMIT $1",
+            )
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+        })?;
+        Ok(jobs)
+    }
+
+    /// Find ledger sequences missing from the `ledgers` table.
+    ///
+    /// Holes between stored ledgers are always reported. When `from` or `to` is
+    /// given, missing ledgers between that bound and the nearest stored ledger are
+    /// reported too.
+    pub async fn find_gaps(&self, from: Option<u64>, to: Option<u64>) -> Result<Vec<LedgerGap>> {
+        let lower = from.map_or(0, |v| v as i64);
+        let upper = to.map_or(i64::MAX, |v| v as i64);
+
+        let rows: Vec<(i64, i64)> = with_pool!(&self.pool, |pool| {
+            sqlx::query_as(
+                r#"
+                SELECT sequence + 1 AS gap_start, next_sequence - 1 AS gap_end
+                FROM (
+                    SELECT sequence, LEAD(sequence) OVER (ORDER BY sequence) AS next_sequence
+                    FROM ledgers
+                    WHERE sequence BETWEEN $1 AND $2
+                ) AS seq
+                WHERE next_sequence IS NOT NULL AND next_sequence - sequence > 1
+                ORDER BY gap_start
+                "#,
+            )
+            .bind(lower)
+            .bind(upper)
+            .fetch_all(pool)
+            .await
+        })?;
+
+        let mut gaps: Vec<LedgerGap> = rows
+            .into_iter()
+            .map(|(start, end)| LedgerGap {
+                start: start as u64,
+                end: end as u64,
+            })
+            .collect();
+
+        let bounds: (Option<i64>, Option<i64>) = with_pool!(&self.pool, |pool| {
+            sqlx::query_as(
+                "SELECT MIN(sequence), MAX(sequence) FROM ledgers WHERE sequence BETWEEN $1 AND $2",
+            )
+            .bind(lower)
+            .bind(upper)
+            .fetch_one(pool)
+            .await
+        })?;
+
+        match bounds {
+            (Some(min), Some(max)) => {
+                if let Some(from) = from.filter(|f| (*f as i64) < min) {
+                    gaps.insert(
+                        0,
+                        LedgerGap {
+                            start: from,
+                            end: min as u64 - 1,
+                        },
+                    );
+                }
+                if let Some(to) = to.filter(|t| (*t as i64) > max) {
+                    gaps.push(LedgerGap {
+                        start: max as u64 + 1,
+                        end: to,
+                    });
+                }
+            }
+            _ => {
+                if let (Some(from), Some(to)) = (from, to) {
+                    if from <= to {
+                        gaps.push(LedgerGap {
+                            start: from,
+                            end: to,
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(gaps)
+    }
+
+    /// Scan for gaps and queue them as a single backfill job.
+    ///
+    /// Returns `None` when no gaps were found or the gaps are already covered by
+    /// an unfinished gap-scan job.
+    pub async fn requeue_gaps(
+        &self,
+        from: Option<u64>,
+        to: Option<u64>,
+    ) -> Result<Option<BackfillJob>> {
+        let gaps = self.find_gaps(from, to).await?;
+        if gaps.is_empty() {
+            return Ok(None);
+        }
+
+        let pending: Option<(String,)> = with_pool!(&self.pool, |pool| {
+            sqlx::query_as(
+                "SELECT id FROM ledger_backfill_jobs WHERE source = $1 AND status IN ($2, $3) LIMIT 1",
+            )
+            .bind(SOURCE_GAP_SCAN)
+            .bind(STATUS_PENDING)
+            .bind(STATUS_RUNNING)
+            .fetch_optional(pool)
+            .await
+        })?;
+        if let Some((id,)) = pending {
+            info!(
+                "Found {} ledger gap(s) but gap-scan job {} is still unfinished",
+                gaps.len(),
+                id
+            );
+            return Ok(None);
+        }
+
+        let missing: u64 = gaps.iter().map(LedgerGap::len).sum();
+        if missing > MAX_BACKFILL_LEDGERS {
+            bail!(
+                "{} missing ledgers exceed the backfill limit of {}",
+                missing,
+                MAX_BACKFILL_LEDGERS
+            );
+        }
+
+        let chunk_size = self.config.chunk_size;
+        let ranges: Vec<(u64, u64)> = gaps
+            .iter()
+            .flat_map(|gap| split_range(gap.start, gap.end, chunk_size))
+            .collect();
+
+        info!(
+            "Re-queueing {} missing ledger(s) across {} gap(s)",
+            missing,
+            gaps.len()
+        );
+
+        self.insert_job(&ranges, SOURCE_GAP_SCAN, chunk_size, None)
+            .await
+            .map(Some)
+    }
+
+    /// Resume jobs left unfinished by a previous process.
+    ///
+    /// Jobs marked running are reset first since nothing can be running them
+    /// right after startup.
+    pub async fn resume_incomplete_jobs(self: &Arc<Self>) -> Result<usize> {
+        with_pool!(&self.pool, |pool| {
+            sqlx::query("UPDATE ledger_backfill_jobs SET status = $1 WHERE status = $2")
+                .bind(STATUS_PENDING)
+                .bind(STATUS_RUNNING)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+        })?;
+
+        let ids: Vec<(String,)> = with_pool!(&self.pool, |pool| {
+            sqlx::query_as(
+                "SELECT id FROM ledger_backfill_jobs WHERE status = $1 ORDER BY created_at",
+            )
+            .bind(STATUS_PENDING)
+            .fetch_all(pool)
+            .await
+        })?;
+
+        for (id,) in &ids {
+            info!("Resuming backfill job {}", id);
+            self.spawn_job(id.clone());
+        }
+
+        Ok(ids.len())
+    }
+
+    /// Seconds between automatic gap scans (`LEDGER_GAP_SCAN_INTERVAL_SECS`)
+    pub fn gap_scan_interval() -> Duration {
+        let secs = std::env::var("LEDGER_GAP_SCAN_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(3600);
+        Duration::from_secs(secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::StellarRpcClient;
+    use crate::services::account_merge_detector::AccountMergeDetector;
+    use crate::services::fee_bump_tracker::FeeBumpTrackerService;
+
+    /// First ledger served by the mock RPC client
+    const OLDEST: u64 = 51_565_760;
+
+    async fn service(config: BackfillConfig) -> LedgerBackfillService {
+        let pool = crate::test_support::migrated_pool().await;
+
+        let rpc_client = Arc::new(StellarRpcClient::new_with_defaults(true));
+        let ingestion = Arc::new(LedgerIngestionService::new(
+            Arc::clone(&rpc_client),
+            Arc::new(FeeBumpTrackerService::new(pool.clone().into())),
+            Arc::new(AccountMergeDetector::new(pool.clone().into(), rpc_client)),
+            pool.clone().into(),
+        ));
+        LedgerBackfillService::new(ingestion, pool.into(), config)
+    }
+
+    async fn store_ledgers(service: &LedgerBackfillService, sequences: &[u64]) {
+        for sequence in sequences {
+            sqlx::query("INSERT INTO ledgers (sequence, hash, close_time) VALUES ($1, $2, '0')")
+                .bind(*sequence as i64)
+                .bind(format!("hash_{}", sequence))
+                .execute(service.pool.sqlite())
+                .await
+                .unwrap();
+        }
+    }
+
+    async fn chunks(service: &LedgerBackfillService, job_id: &str) -> Vec<BackfillChunk> {
+        service.get_job(job_id).await.unwrap().unwrap().chunks
+    }
+
+    #[tokio::test]
+    async fn test_find_gaps_between_stored_ledgers() {
+        let service = service(BackfillConfig::default()).await;
+        store_ledgers(&service, &[10, 11, 15, 16, 20]).await;
+
+        let gaps = service.find_gaps(None, None).await.unwrap();
+        assert_eq!(
+            gaps,
+            vec![
+                LedgerGap { start: 12, end: 14 },
+                LedgerGap { start: 17, end: 19 }
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_gaps_with_bounds() {
+        let service = service(BackfillConfig::default()).await;
+        store_ledgers(&service, &[10, 11, 15, 16, 20]).await;
+
+        let gaps = service.find_gaps(Some(5), Some(16)).await.unwrap();
+        assert_eq!(
+            gaps,
+            vec![
+                LedgerGap { start: 5, end: 9 },
+                LedgerGap { start: 12, end: 14 }
+            ]
+        );
+
+        let gaps = service.find_gaps(Some(16), Some(22)).await.unwrap();
+        assert_eq!(
+            gaps,
+            vec![
+                LedgerGap { start: 17, end: 19 },
+                LedgerGap { start: 21, end: 22 }
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_gaps_empty_storage() {
+        let service = service(BackfillConfig::default()).await;
+
+        assert!(service.find_gaps(None, None).await.unwrap().is_empty());
+        assert_eq!(
+            service.find_gaps(Some(1), Some(3)).await.unwrap(),
+            vec![LedgerGap { start: 1, end: 3 }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_requeue_gaps_creates_one_job() {
+        let service = service(BackfillConfig {
+            chunk_size: 2,
+            ..BackfillConfig::default()
+        })
+        .await;
+        store_ledgers(&service, &[10, 14, 15, 17]).await;
+
+        let job = service.requeue_gaps(None, None).await.unwrap().unwrap();
+        assert_eq!(job.source, SOURCE_GAP_SCAN);
+        assert_eq!(job.status, STATUS_PENDING);
+        assert_eq!((job.start_ledger, job.end_ledger), (11, 16));
+
+        let ranges: Vec<(i64, i64)> = chunks(&service, &job.id)
+            .await
+            .iter()
+            .map(|c| (c.chunk_start, c.chunk_end))
+            .collect();
+        assert_eq!(ranges, vec![(11, 12), (13, 13), (16, 16)]);
+
+        // The unfinished job already covers the gaps
+        assert!(service.requeue_gaps(None, None).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_requeue_gaps_without_gaps() {
+        let service = service(BackfillConfig::default()).await;
+        store_ledgers(&service, &[10, 11, 12]).await;
+
+        assert!(service.requeue_gaps(None, None).await.unwrap().is_none());
+        assert!(service.list_jobs(10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_requeued_gap_is_filled() {
+        let service = service(BackfillConfig::default()).await;
+        store_ledgers(&service, &[OLDEST, OLDEST + 4]).await;
+
+        let job = service.requeue_gaps(None, None).await.unwrap().unwrap();
+        let job = service.run_job(&job.id).await.unwrap();
+
+        assert_eq!(job.status, STATUS_COMPLETED);
+        assert!(service.find_gaps(None, None).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_chunk_stays_incomplete_when_a_ledger_cannot_be_persisted() {
+        let service = service(BackfillConfig {
+            max_chunk_attempts: 1,
+            ..BackfillConfig::default()
+        })
+        .await;
+        let job = service
+            .create_job(OLDEST, OLDEST + 4, None, Some(1))
+            .await
+            .unwrap();
+        sqlx::query("DROP TABLE transactions")
+            .execute(service.pool.sqlite())
+            .await
+            .unwrap();
+
+        let job = service.run_job(&job.id).await.unwrap();
+        assert_eq!(job.status, STATUS_FAILED);
+
+        let chunk = &chunks(&service, &job.id).await[0];
+        assert_eq!(chunk.status, STATUS_FAILED);
+        assert_eq!(chunk.next_ledger, OLDEST as i64);
+        assert_eq!(chunk.ledgers_ingested, 0);
+        assert!(chunk.error.is_some());
+
+        let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM ledgers")
+            .fetch_one(service.pool.sqlite())
+            .await
+            .unwrap();
+        assert_eq!(stored, 0);
+    }
+
+    #[test]
+    fn test_split_range_even() {
+        assert_eq!(split_range(1, 30, 10), vec![(1, 10), (11, 20), (21, 30)]);
+    }
+
+    #[test]
+    fn test_split_range_remainder() {
+        assert_eq!(
+            split_range(100, 124, 10),
+            vec![(100, 109), (110, 119), (120, 124)]
+        );
+    }
+
+    #[test]
+    fn test_split_range_single_ledger() {
+        assert_eq!(split_range(7, 7, 1_000), vec![(7, 7)]);
+    }
+
+    #[test]
+    fn test_split_range_zero_chunk_size() {
+        assert_eq!(split_range(1, 3, 0), vec![(1, 1), (2, 2), (3, 3)]);
+    }
+
+    #[test]
+    fn test_split_range_empty() {
+        assert!(split_range(10, 9, 5).is_empty());
+    }
+
+    #[test]
+    fn test_gap_len() {
+        let gap = LedgerGap { start: 5, end: 9 };
+        assert_eq!(gap.len(), 5);
+        assert!(!gap.is_empty());
+    }
+}
@@ -0,0 +1,600 @@
+//! Decoding of the Soroban contract events returned by RPC `getEvents`.
+//!
+//! Topics and values arrive as base64 `ScVal` XDR. Events of the governance,
+//! analytics and snapshot contracts in `contracts/` are recognised by their first
+//! topic symbol and decoded into typed structs; `#[contracttype]` structs are encoded
+//! as maps keyed by field name and tuples as vectors. Any other event is kept as a
+//! JSON rendering of its topics and value.
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use stellar_xdr::curr::{Limits, ReadXdr, ScMap, ScSymbol, ScVal, WriteXdr};
+
+/// Governance: a proposal was created
+pub const PROPOSAL_CREATED_TOPIC: &str = "PROP_CRT";
+/// Governance: a vote was cast
+pub const VOTE_CAST_TOPIC: &str = "VOTE_CST";
+/// Governance: voting on a proposal ended
+pub const PROPOSAL_FINALIZED_TOPIC: &str = "PROP_FIN";
+/// Analytics and snapshot contracts: a snapshot hash was submitted
+pub const SNAPSHOT_SUBMITTED_TOPIC: &str = "SNAP_SUB";
+/// Snapshot contract administration
+pub const CONTRACT_STOPPED_TOPIC: &str = "STOPPED";
+pub const CONTRACT_RESUMED_TOPIC: &str = "RESUMED";
+pub const ADMIN_TRANSFERRED_TOPIC: &str = "ADM_XFER";
+pub const CONTRACT_UPGRADED_TOPIC: &str = "UPGRADED";
+pub const CONTRACT_MIGRATED_TOPIC: &str = "MIGRATED";
+
+/// Choice of a governance vote, as the contract's `VoteChoice` discriminant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VoteChoice {
+    For,
+    Against,
+    Abstain,
+}
+
+impl VoteChoice {
+    pub fn from_discriminant(value: u32) -> Option<Self> {
+        match value {
+            0 => Some(Self::For),
+            1 => Some(Self::Against),
+            2 => Some(Self::Abstain),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::For => "for",
+            Self::Against => "against",
+            Self::Abstain => "abstain",
+        }
+    }
+}
+
+/// On-chain status of a governance proposal, as the contract's `ProposalStatus`
+/// discriminant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalStatus {
+    Active,
+    Passed,
+    Failed,
+    Executed,
+}
+
+impl ProposalStatus {
+    pub fn from_discriminant(value: u32) -> Option<Self> {
+        match value {
+            0 => Some(Self::Active),
+            1 => Some(Self::Passed),
+            2 => Some(Self::Failed),
+            3 => Some(Self::Executed),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Passed => "passed",
+            Self::Failed => "failed",
+            Self::Executed => "executed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProposalCreatedEvent {
+    pub proposal_id: u64,
+    pub proposer: String,
+    pub target_contract: String,
+    /// Ledger timestamp voting closes at, in seconds
+    pub voting_ends_at: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoteCastEvent {
+    pub proposal_id: u64,
+    pub voter: String,
+    pub choice: VoteChoice,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProposalFinalizedEvent {
+    pub proposal_id: u64,
+    pub status: ProposalStatus,
+    pub votes_for: u64,
+    pub votes_against: u64,
+    pub total_voters: u64,
+}
+
+/// A snapshot hash anchored on-chain. The analytics contract names the submitter; the
+/// snapshot contract and the analytics contract's legacy event do not.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotSubmittedEvent {
+    pub epoch: u64,
+    /// Hex-encoded snapshot hash
+    pub hash: String,
+    /// Ledger timestamp the snapshot was recorded at, in seconds
+    pub ledger_timestamp: u64,
+    pub submitter: Option<String>,
+}
+
+/// A decoded contract event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DecodedEvent {
+    ProposalCreated(ProposalCreatedEvent),
+    VoteCast(VoteCastEvent),
+    ProposalFinalized(ProposalFinalizedEvent),
+    SnapshotSubmitted(SnapshotSubmittedEvent),
+    ContractStopped {
+        admin: String,
+    },
+    ContractResumed {
+        admin: String,
+    },
+    AdminTransferred {
+        previous_admin: String,
+        new_admin: String,
+    },
+    ContractUpgraded {
+        /// Hex-encoded WASM hash
+        wasm_hash: String,
+        version: u32,
+    },
+    ContractMigrated {
+        from_version: u32,
+        to_version: u32,
+    },
+    /// An event without a decoder
+    Other {
+        topics: Vec<Value>,
+        value: Value,
+    },
+}
+
+impl DecodedEvent {
+    /// Event type stored alongside the event. Events without a decoder use their
+    /// first topic when it is a symbol.
+    pub fn event_type(&self) -> String {
+        match self {
+            Self::ProposalCreated(_) => "proposal_created".to_string(),
+            Self::VoteCast(_) => "vote_cast".to_string(),
+            Self::ProposalFinalized(_) => "proposal_finalized".to_string(),
+            Self::SnapshotSubmitted(_) => "snapshot_submitted".to_string(),
+            Self::ContractStopped { .. } => "contract_stopped".to_string(),
+            Self::ContractResumed { .. } => "contract_resumed".to_string(),
+            Self::AdminTransferred { .. } => "admin_transferred".to_string(),
+            Self::ContractUpgraded { .. } => "contract_upgraded".to_string(),
+            Self::ContractMigrated { .. } => "contract_migrated".to_string(),
+            Self::Other { topics, .. } => topics
+                .first()
+                .and_then(Value::as_str)
+                .map_or_else(|| "unknown".to_string(), str::to_lowercase),
+        }
+    }
+}
+
+/// A decoded event with its position on the ledger
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexedEvent {
+    /// RPC event id, unique per event
+    pub id: String,
+    pub contract_id: String,
+    pub ledger_sequence: u64,
+    pub transaction_hash: String,
+    pub closed_at: DateTime<Utc>,
+    pub event: DecodedEvent,
+}
+
+/// Decode one base64 `ScVal`
+pub fn decode_sc_val(encoded: &str) -> Result<ScVal> {
+    let bytes = BASE64.decode(encoded).context("ScVal is not base64")?;
+    ScVal::from_xdr(bytes, Limits::none()).context("Invalid ScVal XDR")
+}
+
+/// Base64 XDR of a symbol, the form `getEvents` topic filters take
+pub fn symbol_topic(symbol: &str) -> Result<String> {
+    // Soroban symbols are up to 32 characters of `[a-zA-Z0-9_]`
+    if symbol.is_empty()
+        || symbol.len() > 32
+        || !symbol
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        bail!("Invalid topic symbol: {}", symbol);
+    }
+    let symbol = ScSymbol(symbol.try_into()?);
+    Ok(BASE64.encode(ScVal::Symbol(symbol).to_xdr(Limits::none())?))
+}
+
+/// Decode an event from its base64 topics and value. Events whose first topic is
+/// unknown decode to [`DecodedEvent::Other`]; a known topic with an unexpected
+/// payload is an error.
+pub fn decode_event(topics: &[String], value: &str) -> Result<DecodedEvent> {
+    let topics = topics
+        .iter()
+        .map(|t| decode_sc_val(t))
+        .collect::<Result<Vec<_>>>()?;
+    let value = decode_sc_val(value)?;
+
+    let symbol = match topics.first() {
+        Some(ScVal::Symbol(symbol)) => symbol.to_utf8_string_lossy(),
+        _ => String::new(),
+    };
+    let decoded = match symbol.as_str() {
+        PROPOSAL_CREATED_TOPIC => {
+            let fields = struct_fields(&value)?;
+            DecodedEvent::ProposalCreated(ProposalCreatedEvent {
+                proposal_id: as_u64(field(fields, "proposal_id")?)?,
+                proposer: as_address(field(fields, "proposer")?)?,
+                target_contract: as_address(field(fields, "target_contract")?)?,
+                voting_ends_at: as_u64(field(fields, "voting_ends_at")?)?,
+            })
+        }
+        VOTE_CAST_TOPIC => {
+            let fields = struct_fields(&value)?;
+            let choice = as_u32(field(fields, "choice")?)?;
+            DecodedEvent::VoteCast(VoteCastEvent {
+                proposal_id: as_u64(field(fields, "proposal_id")?)?,
+                voter: as_address(field(fields, "voter")?)?,
+                choice: VoteChoice::from_discriminant(choice)
+                    .ok_or_else(|| anyhow!("Unknown vote choice {}", choice))?,
+            })
+        }
+        PROPOSAL_FINALIZED_TOPIC => {
+            let fields = struct_fields(&value)?;
+            let status = as_u32(field(fields, "status")?)?;
+            DecodedEvent::ProposalFinalized(ProposalFinalizedEvent {
+                proposal_id: as_u64(field(fields, "proposal_id")?)?,
+                status: ProposalStatus::from_discriminant(status)
+                    .ok_or_else(|| anyhow!("Unknown proposal status {}", status))?,
+                votes_for: as_u64(field(fields, "votes_for")?)?,
+                votes_against: as_u64(field(fields, "votes_against")?)?,
+                total_voters: as_u64(field(fields, "total_voters")?)?,
+            })
+        }
+        SNAPSHOT_SUBMITTED_TOPIC => DecodedEvent::SnapshotSubmitted(decode_snapshot(&value)?),
+        CONTRACT_STOPPED_TOPIC => DecodedEvent::ContractStopped {
+            admin: as_address(tuple_item(&value, 0)?)?,
+        },
+        CONTRACT_RESUMED_TOPIC => DecodedEvent::ContractResumed {
+            admin: as_address(tuple_item(&value, 0)?)?,
+        },
+        ADMIN_TRANSFERRED_TOPIC => DecodedEvent::AdminTransferred {
+            previous_admin: as_address(tuple_item(&value, 0)?)?,
+            new_admin: as_address(tuple_item(&value, 1)?)?,
+        },
+        CONTRACT_UPGRADED_TOPIC => DecodedEvent::ContractUpgraded {
+            wasm_hash: as_bytes_hex(tuple_item(&value, 0)?)?,
+            version: as_u32(tuple_item(&value, 1)?)?,
+        },
+        CONTRACT_MIGRATED_TOPIC => DecodedEvent::ContractMigrated {
+            from_version: as_u32(tuple_item(&value, 0)?)?,
+            to_version: as_u32(tuple_item(&value, 1)?)?,
+        },
+        _ => DecodedEvent::Other {
+            topics: topics.iter().map(sc_val_to_json).collect(),
+            value: sc_val_to_json(&value),
+        },
+    };
+    Ok(decoded)
+}
+
+/// The analytics contract publishes a struct, the snapshot contract a
+/// `(hash, epoch, timestamp)` tuple
+fn decode_snapshot(value: &ScVal) -> Result<SnapshotSubmittedEvent> {
+    if let ScVal::Map(_) = value {
+        let fields = struct_fields(value)?;
+        let submitter = match fields.0.iter().find(|e| is_symbol(&e.key, "submitter")) {
+            Some(entry) => Some(as_address(&entry.val)?),
+            None => None,
+        };
+        return Ok(SnapshotSubmittedEvent {
+            epoch: as_u64(field(fields, "epoch")?)?,
+            hash: as_bytes_hex(field(fields, "hash")?)?,
+            ledger_timestamp: as_u64(field(fields, "timestamp")?)?,
+            submitter,
+        });
+    }
+    Ok(SnapshotSubmittedEvent {
+        hash: as_bytes_hex(tuple_item(value, 0)?)?,
+        epoch: as_u64(tuple_item(value, 1)?)?,
+        ledger_timestamp: as_u64(tuple_item(value, 2)?)?,
+        submitter: None,
+    })
+}
+
+fn is_symbol(val: &ScVal, name: &str) -> bool {
+    matches!(val, ScVal::Symbol(symbol) if symbol.as_slice() == name.as_bytes())
+}
+
+fn struct_fields(value: &ScVal) -> Result<&ScMap> {
+    match value {
+        ScVal::Map(Some(map)) => Ok(map),
+        other => bail!("Expected a struct, got {:?}", other.discriminant()),
+    }
+}
+
+fn field<'a>(fields: &'a ScMap, name: &str) -> Result<&'a ScVal> {
+    fields
+        .0
+        .iter()
+        .find(|entry| is_symbol(&entry.key, name))
+        .map(|entry| &entry.val)
+        .ok_or_else(|| anyhow!("Missing field {}", name))
+}
+
+fn tuple_item(value: &ScVal, index: usize) -> Result<&ScVal> {
+    match value {
+        ScVal::Vec(Some(items)) => items
+            .0
+            .get(index)
+            .ok_or_else(|| anyhow!("Missing tuple item {}", index)),
+        other => bail!("Expected a tuple, got {:?}", other.discriminant()),
+    }
+}
+
+fn as_u64(value: &ScVal) -> Result<u64> {
+    match value {
+        ScVal::U64(v) => Ok(*v),
+        ScVal::U32(v) => Ok(u64::from(*v)),
+        other => bail!("Expected u64, got {:?}", other.discriminant()),
+    }
+}
+
+fn as_u32(value: &ScVal) -> Result<u32> {
+    match value {
+        ScVal::U32(v) => Ok(*v),
+        other => bail!("Expected u32, got {:?}", other.discriminant()),
+    }
+}
+
+fn as_address(value: &ScVal) -> Result<String> {
+    match value {
+        ScVal::Address(address) => Ok(address.to_string()),
+        other => bail!("Expected an address, got {:?}", other.discriminant()),
+    }
+}
+
+fn as_bytes_hex(value: &ScVal) -> Result<String> {
+    match value {
+        ScVal::Bytes(bytes) => Ok(hex::encode(bytes.as_slice())),
+        other => bail!("Expected bytes, got {:?}", other.discriminant()),
+    }
+}
+
+/// JSON rendering of an `ScVal`. 128-bit integers become decimal strings and 256-bit
+/// integers and bytes hex strings; maps keyed by symbols or strings become objects,
+/// other maps arrays of `[key, value]` pairs.
+pub fn sc_val_to_json(value: &ScVal) -> Value {
+    match value {
+        ScVal::Bool(v) => Value::Bool(*v),
+        ScVal::Void => Value::Null,
+        ScVal::U32(v) => Value::from(*v),
+        ScVal::I32(v) => Value::from(*v),
+        ScVal::U64(v) => Value::from(*v),
+        ScVal::I64(v) => Value::from(*v),
+        ScVal::Timepoint(v) => Value::from(v.0),
+        ScVal::Duration(v) => Value::from(v.0),
+        ScVal::U128(parts) => {
+            Value::String((u128::from(parts.hi) << 64 | u128::from(parts.lo)).to_string())
+        }
+        ScVal::I128(parts) => {
+            Value::String((i128::from(parts.hi) << 64 | i128::from(parts.lo)).to_string())
+        }
+        ScVal::U256(parts) => Value::String(format!(
+            "0x{:016x}{:016x}{:016x}{:016x}",
+            parts.hi_hi, parts.hi_lo, parts.lo_hi, parts.lo_lo
+        )),
+        ScVal::I256(parts) => Value::String(format!(
+            "0x{:016x}{:016x}{:016x}{:016x}",
+            parts.hi_hi, parts.hi_lo, parts.lo_hi, parts.lo_lo
+        )),
+        ScVal::Bytes(bytes) => Value::String(hex::encode(bytes.as_slice())),
+        ScVal::String(s) => Value::String(s.to_utf8_string_lossy()),
+        ScVal::Symbol(s) => Value::String(s.to_utf8_string_lossy()),
+        ScVal::Address(address) => Value::String(address.to_string()),
+        ScVal::Vec(items) => Value::Array(
+            items
+                .iter()
+                .flat_map(|items| items.0.iter())
+                .map(sc_val_to_json)
+                .collect(),
+        ),
+        ScVal::Map(None) => Value::Object(Map::new()),
+        ScVal::Map(Some(map)) => {
+            let keys: Option<Vec<String>> = map
+                .0
+                .iter()
+                .map(|entry| match &entry.key {
+                    ScVal::Symbol(s) => Some(s.to_utf8_string_lossy()),
+                    ScVal::String(s) => Some(s.to_utf8_string_lossy()),
+                    _ => None,
+                })
+                .collect();
+            match keys {
+                Some(keys) => Value::Object(
+                    keys.into_iter()
+                        .zip(map.0.iter())
+                        .map(|(key, entry)| (key, sc_val_to_json(&entry.val)))
+                        .collect(),
+                ),
+                None => Value::Array(
+                    map.0
+                        .iter()
+                        .map(|entry| {
+                            Value::Array(vec![
+                                sc_val_to_json(&entry.key),
+                                sc_val_to_json(&entry.val),
+                            ])
+                        })
+                        .collect(),
+                ),
+            }
+        }
+        ScVal::Error(error) => Value::String(format!("{:?}", error)),
+        other => Value::String(other.name().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stellar_xdr::curr::{Hash, ScAddress, ScBytes, ScMapEntry, UInt128Parts};
+
+    fn encode(value: &ScVal) -> String {
+        BASE64.encode(value.to_xdr(Limits::none()).unwrap())
+    }
+
+    fn symbol(name: &str) -> ScVal {
+        ScVal::Symbol(ScSymbol(name.try_into().unwrap()))
+    }
+
+    fn contract(byte: u8) -> ScVal {
+        ScVal::Address(ScAddress::Contract(Hash([byte; 32])))
+    }
+
+    fn strukt(fields: Vec<(&str, ScVal)>) -> ScVal {
+        let entries: Vec<ScMapEntry> = fields
+            .into_iter()
+            .map(|(key, val)| ScMapEntry {
+                key: symbol(key),
+                val,
+            })
+            .collect();
+        ScVal::Map(Some(ScMap(entries.try_into().unwrap())))
+    }
+
+    fn tuple(items: Vec<ScVal>) -> ScVal {
+        ScVal::Vec(Some(items.try_into().unwrap()))
+    }
+
+    fn decode(topics: &[&str], value: ScVal) -> Result<DecodedEvent> {
+        let topics: Vec<String> = topics.iter().map(|t| encode(&symbol(t))).collect();
+        decode_event(&topics, &encode(&value))
+    }
+
+    #[test]
+    fn test_decode_governance_events() {
+        let vote = decode(
+            &[VOTE_CAST_TOPIC, "GOV_LFE"],
+            strukt(vec![
+                ("choice", ScVal::U32(1)),
+                ("proposal_id", ScVal::U64(4)),
+                ("voter", contract(3)),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(
+            vote,
+            DecodedEvent::VoteCast(VoteCastEvent {
+                proposal_id: 4,
+                voter: stellar_strkey::Contract([3; 32]).to_string(),
+                choice: VoteChoice::Against,
+            })
+        );
+        assert_eq!(vote.event_type(), "vote_cast");
+
+        let finalized = decode(
+            &[PROPOSAL_FINALIZED_TOPIC, "GOV_LFE"],
+            strukt(vec![
+                ("proposal_id", ScVal::U64(4)),
+                ("status", ScVal::U32(1)),
+                ("total_voters", ScVal::U64(5)),
+                ("votes_against", ScVal::U64(1)),
+                ("votes_for", ScVal::U64(3)),
+            ]),
+        )
+        .unwrap();
+        let DecodedEvent::ProposalFinalized(finalized) = finalized else {
+            panic!("expected a finalized proposal");
+        };
+        assert_eq!(finalized.status, ProposalStatus::Passed);
+        assert_eq!((finalized.votes_for, finalized.total_voters), (3, 5));
+
+        // A vote choice the contract does not define
+        assert!(decode(
+            &[VOTE_CAST_TOPIC, "GOV_LFE"],
+            strukt(vec![
+                ("choice", ScVal::U32(9)),
+                ("proposal_id", ScVal::U64(4)),
+                ("voter", contract(3)),
+            ]),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_decode_snapshot_events_of_both_contracts() {
+        let hash = ScVal::Bytes(ScBytes(vec![0xab; 32].try_into().unwrap()));
+
+        let analytics = decode(
+            &[SNAPSHOT_SUBMITTED_TOPIC, "SNAP_LFE"],
+            strukt(vec![
+                ("epoch", ScVal::U64(7)),
+                ("hash", hash.clone()),
+                ("submitter", contract(5)),
+                ("timestamp", ScVal::U64(1_700_000_000)),
+            ]),
+        )
+        .unwrap();
+        let snapshot_contract = decode(
+            &[SNAPSHOT_SUBMITTED_TOPIC],
+            tuple(vec![hash, ScVal::U64(7), ScVal::U64(1_700_000_000)]),
+        )
+        .unwrap();
+
+        let expected = SnapshotSubmittedEvent {
+            epoch: 7,
+            hash: "ab".repeat(32),
+            ledger_timestamp: 1_700_000_000,
+            submitter: None,
+        };
+        assert_eq!(
+            snapshot_contract,
+            DecodedEvent::SnapshotSubmitted(expected.clone())
+        );
+        assert_eq!(
+            analytics,
+            DecodedEvent::SnapshotSubmitted(SnapshotSubmittedEvent {
+                submitter: Some(stellar_strkey::Contract([5; 32]).to_string()),
+                ..expected
+            })
+        );
+
+        // The replay snapshot processor reads `epoch` and `hash` from the payload
+        let data = serde_json::to_value(&snapshot_contract).unwrap();
+        assert_eq!(data["epoch"], 7);
+        assert_eq!(data["hash"], "ab".repeat(32));
+    }
+
+    #[test]
+    fn test_decode_unknown_event_as_json() {
+        let value = strukt(vec![
+            ("amount", ScVal::U128(UInt128Parts { hi: 1, lo: 0 })),
+            ("to", contract(1)),
+        ]);
+        let event = decode(&["transfer"], value).unwrap();
+        assert_eq!(event.event_type(), "transfer");
+        let DecodedEvent::Other { topics, value } = event else {
+            panic!("expected an undecoded event");
+        };
+        assert_eq!(topics, vec![Value::from("transfer")]);
+        assert_eq!(value["amount"], "18446744073709551616");
+        assert_eq!(value["to"], stellar_strkey::Contract([1; 32]).to_string());
+    }
+
+    #[test]
+    fn test_symbol_topic_round_trips() {
+        let topic = symbol_topic(VOTE_CAST_TOPIC).unwrap();
+        assert_eq!(decode_sc_val(&topic).unwrap(), symbol(VOTE_CAST_TOPIC));
+        assert!(symbol_topic("not a symbol").is_err());
+    }
+}
@@ -1,19 +1,65 @@
-use anyhow::{Context, Result};
+use crate::database::DbPool;
+use crate::with_pool;
+use anyhow::{bail, Context, Result};
 use chrono::{DateTime, TimeZone, Utc};
-use sqlx::SqlitePool;
+use std::collections::HashMap;
 use std::sync::Arc;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 
+use crate::ingestion::xdr::{self, DecodedLedger, DecodedOperationKind};
 use crate::rpc::{GetLedgersResult, RpcLedger, StellarRpcClient};
 use crate::services::account_merge_detector::AccountMergeDetector;
 use crate::services::fee_bump_tracker::FeeBumpTrackerService;
 
-/// Ledger ingestion service that fetches and persists ledgers sequentially
+/// Ledgers requested per `getLedgers` call while searching for a fork point
+const FORK_SEARCH_WINDOW: u64 = 100;
+/// Deepest fork searched for before giving up instead of rolling back
+const MAX_REORG_DEPTH: u64 = 10_000;
+
+/// Ledger ingestion service that fetches and persists ledgers sequentially.
+///
+/// Transactions, operations and results are decoded from the `LedgerCloseMeta`
+/// returned by `getLedgers`; Horizon is only used for ledgers whose metadata
+/// cannot be decoded.
+///
+/// Each ledger's hash and previous-ledger hash are verified against the stored
+/// chain before it is persisted. On a mismatch everything derived from ledgers
+/// past the fork point is rolled back and the cursor rewound so they are
+/// ingested again.
 pub struct LedgerIngestionService {
     rpc_client: Arc<StellarRpcClient>,
     fee_bump_tracker: Arc<FeeBumpTrackerService>,
     account_merge_detector: Arc<AccountMergeDetector>,
-    pool: SqlitePool,
+    pool: DbPool,
+}
+
+/// A stored ledger whose hash disagrees with the chain reported by RPC
+#[derive(Debug, Clone)]
+struct ChainMismatch {
+    /// Highest stored ledger known to be off the canonical chain
+    sequence: u64,
+    /// Hash stored locally
+    expected_hash: String,
+    /// Hash reported by RPC
+    actual_hash: String,
+}
+
+/// Rows removed when rolling back to a fork point
+#[derive(Debug, Clone, Default)]
+pub struct RollbackSummary {
+    pub ledgers: u64,
+    /// Rows removed from both `ledger_payments` and `payments`
+    pub payments: u64,
+    pub fee_bumps: u64,
+    pub account_merges: u64,
+}
+
+/// Result of processing one batch of ledgers
+#[derive(Debug, Clone, Default)]
+struct BatchOutcome {
+    persisted: u64,
+    /// Set when a fork was detected and stored data was rolled back to this ledger
+    fork_point: Option<u64>,
 }
 
 /// Represents a payment operation extracted from a ledger
@@ -34,7 +80,7 @@ impl LedgerIngestionService {
         rpc_client: Arc<StellarRpcClient>,
         fee_bump_tracker: Arc<FeeBumpTrackerService>,
         account_merge_detector: Arc<AccountMergeDetector>,
-        pool: SqlitePool,
+        pool: DbPool,
     ) -> Self {
         Self {
             rpc_client,
@@ -70,145 +116,610 @@ impl LedgerIngestionService {
             .await
             .context("Failed to fetch ledgers")?;
 
-        let count = self.process_ledgers(&result).await?;
+        let outcome = self.process_ledgers(&result).await?;
+
+        // The cursor was rewound to the fork point, don't move it past the rolled back ledgers
+        if outcome.fork_point.is_some() {
+            return Ok(outcome.persisted);
+        }
 
         // I'm saving cursor for restart safety
         if let Some(new_cursor) = &result.cursor {
-            self.save_cursor(new_cursor, result.ledgers.last().map(|l| l.sequence))
-                .await?;
+            self.save_cursor(new_cursor, result.ledgers.last()).await?;
+        }
+
+        Ok(outcome.persisted)
+    }
+
+    /// Ingest up to `limit` ledgers starting at `start_ledger` without touching the
+    /// live cursor. Used by backfills, which track their own progress.
+    ///
+    /// Returns the number of ledgers persisted and the last sequence returned by RPC.
+    pub async fn ingest_batch_from(
+        &self,
+        start_ledger: u64,
+        limit: u32,
+    ) -> Result<(u64, Option<u64>)> {
+        let result = self
+            .rpc_client
+            .fetch_ledgers(Some(start_ledger), limit, None)
+            .await
+            .with_context(|| format!("Failed to fetch ledgers from {}", start_ledger))?;
+
+        let outcome = self.process_ledgers(&result).await?;
+        if let Some(fork_point) = outcome.fork_point {
+            bail!(
+                "chain mismatch while ingesting from {}, rolled back to ledger {}",
+                start_ledger,
+                fork_point
+            );
         }
 
-        Ok(count)
+        Ok((outcome.persisted, result.ledgers.last().map(|l| l.sequence)))
     }
 
-    /// I'm processing and persisting fetched ledgers
-    async fn process_ledgers(&self, result: &GetLedgersResult) -> Result<u64> {
+    /// I'm processing and persisting fetched ledgers, verifying chain links first
+    async fn process_ledgers(&self, result: &GetLedgersResult) -> Result<BatchOutcome> {
+        let network_passphrase = self.rpc_client.network_config().network_passphrase();
         let mut count = 0u64;
+        let mut parent: Option<(u64, &str)> = None;
 
         for ledger in &result.ledgers {
-            if let Err(e) = self.persist_ledger(ledger).await {
-                warn!("Failed to persist ledger {}: {}", ledger.sequence, e);
-                continue;
-            }
+            let decoded = xdr::decode_rpc_ledger(ledger, network_passphrase);
+            let previous_hash = match &decoded {
+                Ok(decoded) => Some(decoded.previous_hash.clone()),
+                Err(_) => xdr::decode_previous_hash(ledger).ok(),
+            };
 
-            // Fetch real payments from Horizon
-            match self
-                .rpc_client
-                .fetch_payments_for_ledger(ledger.sequence)
-                .await
+            if let Some(mismatch) = self
+                .verify_chain_link(ledger, previous_hash.as_deref(), parent)
+                .await?
             {
-                Ok(payments) => {
-                    for payment in payments {
-                        // Convert RPC Payment to ExtractedPayment
-                        // Uses helper methods to support both old and new Horizon formats
-                        let extracted = ExtractedPayment {
-                            ledger_sequence: ledger.sequence,
-                            transaction_hash: payment.transaction_hash.clone(),
-                            operation_type: "payment".to_string(), // Horizon 'payments' endpoint returns payments
-                            source_account: payment.source_account.clone(),
-                            destination: payment.get_destination().unwrap_or_default(),
-                            asset_code: payment.get_asset_code(),
-                            asset_issuer: payment.get_asset_issuer(),
-                            amount: payment.get_amount(),
-                        };
+                let fork_point = self
+                    .handle_chain_mismatch(ledger.sequence, mismatch)
+                    .await?;
+                info!("Processed {} ledgers before rolling back", count);
+                return Ok(BatchOutcome {
+                    persisted: count,
+                    fork_point: Some(fork_point),
+                });
+            }
 
-                        if let Err(e) = self.persist_payment(&extracted).await {
-                            warn!("Failed to persist payment: {}", e);
-                        }
-                    }
-                }
+            // A ledger that cannot be stored fails the whole batch: skipping it would
+            // leave a hole behind a cursor (or backfill chunk) that has moved past it
+            let persisted = match decoded {
+                Ok(decoded) => self.persist_decoded_ledger(&decoded).await,
                 Err(e) => {
-                    warn!(
-                        "Failed to fetch payments for ledger {}: {}",
+                    debug!(
+                        "Ledger {} metadata could not be decoded ({:#}), falling back to Horizon",
                         ledger.sequence, e
                     );
-                    // Non-fatal, continue ingesting ledgers
+                    match self.persist_ledger(ledger, previous_hash.as_deref()).await {
+                        Ok(true) => {
+                            self.ingest_from_horizon(ledger).await;
+                            Ok(())
+                        }
+                        Ok(false) => Ok(()),
+                        Err(e) => Err(e),
+                    }
                 }
+            };
+            persisted.with_context(|| {
+                format!(
+                    "Failed to persist ledger {} after {} ledger(s) of the batch",
+                    ledger.sequence, count
+                )
+            })?;
+
+            parent = Some((ledger.sequence, ledger.hash.as_str()));
+            count += 1;
+        }
+
+        info!("Processed {} ledgers", count);
+        Ok(BatchOutcome {
+            persisted: count,
+            fork_point: None,
+        })
+    }
+
+    /// Check a fetched ledger against the stored chain.
+    ///
+    /// The ledger's own hash must match any copy already stored, and its
+    /// previous-ledger hash must match the stored parent. Links that cannot be
+    /// checked (parent not stored, header not decodable) are accepted.
+    async fn verify_chain_link(
+        &self,
+        ledger: &RpcLedger,
+        previous_hash: Option<&str>,
+        parent: Option<(u64, &str)>,
+    ) -> Result<Option<ChainMismatch>> {
+        if let Some(stored) = self.stored_hash(ledger.sequence).await? {
+            if !stored.eq_ignore_ascii_case(&ledger.hash) {
+                return Ok(Some(ChainMismatch {
+                    sequence: ledger.sequence,
+                    expected_hash: stored,
+                    actual_hash: ledger.hash.clone(),
+                }));
+            }
+        }
+
+        let (Some(previous_hash), Some(parent_sequence)) =
+            (previous_hash, ledger.sequence.checked_sub(1))
+        else {
+            return Ok(None);
+        };
+
+        let parent_hash = match parent {
+            Some((sequence, hash)) if sequence == parent_sequence => Some(hash.to_string()),
+            _ => self.stored_hash(parent_sequence).await?,
+        };
+
+        match parent_hash {
+            Some(parent_hash) if !parent_hash.eq_ignore_ascii_case(previous_hash) => {
+                Ok(Some(ChainMismatch {
+                    sequence: parent_sequence,
+                    expected_hash: parent_hash,
+                    actual_hash: previous_hash.to_string(),
+                }))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Find the fork point for a mismatch, roll back past it and record the reorg
+    async fn handle_chain_mismatch(
+        &self,
+        detected_at: u64,
+        mismatch: ChainMismatch,
+    ) -> Result<u64> {
+        warn!(
+            "Chain mismatch at ledger {}: stored hash {} but RPC reports {}",
+            mismatch.sequence, mismatch.expected_hash, mismatch.actual_hash
+        );
+
+        let fork_point = self
+            .find_fork_point(mismatch.sequence)
+            .await
+            .context("Failed to locate fork point")?;
+        // The rollback and its reorg record are committed together
+        let summary = self
+            .rollback(fork_point, Some((detected_at, &mismatch)))
+            .await?;
+
+        warn!(
+            "Rolled back to ledger {}: {} ledgers, {} payments, {} fee bumps, {} account merges removed",
+            fork_point, summary.ledgers, summary.payments, summary.fee_bumps, summary.account_merges
+        );
+
+        Ok(fork_point)
+    }
+
+    /// Walk back from a ledger known to be off the canonical chain until a stored
+    /// ledger matches the hash reported by RPC. Returns that ledger's sequence, or
+    /// the sequence before the lowest stored ledger if none match.
+    async fn find_fork_point(&self, off_chain: u64) -> Result<u64> {
+        let lowest: Option<i64> = with_pool!(&self.pool, |pool| {
+            sqlx::query_scalar("SELECT MIN(sequence) FROM ledgers")
+                .fetch_one(pool)
+                .await
+        })?;
+        let Some(lowest) = lowest.map(|l| l as u64) else {
+            return Ok(off_chain.saturating_sub(1));
+        };
+
+        let mut top = off_chain.saturating_sub(1);
+        while top >= lowest && top > 0 {
+            if off_chain - top > MAX_REORG_DEPTH {
+                bail!(
+                    "no common ledger within {} ledgers of {}, refusing to roll back",
+                    MAX_REORG_DEPTH,
+                    off_chain
+                );
             }
 
-            // Fetch and process transactions for fee bumps
-            match self
+            let start = top
+                .saturating_sub(FORK_SEARCH_WINDOW - 1)
+                .max(lowest)
+                .max(1);
+            let canonical: HashMap<u64, String> = self
                 .rpc_client
-                .fetch_transactions_for_ledger(ledger.sequence)
+                .fetch_ledgers(Some(start), (top - start + 1) as u32, None)
                 .await
-            {
-                Ok(transactions) => {
-                    if let Err(e) = self
-                        .fee_bump_tracker
-                        .process_transactions(&transactions)
-                        .await
-                    {
-                        warn!("Failed to process transactions for fee bumps: {}", e);
-                    }
-                }
-                Err(e) => {
-                    warn!(
-                        "Failed to fetch transactions for ledger {}: {}",
-                        ledger.sequence, e
+                .with_context(|| format!("Failed to fetch ledgers {}..={}", start, top))?
+                .ledgers
+                .into_iter()
+                .map(|l| (l.sequence, l.hash))
+                .collect();
+
+            let stored: Vec<(i64, String)> = with_pool!(&self.pool, |pool| {
+                sqlx::query_as(
+                    "SELECT sequence, hash FROM ledgers WHERE sequence BETWEEN $1 AND $2 ORDER BY sequence DESC",
+                )
+                .bind(start as i64)
+                .bind(top as i64)
+                .fetch_all(pool)
+                .await
+            })?;
+
+            for (sequence, hash) in stored {
+                let sequence = sequence as u64;
+                let Some(canonical_hash) = canonical.get(&sequence) else {
+                    bail!(
+                        "RPC did not return ledger {} while searching for fork point",
+                        sequence
                     );
+                };
+                if canonical_hash.eq_ignore_ascii_case(&hash) {
+                    return Ok(sequence);
                 }
             }
 
-            if let Err(e) = self
-                .account_merge_detector
-                .process_ledger_operations(ledger.sequence)
+            top = start - 1;
+        }
+
+        Ok(lowest.saturating_sub(1))
+    }
+
+    /// Delete every ledger after `fork_point` with the data derived from it and
+    /// rewind the cursor so those ledgers are ingested again
+    pub async fn rollback_to(&self, fork_point: u64) -> Result<RollbackSummary> {
+        self.rollback(fork_point, None).await
+    }
+
+    /// [`Self::rollback_to`], recording the chain mismatch that caused it as a
+    /// reorg in the same database transaction
+    async fn rollback(
+        &self,
+        fork_point: u64,
+        reorg: Option<(u64, &ChainMismatch)>,
+    ) -> Result<RollbackSummary> {
+        let fork_point = fork_point as i64;
+
+        with_pool!(&self.pool, |pool| {
+            let mut tx = pool.begin().await?;
+
+            // `payments` has no ledger column, so match it on the transactions being
+            // removed before they are deleted
+            let horizon_payments = sqlx::query(
+                r#"
+                DELETE FROM payments
+                WHERE transaction_hash IN (
+                    SELECT hash FROM transactions WHERE ledger_sequence > $1
+                    UNION
+                    SELECT transaction_hash FROM ledger_payments WHERE ledger_sequence > $1
+                )
+                "#,
+            )
+            .bind(fork_point)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            let ledger_payments =
+                sqlx::query("DELETE FROM ledger_payments WHERE ledger_sequence > $1")
+                    .bind(fork_point)
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected();
+            let fee_bumps =
+                sqlx::query("DELETE FROM fee_bump_transactions WHERE ledger_sequence > $1")
+                    .bind(fork_point)
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected();
+            let account_merges =
+                sqlx::query("DELETE FROM account_merges WHERE ledger_sequence > $1")
+                    .bind(fork_point)
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected();
+            sqlx::query("DELETE FROM transactions WHERE ledger_sequence > $1")
+                .bind(fork_point)
+                .execute(&mut *tx)
+                .await?;
+            let ledgers = sqlx::query("DELETE FROM ledgers WHERE sequence > $1")
+                .bind(fork_point)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+
+            let fork_hash: Option<String> =
+                sqlx::query_scalar("SELECT hash FROM ledgers WHERE sequence = $1")
+                    .bind(fork_point)
+                    .fetch_optional(&mut *tx)
+                    .await?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO ingestion_cursor (id, last_ledger_sequence, cursor, last_ledger_hash, updated_at)
+                VALUES (1, $1, NULL, $2, CURRENT_TIMESTAMP)
+                ON CONFLICT (id) DO UPDATE SET
+                    last_ledger_sequence = CASE
+                        WHEN ingestion_cursor.last_ledger_sequence < EXCLUDED.last_ledger_sequence
+                        THEN ingestion_cursor.last_ledger_sequence
+                        ELSE EXCLUDED.last_ledger_sequence
+                    END,
+                    cursor = NULL,
+                    last_ledger_hash = EXCLUDED.last_ledger_hash,
+                    updated_at = CURRENT_TIMESTAMP
+                "#,
+            )
+            .bind(fork_point)
+            .bind(&fork_hash)
+            .execute(&mut *tx)
+            .await?;
+
+            let summary = RollbackSummary {
+                ledgers,
+                payments: horizon_payments + ledger_payments,
+                fee_bumps,
+                account_merges,
+            };
+
+            if let Some((detected_at, mismatch)) = reorg {
+                sqlx::query(
+                    r#"
+                    INSERT INTO ledger_reorgs (
+                        detected_at_ledger, fork_point, expected_hash, actual_hash,
+                        ledgers_rolled_back, payments_rolled_back, fee_bumps_rolled_back, account_merges_rolled_back
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                    "#,
+                )
+                .bind(detected_at as i64)
+                .bind(fork_point)
+                .bind(&mismatch.expected_hash)
+                .bind(&mismatch.actual_hash)
+                .bind(summary.ledgers as i64)
+                .bind(summary.payments as i64)
+                .bind(summary.fee_bumps as i64)
+                .bind(summary.account_merges as i64)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            tx.commit().await?;
+            Ok(summary)
+        })
+    }
+
+    async fn stored_hash(&self, sequence: u64) -> Result<Option<String>> {
+        let hash = with_pool!(&self.pool, |pool| {
+            sqlx::query_scalar("SELECT hash FROM ledgers WHERE sequence = $1")
+                .bind(sequence as i64)
+                .fetch_optional(pool)
                 .await
-            {
+        })?;
+        Ok(hash)
+    }
+
+    /// Persist a ledger decoded from its close meta along with its transactions,
+    /// payments, fee bumps and account merges
+    ///
+    /// The ledger, its transactions and payments are written in one database
+    /// transaction. A ledger that is already stored is left as is, so a batch that
+    /// is retried after a failure does not duplicate its payments.
+    ///
+    /// Payment operations of failed transactions are stored as well; the
+    /// transaction's `successful` flag tells them apart.
+    async fn persist_decoded_ledger(&self, ledger: &DecodedLedger) -> Result<()> {
+        with_pool!(&self.pool, |pool| {
+            let mut db_tx = pool.begin().await?;
+
+            let inserted = sqlx::query(
+                r#"
+                INSERT INTO ledgers (sequence, hash, previous_hash, close_time, transaction_count, operation_count)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (sequence) DO NOTHING
+                "#,
+            )
+            .bind(ledger.sequence as i64)
+            .bind(&ledger.hash)
+            .bind(&ledger.previous_hash)
+            .bind(ledger.close_time)
+            .bind(ledger.transactions.len() as i32)
+            .bind(ledger.operation_count() as i32)
+            .execute(&mut *db_tx)
+            .await?;
+            if inserted.rows_affected() == 0 {
+                debug!("Ledger {} is already stored, skipping", ledger.sequence);
+                return Ok(());
+            }
+
+            for tx in &ledger.transactions {
+                sqlx::query(
+                    r#"
+                    INSERT INTO transactions (hash, ledger_sequence, source_account, fee, operation_count, successful)
+                    VALUES ($1, $2, $3, $4, $5, $6)
+                    ON CONFLICT (hash) DO NOTHING
+                    "#,
+                )
+                .bind(&tx.hash)
+                .bind(ledger.sequence as i64)
+                .bind(&tx.source_account)
+                .bind(tx.fee_charged)
+                .bind(tx.operations.len() as i32)
+                .bind(tx.successful)
+                .execute(&mut *db_tx)
+                .await?;
+
+                for operation in &tx.operations {
+                    if let DecodedOperationKind::Payment(payment) = &operation.kind {
+                        let extracted = ExtractedPayment {
+                            ledger_sequence: ledger.sequence,
+                            transaction_hash: tx.hash.clone(),
+                            operation_type: payment.operation_type.to_string(),
+                            source_account: operation.source_account.clone(),
+                            destination: payment.destination.clone(),
+                            asset_code: payment.asset_code.clone(),
+                            asset_issuer: payment.asset_issuer.clone(),
+                            amount: xdr::format_stroops(payment.amount),
+                        };
+
+                        Self::persist_payment(&mut *db_tx, &extracted).await?;
+                    }
+                }
+            }
+
+            db_tx.commit().await?;
+        });
+
+        if let Err(e) = self.fee_bump_tracker.process_decoded_ledger(ledger).await {
+            warn!(
+                "Failed to process fee bumps for ledger {}: {}",
+                ledger.sequence, e
+            );
+        }
+
+        if let Err(e) = self
+            .account_merge_detector
+            .process_decoded_ledger(ledger)
+            .await
+        {
+            warn!(
+                "Failed to process account merge operations for ledger {}: {}",
+                ledger.sequence, e
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Fallback for ledgers without decodable metadata: fetch payments,
+    /// transactions and operations from Horizon
+    async fn ingest_from_horizon(&self, ledger: &RpcLedger) {
+        match self
+            .rpc_client
+            .fetch_payments_for_ledger(ledger.sequence)
+            .await
+        {
+            Ok(payments) => {
+                for payment in payments {
+                    // Convert RPC Payment to ExtractedPayment
+                    // Uses helper methods to support both old and new Horizon formats
+                    let extracted = ExtractedPayment {
+                        ledger_sequence: ledger.sequence,
+                        transaction_hash: payment.transaction_hash.clone(),
+                        operation_type: "payment".to_string(), // Horizon 'payments' endpoint returns payments
+                        source_account: payment.source_account.clone(),
+                        destination: payment.get_destination().unwrap_or_default(),
+                        asset_code: payment.get_asset_code(),
+                        asset_issuer: payment.get_asset_issuer(),
+                        amount: payment.get_amount(),
+                    };
+
+                    let persisted = with_pool!(&self.pool, |pool| {
+                        Self::persist_payment(pool, &extracted).await
+                    });
+                    if let Err(e) = persisted {
+                        warn!("Failed to persist payment: {}", e);
+                    }
+                }
+            }
+            Err(e) => {
                 warn!(
-                    "Failed to process account merge operations for ledger {}: {}",
+                    "Failed to fetch payments for ledger {}: {}",
                     ledger.sequence, e
                 );
+                // Non-fatal, continue ingesting ledgers
             }
+        }
 
-            count += 1;
+        // Fetch and process transactions for fee bumps
+        match self
+            .rpc_client
+            .fetch_transactions_for_ledger(ledger.sequence)
+            .await
+        {
+            Ok(transactions) => {
+                if let Err(e) = self
+                    .fee_bump_tracker
+                    .process_transactions(&transactions)
+                    .await
+                {
+                    warn!("Failed to process transactions for fee bumps: {}", e);
+                }
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to fetch transactions for ledger {}: {}",
+                    ledger.sequence, e
+                );
+            }
         }
 
-        info!("Processed {} ledgers", count);
-        Ok(count)
+        if let Err(e) = self
+            .account_merge_detector
+            .process_ledger_operations(ledger.sequence)
+            .await
+        {
+            warn!(
+                "Failed to process account merge operations for ledger {}: {}",
+                ledger.sequence, e
+            );
+        }
     }
 
-    /// I'm persisting a single ledger to the database
-    async fn persist_ledger(&self, ledger: &RpcLedger) -> Result<()> {
+    /// I'm persisting a single ledger to the database (Horizon fallback path).
+    /// Returns false when the ledger was already stored.
+    async fn persist_ledger(
+        &self,
+        ledger: &RpcLedger,
+        previous_hash: Option<&str>,
+    ) -> Result<bool> {
         let close_time = self.parse_ledger_time(&ledger.ledger_close_time)?;
+        with_pool!(&self.pool, |pool| {
+            let mut db_tx = pool.begin().await?;
 
-        sqlx::query(
-            r#"
-            INSERT INTO ledgers (sequence, hash, close_time, transaction_count, operation_count)
-            VALUES ($1, $2, $3, $4, $5)
-            ON CONFLICT (sequence) DO NOTHING
-            "#,
-        )
-        .bind(ledger.sequence as i64)
-        .bind(&ledger.hash)
-        .bind(close_time)
-        .bind(0i32) // I'd get real counts from XDR parsing
-        .bind(0i32)
-        .execute(&self.pool)
-        .await?;
+            let inserted = sqlx::query(
+                r#"
+                INSERT INTO ledgers (sequence, hash, previous_hash, close_time, transaction_count, operation_count)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (sequence) DO NOTHING
+                "#,
+            )
+            .bind(ledger.sequence as i64)
+            .bind(&ledger.hash)
+            .bind(previous_hash)
+            .bind(close_time)
+            .bind(0i32) // I'd get real counts from XDR parsing
+            .bind(0i32)
+            .execute(&mut *db_tx)
+            .await?;
 
-        // I'm also storing a placeholder transaction for the ledger
-        let tx_hash = format!("tx_{}", ledger.sequence);
-        sqlx::query(
-            r#"
-            INSERT INTO transactions (hash, ledger_sequence, source_account, fee, operation_count, successful)
-            VALUES ($1, $2, $3, $4, $5, $6)
-            ON CONFLICT (hash) DO NOTHING
-            "#,
-        )
-        .bind(&tx_hash)
-        .bind(ledger.sequence as i64)
-        .bind("GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF")
-        .bind(100i64)
-        .bind(1i32)
-        .bind(true)
-        .execute(&self.pool)
-        .await?;
+            // I'm also storing a placeholder transaction for the ledger
+            let tx_hash = format!("tx_{}", ledger.sequence);
+            sqlx::query(
+                r#"
+                INSERT INTO transactions (hash, ledger_sequence, source_account, fee, operation_count, successful)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (hash) DO NOTHING
+                "#,
+            )
+            .bind(&tx_hash)
+            .bind(ledger.sequence as i64)
+            .bind("GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF")
+            .bind(100i64)
+            .bind(1i32)
+            .bind(true)
+            .execute(&mut *db_tx)
+            .await?;
 
-        Ok(())
+            db_tx.commit().await?;
+
+            Ok(inserted.rows_affected() > 0)
+        })
     }
 
     /// I'm persisting an extracted payment to the database
-    async fn persist_payment(&self, payment: &ExtractedPayment) -> Result<()> {
+    async fn persist_payment<'e, DB, E>(executor: E, payment: &ExtractedPayment) -> Result<()>
+    where
+        DB: sqlx::Database,
+        E: sqlx::Executor<'e, Database = DB>,
+        for<'q> DB::Arguments<'q>: sqlx::IntoArguments<'q, DB>,
+        for<'q> i64: sqlx::Encode<'q, DB> + sqlx::Type<DB>,
+        for<'q> &'q String: sqlx::Encode<'q, DB> + sqlx::Type<DB>,
+        for<'q> &'q Option<String>: sqlx::Encode<'q, DB> + sqlx::Type<DB>,
+    {
         sqlx::query(
             r#"
             INSERT INTO ledger_payments (ledger_sequence, transaction_hash, operation_type, source_account, destination, asset_code, asset_issuer, amount)
@@ -223,7 +734,7 @@ impl LedgerIngestionService {
         .bind(&payment.asset_code)
         .bind(&payment.asset_issuer)
         .bind(&payment.amount)
-        .execute(&self.pool)
+        .execute(executor)
         .await?;
 
         Ok(())
@@ -231,39 +742,46 @@ impl LedgerIngestionService {
 
     /// I'm getting the last ingested ledger sequence for resume
     async fn get_last_ledger(&self) -> Result<Option<u64>> {
-        let row: Option<(i64,)> =
+        let row: Option<(i64,)> = with_pool!(&self.pool, |pool| {
             sqlx::query_as("SELECT last_ledger_sequence FROM ingestion_cursor WHERE id = 1")
-                .fetch_optional(&self.pool)
-                .await?;
+                .fetch_optional(pool)
+                .await
+        })?;
         Ok(row.map(|r| r.0 as u64))
     }
 
     /// I'm getting the saved cursor for pagination
     async fn get_cursor(&self) -> Result<Option<String>> {
-        let row: Option<(Option<String>,)> =
+        let row: Option<(Option<String>,)> = with_pool!(&self.pool, |pool| {
             sqlx::query_as("SELECT cursor FROM ingestion_cursor WHERE id = 1")
-                .fetch_optional(&self.pool)
-                .await?;
+                .fetch_optional(pool)
+                .await
+        })?;
         Ok(row.and_then(|r| r.0))
     }
 
-    /// I'm saving cursor and last ledger for restart safety
-    async fn save_cursor(&self, cursor: &str, last_ledger: Option<u64>) -> Result<()> {
-        let seq = last_ledger.unwrap_or(0) as i64;
-        sqlx::query(
-            r#"
-            INSERT INTO ingestion_cursor (id, last_ledger_sequence, cursor, updated_at)
-            VALUES (1, $1, $2, CURRENT_TIMESTAMP)
-            ON CONFLICT (id) DO UPDATE SET
-                last_ledger_sequence = EXCLUDED.last_ledger_sequence,
-                cursor = EXCLUDED.cursor,
-                updated_at = CURRENT_TIMESTAMP
-            "#,
-        )
-        .bind(seq)
-        .bind(cursor)
-        .execute(&self.pool)
-        .await?;
+    /// I'm saving cursor, last ledger and its hash for restart safety
+    async fn save_cursor(&self, cursor: &str, last_ledger: Option<&RpcLedger>) -> Result<()> {
+        let seq = last_ledger.map_or(0, |l| l.sequence) as i64;
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO ingestion_cursor (id, last_ledger_sequence, cursor, last_ledger_hash, updated_at)
+                VALUES (1, $1, $2, $3, CURRENT_TIMESTAMP)
+                ON CONFLICT (id) DO UPDATE SET
+                    last_ledger_sequence = EXCLUDED.last_ledger_sequence,
+                    cursor = EXCLUDED.cursor,
+                    last_ledger_hash = EXCLUDED.last_ledger_hash,
+                    updated_at = CURRENT_TIMESTAMP
+                "#,
+            )
+            .bind(seq)
+            .bind(cursor)
+            .bind(last_ledger.map(|l| l.hash.as_str()))
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
         Ok(())
     }
 
@@ -273,3 +791,136 @@ impl LedgerIngestionService {
         Ok(Utc.timestamp_opt(ts, 0).single().unwrap_or_else(Utc::now))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::SqlitePool;
+
+    const OLDEST: u64 = 51_565_760;
+
+    async fn service() -> (LedgerIngestionService, SqlitePool) {
+        let pool = crate::test_support::migrated_pool().await;
+
+        let rpc_client = Arc::new(StellarRpcClient::new_with_defaults(true));
+        let service = LedgerIngestionService::new(
+            Arc::clone(&rpc_client),
+            Arc::new(FeeBumpTrackerService::new(pool.clone().into())),
+            Arc::new(AccountMergeDetector::new(pool.clone().into(), rpc_client)),
+            pool.clone().into(),
+        );
+        (service, pool)
+    }
+
+    /// Store ledgers, using the mock RPC's canonical hashes up to `diverge_after`
+    async fn store_chain(pool: &SqlitePool, last: u64, diverge_after: u64) {
+        for sequence in OLDEST..=last {
+            let hash = if sequence > diverge_after {
+                format!("orphan_{}", sequence)
+            } else {
+                format!("hash_{}", sequence)
+            };
+            sqlx::query("INSERT INTO ledgers (sequence, hash, close_time) VALUES ($1, $2, '0')")
+                .bind(sequence as i64)
+                .bind(&hash)
+                .execute(pool)
+                .await
+                .unwrap();
+            sqlx::query(
+                "INSERT INTO ledger_payments (ledger_sequence, transaction_hash) VALUES ($1, $2)",
+            )
+            .bind(sequence as i64)
+            .bind(format!("tx_{}", sequence))
+            .execute(pool)
+            .await
+            .unwrap();
+            sqlx::query(
+                r#"
+                INSERT INTO payments (id, transaction_hash, source_account, destination_account, asset_type, amount, created_at)
+                VALUES ($1, $2, 'GSOURCE', 'GDEST', 'native', 1.0, '0')
+                "#,
+            )
+            .bind(format!("payment_{}", sequence))
+            .bind(format!("tx_{}", sequence))
+            .execute(pool)
+            .await
+            .unwrap();
+        }
+    }
+
+    async fn count(pool: &SqlitePool, table: &str) -> i64 {
+        sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", table))
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_find_fork_point() {
+        let (service, pool) = service().await;
+        store_chain(&pool, OLDEST + 10, OLDEST + 5).await;
+
+        let fork_point = service.find_fork_point(OLDEST + 10).await.unwrap();
+        assert_eq!(fork_point, OLDEST + 5);
+    }
+
+    #[tokio::test]
+    async fn test_rollback_removes_derived_rows_and_rewinds_cursor() {
+        let (service, pool) = service().await;
+        store_chain(&pool, OLDEST + 10, OLDEST + 10).await;
+        service
+            .save_cursor(
+                "cursor",
+                Some(&RpcLedger {
+                    hash: format!("hash_{}", OLDEST + 10),
+                    sequence: OLDEST + 10,
+                    ledger_close_time: "0".to_string(),
+                    header_xdr: None,
+                    metadata_xdr: None,
+                }),
+            )
+            .await
+            .unwrap();
+
+        let summary = service.rollback_to(OLDEST + 7).await.unwrap();
+        assert_eq!(summary.ledgers, 3);
+        assert_eq!(summary.payments, 6);
+        assert_eq!(count(&pool, "ledger_payments").await, 8);
+        assert_eq!(count(&pool, "payments").await, 8);
+
+        assert_eq!(service.get_last_ledger().await.unwrap(), Some(OLDEST + 7));
+        assert_eq!(service.get_cursor().await.unwrap(), None);
+        let hash: Option<String> =
+            sqlx::query_scalar("SELECT last_ledger_hash FROM ingestion_cursor WHERE id = 1")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(hash, Some(format!("hash_{}", OLDEST + 7)));
+    }
+
+    #[tokio::test]
+    async fn test_mismatched_hash_triggers_rollback() {
+        let (service, pool) = service().await;
+        store_chain(&pool, OLDEST + 10, OLDEST + 3).await;
+
+        let result = service
+            .rpc_client
+            .fetch_ledgers(Some(OLDEST + 8), 5, None)
+            .await
+            .unwrap();
+        let outcome = service.process_ledgers(&result).await.unwrap();
+
+        assert_eq!(outcome.fork_point, Some(OLDEST + 3));
+        assert_eq!(outcome.persisted, 0);
+        assert_eq!(service.get_last_ledger().await.unwrap(), Some(OLDEST + 3));
+
+        assert_eq!(count(&pool, "ledger_reorgs").await, 1);
+        assert_eq!(count(&pool, "payments").await, 4);
+
+        let rolled_back: i64 = sqlx::query_scalar("SELECT payments_rolled_back FROM ledger_reorgs")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(rolled_back, 14);
+    }
+}
@@ -1,13 +1,23 @@
 // I'm exporting the ledger ingestion module as required by issue #2
+pub mod backfill;
+pub mod contract_events;
 pub mod ledger;
+pub mod settlement;
+pub mod xdr;
 
 use anyhow::{Context, Result};
 use serde::Serialize;
 use std::sync::Arc;
 use tracing::{info, warn};
 
-use crate::database::Database;
+use crate::database::{AnchorPaymentSync, Database};
 use crate::rpc::StellarRpcClient;
+use crate::with_pool;
+
+/// Payments requested per Horizon page
+const PAYMENTS_PAGE_SIZE: u32 = 200;
+/// Pages read per anchor per sync; a backlog larger than this is picked up on later runs
+const MAX_PAYMENT_PAGES_PER_SYNC: usize = 50;
 
 pub struct DataIngestionService {
     rpc_client: Arc<StellarRpcClient>,
@@ -45,44 +55,67 @@ impl DataIngestionService {
         Ok(())
     }
 
-    /// Process metrics for a single anchor
+    /// Process metrics for a single anchor.
+    ///
+    /// Pages forward through the anchor's payment history from the stored
+    /// cursor, so the first run walks the full history and later runs only see
+    /// new payments. Counters accumulate across runs.
     async fn process_anchor_metrics(&self, account_id: &str) -> Result<()> {
-        let payments = self
-            .rpc_client
-            .fetch_account_payments(account_id, 100)
-            .await
-            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        let mut sync = self
+            .db
+            .get_anchor_payment_sync(account_id)
+            .await?
+            .unwrap_or_else(|| AnchorPaymentSync {
+                stellar_account: account_id.to_string(),
+                ..Default::default()
+            });
+
+        let (total_before, failed_before) = (sync.stats.total_payments, sync.stats.failed_payments);
+        let mut pages = 0;
+        loop {
+            let payments = self
+                .rpc_client
+                .fetch_account_payments_page(account_id, sync.cursor.as_deref(), PAYMENTS_PAGE_SIZE)
+                .await
+                .map_err(|e| anyhow::anyhow!("{}", e))?;
+
+            let Some(last) = payments.last() else {
+                break;
+            };
+            sync.cursor = Some(last.payment.paging_token.clone());
+
+            let hashes: Vec<String> = payments
+                .iter()
+                .map(|p| p.payment.transaction_hash.clone())
+                .collect();
+            let sep_transfers = self.db.get_sep_transfers(account_id, &hashes).await?;
+
+            for payment in &payments {
+                sync.stats.record(
+                    payment,
+                    sep_transfers.get(&payment.payment.transaction_hash),
+                );
+            }
 
-        if payments.is_empty() {
-            return Ok(());
+            pages += 1;
+            if payments.len() < PAYMENTS_PAGE_SIZE as usize || pages >= MAX_PAYMENT_PAGES_PER_SYNC {
+                break;
+            }
         }
 
-        let mut successful = 0;
-        let failed = 0;
-        let mut total_volume = 0.0;
-        let settlement_times = Vec::new(); // Removed mut as it's never pushed to
-
-        for payment in &payments {
-            let amount: f64 = payment.get_amount().parse().unwrap_or(0.0);
-            total_volume += amount;
-
-            successful += 1;
+        if sync.stats.total_payments == 0 {
+            return Ok(());
         }
 
-        let total_transactions = (successful + failed) as i64;
-        let success_rate = if total_transactions > 0 {
-            (successful as f64 / total_transactions as f64) * 100.0
-        } else {
-            0.0
-        };
-
-        let reliability_score = self.calculate_reliability_score(success_rate, failed as i64);
+        self.db.save_anchor_payment_sync(&sync).await?;
 
-        let avg_settlement_time = if !settlement_times.is_empty() {
-            settlement_times.iter().sum::<i32>() / settlement_times.len() as i32
-        } else {
-            1000
-        };
+        let stats = &sync.stats;
+        let success_rate = stats.success_rate();
+        let reliability_score = self.calculate_reliability_score(
+            success_rate,
+            stats.failed_payments - failed_before,
+            stats.total_payments - total_before,
+        );
 
         let status = if success_rate >= 98.0 {
             "green"
@@ -95,11 +128,11 @@ impl DataIngestionService {
         self.db
             .update_anchor_from_rpc(crate::database::AnchorRpcUpdate {
                 stellar_account: account_id.to_string(),
-                total_transactions,
-                successful_transactions: successful as i64,
-                failed_transactions: failed as i64,
-                total_volume_usd: total_volume,
-                avg_settlement_time_ms: avg_settlement_time,
+                total_transactions: stats.total_payments,
+                successful_transactions: stats.successful_payments,
+                failed_transactions: stats.failed_payments,
+                total_volume_usd: stats.total_volume,
+                avg_settlement_time_ms: stats.avg_settlement_time_ms(),
                 reliability_score,
                 status: status.to_string(),
             })
@@ -108,9 +141,20 @@ impl DataIngestionService {
         Ok(())
     }
 
-    fn calculate_reliability_score(&self, success_rate: f64, failed_count: i64) -> f64 {
+    /// Lifetime success rate, less up to 0.2 for the failure rate among the
+    /// payments read by this sync
+    fn calculate_reliability_score(
+        &self,
+        success_rate: f64,
+        recent_failed: i64,
+        recent_total: i64,
+    ) -> f64 {
         let base_score = success_rate / 100.0;
-        let penalty = (failed_count as f64 * 0.01).min(0.2);
+        let penalty = if recent_total > 0 {
+            recent_failed as f64 / recent_total as f64 * 0.2
+        } else {
+            0.0
+        };
         (base_score - penalty).clamp(0.0, 1.0)
     }
 
@@ -148,10 +192,11 @@ impl DataIngestionService {
 
     pub async fn get_ingestion_status(&self) -> Result<IngestionStatus> {
         // We get local state
-        let cursor_row: Option<(i64,)> =
+        let cursor_row: Option<(i64,)> = with_pool!(self.db.pool(), |pool| {
             sqlx::query_as("SELECT last_ledger_sequence FROM ingestion_cursor WHERE id = 1")
-                .fetch_optional(self.db.pool())
-                .await?;
+                .fetch_optional(pool)
+                .await
+        })?;
 
         let last_ingested = cursor_row.map(|r| r.0 as u64).unwrap_or(0);
 
@@ -0,0 +1,318 @@
+//! Outcome and settlement-time aggregation for anchor payments.
+//!
+//! Success and failure come from the parent transaction's result. Settlement
+//! latency is measured from the anchor's own SEP-24/SEP-31 `started_at` for the
+//! transfer when the anchor reported one. Otherwise it falls back to an on-chain
+//! estimate: from the transaction's lower time bound (`valid_after`) to the close
+//! time of the ledger that included it. Wallets set that bound when they build
+//! the transaction, so it approximates the submission time, and it only exists
+//! for transactions built with time bounds. Payments with neither have no
+//! latency and are left out of the average.
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use crate::rpc::stellar::AccountPayment;
+
+pub const PROTOCOL_SEP24: &str = "sep24";
+pub const PROTOCOL_SEP31: &str = "sep31";
+
+/// Time bounds further than this before ledger close are treated as
+/// pre-signed transactions rather than submission times
+const MAX_ON_CHAIN_LATENCY_MS: i64 = 60 * 60 * 1000;
+
+/// Timestamps an anchor reported for a SEP-24 or SEP-31 transfer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SepTransferTimes {
+    pub stellar_transaction_id: String,
+    pub protocol: String,
+    pub transfer_id: String,
+    pub status: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Extract transfer timestamps from a SEP-24/SEP-31 `/transaction(s)` response.
+///
+/// Accepts both the list shape (`{"transactions": [...]}`) and the single
+/// shape (`{"transaction": {...}}`). Transfers without an on-chain transaction
+/// id are skipped since they cannot be matched to a payment.
+pub fn parse_sep_transactions(protocol: &str, body: &Value) -> Vec<SepTransferTimes> {
+    let records: Vec<&Value> = match (body.get("transactions"), body.get("transaction")) {
+        (Some(Value::Array(list)), _) => list.iter().collect(),
+        (_, Some(single @ Value::Object(_))) => vec![single],
+        _ => Vec::new(),
+    };
+
+    records
+        .into_iter()
+        .filter_map(|record| {
+            let stellar_transaction_id = record
+                .get("stellar_transaction_id")
+                .and_then(Value::as_str)
+                .filter(|id| !id.is_empty())?;
+            let field = |name: &str| record.get(name).and_then(Value::as_str);
+
+            Some(SepTransferTimes {
+                stellar_transaction_id: stellar_transaction_id.to_string(),
+                protocol: protocol.to_string(),
+                transfer_id: field("id").unwrap_or_default().to_string(),
+                status: field("status").map(str::to_string),
+                started_at: field("started_at").and_then(parse_time),
+                completed_at: field("completed_at").and_then(parse_time),
+            })
+        })
+        .collect()
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Settlement latency of a successful payment in milliseconds, if it can be
+/// determined.
+///
+/// Uses the anchor's `started_at` when `sep` has it; otherwise the transaction's
+/// lower time bound, so payments without SEP timestamps or time bounds get `None`.
+pub fn settlement_latency_ms(
+    payment: &AccountPayment,
+    sep: Option<&SepTransferTimes>,
+) -> Option<i64> {
+    if !payment.is_successful() {
+        return None;
+    }
+
+    let closed_at = payment
+        .transaction
+        .as_ref()
+        .map_or(payment.payment.created_at.as_str(), |tx| {
+            tx.created_at.as_str()
+        });
+    let closed_at = parse_time(closed_at);
+
+    if let Some(sep) = sep {
+        if let Some(started) = sep.started_at {
+            if let Some(finished) = sep.completed_at.or(closed_at) {
+                let latency = (finished - started).num_milliseconds();
+                if latency >= 0 {
+                    return Some(latency);
+                }
+            }
+        }
+    }
+
+    let submitted_at = payment
+        .transaction
+        .as_ref()
+        .and_then(|tx| tx.valid_after.as_deref())
+        .and_then(parse_time)
+        .filter(|t| t.timestamp() > 0)?;
+
+    let latency = (closed_at? - submitted_at).num_milliseconds();
+    (0..=MAX_ON_CHAIN_LATENCY_MS)
+        .contains(&latency)
+        .then_some(latency)
+}
+
+/// Running payment counters for one anchor
+#[derive(Debug, Clone, Default, PartialEq, sqlx::FromRow)]
+pub struct PaymentStats {
+    pub total_payments: i64,
+    pub successful_payments: i64,
+    pub failed_payments: i64,
+    pub total_volume: f64,
+    pub settlement_samples: i64,
+    pub settlement_time_total_ms: i64,
+}
+
+impl PaymentStats {
+    pub fn record(&mut self, payment: &AccountPayment, sep: Option<&SepTransferTimes>) {
+        self.total_payments += 1;
+
+        if !payment.is_successful() {
+            self.failed_payments += 1;
+            return;
+        }
+
+        self.successful_payments += 1;
+        self.total_volume += payment.payment.get_amount().parse::<f64>().unwrap_or(0.0);
+
+        if let Some(latency) = settlement_latency_ms(payment, sep) {
+            self.settlement_samples += 1;
+            self.settlement_time_total_ms += latency;
+        }
+    }
+
+    /// Success rate as a percentage
+    pub fn success_rate(&self) -> f64 {
+        if self.total_payments > 0 {
+            (self.successful_payments as f64 / self.total_payments as f64) * 100.0
+        } else {
+            0.0
+        }
+    }
+
+    /// Mean settlement time, or `None` when no payment had usable timing
+    pub fn avg_settlement_time_ms(&self) -> Option<i32> {
+        if self.settlement_samples == 0 {
+            return None;
+        }
+        i32::try_from(self.settlement_time_total_ms / self.settlement_samples).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::stellar::{Payment, PaymentTransaction};
+    use serde_json::json;
+
+    fn account_payment(
+        successful: bool,
+        closed_at: &str,
+        valid_after: Option<&str>,
+    ) -> AccountPayment {
+        let payment: Payment = serde_json::from_value(json!({
+            "id": "1",
+            "paging_token": "1",
+            "transaction_hash": "abc",
+            "source_account": "GSOURCE",
+            "destination": "GDEST",
+            "asset_type": "credit_alphanum4",
+            "asset_code": "USDC",
+            "asset_issuer": "GISSUER",
+            "amount": "25.5000000",
+            "created_at": closed_at,
+        }))
+        .unwrap();
+
+        AccountPayment {
+            payment,
+            transaction_successful: Some(successful),
+            transaction: Some(PaymentTransaction {
+                hash: "abc".to_string(),
+                ledger: 100,
+                created_at: closed_at.to_string(),
+                successful,
+                valid_after: valid_after.map(str::to_string),
+                valid_before: None,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_account_payment_deserializes_joined_transaction() {
+        let record: AccountPayment = serde_json::from_value(json!({
+            "id": "1",
+            "paging_token": "1",
+            "transaction_hash": "abc",
+            "transaction_successful": false,
+            "source_account": "GSOURCE",
+            "asset_type": "native",
+            "amount": "1.0000000",
+            "created_at": "2026-01-22T10:00:05Z",
+            "type": "payment",
+            "transaction": {
+                "hash": "abc",
+                "ledger": 100,
+                "created_at": "2026-01-22T10:00:05Z",
+                "successful": false,
+                "valid_after": "1970-01-01T00:00:00Z"
+            }
+        }))
+        .unwrap();
+
+        assert!(!record.is_successful());
+        assert_eq!(record.payment.operation_type.as_deref(), Some("payment"));
+    }
+
+    #[test]
+    fn test_latency_from_time_bounds() {
+        let payment = account_payment(true, "2026-01-22T10:00:05Z", Some("2026-01-22T10:00:01Z"));
+        assert_eq!(settlement_latency_ms(&payment, None), Some(4_000));
+    }
+
+    #[test]
+    fn test_latency_ignores_unset_and_stale_time_bounds() {
+        let unset = account_payment(true, "2026-01-22T10:00:05Z", Some("1970-01-01T00:00:00Z"));
+        assert_eq!(settlement_latency_ms(&unset, None), None);
+
+        let presigned = account_payment(true, "2026-01-22T10:00:05Z", Some("2026-01-20T10:00:00Z"));
+        assert_eq!(settlement_latency_ms(&presigned, None), None);
+    }
+
+    #[test]
+    fn test_latency_prefers_sep_timestamps() {
+        let payment = account_payment(true, "2026-01-22T10:00:05Z", Some("2026-01-22T10:00:01Z"));
+        let sep = SepTransferTimes {
+            stellar_transaction_id: "abc".to_string(),
+            protocol: PROTOCOL_SEP24.to_string(),
+            transfer_id: "t1".to_string(),
+            status: Some("completed".to_string()),
+            started_at: parse_time("2026-01-22T09:58:00Z"),
+            completed_at: parse_time("2026-01-22T10:01:00Z"),
+        };
+        assert_eq!(settlement_latency_ms(&payment, Some(&sep)), Some(180_000));
+
+        let in_progress = SepTransferTimes {
+            completed_at: None,
+            ..sep
+        };
+        assert_eq!(
+            settlement_latency_ms(&payment, Some(&in_progress)),
+            Some(125_000)
+        );
+    }
+
+    #[test]
+    fn test_stats_count_failures() {
+        let mut stats = PaymentStats::default();
+        stats.record(
+            &account_payment(true, "2026-01-22T10:00:05Z", Some("2026-01-22T10:00:03Z")),
+            None,
+        );
+        stats.record(
+            &account_payment(false, "2026-01-22T10:00:10Z", Some("2026-01-22T10:00:03Z")),
+            None,
+        );
+        stats.record(&account_payment(true, "2026-01-22T10:00:15Z", None), None);
+
+        assert_eq!(stats.total_payments, 3);
+        assert_eq!(stats.successful_payments, 2);
+        assert_eq!(stats.failed_payments, 1);
+        assert!((stats.total_volume - 51.0).abs() < f64::EPSILON);
+        assert_eq!(stats.settlement_samples, 1);
+        assert_eq!(stats.avg_settlement_time_ms(), Some(2_000));
+        assert!((stats.success_rate() - 66.666).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_parse_sep_transactions() {
+        let body = json!({
+            "transactions": [
+                {
+                    "id": "t1",
+                    "status": "completed",
+                    "started_at": "2026-01-22T09:58:00Z",
+                    "completed_at": "2026-01-22T10:01:00Z",
+                    "stellar_transaction_id": "abc"
+                },
+                { "id": "t2", "status": "pending_user_transfer_start", "stellar_transaction_id": "" }
+            ]
+        });
+        let parsed = parse_sep_transactions(PROTOCOL_SEP24, &body);
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].transfer_id, "t1");
+        assert!(parsed[0].completed_at.is_some());
+
+        let single = json!({
+            "transaction": { "id": "t3", "started_at": "2026-01-22T09:58:00Z", "stellar_transaction_id": "def" }
+        });
+        let parsed = parse_sep_transactions(PROTOCOL_SEP31, &single);
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].protocol, PROTOCOL_SEP31);
+        assert_eq!(parsed[0].completed_at, None);
+    }
+}
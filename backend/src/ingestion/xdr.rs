@@ -0,0 +1,751 @@
+//! In-process decoding of the `LedgerCloseMeta` XDR returned by RPC `getLedgers`.
+//!
+//! Every ledger returned by `getLedgers` carries its full close meta, which
+//! already contains the transaction set, the results and the per-operation
+//! outcomes. Decoding it locally avoids three Horizon round trips per ledger.
+
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chrono::{DateTime, TimeZone, Utc};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use stellar_xdr::curr::{
+    AccountId, AccountMergeResult, Asset, FeeBumpTransactionInnerTx, GeneralizedTransactionSet,
+    Hash, LedgerCloseMeta, LedgerHeaderHistoryEntry, Limits, MuxedAccount, Operation,
+    OperationBody, OperationResult, OperationResultTr, OperationType, PathPaymentStrictSendResult,
+    Preconditions, PublicKey, ReadXdr, Transaction, TransactionEnvelope, TransactionExt,
+    TransactionPhase, TransactionResultMeta, TransactionResultResult, TransactionSignaturePayload,
+    TransactionSignaturePayloadTaggedTransaction, TransactionV0, TxSetComponent, Uint256, WriteXdr,
+};
+
+use crate::rpc::RpcLedger;
+
+/// Number of stroops in one unit of any Stellar asset
+const STROOPS_PER_UNIT: i64 = 10_000_000;
+
+/// A ledger decoded from its `LedgerCloseMeta`
+#[derive(Debug, Clone)]
+pub struct DecodedLedger {
+    pub sequence: u64,
+    pub hash: String,
+    pub previous_hash: String,
+    pub close_time: DateTime<Utc>,
+    /// Transactions in application order
+    pub transactions: Vec<DecodedTransaction>,
+}
+
+impl DecodedLedger {
+    pub fn operation_count(&self) -> usize {
+        self.transactions.iter().map(|tx| tx.operations.len()).sum()
+    }
+}
+
+/// A transaction paired with its result
+#[derive(Debug, Clone)]
+pub struct DecodedTransaction {
+    /// Hex-encoded hash of the outer envelope (the fee-bump hash for fee bumps)
+    pub hash: String,
+    /// 1-based position in the ledger's application order
+    pub application_order: u32,
+    /// Source account of the (inner) transaction
+    pub source_account: String,
+    pub fee_charged: i64,
+    /// Maximum fee the outer envelope was willing to pay
+    pub max_fee: i64,
+    pub successful: bool,
+    pub fee_bump: Option<DecodedFeeBump>,
+    pub operations: Vec<DecodedOperation>,
+}
+
+/// Fee-bump envelope details
+#[derive(Debug, Clone)]
+pub struct DecodedFeeBump {
+    pub fee_source: String,
+    pub inner_transaction_hash: String,
+    pub inner_max_fee: i64,
+    pub signatures_count: i32,
+}
+
+/// A single operation with the outcome taken from the transaction result
+#[derive(Debug, Clone)]
+pub struct DecodedOperation {
+    /// Horizon-compatible operation id (TOID)
+    pub id: String,
+    pub index: u32,
+    pub source_account: String,
+    pub successful: bool,
+    pub kind: DecodedOperationKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum DecodedOperationKind {
+    Payment(DecodedPayment),
+    AccountMerge {
+        destination: String,
+        /// Balance credited to the destination, only known when the merge succeeded
+        merged_balance: Option<i64>,
+    },
+    Other(OperationType),
+}
+
+/// Payment or path payment, normalized to what the destination received
+#[derive(Debug, Clone)]
+pub struct DecodedPayment {
+    /// Horizon operation type name (`payment`, `path_payment_strict_send`, ...)
+    pub operation_type: &'static str,
+    pub destination: String,
+    pub asset_code: Option<String>,
+    pub asset_issuer: Option<String>,
+    /// Amount in stroops
+    pub amount: i64,
+}
+
+/// Decode the metadata attached to an RPC ledger.
+///
+/// Returns an error when the ledger has no metadata or it is not a valid
+/// `LedgerCloseMeta`, so callers can fall back to another source.
+pub fn decode_rpc_ledger(ledger: &RpcLedger, network_passphrase: &str) -> Result<DecodedLedger> {
+    let metadata = ledger
+        .metadata_xdr
+        .as_deref()
+        .ok_or_else(|| anyhow!("ledger {} has no metadata XDR", ledger.sequence))?;
+
+    let bytes = BASE64
+        .decode(metadata)
+        .with_context(|| format!("metadata for ledger {} is not base64", ledger.sequence))?;
+    let meta = LedgerCloseMeta::from_xdr(bytes, Limits::none())
+        .with_context(|| format!("failed to decode LedgerCloseMeta for {}", ledger.sequence))?;
+
+    decode_ledger_close_meta(&meta, network_passphrase)
+}
+
+/// Previous-ledger hash from the header XDR attached to an RPC ledger.
+///
+/// Lets the chain link be verified for ledgers whose close meta cannot be
+/// decoded.
+pub fn decode_previous_hash(ledger: &RpcLedger) -> Result<String> {
+    let header = ledger
+        .header_xdr
+        .as_deref()
+        .ok_or_else(|| anyhow!("ledger {} has no header XDR", ledger.sequence))?;
+
+    let bytes = BASE64
+        .decode(header)
+        .with_context(|| format!("header for ledger {} is not base64", ledger.sequence))?;
+    let entry = LedgerHeaderHistoryEntry::from_xdr(bytes, Limits::none())
+        .with_context(|| format!("failed to decode header for {}", ledger.sequence))?;
+
+    Ok(hex::encode(entry.header.previous_ledger_hash.0))
+}
+
+/// Decode an already parsed `LedgerCloseMeta`
+pub fn decode_ledger_close_meta(
+    meta: &LedgerCloseMeta,
+    network_passphrase: &str,
+) -> Result<DecodedLedger> {
+    let (header, envelopes, tx_processing): (_, Vec<&TransactionEnvelope>, _) = match meta {
+        LedgerCloseMeta::V0(v0) => (
+            &v0.ledger_header,
+            v0.tx_set.txs.iter().collect(),
+            &v0.tx_processing,
+        ),
+        LedgerCloseMeta::V1(v1) => (
+            &v1.ledger_header,
+            generalized_envelopes(&v1.tx_set),
+            &v1.tx_processing,
+        ),
+    };
+
+    let sequence = u64::from(header.header.ledger_seq);
+    let network_id = Hash(Sha256::digest(network_passphrase.as_bytes()).into());
+
+    // The transaction set is ordered by hash, results are in application order
+    let mut by_hash: HashMap<[u8; 32], &TransactionEnvelope> = HashMap::new();
+    for envelope in envelopes {
+        by_hash.insert(envelope_hash(envelope, &network_id)?, envelope);
+    }
+
+    let mut transactions = Vec::with_capacity(tx_processing.len());
+    for (position, result_meta) in tx_processing.iter().enumerate() {
+        let application_order = u32::try_from(position + 1)?;
+        let envelope = by_hash
+            .get(&result_meta.result.transaction_hash.0)
+            .ok_or_else(|| {
+                anyhow!(
+                    "ledger {} result {} has no matching envelope",
+                    sequence,
+                    hex::encode(result_meta.result.transaction_hash.0)
+                )
+            })?;
+        transactions.push(decode_transaction(
+            sequence,
+            application_order,
+            envelope,
+            result_meta,
+        ));
+    }
+
+    Ok(DecodedLedger {
+        sequence,
+        hash: hex::encode(header.hash.0),
+        previous_hash: hex::encode(header.header.previous_ledger_hash.0),
+        close_time: close_time(header),
+        transactions,
+    })
+}
+
+/// Convert a stroop amount to Horizon's 7-decimal string representation
+pub fn format_stroops(amount: i64) -> String {
+    let sign = if amount < 0 { "-" } else { "" };
+    let abs = amount.unsigned_abs();
+    let unit = STROOPS_PER_UNIT.unsigned_abs();
+    format!("{}{}.{:07}", sign, abs / unit, abs % unit)
+}
+
+/// Horizon-compatible total order id for an operation
+pub fn operation_toid(ledger_sequence: u64, application_order: u32, op_index: u32) -> i64 {
+    ((ledger_sequence as i64) << 32)
+        | (i64::from(application_order) << 12)
+        | i64::from(op_index + 1)
+}
+
+fn close_time(header: &LedgerHeaderHistoryEntry) -> DateTime<Utc> {
+    let secs = i64::try_from(header.header.scp_value.close_time.0).unwrap_or(0);
+    Utc.timestamp_opt(secs, 0).single().unwrap_or_else(Utc::now)
+}
+
+fn generalized_envelopes(tx_set: &GeneralizedTransactionSet) -> Vec<&TransactionEnvelope> {
+    let GeneralizedTransactionSet::V1(set) = tx_set;
+    set.phases
+        .iter()
+        .flat_map(|phase| {
+            let TransactionPhase::V0(components) = phase;
+            components.iter()
+        })
+        .flat_map(|component| {
+            let TxSetComponent::TxsetCompTxsMaybeDiscountedFee(txs) = component;
+            txs.txs.iter()
+        })
+        .collect()
+}
+
+/// Hash an envelope the way stellar-core does, so it can be matched to its result
+fn envelope_hash(envelope: &TransactionEnvelope, network_id: &Hash) -> Result<[u8; 32]> {
+    let tagged_transaction = match envelope {
+        TransactionEnvelope::TxV0(env) => {
+            TransactionSignaturePayloadTaggedTransaction::Tx(v0_to_transaction(&env.tx))
+        }
+        TransactionEnvelope::Tx(env) => {
+            TransactionSignaturePayloadTaggedTransaction::Tx(env.tx.clone())
+        }
+        TransactionEnvelope::TxFeeBump(env) => {
+            TransactionSignaturePayloadTaggedTransaction::TxFeeBump(env.tx.clone())
+        }
+    };
+
+    let payload = TransactionSignaturePayload {
+        network_id: network_id.clone(),
+        tagged_transaction,
+    };
+    let bytes = payload.to_xdr(Limits::none())?;
+    Ok(Sha256::digest(bytes).into())
+}
+
+fn v0_to_transaction(tx: &TransactionV0) -> Transaction {
+    Transaction {
+        source_account: MuxedAccount::Ed25519(tx.source_account_ed25519.clone()),
+        fee: tx.fee,
+        seq_num: tx.seq_num.clone(),
+        cond: tx
+            .time_bounds
+            .as_ref()
+            .map_or(Preconditions::None, |bounds| {
+                Preconditions::Time(bounds.clone())
+            }),
+        memo: tx.memo.clone(),
+        operations: tx.operations.clone(),
+        ext: TransactionExt::V0,
+    }
+}
+
+fn decode_transaction(
+    ledger_sequence: u64,
+    application_order: u32,
+    envelope: &TransactionEnvelope,
+    result_meta: &TransactionResultMeta,
+) -> DecodedTransaction {
+    let result = &result_meta.result.result;
+
+    let (source_account, max_fee, operations, fee_bump) = match envelope {
+        TransactionEnvelope::TxV0(env) => (
+            account_from_ed25519(&env.tx.source_account_ed25519),
+            i64::from(env.tx.fee),
+            env.tx.operations.as_slice(),
+            None,
+        ),
+        TransactionEnvelope::Tx(env) => (
+            muxed_to_account(&env.tx.source_account),
+            i64::from(env.tx.fee),
+            env.tx.operations.as_slice(),
+            None,
+        ),
+        TransactionEnvelope::TxFeeBump(env) => {
+            let FeeBumpTransactionInnerTx::Tx(inner) = &env.tx.inner_tx;
+            let inner_transaction_hash = match &result.result {
+                TransactionResultResult::TxFeeBumpInnerSuccess(pair)
+                | TransactionResultResult::TxFeeBumpInnerFailed(pair) => {
+                    hex::encode(pair.transaction_hash.0)
+                }
+                _ => String::new(),
+            };
+            (
+                muxed_to_account(&inner.tx.source_account),
+                env.tx.fee,
+                inner.tx.operations.as_slice(),
+                Some(DecodedFeeBump {
+                    fee_source: muxed_to_account(&env.tx.fee_source),
+                    inner_transaction_hash,
+                    inner_max_fee: i64::from(inner.tx.fee),
+                    signatures_count: i32::try_from(env.signatures.len()).unwrap_or(i32::MAX),
+                }),
+            )
+        }
+    };
+
+    let (successful, op_results) = transaction_outcome(&result.result);
+
+    let operations = operations
+        .iter()
+        .enumerate()
+        .map(|(index, op)| {
+            let index = u32::try_from(index).unwrap_or(u32::MAX);
+            decode_operation(
+                ledger_sequence,
+                application_order,
+                index,
+                op,
+                &source_account,
+                successful,
+                op_results.and_then(|results| results.get(index as usize)),
+            )
+        })
+        .collect();
+
+    DecodedTransaction {
+        hash: hex::encode(result_meta.result.transaction_hash.0),
+        application_order,
+        source_account,
+        fee_charged: result.fee_charged,
+        max_fee,
+        successful,
+        fee_bump,
+        operations,
+    }
+}
+
+/// Whether the transaction applied, and the per-operation results when present
+fn transaction_outcome(result: &TransactionResultResult) -> (bool, Option<&[OperationResult]>) {
+    use stellar_xdr::curr::InnerTransactionResultResult as Inner;
+
+    match result {
+        TransactionResultResult::TxSuccess(ops) => (true, Some(ops.as_slice())),
+        TransactionResultResult::TxFailed(ops) => (false, Some(ops.as_slice())),
+        TransactionResultResult::TxFeeBumpInnerSuccess(pair)
+        | TransactionResultResult::TxFeeBumpInnerFailed(pair) => match &pair.result.result {
+            Inner::TxSuccess(ops) => (true, Some(ops.as_slice())),
+            Inner::TxFailed(ops) => (false, Some(ops.as_slice())),
+            _ => (false, None),
+        },
+        _ => (false, None),
+    }
+}
+
+fn decode_operation(
+    ledger_sequence: u64,
+    application_order: u32,
+    index: u32,
+    op: &Operation,
+    tx_source: &str,
+    tx_successful: bool,
+    result: Option<&OperationResult>,
+) -> DecodedOperation {
+    let result_tr = match result {
+        Some(OperationResult::OpInner(tr)) => Some(tr),
+        _ => None,
+    };
+
+    let kind = match &op.body {
+        OperationBody::Payment(payment) => DecodedOperationKind::Payment(DecodedPayment::new(
+            "payment",
+            &payment.destination,
+            &payment.asset,
+            payment.amount,
+        )),
+        OperationBody::PathPaymentStrictReceive(payment) => {
+            DecodedOperationKind::Payment(DecodedPayment::new(
+                "path_payment_strict_receive",
+                &payment.destination,
+                &payment.dest_asset,
+                payment.dest_amount,
+            ))
+        }
+        OperationBody::PathPaymentStrictSend(payment) => {
+            // The delivered amount is only known from the result
+            let delivered = match result_tr {
+                Some(OperationResultTr::PathPaymentStrictSend(
+                    PathPaymentStrictSendResult::Success(success),
+                )) => success.last.amount,
+                _ => payment.dest_min,
+            };
+            DecodedOperationKind::Payment(DecodedPayment::new(
+                "path_payment_strict_send",
+                &payment.destination,
+                &payment.dest_asset,
+                delivered,
+            ))
+        }
+        OperationBody::AccountMerge(destination) => DecodedOperationKind::AccountMerge {
+            destination: muxed_to_account(destination),
+            merged_balance: match result_tr {
+                Some(OperationResultTr::AccountMerge(AccountMergeResult::Success(balance))) => {
+                    Some(*balance)
+                }
+                _ => None,
+            },
+        },
+        other => DecodedOperationKind::Other(other.discriminant()),
+    };
+
+    DecodedOperation {
+        id: operation_toid(ledger_sequence, application_order, index).to_string(),
+        index,
+        source_account: op
+            .source_account
+            .as_ref()
+            .map_or_else(|| tx_source.to_string(), muxed_to_account),
+        successful: tx_successful,
+        kind,
+    }
+}
+
+impl DecodedPayment {
+    fn new(
+        operation_type: &'static str,
+        destination: &MuxedAccount,
+        asset: &Asset,
+        amount: i64,
+    ) -> Self {
+        let (asset_code, asset_issuer) = asset_parts(asset);
+        Self {
+            operation_type,
+            destination: muxed_to_account(destination),
+            asset_code,
+            asset_issuer,
+            amount,
+        }
+    }
+}
+
+fn asset_parts(asset: &Asset) -> (Option<String>, Option<String>) {
+    match asset {
+        Asset::Native => (None, None),
+        Asset::CreditAlphanum4(a) => (Some(a.asset_code.to_string()), Some(a.issuer.to_string())),
+        Asset::CreditAlphanum12(a) => (Some(a.asset_code.to_string()), Some(a.issuer.to_string())),
+    }
+}
+
+/// Render a (possibly muxed) account as its underlying `G...` address
+fn muxed_to_account(account: &MuxedAccount) -> String {
+    match account {
+        MuxedAccount::Ed25519(key) => account_from_ed25519(key),
+        MuxedAccount::MuxedEd25519(muxed) => account_from_ed25519(&muxed.ed25519),
+    }
+}
+
+fn account_from_ed25519(key: &Uint256) -> String {
+    AccountId(PublicKey::PublicKeyTypeEd25519(key.clone())).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stellar_xdr::curr::{
+        AlphaNum4, AssetCode4, DecoratedSignature, FeeBumpTransaction, FeeBumpTransactionEnvelope,
+        FeeBumpTransactionExt, InnerTransactionResult, InnerTransactionResultExt,
+        InnerTransactionResultPair, InnerTransactionResultResult, LedgerCloseMetaV0,
+        LedgerEntryChanges, LedgerHeader, LedgerHeaderExt, LedgerHeaderHistoryEntryExt, Memo,
+        PaymentOp, PaymentResult, SequenceNumber, Signature, SignatureHint, StellarValue,
+        StellarValueExt, TimePoint, TransactionMeta, TransactionResult, TransactionResultExt,
+        TransactionResultPair, TransactionSet, TransactionV1Envelope, VecM,
+    };
+
+    const PASSPHRASE: &str = "Test SDF Network ; September 2015";
+
+    fn account(byte: u8) -> MuxedAccount {
+        MuxedAccount::Ed25519(Uint256([byte; 32]))
+    }
+
+    fn transaction(source: u8, operations: Vec<Operation>) -> Transaction {
+        Transaction {
+            source_account: account(source),
+            fee: 200,
+            seq_num: SequenceNumber(1),
+            cond: Preconditions::None,
+            memo: Memo::None,
+            operations: operations.try_into().unwrap(),
+            ext: TransactionExt::V0,
+        }
+    }
+
+    fn signature() -> DecoratedSignature {
+        DecoratedSignature {
+            hint: SignatureHint([0; 4]),
+            signature: Signature(vec![0u8; 64].try_into().unwrap()),
+        }
+    }
+
+    fn result_meta(hash: [u8; 32], result: TransactionResult) -> TransactionResultMeta {
+        TransactionResultMeta {
+            result: TransactionResultPair {
+                transaction_hash: Hash(hash),
+                result,
+            },
+            fee_processing: LedgerEntryChanges(VecM::default()),
+            tx_apply_processing: TransactionMeta::V0(VecM::default()),
+        }
+    }
+
+    fn fixture() -> LedgerCloseMeta {
+        let network_id = Hash(Sha256::digest(PASSPHRASE.as_bytes()).into());
+
+        let usdc = Asset::CreditAlphanum4(AlphaNum4 {
+            asset_code: AssetCode4(*b"USDC"),
+            issuer: AccountId(PublicKey::PublicKeyTypeEd25519(Uint256([9; 32]))),
+        });
+        let payment_env = TransactionEnvelope::Tx(TransactionV1Envelope {
+            tx: transaction(
+                1,
+                vec![Operation {
+                    source_account: None,
+                    body: OperationBody::Payment(PaymentOp {
+                        destination: account(2),
+                        asset: usdc,
+                        amount: 1_250_000_000,
+                    }),
+                }],
+            ),
+            signatures: vec![signature()].try_into().unwrap(),
+        });
+
+        let inner = TransactionV1Envelope {
+            tx: transaction(
+                3,
+                vec![Operation {
+                    source_account: None,
+                    body: OperationBody::AccountMerge(account(4)),
+                }],
+            ),
+            signatures: vec![signature()].try_into().unwrap(),
+        };
+        let inner_hash =
+            envelope_hash(&TransactionEnvelope::Tx(inner.clone()), &network_id).unwrap();
+        let fee_bump_env = TransactionEnvelope::TxFeeBump(FeeBumpTransactionEnvelope {
+            tx: FeeBumpTransaction {
+                fee_source: account(5),
+                fee: 5_000,
+                inner_tx: FeeBumpTransactionInnerTx::Tx(inner),
+                ext: FeeBumpTransactionExt::V0,
+            },
+            signatures: vec![signature(), signature()].try_into().unwrap(),
+        });
+
+        let payment_hash = envelope_hash(&payment_env, &network_id).unwrap();
+        let fee_bump_hash = envelope_hash(&fee_bump_env, &network_id).unwrap();
+
+        let payment_result = TransactionResult {
+            fee_charged: 100,
+            result: TransactionResultResult::TxSuccess(
+                vec![OperationResult::OpInner(OperationResultTr::Payment(
+                    PaymentResult::Success,
+                ))]
+                .try_into()
+                .unwrap(),
+            ),
+            ext: TransactionResultExt::V0,
+        };
+        let fee_bump_result = TransactionResult {
+            fee_charged: 300,
+            result: TransactionResultResult::TxFeeBumpInnerSuccess(InnerTransactionResultPair {
+                transaction_hash: Hash(inner_hash),
+                result: InnerTransactionResult {
+                    fee_charged: 100,
+                    result: InnerTransactionResultResult::TxSuccess(
+                        vec![OperationResult::OpInner(OperationResultTr::AccountMerge(
+                            AccountMergeResult::Success(420_000_000),
+                        ))]
+                        .try_into()
+                        .unwrap(),
+                    ),
+                    ext: InnerTransactionResultExt::V0,
+                },
+            }),
+            ext: TransactionResultExt::V0,
+        };
+
+        LedgerCloseMeta::V0(LedgerCloseMetaV0 {
+            ledger_header: LedgerHeaderHistoryEntry {
+                hash: Hash([0xab; 32]),
+                header: LedgerHeader {
+                    ledger_version: 21,
+                    previous_ledger_hash: Hash([0xcd; 32]),
+                    scp_value: StellarValue {
+                        tx_set_hash: Hash([0; 32]),
+                        close_time: TimePoint(1_734_032_457),
+                        upgrades: VecM::default(),
+                        ext: StellarValueExt::Basic,
+                    },
+                    tx_set_result_hash: Hash([0; 32]),
+                    bucket_list_hash: Hash([0; 32]),
+                    ledger_seq: 51_565_760,
+                    total_coins: 0,
+                    fee_pool: 0,
+                    inflation_seq: 0,
+                    id_pool: 0,
+                    base_fee: 100,
+                    base_reserve: 5_000_000,
+                    max_tx_set_size: 1_000,
+                    skip_list: [Hash([0; 32]), Hash([0; 32]), Hash([0; 32]), Hash([0; 32])],
+                    ext: LedgerHeaderExt::V0,
+                },
+                ext: LedgerHeaderHistoryEntryExt::V0,
+            },
+            tx_set: TransactionSet {
+                previous_ledger_hash: Hash([0xcd; 32]),
+                // Deliberately not in application order
+                txs: vec![fee_bump_env, payment_env].try_into().unwrap(),
+            },
+            tx_processing: vec![
+                result_meta(payment_hash, payment_result),
+                result_meta(fee_bump_hash, fee_bump_result),
+            ]
+            .try_into()
+            .unwrap(),
+            upgrades_processing: VecM::default(),
+            scp_info: VecM::default(),
+        })
+    }
+
+    #[test]
+    fn test_decode_rpc_ledger_from_base64() {
+        let encoded = BASE64.encode(fixture().to_xdr(Limits::none()).unwrap());
+        let ledger = RpcLedger {
+            hash: "ab".repeat(32),
+            sequence: 51_565_760,
+            ledger_close_time: "1734032457".to_string(),
+            header_xdr: None,
+            metadata_xdr: Some(encoded),
+        };
+
+        let decoded = decode_rpc_ledger(&ledger, PASSPHRASE).unwrap();
+        assert_eq!(decoded.sequence, 51_565_760);
+        assert_eq!(decoded.hash, "ab".repeat(32));
+        assert_eq!(decoded.previous_hash, "cd".repeat(32));
+        assert_eq!(decoded.close_time.timestamp(), 1_734_032_457);
+        assert_eq!(decoded.transactions.len(), 2);
+        assert_eq!(decoded.operation_count(), 2);
+    }
+
+    #[test]
+    fn test_decode_payment_transaction() {
+        let decoded = decode_ledger_close_meta(&fixture(), PASSPHRASE).unwrap();
+        let tx = &decoded.transactions[0];
+
+        assert_eq!(tx.application_order, 1);
+        assert!(tx.successful);
+        assert!(tx.fee_bump.is_none());
+        assert_eq!(tx.fee_charged, 100);
+        assert_eq!(tx.source_account, muxed_to_account(&account(1)));
+
+        let op = &tx.operations[0];
+        assert_eq!(op.id, operation_toid(51_565_760, 1, 0).to_string());
+        match &op.kind {
+            DecodedOperationKind::Payment(payment) => {
+                assert_eq!(payment.operation_type, "payment");
+                assert_eq!(payment.asset_code.as_deref(), Some("USDC"));
+                assert_eq!(format_stroops(payment.amount), "125.0000000");
+                assert_eq!(payment.destination, muxed_to_account(&account(2)));
+            }
+            other => panic!("unexpected operation {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_fee_bump_with_account_merge() {
+        let decoded = decode_ledger_close_meta(&fixture(), PASSPHRASE).unwrap();
+        let tx = &decoded.transactions[1];
+
+        assert_eq!(tx.application_order, 2);
+        assert_eq!(tx.max_fee, 5_000);
+        assert_eq!(tx.fee_charged, 300);
+        assert_eq!(tx.source_account, muxed_to_account(&account(3)));
+
+        let fee_bump = tx.fee_bump.as_ref().unwrap();
+        assert_eq!(fee_bump.fee_source, muxed_to_account(&account(5)));
+        assert_eq!(fee_bump.inner_max_fee, 200);
+        assert_eq!(fee_bump.signatures_count, 2);
+        assert_eq!(fee_bump.inner_transaction_hash.len(), 64);
+
+        match &tx.operations[0].kind {
+            DecodedOperationKind::AccountMerge {
+                destination,
+                merged_balance,
+            } => {
+                assert_eq!(destination, &muxed_to_account(&account(4)));
+                assert_eq!(*merged_balance, Some(420_000_000));
+            }
+            other => panic!("unexpected operation {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_rejects_mock_metadata() {
+        let ledger = RpcLedger {
+            hash: "hash_1".to_string(),
+            sequence: 1,
+            ledger_close_time: "0".to_string(),
+            header_xdr: Some("mock_header".to_string()),
+            metadata_xdr: Some("mock_metadata".to_string()),
+        };
+        assert!(decode_rpc_ledger(&ledger, PASSPHRASE).is_err());
+    }
+
+    #[test]
+    fn test_decode_previous_hash_from_header() {
+        let LedgerCloseMeta::V0(meta) = fixture() else {
+            unreachable!()
+        };
+        let ledger = RpcLedger {
+            hash: "ab".repeat(32),
+            sequence: 51_565_760,
+            ledger_close_time: "1734032457".to_string(),
+            header_xdr: Some(BASE64.encode(meta.ledger_header.to_xdr(Limits::none()).unwrap())),
+            metadata_xdr: None,
+        };
+
+        assert_eq!(decode_previous_hash(&ledger).unwrap(), "cd".repeat(32));
+
+        let mock = RpcLedger {
+            header_xdr: Some("mock_header".to_string()),
+            ..ledger
+        };
+        assert!(decode_previous_hash(&mock).is_err());
+    }
+
+    #[test]
+    fn test_format_stroops() {
+        assert_eq!(format_stroops(0), "0.0000000");
+        assert_eq!(format_stroops(1), "0.0000001");
+        assert_eq!(format_stroops(10_000_000), "1.0000000");
+        assert_eq!(format_stroops(-15_000_000), "-1.5000000");
+    }
+}
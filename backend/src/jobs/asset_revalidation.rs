@@ -1,6 +1,7 @@
+use crate::database::DbPool;
+use crate::with_pool;
 use anyhow::Result;
 use chrono::{Duration, Utc};
-use sqlx::SqlitePool;
 use std::sync::Arc;
 use tokio::time::{interval, Duration as TokioDuration};
 use tracing::{error, info, warn};
@@ -34,13 +35,13 @@ impl Default for RevalidationConfig {
 
 /// Asset revalidation job
 pub struct AssetRevalidationJob {
-    pool: SqlitePool,
+    pool: DbPool,
     config: RevalidationConfig,
 }
 
 impl AssetRevalidationJob {
     /// Create a new asset revalidation job
-    pub fn new(pool: SqlitePool, config: RevalidationConfig) -> Self {
+    pub fn new(pool: DbPool, config: RevalidationConfig) -> Self {
         Self { pool, config }
     }
 
@@ -74,18 +75,20 @@ impl AssetRevalidationJob {
         let cutoff_date = Utc::now() - Duration::days(self.config.max_age_days);
 
         // Get assets that need revalidation (oldest first)
-        let assets = sqlx::query_as::<_, VerifiedAsset>(
-            r#"
-            SELECT * FROM verified_assets
-            WHERE last_verified_at IS NULL OR last_verified_at < ?
-            ORDER BY last_verified_at ASC NULLS FIRST
-            LIMIT ?
-            "#,
-        )
-        .bind(cutoff_date)
-        .bind(self.config.batch_size as i64)
-        .fetch_all(&self.pool)
-        .await?;
+        let assets = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, VerifiedAsset>(
+                r#"
+                SELECT * FROM verified_assets
+                WHERE last_verified_at IS NULL OR last_verified_at < $1
+                ORDER BY last_verified_at ASC NULLS FIRST
+                LIMIT $2
+                "#,
+            )
+            .bind(cutoff_date)
+            .bind(self.config.batch_size as i64)
+            .fetch_all(pool)
+            .await
+        })?;
 
         if assets.is_empty() {
             info!("No assets need revalidation");
@@ -162,20 +165,22 @@ impl AssetRevalidationJob {
             suspicious_count: Option<i64>,
         }
 
-        let row = sqlx::query_as::<_, StatsRow>(
-            r#"
-            SELECT
-                COUNT(*) as total_assets,
-                SUM(CASE WHEN last_verified_at IS NULL OR last_verified_at < ? THEN 1 ELSE 0 END) as needs_revalidation,
-                SUM(CASE WHEN verification_status = 'verified' THEN 1 ELSE 0 END) as verified_count,
-                SUM(CASE WHEN verification_status = 'unverified' THEN 1 ELSE 0 END) as unverified_count,
-                SUM(CASE WHEN verification_status = 'suspicious' THEN 1 ELSE 0 END) as suspicious_count
-            FROM verified_assets
-            "#,
-        )
-        .bind(cutoff_date)
-        .fetch_one(&self.pool)
-        .await?;
+        let row = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, StatsRow>(
+                r#"
+                SELECT
+                    COUNT(*) as total_assets,
+                    SUM(CASE WHEN last_verified_at IS NULL OR last_verified_at < $1 THEN 1 ELSE 0 END) as needs_revalidation,
+                    SUM(CASE WHEN verification_status = 'verified' THEN 1 ELSE 0 END) as verified_count,
+                    SUM(CASE WHEN verification_status = 'unverified' THEN 1 ELSE 0 END) as unverified_count,
+                    SUM(CASE WHEN verification_status = 'suspicious' THEN 1 ELSE 0 END) as suspicious_count
+                FROM verified_assets
+                "#,
+            )
+            .bind(cutoff_date)
+            .fetch_one(pool)
+            .await
+        })?;
 
         Ok(RevalidationStats {
             total_assets: row.total_assets.unwrap_or(0) as i64,
@@ -21,6 +21,7 @@ pub mod email;
 pub mod env_config;
 pub mod error;
 // pub mod gdpr;
+pub mod graphql;
 pub mod handlers;
 pub mod http_cache;
 pub mod ingestion;
@@ -55,3 +56,5 @@ pub mod telegram;
 
 #[cfg(test)]
 mod ml_tests;
+#[cfg(test)]
+pub(crate) mod test_support;
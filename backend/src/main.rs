@@ -1,8 +1,4 @@
 use anyhow::{Context, Result};
-use async_graphql::http::{playground_source, GraphiQLSource};
-use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
-use axum::extract::State;
-use axum::response::{Html, IntoResponse};
 use axum::{
     http::Method,
     routing::{get, post, put},
@@ -24,13 +20,17 @@ use stellar_insights_backend::api::anchors_cached::get_anchors;
 use stellar_insights_backend::api::api_analytics;
 use stellar_insights_backend::api::api_keys;
 use stellar_insights_backend::api::asset_verification;
+use stellar_insights_backend::api::backfill;
 use stellar_insights_backend::api::cache_stats;
+use stellar_insights_backend::api::contract_events;
+use stellar_insights_backend::api::corridors;
 use stellar_insights_backend::api::corridors_cached::{get_corridor_detail, list_corridors};
 use stellar_insights_backend::api::cost_calculator;
 use stellar_insights_backend::api::fee_bump;
 use stellar_insights_backend::api::liquidity_pools;
 use stellar_insights_backend::api::metrics_cached;
 use stellar_insights_backend::api::oauth;
+use stellar_insights_backend::api::prediction;
 use stellar_insights_backend::api::verification_rewards;
 use stellar_insights_backend::api::webhooks;
 use stellar_insights_backend::auth::AuthService;
@@ -39,15 +39,18 @@ use stellar_insights_backend::cache::{CacheConfig, CacheManager};
 use stellar_insights_backend::cache_invalidation::CacheInvalidationService;
 use stellar_insights_backend::database::Database;
 use stellar_insights_backend::elk_health;
-// use stellar_insights_backend::graphql::{build_schema, AppSchema};
+use stellar_insights_backend::graphql::{self, build_schema, GraphQLAuth, GraphQLConfig};
 // use stellar_insights_backend::gdpr::{GdprService, handlers as gdpr_handlers};
 use stellar_insights_backend::handlers::*;
+use stellar_insights_backend::ingestion::backfill::{BackfillConfig, LedgerBackfillService};
 use stellar_insights_backend::ingestion::ledger::LedgerIngestionService;
 use stellar_insights_backend::ingestion::DataIngestionService;
 use stellar_insights_backend::ip_whitelist_middleware::{
     ip_whitelist_middleware, IpWhitelistConfig,
 };
 use stellar_insights_backend::jobs::JobScheduler;
+use stellar_insights_backend::ml::{MLService, TrainingConfig};
+use stellar_insights_backend::ml_handlers;
 use stellar_insights_backend::monitor::CorridorMonitor;
 use stellar_insights_backend::network::NetworkConfig;
 use stellar_insights_backend::observability::{metrics as obs_metrics, tracing as obs_tracing};
@@ -59,14 +62,25 @@ use stellar_insights_backend::request_id::request_id_middleware;
 use stellar_insights_backend::rpc::StellarRpcClient;
 use stellar_insights_backend::rpc_handlers;
 use stellar_insights_backend::services::account_merge_detector::AccountMergeDetector;
+use stellar_insights_backend::services::aggregation::{AggregationConfig, AggregationService};
+use stellar_insights_backend::services::anomaly_detection::{
+    AnomalyConfig, AnomalyDetectionService,
+};
+use stellar_insights_backend::services::contract_event_indexer::{
+    ContractEventIndexer, ContractEventIndexerConfig,
+};
+use stellar_insights_backend::services::dex_aggregator::{DexAggregator, DexAggregatorConfig};
 use stellar_insights_backend::services::fee_bump_tracker::FeeBumpTrackerService;
 use stellar_insights_backend::services::liquidity_pool_analyzer::LiquidityPoolAnalyzer;
 use stellar_insights_backend::services::price_feed::{
     default_asset_mapping, PriceFeedClient, PriceFeedConfig,
 };
 use stellar_insights_backend::services::realtime_broadcaster::RealtimeBroadcaster;
+use stellar_insights_backend::services::rollup::RollupConfig;
 use stellar_insights_backend::services::trustline_analyzer::TrustlineAnalyzer;
-use stellar_insights_backend::services::webhook_dispatcher::WebhookDispatcher;
+use stellar_insights_backend::services::webhook_dispatcher::{
+    WebhookDispatcher, WebhookDispatcherConfig,
+};
 use stellar_insights_backend::shutdown::{
     flush_cache, log_shutdown_summary, shutdown_background_tasks, shutdown_database,
     shutdown_websockets, wait_for_signal, ShutdownConfig, ShutdownCoordinator,
@@ -145,7 +159,7 @@ async fn main() -> Result<()> {
     let pool = pool_config.create_pool(&database_url).await?;
 
     tracing::info!("Running database migrations...");
-    sqlx::migrate!("./migrations").run(&pool).await?;
+    stellar_insights_backend::database::run_migrations(&pool).await?;
 
     let db = Arc::new(Database::new(pool.clone()));
 
@@ -221,6 +235,13 @@ async fn main() -> Result<()> {
         pool.clone(),
     ));
 
+    // Initialize Ledger Backfill Service
+    let ledger_backfill_service = Arc::new(LedgerBackfillService::new(
+        Arc::clone(&ledger_ingestion_service),
+        pool.clone(),
+        BackfillConfig::from_env(),
+    ));
+
     // Initialize Redis cache
     let cache_config = CacheConfig::default();
     let cache = Arc::new(CacheManager::new(cache_config).await?);
@@ -250,7 +271,8 @@ async fn main() -> Result<()> {
     tracing::info!("RealtimeBroadcaster initialized");
 
     // Initialize Webhook Dispatcher
-    let webhook_dispatcher = WebhookDispatcher::new(pool.clone());
+    let webhook_dispatcher =
+        WebhookDispatcher::new(pool.clone(), WebhookDispatcherConfig::from_env());
     tracing::info!("Webhook dispatcher initialized");
 
     // Create app state for handlers that need it
@@ -386,21 +408,58 @@ async fn main() -> Result<()> {
     // let gdpr_service = Arc::new(GdprService::new(pool.clone()));
     // tracing::info!("GDPR service initialized");
 
-    // ML Retraining task (commented out)
-    /*
-    let ml_service_clone = ml_service.clone();
-    tokio::spawn(async move {
-        let mut interval = tokio::time::interval(std::time::Duration::from_secs(7 * 24 * 3600)); // 7 days
-        loop {
+    // Payment success model: serve the active registry version, retrain periodically and
+    // label logged predictions as their transactions are ingested
+    let ml_service = Arc::new(MLService::new(Arc::clone(&db), TrainingConfig::from_env()));
+    match ml_service.load_registry().await {
+        Ok(true) => {}
+        Ok(false) => tracing::info!("No payment success model yet; training on startup"),
+        Err(e) => tracing::warn!("Failed to load payment success model: {}", e),
+    }
+    let ml_retrain_hours = std::env::var("ML_RETRAIN_INTERVAL_HOURS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(168)
+        .max(1);
+
+    let ml_service_clone = Arc::clone(&ml_service);
+    let shutdown_rx_ml = shutdown_coordinator.subscribe();
+    let task = tokio::spawn(async move {
+        tracing::info!("Starting ML retraining background task");
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(ml_retrain_hours * 3600));
+        // The first tick fires immediately; skip it when a trained model is already served
+        if ml_service_clone.current_model().is_some() {
             interval.tick().await;
-            if let Ok(mut service) = ml_service_clone.try_write() {
-                if let Err(e) = service.retrain_weekly().await {
-                    tracing::error!("Weekly ML retraining failed: {}", e);
+        }
+        let mut outcome_interval = tokio::time::interval(std::time::Duration::from_secs(600));
+        let mut shutdown_rx = shutdown_rx_ml;
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    if let Err(e) = ml_service_clone.train_model().await {
+                        tracing::error!("Payment success model retraining failed: {}", e);
+                        obs_metrics::record_background_job("ml_retraining", "error");
+                    } else {
+                        obs_metrics::record_background_job("ml_retraining", "success");
+                    }
+                }
+                _ = outcome_interval.tick() => {
+                    if let Err(e) = ml_service_clone.resolve_outcomes().await {
+                        tracing::error!("Prediction outcome resolution failed: {}", e);
+                        obs_metrics::record_background_job("ml_outcome_resolution", "error");
+                    } else {
+                        obs_metrics::record_background_job("ml_outcome_resolution", "success");
+                    }
+                }
+                _ = shutdown_rx.recv() => {
+                    tracing::info!("ML retraining task shutting down");
+                    break;
                 }
             }
         }
     });
-    */
+    background_tasks.push(task);
 
     // Ledger ingestion task
     let ledger_ingestion_clone = Arc::clone(&ledger_ingestion_service);
@@ -436,6 +495,46 @@ async fn main() -> Result<()> {
     });
     background_tasks.push(task);
 
+    // Resume backfill jobs interrupted by a restart
+    match ledger_backfill_service.resume_incomplete_jobs().await {
+        Ok(0) => {}
+        Ok(count) => tracing::info!("Resumed {} ledger backfill job(s)", count),
+        Err(e) => tracing::error!("Failed to resume ledger backfill jobs: {}", e),
+    }
+
+    // Ledger gap scan background task
+    let ledger_backfill_clone = Arc::clone(&ledger_backfill_service);
+    let shutdown_rx_gaps = shutdown_coordinator.subscribe();
+    let task = tokio::spawn(async move {
+        tracing::info!("Starting ledger gap scan background task");
+        let mut interval = tokio::time::interval(LedgerBackfillService::gap_scan_interval());
+        let mut shutdown_rx = shutdown_rx_gaps;
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    match ledger_backfill_clone.requeue_gaps(None, None).await {
+                        Ok(Some(job)) => {
+                            obs_metrics::record_background_job("ledger_gap_scan", "success");
+                            ledger_backfill_clone.spawn_job(job.id);
+                        }
+                        Ok(None) => {
+                            obs_metrics::record_background_job("ledger_gap_scan", "success");
+                        }
+                        Err(e) => {
+                            tracing::error!("Ledger gap scan failed: {}", e);
+                            obs_metrics::record_background_job("ledger_gap_scan", "error");
+                        }
+                    }
+                }
+                _ = shutdown_rx.recv() => {
+                    tracing::info!("Ledger gap scan task shutting down");
+                    break;
+                }
+            }
+        }
+    });
+    background_tasks.push(task);
+
     // Liquidity pool sync background task
     let liquidity_pool_analyzer_clone = Arc::clone(&liquidity_pool_analyzer);
     let shutdown_rx3 = shutdown_coordinator.subscribe();
@@ -468,6 +567,84 @@ async fn main() -> Result<()> {
     });
     background_tasks.push(task);
 
+    // DEX liquidity snapshot background task: order book and pool depth of the busiest
+    // corridors
+    let dex_aggregator = DexAggregator::new(
+        Arc::clone(&db),
+        Arc::clone(&rpc_client),
+        Arc::clone(&price_feed),
+        DexAggregatorConfig::from_env(),
+    );
+    let shutdown_rx_dex = shutdown_coordinator.subscribe();
+    let task = tokio::spawn(async move {
+        tracing::info!("Starting DEX liquidity snapshot background task");
+        let mut interval = tokio::time::interval(dex_aggregator.config().snapshot_interval);
+        let mut shutdown_rx = shutdown_rx_dex;
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    if let Err(e) = dex_aggregator.take_snapshots(chrono::Utc::now()).await {
+                        tracing::error!("DEX liquidity snapshot failed: {}", e);
+                        obs_metrics::record_background_job("dex_liquidity_snapshot", "error");
+                    } else {
+                        obs_metrics::record_background_job("dex_liquidity_snapshot", "success");
+                    }
+                }
+                _ = shutdown_rx.recv() => {
+                    tracing::info!("DEX liquidity snapshot task shutting down");
+                    break;
+                }
+            }
+        }
+    });
+    background_tasks.push(task);
+
+    // Soroban contract event indexer, enabled when contract ids are configured
+    let event_indexer_config = ContractEventIndexerConfig::from_env();
+    if event_indexer_config.contract_ids.is_empty() {
+        tracing::info!("SOROBAN_EVENT_CONTRACT_IDS not set, contract event indexer disabled");
+    } else {
+        let event_indexer = ContractEventIndexer::new(
+            Arc::clone(&db),
+            Arc::clone(&rpc_client),
+            event_indexer_config,
+        )
+        .with_websocket(Arc::clone(&ws_state));
+        let shutdown_rx_events = shutdown_coordinator.subscribe();
+        let task = tokio::spawn(async move {
+            tracing::info!("Starting contract event indexer background task");
+            let mut interval = tokio::time::interval(event_indexer.config().poll_interval);
+            let mut shutdown_rx = shutdown_rx_events;
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {
+                        match event_indexer.run_once().await {
+                            Ok(run) => {
+                                if run.stored > 0 {
+                                    tracing::info!(
+                                        "Indexed {} contract events ({} undecoded)",
+                                        run.stored,
+                                        run.undecoded
+                                    );
+                                }
+                                obs_metrics::record_background_job("contract_event_indexer", "success");
+                            }
+                            Err(e) => {
+                                tracing::error!("Contract event indexing failed: {}", e);
+                                obs_metrics::record_background_job("contract_event_indexer", "error");
+                            }
+                        }
+                    }
+                    _ = shutdown_rx.recv() => {
+                        tracing::info!("Contract event indexer task shutting down");
+                        break;
+                    }
+                }
+            }
+        });
+        background_tasks.push(task);
+    }
+
     // Trustline stats sync background task
     let trustline_analyzer_clone = Arc::clone(&trustline_analyzer);
     let shutdown_rx4 = shutdown_coordinator.subscribe();
@@ -500,6 +677,53 @@ async fn main() -> Result<()> {
     });
     background_tasks.push(task);
 
+    // Corridor aggregation background task: hourly metrics, then day/week/month rollups,
+    // then anomaly detection on the newly finalized hours
+    let aggregation_config = AggregationConfig::default();
+    let aggregation_interval = aggregation_config.interval_hours * 3600;
+    let aggregation_service = AggregationService::new(Arc::clone(&db), aggregation_config)
+        .with_rollup_config(RollupConfig::from_env());
+    let anomaly_service = AnomalyDetectionService::new(Arc::clone(&db), AnomalyConfig::from_env())
+        .with_websocket(Arc::clone(&ws_state));
+    let shutdown_rx_aggregation = shutdown_coordinator.subscribe();
+    let task = tokio::spawn(async move {
+        tracing::info!("Starting corridor aggregation background task");
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(aggregation_interval));
+        let mut shutdown_rx = shutdown_rx_aggregation;
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    if let Err(e) = aggregation_service.run_hourly_aggregation().await {
+                        tracing::error!("Hourly corridor aggregation failed: {}", e);
+                        obs_metrics::record_background_job("corridor_aggregation", "error");
+                    } else {
+                        obs_metrics::record_background_job("corridor_aggregation", "success");
+                    }
+                    if let Err(e) = aggregation_service.run_rollups().await {
+                        tracing::error!("Corridor rollups failed: {}", e);
+                        obs_metrics::record_background_job("corridor_rollups", "error");
+                    } else {
+                        obs_metrics::record_background_job("corridor_rollups", "success");
+                    }
+                    let now = chrono::Utc::now();
+                    let complete_until = aggregation_service.hourly_complete_until(now);
+                    if let Err(e) = anomaly_service.run(now, complete_until).await {
+                        tracing::error!("Corridor anomaly detection failed: {}", e);
+                        obs_metrics::record_background_job("corridor_anomaly_detection", "error");
+                    } else {
+                        obs_metrics::record_background_job("corridor_anomaly_detection", "success");
+                    }
+                }
+                _ = shutdown_rx.recv() => {
+                    tracing::info!("Corridor aggregation task shutting down");
+                    break;
+                }
+            }
+        }
+    });
+    background_tasks.push(task);
+
     // Start RealtimeBroadcaster background task
     let shutdown_rx5 = shutdown_coordinator.subscribe();
     let task = tokio::spawn(async move {
@@ -909,6 +1133,26 @@ async fn main() -> Result<()> {
         )
         .route("/api/anchors/:id/assets", get(get_anchor_assets))
         .route("/api/analytics/muxed", get(get_muxed_analytics))
+        .route(
+            "/api/corridors/:corridor_key/metrics",
+            get(corridors::get_corridor_metrics_series),
+        )
+        .route(
+            "/api/corridors/:corridor_key/distribution",
+            get(corridors::get_corridor_distribution),
+        )
+        .route(
+            "/api/corridors/:corridor_key/anomalies",
+            get(corridors::get_corridor_anomalies),
+        )
+        .route(
+            "/api/corridors/:corridor_key/forecast",
+            get(corridors::get_corridor_forecast),
+        )
+        .route(
+            "/api/corridors/:corridor_key/depth",
+            get(corridors::get_corridor_depth_history),
+        )
         .with_state(app_state.clone())
         .layer(ServiceBuilder::new().layer(middleware::from_fn_with_state(
             rate_limiter.clone(),
@@ -945,7 +1189,7 @@ async fn main() -> Result<()> {
 
     // Build webhook routes (require authentication)
     let webhook_routes = Router::new()
-        .nest("/api/webhooks", webhooks::routes(pool.clone()))
+        .merge(webhooks::routes(pool.clone()))
         .layer(
             ServiceBuilder::new()
                 .layer(middleware::from_fn(auth_middleware))
@@ -962,6 +1206,7 @@ async fn main() -> Result<()> {
     // Build RPC router
     let rpc_routes = Router::new()
         .route("/api/rpc/health", get(rpc_handlers::rpc_health_check))
+        .route("/api/rpc/providers", get(rpc_handlers::get_provider_status))
         .route(
             "/api/rpc/ledger/latest",
             get(rpc_handlers::get_latest_ledger),
@@ -973,7 +1218,7 @@ async fn main() -> Result<()> {
         )
         .route("/api/rpc/trades", get(rpc_handlers::get_trades))
         .route("/api/rpc/orderbook", get(rpc_handlers::get_order_book))
-        .with_state(rpc_client)
+        .with_state(Arc::clone(&rpc_client))
         .layer(ServiceBuilder::new().layer(middleware::from_fn_with_state(
             rate_limiter.clone(),
             rate_limit_middleware,
@@ -992,6 +1237,18 @@ async fn main() -> Result<()> {
         )))
         .layer(cors.clone());
 
+    // Build indexed contract event routes
+    let contract_event_routes = Router::new()
+        .nest(
+            "/api/contract-events",
+            contract_events::routes(Arc::clone(&db)),
+        )
+        .layer(ServiceBuilder::new().layer(middleware::from_fn_with_state(
+            rate_limiter.clone(),
+            rate_limit_middleware,
+        )))
+        .layer(cors.clone());
+
     // Build account merge routes
     let account_merge_routes = Router::new()
         .nest(
@@ -1032,14 +1289,57 @@ async fn main() -> Result<()> {
     let cost_calculator_routes = Router::new()
         .nest(
             "/api/cost-calculator",
-            cost_calculator::routes(Arc::clone(&price_feed)),
+            cost_calculator::routes(Arc::clone(&price_feed), Arc::clone(&rpc_client)),
+        )
+        .layer(ServiceBuilder::new().layer(middleware::from_fn_with_state(
+            rate_limiter.clone(),
+            rate_limit_middleware,
+        )))
+        .layer(cors.clone());
+
+    // Build payment success prediction routes
+    let prediction_routes = Router::new()
+        .route("/api/predict/success", post(prediction::predict_success))
+        .route("/api/ml/predict", get(ml_handlers::predict_payment_success))
+        .route("/api/ml/status", get(ml_handlers::get_model_status))
+        .route("/api/ml/models", get(ml_handlers::list_models))
+        .route(
+            "/api/ml/models/evaluation",
+            get(ml_handlers::get_live_evaluation),
         )
+        .layer(axum::Extension(Arc::clone(&ml_service)))
         .layer(ServiceBuilder::new().layer(middleware::from_fn_with_state(
             rate_limiter.clone(),
             rate_limit_middleware,
         )))
         .layer(cors.clone());
 
+    // Promoting and retiring models, and reporting the outcomes they are evaluated
+    // against (ADMIN - IP whitelisted and authenticated)
+    let protected_ml_routes = Router::new()
+        .route(
+            "/api/ml/models/:version/status",
+            post(ml_handlers::set_model_status),
+        )
+        .route(
+            "/api/ml/predictions/:prediction_id/outcome",
+            post(ml_handlers::record_prediction_outcome),
+        )
+        .layer(axum::Extension(Arc::clone(&ml_service)))
+        .layer(
+            ServiceBuilder::new()
+                .layer(middleware::from_fn_with_state(
+                    ip_whitelist_config.clone(),
+                    ip_whitelist_middleware,
+                ))
+                .layer(middleware::from_fn(auth_middleware))
+                .layer(middleware::from_fn_with_state(
+                    rate_limiter.clone(),
+                    rate_limit_middleware,
+                )),
+        )
+        .layer(cors.clone());
+
     // Build network routes
     let network_routes = Router::new()
         .nest(
@@ -1064,30 +1364,28 @@ async fn main() -> Result<()> {
         )))
         .layer(cors.clone());
 
-    // Build GraphQL schema
-    // let graphql_schema = build_schema(Arc::new(pool.clone()));
-    // tracing::info!("GraphQL schema initialized");
-
-    // GraphQL handler
-    // async fn graphql_handler(
-    //     State(schema): State<AppSchema>,
-    //     req: GraphQLRequest,
-    // ) -> GraphQLResponse {
-    //     schema.execute(req.into_inner()).await.into()
-    // }
-
-    // GraphQL Playground handler
-    // async fn graphql_playground() -> impl IntoResponse {
-    //     Html(playground_source(
-    //         async_graphql::http::GraphQLPlaygroundConfig::new("/graphql"),
-    //     ))
-    // }
-
-    // Build GraphQL routes
-    // let graphql_routes = Router::new()
-    //     .route("/graphql", post(graphql_handler))
-    //     .route("/graphql/playground", get(graphql_playground))
-    //     .with_state(graphql_schema);
+    // Build GraphQL schema; subscriptions read the same events as WebSocket clients
+    let graphql_schema = build_schema(
+        Arc::clone(&db),
+        Arc::clone(&ws_state),
+        GraphQLConfig::from_env(),
+    );
+    // Mutations identify the caller by API key, JWT or SEP-10 session
+    let graphql_auth = Arc::new(GraphQLAuth::new(
+        Arc::clone(&db),
+        auth_service.clone(),
+        Arc::clone(&sep10_service),
+    ));
+    tracing::info!("GraphQL schema initialized");
+
+    // Build GraphQL routes: queries on /graphql, subscriptions on /graphql/ws
+    let graphql_routes = Router::new()
+        .merge(graphql::routes(graphql_schema, graphql_auth))
+        .layer(ServiceBuilder::new().layer(middleware::from_fn_with_state(
+            rate_limiter.clone(),
+            rate_limit_middleware,
+        )))
+        .layer(cors.clone());
 
     // Build achievements / quests routes
     let achievements_routes = Router::new()
@@ -1117,6 +1415,22 @@ async fn main() -> Result<()> {
         )
         .layer(cors.clone());
 
+    // Build ledger backfill routes (ADMIN - IP whitelisted)
+    let backfill_routes = Router::new()
+        .merge(backfill::routes(Arc::clone(&ledger_backfill_service)))
+        .layer(
+            ServiceBuilder::new()
+                .layer(middleware::from_fn_with_state(
+                    ip_whitelist_config.clone(),
+                    ip_whitelist_middleware,
+                ))
+                .layer(middleware::from_fn_with_state(
+                    rate_limiter.clone(),
+                    rate_limit_middleware,
+                )),
+        )
+        .layer(cors.clone());
+
     // Build cache stats routes (ADMIN - IP whitelisted)
     let cache_routes = Router::new()
         .merge(cache_stats::routes(Arc::clone(&cache)))
@@ -1274,18 +1588,22 @@ async fn main() -> Result<()> {
         .merge(protected_anchor_routes)
         .merge(rpc_routes)
         .merge(fee_bump_routes)
+        .merge(contract_event_routes)
         .merge(account_merge_routes)
         .merge(liquidity_pool_routes)
         .merge(price_routes)
         .merge(cost_calculator_routes)
+        .merge(prediction_routes)
+        .merge(protected_ml_routes)
         .merge(trustline_routes)
         .merge(achievements_routes)
         .merge(governance_routes)
         .merge(network_routes)
         .merge(api_analytics_routes)
         .merge(cache_routes)
+        .merge(backfill_routes)
         .merge(metrics_routes)
-        // .merge(graphql_routes) // Add GraphQL routes
+        .merge(graphql_routes)
         .merge(admin_db_routes)
         .merge(verification_routes)
         .merge(asset_verification_routes)
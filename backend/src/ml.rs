@@ -1,15 +1,107 @@
+//! Payment success prediction.
+//!
+//! A logistic regression is trained on historical `payments`, labelled with the outcome of
+//! their parent transaction, using features that describe the payment (size, time of day
+//! and week) and the state of its corridor when it was made (liquidity depth and recent
+//! success rate). The most recent 20% of the history is held out to measure ranking
+//! quality (ROC AUC) and calibration, and every trained model is persisted as a new
+//! version so a restart serves the same model that was evaluated.
+
 use crate::database::Database;
-use chrono::{DateTime, Datelike, Timelike, Utc};
+use crate::models::corridor::Corridor;
+use crate::services::rollup::{CorridorRollup, Resolution};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Datelike, Duration, Timelike, Utc};
+use ndarray::{Array1, Array2, Axis};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Name under which payment success models are versioned
+pub const MODEL_NAME: &str = "payment_success";
+
+/// Features describing one payment and its corridor at the time it was made
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PredictionFeatures {
-    pub corridor_hash: f32,
-    pub amount_usd: f32,
-    pub hour_of_day: f32,
-    pub day_of_week: f32,
-    pub liquidity_depth: f32,
-    pub recent_success_rate: f32,
+    /// log10(1 + amount in USD)
+    pub amount_usd_log: f64,
+    /// Hour of day and day of week encoded on the unit circle, so 23:00 sits next to 00:00
+    pub hour_sin: f64,
+    pub hour_cos: f64,
+    pub day_sin: f64,
+    pub day_cos: f64,
+    /// log10(1 + latest hourly liquidity depth in USD)
+    pub liquidity_depth_log: f64,
+    /// log10 of the payment size relative to liquidity depth
+    pub amount_to_liquidity_log: f64,
+    /// Laplace-smoothed success rate of the corridor's recent payments (0-1)
+    pub recent_success_rate: f64,
+}
+
+impl PredictionFeatures {
+    pub const NAMES: [&'static str; 8] = [
+        "amount_usd_log",
+        "hour_sin",
+        "hour_cos",
+        "day_sin",
+        "day_cos",
+        "liquidity_depth_log",
+        "amount_to_liquidity_log",
+        "recent_success_rate",
+    ];
+
+    pub fn new(amount_usd: f64, timestamp: DateTime<Utc>, context: &CorridorContext) -> Self {
+        use std::f64::consts::TAU;
+
+        let amount = amount_usd.max(0.0);
+        let liquidity = context.liquidity_depth_usd.max(0.0);
+        let hour = (timestamp.hour() as f64 + timestamp.minute() as f64 / 60.0) / 24.0;
+        let day = timestamp.weekday().num_days_from_monday() as f64 / 7.0;
+
+        Self {
+            amount_usd_log: amount.log10_1p(),
+            hour_sin: (TAU * hour).sin(),
+            hour_cos: (TAU * hour).cos(),
+            day_sin: (TAU * day).sin(),
+            day_cos: (TAU * day).cos(),
+            liquidity_depth_log: liquidity.log10_1p(),
+            amount_to_liquidity_log: ((amount + 1.0) / (liquidity + 1.0)).log10(),
+            recent_success_rate: (context.recent_successes as f64 + 1.0)
+                / (context.recent_payments as f64 + 2.0),
+        }
+    }
+
+    pub fn to_vec(&self) -> Vec<f64> {
+        vec![
+            self.amount_usd_log,
+            self.hour_sin,
+            self.hour_cos,
+            self.day_sin,
+            self.day_cos,
+            self.liquidity_depth_log,
+            self.amount_to_liquidity_log,
+            self.recent_success_rate,
+        ]
+    }
+}
+
+trait Log10OnePlus {
+    fn log10_1p(self) -> f64;
+}
+
+impl Log10OnePlus for f64 {
+    fn log10_1p(self) -> f64 {
+        self.ln_1p() / std::f64::consts::LN_10
+    }
+}
+
+/// State of a corridor just before a payment
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CorridorContext {
+    pub liquidity_depth_usd: f64,
+    pub recent_payments: u64,
+    pub recent_successes: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,157 +111,862 @@ pub struct PredictionResult {
     pub model_version: String,
 }
 
+/// One labelled historical payment
+#[derive(Debug, Clone)]
+pub struct TrainingExample {
+    pub features: PredictionFeatures,
+    pub successful: bool,
+    pub timestamp: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone)]
-pub struct SimpleMLModel {
-    weights: Vec<f32>,
-    bias: f32,
-    version: String,
+pub struct TrainingConfig {
+    /// History the model is trained on
+    pub lookback: Duration,
+    /// Most recent payments used, at most
+    pub max_samples: i64,
+    /// Window the recent success rate feature is computed over
+    pub success_window: Duration,
+    /// Most recent share of the history held out for evaluation
+    pub holdout_fraction: f64,
+    /// Training is refused below this many labelled payments
+    pub min_samples: usize,
+    pub learning_rate: f64,
+    pub epochs: usize,
+    /// L2 penalty on the weights
+    pub l2: f64,
+    /// Serve newly trained models immediately instead of shadowing them until promoted
+    pub auto_promote: bool,
+}
+
+impl Default for TrainingConfig {
+    fn default() -> Self {
+        Self {
+            lookback: Duration::days(30),
+            max_samples: 200_000,
+            success_window: Duration::hours(24),
+            holdout_fraction: 0.2,
+            min_samples: 200,
+            learning_rate: 0.5,
+            epochs: 500,
+            l2: 1e-3,
+            auto_promote: false,
+        }
+    }
+}
+
+impl TrainingConfig {
+    /// Reads `ML_TRAINING_LOOKBACK_DAYS`, `ML_TRAINING_MAX_SAMPLES` and `ML_AUTO_PROMOTE`,
+    /// falling back to the defaults.
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Some(days) = std::env::var("ML_TRAINING_LOOKBACK_DAYS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+        {
+            config.lookback = Duration::days(days);
+        }
+        if let Some(max) = std::env::var("ML_TRAINING_MAX_SAMPLES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+        {
+            config.max_samples = max;
+        }
+        if let Some(auto_promote) = std::env::var("ML_AUTO_PROMOTE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+        {
+            config.auto_promote = auto_promote;
+        }
+        config
+    }
+}
+
+/// Standardized logistic regression
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogisticRegression {
+    pub weights: Vec<f64>,
+    pub bias: f64,
+    /// Per-feature mean and standard deviation used to standardize inputs
+    pub feature_means: Vec<f64>,
+    pub feature_stds: Vec<f64>,
+}
+
+impl LogisticRegression {
+    /// Fit by full-batch gradient descent on standardized features
+    pub fn fit(x: &Array2<f64>, y: &Array1<f64>, config: &TrainingConfig) -> Self {
+        let n = x.nrows().max(1) as f64;
+        let means = x
+            .mean_axis(Axis(0))
+            .unwrap_or_else(|| Array1::zeros(x.ncols()));
+        let stds = x
+            .std_axis(Axis(0), 0.0)
+            .mapv(|s| if s > 1e-9 { s } else { 1.0 });
+        let z = (x - &means) / &stds;
+
+        // Start from the base rate so early steps only have to learn the feature effects
+        let base_rate = (y.sum() / n).clamp(1e-6, 1.0 - 1e-6);
+        let mut bias = (base_rate / (1.0 - base_rate)).ln();
+        let mut weights = Array1::<f64>::zeros(x.ncols());
+
+        for _ in 0..config.epochs {
+            let predictions = (z.dot(&weights) + bias).mapv(sigmoid);
+            let error = &predictions - y;
+            let weight_gradient = z.t().dot(&error) / n + &weights * config.l2;
+            let bias_gradient = error.sum() / n;
+
+            weights = weights - &weight_gradient * config.learning_rate;
+            bias -= bias_gradient * config.learning_rate;
+
+            let gradient_norm = weight_gradient.dot(&weight_gradient) + bias_gradient.powi(2);
+            if gradient_norm < 1e-12 {
+                break;
+            }
+        }
+
+        Self {
+            weights: weights.to_vec(),
+            bias,
+            feature_means: means.to_vec(),
+            feature_stds: stds.to_vec(),
+        }
+    }
+
+    pub fn predict_proba(&self, features: &[f64]) -> f64 {
+        let score = self.bias
+            + features
+                .iter()
+                .zip(&self.weights)
+                .zip(self.feature_means.iter().zip(&self.feature_stds))
+                .map(|((value, weight), (mean, std))| weight * (value - mean) / std)
+                .sum::<f64>();
+        sigmoid(score)
+    }
+}
+
+fn sigmoid(score: f64) -> f64 {
+    1.0 / (1.0 + (-score).exp())
+}
+
+/// Held-out predictions grouped by predicted probability
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalibrationBin {
+    pub lower: f64,
+    pub upper: f64,
+    pub count: usize,
+    pub mean_predicted: f64,
+    pub observed_rate: f64,
+}
+
+/// Quality of a model on held-out payments
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvaluationMetrics {
+    pub samples: usize,
+    /// Share of successful payments
+    pub base_rate: f64,
+    /// Area under the ROC curve; `None` if the holdout has a single class
+    pub auc: Option<f64>,
+    pub log_loss: f64,
+    pub brier_score: f64,
+    /// Accuracy at a 0.5 threshold
+    pub accuracy: f64,
+    /// Count-weighted mean gap between predicted and observed success per bin
+    pub expected_calibration_error: f64,
+    pub calibration: Vec<CalibrationBin>,
 }
 
-impl SimpleMLModel {
-    pub fn new() -> Self {
-        // Simple linear model weights (trained offline)
+const CALIBRATION_BINS: usize = 10;
+
+impl EvaluationMetrics {
+    pub fn compute(probabilities: &[f64], labels: &[bool]) -> Self {
+        let n = probabilities.len().max(1) as f64;
+        let mut log_loss = 0.0;
+        let mut brier_score = 0.0;
+        let mut correct = 0usize;
+        for (&p, &label) in probabilities.iter().zip(labels) {
+            let y = if label { 1.0 } else { 0.0 };
+            let clamped = p.clamp(1e-12, 1.0 - 1e-12);
+            log_loss -= y * clamped.ln() + (1.0 - y) * (1.0 - clamped).ln();
+            brier_score += (p - y).powi(2);
+            if (p >= 0.5) == label {
+                correct += 1;
+            }
+        }
+
+        let calibration = calibration_bins(probabilities, labels, CALIBRATION_BINS);
+        let expected_calibration_error = calibration
+            .iter()
+            .map(|bin| bin.count as f64 * (bin.mean_predicted - bin.observed_rate).abs())
+            .sum::<f64>()
+            / n;
+
         Self {
-            weights: vec![0.1, 0.3, 0.05, 0.02, 0.4, 0.6], // 6 features
-            bias: 0.2,
-            version: "1.0.0".to_string(),
+            samples: probabilities.len(),
+            base_rate: labels.iter().filter(|&&l| l).count() as f64 / n,
+            auc: roc_auc(probabilities, labels),
+            log_loss: log_loss / n,
+            brier_score: brier_score / n,
+            accuracy: correct as f64 / n,
+            expected_calibration_error,
+            calibration,
+        }
+    }
+
+    /// Calibration bin containing probability `p`
+    pub fn bin_for(&self, p: f64) -> Option<&CalibrationBin> {
+        self.calibration
+            .iter()
+            .find(|bin| p >= bin.lower && (p < bin.upper || bin.upper >= 1.0))
+    }
+}
+
+/// Area under the ROC curve: the probability that a random successful payment is scored
+/// above a random failed one, with ties counting half
+pub fn roc_auc(scores: &[f64], labels: &[bool]) -> Option<f64> {
+    let positives = labels.iter().filter(|&&l| l).count();
+    let negatives = labels.len() - positives;
+    if positives == 0 || negatives == 0 {
+        return None;
+    }
+
+    let mut ranked: Vec<(f64, bool)> = scores.iter().copied().zip(labels.iter().copied()).collect();
+    ranked.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    // Sum of 1-based ranks of the positives, averaging ranks across ties
+    let mut positive_rank_sum = 0.0;
+    let mut i = 0;
+    while i < ranked.len() {
+        let mut j = i;
+        while j + 1 < ranked.len() && ranked[j + 1].0 == ranked[i].0 {
+            j += 1;
         }
+        let average_rank = (i + j) as f64 / 2.0 + 1.0;
+        let tied_positives = ranked[i..=j].iter().filter(|(_, l)| *l).count();
+        positive_rank_sum += average_rank * tied_positives as f64;
+        i = j + 1;
     }
 
-    pub fn predict(&self, features: PredictionFeatures) -> PredictionResult {
-        let input = vec![
-            features.corridor_hash,
-            features.amount_usd,
-            features.hour_of_day,
-            features.day_of_week,
-            features.liquidity_depth,
-            features.recent_success_rate,
-        ];
+    let positives = positives as f64;
+    Some((positive_rank_sum - positives * (positives + 1.0) / 2.0) / (positives * negatives as f64))
+}
+
+/// Groups predictions into `bins` equal-width probability bins
+pub fn calibration_bins(
+    probabilities: &[f64],
+    labels: &[bool],
+    bins: usize,
+) -> Vec<CalibrationBin> {
+    let mut sums = vec![(0usize, 0.0, 0usize); bins];
+    for (&p, &label) in probabilities.iter().zip(labels) {
+        let index = ((p * bins as f64) as usize).min(bins - 1);
+        sums[index].0 += 1;
+        sums[index].1 += p;
+        sums[index].2 += usize::from(label);
+    }
 
-        let mut score = self.bias;
-        for (i, &weight) in self.weights.iter().enumerate() {
-            score += weight * input[i];
+    sums.into_iter()
+        .enumerate()
+        .map(|(i, (count, predicted, successes))| CalibrationBin {
+            lower: i as f64 / bins as f64,
+            upper: (i + 1) as f64 / bins as f64,
+            count,
+            mean_predicted: if count > 0 {
+                predicted / count as f64
+            } else {
+                0.0
+            },
+            observed_rate: if count > 0 {
+                successes as f64 / count as f64
+            } else {
+                0.0
+            },
+        })
+        .collect()
+}
+
+/// A trained, evaluated payment success model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentSuccessModel {
+    /// Assigned when the model is persisted; 0 until then
+    pub version: i64,
+    pub trained_at: DateTime<Utc>,
+    pub feature_names: Vec<String>,
+    pub training_samples: usize,
+    pub model: LogisticRegression,
+    pub metrics: EvaluationMetrics,
+}
+
+impl PaymentSuccessModel {
+    /// Train on `examples` (sorted oldest first), holding out the most recent
+    /// `holdout_fraction` for evaluation
+    pub fn train(examples: &[TrainingExample], config: &TrainingConfig) -> Result<Self> {
+        if examples.len() < config.min_samples {
+            bail!(
+                "need at least {} labelled payments to train, have {}",
+                config.min_samples,
+                examples.len()
+            );
+        }
+        let successes = examples.iter().filter(|e| e.successful).count();
+        if successes == 0 || successes == examples.len() {
+            bail!("training payments are all successful or all failed; nothing to learn");
         }
 
-        // Sigmoid activation
-        let prob = 1.0 / (1.0 + (-score).exp());
+        let holdout = ((examples.len() as f64 * config.holdout_fraction) as usize)
+            .clamp(1, examples.len() - 1);
+        let (train, test) = examples.split_at(examples.len() - holdout);
+
+        let (x, y) = to_matrix(train);
+        let model = LogisticRegression::fit(&x, &y, config);
+
+        let probabilities: Vec<f64> = test
+            .iter()
+            .map(|e| model.predict_proba(&e.features.to_vec()))
+            .collect();
+        let labels: Vec<bool> = test.iter().map(|e| e.successful).collect();
+
+        Ok(Self {
+            version: 0,
+            trained_at: Utc::now(),
+            feature_names: PredictionFeatures::NAMES
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            training_samples: train.len(),
+            model,
+            metrics: EvaluationMetrics::compute(&probabilities, &labels),
+        })
+    }
+
+    pub fn version_label(&self) -> String {
+        format!("{}-v{}", MODEL_NAME, self.version)
+    }
+
+    /// 95% interval for the success rate of payments scored `probability`, from how many
+    /// holdout payments fell in the same calibration bin
+    pub fn confidence_interval(&self, probability: f64) -> (f64, f64) {
+        let samples = self
+            .metrics
+            .bin_for(probability)
+            .map(|bin| bin.count)
+            .unwrap_or(0)
+            .max(1) as f64;
+        let half_width = 1.96 * (probability * (1.0 - probability) / samples).sqrt();
+        (
+            (probability - half_width).max(0.0),
+            (probability + half_width).min(1.0),
+        )
+    }
+
+    pub fn predict(&self, features: &PredictionFeatures) -> PredictionResult {
+        let probability = self.model.predict_proba(&features.to_vec());
+
+        // How well predictions like this one matched reality on the holdout
+        let calibration_gap = match self.metrics.bin_for(probability) {
+            Some(bin) if bin.count >= 30 => (bin.mean_predicted - bin.observed_rate).abs(),
+            _ => self.metrics.expected_calibration_error,
+        };
 
         PredictionResult {
-            success_probability: prob,
-            confidence: if prob > 0.7 || prob < 0.3 { 0.9 } else { 0.7 },
-            model_version: self.version.clone(),
+            success_probability: probability as f32,
+            confidence: (1.0 - calibration_gap).clamp(0.0, 1.0) as f32,
+            model_version: self.version_label(),
         }
     }
+}
+
+fn to_matrix(examples: &[TrainingExample]) -> (Array2<f64>, Array1<f64>) {
+    let columns = PredictionFeatures::NAMES.len();
+    let values: Vec<f64> = examples.iter().flat_map(|e| e.features.to_vec()).collect();
+    let x = Array2::from_shape_vec((examples.len(), columns), values)
+        .expect("every feature vector has one value per feature name");
+    let y = examples
+        .iter()
+        .map(|e| if e.successful { 1.0 } else { 0.0 })
+        .collect();
+    (x, y)
+}
+
+/// A historical payment and the outcome of its transaction
+#[derive(Debug, Clone)]
+pub struct LabelledPayment {
+    pub corridor_key: String,
+    pub amount: f64,
+    pub timestamp: DateTime<Utc>,
+    pub successful: bool,
+}
+
+/// Payments only record the delivered asset, so the corridor of a payment is the one
+/// aggregation assigns it: the asset paired with itself
+pub fn payment_corridor_key(asset_code: &str, asset_issuer: &str) -> String {
+    Corridor::new(
+        asset_code.to_string(),
+        asset_issuer.to_string(),
+        asset_code.to_string(),
+        asset_issuer.to_string(),
+    )
+    .to_string_key()
+}
+
+/// Parse an asset given as "native", "XLM" or "CODE:ISSUER" into the code and issuer
+/// payments are stored under
+pub fn parse_asset(asset: &str) -> Option<(String, String)> {
+    let asset = asset.trim();
+    if asset.eq_ignore_ascii_case("native") || asset.eq_ignore_ascii_case("XLM") {
+        return Some(("XLM".to_string(), "native".to_string()));
+    }
+    let (code, issuer) = asset.split_once(':')?;
+    if code.is_empty() || issuer.is_empty() {
+        return None;
+    }
+    Some((code.to_string(), issuer.to_string()))
+}
+
+/// Builds training examples from payments sorted oldest first, computing each payment's
+/// corridor context only from data available before it: the latest hourly bucket that
+/// had closed and the payments within `success_window` before it.
+pub fn build_training_examples(
+    payments: &[LabelledPayment],
+    hourly: &[CorridorRollup],
+    success_window: Duration,
+) -> Vec<TrainingExample> {
+    let mut liquidity: HashMap<&str, Vec<(DateTime<Utc>, f64)>> = HashMap::new();
+    for bucket in hourly {
+        liquidity
+            .entry(bucket.corridor_key.as_str())
+            .or_default()
+            .push((bucket.bucket_start, bucket.liquidity_depth_usd));
+    }
+    for buckets in liquidity.values_mut() {
+        buckets.sort_by_key(|(start, _)| *start);
+    }
+
+    let mut recent: HashMap<&str, VecDeque<(DateTime<Utc>, bool)>> = HashMap::new();
+    let mut examples = Vec::with_capacity(payments.len());
+    for payment in payments {
+        let window = recent.entry(payment.corridor_key.as_str()).or_default();
+        while window
+            .front()
+            .is_some_and(|(at, _)| *at < payment.timestamp - success_window)
+        {
+            window.pop_front();
+        }
+
+        let liquidity_depth_usd = liquidity
+            .get(payment.corridor_key.as_str())
+            .and_then(|buckets| {
+                let closed = buckets
+                    .partition_point(|(start, _)| *start + Duration::hours(1) <= payment.timestamp);
+                closed.checked_sub(1).map(|i| buckets[i].1)
+            })
+            .unwrap_or(0.0);
+        let context = CorridorContext {
+            liquidity_depth_usd,
+            recent_payments: window.len() as u64,
+            recent_successes: window.iter().filter(|(_, ok)| *ok).count() as u64,
+        };
+
+        examples.push(TrainingExample {
+            features: PredictionFeatures::new(payment.amount, payment.timestamp, &context),
+            successful: payment.successful,
+            timestamp: payment.timestamp,
+        });
+        window.push_back((payment.timestamp, payment.successful));
+    }
+
+    examples
+}
+
+/// Lifecycle of a registered model version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelStatus {
+    /// Registered but not scored
+    Candidate,
+    /// Scored alongside the active model on live traffic without affecting responses
+    Shadow,
+    /// Serving predictions; at most one version per model
+    Active,
+    Retired,
+}
 
-    pub fn train(&mut self, _training_data: &[(Vec<f32>, f32)]) {
-        // Simple gradient descent (placeholder)
-        // In production, this would implement actual training
-        println!("Training model with {} samples", _training_data.len());
+impl ModelStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModelStatus::Candidate => "candidate",
+            ModelStatus::Shadow => "shadow",
+            ModelStatus::Active => "active",
+            ModelStatus::Retired => "retired",
+        }
+    }
+}
+
+impl std::str::FromStr for ModelStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "candidate" => Ok(ModelStatus::Candidate),
+            "shadow" => Ok(ModelStatus::Shadow),
+            "active" => Ok(ModelStatus::Active),
+            "retired" => Ok(ModelStatus::Retired),
+            other => bail!("unknown model status '{}'", other),
+        }
+    }
+}
 
-        // Update version after training
-        self.version = format!("1.0.{}", chrono::Utc::now().timestamp() % 1000);
+impl std::fmt::Display for ModelStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
     }
 }
 
+/// A model version in the registry, without its parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisteredModel {
+    pub model_name: String,
+    pub version: i64,
+    pub status: ModelStatus,
+    pub trained_at: DateTime<Utc>,
+    pub status_changed_at: Option<DateTime<Utc>>,
+    pub training_samples: usize,
+    pub feature_names: Vec<String>,
+    /// Held-out evaluation from training
+    pub metrics: EvaluationMetrics,
+}
+
+/// Score of one model for a logged prediction request
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelScore {
+    pub version: i64,
+    pub role: ModelStatus,
+    pub probability: f64,
+}
+
+/// A served prediction and the id its outcome can later be reported under
+#[derive(Debug, Clone)]
+pub struct LoggedPrediction {
+    pub prediction_id: String,
+    pub result: PredictionResult,
+}
+
+/// How a model version scored on live traffic whose outcome has been observed
+#[derive(Debug, Clone, Serialize)]
+pub struct LiveEvaluation {
+    pub version: i64,
+    pub status: Option<ModelStatus>,
+    pub predictions: usize,
+    pub resolved: usize,
+    /// `None` until at least one outcome has been observed
+    pub metrics: Option<EvaluationMetrics>,
+}
+
+/// Groups logged `(version, probability, outcome)` rows into per-version live metrics
+pub fn evaluate_live_predictions(
+    rows: &[(i64, f64, Option<bool>)],
+    statuses: &HashMap<i64, ModelStatus>,
+) -> Vec<LiveEvaluation> {
+    let mut by_version: std::collections::BTreeMap<i64, (usize, Vec<f64>, Vec<bool>)> =
+        Default::default();
+    for &(version, probability, outcome) in rows {
+        let entry = by_version.entry(version).or_default();
+        entry.0 += 1;
+        if let Some(outcome) = outcome {
+            entry.1.push(probability);
+            entry.2.push(outcome);
+        }
+    }
+
+    by_version
+        .into_iter()
+        .rev()
+        .map(
+            |(version, (predictions, probabilities, outcomes))| LiveEvaluation {
+                version,
+                status: statuses.get(&version).copied(),
+                predictions,
+                resolved: outcomes.len(),
+                metrics: (!outcomes.is_empty())
+                    .then(|| EvaluationMetrics::compute(&probabilities, &outcomes)),
+            },
+        )
+        .collect()
+}
+
+#[derive(Default)]
+struct ServingModels {
+    active: Option<Arc<PaymentSuccessModel>>,
+    shadows: Vec<Arc<PaymentSuccessModel>>,
+}
+
 pub struct MLService {
-    model: SimpleMLModel,
-    #[allow(dead_code)] // Reserved for future ML model training from database
-    db: Database,
+    db: Arc<Database>,
+    config: TrainingConfig,
+    models: RwLock<ServingModels>,
+    predictions: AtomicU64,
 }
 
 impl MLService {
-    pub fn new(db: Database) -> anyhow::Result<Self> {
-        let model = SimpleMLModel::new();
-        Ok(Self { model, db })
+    pub fn new(db: Arc<Database>, config: TrainingConfig) -> Self {
+        Self {
+            db,
+            config,
+            models: RwLock::new(ServingModels::default()),
+            predictions: AtomicU64::new(0),
+        }
     }
 
-    pub async fn train_model(&mut self) -> anyhow::Result<()> {
-        let training_data = self.prepare_training_data().await?;
-        self.model.train(&training_data);
-        Ok(())
+    /// The model currently serving predictions
+    pub fn current_model(&self) -> Option<Arc<PaymentSuccessModel>> {
+        self.models
+            .read()
+            .ok()
+            .and_then(|models| models.active.clone())
     }
 
-    async fn prepare_training_data(&self) -> anyhow::Result<Vec<(Vec<f32>, f32)>> {
-        // Mock training data for now
-        let mut training_data = Vec::new();
+    /// Models scored in shadow alongside the active one
+    pub fn shadow_models(&self) -> Vec<Arc<PaymentSuccessModel>> {
+        self.models
+            .read()
+            .map(|models| models.shadows.clone())
+            .unwrap_or_default()
+    }
 
-        // Generate some sample data
-        for i in 0..1000 {
-            let features = vec![
-                (i % 100) as f32 / 100.0,      // corridor_hash
-                (i % 50) as f32 / 10.0,        // amount (log scale)
-                (i % 24) as f32 / 24.0,        // hour
-                (i % 7) as f32 / 7.0,          // day
-                5.0 + (i % 10) as f32,         // liquidity
-                0.7 + (i % 30) as f32 / 100.0, // success rate
-            ];
+    /// Number of predictions served since startup
+    pub fn total_predictions(&self) -> u64 {
+        self.predictions.load(Ordering::Relaxed)
+    }
 
-            // Simple target: higher success rate = higher probability
-            let target = if features[5] > 0.8 { 1.0 } else { 0.0 };
-            training_data.push((features, target));
+    /// Load the active and shadow versions from the registry. Returns whether an active
+    /// model is being served.
+    pub async fn load_registry(&self) -> Result<bool> {
+        let mut serving = ServingModels::default();
+        for (status, model) in self.db.ml_db().load_serving_models(MODEL_NAME).await? {
+            match status {
+                ModelStatus::Active => serving.active = Some(Arc::new(model)),
+                _ => serving.shadows.push(Arc::new(model)),
+            }
         }
 
-        Ok(training_data)
+        if let Some(active) = &serving.active {
+            tracing::info!(
+                "Serving payment success model {} with {} shadow model(s)",
+                active.version_label(),
+                serving.shadows.len()
+            );
+        }
+        let has_active = serving.active.is_some();
+        if let Ok(mut models) = self.models.write() {
+            *models = serving;
+        }
+        Ok(has_active)
     }
 
-    fn hash_corridor(&self, asset_code: &Option<String>, asset_issuer: &Option<String>) -> f32 {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
+    /// Train a model on recent history and register it as the next version. The first
+    /// model (or every model, with `auto_promote`) becomes active; later ones run in
+    /// shadow until promoted.
+    pub async fn train_model(&self) -> Result<Arc<PaymentSuccessModel>> {
+        let end = Utc::now();
+        let start = end - self.config.lookback;
+        let ml_db = self.db.ml_db();
 
-        let mut hasher = DefaultHasher::new();
-        asset_code.hash(&mut hasher);
-        asset_issuer.hash(&mut hasher);
-        (hasher.finish() % 1000) as f32 / 1000.0
-    }
+        let payments = ml_db
+            .fetch_labelled_payments(start, end, self.config.max_samples)
+            .await?;
+        let hourly = self
+            .db
+            .rollup_db()
+            .fetch_buckets(Resolution::Hour, None, start - Duration::days(1), end)
+            .await?;
 
-    pub async fn predict_payment_success(
-        &self,
-        corridor: &str,
-        amount_usd: f64,
-        timestamp: DateTime<Utc>,
-    ) -> anyhow::Result<PredictionResult> {
-        let parts: Vec<&str> = corridor.split('-').collect();
-        let corridor_hash = self.hash_corridor(
-            &Some(parts.get(0).unwrap_or(&"").to_string()),
-            &Some(parts.get(1).unwrap_or(&"").to_string()),
-        );
+        let config = self.config.clone();
+        let mut model = tokio::task::spawn_blocking(move || {
+            let examples = build_training_examples(&payments, &hourly, config.success_window);
+            PaymentSuccessModel::train(&examples, &config)
+        })
+        .await
+        .context("Model training task panicked")??;
 
-        let liquidity = self
-            .get_corridor_liquidity(corridor)
-            .await
-            .unwrap_or(1000.0);
-        let recent_success = self.get_recent_success_rate(corridor).await.unwrap_or(0.8);
-
-        let features = PredictionFeatures {
-            corridor_hash,
-            amount_usd: amount_usd.log10().max(0.0) as f32,
-            hour_of_day: timestamp.hour() as f32 / 24.0,
-            day_of_week: timestamp.weekday().num_days_from_monday() as f32 / 7.0,
-            liquidity_depth: liquidity.log10() as f32,
-            recent_success_rate: recent_success,
+        model.version = ml_db.save_model(MODEL_NAME, &model).await?;
+        let status = if self.config.auto_promote || self.current_model().is_none() {
+            ModelStatus::Active
+        } else {
+            ModelStatus::Shadow
         };
+        ml_db
+            .set_model_status(MODEL_NAME, model.version, status, Utc::now())
+            .await?;
+        tracing::info!(
+            "Trained payment success model {} on {} payments as {} (holdout AUC {:?}, ECE {:.3})",
+            model.version_label(),
+            model.training_samples,
+            status,
+            model.metrics.auc,
+            model.metrics.expected_calibration_error
+        );
 
-        Ok(self.model.predict(features))
+        self.load_registry().await?;
+        Ok(Arc::new(model))
     }
 
-    async fn get_corridor_liquidity(&self, corridor: &str) -> Option<f64> {
-        // Mock data for now - in production this would query the database
-        Some(1000.0 + (corridor.len() as f64 * 100.0))
+    /// All registered versions, newest first
+    pub async fn list_models(&self) -> Result<Vec<RegisteredModel>> {
+        self.db.ml_db().list_models(MODEL_NAME).await
     }
 
-    async fn get_recent_success_rate(&self, corridor: &str) -> Option<f32> {
-        // Mock data for now - in production this would query the database
-        Some(0.8 + (corridor.len() as f32 * 0.01) % 0.2)
+    /// Move `version` to `status`; promoting to active retires the previously active
+    /// version. Returns `false` if the version does not exist.
+    pub async fn set_model_status(&self, version: i64, status: ModelStatus) -> Result<bool> {
+        let updated = self
+            .db
+            .ml_db()
+            .set_model_status(MODEL_NAME, version, status, Utc::now())
+            .await?;
+        if updated {
+            tracing::info!(
+                "Payment success model {}-v{} is now {}",
+                MODEL_NAME,
+                version,
+                status
+            );
+            self.load_registry().await?;
+        }
+        Ok(updated)
     }
 
-    pub async fn retrain_weekly(&mut self) -> anyhow::Result<()> {
-        println!("Starting weekly model retraining...");
-        self.train_model().await?;
+    /// Record the hash of the transaction submitted for a served prediction. The
+    /// outcome is taken from the ingested transaction by [`Self::resolve_outcomes`],
+    /// never from the caller. Returns `false` if the prediction is unknown or already
+    /// resolved.
+    pub async fn record_transaction(
+        &self,
+        prediction_id: &str,
+        transaction_hash: &str,
+    ) -> Result<bool> {
+        let updated = self
+            .db
+            .ml_db()
+            .record_transaction_hash(prediction_id, transaction_hash)
+            .await?;
+        Ok(updated > 0)
+    }
 
-        println!(
-            "Model retrained successfully. Version: {}",
-            self.model.version
-        );
-        Ok(())
+    /// Label predictions whose reported transaction has since been ingested
+    pub async fn resolve_outcomes(&self) -> Result<u64> {
+        self.db.ml_db().resolve_outcomes(Utc::now()).await
+    }
+
+    /// Live metrics of every version that scored predictions since `since`. Shadow and
+    /// active versions score the same requests, so their metrics are directly comparable
+    /// over the period they ran together.
+    pub async fn live_evaluation(&self, since: DateTime<Utc>) -> Result<Vec<LiveEvaluation>> {
+        let ml_db = self.db.ml_db();
+        let statuses: HashMap<i64, ModelStatus> = ml_db
+            .list_models(MODEL_NAME)
+            .await?
+            .into_iter()
+            .map(|model| (model.version, model.status))
+            .collect();
+        let rows = ml_db.fetch_logged_predictions(MODEL_NAME, since).await?;
+        Ok(evaluate_live_predictions(&rows, &statuses))
+    }
+
+    /// Current state of the corridor of `asset_code`/`asset_issuer` payments, as the
+    /// features of a payment made at `at` would see it
+    pub async fn corridor_context(
+        &self,
+        asset_code: &str,
+        asset_issuer: &str,
+        at: DateTime<Utc>,
+    ) -> Result<CorridorContext> {
+        let corridor_key = payment_corridor_key(asset_code, asset_issuer);
+        let buckets = self
+            .db
+            .rollup_db()
+            .fetch_buckets(
+                Resolution::Hour,
+                Some(&corridor_key),
+                at - Duration::hours(48),
+                at,
+            )
+            .await?;
+        let (recent_payments, recent_successes) = self
+            .db
+            .ml_db()
+            .count_recent_outcomes(
+                asset_code,
+                asset_issuer,
+                at - self.config.success_window,
+                at,
+            )
+            .await?;
+
+        Ok(CorridorContext {
+            liquidity_depth_usd: buckets
+                .last()
+                .map(|bucket| bucket.liquidity_depth_usd)
+                .unwrap_or(0.0),
+            recent_payments,
+            recent_successes,
+        })
+    }
+
+    /// Probability that a payment of `amount_usd` delivering `asset_code`/`asset_issuer`
+    /// at `timestamp` succeeds, from the active model. Shadow models score the same
+    /// features, and every score is logged for later comparison against the outcome.
+    /// Fails if no model is active.
+    pub async fn predict_payment_success(
+        &self,
+        asset_code: &str,
+        asset_issuer: &str,
+        amount_usd: f64,
+        timestamp: DateTime<Utc>,
+    ) -> Result<LoggedPrediction> {
+        let Some(model) = self.current_model() else {
+            bail!("no payment success model has been trained yet");
+        };
+
+        let context = self
+            .corridor_context(asset_code, asset_issuer, Utc::now())
+            .await?;
+        let features = PredictionFeatures::new(amount_usd, timestamp, &context);
+        let result = model.predict(&features);
+        self.predictions.fetch_add(1, Ordering::Relaxed);
+
+        let mut scores = vec![ModelScore {
+            version: model.version,
+            role: ModelStatus::Active,
+            probability: f64::from(result.success_probability),
+        }];
+        scores.extend(self.shadow_models().iter().map(|shadow| ModelScore {
+            version: shadow.version,
+            role: ModelStatus::Shadow,
+            probability: shadow.model.predict_proba(&features.to_vec()),
+        }));
+
+        let prediction_id = uuid::Uuid::new_v4().to_string();
+        // Logging is best effort; a prediction is still served if it fails
+        if let Err(e) = self
+            .db
+            .ml_db()
+            .log_prediction(
+                &prediction_id,
+                MODEL_NAME,
+                asset_code,
+                asset_issuer,
+                amount_usd,
+                &features,
+                &scores,
+                Utc::now(),
+            )
+            .await
+        {
+            tracing::warn!("Failed to log prediction {}: {}", prediction_id, e);
+        }
+
+        Ok(LoggedPrediction {
+            prediction_id,
+            result,
+        })
     }
 }
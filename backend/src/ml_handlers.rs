@@ -1,13 +1,21 @@
-use crate::ml::{MLService, PredictionResult};
-use axum::{extract::Query, http::StatusCode, response::Json, Extension};
-use chrono::{DateTime, Utc};
+use crate::ml::{
+    parse_asset, EvaluationMetrics, LiveEvaluation, MLService, ModelStatus, PredictionResult,
+    RegisteredModel,
+};
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    response::Json,
+    Extension,
+};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tokio::sync::RwLock;
 
 #[derive(Debug, Deserialize)]
 pub struct PredictionQuery {
-    pub corridor: String,
+    /// Delivered asset, "native" or "CODE:ISSUER"
+    pub asset: String,
     pub amount_usd: f64,
     #[serde(default = "default_timestamp")]
     pub timestamp: DateTime<Utc>,
@@ -24,6 +32,9 @@ pub struct PredictionResponse {
     pub model_version: String,
     pub risk_level: String,
     pub recommendation: String,
+    /// Id under which the payment's outcome can be reported
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prediction_id: Option<String>,
 }
 
 impl From<PredictionResult> for PredictionResponse {
@@ -46,54 +57,168 @@ impl From<PredictionResult> for PredictionResponse {
             model_version: result.model_version,
             risk_level: risk_level.to_string(),
             recommendation: recommendation.to_string(),
+            prediction_id: None,
         }
     }
 }
 
 pub async fn predict_payment_success(
     Query(query): Query<PredictionQuery>,
-    Extension(ml_service): Extension<Arc<RwLock<MLService>>>,
+    Extension(ml_service): Extension<Arc<MLService>>,
 ) -> Result<Json<PredictionResponse>, StatusCode> {
-    let service = ml_service.read().await;
+    let (asset_code, asset_issuer) = parse_asset(&query.asset).ok_or(StatusCode::BAD_REQUEST)?;
+    if ml_service.current_model().is_none() {
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    }
 
-    match service
-        .predict_payment_success(&query.corridor, query.amount_usd, query.timestamp)
+    match ml_service
+        .predict_payment_success(
+            &asset_code,
+            &asset_issuer,
+            query.amount_usd,
+            query.timestamp,
+        )
         .await
     {
-        Ok(result) => Ok(Json(result.into())),
+        Ok(prediction) => {
+            let mut response = PredictionResponse::from(prediction.result);
+            response.prediction_id = Some(prediction.prediction_id);
+            Ok(Json(response))
+        }
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
 }
 
 #[derive(Debug, Serialize)]
 pub struct ModelStatusResponse {
-    pub version: String,
-    pub last_trained: String,
-    pub accuracy: f32,
+    /// `None` until a model has been trained
+    pub version: Option<String>,
+    pub last_trained: Option<DateTime<Utc>>,
+    pub training_samples: usize,
+    /// Held-out evaluation of the served model
+    pub metrics: Option<EvaluationMetrics>,
+    /// Versions scored in shadow alongside the served one
+    pub shadow_versions: Vec<String>,
     pub total_predictions: u64,
 }
 
 pub async fn get_model_status(
-    Extension(_ml_service): Extension<Arc<RwLock<MLService>>>,
+    Extension(ml_service): Extension<Arc<MLService>>,
 ) -> Json<ModelStatusResponse> {
+    let model = ml_service.current_model();
     Json(ModelStatusResponse {
-        version: "1.0.0".to_string(),
-        last_trained: Utc::now().format("%Y-%m-%d").to_string(),
-        accuracy: 0.87,
-        total_predictions: 1000,
+        version: model.as_ref().map(|m| m.version_label()),
+        last_trained: model.as_ref().map(|m| m.trained_at),
+        training_samples: model.as_ref().map_or(0, |m| m.training_samples),
+        metrics: model.as_ref().map(|m| m.metrics.clone()),
+        shadow_versions: ml_service
+            .shadow_models()
+            .iter()
+            .map(|m| m.version_label())
+            .collect(),
+        total_predictions: ml_service.total_predictions(),
     })
 }
 
 pub async fn retrain_model(
-    Extension(ml_service): Extension<Arc<RwLock<MLService>>>,
+    Extension(ml_service): Extension<Arc<MLService>>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
-    let mut service = ml_service.write().await;
-
-    match service.retrain_weekly().await {
-        Ok(_) => Ok(Json(serde_json::json!({
+    match ml_service.train_model().await {
+        Ok(model) => Ok(Json(serde_json::json!({
             "status": "success",
-            "message": "Model retrained successfully"
+            "message": "Model retrained successfully",
+            "version": model.version_label(),
+            "metrics": model.metrics,
         }))),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Err(e) => {
+            tracing::error!("Model retraining failed: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn list_models(
+    Extension(ml_service): Extension<Arc<MLService>>,
+) -> Result<Json<Vec<RegisteredModel>>, StatusCode> {
+    ml_service.list_models().await.map(Json).map_err(|e| {
+        tracing::error!("Failed to list models: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LiveEvaluationQuery {
+    /// Days of logged predictions to evaluate (default 7, max 90)
+    pub days: Option<i64>,
+}
+
+/// Live metrics of the active and shadow versions on predictions with observed outcomes
+pub async fn get_live_evaluation(
+    Query(query): Query<LiveEvaluationQuery>,
+    Extension(ml_service): Extension<Arc<MLService>>,
+) -> Result<Json<Vec<LiveEvaluation>>, StatusCode> {
+    let days = query.days.unwrap_or(7).clamp(1, 90);
+    ml_service
+        .live_evaluation(Utc::now() - Duration::days(days))
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("Failed to evaluate live predictions: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModelStatusRequest {
+    pub status: ModelStatus,
+}
+
+/// Move a model version to `candidate`, `shadow`, `active` (promote) or `retired`
+pub async fn set_model_status(
+    Path(version): Path<i64>,
+    Extension(ml_service): Extension<Arc<MLService>>,
+    Json(request): Json<ModelStatusRequest>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    match ml_service.set_model_status(version, request.status).await {
+        Ok(true) => Ok(Json(serde_json::json!({
+            "version": version,
+            "status": request.status,
+        }))),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to update model status: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PredictionOutcomeRequest {
+    /// Hash of the submitted transaction. Its result is read from ledger ingestion
+    /// once the transaction is ingested.
+    pub transaction_hash: String,
+}
+
+/// Report the transaction submitted for the payment a prediction was served for
+pub async fn record_prediction_outcome(
+    Path(prediction_id): Path<String>,
+    Extension(ml_service): Extension<Arc<MLService>>,
+    Json(request): Json<PredictionOutcomeRequest>,
+) -> Result<StatusCode, StatusCode> {
+    let transaction_hash = request.transaction_hash.to_ascii_lowercase();
+    if transaction_hash.len() != 64 || !transaction_hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    match ml_service
+        .record_transaction(&prediction_id, &transaction_hash)
+        .await
+    {
+        Ok(true) => Ok(StatusCode::ACCEPTED),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to record prediction transaction: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
     }
 }
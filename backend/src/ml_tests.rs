@@ -1,21 +1,32 @@
-use crate::ml::PredictionFeatures;
+use crate::ml::{
+    build_training_examples, evaluate_live_predictions, parse_asset, roc_auc, CorridorContext,
+    LabelledPayment, ModelStatus, PaymentSuccessModel, PredictionFeatures, TrainingConfig,
+    TrainingExample,
+};
+use chrono::{Duration, TimeZone, Utc};
+use std::collections::HashMap;
 
 #[tokio::test]
 async fn test_ml_prediction() {
-    let features = PredictionFeatures {
-        corridor_hash: 0.5,
-        amount_usd: 2.0,      // log10(100)
-        hour_of_day: 0.5,     // 12 PM
-        day_of_week: 0.3,     // Tuesday
-        liquidity_depth: 3.0, // log10(1000)
-        recent_success_rate: 0.85,
+    let context = CorridorContext {
+        liquidity_depth_usd: 1_000.0,
+        recent_payments: 18,
+        recent_successes: 17,
     };
+    let timestamp = Utc.with_ymd_and_hms(2024, 1, 2, 12, 0, 0).unwrap();
+    let features = PredictionFeatures::new(99.0, timestamp, &context);
 
-    // Test that features are in expected ranges
-    assert!(features.corridor_hash >= 0.0 && features.corridor_hash <= 1.0);
-    assert!(features.hour_of_day >= 0.0 && features.hour_of_day <= 1.0);
-    assert!(features.day_of_week >= 0.0 && features.day_of_week <= 1.0);
-    assert!(features.recent_success_rate >= 0.0 && features.recent_success_rate <= 1.0);
+    assert!((features.amount_usd_log - 2.0).abs() < 1e-9);
+    assert!((features.liquidity_depth_log - 1001f64.log10()).abs() < 1e-9);
+    assert!(features.amount_to_liquidity_log < 0.0);
+    // Noon sits opposite midnight on the hour circle
+    assert!((features.hour_cos + 1.0).abs() < 1e-9);
+    assert!((features.recent_success_rate - 0.9).abs() < 1e-9);
+    assert_eq!(features.to_vec().len(), PredictionFeatures::NAMES.len());
+
+    // No recent payments gives an uninformative success rate
+    let cold = PredictionFeatures::new(10.0, timestamp, &CorridorContext::default());
+    assert!((cold.recent_success_rate - 0.5).abs() < 1e-9);
 }
 
 #[test]
@@ -44,22 +55,241 @@ fn test_prediction_result_risk_levels() {
     assert!(response.recommendation.contains("High risk"));
 }
 
+/// Payments that fail when large relative to the corridor's liquidity
+fn synthetic_examples(count: usize) -> Vec<TrainingExample> {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    (0..count)
+        .map(|i| {
+            // Deterministic spread of sizes over four orders of magnitude
+            let amount = 10f64.powf(((i * 7919) % 1000) as f64 / 250.0);
+            let context = CorridorContext {
+                liquidity_depth_usd: 500.0,
+                recent_payments: 10,
+                recent_successes: 9,
+            };
+            TrainingExample {
+                features: PredictionFeatures::new(
+                    amount,
+                    start + Duration::minutes(i as i64),
+                    &context,
+                ),
+                successful: amount < 500.0 || i % 10 == 0,
+                timestamp: start + Duration::minutes(i as i64),
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn test_training_learns_and_evaluates_on_holdout() {
+    let examples = synthetic_examples(1_000);
+    let config = TrainingConfig::default();
+    let model = PaymentSuccessModel::train(&examples, &config).unwrap();
+
+    assert_eq!(model.training_samples, 800);
+    assert_eq!(model.metrics.samples, 200);
+    assert!(model.metrics.auc.unwrap() > 0.9, "{:?}", model.metrics.auc);
+    assert!(model.metrics.accuracy > 0.85);
+    assert!(model.metrics.expected_calibration_error < 0.1);
+    let binned: usize = model.metrics.calibration.iter().map(|bin| bin.count).sum();
+    assert_eq!(binned, 200);
+
+    let context = CorridorContext {
+        liquidity_depth_usd: 500.0,
+        recent_payments: 10,
+        recent_successes: 9,
+    };
+    let at = Utc::now();
+    let small = model.predict(&PredictionFeatures::new(5.0, at, &context));
+    let large = model.predict(&PredictionFeatures::new(5_000.0, at, &context));
+    assert!(small.success_probability > 0.8);
+    assert!(large.success_probability < 0.3);
+    assert!(small.confidence >= 0.0 && small.confidence <= 1.0);
+    assert_eq!(small.model_version, "payment_success-v0");
+
+    let (low, high) = model.confidence_interval(f64::from(small.success_probability));
+    assert!(low <= f64::from(small.success_probability) && high >= low && high <= 1.0);
+}
+
 #[test]
-fn test_simple_model_prediction() {
-    use crate::ml::SimpleMLModel;
-
-    let model = SimpleMLModel::new();
-    let features = PredictionFeatures {
-        corridor_hash: 0.5,
-        amount_usd: 2.0,
-        hour_of_day: 0.5,
-        day_of_week: 0.3,
-        liquidity_depth: 3.0,
-        recent_success_rate: 0.85,
+fn test_training_rejects_insufficient_or_single_class_data() {
+    let config = TrainingConfig::default();
+    assert!(PaymentSuccessModel::train(&synthetic_examples(50), &config).is_err());
+
+    let all_successful: Vec<TrainingExample> = synthetic_examples(500)
+        .into_iter()
+        .map(|mut example| {
+            example.successful = true;
+            example
+        })
+        .collect();
+    assert!(PaymentSuccessModel::train(&all_successful, &config).is_err());
+}
+
+#[test]
+fn test_roc_auc() {
+    assert_eq!(
+        roc_auc(&[0.1, 0.4, 0.35, 0.8], &[false, false, true, true]),
+        Some(0.75)
+    );
+    assert_eq!(roc_auc(&[0.5, 0.5], &[false, true]), Some(0.5));
+    assert_eq!(roc_auc(&[0.2, 0.9], &[true, true]), None);
+}
+
+#[test]
+fn test_training_examples_only_use_prior_data() {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+    let payment = |minutes: i64, successful: bool| LabelledPayment {
+        corridor_key: "USDC:GA->USDC:GA".to_string(),
+        amount: 100.0,
+        timestamp: start + Duration::minutes(minutes),
+        successful,
     };
+    let payments = vec![payment(0, false), payment(30, true), payment(90, true)];
+
+    let examples = build_training_examples(&payments, &[], Duration::hours(1));
+    // First payment has no history; the second sees one failure; the third sees only
+    // the success within the last hour
+    assert!((examples[0].features.recent_success_rate - 0.5).abs() < 1e-9);
+    assert!((examples[1].features.recent_success_rate - 1.0 / 3.0).abs() < 1e-9);
+    assert!((examples[2].features.recent_success_rate - 2.0 / 3.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_parse_asset() {
+    assert_eq!(
+        parse_asset("native"),
+        Some(("XLM".to_string(), "native".to_string()))
+    );
+    assert_eq!(
+        parse_asset("USDC:GA5Z"),
+        Some(("USDC".to_string(), "GA5Z".to_string()))
+    );
+    assert_eq!(parse_asset("USDC"), None);
+}
+
+#[test]
+fn test_live_evaluation_groups_resolved_predictions_by_version() {
+    let rows = vec![
+        (1, 0.9, Some(true)),
+        (1, 0.2, Some(false)),
+        (1, 0.7, None),
+        (2, 0.6, Some(true)),
+        (2, 0.6, Some(false)),
+        (3, 0.5, None),
+    ];
+    let statuses = HashMap::from([(1, ModelStatus::Active), (2, ModelStatus::Shadow)]);
+
+    let evaluations = evaluate_live_predictions(&rows, &statuses);
+    let versions: Vec<i64> = evaluations.iter().map(|e| e.version).collect();
+    assert_eq!(versions, vec![3, 2, 1]);
+
+    let unresolved = &evaluations[0];
+    assert_eq!((unresolved.predictions, unresolved.resolved), (1, 0));
+    assert!(unresolved.metrics.is_none());
+    assert_eq!(unresolved.status, None);
+
+    let shadow = &evaluations[1];
+    assert_eq!(shadow.status, Some(ModelStatus::Shadow));
+    assert_eq!(shadow.metrics.as_ref().unwrap().auc, Some(0.5));
+
+    let active = &evaluations[2];
+    assert_eq!((active.predictions, active.resolved), (3, 2));
+    assert_eq!(active.metrics.as_ref().unwrap().auc, Some(1.0));
+}
+
+#[test]
+fn test_model_status_round_trip() {
+    for status in [
+        ModelStatus::Candidate,
+        ModelStatus::Shadow,
+        ModelStatus::Active,
+        ModelStatus::Retired,
+    ] {
+        assert_eq!(status.as_str().parse::<ModelStatus>().unwrap(), status);
+    }
+    assert!("serving".parse::<ModelStatus>().is_err());
+}
+
+/// Successful payments as Horizon returns them, and failed payment operations as ledger
+/// ingestion stores them: large payments fail
+#[tokio::test]
+async fn test_training_on_failed_ledger_payments() {
+    use crate::db::ml::MlDb;
+
+    let pool = crate::test_support::migrated_pool().await;
 
-    let result = model.predict(features);
-    assert!(result.success_probability >= 0.0 && result.success_probability <= 1.0);
-    assert!(result.confidence >= 0.0 && result.confidence <= 1.0);
-    assert_eq!(result.model_version, "1.0.0");
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    for i in 0..400i64 {
+        let at = start + Duration::minutes(i);
+        let hash = format!("tx-{}", i);
+        if i % 4 == 0 {
+            sqlx::query(
+                r#"
+                INSERT INTO ledgers (sequence, hash, close_time) VALUES ($1, $2, $3);
+                INSERT INTO transactions (hash, ledger_sequence, successful) VALUES ($2, $1, 0);
+                INSERT INTO ledger_payments (ledger_sequence, transaction_hash, operation_type, asset_code, asset_issuer, amount)
+                VALUES ($1, $2, 'payment', 'USDC', 'GISSUER', $4);
+                "#,
+            )
+            .bind(i)
+            .bind(&hash)
+            .bind(at)
+            .bind(format!("{}.0000000", 5_000 + i))
+            .execute(&pool)
+            .await
+            .unwrap();
+        } else {
+            sqlx::query(
+                r#"
+                INSERT INTO payments (id, transaction_hash, source_account, destination_account, asset_type, asset_code, asset_issuer, amount, created_at)
+                VALUES ($1, $1, 'GSRC', 'GDST', 'credit_alphanum4', 'USDC', 'GISSUER', $2, $3)
+                "#,
+            )
+            .bind(&hash)
+            .bind(10.0 + (i % 50) as f64)
+            .bind(at)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+    }
+
+    let ml_db = MlDb::new(pool.into());
+    let end = start + Duration::hours(8);
+    let payments = ml_db
+        .fetch_labelled_payments(start, end, 1_000)
+        .await
+        .unwrap();
+    assert_eq!(payments.len(), 400);
+    assert_eq!(payments.iter().filter(|p| !p.successful).count(), 100);
+    assert!(payments
+        .windows(2)
+        .all(|w| w[0].timestamp <= w[1].timestamp));
+    assert_eq!(
+        ml_db
+            .count_recent_outcomes("USDC", "GISSUER", start, start + Duration::minutes(8))
+            .await
+            .unwrap(),
+        (8, 6)
+    );
+
+    // The most recent payments are kept when over the limit
+    let recent = ml_db.fetch_labelled_payments(start, end, 10).await.unwrap();
+    assert_eq!(recent.len(), 10);
+    assert_eq!(recent[0].timestamp, start + Duration::minutes(390));
+
+    let examples = build_training_examples(&payments, &[], Duration::hours(1));
+    let model = PaymentSuccessModel::train(&examples, &TrainingConfig::default()).unwrap();
+    assert!(model.metrics.auc.unwrap() > 0.9, "{:?}", model.metrics.auc);
+
+    let context = CorridorContext {
+        recent_payments: 10,
+        recent_successes: 7,
+        ..CorridorContext::default()
+    };
+    let at = start + Duration::hours(9);
+    let small = model.predict(&PredictionFeatures::new(20.0, at, &context));
+    let large = model.predict(&PredictionFeatures::new(5_000.0, at, &context));
+    assert!(small.success_probability > large.success_probability);
 }
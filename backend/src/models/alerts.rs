@@ -7,7 +7,7 @@ pub struct AlertRule {
     pub user_id: String,
     pub corridor_id: Option<String>,
     pub metric_type: String, // e.g., "success_rate", "latency", "liquidity"
-    pub condition: String,   // e.g., "above", "below", "equals"
+    pub condition: String,   // e.g., "above", "below", "equals", "anomaly" (threshold is a z-score)
     pub threshold: f64,
     pub notify_email: bool,
     pub notify_webhook: bool,
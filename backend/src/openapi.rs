@@ -38,6 +38,8 @@ use utoipa::OpenApi;
             crate::api::corridors_cached::SuccessRateDataPoint,
             crate::api::corridors_cached::LatencyDataPoint,
             crate::api::corridors_cached::LiquidityDataPoint,
+            crate::api::corridors_cached::CorridorMarketDepth,
+            crate::api::corridors_cached::MarketDepthDataPoint,
             crate::api::price_feed::PriceResponse,
             crate::api::price_feed::PricesResponse,
             crate::api::price_feed::ConvertResponse,
@@ -45,7 +47,11 @@ use utoipa::OpenApi;
             crate::api::cost_calculator::PaymentRoute,
             crate::api::cost_calculator::CostCalculationRequest,
             crate::api::cost_calculator::RouteCostBreakdown,
+            crate::api::cost_calculator::RouteHop,
             crate::api::cost_calculator::RouteEstimate,
+            crate::api::cost_calculator::HorizonQuote,
+            crate::api::cost_calculator::HorizonComparison,
+            crate::api::cost_calculator::QuoteSource,
             crate::api::cost_calculator::CostCalculationResponse,
             crate::api::cost_calculator::ErrorResponse,
         )
@@ -11,6 +11,7 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 
 use crate::models::api_key::hash_api_key;
+use crate::with_pool;
 
 /// Rate limit configuration for an endpoint
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -90,7 +91,7 @@ pub struct RateLimiter {
     redis_connection: Arc<RwLock<Option<MultiplexedConnection>>>,
     endpoint_configs: Arc<RwLock<HashMap<String, RateLimitConfig>>>,
     fallback_memory_store: Arc<RwLock<HashMap<String, (u32, i64)>>>,
-    db_pool: Option<sqlx::SqlitePool>,
+    db_pool: Option<crate::database::DbPool>,
 }
 
 impl RateLimiter {
@@ -98,7 +99,7 @@ impl RateLimiter {
         Self::new_with_db(None).await
     }
 
-    pub async fn new_with_db(db_pool: Option<sqlx::SqlitePool>) -> anyhow::Result<Self> {
+    pub async fn new_with_db(db_pool: Option<crate::database::DbPool>) -> anyhow::Result<Self> {
         let redis_url =
             std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
 
@@ -168,27 +169,34 @@ impl RateLimiter {
     /// Get API key from database by hash
     async fn get_api_key_by_hash(
         &self,
-        pool: &sqlx::SqlitePool,
+        pool: &crate::database::DbPool,
         key_hash: &str,
     ) -> Result<Option<crate::models::api_key::ApiKey>, sqlx::Error> {
-        sqlx::query_as::<_, crate::models::api_key::ApiKey>(
-            "SELECT * FROM api_keys WHERE key_hash = ? AND status = 'active' AND (expires_at IS NULL OR expires_at > datetime('now'))"
-        )
-        .bind(key_hash)
-        .fetch_optional(pool)
-        .await
+        with_pool!(pool, |pool| {
+            sqlx::query_as::<_, crate::models::api_key::ApiKey>(
+                "SELECT * FROM api_keys WHERE key_hash = $1 AND status = 'active' AND (expires_at IS NULL OR expires_at > $2)"
+            )
+            .bind(key_hash)
+            .bind(chrono::Utc::now().to_rfc3339())
+            .fetch_optional(pool)
+            .await
+        })
     }
 
     /// Update API key last_used_at timestamp
     async fn update_api_key_last_used(
         &self,
-        pool: &sqlx::SqlitePool,
+        pool: &crate::database::DbPool,
         key_id: &str,
     ) -> Result<(), sqlx::Error> {
-        sqlx::query("UPDATE api_keys SET last_used_at = datetime('now') WHERE id = ?")
-            .bind(key_id)
-            .execute(pool)
-            .await?;
+        with_pool!(pool, |pool| {
+            sqlx::query("UPDATE api_keys SET last_used_at = $1 WHERE id = $2")
+                .bind(chrono::Utc::now().to_rfc3339())
+                .bind(key_id)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+        })?;
         Ok(())
     }
 
@@ -3,10 +3,11 @@
 //! Provides checkpoint functionality for saving and resuming replay progress.
 //! Checkpoints enable recovery from failures and allow pausing/resuming replays.
 
+use crate::database::DbPool;
+use crate::with_pool;
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::SqlitePool;
 use std::collections::HashMap;
 use tracing::{debug, info};
 
@@ -68,12 +69,12 @@ impl Checkpoint {
 
 /// Manages checkpoint storage and retrieval
 pub struct CheckpointManager {
-    pool: SqlitePool,
+    pool: DbPool,
 }
 
 impl CheckpointManager {
     /// Create a new checkpoint manager
-    pub fn new(pool: SqlitePool) -> Self {
+    pub fn new(pool: DbPool) -> Self {
         Self { pool }
     }
 
@@ -87,31 +88,34 @@ impl CheckpointManager {
         let metadata_json = serde_json::to_string(&checkpoint.metadata)?;
         let state_json = serde_json::to_string(&checkpoint.state_snapshot)?;
 
-        sqlx::query(
-            r#"
-            INSERT INTO replay_checkpoints (
-                id, session_id, last_ledger, events_processed, events_failed,
-                state_snapshot, metadata, created_at
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO replay_checkpoints (
+                    id, session_id, last_ledger, events_processed, events_failed,
+                    state_snapshot, metadata, created_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT (id) DO UPDATE SET
+                    last_ledger = EXCLUDED.last_ledger,
+                    events_processed = EXCLUDED.events_processed,
+                    events_failed = EXCLUDED.events_failed,
+                    state_snapshot = EXCLUDED.state_snapshot,
+                    metadata = EXCLUDED.metadata
+                "#,
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            ON CONFLICT (id) DO UPDATE SET
-                last_ledger = EXCLUDED.last_ledger,
-                events_processed = EXCLUDED.events_processed,
-                events_failed = EXCLUDED.events_failed,
-                state_snapshot = EXCLUDED.state_snapshot,
-                metadata = EXCLUDED.metadata
-            "#,
-        )
-        .bind(&checkpoint.id)
-        .bind(&checkpoint.session_id)
-        .bind(checkpoint.last_ledger as i64)
-        .bind(checkpoint.events_processed as i64)
-        .bind(checkpoint.events_failed as i64)
-        .bind(&state_json)
-        .bind(&metadata_json)
-        .bind(checkpoint.created_at)
-        .execute(&self.pool)
-        .await
+            .bind(&checkpoint.id)
+            .bind(&checkpoint.session_id)
+            .bind(checkpoint.last_ledger as i64)
+            .bind(checkpoint.events_processed as i64)
+            .bind(checkpoint.events_failed as i64)
+            .bind(&state_json)
+            .bind(&metadata_json)
+            .bind(checkpoint.created_at)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .context("Failed to save checkpoint")?;
 
         debug!("Checkpoint {} saved successfully", checkpoint.id);
@@ -123,17 +127,19 @@ impl CheckpointManager {
         debug!("Loading checkpoint {}", checkpoint_id);
 
         let row: Option<(String, String, i64, i64, i64, String, String, DateTime<Utc>)> =
-            sqlx::query_as(
-                r#"
-            SELECT id, session_id, last_ledger, events_processed, events_failed,
-                   state_snapshot, metadata, created_at
-            FROM replay_checkpoints
-            WHERE id = $1
-            "#,
-            )
-            .bind(checkpoint_id)
-            .fetch_optional(&self.pool)
-            .await
+            with_pool!(&self.pool, |pool| {
+                sqlx::query_as(
+                    r#"
+                SELECT id, session_id, last_ledger, events_processed, events_failed,
+                       state_snapshot, metadata, created_at
+                FROM replay_checkpoints
+                WHERE id = $1
+                "#,
+                )
+                .bind(checkpoint_id)
+                .fetch_optional(pool)
+                .await
+            })
             .context("Failed to load checkpoint")?;
 
         match row {
@@ -170,19 +176,21 @@ impl CheckpointManager {
         debug!("Getting latest checkpoint for session {}", session_id);
 
         let row: Option<(String, String, i64, i64, i64, String, String, DateTime<Utc>)> =
-            sqlx::query_as(
-                r#"
-            SELECT id, session_id, last_ledger, events_processed, events_failed,
-                   state_snapshot, metadata, created_at
-            FROM replay_checkpoints
-            WHERE session_id = $1
-            ORDER BY created_at DESC
-            LIMIT 1
-            "#,
-            )
-            .bind(session_id)
-            .fetch_optional(&self.pool)
-            .await
+            with_pool!(&self.pool, |pool| {
+                sqlx::query_as(
+                    r#"
+                SELECT id, session_id, last_ledger, events_processed, events_failed,
+                       state_snapshot, metadata, created_at
+                FROM replay_checkpoints
+                WHERE session_id = $1
+                ORDER BY created_at DESC
+                LIMIT 1
+                "#,
+                )
+                .bind(session_id)
+                .fetch_optional(pool)
+                .await
+            })
             .context("Failed to get latest checkpoint")?;
 
         match row {
@@ -219,18 +227,20 @@ impl CheckpointManager {
         debug!("Listing checkpoints for session {}", session_id);
 
         let rows: Vec<(String, String, i64, i64, i64, String, String, DateTime<Utc>)> =
-            sqlx::query_as(
-                r#"
-            SELECT id, session_id, last_ledger, events_processed, events_failed,
-                   state_snapshot, metadata, created_at
-            FROM replay_checkpoints
-            WHERE session_id = $1
-            ORDER BY created_at DESC
-            "#,
-            )
-            .bind(session_id)
-            .fetch_all(&self.pool)
-            .await
+            with_pool!(&self.pool, |pool| {
+                sqlx::query_as(
+                    r#"
+                SELECT id, session_id, last_ledger, events_processed, events_failed,
+                       state_snapshot, metadata, created_at
+                FROM replay_checkpoints
+                WHERE session_id = $1
+                ORDER BY created_at DESC
+                "#,
+                )
+                .bind(session_id)
+                .fetch_all(pool)
+                .await
+            })
             .context("Failed to list checkpoints")?;
 
         let checkpoints = rows
@@ -270,11 +280,14 @@ impl CheckpointManager {
     pub async fn delete(&self, checkpoint_id: &str) -> Result<()> {
         info!("Deleting checkpoint {}", checkpoint_id);
 
-        sqlx::query("DELETE FROM replay_checkpoints WHERE id = $1")
-            .bind(checkpoint_id)
-            .execute(&self.pool)
-            .await
-            .context("Failed to delete checkpoint")?;
+        with_pool!(&self.pool, |pool| {
+            sqlx::query("DELETE FROM replay_checkpoints WHERE id = $1")
+                .bind(checkpoint_id)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+        })
+        .context("Failed to delete checkpoint")?;
 
         Ok(())
     }
@@ -283,11 +296,14 @@ impl CheckpointManager {
     pub async fn delete_for_session(&self, session_id: &str) -> Result<()> {
         info!("Deleting all checkpoints for session {}", session_id);
 
-        sqlx::query("DELETE FROM replay_checkpoints WHERE session_id = $1")
-            .bind(session_id)
-            .execute(&self.pool)
-            .await
-            .context("Failed to delete checkpoints")?;
+        with_pool!(&self.pool, |pool| {
+            sqlx::query("DELETE FROM replay_checkpoints WHERE session_id = $1")
+                .bind(session_id)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+        })
+        .context("Failed to delete checkpoints")?;
 
         Ok(())
     }
@@ -298,13 +314,15 @@ impl CheckpointManager {
 
         let cutoff = Utc::now() - chrono::Duration::days(days);
 
-        let result = sqlx::query("DELETE FROM replay_checkpoints WHERE created_at < $1")
-            .bind(cutoff)
-            .execute(&self.pool)
-            .await
-            .context("Failed to cleanup old checkpoints")?;
+        let deleted = with_pool!(&self.pool, |pool| {
+            sqlx::query("DELETE FROM replay_checkpoints WHERE created_at < $1")
+                .bind(cutoff)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+        })
+        .context("Failed to cleanup old checkpoints")?;
 
-        let deleted = result.rows_affected();
         info!("Deleted {} old checkpoints", deleted);
 
         Ok(deleted)
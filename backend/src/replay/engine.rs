@@ -58,6 +58,11 @@ impl ReplayEngine {
         })
     }
 
+    /// ID of the session this engine records its progress under
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
     /// Start the replay process
     pub async fn start(&self) -> ReplayResult<ReplayMetadata> {
         info!(
@@ -147,8 +152,26 @@ impl ReplayEngine {
         let mut total_processed = 0u64;
         let mut total_failed = 0u64;
 
-        // Create processing context
-        let context = ProcessingContext::for_replay(self.session_id.clone(), self.config.dry_run);
+        // Create processing context; verification replays build a shadow state
+        // and never write to the live tables
+        let verifying = self.config.mode == ReplayMode::Verification;
+        let context = ProcessingContext::for_replay(
+            self.session_id.clone(),
+            self.config.dry_run || verifying,
+        );
+
+        // A full replay rebuilds derived state from scratch, so anything left
+        // over from earlier runs in this range is cleared first
+        if self.config.mode == ReplayMode::Full && !self.config.dry_run {
+            info!(
+                "Clearing derived state for ledgers {} to {}",
+                start_ledger, end_ledger
+            );
+            self.processor
+                .reset_range(start_ledger, end_ledger)
+                .await
+                .context("Failed to reset derived state")?;
+        }
 
         while current_ledger <= end_ledger {
             // Fetch batch of events
@@ -159,7 +182,7 @@ impl ReplayEngine {
                 current_ledger, batch_end, self.config.batch_size
             );
 
-            let events = self
+            let mut events = self
                 .event_storage
                 .get_events_in_range(
                     current_ledger,
@@ -170,6 +193,16 @@ impl ReplayEngine {
                 .await
                 .context("Failed to fetch events")?;
 
+            // Interleave ingested ledgers and payments with contract events;
+            // the sort is stable so each source keeps its own order
+            events.extend(
+                self.event_storage
+                    .get_ledger_events_in_range(current_ledger, batch_end, &self.config.filter)
+                    .await
+                    .context("Failed to fetch ledger events")?,
+            );
+            events.sort_by_key(|event| event.ledger_sequence);
+
             info!("Fetched {} events in batch", events.len());
 
             // Process events
@@ -198,6 +231,11 @@ impl ReplayEngine {
                 }
             }
 
+            self.processor
+                .flush(&context)
+                .await
+                .context("Failed to flush processor state")?;
+
             // Update current ledger
             current_ledger = batch_end + 1;
 
@@ -222,8 +260,10 @@ impl ReplayEngine {
         self.create_checkpoint(end_ledger, total_processed, total_failed, metadata)
             .await?;
 
-        // Persist final state
-        if !self.config.dry_run {
+        if verifying {
+            self.verify_against_live(start_ledger, end_ledger).await?;
+        } else if !self.config.dry_run {
+            // Persist final state
             let state_builder = self.state_builder.read().await;
             state_builder.persist_state().await?;
         }
@@ -231,6 +271,46 @@ impl ReplayEngine {
         Ok((total_processed, total_failed))
     }
 
+    /// Recompute every derived bucket of the range into the shadow state, diff
+    /// it against the live tables and store the resulting report
+    async fn verify_against_live(&self, start_ledger: u64, end_ledger: u64) -> Result<()> {
+        self.processor
+            .mark_range(start_ledger, end_ledger)
+            .await
+            .context("Failed to mark derived state for verification")?;
+        let buckets = self
+            .processor
+            .recompute()
+            .await
+            .context("Failed to recompute derived state")?;
+
+        let mut state_builder = self.state_builder.write().await;
+        state_builder.record_derived(buckets);
+        let report = state_builder
+            .diff_live(
+                &self.session_id,
+                start_ledger,
+                end_ledger,
+                &self.config.filter,
+            )
+            .await
+            .context("Failed to diff shadow state against live tables")?;
+
+        if !report.is_consistent() {
+            warn!(
+                "Verification found {} mismatched rows, first diverging ledger {:?}",
+                report.rows_mismatched, report.first_diverging_ledger
+            );
+        }
+
+        self.replay_storage
+            .save_verification_report(&report)
+            .await
+            .context("Failed to save verification report")?;
+
+        Ok(())
+    }
+
     /// Process a single event
     async fn process_event(
         &self,
@@ -273,7 +353,12 @@ impl ReplayEngine {
 
     /// Determine the ledger range for replay
     async fn determine_ledger_range(&self) -> Result<(u64, u64)> {
-        let latest_ledger = self.event_storage.get_latest_ledger().await?.unwrap_or(0);
+        let latest_ledger = self
+            .event_storage
+            .get_latest_ledger()
+            .await?
+            .max(self.event_storage.get_latest_ingested_ledger().await?)
+            .unwrap_or(0);
 
         let checkpoint_ledger = if let Some(checkpoint_id) = self.get_checkpoint_id() {
             self.checkpoint_manager
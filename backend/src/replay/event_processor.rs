@@ -11,7 +11,9 @@ use std::time::Duration;
 use tokio::time::timeout;
 use tracing::{debug, error, info, warn};
 
+use super::verification::DerivedBucket;
 use super::{ContractEvent, ReplayError};
+use crate::with_pool;
 
 /// Context provided to event processors
 #[derive(Debug, Clone)]
@@ -169,6 +171,35 @@ pub trait EventProcessor: Send + Sync {
 
     /// Get processor name for logging
     fn name(&self) -> &str;
+
+    /// Whether this processor handles the event. Events are offered to every
+    /// processor that handles them.
+    fn handles(&self, _event: &ContractEvent) -> bool {
+        true
+    }
+
+    /// Clear state derived from ledgers `start_ledger..=end_ledger` ahead of a
+    /// full rebuild
+    async fn reset_range(&self, _start_ledger: u64, _end_ledger: u64) -> Result<()> {
+        Ok(())
+    }
+
+    /// Write out anything buffered while processing the current batch
+    async fn flush(&self, _context: &ProcessingContext) -> Result<()> {
+        Ok(())
+    }
+
+    /// Mark all state derived from ledgers `start_ledger..=end_ledger` so the
+    /// next flush or recompute revisits it, whether or not events touched it
+    async fn mark_range(&self, _start_ledger: u64, _end_ledger: u64) -> Result<()> {
+        Ok(())
+    }
+
+    /// Recompute marked state into shadow rows without writing it, for
+    /// verification replays
+    async fn recompute(&self) -> Result<Vec<DerivedBucket>> {
+        Ok(Vec::new())
+    }
 }
 
 /// Composite processor that delegates to specific processors based on event type
@@ -235,20 +266,75 @@ impl CompositeEventProcessor {
         ))
     }
 
-    /// Internal processing logic
+    /// Clear derived state for a ledger range in every processor
+    pub async fn reset_range(&self, start_ledger: u64, end_ledger: u64) -> Result<()> {
+        for processor in &self.processors {
+            processor
+                .reset_range(start_ledger, end_ledger)
+                .await
+                .with_context(|| format!("{} failed to reset state", processor.name()))?;
+        }
+        Ok(())
+    }
+
+    /// Flush buffered state in every processor
+    pub async fn flush(&self, context: &ProcessingContext) -> Result<()> {
+        for processor in &self.processors {
+            processor
+                .flush(context)
+                .await
+                .with_context(|| format!("{} failed to flush state", processor.name()))?;
+        }
+        Ok(())
+    }
+
+    /// Mark derived state for a ledger range in every processor
+    pub async fn mark_range(&self, start_ledger: u64, end_ledger: u64) -> Result<()> {
+        for processor in &self.processors {
+            processor
+                .mark_range(start_ledger, end_ledger)
+                .await
+                .with_context(|| format!("{} failed to mark state", processor.name()))?;
+        }
+        Ok(())
+    }
+
+    /// Recompute marked state in every processor into shadow rows
+    pub async fn recompute(&self) -> Result<Vec<DerivedBucket>> {
+        let mut buckets = Vec::new();
+        for processor in &self.processors {
+            buckets.extend(
+                processor
+                    .recompute()
+                    .await
+                    .with_context(|| format!("{} failed to recompute state", processor.name()))?,
+            );
+        }
+        Ok(buckets)
+    }
+
+    /// Internal processing logic. The event goes to every processor that
+    /// handles it; processors that already recorded it are skipped, so a retry
+    /// only re-runs the ones that failed.
     async fn process_event_internal(
         &self,
         event: &ContractEvent,
         context: &ProcessingContext,
     ) -> Result<ProcessingResult> {
         let start = std::time::Instant::now();
+        let mut combined: Option<ProcessingResult> = None;
+        let mut last_error = None;
 
-        // Find appropriate processor
-        for processor in &self.processors {
+        for processor in self.processors.iter().filter(|p| p.handles(event)) {
             // Check idempotency
             if processor.is_processed(event).await? {
-                debug!("Event {} already processed, skipping", event.unique_id());
-                return Ok(ProcessingResult::skipped());
+                debug!(
+                    "Event {} already processed by {}, skipping",
+                    event.unique_id(),
+                    processor.name()
+                );
+                combined.get_or_insert_with(ProcessingResult::skipped);
+                continue;
             }
 
             // Validate event
@@ -256,24 +342,31 @@ impl CompositeEventProcessor {
 
             // Process event
             match processor.process_event(event, context).await {
-                Ok(mut result) => {
-                    result.duration_ms = start.elapsed().as_millis() as u64;
-
+                Ok(result) => {
                     // Mark as processed if not dry-run
                     if !context.dry_run && result.success {
                         processor.mark_processed(event).await?;
                     }
 
-                    info!(
-                        "Event {} processed by {} in {}ms (success: {}, skipped: {})",
+                    debug!(
+                        "Event {} processed by {} (success: {}, skipped: {})",
                         event.unique_id(),
                         processor.name(),
-                        result.duration_ms,
                         result.success,
                         result.skipped
                     );
 
-                    return Ok(result);
+                    if !result.success {
+                        last_error = result.error.clone();
+                    }
+                    combined = Some(match combined {
+                        Some(mut merged) => {
+                            merged.skipped &= result.skipped;
+                            merged.state_changes.extend(result.state_changes);
+                            merged
+                        }
+                        None => result,
+                    });
                 }
                 Err(e) => {
                     warn!(
@@ -282,16 +375,32 @@ impl CompositeEventProcessor {
                         event.unique_id(),
                         e
                     );
-                    continue;
+                    last_error = Some(e.to_string());
                 }
             }
         }
 
-        // No processor handled the event
-        warn!("No processor found for event {}", event.unique_id());
-        Ok(ProcessingResult::failure(
-            "No processor found for event".to_string(),
-        ))
+        match (combined, last_error) {
+            (_, Some(error)) => Ok(ProcessingResult::failure(error)),
+            (Some(result), None) => {
+                let result = result.with_duration(start.elapsed().as_millis() as u64);
+                info!(
+                    "Event {} processed in {}ms (success: {}, skipped: {})",
+                    event.unique_id(),
+                    result.duration_ms,
+                    result.success,
+                    result.skipped
+                );
+                Ok(result)
+            }
+            (None, None) => {
+                // No processor handled the event
+                warn!("No processor found for event {}", event.unique_id());
+                Ok(ProcessingResult::failure(
+                    "No processor found for event".to_string(),
+                ))
+            }
+        }
     }
 }
 
@@ -303,11 +412,11 @@ impl Default for CompositeEventProcessor {
 
 /// Example processor for snapshot submission events
 pub struct SnapshotEventProcessor {
-    pool: sqlx::SqlitePool,
+    pool: crate::database::DbPool,
 }
 
 impl SnapshotEventProcessor {
-    pub fn new(pool: sqlx::SqlitePool) -> Self {
+    pub fn new(pool: crate::database::DbPool) -> Self {
         Self { pool }
     }
 
@@ -335,13 +444,15 @@ impl SnapshotEventProcessor {
         );
 
         // Check if already exists (idempotency)
-        let exists: bool = sqlx::query_scalar(
-            "SELECT EXISTS(SELECT 1 FROM snapshots WHERE epoch = $1 AND hash = $2)",
-        )
-        .bind(epoch as i64)
-        .bind(hash)
-        .fetch_one(&self.pool)
-        .await?;
+        let exists: bool = with_pool!(&self.pool, |pool| {
+            sqlx::query_scalar(
+                "SELECT EXISTS(SELECT 1 FROM snapshots WHERE epoch = $1 AND hash = $2)",
+            )
+            .bind(epoch as i64)
+            .bind(hash)
+            .fetch_one(pool)
+            .await
+        })?;
 
         if exists {
             return Ok(ProcessingResult::skipped());
@@ -349,20 +460,23 @@ impl SnapshotEventProcessor {
 
         // Insert snapshot record (if not dry-run)
         if !context.dry_run {
-            sqlx::query(
-                r#"
-                INSERT INTO snapshots (epoch, hash, ledger_sequence, transaction_hash, created_at)
-                VALUES ($1, $2, $3, $4, $5)
-                ON CONFLICT (epoch) DO NOTHING
-                "#,
-            )
-            .bind(epoch as i64)
-            .bind(hash)
-            .bind(event.ledger_sequence as i64)
-            .bind(&event.transaction_hash)
-            .bind(event.timestamp)
-            .execute(&self.pool)
-            .await?;
+            with_pool!(&self.pool, |pool| {
+                sqlx::query(
+                    r#"
+                    INSERT INTO snapshots (epoch, hash, ledger_sequence, transaction_hash, created_at)
+                    VALUES ($1, $2, $3, $4, $5)
+                    ON CONFLICT (epoch) DO NOTHING
+                    "#,
+                )
+                .bind(epoch as i64)
+                .bind(hash)
+                .bind(event.ledger_sequence as i64)
+                .bind(&event.transaction_hash)
+                .bind(event.timestamp)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+            })?;
         }
 
         Ok(ProcessingResult::success().with_change(StateChange {
@@ -396,27 +510,31 @@ impl EventProcessor for SnapshotEventProcessor {
     }
 
     async fn is_processed(&self, event: &ContractEvent) -> Result<bool> {
-        let exists: bool =
+        let exists: bool = with_pool!(&self.pool, |pool| {
             sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM processed_events WHERE event_id = $1)")
                 .bind(&event.unique_id())
-                .fetch_one(&self.pool)
-                .await?;
+                .fetch_one(pool)
+                .await
+        })?;
 
         Ok(exists)
     }
 
     async fn mark_processed(&self, event: &ContractEvent) -> Result<()> {
-        sqlx::query(
-            r#"
-            INSERT INTO processed_events (event_id, ledger_sequence, processed_at)
-            VALUES ($1, $2, CURRENT_TIMESTAMP)
-            ON CONFLICT (event_id) DO NOTHING
-            "#,
-        )
-        .bind(&event.unique_id())
-        .bind(event.ledger_sequence as i64)
-        .execute(&self.pool)
-        .await?;
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO processed_events (event_id, ledger_sequence, processed_at)
+                VALUES ($1, $2, CURRENT_TIMESTAMP)
+                ON CONFLICT (event_id) DO NOTHING
+                "#,
+            )
+            .bind(&event.unique_id())
+            .bind(event.ledger_sequence as i64)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
 
         Ok(())
     }
@@ -424,6 +542,10 @@ impl EventProcessor for SnapshotEventProcessor {
     fn name(&self) -> &str {
         "SnapshotEventProcessor"
     }
+
+    fn handles(&self, event: &ContractEvent) -> bool {
+        event.event_type == "snapshot_submitted"
+    }
 }
 
 #[cfg(test)]
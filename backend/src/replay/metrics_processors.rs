@@ -0,0 +1,1091 @@
+//! Metrics Rebuild Processors
+//!
+//! Event processors that rebuild derived analytics from ingested ledgers and
+//! payments. Processing an event only marks the time bucket it falls into;
+//! on flush each marked bucket is recomputed from the stored source rows with
+//! the current formulas. Replaying a range is therefore deterministic and safe
+//! to repeat, and buckets that straddle the range boundary still see every
+//! payment they contain. A `ReplayMode::Full` replay also clears the derived
+//! rows covering the range first, so buckets left without payments disappear.
+//! A `ReplayMode::Verification` replay recomputes every bucket in the range
+//! into shadow rows instead, leaving the tables untouched.
+//!
+//! Replayed ledgers decide which buckets are rebuilt. Corridor rows are
+//! recomputed from the `payments` table through the same conversion the
+//! aggregation service uses, so they carry the same corridors as the rows it
+//! writes. Anchor entries come from the ledger payments of anchor accounts.
+
+use crate::database::DbPool;
+use crate::with_pool;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Mutex, PoisonError};
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use super::event_processor::{EventProcessor, ProcessingContext, ProcessingResult, StateChange};
+use super::storage::{LEDGER_CLOSED_EVENT, LEDGER_EVENT_SOURCE, PAYMENT_EVENT};
+use super::verification::DerivedBucket;
+use super::ContractEvent;
+use crate::analytics::compute_anchor_metrics;
+use crate::db::aggregation::AggregationDb;
+use crate::models::corridor::{CorridorMetrics, PaymentRecord};
+use crate::models::AnchorMetrics;
+use crate::services::analytics::compute_metrics_from_payments;
+use crate::services::liquidity_pool_analyzer::LiquidityPoolAnalyzer;
+
+/// Start of the UTC hour containing `at`
+pub fn hour_bucket(at: DateTime<Utc>) -> DateTime<Utc> {
+    at.duration_trunc(Duration::hours(1)).unwrap_or(at)
+}
+
+/// Start of the UTC day containing `at`
+pub fn day_bucket(at: DateTime<Utc>) -> DateTime<Utc> {
+    at.duration_trunc(Duration::days(1)).unwrap_or(at)
+}
+
+fn is_ledger_event(event: &ContractEvent, event_type: &str) -> bool {
+    event.contract_id == LEDGER_EVENT_SOURCE && event.event_type == event_type
+}
+
+fn recompute_change(entity_type: &str, entity_id: String) -> StateChange {
+    StateChange {
+        change_type: "recompute".to_string(),
+        entity_type: entity_type.to_string(),
+        entity_id,
+        previous_value: None,
+        new_value: None,
+    }
+}
+
+/// Close times of the first and last stored ledger in a range
+async fn ledger_time_window(
+    pool: &DbPool,
+    start_ledger: u64,
+    end_ledger: u64,
+) -> Result<Option<(DateTime<Utc>, DateTime<Utc>)>> {
+    let (first, last): (Option<DateTime<Utc>>, Option<DateTime<Utc>>) = with_pool!(pool, |pool| {
+        sqlx::query_as(
+            "SELECT MIN(close_time), MAX(close_time) FROM ledgers WHERE sequence >= $1 AND sequence <= $2",
+        )
+        .bind(start_ledger as i64)
+        .bind(end_ledger as i64)
+        .fetch_one(pool)
+        .await
+    })
+    .context("Failed to look up ledger close times")?;
+
+    Ok(first.zip(last))
+}
+
+/// Starts of every bucket of `width` between `first` and `last`, inclusive
+fn bucket_starts(
+    first: DateTime<Utc>,
+    last: DateTime<Utc>,
+    width: Duration,
+) -> impl Iterator<Item = DateTime<Utc>> {
+    std::iter::successors(Some(first), move |bucket| Some(*bucket + width))
+        .take_while(move |bucket| *bucket <= last)
+}
+
+/// A stored payment with the close time of its ledger, its transaction result
+/// and any SEP transfer timestamps reported for its transaction
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct LedgerPaymentRow {
+    pub id: i64,
+    pub transaction_hash: String,
+    pub source_account: Option<String>,
+    pub destination: Option<String>,
+    pub asset_code: Option<String>,
+    pub asset_issuer: Option<String>,
+    pub amount: Option<String>,
+    pub successful: bool,
+    pub close_time: DateTime<Utc>,
+    pub sep_started_at: Option<DateTime<Utc>>,
+    pub sep_completed_at: Option<DateTime<Utc>>,
+}
+
+impl LedgerPaymentRow {
+    pub fn touches(&self, account: &str) -> bool {
+        self.source_account.as_deref() == Some(account)
+            || self.destination.as_deref() == Some(account)
+    }
+
+    /// Settlement is measured from the anchor-reported start to its reported
+    /// completion, or to ledger close when completion is unknown. The asset
+    /// fills both sides of the corridor, which anchor metrics do not use.
+    pub fn to_payment_record(&self) -> PaymentRecord {
+        let code = self.asset_code.clone().unwrap_or_else(|| "XLM".to_string());
+        let issuer = self
+            .asset_issuer
+            .clone()
+            .unwrap_or_else(|| "native".to_string());
+
+        PaymentRecord {
+            id: Uuid::from_u64_pair(0, self.id as u64),
+            source_asset_code: code.clone(),
+            source_asset_issuer: issuer.clone(),
+            destination_asset_code: code,
+            destination_asset_issuer: issuer,
+            amount: self
+                .amount
+                .as_deref()
+                .and_then(|a| a.parse().ok())
+                .unwrap_or(0.0),
+            successful: self.successful,
+            timestamp: self.close_time,
+            submission_time: self.sep_started_at,
+            confirmation_time: self
+                .sep_started_at
+                .map(|_| self.sep_completed_at.unwrap_or(self.close_time)),
+        }
+    }
+}
+
+/// Payments in ledgers closed within `[from, to)`
+pub async fn load_payments(
+    pool: &DbPool,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<LedgerPaymentRow>> {
+    with_pool!(pool, |pool| {
+        sqlx::query_as::<_, LedgerPaymentRow>(
+            r#"
+            SELECT lp.id, lp.transaction_hash, lp.source_account, lp.destination,
+                   lp.asset_code, lp.asset_issuer, lp.amount,
+                   COALESCE(t.successful, TRUE) AS successful,
+                   l.close_time,
+                   s.started_at AS sep_started_at,
+                   s.completed_at AS sep_completed_at
+            FROM ledger_payments lp
+            JOIN ledgers l ON l.sequence = lp.ledger_sequence
+            LEFT JOIN transactions t ON t.hash = lp.transaction_hash
+            LEFT JOIN anchor_sep_transactions s ON s.stellar_transaction_id = lp.transaction_hash
+            WHERE l.close_time >= $1 AND l.close_time < $2
+            ORDER BY lp.ledger_sequence ASC, lp.id ASC
+            "#,
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(pool)
+        .await
+    })
+    .context("Failed to load payments")
+}
+
+/// Corridor metrics for one bucket, ordered by corridor key
+fn bucket_corridor_metrics(payments: &[PaymentRecord]) -> Vec<CorridorMetrics> {
+    let mut metrics = compute_metrics_from_payments(payments);
+    metrics.sort_by(|a, b| a.corridor_key.cmp(&b.corridor_key));
+    metrics
+}
+
+/// Bucket width and target table of a [`CorridorMetricsProcessor`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorridorResolution {
+    /// `corridor_metrics_hourly`
+    Hourly,
+    /// `corridor_metrics`
+    Daily,
+}
+
+impl CorridorResolution {
+    fn bucket(self, at: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            Self::Hourly => hour_bucket(at),
+            Self::Daily => day_bucket(at),
+        }
+    }
+
+    fn width(self) -> Duration {
+        match self {
+            Self::Hourly => Duration::hours(1),
+            Self::Daily => Duration::days(1),
+        }
+    }
+
+    fn table(self) -> &'static str {
+        match self {
+            Self::Hourly => "corridor_metrics_hourly",
+            Self::Daily => "corridor_metrics",
+        }
+    }
+
+    fn time_column(self) -> &'static str {
+        match self {
+            Self::Hourly => "hour_bucket",
+            Self::Daily => "date",
+        }
+    }
+
+    /// Stored columns of a row, for verification
+    fn values(self, metric: &CorridorMetrics) -> Vec<(&'static str, Option<f64>)> {
+        let mut values = vec![
+            ("total_transactions", Some(metric.total_transactions as f64)),
+            (
+                "successful_transactions",
+                Some(metric.successful_transactions as f64),
+            ),
+            (
+                "failed_transactions",
+                Some(metric.failed_transactions as f64),
+            ),
+            ("success_rate", Some(metric.success_rate)),
+            ("volume_usd", Some(metric.volume_usd)),
+        ];
+        if self == Self::Hourly {
+            values.push((
+                "avg_settlement_latency_ms",
+                metric.avg_settlement_latency_ms.map(f64::from),
+            ));
+            values.push(("liquidity_depth_usd", Some(metric.liquidity_depth_usd)));
+        }
+        values
+    }
+}
+
+/// Rebuilds `corridor_metrics_hourly` or the daily `corridor_metrics` from
+/// payment events
+pub struct CorridorMetricsProcessor {
+    pool: DbPool,
+    resolution: CorridorResolution,
+    dirty: Mutex<BTreeSet<DateTime<Utc>>>,
+}
+
+impl CorridorMetricsProcessor {
+    pub fn new(pool: DbPool, resolution: CorridorResolution) -> Self {
+        Self {
+            pool,
+            resolution,
+            dirty: Mutex::new(BTreeSet::new()),
+        }
+    }
+
+    pub fn hourly(pool: DbPool) -> Self {
+        Self::new(pool, CorridorResolution::Hourly)
+    }
+
+    pub fn daily(pool: DbPool) -> Self {
+        Self::new(pool, CorridorResolution::Daily)
+    }
+
+    fn mark(&self, bucket: DateTime<Utc>) {
+        self.dirty
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(bucket);
+    }
+
+    fn take_dirty(&self) -> BTreeSet<DateTime<Utc>> {
+        std::mem::take(&mut *self.dirty.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Metrics of one bucket from the `payments` table the aggregation
+    /// service reads, so rebuilt rows match the ones it writes
+    async fn compute_bucket(&self, bucket: DateTime<Utc>) -> Result<Vec<CorridorMetrics>> {
+        let payments = AggregationDb::new(self.pool.clone())
+            .fetch_payments_in_bucket(bucket, bucket + self.resolution.width())
+            .await?;
+        Ok(bucket_corridor_metrics(&payments))
+    }
+
+    /// Replace the rows of one bucket with metrics recomputed from its payments
+    async fn rebuild_bucket(&self, bucket: DateTime<Utc>) -> Result<usize> {
+        let metrics = self.compute_bucket(bucket).await?;
+
+        with_pool!(&self.pool, |pool| {
+            let mut tx = pool.begin().await?;
+            match self.resolution {
+                CorridorResolution::Hourly => {
+                    sqlx::query("DELETE FROM corridor_metrics_hourly WHERE hour_bucket = $1")
+                        .bind(bucket.to_rfc3339())
+                        .execute(&mut *tx)
+                        .await?;
+
+                    for metric in &metrics {
+                        sqlx::query(
+                            r#"
+                            INSERT INTO corridor_metrics_hourly (
+                                id, corridor_key, asset_a_code, asset_a_issuer, asset_b_code,
+                                asset_b_issuer, hour_bucket, total_transactions,
+                                successful_transactions, failed_transactions, success_rate,
+                                volume_usd, avg_slippage_bps, avg_settlement_latency_ms,
+                                liquidity_depth_usd
+                            )
+                            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, 0, $13, $14)
+                            "#,
+                        )
+                        .bind(Uuid::new_v4().to_string())
+                        .bind(&metric.corridor_key)
+                        .bind(&metric.asset_a_code)
+                        .bind(&metric.asset_a_issuer)
+                        .bind(&metric.asset_b_code)
+                        .bind(&metric.asset_b_issuer)
+                        .bind(bucket.to_rfc3339())
+                        .bind(metric.total_transactions)
+                        .bind(metric.successful_transactions)
+                        .bind(metric.failed_transactions)
+                        .bind(metric.success_rate)
+                        .bind(metric.volume_usd)
+                        .bind(metric.avg_settlement_latency_ms)
+                        .bind(metric.liquidity_depth_usd)
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+                }
+                CorridorResolution::Daily => {
+                    sqlx::query("DELETE FROM corridor_metrics WHERE date >= $1 AND date < $2")
+                        .bind(bucket)
+                        .bind(bucket + Duration::days(1))
+                        .execute(&mut *tx)
+                        .await?;
+
+                    for metric in &metrics {
+                        sqlx::query(
+                            r#"
+                            INSERT INTO corridor_metrics (
+                                corridor_key, asset_a_code, asset_a_issuer, asset_b_code, asset_b_issuer,
+                                date, total_transactions, successful_transactions, failed_transactions,
+                                success_rate, volume_usd
+                            )
+                            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                            "#,
+                        )
+                        .bind(&metric.corridor_key)
+                        .bind(&metric.asset_a_code)
+                        .bind(&metric.asset_a_issuer)
+                        .bind(&metric.asset_b_code)
+                        .bind(&metric.asset_b_issuer)
+                        .bind(bucket)
+                        .bind(metric.total_transactions)
+                        .bind(metric.successful_transactions)
+                        .bind(metric.failed_transactions)
+                        .bind(metric.success_rate)
+                        .bind(metric.volume_usd)
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+                }
+            }
+            tx.commit().await?;
+        });
+
+        Ok(metrics.len())
+    }
+}
+
+#[async_trait]
+impl EventProcessor for CorridorMetricsProcessor {
+    async fn process_event(
+        &self,
+        event: &ContractEvent,
+        _context: &ProcessingContext,
+    ) -> Result<ProcessingResult> {
+        let bucket = self.resolution.bucket(event.timestamp);
+        self.mark(bucket);
+
+        Ok(ProcessingResult::success().with_change(recompute_change(
+            self.resolution.table(),
+            bucket.to_rfc3339(),
+        )))
+    }
+
+    /// Buckets are recomputed from source rows, so processing an event twice
+    /// is harmless and nothing needs to be tracked
+    async fn is_processed(&self, _event: &ContractEvent) -> Result<bool> {
+        Ok(false)
+    }
+
+    async fn mark_processed(&self, _event: &ContractEvent) -> Result<()> {
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        match self.resolution {
+            CorridorResolution::Hourly => "HourlyCorridorMetricsProcessor",
+            CorridorResolution::Daily => "CorridorMetricsProcessor",
+        }
+    }
+
+    fn handles(&self, event: &ContractEvent) -> bool {
+        is_ledger_event(event, PAYMENT_EVENT)
+    }
+
+    async fn reset_range(&self, start_ledger: u64, end_ledger: u64) -> Result<()> {
+        let Some((from, to)) = ledger_time_window(&self.pool, start_ledger, end_ledger).await?
+        else {
+            return Ok(());
+        };
+        let (first, last) = (self.resolution.bucket(from), self.resolution.bucket(to));
+
+        let end = last + self.resolution.width();
+        let delete = format!(
+            "DELETE FROM {} WHERE {col} >= $1 AND {col} < $2",
+            self.resolution.table(),
+            col = self.resolution.time_column()
+        );
+        // hour_bucket holds RFC 3339 text, the daily date a timestamp
+        let deleted = with_pool!(&self.pool, |pool| {
+            let delete = match self.resolution {
+                CorridorResolution::Hourly => sqlx::query(&delete)
+                    .bind(first.to_rfc3339())
+                    .bind(end.to_rfc3339()),
+                CorridorResolution::Daily => sqlx::query(&delete).bind(first).bind(end),
+            };
+            delete.execute(pool).await?.rows_affected()
+        });
+
+        // Edge buckets may hold payments outside the range that must survive
+        self.mark(first);
+        self.mark(last);
+
+        info!(
+            "Cleared {} {} rows for ledgers {} to {}",
+            deleted,
+            self.resolution.table(),
+            start_ledger,
+            end_ledger
+        );
+        Ok(())
+    }
+
+    async fn flush(&self, context: &ProcessingContext) -> Result<()> {
+        let buckets = self.take_dirty();
+        if context.dry_run {
+            return Ok(());
+        }
+
+        for bucket in buckets {
+            let rows = self.rebuild_bucket(bucket).await?;
+            debug!(
+                "Rebuilt {} {} rows for {}",
+                rows,
+                self.resolution.table(),
+                bucket
+            );
+        }
+        Ok(())
+    }
+
+    async fn mark_range(&self, start_ledger: u64, end_ledger: u64) -> Result<()> {
+        if let Some((from, to)) = ledger_time_window(&self.pool, start_ledger, end_ledger).await? {
+            for bucket in bucket_starts(
+                self.resolution.bucket(from),
+                self.resolution.bucket(to),
+                self.resolution.width(),
+            ) {
+                self.mark(bucket);
+            }
+        }
+        Ok(())
+    }
+
+    async fn recompute(&self) -> Result<Vec<DerivedBucket>> {
+        let mut derived = Vec::new();
+        for bucket in self.take_dirty() {
+            let shadow = self.compute_bucket(bucket).await?.iter().fold(
+                DerivedBucket::new(
+                    self.resolution.table(),
+                    bucket,
+                    bucket + self.resolution.width(),
+                ),
+                |shadow, metric| {
+                    shadow.with_row(metric.corridor_key.clone(), &self.resolution.values(metric))
+                },
+            );
+            derived.push(shadow);
+        }
+        Ok(derived)
+    }
+}
+
+/// Rebuilds hourly `anchor_metrics_history` entries from payments sent or
+/// received by anchor accounts
+pub struct AnchorMetricsProcessor {
+    pool: DbPool,
+    /// Anchor IDs by Stellar account, loaded on first use
+    anchors: tokio::sync::OnceCell<HashMap<String, String>>,
+    dirty: Mutex<BTreeSet<(DateTime<Utc>, String)>>,
+}
+
+impl AnchorMetricsProcessor {
+    pub fn new(pool: DbPool) -> Self {
+        Self {
+            pool,
+            anchors: tokio::sync::OnceCell::new(),
+            dirty: Mutex::new(BTreeSet::new()),
+        }
+    }
+
+    async fn anchors(&self) -> Result<&HashMap<String, String>> {
+        self.anchors
+            .get_or_try_init(|| async {
+                let rows: Vec<(String, String)> = with_pool!(&self.pool, |pool| {
+                    sqlx::query_as("SELECT stellar_account, id FROM anchors")
+                        .fetch_all(pool)
+                        .await
+                })
+                .context("Failed to load anchors")?;
+                Ok::<_, anyhow::Error>(rows.into_iter().collect())
+            })
+            .await
+    }
+
+    fn mark(&self, hour: DateTime<Utc>, anchor_id: String) {
+        self.dirty
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert((hour, anchor_id));
+    }
+
+    /// Marked anchors grouped by hour, with their accounts
+    async fn take_dirty(&self) -> Result<BTreeMap<DateTime<Utc>, Vec<(String, String)>>> {
+        let entries =
+            std::mem::take(&mut *self.dirty.lock().unwrap_or_else(PoisonError::into_inner));
+        let mut by_hour: BTreeMap<DateTime<Utc>, Vec<(String, String)>> = BTreeMap::new();
+        if entries.is_empty() {
+            return Ok(by_hour);
+        }
+
+        let accounts: HashMap<&str, &str> = self
+            .anchors()
+            .await?
+            .iter()
+            .map(|(account, id)| (id.as_str(), account.as_str()))
+            .collect();
+        for (hour, anchor_id) in entries {
+            if let Some(account) = accounts.get(anchor_id.as_str()) {
+                by_hour
+                    .entry(hour)
+                    .or_default()
+                    .push((anchor_id, account.to_string()));
+            }
+        }
+        Ok(by_hour)
+    }
+
+    /// Metrics and volume of an anchor's payments, or `None` without any
+    fn compute_entry(account: &str, payments: &[LedgerPaymentRow]) -> Option<(AnchorMetrics, f64)> {
+        let records: Vec<PaymentRecord> = payments
+            .iter()
+            .filter(|p| p.touches(account))
+            .map(LedgerPaymentRow::to_payment_record)
+            .collect();
+        if records.is_empty() {
+            return None;
+        }
+
+        let total = records.len() as i64;
+        let successful = records.iter().filter(|r| r.successful).count() as i64;
+        let volume: f64 = records
+            .iter()
+            .filter(|r| r.successful)
+            .map(|r| r.amount)
+            .sum();
+        let latencies: Vec<i64> = records
+            .iter()
+            .filter(|r| r.successful)
+            .filter_map(PaymentRecord::settlement_latency_ms)
+            .filter(|ms| *ms >= 0)
+            .collect();
+        let avg_settlement_time_ms = (!latencies.is_empty())
+            .then(|| (latencies.iter().sum::<i64>() / latencies.len() as i64) as i32);
+
+        let metrics = compute_anchor_metrics(
+            total,
+            successful,
+            total - successful,
+            avg_settlement_time_ms,
+        );
+        Some((metrics, volume))
+    }
+
+    /// Replace one anchor's history entry for an hour with metrics recomputed
+    /// from its payments in that hour
+    async fn rebuild_entry(
+        &self,
+        hour: DateTime<Utc>,
+        anchor_id: &str,
+        account: &str,
+        payments: &[LedgerPaymentRow],
+    ) -> Result<()> {
+        with_pool!(&self.pool, |pool| {
+            let mut tx = pool.begin().await?;
+            sqlx::query(
+                "DELETE FROM anchor_metrics_history WHERE anchor_id = $1 AND timestamp = $2",
+            )
+            .bind(anchor_id)
+            .bind(hour)
+            .execute(&mut *tx)
+            .await?;
+
+            if let Some((metrics, volume)) = Self::compute_entry(account, payments) {
+                sqlx::query(
+                    r#"
+                    INSERT INTO anchor_metrics_history (
+                        id, anchor_id, timestamp, success_rate, failure_rate, reliability_score,
+                        total_transactions, successful_transactions, failed_transactions,
+                        avg_settlement_time_ms, volume_usd
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                    "#,
+                )
+                .bind(Uuid::new_v4().to_string())
+                .bind(anchor_id)
+                .bind(hour)
+                .bind(metrics.success_rate)
+                .bind(metrics.failure_rate)
+                .bind(metrics.reliability_score)
+                .bind(metrics.total_transactions)
+                .bind(metrics.successful_transactions)
+                .bind(metrics.failed_transactions)
+                .bind(metrics.avg_settlement_time_ms)
+                .bind(volume)
+                .execute(&mut *tx)
+                .await?;
+            }
+            tx.commit().await?;
+        });
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EventProcessor for AnchorMetricsProcessor {
+    async fn process_event(
+        &self,
+        event: &ContractEvent,
+        _context: &ProcessingContext,
+    ) -> Result<ProcessingResult> {
+        let anchors = self.anchors().await?;
+        let hour = hour_bucket(event.timestamp);
+        let mut result = ProcessingResult::success();
+        let mut matched = false;
+
+        for field in ["source_account", "destination"] {
+            let anchor_id = event
+                .data
+                .get(field)
+                .and_then(|v| v.as_str())
+                .and_then(|account| anchors.get(account));
+            if let Some(anchor_id) = anchor_id {
+                matched = true;
+                self.mark(hour, anchor_id.clone());
+                result = result.with_change(recompute_change(
+                    "anchor_metrics_history",
+                    format!("{}:{}", anchor_id, hour.to_rfc3339()),
+                ));
+            }
+        }
+
+        Ok(if matched {
+            result
+        } else {
+            ProcessingResult::skipped()
+        })
+    }
+
+    /// Entries are recomputed from source rows, so processing an event twice
+    /// is harmless and nothing needs to be tracked
+    async fn is_processed(&self, _event: &ContractEvent) -> Result<bool> {
+        Ok(false)
+    }
+
+    async fn mark_processed(&self, _event: &ContractEvent) -> Result<()> {
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "AnchorMetricsProcessor"
+    }
+
+    fn handles(&self, event: &ContractEvent) -> bool {
+        is_ledger_event(event, PAYMENT_EVENT)
+    }
+
+    async fn reset_range(&self, start_ledger: u64, end_ledger: u64) -> Result<()> {
+        let Some((from, to)) = ledger_time_window(&self.pool, start_ledger, end_ledger).await?
+        else {
+            return Ok(());
+        };
+        let (first, last) = (hour_bucket(from), hour_bucket(to));
+
+        let deleted = with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                "DELETE FROM anchor_metrics_history WHERE timestamp >= $1 AND timestamp < $2",
+            )
+            .bind(first)
+            .bind(last + Duration::hours(1))
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
+
+        // Edge hours may hold payments outside the range that must survive
+        for anchor_id in self.anchors().await?.values() {
+            self.mark(first, anchor_id.clone());
+            self.mark(last, anchor_id.clone());
+        }
+
+        info!(
+            "Cleared {} anchor_metrics_history rows for ledgers {} to {}",
+            deleted, start_ledger, end_ledger
+        );
+        Ok(())
+    }
+
+    async fn flush(&self, context: &ProcessingContext) -> Result<()> {
+        if context.dry_run {
+            self.dirty
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clear();
+            return Ok(());
+        }
+
+        for (hour, anchors) in self.take_dirty().await? {
+            let payments = load_payments(&self.pool, hour, hour + Duration::hours(1)).await?;
+            for (anchor_id, account) in anchors {
+                self.rebuild_entry(hour, &anchor_id, &account, &payments)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn mark_range(&self, start_ledger: u64, end_ledger: u64) -> Result<()> {
+        if let Some((from, to)) = ledger_time_window(&self.pool, start_ledger, end_ledger).await? {
+            let anchors = self.anchors().await?;
+            for hour in bucket_starts(hour_bucket(from), hour_bucket(to), Duration::hours(1)) {
+                for anchor_id in anchors.values() {
+                    self.mark(hour, anchor_id.clone());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn recompute(&self) -> Result<Vec<DerivedBucket>> {
+        let mut derived = Vec::new();
+        for (hour, anchors) in self.take_dirty().await? {
+            let end = hour + Duration::hours(1);
+            let payments = load_payments(&self.pool, hour, end).await?;
+            let mut shadow = DerivedBucket::new("anchor_metrics_history", hour, end);
+            for (anchor_id, account) in anchors {
+                if let Some((metrics, volume)) = Self::compute_entry(&account, &payments) {
+                    shadow = shadow.with_row(
+                        anchor_id,
+                        &[
+                            ("success_rate", Some(metrics.success_rate)),
+                            ("failure_rate", Some(metrics.failure_rate)),
+                            ("reliability_score", Some(metrics.reliability_score)),
+                            (
+                                "total_transactions",
+                                Some(metrics.total_transactions as f64),
+                            ),
+                            (
+                                "successful_transactions",
+                                Some(metrics.successful_transactions as f64),
+                            ),
+                            (
+                                "failed_transactions",
+                                Some(metrics.failed_transactions as f64),
+                            ),
+                            (
+                                "avg_settlement_time_ms",
+                                metrics.avg_settlement_time_ms.map(f64::from),
+                            ),
+                            ("volume_usd", Some(volume)),
+                        ],
+                    );
+                }
+            }
+            derived.push(shadow);
+        }
+        Ok(derived)
+    }
+}
+
+/// Recomputes the derived columns of `liquidity_pool_snapshots` (value, fees,
+/// APY and impermanent loss) taken while replayed ledgers closed. Reserves and
+/// volume are observations that ledgers do not record, so snapshots are
+/// updated in place rather than cleared on a full replay.
+pub struct LiquidityPoolSnapshotProcessor {
+    pool: DbPool,
+    dirty: Mutex<BTreeSet<DateTime<Utc>>>,
+}
+
+impl LiquidityPoolSnapshotProcessor {
+    pub fn new(pool: DbPool) -> Self {
+        Self {
+            pool,
+            dirty: Mutex::new(BTreeSet::new()),
+        }
+    }
+
+    fn take_dirty(&self) -> BTreeSet<DateTime<Utc>> {
+        std::mem::take(&mut *self.dirty.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Recompute value, fees, APY and impermanent loss of the snapshots taken
+    /// within an hour, by snapshot ID
+    async fn compute_hour(
+        &self,
+        hour: DateTime<Utc>,
+        initial_reserves: &mut HashMap<String, Option<(f64, f64)>>,
+    ) -> Result<Vec<(i64, [f64; 4])>> {
+        let snapshots: Vec<(i64, String, f64, f64, f64, i32)> =
+            with_pool!(&self.pool, |pool| {
+                sqlx::query_as(
+                    r#"
+                SELECT s.id, s.pool_id, s.reserve_a_amount, s.reserve_b_amount, s.volume_usd, p.fee_bp
+                FROM liquidity_pool_snapshots s
+                JOIN liquidity_pools p ON p.pool_id = s.pool_id
+                WHERE s.snapshot_at >= $1 AND s.snapshot_at < $2
+                ORDER BY s.id ASC
+                "#,
+                )
+                .bind(hour)
+                .bind(hour + Duration::hours(1))
+                .fetch_all(pool)
+                .await
+            })
+            .context("Failed to load liquidity pool snapshots")?;
+
+        let mut computed = Vec::with_capacity(snapshots.len());
+        for (id, pool_id, reserve_a, reserve_b, volume, fee_bp) in &snapshots {
+            if !initial_reserves.contains_key(pool_id) {
+                let initial: Option<(f64, f64)> = with_pool!(&self.pool, |pool| {
+                    sqlx::query_as(
+                        r#"
+                        SELECT reserve_a_amount, reserve_b_amount
+                        FROM liquidity_pool_snapshots
+                        WHERE pool_id = $1
+                        ORDER BY snapshot_at ASC, id ASC
+                        LIMIT 1
+                        "#,
+                    )
+                    .bind(pool_id)
+                    .fetch_optional(pool)
+                    .await
+                })?;
+                initial_reserves.insert(pool_id.clone(), initial);
+            }
+
+            let total_value = LiquidityPoolAnalyzer::estimate_total_value(*reserve_a, *reserve_b);
+            let fees = LiquidityPoolAnalyzer::compute_fees(*volume, *fee_bp);
+            let apy = LiquidityPoolAnalyzer::compute_apy(fees, total_value);
+            let impermanent_loss = initial_reserves[pool_id].map_or(0.0, |(a, b)| {
+                LiquidityPoolAnalyzer::compute_impermanent_loss(a, b, *reserve_a, *reserve_b)
+            });
+            computed.push((*id, [total_value, fees, apy, impermanent_loss]));
+        }
+
+        Ok(computed)
+    }
+
+    async fn rebuild_hour(
+        &self,
+        hour: DateTime<Utc>,
+        initial_reserves: &mut HashMap<String, Option<(f64, f64)>>,
+    ) -> Result<usize> {
+        let computed = self.compute_hour(hour, initial_reserves).await?;
+        for (id, [total_value, fees, apy, impermanent_loss]) in &computed {
+            with_pool!(&self.pool, |pool| {
+                sqlx::query(
+                    r#"
+                    UPDATE liquidity_pool_snapshots
+                    SET total_value_usd = $1, fees_usd = $2, apy = $3, impermanent_loss_pct = $4
+                    WHERE id = $5
+                    "#,
+                )
+                .bind(total_value)
+                .bind(fees)
+                .bind(apy)
+                .bind(impermanent_loss)
+                .bind(id)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+            })?;
+        }
+
+        Ok(computed.len())
+    }
+}
+
+#[async_trait]
+impl EventProcessor for LiquidityPoolSnapshotProcessor {
+    async fn process_event(
+        &self,
+        event: &ContractEvent,
+        _context: &ProcessingContext,
+    ) -> Result<ProcessingResult> {
+        let hour = hour_bucket(event.timestamp);
+        let newly_marked = self
+            .dirty
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(hour);
+
+        // One change per hour rather than per ledger
+        Ok(if newly_marked {
+            ProcessingResult::success().with_change(recompute_change(
+                "liquidity_pool_snapshots",
+                hour.to_rfc3339(),
+            ))
+        } else {
+            ProcessingResult::success()
+        })
+    }
+
+    /// Snapshots are recomputed from their stored reserves, so processing a
+    /// ledger twice is harmless and nothing needs to be tracked
+    async fn is_processed(&self, _event: &ContractEvent) -> Result<bool> {
+        Ok(false)
+    }
+
+    async fn mark_processed(&self, _event: &ContractEvent) -> Result<()> {
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "LiquidityPoolSnapshotProcessor"
+    }
+
+    fn handles(&self, event: &ContractEvent) -> bool {
+        is_ledger_event(event, LEDGER_CLOSED_EVENT)
+    }
+
+    async fn flush(&self, context: &ProcessingContext) -> Result<()> {
+        let hours = self.take_dirty();
+        if context.dry_run {
+            return Ok(());
+        }
+
+        let mut initial_reserves = HashMap::new();
+        for hour in hours {
+            let rows = self.rebuild_hour(hour, &mut initial_reserves).await?;
+            debug!("Recomputed {} liquidity pool snapshots for {}", rows, hour);
+        }
+        Ok(())
+    }
+
+    async fn mark_range(&self, start_ledger: u64, end_ledger: u64) -> Result<()> {
+        if let Some((from, to)) = ledger_time_window(&self.pool, start_ledger, end_ledger).await? {
+            let mut dirty = self.dirty.lock().unwrap_or_else(PoisonError::into_inner);
+            dirty.extend(bucket_starts(
+                hour_bucket(from),
+                hour_bucket(to),
+                Duration::hours(1),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn recompute(&self) -> Result<Vec<DerivedBucket>> {
+        let mut initial_reserves = HashMap::new();
+        let mut derived = Vec::new();
+        for hour in self.take_dirty() {
+            let end = hour + Duration::hours(1);
+            let shadow = self
+                .compute_hour(hour, &mut initial_reserves)
+                .await?
+                .into_iter()
+                .fold(
+                    DerivedBucket::new("liquidity_pool_snapshots", hour, end),
+                    |shadow, (id, [total_value, fees, apy, impermanent_loss])| {
+                        shadow.with_row(
+                            id.to_string(),
+                            &[
+                                ("total_value_usd", Some(total_value)),
+                                ("fees_usd", Some(fees)),
+                                ("apy", Some(apy)),
+                                ("impermanent_loss_pct", Some(impermanent_loss)),
+                            ],
+                        )
+                    },
+                );
+            derived.push(shadow);
+        }
+        Ok(derived)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn payment(id: i64, asset_code: Option<&str>, successful: bool) -> LedgerPaymentRow {
+        LedgerPaymentRow {
+            id,
+            transaction_hash: format!("tx-{}", id),
+            source_account: Some("GSOURCE".to_string()),
+            destination: Some("GDEST".to_string()),
+            asset_code: asset_code.map(str::to_string),
+            asset_issuer: asset_code.map(|_| "GISSUER".to_string()),
+            amount: Some("100.5".to_string()),
+            successful,
+            close_time: at("2026-01-22T10:15:00Z"),
+            sep_started_at: None,
+            sep_completed_at: None,
+        }
+    }
+
+    #[test]
+    fn test_buckets_truncate_to_utc_boundaries() {
+        let t = at("2026-01-22T10:15:42.5Z");
+        assert_eq!(hour_bucket(t), at("2026-01-22T10:00:00Z"));
+        assert_eq!(day_bucket(t), at("2026-01-22T00:00:00Z"));
+    }
+
+    #[test]
+    fn test_payment_record_settlement_from_sep_times() {
+        let mut row = payment(7, None, true);
+        let record = row.to_payment_record();
+        assert_eq!(
+            record.get_corridor().to_string_key(),
+            "XLM:native->XLM:native"
+        );
+        assert_eq!(record.settlement_latency_ms(), None);
+        assert!((record.amount - 100.5).abs() < f64::EPSILON);
+
+        // Without a reported completion the ledger close ends settlement
+        row.sep_started_at = Some(at("2026-01-22T10:14:00Z"));
+        assert_eq!(
+            row.to_payment_record().settlement_latency_ms(),
+            Some(60_000)
+        );
+
+        row.sep_completed_at = Some(at("2026-01-22T10:14:30Z"));
+        assert_eq!(
+            row.to_payment_record().settlement_latency_ms(),
+            Some(30_000)
+        );
+    }
+
+    #[test]
+    fn test_bucket_corridor_metrics_sorted_and_counted() {
+        let payments: Vec<PaymentRecord> = [
+            payment(1, Some("USDC"), true),
+            payment(2, Some("USDC"), false),
+            payment(3, Some("EURC"), true),
+        ]
+        .iter()
+        .map(LedgerPaymentRow::to_payment_record)
+        .collect();
+
+        let metrics = bucket_corridor_metrics(&payments);
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(metrics[0].corridor_key, "EURC:GISSUER->EURC:GISSUER");
+        assert_eq!(metrics[1].total_transactions, 2);
+        assert!((metrics[1].success_rate - 50.0).abs() < f64::EPSILON);
+    }
+}
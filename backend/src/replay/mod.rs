@@ -12,20 +12,28 @@
 //! - Network and contract filtering
 //! - Shared processing logic with live event handling
 //! - Performance optimized for large datasets
+//! - Rebuilding corridor, anchor and liquidity pool metrics from ingested ledgers
+//! - Verifying stored metrics against a shadow rebuild
 
 pub mod checkpoint;
 pub mod config;
 pub mod engine;
 pub mod event_processor;
+pub mod metrics_processors;
 pub mod state_builder;
 pub mod storage;
+pub mod verification;
 
 pub use checkpoint::{Checkpoint, CheckpointManager};
 pub use config::{ReplayConfig, ReplayMode, ReplayRange};
 pub use engine::ReplayEngine;
 pub use event_processor::{EventProcessor, ProcessingContext, ProcessingResult};
+pub use metrics_processors::{
+    AnchorMetricsProcessor, CorridorMetricsProcessor, LiquidityPoolSnapshotProcessor,
+};
 pub use state_builder::StateBuilder;
 pub use storage::{EventStorage, ReplayStorage};
+pub use verification::VerificationReport;
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
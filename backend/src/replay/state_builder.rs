@@ -2,13 +2,15 @@
 //!
 //! Rebuilds application state from contract events in a deterministic manner.
 
+use crate::database::DbPool;
+use crate::with_pool;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use sqlx::SqlitePool;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use tracing::{debug, info};
 
-use super::{ContractEvent, ProcessingResult};
+use super::verification::{self, DerivedBucket, VerificationReport};
+use super::{ContractEvent, EventFilter, ProcessingResult};
 
 /// Represents the application state at a specific point in time
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +23,9 @@ pub struct ApplicationState {
     pub verifications: HashMap<String, VerificationState>,
     /// Metadata
     pub metadata: HashMap<String, serde_json::Value>,
+    /// Derived rows recomputed by a verification replay, by bucket
+    #[serde(default)]
+    pub derived: BTreeMap<String, DerivedBucket>,
 }
 
 impl ApplicationState {
@@ -31,6 +36,7 @@ impl ApplicationState {
             snapshots: HashMap::new(),
             verifications: HashMap::new(),
             metadata: HashMap::new(),
+            derived: BTreeMap::new(),
         }
     }
 
@@ -41,6 +47,7 @@ impl ApplicationState {
             snapshots: HashMap::new(),
             verifications: HashMap::new(),
             metadata: HashMap::new(),
+            derived: BTreeMap::new(),
         }
     }
 
@@ -89,13 +96,13 @@ pub struct VerificationState {
 
 /// Builds application state from events
 pub struct StateBuilder {
-    pool: SqlitePool,
+    pool: DbPool,
     state: ApplicationState,
 }
 
 impl StateBuilder {
     /// Create a new state builder
-    pub fn new(pool: SqlitePool) -> Self {
+    pub fn new(pool: DbPool) -> Self {
         Self {
             pool,
             state: ApplicationState::new(),
@@ -103,7 +110,7 @@ impl StateBuilder {
     }
 
     /// Create state builder with initial state
-    pub fn with_state(pool: SqlitePool, state: ApplicationState) -> Self {
+    pub fn with_state(pool: DbPool, state: ApplicationState) -> Self {
         Self { pool, state }
     }
 
@@ -223,21 +230,24 @@ impl StateBuilder {
         let state_json = self.state.to_json()?;
         let state_hash = self.state.compute_hash();
 
-        sqlx::query(
-            r#"
-            INSERT INTO replay_state (ledger, state_json, state_hash, updated_at)
-            VALUES ($1, $2, $3, CURRENT_TIMESTAMP)
-            ON CONFLICT (ledger) DO UPDATE SET
-                state_json = EXCLUDED.state_json,
-                state_hash = EXCLUDED.state_hash,
-                updated_at = CURRENT_TIMESTAMP
-            "#,
-        )
-        .bind(self.state.ledger as i64)
-        .bind(serde_json::to_string(&state_json)?)
-        .bind(&state_hash)
-        .execute(&self.pool)
-        .await
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO replay_state (ledger, state_json, state_hash, updated_at)
+                VALUES ($1, $2, $3, CURRENT_TIMESTAMP)
+                ON CONFLICT (ledger) DO UPDATE SET
+                    state_json = EXCLUDED.state_json,
+                    state_hash = EXCLUDED.state_hash,
+                    updated_at = CURRENT_TIMESTAMP
+                "#,
+            )
+            .bind(self.state.ledger as i64)
+            .bind(serde_json::to_string(&state_json)?)
+            .bind(&state_hash)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .context("Failed to persist state")?;
 
         Ok(())
@@ -247,11 +257,12 @@ impl StateBuilder {
     pub async fn load_state(&mut self, ledger: u64) -> Result<bool> {
         debug!("Loading state at ledger {}", ledger);
 
-        let row: Option<(String, String)> =
+        let row: Option<(String, String)> = with_pool!(&self.pool, |pool| {
             sqlx::query_as("SELECT state_json, state_hash FROM replay_state WHERE ledger = $1")
                 .bind(ledger as i64)
-                .fetch_optional(&self.pool)
-                .await?;
+                .fetch_optional(pool)
+                .await
+        })?;
 
         match row {
             Some((state_json, state_hash)) => {
@@ -282,11 +293,12 @@ impl StateBuilder {
     pub async fn verify_state(&self, ledger: u64) -> Result<bool> {
         debug!("Verifying state at ledger {}", ledger);
 
-        let row: Option<(String,)> =
+        let row: Option<(String,)> = with_pool!(&self.pool, |pool| {
             sqlx::query_as("SELECT state_hash FROM replay_state WHERE ledger = $1")
                 .bind(ledger as i64)
-                .fetch_optional(&self.pool)
-                .await?;
+                .fetch_optional(pool)
+                .await
+        })?;
 
         match row {
             Some((expected_hash,)) => {
@@ -311,6 +323,34 @@ impl StateBuilder {
         }
     }
 
+    /// Record recomputed derived rows in the shadow state, replacing any
+    /// earlier rows for the same buckets
+    pub fn record_derived(&mut self, buckets: Vec<DerivedBucket>) {
+        for bucket in buckets {
+            self.state.derived.insert(bucket.state_key(), bucket);
+        }
+    }
+
+    /// Diff the shadow state built over `start_ledger..=end_ledger` against
+    /// the live tables
+    pub async fn diff_live(
+        &self,
+        session_id: &str,
+        start_ledger: u64,
+        end_ledger: u64,
+        filter: &EventFilter,
+    ) -> Result<VerificationReport> {
+        verification::diff_state(
+            &self.pool,
+            &self.state,
+            session_id,
+            start_ledger,
+            end_ledger,
+            filter,
+        )
+        .await
+    }
+
     /// Reset state to empty
     pub fn reset(&mut self) {
         self.state = ApplicationState::new();
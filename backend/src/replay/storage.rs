@@ -2,22 +2,34 @@
 //!
 //! Provides storage and retrieval of contract events for replay.
 
+use crate::database::DbPool;
+use crate::with_pool;
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
-use sqlx::SqlitePool;
 use std::sync::Arc;
 use tracing::{debug, info};
 
+use super::verification::VerificationReport;
 use super::{ContractEvent, EventFilter, ReplayMetadata, ReplayStatus};
 
+/// `contract_id` of events synthesized from ingested ledgers rather than
+/// emitted by a contract
+pub const LEDGER_EVENT_SOURCE: &str = "ledger";
+
+/// Event type synthesized for every ingested ledger
+pub const LEDGER_CLOSED_EVENT: &str = "ledger_closed";
+
+/// Event type synthesized for every ingested payment operation
+pub const PAYMENT_EVENT: &str = "payment";
+
 /// Storage for contract events
 pub struct EventStorage {
-    pool: SqlitePool,
+    pool: DbPool,
 }
 
 impl EventStorage {
     /// Create a new event storage
-    pub fn new(pool: SqlitePool) -> Self {
+    pub fn new(pool: DbPool) -> Self {
         Self { pool }
     }
 
@@ -25,26 +37,29 @@ impl EventStorage {
     pub async fn store_event(&self, event: &ContractEvent) -> Result<()> {
         let data_json = serde_json::to_string(&event.data)?;
 
-        sqlx::query(
-            r#"
-            INSERT INTO contract_events (
-                id, ledger_sequence, transaction_hash, contract_id,
-                event_type, data, timestamp, network
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO contract_events (
+                    id, ledger_sequence, transaction_hash, contract_id,
+                    event_type, data, timestamp, network
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT (id) DO NOTHING
+                "#,
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            ON CONFLICT (id) DO NOTHING
-            "#,
-        )
-        .bind(&event.id)
-        .bind(event.ledger_sequence as i64)
-        .bind(&event.transaction_hash)
-        .bind(&event.contract_id)
-        .bind(&event.event_type)
-        .bind(&data_json)
-        .bind(event.timestamp)
-        .bind(&event.network)
-        .execute(&self.pool)
-        .await
+            .bind(&event.id)
+            .bind(event.ledger_sequence as i64)
+            .bind(&event.transaction_hash)
+            .bind(&event.contract_id)
+            .bind(&event.event_type)
+            .bind(&data_json)
+            .bind(event.timestamp)
+            .bind(&event.network)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .context("Failed to store event")?;
 
         Ok(())
@@ -63,53 +78,57 @@ impl EventStorage {
             start_ledger, end_ledger
         );
 
-        let mut query = String::from(
-            r#"
-            SELECT id, ledger_sequence, transaction_hash, contract_id,
-                   event_type, data, timestamp, network
-            FROM contract_events
-            WHERE ledger_sequence >= $1 AND ledger_sequence <= $2
-            "#,
-        );
-
-        // Apply filters
-        let mut bind_index = 3;
-        if filter.contract_ids.is_some() {
-            query.push_str(&format!(" AND contract_id IN (${}) ", bind_index));
-            bind_index += 1;
-        }
-        if filter.event_types.is_some() {
-            query.push_str(&format!(" AND event_type IN (${}) ", bind_index));
-            bind_index += 1;
-        }
-        if filter.network.is_some() {
-            query.push_str(&format!(" AND network = ${} ", bind_index));
-        }
+        let rows: Vec<(
+            String,
+            i64,
+            String,
+            String,
+            String,
+            String,
+            DateTime<Utc>,
+            String,
+        )> = with_pool!(&self.pool, |pool| {
+            let mut query = sqlx::QueryBuilder::new(
+                r#"
+                SELECT id, ledger_sequence, transaction_hash, contract_id,
+                       event_type, data, timestamp, network
+                FROM contract_events
+                WHERE ledger_sequence >= "#,
+            );
+            query.push_bind(start_ledger as i64);
+            query.push(" AND ledger_sequence <= ");
+            query.push_bind(end_ledger as i64);
+
+            // Apply filters
+            if let Some(contract_ids) = &filter.contract_ids {
+                query.push(" AND contract_id IN (");
+                let mut separated = query.separated(", ");
+                for contract_id in contract_ids {
+                    separated.push_bind(contract_id.clone());
+                }
+                separated.push_unseparated(")");
+            }
+            if let Some(event_types) = &filter.event_types {
+                query.push(" AND event_type IN (");
+                let mut separated = query.separated(", ");
+                for event_type in event_types {
+                    separated.push_bind(event_type.clone());
+                }
+                separated.push_unseparated(")");
+            }
+            if let Some(network) = &filter.network {
+                query.push(" AND network = ");
+                query.push_bind(network.clone());
+            }
 
-        query.push_str(" ORDER BY ledger_sequence ASC, id ASC");
+            query.push(" ORDER BY ledger_sequence ASC, id ASC");
 
-        if let Some(lim) = limit {
-            query.push_str(&format!(" LIMIT {}", lim));
-        }
+            if let Some(lim) = limit {
+                query.push(format!(" LIMIT {}", lim));
+            }
 
-        let mut query_builder = sqlx::query_as::<
-            _,
-            (
-                String,
-                i64,
-                String,
-                String,
-                String,
-                String,
-                DateTime<Utc>,
-                String,
-            ),
-        >(&query)
-        .bind(start_ledger as i64)
-        .bind(end_ledger as i64);
-
-        // Bind filter parameters (simplified - in production, use proper parameter binding)
-        let rows = query_builder.fetch_all(&self.pool).await?;
+            query.build_query_as().fetch_all(pool).await
+        })?;
 
         let events = rows
             .into_iter()
@@ -140,40 +159,176 @@ impl EventStorage {
         end_ledger: u64,
         filter: &EventFilter,
     ) -> Result<u64> {
-        let count: i64 = sqlx::query_scalar(
-            r#"
-            SELECT COUNT(*)
-            FROM contract_events
-            WHERE ledger_sequence >= $1 AND ledger_sequence <= $2
-            "#,
-        )
-        .bind(start_ledger as i64)
-        .bind(end_ledger as i64)
-        .fetch_one(&self.pool)
-        .await?;
+        let count: i64 = with_pool!(&self.pool, |pool| {
+            sqlx::query_scalar(
+                r#"
+                SELECT COUNT(*)
+                FROM contract_events
+                WHERE ledger_sequence >= $1 AND ledger_sequence <= $2
+                "#,
+            )
+            .bind(start_ledger as i64)
+            .bind(end_ledger as i64)
+            .fetch_one(pool)
+            .await
+        })?;
 
         Ok(count as u64)
     }
 
+    /// Ingested ledgers and their payments in a ledger range, as events with
+    /// `contract_id` [`LEDGER_EVENT_SOURCE`]. Each ledger yields a
+    /// [`LEDGER_CLOSED_EVENT`] followed by one [`PAYMENT_EVENT`] per payment.
+    pub async fn get_ledger_events_in_range(
+        &self,
+        start_ledger: u64,
+        end_ledger: u64,
+        filter: &EventFilter,
+    ) -> Result<Vec<ContractEvent>> {
+        debug!(
+            "Fetching ledger events from ledger {} to {}",
+            start_ledger, end_ledger
+        );
+
+        // Ledgers are only ingested from the configured network
+        let network = filter.network.clone().unwrap_or_default();
+
+        let ledgers: Vec<(i64, String, DateTime<Utc>, i64, i64)> = with_pool!(&self.pool, |pool| {
+            sqlx::query_as(
+                r#"
+                SELECT sequence, hash, close_time,
+                       CAST(COALESCE(transaction_count, 0) AS BIGINT),
+                       CAST(COALESCE(operation_count, 0) AS BIGINT)
+                FROM ledgers
+                WHERE sequence >= $1 AND sequence <= $2
+                ORDER BY sequence ASC
+                "#,
+            )
+            .bind(start_ledger as i64)
+            .bind(end_ledger as i64)
+            .fetch_all(pool)
+            .await
+        })
+        .context("Failed to fetch ledgers")?;
+
+        let payments: Vec<LedgerPaymentEventRow> = with_pool!(&self.pool, |pool| {
+            sqlx::query_as(
+                r#"
+                SELECT lp.id, lp.ledger_sequence, lp.transaction_hash, lp.operation_type,
+                       lp.source_account, lp.destination, lp.asset_code, lp.asset_issuer,
+                       lp.amount, COALESCE(t.successful, TRUE) AS successful
+                FROM ledger_payments lp
+                LEFT JOIN transactions t ON t.hash = lp.transaction_hash
+                WHERE lp.ledger_sequence >= $1 AND lp.ledger_sequence <= $2
+                ORDER BY lp.ledger_sequence ASC, lp.id ASC
+                "#,
+            )
+            .bind(start_ledger as i64)
+            .bind(end_ledger as i64)
+            .fetch_all(pool)
+            .await
+        })
+        .context("Failed to fetch ledger payments")?;
+
+        let mut payments = payments.into_iter().peekable();
+        let mut events = Vec::new();
+
+        for (sequence, hash, close_time, transaction_count, operation_count) in ledgers {
+            events.push(ContractEvent {
+                id: format!("ledger:{}", sequence),
+                ledger_sequence: sequence as u64,
+                transaction_hash: hash.clone(),
+                contract_id: LEDGER_EVENT_SOURCE.to_string(),
+                event_type: LEDGER_CLOSED_EVENT.to_string(),
+                data: serde_json::json!({
+                    "sequence": sequence,
+                    "hash": hash,
+                    "close_time": close_time,
+                    "transaction_count": transaction_count,
+                    "operation_count": operation_count,
+                }),
+                timestamp: close_time,
+                network: network.clone(),
+            });
+
+            // Payments of ledgers that are not stored have no close time and
+            // are dropped along with them
+            while let Some(payment) = payments.next_if(|p| p.ledger_sequence <= sequence) {
+                if payment.ledger_sequence < sequence {
+                    continue;
+                }
+                events.push(ContractEvent {
+                    id: format!("payment:{}", payment.id),
+                    ledger_sequence: sequence as u64,
+                    transaction_hash: payment.transaction_hash.clone(),
+                    contract_id: LEDGER_EVENT_SOURCE.to_string(),
+                    event_type: PAYMENT_EVENT.to_string(),
+                    data: serde_json::json!({
+                        "operation_type": payment.operation_type,
+                        "source_account": payment.source_account,
+                        "destination": payment.destination,
+                        "asset_code": payment.asset_code,
+                        "asset_issuer": payment.asset_issuer,
+                        "amount": payment.amount,
+                        "successful": payment.successful,
+                    }),
+                    timestamp: close_time,
+                    network: network.clone(),
+                });
+            }
+        }
+
+        Ok(events
+            .into_iter()
+            .filter(|event| event.matches_filter(filter))
+            .collect())
+    }
+
+    /// Get the latest ingested ledger
+    pub async fn get_latest_ingested_ledger(&self) -> Result<Option<u64>> {
+        let ledger: Option<i64> = with_pool!(&self.pool, |pool| {
+            sqlx::query_scalar("SELECT MAX(sequence) FROM ledgers")
+                .fetch_one(pool)
+                .await
+        })?;
+
+        Ok(ledger.map(|l| l as u64))
+    }
+
     /// Get the latest ledger with events
     pub async fn get_latest_ledger(&self) -> Result<Option<u64>> {
-        let ledger: Option<i64> =
+        let ledger: Option<i64> = with_pool!(&self.pool, |pool| {
             sqlx::query_scalar("SELECT MAX(ledger_sequence) FROM contract_events")
-                .fetch_optional(&self.pool)
-                .await?;
+                .fetch_optional(pool)
+                .await
+        })?;
 
         Ok(ledger.map(|l| l as u64))
     }
 }
 
+#[derive(Debug, sqlx::FromRow)]
+struct LedgerPaymentEventRow {
+    id: i64,
+    ledger_sequence: i64,
+    transaction_hash: String,
+    operation_type: Option<String>,
+    source_account: Option<String>,
+    destination: Option<String>,
+    asset_code: Option<String>,
+    asset_issuer: Option<String>,
+    amount: Option<String>,
+    successful: bool,
+}
+
 /// Storage for replay metadata and state
 pub struct ReplayStorage {
-    pool: SqlitePool,
+    pool: DbPool,
 }
 
 impl ReplayStorage {
     /// Create a new replay storage
-    pub fn new(pool: SqlitePool) -> Self {
+    pub fn new(pool: DbPool) -> Self {
         Self { pool }
     }
 
@@ -185,26 +340,29 @@ impl ReplayStorage {
         let status_json = serde_json::to_string(&metadata.status)?;
         let checkpoint_json = serde_json::to_string(&metadata.checkpoint)?;
 
-        sqlx::query(
-            r#"
-            INSERT INTO replay_sessions (
-                session_id, config, status, started_at, ended_at, checkpoint
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO replay_sessions (
+                    session_id, config, status, started_at, ended_at, checkpoint
+                )
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (session_id) DO UPDATE SET
+                    status = EXCLUDED.status,
+                    ended_at = EXCLUDED.ended_at,
+                    checkpoint = EXCLUDED.checkpoint
+                "#,
             )
-            VALUES ($1, $2, $3, $4, $5, $6)
-            ON CONFLICT (session_id) DO UPDATE SET
-                status = EXCLUDED.status,
-                ended_at = EXCLUDED.ended_at,
-                checkpoint = EXCLUDED.checkpoint
-            "#,
-        )
-        .bind(&metadata.session_id)
-        .bind(&config_json)
-        .bind(&status_json)
-        .bind(metadata.started_at)
-        .bind(metadata.ended_at)
-        .bind(&checkpoint_json)
-        .execute(&self.pool)
-        .await
+            .bind(&metadata.session_id)
+            .bind(&config_json)
+            .bind(&status_json)
+            .bind(metadata.started_at)
+            .bind(metadata.ended_at)
+            .bind(&checkpoint_json)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .context("Failed to save replay metadata")?;
 
         Ok(())
@@ -219,16 +377,18 @@ impl ReplayStorage {
             DateTime<Utc>,
             Option<DateTime<Utc>>,
             String,
-        )> = sqlx::query_as(
-            r#"
-                SELECT session_id, config, status, started_at, ended_at, checkpoint
-                FROM replay_sessions
-                WHERE session_id = $1
-                "#,
-        )
-        .bind(session_id)
-        .fetch_optional(&self.pool)
-        .await?;
+        )> = with_pool!(&self.pool, |pool| {
+            sqlx::query_as(
+                r#"
+                    SELECT session_id, config, status, started_at, ended_at, checkpoint
+                    FROM replay_sessions
+                    WHERE session_id = $1
+                    "#,
+            )
+            .bind(session_id)
+            .fetch_optional(pool)
+            .await
+        })?;
 
         match row {
             Some((session_id, config_json, status_json, started_at, ended_at, checkpoint_json)) => {
@@ -270,7 +430,9 @@ impl ReplayStorage {
             DateTime<Utc>,
             Option<DateTime<Utc>>,
             String,
-        )> = sqlx::query_as(&query).fetch_all(&self.pool).await?;
+        )> = with_pool!(&self.pool, |pool| {
+            sqlx::query_as(&query).fetch_all(pool).await
+        })?;
 
         let sessions = rows
             .into_iter()
@@ -295,14 +457,78 @@ impl ReplayStorage {
         Ok(sessions)
     }
 
+    /// Save the report of a verification replay
+    pub async fn save_verification_report(&self, report: &VerificationReport) -> Result<()> {
+        info!(
+            "Saving verification report for session {}",
+            report.session_id
+        );
+
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO replay_verification_reports (
+                    session_id, start_ledger, end_ledger, rows_compared, rows_mismatched,
+                    first_diverging_ledger, report, created_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT (session_id) DO UPDATE SET
+                    start_ledger = EXCLUDED.start_ledger,
+                    end_ledger = EXCLUDED.end_ledger,
+                    rows_compared = EXCLUDED.rows_compared,
+                    rows_mismatched = EXCLUDED.rows_mismatched,
+                    first_diverging_ledger = EXCLUDED.first_diverging_ledger,
+                    report = EXCLUDED.report,
+                    created_at = EXCLUDED.created_at
+                "#,
+            )
+            .bind(&report.session_id)
+            .bind(report.start_ledger as i64)
+            .bind(report.end_ledger as i64)
+            .bind(report.rows_compared as i64)
+            .bind(report.rows_mismatched as i64)
+            .bind(report.first_diverging_ledger.map(|l| l as i64))
+            .bind(serde_json::to_string(report)?)
+            .bind(report.generated_at)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
+        .context("Failed to save verification report")?;
+
+        Ok(())
+    }
+
+    /// Load the report of a verification replay
+    pub async fn load_verification_report(
+        &self,
+        session_id: &str,
+    ) -> Result<Option<VerificationReport>> {
+        let report: Option<String> = with_pool!(&self.pool, |pool| {
+            sqlx::query_scalar(
+                "SELECT report FROM replay_verification_reports WHERE session_id = $1",
+            )
+            .bind(session_id)
+            .fetch_optional(pool)
+            .await
+        })?;
+
+        report
+            .map(|json| serde_json::from_str(&json).context("Invalid verification report"))
+            .transpose()
+    }
+
     /// Delete replay session
     pub async fn delete_session(&self, session_id: &str) -> Result<()> {
         info!("Deleting replay session {}", session_id);
 
-        sqlx::query("DELETE FROM replay_sessions WHERE session_id = $1")
-            .bind(session_id)
-            .execute(&self.pool)
-            .await?;
+        with_pool!(&self.pool, |pool| {
+            sqlx::query("DELETE FROM replay_sessions WHERE session_id = $1")
+                .bind(session_id)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+        })?;
 
         Ok(())
     }
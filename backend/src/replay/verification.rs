@@ -0,0 +1,690 @@
+//! Replay Verification
+//!
+//! A `ReplayMode::Verification` replay never writes to the live tables.
+//! Processors recompute the derived rows of every bucket in the range into the
+//! shadow state held by the `StateBuilder`, and the shadow state is then
+//! diffed against what the live tables contain. The resulting report lists
+//! each mismatched row, how far its columns drifted and the first ledger at
+//! which the stored data diverges from a clean rebuild.
+
+use crate::database::DbPool;
+use crate::with_pool;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::{BTreeMap, HashMap};
+use tracing::info;
+
+use super::state_builder::ApplicationState;
+use super::EventFilter;
+
+/// Values differing by no more than this fraction of their magnitude match
+pub const DRIFT_TOLERANCE: f64 = 1e-9;
+
+/// Mismatches beyond this many are counted but not listed in a report
+pub const MAX_REPORTED_MISMATCHES: usize = 500;
+
+/// Numeric column values of a derived row, by column name
+pub type DerivedValues = BTreeMap<String, Option<f64>>;
+
+/// Rows recomputed for one time bucket of a derived table
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DerivedBucket {
+    /// Derived table the rows belong to
+    pub table: String,
+    /// Start of the bucket (inclusive)
+    pub start: DateTime<Utc>,
+    /// End of the bucket (exclusive)
+    pub end: DateTime<Utc>,
+    /// Rows by key (corridor key, anchor ID or snapshot ID)
+    pub rows: BTreeMap<String, DerivedValues>,
+}
+
+impl DerivedBucket {
+    pub fn new(table: &str, start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        Self {
+            table: table.to_string(),
+            start,
+            end,
+            rows: BTreeMap::new(),
+        }
+    }
+
+    /// Add a row from `(column, value)` pairs
+    pub fn with_row(mut self, key: String, values: &[(&str, Option<f64>)]) -> Self {
+        let values = values
+            .iter()
+            .map(|(column, value)| (column.to_string(), *value))
+            .collect();
+        self.rows.insert(key, values);
+        self
+    }
+
+    /// Key of the bucket within the shadow state
+    pub fn state_key(&self) -> String {
+        format!("{}@{}", self.table, self.start.to_rfc3339())
+    }
+}
+
+/// How a derived table stores its rows
+struct DerivedTable {
+    name: &'static str,
+    /// SQL expression identifying a row within a bucket
+    key: &'static str,
+    time_column: &'static str,
+    /// Whether `time_column` holds RFC 3339 text rather than a timestamp
+    time_is_text: bool,
+    /// Columns recomputed by replay and compared against the live table
+    columns: &'static [&'static str],
+}
+
+const DERIVED_TABLES: &[DerivedTable] = &[
+    DerivedTable {
+        name: "corridor_metrics_hourly",
+        key: "corridor_key",
+        time_column: "hour_bucket",
+        time_is_text: true,
+        columns: &[
+            "total_transactions",
+            "successful_transactions",
+            "failed_transactions",
+            "success_rate",
+            "volume_usd",
+            "avg_settlement_latency_ms",
+            "liquidity_depth_usd",
+        ],
+    },
+    DerivedTable {
+        name: "corridor_metrics",
+        key: "corridor_key",
+        time_column: "date",
+        time_is_text: false,
+        columns: &[
+            "total_transactions",
+            "successful_transactions",
+            "failed_transactions",
+            "success_rate",
+            "volume_usd",
+        ],
+    },
+    DerivedTable {
+        name: "anchor_metrics_history",
+        key: "anchor_id",
+        time_column: "timestamp",
+        time_is_text: false,
+        columns: &[
+            "success_rate",
+            "failure_rate",
+            "reliability_score",
+            "total_transactions",
+            "successful_transactions",
+            "failed_transactions",
+            "avg_settlement_time_ms",
+            "volume_usd",
+        ],
+    },
+    DerivedTable {
+        name: "liquidity_pool_snapshots",
+        key: "CAST(id AS TEXT)",
+        time_column: "snapshot_at",
+        time_is_text: false,
+        columns: &["total_value_usd", "fees_usd", "apy", "impermanent_loss_pct"],
+    },
+];
+
+/// How a row differs between the shadow state and the live table
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MismatchKind {
+    /// Replay produced the row but the live table lacks it
+    Missing,
+    /// The live table holds a row replay did not produce
+    Unexpected,
+    /// Both hold the row with different values
+    Drifted,
+}
+
+/// A column whose live value differs from the replayed one
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ColumnDrift {
+    pub column: String,
+    /// Value produced by replay
+    pub expected: serde_json::Value,
+    /// Value found in the live table
+    pub actual: serde_json::Value,
+    /// Absolute difference, for numeric columns (a missing value counts as 0)
+    pub drift: Option<f64>,
+}
+
+/// A row that differs between the shadow state and the live table
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RowMismatch {
+    pub table: String,
+    pub key: String,
+    /// Start of the time bucket holding the row
+    pub bucket: Option<DateTime<Utc>>,
+    pub kind: MismatchKind,
+    /// First ledger contributing to the row
+    pub first_ledger: Option<u64>,
+    pub columns: Vec<ColumnDrift>,
+}
+
+/// Per-table summary of a verification
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TableDrift {
+    pub table: String,
+    pub rows_compared: u64,
+    pub missing: u64,
+    pub unexpected: u64,
+    pub drifted: u64,
+    /// Largest absolute drift per column
+    pub max_drift: BTreeMap<String, f64>,
+    /// Sum of absolute drift per column
+    pub total_drift: BTreeMap<String, f64>,
+}
+
+impl TableDrift {
+    pub fn mismatched(&self) -> u64 {
+        self.missing + self.unexpected + self.drifted
+    }
+}
+
+/// Outcome of diffing a verification replay against the live tables
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VerificationReport {
+    pub session_id: String,
+    pub start_ledger: u64,
+    pub end_ledger: u64,
+    pub generated_at: DateTime<Utc>,
+    pub rows_compared: u64,
+    pub rows_mismatched: u64,
+    /// Earliest ledger contributing to a mismatched row
+    pub first_diverging_ledger: Option<u64>,
+    pub tables: Vec<TableDrift>,
+    /// Mismatched rows, ordered by ledger and capped at
+    /// [`MAX_REPORTED_MISMATCHES`]
+    pub mismatches: Vec<RowMismatch>,
+    /// Whether mismatches were left out of the list
+    pub truncated: bool,
+}
+
+impl VerificationReport {
+    /// Whether the live tables match a clean rebuild
+    pub fn is_consistent(&self) -> bool {
+        self.rows_mismatched == 0
+    }
+}
+
+/// Absolute drift between two values, or `None` when they match
+fn column_drift(expected: Option<f64>, actual: Option<f64>) -> Option<f64> {
+    match (expected, actual) {
+        (None, None) => None,
+        (Some(a), Some(b)) => {
+            let scale = a.abs().max(b.abs()).max(1.0);
+            let drift = (a - b).abs();
+            (drift > DRIFT_TOLERANCE * scale).then_some(drift)
+        }
+        (value, None) | (None, value) => Some(value.unwrap_or_default().abs()),
+    }
+}
+
+fn json_value(value: Option<f64>) -> serde_json::Value {
+    value.map_or(serde_json::Value::Null, serde_json::Value::from)
+}
+
+/// Columns differing between an expected and a live row. A row absent on
+/// either side is passed as empty, so all of its columns are reported.
+fn diff_values(expected: &DerivedValues, actual: &DerivedValues) -> Vec<ColumnDrift> {
+    let mut columns: Vec<&String> = expected.keys().chain(actual.keys()).collect();
+    columns.sort();
+    columns.dedup();
+
+    columns
+        .into_iter()
+        .filter_map(|column| {
+            let expected = expected.get(column).copied().flatten();
+            let actual = actual.get(column).copied().flatten();
+            column_drift(expected, actual).map(|drift| ColumnDrift {
+                column: column.clone(),
+                expected: json_value(expected),
+                actual: json_value(actual),
+                drift: Some(drift),
+            })
+        })
+        .collect()
+}
+
+/// Compare the rows of one bucket, returning each mismatched key
+fn diff_rows(
+    expected: &BTreeMap<String, DerivedValues>,
+    live: &[(String, DerivedValues)],
+) -> Vec<(String, MismatchKind, Vec<ColumnDrift>)> {
+    let empty = DerivedValues::new();
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    let mut mismatches = Vec::new();
+
+    for (key, actual) in live {
+        let occurrence = seen.entry(key.as_str()).or_default();
+        *occurrence += 1;
+
+        match expected.get(key) {
+            // Duplicates of a key are never produced by a rebuild
+            Some(_) if *occurrence > 1 => mismatches.push((
+                key.clone(),
+                MismatchKind::Unexpected,
+                diff_values(&empty, actual),
+            )),
+            Some(values) => {
+                let columns = diff_values(values, actual);
+                if !columns.is_empty() {
+                    mismatches.push((key.clone(), MismatchKind::Drifted, columns));
+                }
+            }
+            None => mismatches.push((
+                key.clone(),
+                MismatchKind::Unexpected,
+                diff_values(&empty, actual),
+            )),
+        }
+    }
+
+    for (key, values) in expected {
+        if !seen.contains_key(key.as_str()) {
+            mismatches.push((
+                key.clone(),
+                MismatchKind::Missing,
+                diff_values(values, &empty),
+            ));
+        }
+    }
+
+    mismatches
+}
+
+/// Collects mismatches into a report
+struct ReportBuilder {
+    report: VerificationReport,
+    tables: BTreeMap<String, TableDrift>,
+}
+
+impl ReportBuilder {
+    fn new(session_id: &str, start_ledger: u64, end_ledger: u64) -> Self {
+        Self {
+            report: VerificationReport {
+                session_id: session_id.to_string(),
+                start_ledger,
+                end_ledger,
+                generated_at: Utc::now(),
+                rows_compared: 0,
+                rows_mismatched: 0,
+                first_diverging_ledger: None,
+                tables: Vec::new(),
+                mismatches: Vec::new(),
+                truncated: false,
+            },
+            tables: BTreeMap::new(),
+        }
+    }
+
+    fn table(&mut self, table: &str) -> &mut TableDrift {
+        self.tables
+            .entry(table.to_string())
+            .or_insert_with(|| TableDrift {
+                table: table.to_string(),
+                ..Default::default()
+            })
+    }
+
+    fn compared(&mut self, table: &str, rows: usize) {
+        self.table(table).rows_compared += rows as u64;
+    }
+
+    fn record(&mut self, mismatch: RowMismatch) {
+        let summary = self.table(&mismatch.table);
+        match mismatch.kind {
+            MismatchKind::Missing => summary.missing += 1,
+            MismatchKind::Unexpected => summary.unexpected += 1,
+            MismatchKind::Drifted => summary.drifted += 1,
+        }
+        for column in &mismatch.columns {
+            let Some(drift) = column.drift else {
+                continue;
+            };
+            let max = summary.max_drift.entry(column.column.clone()).or_default();
+            *max = max.max(drift);
+            *summary
+                .total_drift
+                .entry(column.column.clone())
+                .or_default() += drift;
+        }
+
+        if let Some(ledger) = mismatch.first_ledger {
+            let first = self.report.first_diverging_ledger.get_or_insert(ledger);
+            *first = (*first).min(ledger);
+        }
+        self.report.mismatches.push(mismatch);
+    }
+
+    fn finish(mut self) -> VerificationReport {
+        let mut report = self.report;
+        report.tables = std::mem::take(&mut self.tables).into_values().collect();
+        report.rows_compared = report.tables.iter().map(|t| t.rows_compared).sum();
+        report.rows_mismatched = report.tables.iter().map(TableDrift::mismatched).sum();
+
+        report
+            .mismatches
+            .sort_by_key(|m| (m.first_ledger.unwrap_or(u64::MAX), m.bucket));
+        report.truncated = report.mismatches.len() > MAX_REPORTED_MISMATCHES;
+        report.mismatches.truncate(MAX_REPORTED_MISMATCHES);
+        report
+    }
+}
+
+/// Live rows of a derived table within one bucket, in storage order
+async fn load_live_rows(
+    pool: &DbPool,
+    table: &DerivedTable,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<(String, DerivedValues)>> {
+    let columns: Vec<String> = table
+        .columns
+        .iter()
+        .map(|column| format!("CAST({column} AS DOUBLE PRECISION) AS {column}"))
+        .collect();
+    let query = format!(
+        "SELECT {} AS row_key, {} FROM {} WHERE {t} >= $1 AND {t} < $2 ORDER BY row_key",
+        table.key,
+        columns.join(", "),
+        table.name,
+        t = table.time_column
+    );
+
+    with_pool!(pool, |pool| {
+        let query = if table.time_is_text {
+            sqlx::query(&query)
+                .bind(start.to_rfc3339())
+                .bind(end.to_rfc3339())
+        } else {
+            sqlx::query(&query).bind(start).bind(end)
+        };
+        let rows = query
+            .fetch_all(pool)
+            .await
+            .with_context(|| format!("Failed to load live {} rows", table.name))?;
+
+        rows.iter()
+            .map(|row| {
+                let key: String = row.try_get("row_key")?;
+                let mut values = DerivedValues::new();
+                for column in table.columns {
+                    values.insert(column.to_string(), row.try_get(*column)?);
+                }
+                Ok((key, values))
+            })
+            .collect()
+    })
+}
+
+/// First stored ledger closed within `[start, end)`
+async fn first_ledger_between(
+    pool: &DbPool,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Option<u64>> {
+    let first: Option<i64> = with_pool!(pool, |pool| {
+        sqlx::query_scalar(
+            "SELECT MIN(sequence) FROM ledgers WHERE close_time >= $1 AND close_time < $2",
+        )
+        .bind(start)
+        .bind(end)
+        .fetch_one(pool)
+        .await
+    })
+    .context("Failed to look up first ledger of bucket")?;
+
+    Ok(first.map(|sequence| sequence as u64))
+}
+
+/// Whether a replay with this filter sees every snapshot submission
+fn covers_snapshots(filter: &EventFilter) -> bool {
+    filter.contract_ids.is_none()
+        && filter
+            .event_types
+            .as_ref()
+            .is_none_or(|types| types.iter().any(|t| t == "snapshot_submitted"))
+}
+
+/// Compare replayed snapshot submissions with the `snapshots` table
+async fn diff_snapshots(
+    pool: &DbPool,
+    state: &ApplicationState,
+    start_ledger: u64,
+    end_ledger: u64,
+    filter: &EventFilter,
+    builder: &mut ReportBuilder,
+) -> Result<()> {
+    let live: Vec<(i64, String, Option<i64>)> = with_pool!(pool, |pool| {
+        sqlx::query_as(
+            "SELECT epoch, hash, ledger_sequence FROM snapshots WHERE ledger_sequence >= $1 AND ledger_sequence <= $2",
+        )
+        .bind(start_ledger as i64)
+        .bind(end_ledger as i64)
+        .fetch_all(pool)
+        .await
+    })
+    .context("Failed to load live snapshots")?;
+    let live: HashMap<u64, (String, Option<i64>)> = live
+        .into_iter()
+        .map(|(epoch, hash, ledger)| (epoch as u64, (hash, ledger)))
+        .collect();
+
+    let mut compared = 0;
+    let mut epochs: Vec<&u64> = state.snapshots.keys().collect();
+    epochs.sort();
+    for epoch in epochs {
+        let snapshot = &state.snapshots[epoch];
+        if snapshot.ledger < start_ledger || snapshot.ledger > end_ledger {
+            continue;
+        }
+        compared += 1;
+
+        let (kind, actual) = match live.get(epoch) {
+            Some((hash, _)) if *hash == snapshot.hash => continue,
+            Some((hash, _)) => (MismatchKind::Drifted, serde_json::json!(hash)),
+            // Submitted before the range, so the live row is outside the window
+            None => {
+                let stored: Option<String> = with_pool!(pool, |pool| {
+                    sqlx::query_scalar("SELECT hash FROM snapshots WHERE epoch = $1")
+                        .bind(*epoch as i64)
+                        .fetch_optional(pool)
+                        .await
+                })?;
+                match stored {
+                    Some(hash) if hash == snapshot.hash => continue,
+                    Some(hash) => (MismatchKind::Drifted, serde_json::json!(hash)),
+                    None => (MismatchKind::Missing, serde_json::Value::Null),
+                }
+            }
+        };
+        builder.record(RowMismatch {
+            table: "snapshots".to_string(),
+            key: epoch.to_string(),
+            bucket: None,
+            kind,
+            first_ledger: Some(snapshot.ledger),
+            columns: vec![ColumnDrift {
+                column: "hash".to_string(),
+                expected: serde_json::json!(snapshot.hash),
+                actual,
+                drift: None,
+            }],
+        });
+    }
+
+    if covers_snapshots(filter) {
+        let mut extra: Vec<(&u64, &(String, Option<i64>))> = live
+            .iter()
+            .filter(|(epoch, _)| !state.snapshots.contains_key(epoch))
+            .collect();
+        extra.sort();
+        for (epoch, (hash, ledger)) in extra {
+            compared += 1;
+            builder.record(RowMismatch {
+                table: "snapshots".to_string(),
+                key: epoch.to_string(),
+                bucket: None,
+                kind: MismatchKind::Unexpected,
+                first_ledger: ledger.map(|l| l as u64),
+                columns: vec![ColumnDrift {
+                    column: "hash".to_string(),
+                    expected: serde_json::Value::Null,
+                    actual: serde_json::json!(hash),
+                    drift: None,
+                }],
+            });
+        }
+    }
+
+    if compared > 0 {
+        builder.compared("snapshots", compared);
+    }
+    Ok(())
+}
+
+/// Diff the shadow state of a verification replay over
+/// `start_ledger..=end_ledger` against the live tables
+pub async fn diff_state(
+    pool: &DbPool,
+    state: &ApplicationState,
+    session_id: &str,
+    start_ledger: u64,
+    end_ledger: u64,
+    filter: &EventFilter,
+) -> Result<VerificationReport> {
+    let mut builder = ReportBuilder::new(session_id, start_ledger, end_ledger);
+
+    for bucket in state.derived.values() {
+        let table = DERIVED_TABLES
+            .iter()
+            .find(|t| t.name == bucket.table)
+            .with_context(|| format!("No verification layout for table {}", bucket.table))?;
+
+        let live = load_live_rows(pool, table, bucket.start, bucket.end).await?;
+        let mismatches = diff_rows(&bucket.rows, &live);
+        let compared = bucket.rows.len()
+            + mismatches
+                .iter()
+                .filter(|(_, kind, _)| *kind == MismatchKind::Unexpected)
+                .count();
+        builder.compared(table.name, compared);
+
+        if mismatches.is_empty() {
+            continue;
+        }
+        let first_ledger = first_ledger_between(pool, bucket.start, bucket.end).await?;
+        for (key, kind, columns) in mismatches {
+            builder.record(RowMismatch {
+                table: table.name.to_string(),
+                key,
+                bucket: Some(bucket.start),
+                kind,
+                first_ledger,
+                columns,
+            });
+        }
+    }
+
+    diff_snapshots(pool, state, start_ledger, end_ledger, filter, &mut builder).await?;
+
+    let report = builder.finish();
+    info!(
+        "Verified {} rows for ledgers {} to {}: {} mismatched (first diverging ledger: {:?})",
+        report.rows_compared,
+        start_ledger,
+        end_ledger,
+        report.rows_mismatched,
+        report.first_diverging_ledger
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(&str, Option<f64>)]) -> DerivedValues {
+        pairs.iter().map(|(c, v)| (c.to_string(), *v)).collect()
+    }
+
+    #[test]
+    fn test_column_drift_tolerance() {
+        assert_eq!(column_drift(Some(100.0), Some(100.0 + 1e-12)), None);
+        assert_eq!(column_drift(Some(100.0), Some(97.5)), Some(2.5));
+        assert_eq!(column_drift(None, None), None);
+        assert_eq!(column_drift(Some(-4.0), None), Some(4.0));
+    }
+
+    #[test]
+    fn test_diff_rows_classifies_mismatches() {
+        let mut expected = BTreeMap::new();
+        expected.insert("a".to_string(), values(&[("volume_usd", Some(10.0))]));
+        expected.insert("b".to_string(), values(&[("volume_usd", Some(5.0))]));
+        expected.insert("c".to_string(), values(&[("volume_usd", Some(1.0))]));
+        let live = vec![
+            ("a".to_string(), values(&[("volume_usd", Some(10.0))])),
+            ("b".to_string(), values(&[("volume_usd", Some(7.0))])),
+            ("b".to_string(), values(&[("volume_usd", Some(5.0))])),
+            ("z".to_string(), values(&[("volume_usd", None)])),
+        ];
+
+        let mismatches = diff_rows(&expected, &live);
+        let kinds: Vec<(&str, MismatchKind)> = mismatches
+            .iter()
+            .map(|(key, kind, _)| (key.as_str(), *kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("b", MismatchKind::Drifted),
+                ("b", MismatchKind::Unexpected),
+                ("z", MismatchKind::Unexpected),
+                ("c", MismatchKind::Missing),
+            ]
+        );
+        assert_eq!(mismatches[0].2[0].drift, Some(2.0));
+        // A row with nothing but NULLs still counts as unexpected
+        assert!(mismatches[2].2.is_empty());
+    }
+
+    #[test]
+    fn test_report_tracks_first_diverging_ledger_and_drift() {
+        let mut builder = ReportBuilder::new("session", 100, 200);
+        builder.compared("corridor_metrics", 3);
+        for (ledger, drift) in [(150, 2.0), (120, 0.5)] {
+            builder.record(RowMismatch {
+                table: "corridor_metrics".to_string(),
+                key: format!("k{}", ledger),
+                bucket: None,
+                kind: MismatchKind::Drifted,
+                first_ledger: Some(ledger),
+                columns: vec![ColumnDrift {
+                    column: "volume_usd".to_string(),
+                    expected: serde_json::json!(0.0),
+                    actual: serde_json::json!(drift),
+                    drift: Some(drift),
+                }],
+            });
+        }
+
+        let report = builder.finish();
+        assert!(!report.is_consistent());
+        assert_eq!(report.rows_compared, 3);
+        assert_eq!(report.rows_mismatched, 2);
+        assert_eq!(report.first_diverging_ledger, Some(120));
+        assert_eq!(report.mismatches[0].key, "k120");
+        assert_eq!(report.tables[0].max_drift["volume_usd"], 2.0);
+        assert_eq!(report.tables[0].total_drift["volume_usd"], 2.5);
+    }
+}
@@ -37,6 +37,15 @@ enum CircuitState {
     HalfOpen { success_count: u32 },
 }
 
+/// Externally visible circuit state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
 /// Circuit breaker for a single logical endpoint (e.g. Horizon API).
 #[derive(Clone)]
 pub struct CircuitBreaker {
@@ -81,6 +90,22 @@ impl CircuitBreaker {
         result
     }
 
+    /// Current state without side effects. An open circuit whose timeout has
+    /// elapsed is reported as half-open since the next call will be let through.
+    pub async fn state(&self) -> BreakerState {
+        match &*self.state.lock().await {
+            CircuitState::Closed { .. } => BreakerState::Closed,
+            CircuitState::HalfOpen { .. } => BreakerState::HalfOpen,
+            CircuitState::Open { opened_at } => {
+                if opened_at.elapsed() >= self.config.timeout_duration {
+                    BreakerState::HalfOpen
+                } else {
+                    BreakerState::Open
+                }
+            }
+        }
+    }
+
     async fn is_open(&self) -> bool {
         let mut state = self.state.lock().await;
         let now = Instant::now();
@@ -99,7 +124,7 @@ impl CircuitBreaker {
         }
     }
 
-    async fn on_success(&self) {
+    pub(crate) async fn on_success(&self) {
         let mut state = self.state.lock().await;
         let current = std::mem::replace(&mut *state, CircuitState::Closed { failure_count: 0 });
         *state = match current {
@@ -120,7 +145,7 @@ impl CircuitBreaker {
         };
     }
 
-    async fn on_failure(&self) {
+    pub(crate) async fn on_failure(&self) {
         let mut state = self.state.lock().await;
         let current = std::mem::replace(&mut *state, CircuitState::Closed { failure_count: 0 });
         *state = match current {
@@ -204,6 +229,26 @@ mod tests {
         assert_eq!(r.unwrap(), 42);
     }
 
+    #[tokio::test]
+    async fn state_reports_open_then_half_open() {
+        let config = CircuitBreakerConfig {
+            failure_threshold: 1,
+            success_threshold: 1,
+            timeout_duration: Duration::from_millis(10),
+            half_open_max_calls: 1,
+        };
+        let cb = CircuitBreaker::new(config, "test");
+        assert_eq!(cb.state().await, BreakerState::Closed);
+
+        let _: Result<(), _> = cb
+            .call(|| async { Err(RpcError::NetworkError("down".into())) })
+            .await;
+        assert_eq!(cb.state().await, BreakerState::Open);
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(cb.state().await, BreakerState::HalfOpen);
+    }
+
     #[tokio::test]
     async fn circuit_closes_after_successes_in_half_open() {
         let config = CircuitBreakerConfig {
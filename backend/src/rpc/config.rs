@@ -51,3 +51,19 @@ pub fn max_backoff_from_env() -> Duration {
         .unwrap_or(5000);
     Duration::from_millis(ms)
 }
+
+/// Provider URLs for a pool: `primary` followed by the comma-separated
+/// fallbacks in `fallback_var` (e.g. STELLAR_HORIZON_FALLBACK_URLS_MAINNET).
+pub fn provider_urls_from_env(primary: String, fallback_var: &str) -> Vec<String> {
+    let mut urls = vec![primary];
+    if let Ok(fallbacks) = std::env::var(fallback_var) {
+        urls.extend(
+            fallbacks
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string),
+        );
+    }
+    urls
+}
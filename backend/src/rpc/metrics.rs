@@ -1,7 +1,10 @@
 //! Prometheus metrics for RPC error rates and circuit breaker state.
 
 use lazy_static::lazy_static;
-use prometheus::{register_int_counter_vec, register_int_gauge_vec, IntCounterVec, IntGaugeVec};
+use prometheus::{
+    register_histogram_vec, register_int_counter_vec, register_int_gauge_vec, HistogramVec,
+    IntCounterVec, IntGaugeVec,
+};
 
 lazy_static! {
     static ref RPC_ERRORS: IntCounterVec = register_int_counter_vec!(
@@ -16,6 +19,25 @@ lazy_static! {
         &["endpoint"]
     )
     .expect("circuit_breaker_state metric");
+    static ref RPC_PROVIDER_LATENCY: HistogramVec = register_histogram_vec!(
+        "rpc_provider_request_duration_seconds",
+        "Request latency per RPC/Horizon provider",
+        &["endpoint"],
+        vec![0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0]
+    )
+    .expect("rpc_provider_request_duration_seconds metric");
+    static ref RPC_FAILOVERS: IntCounterVec = register_int_counter_vec!(
+        "rpc_provider_failovers_total",
+        "Requests moved to another provider after a failure",
+        &["endpoint"]
+    )
+    .expect("rpc_provider_failovers_total metric");
+    static ref RPC_STREAM_RECONNECTS: IntCounterVec = register_int_counter_vec!(
+        "rpc_stream_reconnects_total",
+        "Horizon SSE stream reconnections by stream",
+        &["stream"]
+    )
+    .expect("rpc_stream_reconnects_total metric");
 }
 
 /// Record an RPC error for metrics.
@@ -29,3 +51,20 @@ pub fn set_circuit_breaker_state(endpoint: &str, state: i64) {
         .with_label_values(&[endpoint])
         .set(state);
 }
+
+/// Record the latency of a successful request to a provider.
+pub fn observe_provider_latency(endpoint: &str, seconds: f64) {
+    RPC_PROVIDER_LATENCY
+        .with_label_values(&[endpoint])
+        .observe(seconds);
+}
+
+/// Record a failover away from a provider.
+pub fn record_failover(endpoint: &str) {
+    RPC_FAILOVERS.with_label_values(&[endpoint]).inc();
+}
+
+/// Record a reconnect of a Horizon SSE stream.
+pub fn record_stream_reconnect(stream: &str) {
+    RPC_STREAM_RECONNECTS.with_label_values(&[stream]).inc();
+}
@@ -2,13 +2,23 @@ pub mod circuit_breaker;
 pub mod config;
 pub mod error;
 pub mod metrics;
+pub mod provider_pool;
 pub mod rate_limiter;
 pub mod stellar;
+pub mod streaming;
+pub mod transport;
 
+pub use provider_pool::{ProviderPool, ProviderStatus};
 pub use rate_limiter::{RpcRateLimitConfig, RpcRateLimitMetrics, RpcRateLimiter};
 pub use stellar::{
-    Asset, FeeBumpTransactionInfo, GetLedgersResult, HealthResponse, HorizonAsset, HorizonEffect,
-    HorizonLiquidityPool, HorizonOperation, HorizonPoolReserve, HorizonTransaction,
-    InnerTransaction, LedgerInfo, OrderBook, OrderBookEntry, Payment, Price, RpcLedger,
-    StellarRpcClient, Trade,
+    AccountPayment, Asset, FeeBumpTransactionInfo, GetEventsResult, GetLedgersResult,
+    HealthResponse, HorizonAsset, HorizonEffect, HorizonLiquidityPool, HorizonOperation,
+    HorizonPoolReserve, HorizonTransaction, InnerTransaction, LedgerInfo, OrderBook,
+    OrderBookEntry, Payment, PaymentPath, PaymentTransaction, Price, ProviderPoolStatus,
+    RpcContractEvent, RpcEventFilter, RpcLedger, StellarRpcClient, Trade,
+};
+pub use streaming::{HorizonStream, StreamEvent};
+pub use transport::{
+    HttpTransport, RecordingTransport, ReplayTransport, RpcTransport, TransportRequest,
+    TransportResponse,
 };
@@ -0,0 +1,447 @@
+//! Pool of interchangeable RPC/Horizon providers.
+//!
+//! Each provider has its own circuit breaker and a moving average of its
+//! response latency. Requests go to the healthiest, fastest provider first and
+//! fail over to the next one on transient errors or an open circuit, so a
+//! single degraded endpoint no longer takes ingestion down with it.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+use crate::rpc::circuit_breaker::{BreakerState, CircuitBreaker, CircuitBreakerConfig};
+use crate::rpc::error::{RetryConfig, RpcError};
+use crate::rpc::metrics;
+
+/// Weight of the newest sample in the latency moving average
+const LATENCY_EWMA_ALPHA: f64 = 0.2;
+
+/// A single endpoint in a [`ProviderPool`]
+pub struct RpcProvider {
+    url: String,
+    label: String,
+    breaker: CircuitBreaker,
+    /// Latency moving average in microseconds; 0 until the first success
+    latency_ewma_us: AtomicU64,
+    successes: AtomicU64,
+    failures: AtomicU64,
+}
+
+impl RpcProvider {
+    fn new(label: String, url: String, cb_config: CircuitBreakerConfig) -> Self {
+        Self {
+            breaker: CircuitBreaker::new(cb_config, label.clone()),
+            url,
+            label,
+            latency_ewma_us: AtomicU64::new(0),
+            successes: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Metric label for this provider. Only the host is included so API keys
+    /// embedded in URL paths never reach metrics or status responses.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Record a successful connection made outside [`ProviderPool::execute`],
+    /// such as a long-lived stream
+    pub async fn report_success(&self, elapsed: Duration) {
+        self.record_success(elapsed);
+        self.breaker.on_success().await;
+    }
+
+    /// Record a failed connection or read made outside
+    /// [`ProviderPool::execute`]. Retryable errors count against the circuit.
+    pub async fn report_failure(&self, error: &RpcError) {
+        self.record_failure(error);
+        if error.is_retryable() {
+            self.breaker.on_failure().await;
+        }
+    }
+
+    fn latency_us(&self) -> Option<u64> {
+        match self.latency_ewma_us.load(Ordering::Relaxed) {
+            0 => None,
+            us => Some(us),
+        }
+    }
+
+    fn record_success(&self, elapsed: Duration) {
+        self.successes.fetch_add(1, Ordering::Relaxed);
+        metrics::observe_provider_latency(&self.label, elapsed.as_secs_f64());
+
+        let sample = (elapsed.as_micros() as u64).max(1);
+        // A lost update under contention only skews the average slightly
+        let updated = self.latency_us().map_or(sample, |prev| {
+            (sample as f64).mul_add(LATENCY_EWMA_ALPHA, prev as f64 * (1.0 - LATENCY_EWMA_ALPHA))
+                as u64
+        });
+        self.latency_ewma_us
+            .store(updated.max(1), Ordering::Relaxed);
+    }
+
+    fn record_failure(&self, error: &RpcError) {
+        self.failures.fetch_add(1, Ordering::Relaxed);
+        metrics::record_rpc_error(error.error_type_label(), &self.label);
+    }
+}
+
+/// Health snapshot of one provider
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderStatus {
+    pub label: String,
+    pub primary: bool,
+    pub circuit_state: BreakerState,
+    pub latency_ms: Option<f64>,
+    pub successes: u64,
+    pub failures: u64,
+}
+
+/// Ordered set of providers of the same kind (all RPC or all Horizon)
+pub struct ProviderPool {
+    kind: &'static str,
+    providers: Vec<Arc<RpcProvider>>,
+}
+
+impl ProviderPool {
+    /// Build a pool from `urls`, primary first. Duplicates and blank entries
+    /// are dropped and trailing slashes trimmed.
+    pub fn new(kind: &'static str, urls: Vec<String>, cb_config: CircuitBreakerConfig) -> Self {
+        let mut seen: Vec<String> = Vec::new();
+        for url in urls {
+            let url = url.trim().trim_end_matches('/').to_string();
+            if !url.is_empty() && !seen.contains(&url) {
+                seen.push(url);
+            }
+        }
+
+        let mut providers: Vec<Arc<RpcProvider>> = Vec::with_capacity(seen.len());
+        for url in seen {
+            let host = reqwest::Url::parse(&url)
+                .ok()
+                .and_then(|u| u.host_str().map(str::to_string))
+                .unwrap_or_else(|| url.clone());
+            let mut label = format!("{}:{}", kind, host);
+            if providers.iter().any(|p| p.label == label) {
+                label = format!("{}#{}", label, providers.len());
+            }
+            providers.push(Arc::new(RpcProvider::new(label, url, cb_config.clone())));
+        }
+
+        Self { kind, providers }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        self.kind
+    }
+
+    pub fn len(&self) -> usize {
+        self.providers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+
+    /// URL of the first configured provider
+    pub fn primary_url(&self) -> &str {
+        self.providers.first().map_or("", |p| p.url.as_str())
+    }
+
+    /// Providers in the order requests should try them: closed circuits
+    /// before half-open before open, then by latency. Providers without a
+    /// latency sample yet rank as fastest so every endpoint gets measured.
+    pub async fn ranked(&self) -> Vec<Arc<RpcProvider>> {
+        let mut ranked = Vec::with_capacity(self.providers.len());
+        for provider in &self.providers {
+            let health = match provider.breaker.state().await {
+                BreakerState::Closed => 0,
+                BreakerState::HalfOpen => 1,
+                BreakerState::Open => 2,
+            };
+            ranked.push((health, provider.latency_us().unwrap_or(0), provider.clone()));
+        }
+        // Stable sort keeps configuration order between equals
+        ranked.sort_by_key(|(health, latency, _)| (*health, *latency));
+        ranked.into_iter().map(|(_, _, p)| p).collect()
+    }
+
+    /// Provider for a new long-lived connection: the best ranked one other
+    /// than `avoid` whose circuit is not open, or the best ranked one if there
+    /// is no such provider
+    pub async fn select(&self, avoid: Option<&str>) -> Option<Arc<RpcProvider>> {
+        let ranked = self.ranked().await;
+        for provider in &ranked {
+            if Some(provider.url()) != avoid && provider.breaker.state().await != BreakerState::Open
+            {
+                return Some(provider.clone());
+            }
+        }
+        ranked.into_iter().next()
+    }
+
+    /// Run `operation` against the best provider, failing over to the others
+    /// on retryable errors. A full pass over every provider counts as one
+    /// attempt; passes are repeated with exponential backoff up to
+    /// `config.max_attempts`. Non-retryable errors (4xx, parse failures) are
+    /// returned immediately since another provider would answer the same.
+    pub async fn execute<F, Fut, T>(
+        &self,
+        operation: F,
+        config: &RetryConfig,
+    ) -> Result<T, RpcError>
+    where
+        F: Fn(String) -> Fut,
+        Fut: std::future::Future<Output = Result<T, RpcError>>,
+    {
+        let mut attempt = 0;
+
+        loop {
+            attempt += 1;
+            let ranked = self.ranked().await;
+            let mut last_error = None;
+            let mut retryable = false;
+
+            for (index, provider) in ranked.iter().enumerate() {
+                let start = Instant::now();
+                let result = provider
+                    .breaker
+                    .call(|| operation(provider.url.clone()))
+                    .await;
+
+                match result {
+                    Ok(value) => {
+                        provider.record_success(start.elapsed());
+                        return Ok(value);
+                    }
+                    Err(RpcError::CircuitBreakerOpen) => {
+                        debug!("Skipping {}: circuit open", provider.label);
+                        last_error.get_or_insert(RpcError::CircuitBreakerOpen);
+                    }
+                    Err(e) if e.is_retryable() => {
+                        provider.record_failure(&e);
+                        retryable = true;
+                        if index + 1 < ranked.len() {
+                            warn!(
+                                "{} provider {} failed ({}), failing over",
+                                self.kind, provider.label, e
+                            );
+                            metrics::record_failover(&provider.label);
+                        }
+                        last_error = Some(e);
+                    }
+                    Err(e) => {
+                        provider.record_failure(&e);
+                        return Err(e);
+                    }
+                }
+            }
+
+            let error = last_error.unwrap_or(RpcError::CircuitBreakerOpen);
+            if !retryable || attempt >= config.max_attempts {
+                return Err(error);
+            }
+
+            let delay = std::cmp::min(
+                config
+                    .base_delay_ms
+                    .saturating_mul(2u64.saturating_pow(attempt.saturating_sub(1))),
+                config.max_delay_ms,
+            );
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+        }
+    }
+
+    pub async fn status(&self) -> Vec<ProviderStatus> {
+        let mut status = Vec::with_capacity(self.providers.len());
+        for (index, provider) in self.providers.iter().enumerate() {
+            status.push(ProviderStatus {
+                label: provider.label.clone(),
+                primary: index == 0,
+                circuit_state: provider.breaker.state().await,
+                latency_ms: provider.latency_us().map(|us| us as f64 / 1000.0),
+                successes: provider.successes.load(Ordering::Relaxed),
+                failures: provider.failures.load(Ordering::Relaxed),
+            });
+        }
+        status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+
+    fn cb_config() -> CircuitBreakerConfig {
+        CircuitBreakerConfig {
+            failure_threshold: 1,
+            success_threshold: 1,
+            timeout_duration: Duration::from_secs(60),
+            half_open_max_calls: 1,
+        }
+    }
+
+    fn retry_config(max_attempts: u32) -> RetryConfig {
+        RetryConfig {
+            max_attempts,
+            base_delay_ms: 1,
+            max_delay_ms: 1,
+        }
+    }
+
+    fn pool(urls: &[&str]) -> ProviderPool {
+        ProviderPool::new(
+            "horizon",
+            urls.iter().map(|u| u.to_string()).collect(),
+            cb_config(),
+        )
+    }
+
+    #[test]
+    fn new_dedupes_and_labels_by_host() {
+        let pool = pool(&[
+            "https://horizon.stellar.org/",
+            "https://horizon.stellar.org",
+            " ",
+            "https://rpc.example.com/key-a",
+            "https://rpc.example.com/key-b",
+        ]);
+        let labels: Vec<&str> = pool.providers.iter().map(|p| p.label()).collect();
+        assert_eq!(
+            labels,
+            vec![
+                "horizon:horizon.stellar.org",
+                "horizon:rpc.example.com",
+                "horizon:rpc.example.com#2"
+            ]
+        );
+        assert_eq!(pool.primary_url(), "https://horizon.stellar.org");
+    }
+
+    #[tokio::test]
+    async fn fails_over_to_next_provider() {
+        let pool = pool(&["https://a.example", "https://b.example"]);
+
+        let result = pool
+            .execute(
+                |base| async move {
+                    if base.contains("a.example") {
+                        Err(RpcError::ServerError {
+                            status: 503,
+                            message: "unavailable".into(),
+                        })
+                    } else {
+                        Ok(base)
+                    }
+                },
+                &retry_config(1),
+            )
+            .await;
+
+        assert_eq!(result.unwrap(), "https://b.example");
+
+        // The failed provider's circuit is now open, so it ranks last
+        let ranked = pool.ranked().await;
+        assert_eq!(ranked[0].url(), "https://b.example");
+        let status = pool.status().await;
+        assert_eq!(status[0].circuit_state, BreakerState::Open);
+        assert_eq!(status[0].failures, 1);
+        assert_eq!(status[1].successes, 1);
+    }
+
+    #[tokio::test]
+    async fn client_errors_do_not_fail_over() {
+        let pool = pool(&["https://a.example", "https://b.example"]);
+        let calls = AtomicU32::new(0);
+
+        let result: Result<(), _> = pool
+            .execute(
+                |_| async {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    Err(RpcError::ServerError {
+                        status: 404,
+                        message: "not found".into(),
+                    })
+                },
+                &retry_config(3),
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(RpcError::ServerError { status: 404, .. })
+        ));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn retries_rounds_until_attempts_exhausted() {
+        let pool = ProviderPool::new(
+            "rpc",
+            vec!["https://a.example".into(), "https://b.example".into()],
+            CircuitBreakerConfig {
+                failure_threshold: 10,
+                ..cb_config()
+            },
+        );
+        let calls = AtomicU32::new(0);
+
+        let result: Result<(), _> = pool
+            .execute(
+                |_| async {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    Err(RpcError::TimeoutError("slow".into()))
+                },
+                &retry_config(2),
+            )
+            .await;
+
+        assert!(matches!(result, Err(RpcError::TimeoutError(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn ranks_by_latency_among_healthy_providers() {
+        let pool = pool(&["https://slow.example", "https://fast.example"]);
+        pool.providers[0].record_success(Duration::from_millis(400));
+        pool.providers[1].record_success(Duration::from_millis(40));
+
+        let ranked = pool.ranked().await;
+        assert_eq!(ranked[0].url(), "https://fast.example");
+
+        let status = pool.status().await;
+        assert!(status[0].primary);
+        assert_eq!(status[1].latency_ms, Some(40.0));
+    }
+
+    #[tokio::test]
+    async fn select_moves_past_failed_provider() {
+        let pool = pool(&["https://a.example", "https://b.example"]);
+        assert_eq!(pool.select(None).await.unwrap().url(), "https://a.example");
+
+        let failed = pool.select(None).await.unwrap();
+        failed
+            .report_failure(&RpcError::NetworkError("reset".into()))
+            .await;
+        assert_eq!(
+            pool.select(Some(failed.url())).await.unwrap().url(),
+            "https://b.example"
+        );
+        assert_eq!(pool.status().await[0].circuit_state, BreakerState::Open);
+
+        // With every other circuit open the best ranked provider is retried
+        pool.providers[1]
+            .report_failure(&RpcError::NetworkError("reset".into()))
+            .await;
+        assert!(pool.select(Some("https://b.example")).await.is_some());
+    }
+}
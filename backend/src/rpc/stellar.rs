@@ -1,18 +1,25 @@
 use crate::network::{NetworkConfig, StellarNetwork};
-use crate::rpc::circuit_breaker::CircuitBreaker;
 use crate::rpc::config::{
     circuit_breaker_config_from_env, initial_backoff_from_env, max_backoff_from_env,
-    max_retries_from_env,
+    max_retries_from_env, provider_urls_from_env,
 };
-use crate::rpc::error::{with_retry, RetryConfig, RpcError};
+use crate::rpc::error::{RetryConfig, RpcError};
 use crate::rpc::metrics;
+use crate::rpc::provider_pool::{ProviderPool, ProviderStatus};
 use crate::rpc::rate_limiter::{RpcRateLimitConfig, RpcRateLimitMetrics, RpcRateLimiter};
+use crate::rpc::streaming::{
+    HorizonStream, SseParser, StreamEvent, STREAM_BUFFER, STREAM_IDLE_TIMEOUT,
+};
+use crate::rpc::transport::{
+    transport_from_env, RpcTransport, TransportRequest, TransportResponse,
+};
 use anyhow::{anyhow, Context, Result};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
 use tracing::{debug, info, warn};
 
 const MAX_RETRIES: u32 = 3;
@@ -80,13 +87,16 @@ pub struct AssetFlags {
 
 #[derive(Clone)]
 pub struct StellarRpcClient {
-    client: Client,
-    rpc_url: String,
-    horizon_url: String,
+    transport: Arc<dyn RpcTransport>,
+    /// Client for long-lived SSE connections (no overall request timeout)
+    stream_client: Client,
+    /// Soroban RPC providers, primary first
+    rpc_pool: Arc<ProviderPool>,
+    /// Horizon providers, primary first
+    horizon_pool: Arc<ProviderPool>,
     network_config: NetworkConfig,
     mock_mode: bool,
     rate_limiter: RpcRateLimiter,
-    circuit_breaker: Arc<CircuitBreaker>,
     /// Maximum records per single request (default: 200)
     max_records_per_request: u32,
     /// Maximum total records across all paginated requests (default: 10000)
@@ -105,6 +115,13 @@ pub struct StellarRpcClient {
 // Data Models
 // ============================================================================
 
+/// Provider health for both pools, as reported by `/api/rpc/providers`
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderPoolStatus {
+    pub rpc: Vec<ProviderStatus>,
+    pub horizon: Vec<ProviderStatus>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HealthResponse {
     pub status: String,
@@ -261,6 +278,45 @@ impl Payment {
     }
 }
 
+/// A payment from an account's history together with the outcome and timing
+/// of its parent transaction (`include_failed=true&join=transactions`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountPayment {
+    #[serde(flatten)]
+    pub payment: Payment,
+    #[serde(default)]
+    pub transaction_successful: Option<bool>,
+    #[serde(default)]
+    pub transaction: Option<PaymentTransaction>,
+}
+
+impl AccountPayment {
+    /// Whether the parent transaction was applied successfully
+    pub fn is_successful(&self) -> bool {
+        self.transaction
+            .as_ref()
+            .map(|tx| tx.successful)
+            .or(self.transaction_successful)
+            .unwrap_or(true)
+    }
+}
+
+/// Transaction embedded in a payment record by `join=transactions`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentTransaction {
+    pub hash: String,
+    pub ledger: u64,
+    /// Close time of the ledger that included the transaction
+    pub created_at: String,
+    pub successful: bool,
+    /// Lower time bound of the transaction, if one was set
+    #[serde(default)]
+    pub valid_after: Option<String>,
+    /// Upper time bound of the transaction, if one was set
+    #[serde(default)]
+    pub valid_before: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HorizonOperation {
     pub id: String,
@@ -403,6 +459,56 @@ pub struct GetLedgersResult {
     pub cursor: Option<String>,
 }
 
+// ============================================================================
+// Contract Event Models (Soroban RPC getEvents)
+// ============================================================================
+
+/// A contract event returned by `getEvents`. Topics and value are base64 `ScVal` XDR.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcContractEvent {
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub ledger: u64,
+    pub ledger_closed_at: String,
+    pub contract_id: String,
+    pub id: String,
+    #[serde(default)]
+    pub paging_token: Option<String>,
+    pub topic: Vec<String>,
+    pub value: String,
+    #[serde(default = "default_in_successful_contract_call")]
+    pub in_successful_contract_call: bool,
+    pub tx_hash: String,
+}
+
+fn default_in_successful_contract_call() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetEventsResult {
+    pub events: Vec<RpcContractEvent>,
+    pub latest_ledger: u64,
+    /// Resume point after the last event scanned
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+/// A `getEvents` filter: contract events emitted by any of `contract_ids` whose topics
+/// match any of `topics`. Each topic pattern lists base64 `ScVal` XDR segments or `*`
+/// for any single segment; no patterns matches every topic.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcEventFilter {
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub contract_ids: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub topics: Vec<Vec<String>>,
+}
+
 // ============================================================================
 // Liquidity Pool Models (Horizon API)
 // ============================================================================
@@ -428,11 +534,67 @@ pub struct HorizonLiquidityPool {
     pub paging_token: Option<String>,
 }
 
+// ============================================================================
+// Payment Path Models (Horizon API)
+// ============================================================================
+
+/// A path found by Horizon's strict-send or strict-receive path finding
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentPath {
+    pub source_asset_type: String,
+    pub source_asset_code: Option<String>,
+    pub source_asset_issuer: Option<String>,
+    pub source_amount: String,
+    pub destination_asset_type: String,
+    pub destination_asset_code: Option<String>,
+    pub destination_asset_issuer: Option<String>,
+    pub destination_amount: String,
+    /// Intermediate assets, excluding the source and destination
+    pub path: Vec<Asset>,
+}
+
+impl Asset {
+    /// Parse `"native"` or `"CODE:ISSUER"`, the format of pool reserves
+    pub fn from_id(id: &str) -> Option<Self> {
+        if id == "native" {
+            return Some(Self {
+                asset_type: "native".to_string(),
+                asset_code: None,
+                asset_issuer: None,
+            });
+        }
+        let (code, issuer) = id.split_once(':')?;
+        if code.is_empty() || code.len() > 12 || issuer.is_empty() {
+            return None;
+        }
+        let asset_type = if code.len() <= 4 {
+            "credit_alphanum4"
+        } else {
+            "credit_alphanum12"
+        };
+        Some(Self {
+            asset_type: asset_type.to_string(),
+            asset_code: Some(code.to_string()),
+            asset_issuer: Some(issuer.to_string()),
+        })
+    }
+
+    /// `"native"` or `"CODE:ISSUER"`
+    pub fn id(&self) -> String {
+        match (&self.asset_code, &self.asset_issuer) {
+            (Some(code), Some(issuer)) if self.asset_type != "native" => {
+                format!("{}:{}", code, issuer)
+            }
+            _ => "native".to_string(),
+        }
+    }
+}
+
 // ============================================================================
 // Helpers: map HTTP response to RpcError
 // ============================================================================
 
-fn status_to_rpc_error(
+pub(crate) fn status_to_rpc_error(
     status: reqwest::StatusCode,
     body: String,
     retry_after_secs: Option<u64>,
@@ -454,20 +616,6 @@ fn status_to_rpc_error(
     }
 }
 
-async fn map_response_error(response: reqwest::Response) -> RpcError {
-    let status = response.status();
-    let retry_after = response
-        .headers()
-        .get("Retry-After")
-        .and_then(|v| v.to_str().ok())
-        .and_then(|s| s.parse::<u64>().ok());
-    let body = response
-        .text()
-        .await
-        .unwrap_or_else(|_| "Unknown error".to_string());
-    status_to_rpc_error(status, body, retry_after)
-}
-
 // ============================================================================
 // Implementation
 // ============================================================================
@@ -494,8 +642,7 @@ impl StellarRpcClient {
         };
 
         let network_config = NetworkConfig::for_network(network);
-        let cb_config = circuit_breaker_config_from_env();
-        let circuit_breaker = Arc::new(CircuitBreaker::new(cb_config, "rpc"));
+        let (rpc_pool, horizon_pool) = Self::build_provider_pools(network, rpc_url, horizon_url);
 
         // Load pagination config from environment or use defaults with security limits
         let max_records_per_request = std::env::var("RPC_MAX_RECORDS_PER_REQUEST")
@@ -552,13 +699,14 @@ impl StellarRpcClient {
         );
 
         Self {
-            client,
-            rpc_url,
-            horizon_url,
+            transport: transport_from_env(client)
+                .unwrap_or_else(|e| panic!("Invalid RPC transport configuration: {}", e)),
+            stream_client: Self::build_stream_client(),
+            rpc_pool,
+            horizon_pool,
             network_config,
             mock_mode,
             rate_limiter,
-            circuit_breaker,
             max_records_per_request,
             max_total_records,
             pagination_delay_ms,
@@ -577,8 +725,11 @@ impl StellarRpcClient {
             .build()
             .expect("Failed to build HTTP client");
         let rate_limiter = RpcRateLimiter::new(RpcRateLimitConfig::from_env());
-        let cb_config = circuit_breaker_config_from_env();
-        let circuit_breaker = Arc::new(CircuitBreaker::new(cb_config, "rpc"));
+        let (rpc_pool, horizon_pool) = Self::build_provider_pools(
+            network,
+            network_config.rpc_url.clone(),
+            network_config.horizon_url.clone(),
+        );
 
         // Load pagination config from environment or use defaults with security limits
         let max_records_per_request = std::env::var("RPC_MAX_RECORDS_PER_REQUEST")
@@ -600,13 +751,14 @@ impl StellarRpcClient {
             .max(MIN_PAGINATION_DELAY_MS);
 
         Self {
-            client,
-            rpc_url: network_config.rpc_url.clone(),
-            horizon_url: network_config.horizon_url.clone(),
+            transport: transport_from_env(client)
+                .unwrap_or_else(|e| panic!("Invalid RPC transport configuration: {}", e)),
+            stream_client: Self::build_stream_client(),
+            rpc_pool,
+            horizon_pool,
             network_config,
             mock_mode,
             rate_limiter,
-            circuit_breaker,
             max_records_per_request,
             max_total_records,
             pagination_delay_ms,
@@ -616,11 +768,63 @@ impl StellarRpcClient {
         }
     }
 
+    fn build_stream_client() -> Client {
+        Client::builder()
+            .connect_timeout(Duration::from_secs(10))
+            .build()
+            .expect("Failed to build HTTP client")
+    }
+
+    /// Build RPC and Horizon provider pools with the given primaries followed
+    /// by the fallbacks configured for the network
+    fn build_provider_pools(
+        network: StellarNetwork,
+        rpc_url: String,
+        horizon_url: String,
+    ) -> (Arc<ProviderPool>, Arc<ProviderPool>) {
+        let suffix = if network == StellarNetwork::Testnet {
+            "TESTNET"
+        } else {
+            "MAINNET"
+        };
+        let cb_config = circuit_breaker_config_from_env();
+
+        let rpc_pool = ProviderPool::new(
+            "rpc",
+            provider_urls_from_env(rpc_url, &format!("STELLAR_RPC_FALLBACK_URLS_{}", suffix)),
+            cb_config.clone(),
+        );
+        let horizon_pool = ProviderPool::new(
+            "horizon",
+            provider_urls_from_env(
+                horizon_url,
+                &format!("STELLAR_HORIZON_FALLBACK_URLS_{}", suffix),
+            ),
+            cb_config,
+        );
+
+        info!(
+            "RPC provider pools: {} rpc, {} horizon",
+            rpc_pool.len(),
+            horizon_pool.len()
+        );
+
+        (Arc::new(rpc_pool), Arc::new(horizon_pool))
+    }
+
     /// Create a new client with default OnFinality RPC and Horizon URLs (mainnet)
     pub fn new_with_defaults(mock_mode: bool) -> Self {
         Self::new_with_network(StellarNetwork::Mainnet, mock_mode)
     }
 
+    /// Replace the transport, e.g. with a `ReplayTransport` serving recorded
+    /// fixtures. Has no effect on calls answered by mock mode.
+    pub fn with_transport(mut self, transport: Arc<dyn RpcTransport>) -> Self {
+        debug!("Using {} RPC transport", transport.name());
+        self.transport = transport;
+        self
+    }
+
     /// Get the current network configuration
     pub fn network_config(&self) -> &NetworkConfig {
         &self.network_config
@@ -646,9 +850,23 @@ impl StellarRpcClient {
         self.rate_limiter.metrics()
     }
 
-    async fn execute_with_retry<F, Fut, T>(&self, operation: F) -> Result<T, RpcError>
+    /// Health, latency and circuit state of every configured provider
+    pub async fn provider_status(&self) -> ProviderPoolStatus {
+        ProviderPoolStatus {
+            rpc: self.rpc_pool.status().await,
+            horizon: self.horizon_pool.status().await,
+        }
+    }
+
+    /// Run `operation` against the providers of `pool`, passing the base URL of
+    /// the provider being tried
+    async fn execute_with_failover<F, Fut, T>(
+        &self,
+        pool: &ProviderPool,
+        operation: F,
+    ) -> Result<T, RpcError>
     where
-        F: Fn() -> Fut,
+        F: Fn(String) -> Fut,
         Fut: std::future::Future<Output = Result<T, RpcError>>,
     {
         let retry_config = RetryConfig {
@@ -657,7 +875,7 @@ impl StellarRpcClient {
             max_delay_ms: self.max_backoff.as_millis() as u64,
         };
 
-        with_retry(operation, retry_config, self.circuit_breaker.clone()).await
+        pool.execute(operation, &retry_config).await
     }
 
     /// Check the health of the RPC endpoint
@@ -666,10 +884,12 @@ impl StellarRpcClient {
             return Ok(Self::mock_health_response());
         }
 
-        info!("Checking RPC health at {}", self.rpc_url);
+        info!("Checking RPC health at {}", self.rpc_pool.primary_url());
 
         let result = self
-            .execute_with_retry(|| self.check_health_internal())
+            .execute_with_failover(&self.rpc_pool, |base| async move {
+                self.check_health_internal(&base).await
+            })
             .await;
 
         result.map_err(|e| {
@@ -678,7 +898,7 @@ impl StellarRpcClient {
         })
     }
 
-    async fn check_health_internal(&self) -> Result<HealthResponse, RpcError> {
+    async fn check_health_internal(&self, base: &str) -> Result<HealthResponse, RpcError> {
         let payload = json!({
             "jsonrpc": "2.0",
             "method": "getHealth",
@@ -686,21 +906,15 @@ impl StellarRpcClient {
         });
 
         let response = self
-            .client
-            .post(&self.rpc_url)
-            .json(&payload)
-            .send()
-            .await
-            .map_err(|e| RpcError::NetworkError(e.to_string()))?;
+            .transport
+            .send(TransportRequest::post(base, payload))
+            .await?;
 
-        if !response.status().is_success() {
-            return Err(map_response_error(response).await);
+        if !response.is_success() {
+            return Err(response.into_error());
         }
 
-        let json_response: JsonRpcResponse<HealthResponse> = response
-            .json()
-            .await
-            .map_err(|e| RpcError::ParseError(e.to_string()))?;
+        let json_response: JsonRpcResponse<HealthResponse> = response.json()?;
 
         if let Some(error) = json_response.error {
             return Err(RpcError::ServerError {
@@ -721,7 +935,9 @@ impl StellarRpcClient {
         }
 
         let result = self
-            .execute_with_retry(|| self.fetch_latest_ledger_internal())
+            .execute_with_failover(&self.horizon_pool, |base| async move {
+                self.fetch_latest_ledger_internal(&base).await
+            })
             .await;
 
         result.map_err(|e| {
@@ -730,21 +946,16 @@ impl StellarRpcClient {
         })
     }
 
-    async fn fetch_latest_ledger_internal(&self) -> Result<LedgerInfo, RpcError> {
-        let url = format!("{}/ledgers?order=desc&limit=1", self.horizon_url);
+    async fn fetch_latest_ledger_internal(&self, base: &str) -> Result<LedgerInfo, RpcError> {
+        let url = format!("{}/ledgers?order=desc&limit=1", base);
         let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| RpcError::NetworkError(e.to_string()))?;
-        if !response.status().is_success() {
-            return Err(map_response_error(response).await);
+            .transport
+            .send(TransportRequest::get(base, url))
+            .await?;
+        if !response.is_success() {
+            return Err(response.into_error());
         }
-        let horizon_response: HorizonResponse<LedgerInfo> = response
-            .json()
-            .await
-            .map_err(|e| RpcError::ParseError(e.to_string()))?;
+        let horizon_response: HorizonResponse<LedgerInfo> = response.json()?;
         horizon_response
             .embedded
             .and_then(|e| e.records.into_iter().next())
@@ -771,7 +982,10 @@ impl StellarRpcClient {
         }
 
         let result = self
-            .execute_with_retry(|| self.fetch_ledgers_internal(start_ledger, limit, cursor))
+            .execute_with_failover(&self.rpc_pool, |base| async move {
+                self.fetch_ledgers_internal(&base, start_ledger, limit, cursor)
+                    .await
+            })
             .await;
 
         result.map_err(|e| {
@@ -782,6 +996,7 @@ impl StellarRpcClient {
 
     async fn fetch_ledgers_internal(
         &self,
+        base: &str,
         start_ledger: Option<u64>,
         limit: u32,
         cursor: Option<&str>,
@@ -805,19 +1020,13 @@ impl StellarRpcClient {
             "params": params
         });
         let response = self
-            .client
-            .post(&self.rpc_url)
-            .json(&payload)
-            .send()
-            .await
-            .map_err(|e| RpcError::NetworkError(e.to_string()))?;
-        if !response.status().is_success() {
-            return Err(map_response_error(response).await);
+            .transport
+            .send(TransportRequest::post(base, payload))
+            .await?;
+        if !response.is_success() {
+            return Err(response.into_error());
         }
-        let json_response: JsonRpcResponse<GetLedgersResult> = response
-            .json()
-            .await
-            .map_err(|e| RpcError::ParseError(e.to_string()))?;
+        let json_response: JsonRpcResponse<GetLedgersResult> = response.json()?;
         if let Some(error) = json_response.error {
             return Err(RpcError::ServerError {
                 status: 500,
@@ -829,6 +1038,76 @@ impl StellarRpcClient {
             .ok_or_else(|| RpcError::ParseError("No result in getLedgers response".to_string()))
     }
 
+    /// Fetch contract events via RPC `getEvents`, from `start_ledger` on the first call
+    /// and from `cursor` afterwards
+    pub async fn fetch_contract_events(
+        &self,
+        start_ledger: Option<u64>,
+        cursor: Option<&str>,
+        filters: &[RpcEventFilter],
+        limit: u32,
+    ) -> Result<GetEventsResult, RpcError> {
+        if self.mock_mode {
+            return Self::mock_contract_events(start_ledger, cursor, filters, limit);
+        }
+
+        let result = self
+            .execute_with_failover(&self.rpc_pool, |base| async move {
+                self.fetch_contract_events_internal(&base, start_ledger, cursor, filters, limit)
+                    .await
+            })
+            .await;
+
+        result.map_err(|e| {
+            metrics::record_rpc_error(e.error_type_label(), "stellar");
+            e
+        })
+    }
+
+    async fn fetch_contract_events_internal(
+        &self,
+        base: &str,
+        start_ledger: Option<u64>,
+        cursor: Option<&str>,
+        filters: &[RpcEventFilter],
+        limit: u32,
+    ) -> Result<GetEventsResult, RpcError> {
+        let mut pagination = serde_json::Map::new();
+        pagination.insert("limit".to_string(), json!(limit));
+        let mut params = serde_json::Map::new();
+        params.insert("filters".to_string(), json!(filters));
+        // The cursor already pins the position; RPC rejects both together
+        if let Some(c) = cursor {
+            pagination.insert("cursor".to_string(), json!(c));
+        } else if let Some(start) = start_ledger {
+            params.insert("startLedger".to_string(), json!(start));
+        }
+        params.insert("pagination".to_string(), json!(pagination));
+        let payload = json!({
+            "jsonrpc": "2.0",
+            "method": "getEvents",
+            "id": 1,
+            "params": params
+        });
+        let response = self
+            .transport
+            .send(TransportRequest::post(base, payload))
+            .await?;
+        if !response.is_success() {
+            return Err(response.into_error());
+        }
+        let json_response: JsonRpcResponse<GetEventsResult> = response.json()?;
+        if let Some(error) = json_response.error {
+            return Err(RpcError::ServerError {
+                status: 500,
+                message: format!("RPC error: {} (code: {})", error.message, error.code),
+            });
+        }
+        json_response
+            .result
+            .ok_or_else(|| RpcError::ParseError("No result in getEvents response".to_string()))
+    }
+
     /// Fetch recent payments
     pub async fn fetch_payments(
         &self,
@@ -842,7 +1121,9 @@ impl StellarRpcClient {
         info!("Fetching {} payments from Horizon API", limit);
 
         let result = self
-            .execute_with_retry(|| self.fetch_payments_internal(limit, cursor))
+            .execute_with_failover(&self.horizon_pool, |base| async move {
+                self.fetch_payments_internal(&base, limit, cursor).await
+            })
             .await;
 
         result.map_err(|e| {
@@ -853,26 +1134,22 @@ impl StellarRpcClient {
 
     async fn fetch_payments_internal(
         &self,
+        base: &str,
         limit: u32,
         cursor: Option<&str>,
     ) -> Result<Vec<Payment>, RpcError> {
-        let mut url = format!("{}/payments?order=desc&limit={}", self.horizon_url, limit);
+        let mut url = format!("{}/payments?order=desc&limit={}", base, limit);
         if let Some(c) = cursor {
             url.push_str(&format!("&cursor={}", c));
         }
         let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| RpcError::NetworkError(e.to_string()))?;
-        if !response.status().is_success() {
-            return Err(map_response_error(response).await);
+            .transport
+            .send(TransportRequest::get(base, url))
+            .await?;
+        if !response.is_success() {
+            return Err(response.into_error());
         }
-        let horizon_response: HorizonResponse<Payment> = response
-            .json()
-            .await
-            .map_err(|e| RpcError::ParseError(e.to_string()))?;
+        let horizon_response: HorizonResponse<Payment> = response.json()?;
         Ok(horizon_response
             .embedded
             .map(|e| e.records)
@@ -890,7 +1167,9 @@ impl StellarRpcClient {
         }
 
         let result = self
-            .execute_with_retry(|| self.fetch_trades_internal(limit, cursor))
+            .execute_with_failover(&self.horizon_pool, |base| async move {
+                self.fetch_trades_internal(&base, limit, cursor).await
+            })
             .await;
 
         result.map_err(|e| {
@@ -901,26 +1180,22 @@ impl StellarRpcClient {
 
     async fn fetch_trades_internal(
         &self,
+        base: &str,
         limit: u32,
         cursor: Option<&str>,
     ) -> Result<Vec<Trade>, RpcError> {
-        let mut url = format!("{}/trades?order=desc&limit={}", self.horizon_url, limit);
+        let mut url = format!("{}/trades?order=desc&limit={}", base, limit);
         if let Some(c) = cursor {
             url.push_str(&format!("&cursor={}", c));
         }
         let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| RpcError::NetworkError(e.to_string()))?;
-        if !response.status().is_success() {
-            return Err(map_response_error(response).await);
+            .transport
+            .send(TransportRequest::get(base, url))
+            .await?;
+        if !response.is_success() {
+            return Err(response.into_error());
         }
-        let horizon_response: HorizonResponse<Trade> = response
-            .json()
-            .await
-            .map_err(|e| RpcError::ParseError(e.to_string()))?;
+        let horizon_response: HorizonResponse<Trade> = response.json()?;
         Ok(horizon_response
             .embedded
             .map(|e| e.records)
@@ -939,8 +1214,9 @@ impl StellarRpcClient {
         }
 
         let result = self
-            .execute_with_retry(|| {
-                self.fetch_order_book_internal(selling_asset, buying_asset, limit)
+            .execute_with_failover(&self.horizon_pool, |base| async move {
+                self.fetch_order_book_internal(&base, selling_asset, buying_asset, limit)
+                    .await
             })
             .await;
 
@@ -952,6 +1228,7 @@ impl StellarRpcClient {
 
     async fn fetch_order_book_internal(
         &self,
+        base: &str,
         selling_asset: &Asset,
         buying_asset: &Asset,
         limit: u32,
@@ -962,21 +1239,16 @@ impl StellarRpcClient {
             .map_err(|e| RpcError::ParseError(e.to_string()))?;
         let url = format!(
             "{}/order_book?{}&{}&limit={}",
-            self.horizon_url, selling_params, buying_params, limit
+            base, selling_params, buying_params, limit
         );
         let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| RpcError::NetworkError(e.to_string()))?;
-        if !response.status().is_success() {
-            return Err(map_response_error(response).await);
+            .transport
+            .send(TransportRequest::get(base, url))
+            .await?;
+        if !response.is_success() {
+            return Err(response.into_error());
         }
-        response
-            .json()
-            .await
-            .map_err(|e| RpcError::ParseError(e.to_string()))
+        response.json()
     }
 
     pub async fn fetch_payments_for_ledger(&self, sequence: u64) -> Result<Vec<Payment>, RpcError> {
@@ -985,7 +1257,10 @@ impl StellarRpcClient {
         }
 
         let result = self
-            .execute_with_retry(|| self.fetch_payments_for_ledger_internal(sequence))
+            .execute_with_failover(&self.horizon_pool, |base| async move {
+                self.fetch_payments_for_ledger_internal(&base, sequence)
+                    .await
+            })
             .await;
 
         result.map_err(|e| {
@@ -996,25 +1271,18 @@ impl StellarRpcClient {
 
     async fn fetch_payments_for_ledger_internal(
         &self,
+        base: &str,
         sequence: u64,
     ) -> Result<Vec<Payment>, RpcError> {
-        let url = format!(
-            "{}/ledgers/{}/payments?limit=200",
-            self.horizon_url, sequence
-        );
+        let url = format!("{}/ledgers/{}/payments?limit=200", base, sequence);
         let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| RpcError::NetworkError(e.to_string()))?;
-        if !response.status().is_success() {
-            return Err(map_response_error(response).await);
+            .transport
+            .send(TransportRequest::get(base, url))
+            .await?;
+        if !response.is_success() {
+            return Err(response.into_error());
         }
-        let horizon_response: HorizonResponse<Payment> = response
-            .json()
-            .await
-            .map_err(|e| RpcError::ParseError(e.to_string()))?;
+        let horizon_response: HorizonResponse<Payment> = response.json()?;
         Ok(horizon_response
             .embedded
             .map(|e| e.records)
@@ -1031,7 +1299,10 @@ impl StellarRpcClient {
         }
 
         let result = self
-            .execute_with_retry(|| self.fetch_transactions_for_ledger_internal(sequence))
+            .execute_with_failover(&self.horizon_pool, |base| async move {
+                self.fetch_transactions_for_ledger_internal(&base, sequence)
+                    .await
+            })
             .await;
 
         result.map_err(|e| {
@@ -1042,25 +1313,21 @@ impl StellarRpcClient {
 
     async fn fetch_transactions_for_ledger_internal(
         &self,
+        base: &str,
         sequence: u64,
     ) -> Result<Vec<HorizonTransaction>, RpcError> {
         let url = format!(
             "{}/ledgers/{}/transactions?limit=200&include_failed=true",
-            self.horizon_url, sequence
+            base, sequence
         );
         let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| RpcError::NetworkError(e.to_string()))?;
-        if !response.status().is_success() {
-            return Err(map_response_error(response).await);
+            .transport
+            .send(TransportRequest::get(base, url))
+            .await?;
+        if !response.is_success() {
+            return Err(response.into_error());
         }
-        let horizon_response: HorizonResponse<HorizonTransaction> = response
-            .json()
-            .await
-            .map_err(|e| RpcError::ParseError(e.to_string()))?;
+        let horizon_response: HorizonResponse<HorizonTransaction> = response.json()?;
         Ok(horizon_response
             .embedded
             .map(|e| e.records)
@@ -1077,7 +1344,10 @@ impl StellarRpcClient {
         }
 
         let result = self
-            .execute_with_retry(|| self.fetch_operations_for_ledger_internal(sequence))
+            .execute_with_failover(&self.horizon_pool, |base| async move {
+                self.fetch_operations_for_ledger_internal(&base, sequence)
+                    .await
+            })
             .await;
 
         result.map_err(|e| {
@@ -1088,25 +1358,18 @@ impl StellarRpcClient {
 
     async fn fetch_operations_for_ledger_internal(
         &self,
+        base: &str,
         sequence: u64,
     ) -> Result<Vec<HorizonOperation>, RpcError> {
-        let url = format!(
-            "{}/ledgers/{}/operations?limit=200",
-            self.horizon_url, sequence
-        );
+        let url = format!("{}/ledgers/{}/operations?limit=200", base, sequence);
         let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| RpcError::NetworkError(e.to_string()))?;
-        if !response.status().is_success() {
-            return Err(map_response_error(response).await);
+            .transport
+            .send(TransportRequest::get(base, url))
+            .await?;
+        if !response.is_success() {
+            return Err(response.into_error());
         }
-        let horizon_response: HorizonResponse<HorizonOperation> = response
-            .json()
-            .await
-            .map_err(|e| RpcError::ParseError(e.to_string()))?;
+        let horizon_response: HorizonResponse<HorizonOperation> = response.json()?;
         Ok(horizon_response
             .embedded
             .map(|e| e.records)
@@ -1123,7 +1386,10 @@ impl StellarRpcClient {
         }
 
         let result = self
-            .execute_with_retry(|| self.fetch_operation_effects_internal(operation_id))
+            .execute_with_failover(&self.horizon_pool, |base| async move {
+                self.fetch_operation_effects_internal(&base, operation_id)
+                    .await
+            })
             .await;
 
         result.map_err(|e| {
@@ -1134,25 +1400,18 @@ impl StellarRpcClient {
 
     async fn fetch_operation_effects_internal(
         &self,
+        base: &str,
         operation_id: &str,
     ) -> Result<Vec<HorizonEffect>, RpcError> {
-        let url = format!(
-            "{}/operations/{}/effects?limit=200",
-            self.horizon_url, operation_id
-        );
+        let url = format!("{}/operations/{}/effects?limit=200", base, operation_id);
         let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| RpcError::NetworkError(e.to_string()))?;
-        if !response.status().is_success() {
-            return Err(map_response_error(response).await);
+            .transport
+            .send(TransportRequest::get(base, url))
+            .await?;
+        if !response.is_success() {
+            return Err(response.into_error());
         }
-        let horizon_response: HorizonResponse<HorizonEffect> = response
-            .json()
-            .await
-            .map_err(|e| RpcError::ParseError(e.to_string()))?;
+        let horizon_response: HorizonResponse<HorizonEffect> = response.json()?;
         Ok(horizon_response
             .embedded
             .map(|e| e.records)
@@ -1170,7 +1429,10 @@ impl StellarRpcClient {
         }
 
         let result = self
-            .execute_with_retry(|| self.fetch_account_payments_internal(account_id, limit))
+            .execute_with_failover(&self.horizon_pool, |base| async move {
+                self.fetch_account_payments_internal(&base, account_id, limit)
+                    .await
+            })
             .await;
 
         result.map_err(|e| {
@@ -1181,26 +1443,79 @@ impl StellarRpcClient {
 
     async fn fetch_account_payments_internal(
         &self,
+        base: &str,
         account_id: &str,
         limit: u32,
     ) -> Result<Vec<Payment>, RpcError> {
         let url = format!(
             "{}/accounts/{}/payments?order=desc&limit={}",
-            self.horizon_url, account_id, limit
+            base, account_id, limit
         );
         let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| RpcError::NetworkError(e.to_string()))?;
-        if !response.status().is_success() {
-            return Err(map_response_error(response).await);
+            .transport
+            .send(TransportRequest::get(base, url))
+            .await?;
+        if !response.is_success() {
+            return Err(response.into_error());
         }
-        let horizon_response: HorizonResponse<Payment> = response
-            .json()
-            .await
-            .map_err(|e| RpcError::ParseError(e.to_string()))?;
+        let horizon_response: HorizonResponse<Payment> = response.json()?;
+        Ok(horizon_response
+            .embedded
+            .map(|e| e.records)
+            .unwrap_or_default())
+    }
+
+    /// Fetch one page of an account's payments in ascending order, including
+    /// payments from failed transactions and the transactions themselves
+    pub async fn fetch_account_payments_page(
+        &self,
+        account_id: &str,
+        cursor: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<AccountPayment>, RpcError> {
+        if self.mock_mode {
+            // A single page, so callers paging by cursor terminate
+            if cursor.is_some() {
+                return Ok(Vec::new());
+            }
+            return Ok(Self::mock_account_payments(limit));
+        }
+
+        let result = self
+            .execute_with_failover(&self.horizon_pool, |base| async move {
+                self.fetch_account_payments_page_internal(&base, account_id, cursor, limit)
+                    .await
+            })
+            .await;
+
+        result.map_err(|e| {
+            metrics::record_rpc_error(e.error_type_label(), "stellar");
+            e
+        })
+    }
+
+    async fn fetch_account_payments_page_internal(
+        &self,
+        base: &str,
+        account_id: &str,
+        cursor: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<AccountPayment>, RpcError> {
+        let mut url = format!(
+            "{}/accounts/{}/payments?order=asc&limit={}&include_failed=true&join=transactions",
+            base, account_id, limit
+        );
+        if let Some(c) = cursor {
+            url.push_str(&format!("&cursor={}", c));
+        }
+        let response = self
+            .transport
+            .send(TransportRequest::get(base, url))
+            .await?;
+        if !response.is_success() {
+            return Err(response.into_error());
+        }
+        let horizon_response: HorizonResponse<AccountPayment> = response.json()?;
         Ok(horizon_response
             .embedded
             .map(|e| e.records)
@@ -1392,23 +1707,22 @@ impl StellarRpcClient {
         while fetched < max_records {
             let limit = std::cmp::min(self.max_records_per_request, max_records - fetched);
 
-            let mut url = format!(
-                "{}/accounts/{}/payments?order=desc&limit={}",
-                self.horizon_url, account_id, limit
+            let mut path = format!(
+                "/accounts/{}/payments?order=desc&limit={}",
+                account_id, limit
             );
 
             if let Some(ref cursor_val) = cursor {
-                url.push_str(&format!("&cursor={}", cursor_val));
+                path.push_str(&format!("&cursor={}", cursor_val));
             }
 
             let response = self
-                .retry_request(|| async { self.client.get(&url).send().await })
+                .retry_request(&path)
                 .await
                 .context("Failed to fetch account payments page")?;
 
             let horizon_response: HorizonResponse<Payment> = response
                 .json()
-                .await
                 .context("Failed to parse payments response")?;
 
             let payments = horizon_response
@@ -1454,6 +1768,196 @@ impl StellarRpcClient {
         Ok(all_payments)
     }
 
+    // ============================================================================
+    // Streaming
+    // ============================================================================
+
+    /// Stream a Horizon collection over server-sent events.
+    ///
+    /// Records are delivered on the returned channel as they are closed into
+    /// ledgers. The connection is re-established with backoff after errors or
+    /// idle periods, resuming from the last delivered paging token. Connection
+    /// and read failures count against the provider's circuit breaker, and the
+    /// reconnect after one moves to the next healthy Horizon provider.
+    /// Streaming starts at `cursor`, or at the present (`now`) when none is
+    /// given, and stops once the receiver is dropped. In mock mode mock records
+    /// are emitted every few seconds.
+    pub fn stream(
+        &self,
+        stream: HorizonStream,
+        cursor: Option<String>,
+    ) -> mpsc::Receiver<StreamEvent> {
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        let client = self.clone();
+
+        tokio::spawn(async move {
+            if client.mock_mode {
+                client.run_mock_stream(stream, tx).await;
+            } else {
+                client.run_stream(stream, cursor, tx).await;
+            }
+        });
+
+        rx
+    }
+
+    async fn run_stream(
+        &self,
+        stream: HorizonStream,
+        cursor: Option<String>,
+        tx: mpsc::Sender<StreamEvent>,
+    ) {
+        let mut cursor = cursor.or_else(|| Some("now".to_string()));
+        let mut backoff = self.initial_backoff;
+        let mut connected_once = false;
+        // Provider whose connection last failed, passed over on reconnect
+        let mut failed: Option<String> = None;
+
+        while !tx.is_closed() {
+            if connected_once {
+                metrics::record_stream_reconnect(stream.name());
+            }
+            connected_once = true;
+
+            let provider = match self.horizon_pool.select(failed.as_deref()).await {
+                Some(provider) => provider,
+                None => {
+                    warn!(
+                        "No Horizon provider configured for {} stream",
+                        stream.name()
+                    );
+                    return;
+                }
+            };
+            failed = None;
+            let url = stream.url(provider.url(), cursor.as_deref());
+            debug!("Opening {} stream at {}", stream.name(), url);
+
+            let start = Instant::now();
+            let response = self
+                .stream_client
+                .get(&url)
+                .header(reqwest::header::ACCEPT, "text/event-stream")
+                .send()
+                .await;
+
+            match response {
+                Ok(mut response) if response.status().is_success() => {
+                    provider.report_success(start.elapsed()).await;
+                    info!("Connected {} stream", stream.name());
+                    let mut parser = SseParser::default();
+
+                    loop {
+                        let chunk =
+                            match tokio::time::timeout(STREAM_IDLE_TIMEOUT, response.chunk()).await
+                            {
+                                Ok(Ok(Some(chunk))) => chunk,
+                                Ok(Ok(None)) => {
+                                    debug!("{} stream closed by server", stream.name());
+                                    break;
+                                }
+                                Ok(Err(e)) => {
+                                    warn!("{} stream read failed: {}", stream.name(), e);
+                                    provider
+                                        .report_failure(&RpcError::NetworkError(e.to_string()))
+                                        .await;
+                                    failed = Some(provider.url().to_string());
+                                    break;
+                                }
+                                Err(_) => {
+                                    warn!("{} stream idle, reconnecting", stream.name());
+                                    break;
+                                }
+                            };
+
+                        for event in parser.feed(&chunk) {
+                            if let Some(retry_ms) = event.retry {
+                                backoff = Duration::from_millis(retry_ms).min(self.max_backoff);
+                            }
+                            if event.is_control() {
+                                continue;
+                            }
+
+                            match stream.decode(&event.data) {
+                                Ok(record) => {
+                                    if tx.send(record).await.is_err() {
+                                        return;
+                                    }
+                                    if stream.supports_cursor() {
+                                        if let Some(id) = event.id {
+                                            cursor = Some(id);
+                                        }
+                                    }
+                                    backoff = self.initial_backoff;
+                                }
+                                Err(e) => {
+                                    metrics::record_rpc_error("parse_error", stream.name());
+                                    warn!("Skipping undecodable {} event: {}", stream.name(), e);
+                                }
+                            }
+                        }
+                    }
+                }
+                Ok(response) => {
+                    let error = TransportResponse {
+                        status: response.status(),
+                        headers: response.headers().clone(),
+                        body: response.text().await.unwrap_or_default(),
+                    }
+                    .into_error();
+                    if let RpcError::RateLimitError {
+                        retry_after: Some(delay),
+                    } = &error
+                    {
+                        backoff = backoff.max(*delay);
+                    }
+                    metrics::record_rpc_error(error.error_type_label(), stream.name());
+                    warn!("{} stream rejected: {}", stream.name(), error);
+                    provider.report_failure(&error).await;
+                    if error.is_retryable() {
+                        failed = Some(provider.url().to_string());
+                    }
+                }
+                Err(e) => {
+                    metrics::record_rpc_error("network_error", stream.name());
+                    warn!("{} stream connection failed: {}", stream.name(), e);
+                    provider
+                        .report_failure(&RpcError::NetworkError(e.to_string()))
+                        .await;
+                    failed = Some(provider.url().to_string());
+                }
+            }
+
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(self.max_backoff);
+        }
+    }
+
+    async fn run_mock_stream(&self, stream: HorizonStream, tx: mpsc::Sender<StreamEvent>) {
+        let mut interval = tokio::time::interval(Duration::from_secs(5));
+
+        loop {
+            interval.tick().await;
+            let event = match &stream {
+                HorizonStream::Payments => match Self::mock_account_payments(1).pop() {
+                    Some(payment) => StreamEvent::Payment(Box::new(payment)),
+                    None => continue,
+                },
+                HorizonStream::Trades => match Self::mock_trades(1).pop() {
+                    Some(trade) => StreamEvent::Trade(trade),
+                    None => continue,
+                },
+                HorizonStream::Ledgers => StreamEvent::Ledger(Self::mock_ledger_info()),
+                HorizonStream::OrderBook { selling, buying } => {
+                    StreamEvent::OrderBook(Self::mock_order_book(selling, buying))
+                }
+            };
+            if tx.send(event).await.is_err() {
+                return;
+            }
+        }
+    }
+
     // ============================================================================
     // Helper Methods
     // ============================================================================
@@ -1482,84 +1986,79 @@ impl StellarRpcClient {
         }
     }
 
-    /// Retry a request with exponential backoff
-    async fn retry_request<F, Fut>(&self, request_fn: F) -> Result<reqwest::Response>
-    where
-        F: Fn() -> Fut,
-        Fut: std::future::Future<Output = Result<reqwest::Response, reqwest::Error>>,
-    {
+    /// GET a Horizon `path` with exponential backoff, failing over between
+    /// providers
+    async fn retry_request(&self, path: &str) -> Result<TransportResponse> {
         let retry_config = RetryConfig {
             max_attempts: MAX_RETRIES + 1,
             base_delay_ms: INITIAL_BACKOFF_MS,
             max_delay_ms: INITIAL_BACKOFF_MS * BACKOFF_MULTIPLIER.pow(MAX_RETRIES),
         };
 
-        with_retry(
-            || async {
-                let queue_permit = self
-                    .rate_limiter
-                    .acquire()
-                    .await
-                    .map_err(|_| RpcError::RateLimitError { retry_after: None })?;
-
-                let start_time = Instant::now();
-                let response = request_fn()
-                    .await
-                    .map_err(|e| RpcError::categorize(&e.to_string()))?;
-                let elapsed = start_time.elapsed().as_millis();
-                let status = response.status();
-                let headers = response.headers().clone();
-
-                drop(queue_permit);
-                self.rate_limiter.observe_headers(&headers).await;
-
-                if status.is_success() {
-                    debug!("Request succeeded in {} ms", elapsed);
-                    return Ok(response);
-                }
-
-                if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
-                    self.rate_limiter.on_rate_limited(&headers).await;
-                }
+        self.horizon_pool
+            .execute(
+                |base| async move {
+                    let queue_permit = self
+                        .rate_limiter
+                        .acquire()
+                        .await
+                        .map_err(|_| RpcError::RateLimitError { retry_after: None })?;
+
+                    let start_time = Instant::now();
+                    let response = self
+                        .transport
+                        .send(TransportRequest::get(&base, format!("{}{}", base, path)))
+                        .await?;
+                    let elapsed = start_time.elapsed().as_millis();
+                    let status = response.status;
+                    let headers = response.headers.clone();
+
+                    drop(queue_permit);
+                    self.rate_limiter.observe_headers(&headers).await;
+
+                    if status.is_success() {
+                        debug!("Request succeeded in {} ms", elapsed);
+                        return Ok(response);
+                    }
 
-                let error_text = response
-                    .text()
-                    .await
-                    .unwrap_or_else(|_| "Unknown error".to_string());
-                warn!(
-                    "Request failed with status {} in {} ms: {}",
-                    status, elapsed, error_text
-                );
+                    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                        self.rate_limiter.on_rate_limited(&headers).await;
+                    }
 
-                let msg = format!("HTTP {}: {}", status, error_text);
-                if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
-                    let retry_after = headers
-                        .get("Retry-After")
-                        .and_then(|v| v.to_str().ok())
-                        .and_then(|s| s.parse::<u64>().ok())
-                        .map(Duration::from_secs);
-                    Err(RpcError::RateLimitError { retry_after })
-                } else if status == reqwest::StatusCode::REQUEST_TIMEOUT
-                    || status == reqwest::StatusCode::GATEWAY_TIMEOUT
-                {
-                    Err(RpcError::TimeoutError(msg))
-                } else if status.as_u16() >= 500 {
-                    Err(RpcError::NetworkError(msg))
-                } else {
-                    Err(RpcError::ServerError {
-                        status: status.as_u16(),
-                        message: msg,
-                    })
-                }
-            },
-            retry_config,
-            self.circuit_breaker.clone(),
-        )
-        .await
-        .map_err(|e| {
-            info!("Request failed after retry/circuit-breaker checks: {}", e);
-            anyhow!("Request failed: {}", e)
-        })
+                    let error_text = response.body;
+                    warn!(
+                        "Request failed with status {} in {} ms: {}",
+                        status, elapsed, error_text
+                    );
+
+                    let msg = format!("HTTP {}: {}", status, error_text);
+                    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                        let retry_after = headers
+                            .get("Retry-After")
+                            .and_then(|v| v.to_str().ok())
+                            .and_then(|s| s.parse::<u64>().ok())
+                            .map(Duration::from_secs);
+                        Err(RpcError::RateLimitError { retry_after })
+                    } else if status == reqwest::StatusCode::REQUEST_TIMEOUT
+                        || status == reqwest::StatusCode::GATEWAY_TIMEOUT
+                    {
+                        Err(RpcError::TimeoutError(msg))
+                    } else if status.as_u16() >= 500 {
+                        Err(RpcError::NetworkError(msg))
+                    } else {
+                        Err(RpcError::ServerError {
+                            status: status.as_u16(),
+                            message: msg,
+                        })
+                    }
+                },
+                &retry_config,
+            )
+            .await
+            .map_err(|e| {
+                info!("Request failed after retry/circuit-breaker checks: {}", e);
+                anyhow!("Request failed: {}", e)
+            })
     }
 
     // ============================================================================
@@ -1621,6 +2120,145 @@ impl StellarRpcClient {
         }
     }
 
+    /// A proposal, a vote on it and a snapshot submission, emitted by the first filtered
+    /// contract. The cursor is the index of the last event returned.
+    /// Three governance and snapshot events. Like RPC, rejects a start ledger before
+    /// the oldest retained one and a cursor it cannot place.
+    fn mock_contract_events(
+        start_ledger: Option<u64>,
+        cursor: Option<&str>,
+        filters: &[RpcEventFilter],
+        limit: u32,
+    ) -> Result<GetEventsResult, RpcError> {
+        use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+        use stellar_xdr::curr::{
+            Hash, Limits, ScAddress, ScBytes, ScMap, ScMapEntry, ScSymbol, ScVal, WriteXdr,
+        };
+
+        let symbol = |name: &str| ScVal::Symbol(ScSymbol(name.try_into().unwrap()));
+        let address = |byte: u8| ScVal::Address(ScAddress::Contract(Hash([byte; 32])));
+        let map = |fields: Vec<(&str, ScVal)>| {
+            let entries: Vec<ScMapEntry> = fields
+                .into_iter()
+                .map(|(key, val)| ScMapEntry {
+                    key: symbol(key),
+                    val,
+                })
+                .collect();
+            ScVal::Map(Some(ScMap(entries.try_into().unwrap())))
+        };
+        let encode = |val: &ScVal| BASE64.encode(val.to_xdr(Limits::none()).unwrap());
+
+        let out_of_range = || {
+            RpcError::ServerError {
+            status: 500,
+            message: format!(
+                "RPC error: startLedger must be between the oldest ledger: {} and the latest ledger: {} for this rpc instance. (code: -32600)",
+                MOCK_OLDEST_LEDGER, MOCK_LATEST_LEDGER
+            ),
+        }
+        };
+        let start = match (cursor, start_ledger) {
+            (Some(c), _) => c.parse::<usize>().map_err(|_| out_of_range())? + 1,
+            (None, Some(ledger)) if ledger < MOCK_OLDEST_LEDGER => return Err(out_of_range()),
+            (None, _) => 0,
+        };
+
+        let contract_id = filters
+            .iter()
+            .flat_map(|f| f.contract_ids.first())
+            .next()
+            .cloned()
+            .unwrap_or_else(|| stellar_strkey::Contract([9u8; 32]).to_string());
+        let events = [
+            (
+                vec![symbol("PROP_CRT"), symbol("GOV_LFE")],
+                map(vec![
+                    ("proposal_id", ScVal::U64(1)),
+                    ("proposer", address(1)),
+                    ("target_contract", address(2)),
+                    ("voting_ends_at", ScVal::U64(1_734_100_000)),
+                ]),
+            ),
+            (
+                vec![symbol("VOTE_CST"), symbol("GOV_LFE")],
+                map(vec![
+                    ("choice", ScVal::U32(0)),
+                    ("proposal_id", ScVal::U64(1)),
+                    ("voter", address(3)),
+                ]),
+            ),
+            (
+                vec![symbol("SNAP_SUB")],
+                ScVal::Vec(Some(
+                    vec![
+                        ScVal::Bytes(ScBytes(vec![0xab; 32].try_into().unwrap())),
+                        ScVal::U64(7),
+                        ScVal::U64(1_734_032_457),
+                    ]
+                    .try_into()
+                    .unwrap(),
+                )),
+            ),
+        ];
+
+        let page: Vec<RpcContractEvent> = events
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(limit as usize)
+            .map(|(i, (topics, value))| {
+                let ledger = MOCK_OLDEST_LEDGER + 1 + i as u64;
+                RpcContractEvent {
+                    event_type: "contract".to_string(),
+                    ledger,
+                    ledger_closed_at: "2024-12-12T19:40:57Z".to_string(),
+                    contract_id: contract_id.clone(),
+                    id: format!("{:019}-{:010}", ledger << 32, 1),
+                    paging_token: None,
+                    topic: topics.iter().map(encode).collect(),
+                    value: encode(value),
+                    in_successful_contract_call: true,
+                    tx_hash: format!("{:064x}", ledger),
+                }
+            })
+            .collect();
+
+        let scanned = start + page.len();
+        Ok(GetEventsResult {
+            events: page,
+            latest_ledger: MOCK_LATEST_LEDGER,
+            cursor: (scanned > 0).then(|| (scanned - 1).to_string()),
+        })
+    }
+
+    fn mock_account_payments(limit: u32) -> Vec<AccountPayment> {
+        Self::mock_payments(limit)
+            .into_iter()
+            .enumerate()
+            .map(|(i, payment)| {
+                let successful = i % 10 != 9;
+                let submitted_at = chrono::DateTime::parse_from_rfc3339(&payment.created_at)
+                    .map(|closed| {
+                        (closed - chrono::Duration::seconds(i as i64 % 10 + 2)).to_rfc3339()
+                    })
+                    .ok();
+                AccountPayment {
+                    transaction_successful: Some(successful),
+                    transaction: Some(PaymentTransaction {
+                        hash: payment.transaction_hash.clone(),
+                        ledger: 1_000_000 + i as u64,
+                        created_at: payment.created_at.clone(),
+                        successful,
+                        valid_after: submitted_at,
+                        valid_before: None,
+                    }),
+                    payment,
+                }
+            })
+            .collect()
+    }
+
     fn mock_payments(limit: u32) -> Vec<Payment> {
         (0..limit)
             .map(|i| {
@@ -1959,7 +2597,10 @@ impl StellarRpcClient {
         }
 
         let result = self
-            .execute_with_retry(|| self.fetch_liquidity_pools_internal(limit, cursor))
+            .execute_with_failover(&self.horizon_pool, |base| async move {
+                self.fetch_liquidity_pools_internal(&base, limit, cursor)
+                    .await
+            })
             .await;
 
         result.map_err(|e| {
@@ -1970,35 +2611,74 @@ impl StellarRpcClient {
 
     async fn fetch_liquidity_pools_internal(
         &self,
+        base: &str,
         limit: u32,
         cursor: Option<&str>,
     ) -> Result<Vec<HorizonLiquidityPool>, RpcError> {
-        let mut url = format!(
-            "{}/liquidity_pools?order=desc&limit={}",
-            self.horizon_url, limit
-        );
+        let mut url = format!("{}/liquidity_pools?order=desc&limit={}", base, limit);
         if let Some(c) = cursor {
             url.push_str(&format!("&cursor={}", c));
         }
         let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| RpcError::NetworkError(e.to_string()))?;
-        if !response.status().is_success() {
-            return Err(map_response_error(response).await);
+            .transport
+            .send(TransportRequest::get(base, url))
+            .await?;
+        if !response.is_success() {
+            return Err(response.into_error());
         }
-        let horizon_response: HorizonResponse<HorizonLiquidityPool> = response
-            .json()
-            .await
-            .map_err(|e| RpcError::ParseError(e.to_string()))?;
+        let horizon_response: HorizonResponse<HorizonLiquidityPool> = response.json()?;
         Ok(horizon_response
             .embedded
             .map(|e| e.records)
             .unwrap_or_default())
     }
 
+    /// Fetch the liquidity pools holding every one of `reserves`
+    pub async fn fetch_liquidity_pools_for_reserves(
+        &self,
+        reserves: &[Asset],
+        limit: u32,
+    ) -> Result<Vec<HorizonLiquidityPool>, RpcError> {
+        if self.mock_mode {
+            let ids: Vec<String> = reserves.iter().map(Asset::id).collect();
+            return Ok(Self::mock_liquidity_pools(limit)
+                .into_iter()
+                .filter(|pool| {
+                    ids.iter()
+                        .all(|id| pool.reserves.iter().any(|r| &r.asset == id))
+                })
+                .collect());
+        }
+
+        let result = self
+            .execute_with_failover(&self.horizon_pool, |base| async move {
+                let url = format!(
+                    "{}/liquidity_pools?reserves={}&limit={}",
+                    base,
+                    Self::asset_list_param(reserves),
+                    limit
+                );
+                let response = self
+                    .transport
+                    .send(TransportRequest::get(base, url))
+                    .await?;
+                if !response.is_success() {
+                    return Err(response.into_error());
+                }
+                let horizon_response: HorizonResponse<HorizonLiquidityPool> = response.json()?;
+                Ok(horizon_response
+                    .embedded
+                    .map(|e| e.records)
+                    .unwrap_or_default())
+            })
+            .await;
+
+        result.map_err(|e| {
+            metrics::record_rpc_error(e.error_type_label(), "stellar");
+            e
+        })
+    }
+
     /// Fetch a single liquidity pool by ID
     pub async fn fetch_liquidity_pool(
         &self,
@@ -2012,7 +2692,9 @@ impl StellarRpcClient {
         }
 
         let result = self
-            .execute_with_retry(|| self.fetch_liquidity_pool_internal(pool_id))
+            .execute_with_failover(&self.horizon_pool, |base| async move {
+                self.fetch_liquidity_pool_internal(&base, pool_id).await
+            })
             .await;
 
         result.map_err(|e| {
@@ -2023,22 +2705,18 @@ impl StellarRpcClient {
 
     async fn fetch_liquidity_pool_internal(
         &self,
+        base: &str,
         pool_id: &str,
     ) -> Result<HorizonLiquidityPool, RpcError> {
-        let url = format!("{}/liquidity_pools/{}", self.horizon_url, pool_id);
+        let url = format!("{}/liquidity_pools/{}", base, pool_id);
         let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| RpcError::NetworkError(e.to_string()))?;
-        if !response.status().is_success() {
-            return Err(map_response_error(response).await);
+            .transport
+            .send(TransportRequest::get(base, url))
+            .await?;
+        if !response.is_success() {
+            return Err(response.into_error());
         }
-        response
-            .json()
-            .await
-            .map_err(|e| RpcError::ParseError(e.to_string()))
+        response.json()
     }
 
     /// Fetch trades for a specific liquidity pool
@@ -2052,7 +2730,9 @@ impl StellarRpcClient {
         }
 
         let result = self
-            .execute_with_retry(|| self.fetch_pool_trades_internal(pool_id, limit))
+            .execute_with_failover(&self.horizon_pool, |base| async move {
+                self.fetch_pool_trades_internal(&base, pool_id, limit).await
+            })
             .await;
 
         result.map_err(|e| {
@@ -2063,26 +2743,22 @@ impl StellarRpcClient {
 
     async fn fetch_pool_trades_internal(
         &self,
+        base: &str,
         pool_id: &str,
         limit: u32,
     ) -> Result<Vec<Trade>, RpcError> {
         let url = format!(
             "{}/liquidity_pools/{}/trades?order=desc&limit={}",
-            self.horizon_url, pool_id, limit
+            base, pool_id, limit
         );
         let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| RpcError::NetworkError(e.to_string()))?;
-        if !response.status().is_success() {
-            return Err(map_response_error(response).await);
+            .transport
+            .send(TransportRequest::get(base, url))
+            .await?;
+        if !response.is_success() {
+            return Err(response.into_error());
         }
-        let horizon_response: HorizonResponse<Trade> = response
-            .json()
-            .await
-            .map_err(|e| RpcError::ParseError(e.to_string()))?;
+        let horizon_response: HorizonResponse<Trade> = response.json()?;
         Ok(horizon_response
             .embedded
             .map(|e| e.records)
@@ -2100,7 +2776,9 @@ impl StellarRpcClient {
         }
 
         let result = self
-            .execute_with_retry(|| self.fetch_assets_internal(limit, rating_sort))
+            .execute_with_failover(&self.horizon_pool, |base| async move {
+                self.fetch_assets_internal(&base, limit, rating_sort).await
+            })
             .await;
 
         result.map_err(|e| {
@@ -2111,34 +2789,164 @@ impl StellarRpcClient {
 
     async fn fetch_assets_internal(
         &self,
+        base: &str,
         limit: u32,
         rating_sort: bool,
     ) -> Result<Vec<HorizonAsset>, RpcError> {
-        let mut url = format!("{}/assets?limit={}", self.horizon_url, limit);
+        let mut url = format!("{}/assets?limit={}", base, limit);
         if rating_sort {
             url.push_str("&order=desc&sort=rating");
         } else {
             url.push_str("&order=desc");
         }
         let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| RpcError::NetworkError(e.to_string()))?;
-        if !response.status().is_success() {
-            return Err(map_response_error(response).await);
+            .transport
+            .send(TransportRequest::get(base, url))
+            .await?;
+        if !response.is_success() {
+            return Err(response.into_error());
         }
-        let horizon_response: HorizonResponse<HorizonAsset> = response
-            .json()
-            .await
-            .map_err(|e| RpcError::ParseError(e.to_string()))?;
+        let horizon_response: HorizonResponse<HorizonAsset> = response.json()?;
+        Ok(horizon_response
+            .embedded
+            .map(|e| e.records)
+            .unwrap_or_default())
+    }
+
+    /// Find paths that deliver the most of any `destination_assets` for exactly
+    /// `source_amount` of `source_asset` (Horizon `/paths/strict-send`)
+    pub async fn fetch_strict_send_paths(
+        &self,
+        source_asset: &Asset,
+        source_amount: &str,
+        destination_assets: &[Asset],
+    ) -> Result<Vec<PaymentPath>, RpcError> {
+        if self.mock_mode {
+            return Ok(destination_assets
+                .iter()
+                .filter_map(|destination| {
+                    Self::mock_payment_path(source_asset, destination, source_amount, false)
+                })
+                .collect());
+        }
+
+        let result = self
+            .execute_with_failover(&self.horizon_pool, |base| async move {
+                let source_params = Self::asset_to_query_params("source", source_asset)
+                    .map_err(|e| RpcError::ParseError(e.to_string()))?;
+                let url = format!(
+                    "{}/paths/strict-send?{}&source_amount={}&destination_assets={}",
+                    base,
+                    source_params,
+                    source_amount,
+                    Self::asset_list_param(destination_assets)
+                );
+                self.fetch_payment_paths_internal(&base, url).await
+            })
+            .await;
+
+        result.map_err(|e| {
+            metrics::record_rpc_error(e.error_type_label(), "stellar");
+            e
+        })
+    }
+
+    /// Find paths that deliver exactly `destination_amount` of `destination_asset` for
+    /// the least of any `source_assets` (Horizon `/paths/strict-receive`)
+    pub async fn fetch_strict_receive_paths(
+        &self,
+        source_assets: &[Asset],
+        destination_asset: &Asset,
+        destination_amount: &str,
+    ) -> Result<Vec<PaymentPath>, RpcError> {
+        if self.mock_mode {
+            return Ok(source_assets
+                .iter()
+                .filter_map(|source| {
+                    Self::mock_payment_path(source, destination_asset, destination_amount, true)
+                })
+                .collect());
+        }
+
+        let result = self
+            .execute_with_failover(&self.horizon_pool, |base| async move {
+                let destination_params =
+                    Self::asset_to_query_params("destination", destination_asset)
+                        .map_err(|e| RpcError::ParseError(e.to_string()))?;
+                let url = format!(
+                    "{}/paths/strict-receive?source_assets={}&{}&destination_amount={}",
+                    base,
+                    Self::asset_list_param(source_assets),
+                    destination_params,
+                    destination_amount
+                );
+                self.fetch_payment_paths_internal(&base, url).await
+            })
+            .await;
+
+        result.map_err(|e| {
+            metrics::record_rpc_error(e.error_type_label(), "stellar");
+            e
+        })
+    }
+
+    async fn fetch_payment_paths_internal(
+        &self,
+        base: &str,
+        url: String,
+    ) -> Result<Vec<PaymentPath>, RpcError> {
+        let response = self
+            .transport
+            .send(TransportRequest::get(base, url))
+            .await?;
+        if !response.is_success() {
+            return Err(response.into_error());
+        }
+        let horizon_response: HorizonResponse<PaymentPath> = response.json()?;
         Ok(horizon_response
             .embedded
             .map(|e| e.records)
             .unwrap_or_default())
     }
 
+    /// Comma-separated `native`/`CODE:ISSUER` list, as the path endpoints expect
+    fn asset_list_param(assets: &[Asset]) -> String {
+        assets.iter().map(Asset::id).collect::<Vec<_>>().join(",")
+    }
+
+    // ============================================================================
+    // Payment Path Mock Data
+    // ============================================================================
+
+    /// A direct path at the mock order book's best ask; `amount` is the destination
+    /// amount when `strict_receive` and the source amount otherwise
+    fn mock_payment_path(
+        source: &Asset,
+        destination: &Asset,
+        amount: &str,
+        strict_receive: bool,
+    ) -> Option<PaymentPath> {
+        const RATE: f64 = 200.0 / 201.0;
+        let amount: f64 = amount.parse().ok()?;
+        let (source_amount, destination_amount) = if strict_receive {
+            (amount / RATE, amount)
+        } else {
+            (amount, amount * RATE)
+        };
+
+        Some(PaymentPath {
+            source_asset_type: source.asset_type.clone(),
+            source_asset_code: source.asset_code.clone(),
+            source_asset_issuer: source.asset_issuer.clone(),
+            source_amount: format!("{:.7}", source_amount),
+            destination_asset_type: destination.asset_type.clone(),
+            destination_asset_code: destination.asset_code.clone(),
+            destination_asset_issuer: destination.asset_issuer.clone(),
+            destination_amount: format!("{:.7}", destination_amount),
+            path: Vec::new(),
+        })
+    }
+
     // ============================================================================
     // Liquidity Pool Mock Data
     // ============================================================================
@@ -0,0 +1,243 @@
+//! Horizon server-sent events (SSE) streaming.
+//!
+//! Horizon streams `/payments`, `/trades`, `/ledgers` and `/order_book` when
+//! requested with `Accept: text/event-stream`. Each record arrives as an event
+//! whose `id` is the record's paging token, so a dropped connection resumes
+//! from the last delivered record by reconnecting with `cursor=<id>`.
+//! `StellarRpcClient::stream` drives the connection; this module holds the
+//! stream descriptions and the SSE wire parser.
+
+use std::time::Duration;
+
+use super::stellar::{AccountPayment, Asset, LedgerInfo, OrderBook, Trade};
+
+/// Events buffered between the stream task and its consumer
+pub const STREAM_BUFFER: usize = 256;
+
+/// Reconnect when nothing (not even a keep-alive) arrives for this long
+pub const STREAM_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A Horizon collection that can be streamed
+#[derive(Debug, Clone)]
+pub enum HorizonStream {
+    Payments,
+    Trades,
+    Ledgers,
+    OrderBook { selling: Asset, buying: Asset },
+}
+
+impl HorizonStream {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HorizonStream::Payments => "payments",
+            HorizonStream::Trades => "trades",
+            HorizonStream::Ledgers => "ledgers",
+            HorizonStream::OrderBook { .. } => "order_book",
+        }
+    }
+
+    /// Order book events are snapshots without paging tokens, so that stream
+    /// always restarts from the current book
+    pub fn supports_cursor(&self) -> bool {
+        !matches!(self, HorizonStream::OrderBook { .. })
+    }
+
+    /// Path and query for the stream request, without the cursor
+    pub fn path(&self) -> String {
+        match self {
+            // Failed payments are included so consumers see real success rates
+            HorizonStream::Payments => "/payments?include_failed=true".to_string(),
+            HorizonStream::Trades => "/trades?".to_string(),
+            HorizonStream::Ledgers => "/ledgers?".to_string(),
+            HorizonStream::OrderBook { selling, buying } => format!(
+                "/order_book?{}&{}",
+                asset_query("selling", selling),
+                asset_query("buying", buying)
+            ),
+        }
+    }
+
+    /// Full stream URL for `base`, resuming after `cursor` when given
+    pub fn url(&self, base: &str, cursor: Option<&str>) -> String {
+        let mut url = format!("{}{}", base, self.path());
+        if let (true, Some(cursor)) = (self.supports_cursor(), cursor) {
+            if !url.ends_with('?') {
+                url.push('&');
+            }
+            url.push_str("cursor=");
+            url.push_str(&urlencoding::encode(cursor));
+        }
+        url.trim_end_matches('?').to_string()
+    }
+
+    /// Decode the data of one SSE event into a typed record
+    pub fn decode(&self, data: &str) -> Result<StreamEvent, serde_json::Error> {
+        Ok(match self {
+            HorizonStream::Payments => StreamEvent::Payment(Box::new(serde_json::from_str(data)?)),
+            HorizonStream::Trades => StreamEvent::Trade(serde_json::from_str(data)?),
+            HorizonStream::Ledgers => StreamEvent::Ledger(serde_json::from_str(data)?),
+            HorizonStream::OrderBook { .. } => StreamEvent::OrderBook(serde_json::from_str(data)?),
+        })
+    }
+}
+
+fn asset_query(prefix: &str, asset: &Asset) -> String {
+    match (&asset.asset_code, &asset.asset_issuer) {
+        (Some(code), Some(issuer)) if asset.asset_type != "native" => format!(
+            "{p}_asset_type={}&{p}_asset_code={}&{p}_asset_issuer={}",
+            asset.asset_type,
+            code,
+            issuer,
+            p = prefix
+        ),
+        _ => format!("{}_asset_type=native", prefix),
+    }
+}
+
+/// A record delivered by a Horizon stream
+#[derive(Debug, Clone)]
+pub enum StreamEvent {
+    Payment(Box<AccountPayment>),
+    Trade(Trade),
+    Ledger(LedgerInfo),
+    OrderBook(OrderBook),
+}
+
+/// One dispatched server-sent event
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SseEvent {
+    pub id: Option<String>,
+    pub event: Option<String>,
+    pub data: String,
+    pub retry: Option<u64>,
+}
+
+impl SseEvent {
+    /// Horizon's `open`/`close` handshake events carry no record
+    pub fn is_control(&self) -> bool {
+        matches!(self.event.as_deref(), Some("open") | Some("close") | Some("error"))
+            || self.data.is_empty()
+            // Handshake payloads are bare JSON strings such as "hello" and "byebye"
+            || serde_json::from_str::<String>(&self.data).is_ok()
+    }
+}
+
+/// Incremental parser for the `text/event-stream` format. Bytes are buffered
+/// until a full line arrives, so chunks may split lines or UTF-8 sequences.
+#[derive(Debug, Default)]
+pub struct SseParser {
+    buffer: Vec<u8>,
+    pending: SseEvent,
+    has_data: bool,
+}
+
+impl SseParser {
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let mut line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            let line = String::from_utf8_lossy(&line);
+
+            if line.is_empty() {
+                if self.has_data || self.pending.event.is_some() || self.pending.retry.is_some() {
+                    events.push(std::mem::take(&mut self.pending));
+                }
+                self.pending = SseEvent::default();
+                self.has_data = false;
+                continue;
+            }
+            if line.starts_with(':') {
+                continue; // comment / keep-alive
+            }
+
+            let (field, value) = match line.split_once(':') {
+                Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+                None => (line.as_ref(), ""),
+            };
+            match field {
+                "data" => {
+                    if self.has_data {
+                        self.pending.data.push('\n');
+                    }
+                    self.pending.data.push_str(value);
+                    self.has_data = true;
+                }
+                "id" => self.pending.id = Some(value.to_string()),
+                "event" => self.pending.event = Some(value.to_string()),
+                "retry" => self.pending.retry = value.parse().ok(),
+                _ => {}
+            }
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parser_handles_split_chunks_and_handshake() {
+        let mut parser = SseParser::default();
+        let mut events =
+            parser.feed(b"retry: 1000\nevent: open\ndata: \"hello\"\n\n: keep-alive\n");
+        events.extend(parser.feed(b"id: 1234-1\r\ndata: {\"a\":"));
+        assert_eq!(events.len(), 1);
+        assert!(events[0].is_control());
+        assert_eq!(events[0].retry, Some(1000));
+
+        let events = parser.feed(b"1}\r\n\r\n");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].id.as_deref(), Some("1234-1"));
+        assert_eq!(events[0].data, "{\"a\":1}");
+        assert!(!events[0].is_control());
+    }
+
+    #[test]
+    fn parser_joins_multiline_data() {
+        let mut parser = SseParser::default();
+        let events = parser.feed(b"data: line1\ndata: line2\n\n");
+        assert_eq!(events[0].data, "line1\nline2");
+    }
+
+    #[test]
+    fn stream_urls_resume_from_cursor() {
+        let base = "https://horizon.stellar.org";
+        assert_eq!(
+            HorizonStream::Payments.url(base, Some("now")),
+            "https://horizon.stellar.org/payments?include_failed=true&cursor=now"
+        );
+        assert_eq!(
+            HorizonStream::Ledgers.url(base, Some("123-4")),
+            "https://horizon.stellar.org/ledgers?cursor=123-4"
+        );
+        assert_eq!(
+            HorizonStream::Trades.url(base, None),
+            "https://horizon.stellar.org/trades"
+        );
+
+        let book = HorizonStream::OrderBook {
+            selling: Asset {
+                asset_type: "native".to_string(),
+                asset_code: None,
+                asset_issuer: None,
+            },
+            buying: Asset {
+                asset_type: "credit_alphanum4".to_string(),
+                asset_code: Some("USDC".to_string()),
+                asset_issuer: Some("GISSUER".to_string()),
+            },
+        };
+        assert_eq!(
+            book.url(base, Some("ignored")),
+            "https://horizon.stellar.org/order_book?selling_asset_type=native&buying_asset_type=credit_alphanum4&buying_asset_code=USDC&buying_asset_issuer=GISSUER"
+        );
+    }
+}
@@ -0,0 +1,650 @@
+//! Pluggable HTTP transport for `StellarRpcClient`.
+//!
+//! `HttpTransport` talks to the network. `RecordingTransport` wraps another
+//! transport and writes every request/response pair to a fixture directory,
+//! and `ReplayTransport` serves those fixtures back deterministically so tests
+//! and bug reproductions can run offline against captured traffic.
+//!
+//! Fixtures are keyed by method, path and query relative to the provider base
+//! URL, and request body. The base URL is dropped, path included, so API keys
+//! embedded in provider URLs never reach a fixture and a capture taken against
+//! one provider replays against any provider pool configuration.
+
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Client, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::rpc::error::RpcError;
+
+/// Response headers never written to fixtures
+const REDACTED_HEADERS: &[&str] = &["set-cookie", "authorization"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum HttpMethod {
+    Get,
+    Post,
+}
+
+#[derive(Debug, Clone)]
+pub struct TransportRequest {
+    pub method: HttpMethod,
+    /// Provider base URL the request was built from
+    pub base: String,
+    pub url: String,
+    pub body: Option<Value>,
+}
+
+impl TransportRequest {
+    /// GET `url`, which starts with the provider's `base`
+    pub fn get(base: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            method: HttpMethod::Get,
+            base: base.into(),
+            url: url.into(),
+            body: None,
+        }
+    }
+
+    /// POST to the provider's `base` URL itself, as JSON-RPC does
+    pub fn post(base: impl Into<String>, body: Value) -> Self {
+        let base = base.into();
+        Self {
+            method: HttpMethod::Post,
+            url: base.clone(),
+            base,
+            body: Some(body),
+        }
+    }
+
+    /// Path and query of the request URL after the provider base URL. URLs
+    /// outside the base only lose their scheme and host.
+    fn path(&self) -> String {
+        let base = self.base.trim_end_matches('/');
+        if let Some(rest) = self.url.strip_prefix(base).filter(|_| !base.is_empty()) {
+            if rest.is_empty() || rest.starts_with('?') {
+                return format!("/{}", rest);
+            }
+            if rest.starts_with('/') {
+                return rest.to_string();
+            }
+        }
+        match reqwest::Url::parse(&self.url) {
+            Ok(url) => match url.query() {
+                Some(query) => format!("{}?{}", url.path(), query),
+                None => url.path().to_string(),
+            },
+            Err(_) => self.url.clone(),
+        }
+    }
+
+    /// Key identifying equivalent requests across providers
+    pub fn fixture_key(&self) -> String {
+        let body = self.body.as_ref().map(Value::to_string).unwrap_or_default();
+        let method = match self.method {
+            HttpMethod::Get => "GET",
+            HttpMethod::Post => "POST",
+        };
+        format!("{} {} {}", method, self.path(), body)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TransportResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+impl TransportResponse {
+    pub fn is_success(&self) -> bool {
+        self.status.is_success()
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, RpcError> {
+        serde_json::from_str(&self.body).map_err(|e| RpcError::ParseError(e.to_string()))
+    }
+
+    /// Map a non-success response to the matching `RpcError`
+    pub fn into_error(self) -> RpcError {
+        let retry_after = self
+            .headers
+            .get("Retry-After")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|s| s.parse::<u64>().ok());
+        let body = if self.body.is_empty() {
+            "Unknown error".to_string()
+        } else {
+            self.body
+        };
+        super::stellar::status_to_rpc_error(self.status, body, retry_after)
+    }
+}
+
+/// Sends RPC/Horizon requests. Implementations must be cheap to share
+/// between concurrent callers.
+#[async_trait]
+pub trait RpcTransport: Send + Sync {
+    async fn send(&self, request: TransportRequest) -> Result<TransportResponse, RpcError>;
+
+    /// Short name for logs (e.g. "http", "replay")
+    fn name(&self) -> &'static str;
+}
+
+/// Live transport over reqwest
+pub struct HttpTransport {
+    client: Client,
+}
+
+impl HttpTransport {
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl RpcTransport for HttpTransport {
+    async fn send(&self, request: TransportRequest) -> Result<TransportResponse, RpcError> {
+        let builder = match request.method {
+            HttpMethod::Get => self.client.get(&request.url),
+            HttpMethod::Post => self.client.post(&request.url),
+        };
+        let builder = match &request.body {
+            Some(body) => builder.json(body),
+            None => builder,
+        };
+
+        let response = builder.send().await.map_err(map_reqwest_error)?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.text().await.map_err(map_reqwest_error)?;
+
+        Ok(TransportResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "http"
+    }
+}
+
+fn map_reqwest_error(e: reqwest::Error) -> RpcError {
+    if e.is_timeout() {
+        RpcError::TimeoutError(e.to_string())
+    } else {
+        RpcError::NetworkError(e.to_string())
+    }
+}
+
+// ============================================================================
+// Fixtures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: HttpMethod,
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Parsed JSON body; non-JSON bodies are kept in `body_text`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_text: Option<String>,
+}
+
+impl RecordedResponse {
+    fn from_response(response: &TransportResponse) -> Self {
+        let headers = response
+            .headers
+            .iter()
+            .filter(|(name, _)| !REDACTED_HEADERS.contains(&name.as_str()))
+            .filter_map(|(name, value)| {
+                value
+                    .to_str()
+                    .ok()
+                    .map(|v| (name.as_str().to_string(), v.to_string()))
+            })
+            .collect();
+
+        let (body, body_text) = match serde_json::from_str::<Value>(&response.body) {
+            Ok(json) => (Some(json), None),
+            Err(_) => (None, Some(response.body.clone())),
+        };
+
+        Self {
+            status: response.status.as_u16(),
+            headers,
+            body,
+            body_text,
+        }
+    }
+
+    fn to_response(&self) -> TransportResponse {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                headers.insert(name, value);
+            }
+        }
+
+        let body = match (&self.body, &self.body_text) {
+            (Some(json), _) => json.to_string(),
+            (None, Some(text)) => text.clone(),
+            (None, None) => String::new(),
+        };
+
+        TransportResponse {
+            status: StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK),
+            headers,
+            body,
+        }
+    }
+}
+
+/// One fixture file: a request and every response recorded for it, in order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fixture {
+    pub request: RecordedRequest,
+    pub responses: Vec<RecordedResponse>,
+}
+
+impl Fixture {
+    fn key(&self) -> String {
+        TransportRequest {
+            method: self.request.method,
+            base: String::new(),
+            url: self.request.path.clone(),
+            body: self.request.body.clone(),
+        }
+        .fixture_key()
+    }
+}
+
+/// File name for a request: readable path prefix plus a hash of the full key
+fn fixture_file_name(request: &TransportRequest) -> String {
+    let key = request.fixture_key();
+    let digest = hex::encode(Sha256::digest(key.as_bytes()));
+
+    let path = request.path();
+    let endpoint = match &request.body {
+        // JSON-RPC requests share a path; name them after the method instead
+        Some(body) => body
+            .get("method")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or(path),
+        None => path.split('?').next().unwrap_or_default().to_string(),
+    };
+    let slug: String = endpoint
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>()
+        .trim_matches('_')
+        .chars()
+        .take(48)
+        .collect();
+
+    format!("{}_{}.json", slug, &digest[..12])
+}
+
+/// Forwards to an inner transport and appends each exchange to the fixture
+/// directory. Transport errors (no response) are not recorded.
+pub struct RecordingTransport {
+    inner: Arc<dyn RpcTransport>,
+    dir: PathBuf,
+    write_lock: Mutex<()>,
+}
+
+impl RecordingTransport {
+    pub fn new(inner: Arc<dyn RpcTransport>, dir: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self {
+            inner,
+            dir,
+            write_lock: Mutex::new(()),
+        })
+    }
+
+    async fn record(
+        &self,
+        request: &TransportRequest,
+        response: &TransportResponse,
+    ) -> std::io::Result<()> {
+        let _guard = self.write_lock.lock().await;
+        let path = self.dir.join(fixture_file_name(request));
+
+        let mut fixture = match tokio::fs::read(&path).await {
+            Ok(bytes) => serde_json::from_slice::<Fixture>(&bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Fixture {
+                request: RecordedRequest {
+                    method: request.method,
+                    path: request.path(),
+                    body: request.body.clone(),
+                },
+                responses: Vec::new(),
+            },
+            Err(e) => return Err(e),
+        };
+        fixture
+            .responses
+            .push(RecordedResponse::from_response(response));
+
+        let json = serde_json::to_vec_pretty(&fixture)?;
+        tokio::fs::write(&path, json).await
+    }
+}
+
+#[async_trait]
+impl RpcTransport for RecordingTransport {
+    async fn send(&self, request: TransportRequest) -> Result<TransportResponse, RpcError> {
+        let response = self.inner.send(request.clone()).await?;
+        if let Err(e) = self.record(&request, &response).await {
+            warn!("Failed to record RPC fixture for {}: {}", request.url, e);
+        }
+        Ok(response)
+    }
+
+    fn name(&self) -> &'static str {
+        "record"
+    }
+}
+
+struct ReplayEntry {
+    responses: Vec<TransportResponse>,
+    next: AtomicUsize,
+}
+
+/// Serves recorded fixtures. Repeated requests step through the recorded
+/// responses in order and then keep returning the last one. Requests with no
+/// fixture fail with a 404 `ServerError`, which callers do not retry.
+pub struct ReplayTransport {
+    entries: HashMap<String, ReplayEntry>,
+}
+
+impl ReplayTransport {
+    /// Load every `*.json` fixture in `dir`
+    pub fn from_dir(dir: impl AsRef<Path>) -> std::io::Result<Self> {
+        let mut fixtures = Vec::new();
+        for entry in std::fs::read_dir(dir.as_ref())? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let bytes = std::fs::read(&path)?;
+            let fixture: Fixture = serde_json::from_slice(&bytes).map_err(|e| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("{}: {}", path.display(), e),
+                )
+            })?;
+            fixtures.push(fixture);
+        }
+
+        info!(
+            "Loaded {} RPC fixtures from {}",
+            fixtures.len(),
+            dir.as_ref().display()
+        );
+        Ok(Self::from_fixtures(fixtures))
+    }
+
+    pub fn from_fixtures(fixtures: impl IntoIterator<Item = Fixture>) -> Self {
+        let mut entries: HashMap<String, ReplayEntry> = HashMap::new();
+        for fixture in fixtures {
+            let responses = fixture.responses.iter().map(RecordedResponse::to_response);
+            entries
+                .entry(fixture.key())
+                .or_insert_with(|| ReplayEntry {
+                    responses: Vec::new(),
+                    next: AtomicUsize::new(0),
+                })
+                .responses
+                .extend(responses);
+        }
+        Self { entries }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[async_trait]
+impl RpcTransport for ReplayTransport {
+    async fn send(&self, request: TransportRequest) -> Result<TransportResponse, RpcError> {
+        let key = request.fixture_key();
+        let entry = self
+            .entries
+            .get(&key)
+            .filter(|e| !e.responses.is_empty())
+            .ok_or_else(|| RpcError::ServerError {
+                status: 404,
+                message: format!("No recorded fixture for {}", key.trim_end()),
+            })?;
+
+        let index = entry.next.fetch_add(1, Ordering::Relaxed);
+        let response = entry
+            .responses
+            .get(index)
+            .or_else(|| entry.responses.last())
+            .cloned()
+            .expect("entry has at least one response");
+        Ok(response)
+    }
+
+    fn name(&self) -> &'static str {
+        "replay"
+    }
+}
+
+/// Transport selected by `RPC_TRANSPORT_MODE` (`live`, `record` or `replay`)
+/// with fixtures under `RPC_FIXTURE_DIR` (default `tests/fixtures/rpc`).
+/// Recording falls back to the live transport if the fixture directory is
+/// unusable; replay and unknown modes fail instead of reaching the network.
+pub fn transport_from_env(client: Client) -> std::io::Result<Arc<dyn RpcTransport>> {
+    let live: Arc<dyn RpcTransport> = Arc::new(HttpTransport::new(client));
+    let mode = std::env::var("RPC_TRANSPORT_MODE").unwrap_or_else(|_| "live".to_string());
+    let dir = std::env::var("RPC_FIXTURE_DIR").unwrap_or_else(|_| "tests/fixtures/rpc".to_string());
+
+    match mode.to_lowercase().as_str() {
+        "record" => match RecordingTransport::new(live.clone(), &dir) {
+            Ok(transport) => {
+                info!("Recording RPC traffic to {}", dir);
+                Ok(Arc::new(transport))
+            }
+            Err(e) => {
+                warn!(
+                    "Cannot record RPC fixtures to {}: {}; using live transport",
+                    dir, e
+                );
+                Ok(live)
+            }
+        },
+        "replay" => match ReplayTransport::from_dir(&dir) {
+            Ok(transport) => Ok(Arc::new(transport)),
+            Err(e) => Err(std::io::Error::new(
+                e.kind(),
+                format!("cannot replay RPC fixtures from {}: {}", dir, e),
+            )),
+        },
+        "live" => Ok(live),
+        other => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("unknown RPC_TRANSPORT_MODE '{}'", other),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct StaticTransport;
+
+    #[async_trait]
+    impl RpcTransport for StaticTransport {
+        async fn send(&self, request: TransportRequest) -> Result<TransportResponse, RpcError> {
+            let mut headers = HeaderMap::new();
+            headers.insert("x-ratelimit-remaining", HeaderValue::from_static("42"));
+            headers.insert("set-cookie", HeaderValue::from_static("secret"));
+            Ok(TransportResponse {
+                status: StatusCode::OK,
+                headers,
+                body: json!({ "path": request.url }).to_string(),
+            })
+        }
+
+        fn name(&self) -> &'static str {
+            "static"
+        }
+    }
+
+    fn horizon_get(base: &str, path: &str) -> TransportRequest {
+        TransportRequest::get(base, format!("{}{}", base, path))
+    }
+
+    #[test]
+    fn fixture_key_ignores_host() {
+        let a = horizon_get("https://horizon.stellar.org", "/ledgers?limit=1");
+        let b = horizon_get("https://horizon.example.com", "/ledgers?limit=1");
+        assert_eq!(a.fixture_key(), b.fixture_key());
+
+        let rpc = TransportRequest::post(
+            "https://rpc.example.com",
+            json!({ "jsonrpc": "2.0", "method": "getLedgers", "id": 1 }),
+        );
+        assert!(fixture_file_name(&rpc).starts_with("getLedgers_"));
+    }
+
+    #[tokio::test]
+    async fn record_then_replay_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = RecordingTransport::new(Arc::new(StaticTransport), dir.path()).unwrap();
+
+        let request = horizon_get("https://horizon.stellar.org", "/ledgers?limit=1");
+        recorder.send(request.clone()).await.unwrap();
+        recorder.send(request.clone()).await.unwrap();
+
+        let files: Vec<_> = std::fs::read_dir(dir.path()).unwrap().collect();
+        assert_eq!(files.len(), 1);
+
+        let replay = ReplayTransport::from_dir(dir.path()).unwrap();
+        let response = replay
+            .send(horizon_get("http://localhost", "/ledgers?limit=1"))
+            .await
+            .unwrap();
+
+        assert!(response.is_success());
+        assert_eq!(response.headers.get("x-ratelimit-remaining").unwrap(), "42");
+        assert!(response.headers.get("set-cookie").is_none());
+        let body: Value = response.json().unwrap();
+        assert_eq!(body["path"], "https://horizon.stellar.org/ledgers?limit=1");
+    }
+
+    #[tokio::test]
+    async fn replay_steps_through_responses_then_repeats_last() {
+        let fixture: Fixture = serde_json::from_value(json!({
+            "request": { "method": "GET", "path": "/ledgers" },
+            "responses": [
+                { "status": 503, "body_text": "unavailable" },
+                { "status": 200, "body": { "sequence": 1 } }
+            ]
+        }))
+        .unwrap();
+        let replay = ReplayTransport::from_fixtures([fixture]);
+
+        let first = replay
+            .send(horizon_get("https://h", "/ledgers"))
+            .await
+            .unwrap();
+        assert!(matches!(
+            first.into_error(),
+            RpcError::ServerError { status: 503, .. }
+        ));
+
+        for _ in 0..2 {
+            let next = replay
+                .send(horizon_get("https://h", "/ledgers"))
+                .await
+                .unwrap();
+            assert_eq!(next.json::<Value>().unwrap()["sequence"], 1);
+        }
+
+        let missing = replay.send(horizon_get("https://h", "/trades")).await;
+        assert!(matches!(
+            missing,
+            Err(RpcError::ServerError { status: 404, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn fixtures_drop_provider_base_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = RecordingTransport::new(Arc::new(StaticTransport), dir.path()).unwrap();
+
+        let horizon = "https://horizon.provider.io/api/SECRETKEY/";
+        let rpc = "https://rpc.provider.io/v1/SECRETKEY";
+        let payload = json!({ "jsonrpc": "2.0", "method": "getLedgers", "id": 1 });
+        recorder
+            .send(TransportRequest::get(
+                horizon,
+                format!("{}ledgers?limit=1", horizon),
+            ))
+            .await
+            .unwrap();
+        recorder
+            .send(TransportRequest::post(rpc, payload.clone()))
+            .await
+            .unwrap();
+
+        for entry in std::fs::read_dir(dir.path()).unwrap() {
+            let path = entry.unwrap().path();
+            assert!(!path.to_string_lossy().contains("SECRETKEY"));
+            let fixture: Fixture = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+            assert!(!fixture.request.path.contains("SECRETKEY"));
+        }
+
+        let replay = ReplayTransport::from_dir(dir.path()).unwrap();
+        replay
+            .send(horizon_get("https://horizon.other.org", "/ledgers?limit=1"))
+            .await
+            .unwrap();
+        replay
+            .send(TransportRequest::post(
+                "https://soroban.other.org/",
+                payload,
+            ))
+            .await
+            .unwrap();
+    }
+}
@@ -53,6 +53,12 @@ pub async fn rpc_health_check(
     }
 }
 
+/// Health, latency and circuit state of each configured RPC/Horizon provider
+#[tracing::instrument(skip(client))]
+pub async fn get_provider_status(State(client): State<Arc<StellarRpcClient>>) -> impl IntoResponse {
+    Json(client.provider_status().await)
+}
+
 /// Get latest ledger information
 #[tracing::instrument(skip(client))]
 pub async fn get_latest_ledger(
@@ -1,10 +1,13 @@
+use crate::database::DbPool;
+use crate::with_pool;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use serde::Serialize;
-use sqlx::{Pool, Sqlite};
+
 use std::sync::Arc;
 use tracing::{info, warn};
 
+use crate::ingestion::xdr::{format_stroops, DecodedLedger, DecodedOperationKind};
 use crate::rpc::{HorizonOperation, StellarRpcClient};
 
 #[derive(Debug, Clone, Serialize, sqlx::FromRow)]
@@ -34,12 +37,12 @@ pub struct DestinationAccountPattern {
 }
 
 pub struct AccountMergeDetector {
-    pool: Pool<Sqlite>,
+    pool: DbPool,
     rpc_client: Arc<StellarRpcClient>,
 }
 
 impl AccountMergeDetector {
-    pub fn new(pool: Pool<Sqlite>, rpc_client: Arc<StellarRpcClient>) -> Self {
+    pub fn new(pool: DbPool, rpc_client: Arc<StellarRpcClient>) -> Self {
         Self { pool, rpc_client }
     }
 
@@ -74,6 +77,54 @@ impl AccountMergeDetector {
         Ok(inserted)
     }
 
+    /// Extracts successful account merges from a ledger decoded from its close meta.
+    ///
+    /// The merged balance comes straight from the operation result, so no effects
+    /// lookup is needed.
+    pub async fn process_decoded_ledger(&self, ledger: &DecodedLedger) -> Result<u64> {
+        let mut inserted = 0_u64;
+
+        for tx in &ledger.transactions {
+            for operation in tx.operations.iter().filter(|op| op.successful) {
+                let DecodedOperationKind::AccountMerge {
+                    destination,
+                    merged_balance,
+                } = &operation.kind
+                else {
+                    continue;
+                };
+
+                let merged_balance = merged_balance
+                    .map(format_stroops)
+                    .and_then(|amount| amount.parse::<f64>().ok())
+                    .unwrap_or(0.0);
+
+                let event = AccountMergeEvent {
+                    operation_id: operation.id.clone(),
+                    transaction_hash: tx.hash.clone(),
+                    ledger_sequence: ledger.sequence as i64,
+                    source_account: operation.source_account.clone(),
+                    destination_account: destination.clone(),
+                    merged_balance,
+                    created_at: ledger.close_time,
+                };
+
+                if self.persist_merge_event(&event).await? {
+                    inserted += 1;
+                }
+            }
+        }
+
+        if inserted > 0 {
+            info!(
+                "Detected and stored {} account merge operations for ledger {}",
+                inserted, ledger.sequence
+            );
+        }
+
+        Ok(inserted)
+    }
+
     async fn persist_merge_from_operation(
         &self,
         ledger_sequence: u64,
@@ -142,63 +193,70 @@ impl AccountMergeDetector {
     }
 
     async fn persist_merge_event(&self, event: &AccountMergeEvent) -> Result<bool> {
-        let result = sqlx::query(
-            r#"
-            INSERT INTO account_merges (
-                operation_id,
-                transaction_hash,
-                ledger_sequence,
-                source_account,
-                destination_account,
-                merged_balance,
-                created_at
+        let result = with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO account_merges (
+                    operation_id,
+                    transaction_hash,
+                    ledger_sequence,
+                    source_account,
+                    destination_account,
+                    merged_balance,
+                    created_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                ON CONFLICT (operation_id) DO NOTHING
+                "#,
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
-            ON CONFLICT (operation_id) DO NOTHING
-            "#,
-        )
-        .bind(&event.operation_id)
-        .bind(&event.transaction_hash)
-        .bind(event.ledger_sequence)
-        .bind(&event.source_account)
-        .bind(&event.destination_account)
-        .bind(event.merged_balance)
-        .bind(event.created_at)
-        .execute(&self.pool)
-        .await?;
-
-        Ok(result.rows_affected() > 0)
+            .bind(&event.operation_id)
+            .bind(&event.transaction_hash)
+            .bind(event.ledger_sequence)
+            .bind(&event.source_account)
+            .bind(&event.destination_account)
+            .bind(event.merged_balance)
+            .bind(event.created_at)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
+
+        Ok(result > 0)
     }
 
     pub async fn get_recent_merges(&self, limit: i64) -> Result<Vec<AccountMergeEvent>> {
-        let rows = sqlx::query_as::<_, AccountMergeEvent>(
-            r#"
-            SELECT operation_id, transaction_hash, ledger_sequence, source_account, destination_account, merged_balance, created_at
-            FROM account_merges
-            ORDER BY created_at DESC
-            LIMIT $1
-            "#,
-        )
-        .bind(limit)
-        .fetch_all(&self.pool)
-        .await?;
+        let rows = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, AccountMergeEvent>(
+                r#"
+                SELECT operation_id, transaction_hash, ledger_sequence, source_account, destination_account, merged_balance, created_at
+                FROM account_merges
+                ORDER BY created_at DESC
+                LIMIT $1
+                "#,
+            )
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+        })?;
 
         Ok(rows)
     }
 
     pub async fn get_merge_stats(&self) -> Result<AccountMergeStats> {
-        let row: (i64, f64, i64, i64) = sqlx::query_as(
-            r#"
-            SELECT
-                COUNT(*) AS total_merges,
-                COALESCE(SUM(merged_balance), 0.0) AS total_merged_balance,
-                COUNT(DISTINCT source_account) AS unique_sources,
-                COUNT(DISTINCT destination_account) AS unique_destinations
-            FROM account_merges
-            "#,
-        )
-        .fetch_one(&self.pool)
-        .await?;
+        let row: (i64, f64, i64, i64) = with_pool!(&self.pool, |pool| {
+            sqlx::query_as(
+                r#"
+                SELECT
+                    COUNT(*) AS total_merges,
+                    COALESCE(SUM(merged_balance), 0.0) AS total_merged_balance,
+                    COUNT(DISTINCT source_account) AS unique_sources,
+                    COUNT(DISTINCT destination_account) AS unique_destinations
+                FROM account_merges
+                "#,
+            )
+            .fetch_one(pool)
+            .await
+        })?;
 
         Ok(AccountMergeStats {
             total_merges: row.0,
@@ -212,21 +270,23 @@ impl AccountMergeDetector {
         &self,
         limit: i64,
     ) -> Result<Vec<DestinationAccountPattern>> {
-        let rows = sqlx::query_as::<_, DestinationAccountPattern>(
-            r#"
-            SELECT
-                destination_account,
-                COUNT(*) AS merge_count,
-                COALESCE(SUM(merged_balance), 0.0) AS total_merged_balance
-            FROM account_merges
-            GROUP BY destination_account
-            ORDER BY merge_count DESC, total_merged_balance DESC
-            LIMIT $1
-            "#,
-        )
-        .bind(limit)
-        .fetch_all(&self.pool)
-        .await?;
+        let rows = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, DestinationAccountPattern>(
+                r#"
+                SELECT
+                    destination_account,
+                    COUNT(*) AS merge_count,
+                    COALESCE(SUM(merged_balance), 0.0) AS total_merged_balance
+                FROM account_merges
+                GROUP BY destination_account
+                ORDER BY merge_count DESC, total_merged_balance DESC
+                LIMIT $1
+                "#,
+            )
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+        })?;
 
         Ok(rows)
     }
@@ -1,13 +1,17 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, Duration, Timelike, Utc};
+use std::collections::BTreeSet;
 use std::sync::Arc;
 use tokio::time::{interval, Duration as TokioDuration};
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
 use crate::database::Database;
-use crate::models::corridor::CorridorMetrics;
+use crate::models::corridor::{CorridorMetrics, PaymentRecord};
 use crate::services::analytics::compute_metrics_from_payments;
+use crate::services::rollup::{
+    CorridorSketches, Resolution, RollupConfig, RollupRunStats, RollupService,
+};
 
 const MAX_RETRIES: i32 = 3;
 const RETRY_DELAY_SECS: u64 = 60;
@@ -32,11 +36,23 @@ impl Default for AggregationConfig {
 pub struct AggregationService {
     db: Arc<Database>,
     config: AggregationConfig,
+    rollups: Arc<RollupService>,
 }
 
 impl AggregationService {
     pub fn new(db: Arc<Database>, config: AggregationConfig) -> Self {
-        Self { db, config }
+        let rollups = Arc::new(RollupService::new(Arc::clone(&db), RollupConfig::default()));
+        Self {
+            db,
+            config,
+            rollups,
+        }
+    }
+
+    /// Use `config` for the daily/weekly/monthly rollups instead of the defaults
+    pub fn with_rollup_config(mut self, config: RollupConfig) -> Self {
+        self.rollups = Arc::new(RollupService::new(Arc::clone(&self.db), config));
+        self
     }
 
     /// Start the hourly aggregation job scheduler
@@ -63,6 +79,10 @@ impl AggregationService {
                 error!("Hourly aggregation failed: {}", e);
                 // Continue running despite errors
             }
+
+            if let Err(e) = self.run_rollups().await {
+                error!("Corridor rollups failed: {}", e);
+            }
         }
     }
 
@@ -102,6 +122,20 @@ impl AggregationService {
         }
     }
 
+    /// Roll finalized hourly metrics up into daily, weekly and monthly buckets.
+    ///
+    /// Hours still inside the aggregation lookback window can receive more payments on
+    /// the next run, so only hours before it are treated as final.
+    pub async fn run_rollups(&self) -> Result<RollupRunStats> {
+        let now = Utc::now();
+        self.rollups.run(now, self.hourly_complete_until(now)).await
+    }
+
+    /// Start of the first hour that a later aggregation run may still add payments to
+    pub fn hourly_complete_until(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        self.truncate_to_hour(now - Duration::hours(self.config.lookback_hours))
+    }
+
     /// Execute the actual aggregation logic
     async fn execute_aggregation(&self, job_id: &str, now: DateTime<Utc>) -> Result<usize> {
         // Calculate time window for aggregation
@@ -141,6 +175,8 @@ impl AggregationService {
 
         // Store aggregated metrics
         let stored_count = self.store_hourly_metrics(hourly_metrics).await?;
+        self.store_hourly_sketches(&payments, start_time, end_time)
+            .await?;
 
         // Update last processed hour
         let last_hour = self.truncate_to_hour(end_time);
@@ -233,6 +269,60 @@ impl AggregationService {
         Ok(count)
     }
 
+    /// Replace the stored latency and payment size sketches of every hour the run
+    /// covered.
+    ///
+    /// Consecutive runs overlap by their lookback window, so merging into the stored
+    /// sketches would count a payment once per run that saw it. Hours fully inside
+    /// `[start_time, end_time]` are rebuilt from the run's payments. The hours at the
+    /// edges of the window, including the hour the batch limit cut the run short in,
+    /// are rebuilt from all of their payments instead.
+    async fn store_hourly_sketches(
+        &self,
+        payments: &[PaymentRecord],
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<usize> {
+        let covered_until = match payments.last() {
+            Some(last) if payments.len() as i64 >= self.config.batch_size => last.timestamp,
+            _ => end_time,
+        };
+
+        let mut sketches: Vec<CorridorSketches> = build_hourly_sketches(payments)
+            .into_iter()
+            .filter(|sketch| {
+                sketch.bucket_start >= start_time
+                    && Resolution::Hour.next_bucket(sketch.bucket_start) <= covered_until
+            })
+            .collect();
+
+        let edge_hours: BTreeSet<DateTime<Utc>> = [
+            Resolution::Hour.bucket_start(start_time),
+            Resolution::Hour.bucket_start(covered_until),
+        ]
+        .into_iter()
+        .filter(|&hour| hour < start_time || Resolution::Hour.next_bucket(hour) > covered_until)
+        .collect();
+        for hour in edge_hours {
+            let hour_payments = self
+                .db
+                .fetch_payments_in_bucket(hour, Resolution::Hour.next_bucket(hour))
+                .await
+                .context("Failed to fetch payments for hourly sketches")?;
+            sketches.extend(build_hourly_sketches(&hour_payments));
+        }
+
+        let rollup_db = self.db.rollup_db();
+        for sketch in &sketches {
+            rollup_db
+                .upsert_sketches(sketch)
+                .await
+                .context("Failed to store hourly corridor sketches")?;
+        }
+
+        Ok(sketches.len())
+    }
+
     /// Truncate datetime to hour boundary
     fn truncate_to_hour(&self, dt: DateTime<Utc>) -> DateTime<Utc> {
         dt.with_minute(0)
@@ -391,6 +481,7 @@ impl Clone for AggregationService {
         Self {
             db: Arc::clone(&self.db),
             config: self.config.clone(),
+            rollups: Arc::clone(&self.rollups),
         }
     }
 }
@@ -414,6 +505,34 @@ pub struct HourlyCorridorMetrics {
     pub liquidity_depth_usd: f64,
 }
 
+/// Builds latency and payment size sketches per corridor and payment hour.
+///
+/// Only successful payments are counted, matching how volume and latency are derived in
+/// `compute_metrics_from_payments`.
+pub fn build_hourly_sketches(payments: &[PaymentRecord]) -> Vec<CorridorSketches> {
+    use std::collections::HashMap;
+
+    let mut sketches: HashMap<(String, DateTime<Utc>), CorridorSketches> = HashMap::new();
+
+    for payment in payments.iter().filter(|p| p.successful) {
+        let corridor_key = payment.get_corridor().to_string_key();
+        let hour = Resolution::Hour.bucket_start(payment.timestamp);
+        let entry = sketches
+            .entry((corridor_key.clone(), hour))
+            .or_insert_with(|| CorridorSketches::new(Resolution::Hour, corridor_key, hour));
+
+        entry.amount.insert(payment.amount);
+        if let Some(latency_ms) = payment.settlement_latency_ms() {
+            entry.latency_ms.insert(latency_ms as f64);
+        }
+    }
+
+    let mut sketches: Vec<CorridorSketches> = sketches.into_values().collect();
+    sketches
+        .sort_by(|a, b| (a.bucket_start, &a.corridor_key).cmp(&(b.bucket_start, &b.corridor_key)));
+    sketches
+}
+
 #[derive(Debug, Clone)]
 pub struct VolumeTrend {
     pub corridor_key: String,
@@ -1,4 +1,5 @@
 use crate::models::corridor::{compute_median, CorridorMetrics, PaymentRecord};
+use crate::services::dex_aggregator::MarketDepth;
 use std::collections::HashMap;
 
 #[derive(Debug, Clone)]
@@ -8,59 +9,20 @@ pub struct CorridorTransaction {
     pub amount_usd: f64,
 }
 
-/// Order book structures for computing liquidity depth
-#[derive(Debug, Clone)]
-pub struct OrderBookEntry {
-    pub price: f64,
-    pub amount_usd: f64,
-}
-
-#[derive(Debug, Clone)]
-pub struct OrderBookSnapshot {
-    pub bids: Vec<OrderBookEntry>, // Descending by price
-    pub asks: Vec<OrderBookEntry>, // Ascending by price
-}
-
-/// Compute total liquidity in USD within a max slippage percent
-pub fn compute_liquidity_depth(order_book: &OrderBookSnapshot, max_slippage_percent: f64) -> f64 {
-    if order_book.bids.is_empty() && order_book.asks.is_empty() {
-        return 0.0;
-    }
-
-    let best_bid = order_book.bids.first().map(|b| b.price).unwrap_or(0.0);
-    let best_ask = order_book.asks.first().map(|a| a.price).unwrap_or(0.0);
-    if best_bid == 0.0 || best_ask == 0.0 {
-        return 0.0;
-    }
-
-    let mid_price = (best_bid + best_ask) / 2.0;
-    let max_buy_price = mid_price * (1.0 + max_slippage_percent / 100.0);
-    let min_sell_price = mid_price * (1.0 - max_slippage_percent / 100.0);
-
-    // Buy-side liquidity (asks within max slippage)
-    let buy_liquidity: f64 = order_book
-        .asks
-        .iter()
-        .take_while(|a| a.price <= max_buy_price)
-        .map(|a| a.amount_usd)
-        .sum();
-
-    // Sell-side liquidity (bids within max slippage)
-    let sell_liquidity: f64 = order_book
-        .bids
-        .iter()
-        .take_while(|b| b.price >= min_sell_price)
-        .map(|b| b.amount_usd)
-        .sum();
-
-    buy_liquidity + sell_liquidity
+/// USD liquidity of a pair's order book and pools within a max slippage percent, both
+/// sides together. Zero when the base asset has no USD price.
+pub fn compute_liquidity_depth(market: &MarketDepth, max_slippage_percent: f64) -> f64 {
+    market
+        .depth_at(max_slippage_percent)
+        .and_then(|point| point.depth_usd)
+        .unwrap_or(0.0)
 }
 
 /// Computes corridor metrics from transactions, calculating average and median settlement latency with optional liquidity depth.
 pub fn compute_corridor_metrics(
     txns: &[CorridorTransaction],
-    order_book: Option<&OrderBookSnapshot>, // Optional snapshot for liquidity depth
-    slippage_percent: f64,                  // e.g., 1.0 = 1% slippage
+    market: Option<&MarketDepth>, // Optional order book and pools for liquidity depth
+    slippage_percent: f64,        // e.g., 1.0 = 1% slippage
 ) -> CorridorMetrics {
     if txns.is_empty() {
         return CorridorMetrics {
@@ -114,9 +76,9 @@ pub fn compute_corridor_metrics(
     };
     let median_settlement_latency_ms = compute_median(&mut latency_values).map(|v| v as i32);
 
-    // Compute liquidity depth using order book snapshot if provided
-    let liquidity_depth_usd = order_book
-        .map(|ob| compute_liquidity_depth(ob, slippage_percent))
+    // Compute liquidity depth from the order book and pools if provided
+    let liquidity_depth_usd = market
+        .map(|m| compute_liquidity_depth(m, slippage_percent))
         .unwrap_or(0.0);
 
     CorridorMetrics {
@@ -239,6 +201,7 @@ pub fn compute_metrics_by_window(
 mod tests {
     use super::*;
     use crate::models::corridor::PaymentRecord;
+    use crate::services::dex_aggregator::DepthLevel;
     use chrono::Utc;
     use uuid::Uuid;
 
@@ -350,37 +313,41 @@ mod tests {
             },
         ];
 
-        let order_book = OrderBookSnapshot {
+        let market = MarketDepth {
             bids: vec![
-                OrderBookEntry {
+                DepthLevel {
                     price: 99.0,
-                    amount_usd: 150.0,
+                    amount: 1.5,
                 },
-                OrderBookEntry {
+                DepthLevel {
                     price: 98.5,
-                    amount_usd: 100.0,
+                    amount: 1.0,
                 },
             ],
             asks: vec![
-                OrderBookEntry {
+                DepthLevel {
                     price: 101.0,
-                    amount_usd: 200.0,
+                    amount: 2.0,
                 },
-                OrderBookEntry {
+                DepthLevel {
                     price: 102.0,
-                    amount_usd: 50.0,
+                    amount: 0.5,
                 },
             ],
+            base_usd_price: Some(100.0),
+            ..MarketDepth::default()
         };
 
-        let metrics = compute_corridor_metrics(&txns, Some(&order_book), 1.0); // 1% slippage
+        let metrics = compute_corridor_metrics(&txns, Some(&market), 1.0); // 1% slippage
         assert_eq!(metrics.total_transactions, 3);
         assert_eq!(metrics.successful_transactions, 2);
         assert_eq!(metrics.failed_transactions, 1);
         assert_eq!(metrics.success_rate, (2.0 / 3.0) * 100.0);
         assert_eq!(metrics.avg_settlement_latency_ms, Some(2000));
         assert_eq!(metrics.median_settlement_latency_ms, Some(2000)); // Median of [1000, 3000]
-        assert!(metrics.liquidity_depth_usd > 0.0); // computed from order book
+
+        // Best bid and ask are within 1% of the 100.0 mid: 3.5 units at $100
+        assert!((metrics.liquidity_depth_usd - 350.0).abs() < 1e-9);
     }
 
     #[test]
@@ -0,0 +1,725 @@
+//! Corridor anomaly detection.
+//!
+//! Learns a seasonal baseline (see [`crate::analytics::anomaly`]) for the success rate,
+//! volume, latency and liquidity of every corridor from its finalized hourly aggregates,
+//! and flags hours that deviate from it by more than a z-score threshold in the direction
+//! that matters for that metric. Fresh anomalies are recorded, broadcast to WebSocket
+//! clients as `HealthAlert` messages, queued for `corridor.anomaly_detected` webhooks and
+//! written to the alert history of users with a matching `anomaly` alert rule.
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::analytics::anomaly::{
+    AnomalyDirection, AnomalyMetric, BaselineSmoothing, SeasonalBaseline,
+};
+use crate::database::Database;
+use crate::models::alerts::AlertRule;
+use crate::services::rollup::{CorridorRollup, Resolution};
+use crate::webhooks::events::CorridorAnomalyDetectedEvent;
+use crate::webhooks::{WebhookEventType, WebhookService};
+use crate::websocket::{WsMessage, WsState};
+
+/// Alert rule `condition` that subscribes a user to detected anomalies. The rule's
+/// `threshold` is the minimum absolute z-score to be notified of.
+pub const ANOMALY_CONDITION: &str = "anomaly";
+
+#[derive(Debug, Clone)]
+pub struct AnomalyConfig {
+    /// Absolute z-score at which an adverse deviation is flagged
+    pub z_threshold: f64,
+    /// Absolute z-score at which an anomaly is reported as critical
+    pub critical_z: f64,
+    /// Hours a baseline must learn from before it is used for scoring
+    pub min_observations: u64,
+    /// Transactions an hour needs before its success rate and latency are trusted
+    pub min_transactions: i64,
+    /// How far back a new corridor's baseline is trained from
+    pub training_window: Duration,
+    /// Anomalies in older hours are recorded but not notified, e.g. while backfilling
+    pub max_alert_age: Duration,
+    pub smoothing: BaselineSmoothing,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            z_threshold: 3.0,
+            critical_z: 5.0,
+            min_observations: 24 * 7,
+            min_transactions: 20,
+            training_window: Duration::days(28),
+            max_alert_age: Duration::hours(6),
+            smoothing: BaselineSmoothing::default(),
+        }
+    }
+}
+
+impl AnomalyConfig {
+    /// Reads `ANOMALY_Z_THRESHOLD`, `ANOMALY_CRITICAL_Z`, `ANOMALY_MIN_OBSERVATIONS`,
+    /// `ANOMALY_MIN_TRANSACTIONS`, `ANOMALY_TRAINING_DAYS` and
+    /// `ANOMALY_MAX_ALERT_AGE_HOURS`, falling back to the defaults.
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(key: &str) -> Option<T> {
+            std::env::var(key).ok().and_then(|v| v.parse().ok())
+        }
+
+        let mut config = Self::default();
+        if let Some(z) = var("ANOMALY_Z_THRESHOLD") {
+            config.z_threshold = z;
+        }
+        if let Some(z) = var("ANOMALY_CRITICAL_Z") {
+            config.critical_z = z;
+        }
+        if let Some(n) = var("ANOMALY_MIN_OBSERVATIONS") {
+            config.min_observations = n;
+        }
+        if let Some(n) = var("ANOMALY_MIN_TRANSACTIONS") {
+            config.min_transactions = n;
+        }
+        if let Some(days) = var("ANOMALY_TRAINING_DAYS") {
+            config.training_window = Duration::days(days);
+        }
+        if let Some(hours) = var("ANOMALY_MAX_ALERT_AGE_HOURS") {
+            config.max_alert_age = Duration::hours(hours);
+        }
+        config
+    }
+}
+
+/// A metric of one corridor hour that deviated from its baseline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorridorAnomaly {
+    pub id: String,
+    pub corridor_key: String,
+    pub metric: AnomalyMetric,
+    pub bucket_start: DateTime<Utc>,
+    pub observed: f64,
+    pub expected: f64,
+    pub z_score: f64,
+    pub direction: AnomalyDirection,
+    pub severity: String, // "warning" | "critical"
+    pub detected_at: DateTime<Utc>,
+}
+
+impl CorridorAnomaly {
+    pub fn message(&self) -> String {
+        let (observed, expected) = match self.metric {
+            AnomalyMetric::SuccessRate => (
+                format!("{:.1}%", self.observed),
+                format!("{:.1}%", self.expected),
+            ),
+            AnomalyMetric::Latency => (
+                format!("{:.0}ms", self.observed),
+                format!("{:.0}ms", self.expected),
+            ),
+            AnomalyMetric::Volume | AnomalyMetric::Liquidity => (
+                format!("${:.0}", self.observed),
+                format!("${:.0}", self.expected),
+            ),
+        };
+        format!(
+            "Anomalous {} {} on {} at {}: {} vs expected {} (z = {:.1})",
+            self.metric.as_str().replace('_', " "),
+            self.direction.as_str(),
+            self.corridor_key,
+            self.bucket_start.format("%Y-%m-%d %H:00 UTC"),
+            observed,
+            expected,
+            self.z_score
+        )
+    }
+}
+
+/// Baseline of one corridor metric as persisted between runs
+#[derive(Debug, Clone)]
+pub struct StoredBaseline {
+    pub corridor_key: String,
+    pub baseline: SeasonalBaseline,
+    /// Start of the last hour the baseline learned from
+    pub last_bucket: DateTime<Utc>,
+}
+
+/// Baselines of every metric of one corridor
+#[derive(Debug, Clone)]
+pub struct CorridorBaselines {
+    pub baselines: HashMap<AnomalyMetric, SeasonalBaseline>,
+    pub last_bucket: Option<DateTime<Utc>>,
+}
+
+impl Default for CorridorBaselines {
+    fn default() -> Self {
+        Self {
+            baselines: AnomalyMetric::ALL
+                .into_iter()
+                .map(|metric| (metric, SeasonalBaseline::new(metric)))
+                .collect(),
+            last_bucket: None,
+        }
+    }
+}
+
+/// Value of `metric` in an hourly bucket, or `None` if the hour carries too few
+/// transactions for it to be meaningful
+pub fn metric_value(
+    bucket: &CorridorRollup,
+    metric: AnomalyMetric,
+    min_transactions: i64,
+) -> Option<f64> {
+    match metric {
+        AnomalyMetric::SuccessRate => {
+            (bucket.total_transactions >= min_transactions).then_some(bucket.success_rate)
+        }
+        AnomalyMetric::Latency => bucket
+            .avg_settlement_latency_ms
+            .filter(|_| bucket.successful_transactions >= min_transactions)
+            .map(f64::from),
+        AnomalyMetric::Volume => Some(bucket.volume_usd),
+        AnomalyMetric::Liquidity => {
+            (bucket.source_buckets > 0).then_some(bucket.liquidity_depth_usd)
+        }
+    }
+}
+
+/// Stand-in for an hour in which a corridor recorded no payments. It carries zero volume;
+/// with no transactions and no source bucket, the other metrics are skipped for the hour.
+fn quiet_hour(corridor_key: &str, bucket_start: DateTime<Utc>) -> CorridorRollup {
+    CorridorRollup {
+        resolution: Resolution::Hour,
+        corridor_key: corridor_key.to_string(),
+        asset_a_code: String::new(),
+        asset_a_issuer: String::new(),
+        asset_b_code: String::new(),
+        asset_b_issuer: String::new(),
+        bucket_start,
+        total_transactions: 0,
+        successful_transactions: 0,
+        failed_transactions: 0,
+        success_rate: 0.0,
+        volume_usd: 0.0,
+        avg_slippage_bps: 0.0,
+        avg_settlement_latency_ms: None,
+        liquidity_depth_usd: 0.0,
+        source_buckets: 0,
+    }
+}
+
+/// Every hour of a corridor from `from` up to `until`, with hours that have no stored
+/// bucket filled by [`quiet_hour`], so a corridor that stops transacting reads as a drop
+/// in volume rather than as missing data.
+fn dense_hourly_series(
+    corridor_key: &str,
+    buckets: Vec<CorridorRollup>,
+    from: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Vec<CorridorRollup> {
+    let mut by_hour: HashMap<DateTime<Utc>, CorridorRollup> = buckets
+        .into_iter()
+        .map(|bucket| (bucket.bucket_start, bucket))
+        .collect();
+
+    let mut series = Vec::new();
+    let mut hour = from;
+    while hour < until {
+        series.push(
+            by_hour
+                .remove(&hour)
+                .unwrap_or_else(|| quiet_hour(corridor_key, hour)),
+        );
+        hour += Duration::hours(1);
+    }
+    series
+}
+
+/// Scores one hourly bucket against the corridor's baselines, then folds it into them.
+///
+/// Buckets at or before the baselines' last bucket have already been learned from and are
+/// skipped.
+pub fn detect(
+    state: &mut CorridorBaselines,
+    bucket: &CorridorRollup,
+    config: &AnomalyConfig,
+    now: DateTime<Utc>,
+) -> Vec<CorridorAnomaly> {
+    if state
+        .last_bucket
+        .is_some_and(|last| bucket.bucket_start <= last)
+    {
+        return Vec::new();
+    }
+
+    let mut anomalies = Vec::new();
+    for metric in AnomalyMetric::ALL {
+        let Some(value) = metric_value(bucket, metric, config.min_transactions) else {
+            continue;
+        };
+        let baseline = state
+            .baselines
+            .entry(metric)
+            .or_insert_with(|| SeasonalBaseline::new(metric));
+
+        if let Some(deviation) = baseline.score(bucket.bucket_start, value, config.min_observations)
+        {
+            if deviation.z_score.abs() >= config.z_threshold
+                && metric.is_adverse(deviation.direction())
+            {
+                let severity = if deviation.z_score.abs() >= config.critical_z {
+                    "critical"
+                } else {
+                    "warning"
+                };
+                anomalies.push(CorridorAnomaly {
+                    id: Uuid::new_v4().to_string(),
+                    corridor_key: bucket.corridor_key.clone(),
+                    metric,
+                    bucket_start: bucket.bucket_start,
+                    observed: deviation.observed,
+                    expected: deviation.expected,
+                    z_score: deviation.z_score,
+                    direction: deviation.direction(),
+                    severity: severity.to_string(),
+                    detected_at: now,
+                });
+            }
+        }
+
+        baseline.update(bucket.bucket_start, value, &config.smoothing);
+    }
+
+    state.last_bucket = Some(bucket.bucket_start);
+    anomalies
+}
+
+/// Whether `rule` subscribes its owner to `anomaly`
+fn rule_matches(rule: &AlertRule, anomaly: &CorridorAnomaly, now: DateTime<Utc>) -> bool {
+    rule.condition == ANOMALY_CONDITION
+        && rule.metric_type == anomaly.metric.as_str()
+        && rule
+            .corridor_id
+            .as_ref()
+            .is_none_or(|corridor| corridor == &anomaly.corridor_key)
+        && rule.snoozed_until.is_none_or(|until| until <= now)
+        && anomaly.z_score.abs() >= rule.threshold
+}
+
+/// Outcome of one detection run
+#[derive(Debug, Clone, Default)]
+pub struct AnomalyRunStats {
+    pub buckets_scored: usize,
+    pub anomalies_detected: usize,
+    pub anomalies_notified: usize,
+}
+
+pub struct AnomalyDetectionService {
+    db: Arc<Database>,
+    config: AnomalyConfig,
+    webhooks: WebhookService,
+    ws_state: Option<Arc<WsState>>,
+}
+
+impl AnomalyDetectionService {
+    pub fn new(db: Arc<Database>, config: AnomalyConfig) -> Self {
+        let webhooks = WebhookService::new(db.pool().clone());
+        Self {
+            db,
+            config,
+            webhooks,
+            ws_state: None,
+        }
+    }
+
+    /// Broadcast detected anomalies to WebSocket clients as `HealthAlert` messages
+    pub fn with_websocket(mut self, ws_state: Arc<WsState>) -> Self {
+        self.ws_state = Some(ws_state);
+        self
+    }
+
+    /// Score and learn from hourly buckets up to `hourly_complete_until`.
+    ///
+    /// Only finalized hours are used, so a bucket still receiving payments is never
+    /// mistaken for a drop in volume. Detection resumes after the last hour learned from;
+    /// on the first run baselines are trained on up to `training_window` of history. Hours
+    /// in which a known corridor recorded nothing are scored as zero volume.
+    pub async fn run(
+        &self,
+        now: DateTime<Utc>,
+        hourly_complete_until: DateTime<Utc>,
+    ) -> Result<AnomalyRunStats> {
+        let store = self.db.anomaly_db();
+        let mut corridors: HashMap<String, CorridorBaselines> = HashMap::new();
+        for stored in store.load_baselines().await? {
+            let state = corridors.entry(stored.corridor_key).or_default();
+            state
+                .baselines
+                .insert(stored.baseline.metric(), stored.baseline);
+            state.last_bucket = state.last_bucket.max(Some(stored.last_bucket));
+        }
+
+        // Every run learns from all corridors up to `hourly_complete_until`, so the latest
+        // hour any baseline has seen marks where the previous run stopped.
+        let training_start = hourly_complete_until - self.config.training_window;
+        let start = corridors
+            .values()
+            .filter_map(|state| state.last_bucket)
+            .max()
+            .map(|last| (last + Duration::hours(1)).max(training_start))
+            .unwrap_or(training_start);
+        let mut stats = AnomalyRunStats::default();
+        if start >= hourly_complete_until {
+            return Ok(stats);
+        }
+
+        let buckets = self
+            .db
+            .rollup_db()
+            .fetch_buckets(Resolution::Hour, None, start, hourly_complete_until)
+            .await?;
+
+        let mut by_corridor: HashMap<String, Vec<CorridorRollup>> = HashMap::new();
+        for bucket in buckets {
+            by_corridor
+                .entry(bucket.corridor_key.clone())
+                .or_default()
+                .push(bucket);
+        }
+        let mut corridor_keys: Vec<String> = corridors
+            .keys()
+            .chain(by_corridor.keys())
+            .cloned()
+            .collect();
+        corridor_keys.sort();
+        corridor_keys.dedup();
+
+        // Known corridors continue hour by hour from where their baselines stopped, quiet
+        // hours included; new ones start at their first bucket.
+        let mut touched = Vec::new();
+        let mut anomalies = Vec::new();
+        for corridor_key in corridor_keys {
+            let buckets = by_corridor.remove(&corridor_key).unwrap_or_default();
+            let state = corridors.entry(corridor_key.clone()).or_default();
+            let from = match state.last_bucket {
+                Some(last) => (last + Duration::hours(1)).max(start),
+                None => match buckets.iter().map(|bucket| bucket.bucket_start).min() {
+                    Some(first) => first,
+                    None => continue,
+                },
+            };
+
+            let series = dense_hourly_series(&corridor_key, buckets, from, hourly_complete_until);
+            if series.is_empty() {
+                continue;
+            }
+            for bucket in &series {
+                anomalies.extend(detect(state, bucket, &self.config, now));
+            }
+            stats.buckets_scored += series.len();
+            touched.push(corridor_key);
+        }
+
+        // Anomalies are recorded before the baselines move past them, so a run that fails
+        // half way is repeated rather than skipped; the unique key keeps it from notifying
+        // twice.
+        let alert_cutoff = now - self.config.max_alert_age;
+        let mut rules: Option<Vec<AlertRule>> = None;
+        for anomaly in &anomalies {
+            if !store.insert_anomaly(anomaly).await? {
+                continue;
+            }
+            stats.anomalies_detected += 1;
+            if anomaly.bucket_start < alert_cutoff {
+                debug!(
+                    "Recorded backfilled anomaly without notifying: {}",
+                    anomaly.message()
+                );
+                continue;
+            }
+
+            if rules.is_none() {
+                rules = Some(self.db.get_all_active_alert_rules().await?);
+            }
+            self.notify(anomaly, rules.as_deref().unwrap_or_default(), now)
+                .await;
+            stats.anomalies_notified += 1;
+        }
+
+        for corridor_key in &touched {
+            let Some(state) = corridors.get(corridor_key) else {
+                continue;
+            };
+            let Some(last_bucket) = state.last_bucket else {
+                continue;
+            };
+            for baseline in state.baselines.values() {
+                store
+                    .upsert_baseline(corridor_key, baseline, last_bucket)
+                    .await?;
+            }
+        }
+
+        info!(
+            "Anomaly detection scored {} corridor hours, {} new anomalies ({} notified)",
+            stats.buckets_scored, stats.anomalies_detected, stats.anomalies_notified
+        );
+        Ok(stats)
+    }
+
+    /// Fan an anomaly out to WebSocket clients, webhooks and matching alert rules. Failures
+    /// are logged rather than returned so one channel cannot starve the others.
+    async fn notify(&self, anomaly: &CorridorAnomaly, rules: &[AlertRule], now: DateTime<Utc>) {
+        let message = anomaly.message();
+        warn!("{}", message);
+
+        if let Some(ws_state) = &self.ws_state {
+            ws_state.broadcast(WsMessage::HealthAlert {
+                corridor_id: anomaly.corridor_key.clone(),
+                severity: anomaly.severity.clone(),
+                message: message.clone(),
+                timestamp: now.to_rfc3339(),
+            });
+        }
+
+        let event = CorridorAnomalyDetectedEvent {
+            corridor_key: anomaly.corridor_key.clone(),
+            metric: anomaly.metric.as_str().to_string(),
+            bucket_start: anomaly.bucket_start.to_rfc3339(),
+            observed: anomaly.observed,
+            expected: anomaly.expected,
+            z_score: anomaly.z_score,
+            direction: anomaly.direction.as_str().to_string(),
+            severity: anomaly.severity.clone(),
+        };
+        if let Err(e) = self
+            .webhooks
+            .queue_event(
+                &WebhookEventType::CorridorAnomalyDetected,
+                serde_json::json!(event),
+            )
+            .await
+        {
+            warn!("Failed to queue anomaly webhooks: {}", e);
+        }
+
+        for rule in rules.iter().filter(|rule| rule_matches(rule, anomaly, now)) {
+            if let Err(e) = self
+                .db
+                .insert_alert_history(
+                    &rule.id,
+                    &rule.user_id,
+                    Some(anomaly.corridor_key.clone()),
+                    &rule.metric_type,
+                    anomaly.z_score.abs(),
+                    rule.threshold,
+                    &rule.condition,
+                    &message,
+                )
+                .await
+            {
+                warn!("Failed to record anomaly alert for rule {}: {}", rule.id, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::aggregation::HourlyCorridorMetrics;
+    use chrono::TimeZone;
+
+    fn bucket(at: DateTime<Utc>, success_rate: f64, volume_usd: f64) -> CorridorRollup {
+        CorridorRollup {
+            resolution: Resolution::Hour,
+            corridor_key: "USDC:GA5Z->XLM:native".to_string(),
+            asset_a_code: "USDC".to_string(),
+            asset_a_issuer: "GA5Z".to_string(),
+            asset_b_code: "XLM".to_string(),
+            asset_b_issuer: "native".to_string(),
+            bucket_start: at,
+            total_transactions: 100,
+            successful_transactions: success_rate as i64,
+            failed_transactions: 100 - success_rate as i64,
+            success_rate,
+            volume_usd,
+            avg_slippage_bps: 0.0,
+            avg_settlement_latency_ms: Some(400),
+            liquidity_depth_usd: 50_000.0,
+            source_buckets: 1,
+        }
+    }
+
+    fn rule(condition: &str, metric_type: &str, corridor_id: Option<&str>) -> AlertRule {
+        let now = Utc::now();
+        AlertRule {
+            id: "rule".to_string(),
+            user_id: "user".to_string(),
+            corridor_id: corridor_id.map(str::to_string),
+            metric_type: metric_type.to_string(),
+            condition: condition.to_string(),
+            threshold: 4.0,
+            notify_email: false,
+            notify_webhook: false,
+            notify_in_app: true,
+            is_active: true,
+            snoozed_until: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_detects_success_rate_drop_after_warmup() {
+        let config = AnomalyConfig::default();
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let now = start + Duration::days(30);
+        let mut state = CorridorBaselines::default();
+
+        for i in 0..24 * 14 {
+            let at = start + Duration::hours(i);
+            let rate = 97.0 + (i % 3) as f64;
+            assert!(detect(&mut state, &bucket(at, rate, 10_000.0), &config, now).is_empty());
+        }
+
+        let at = start + Duration::days(14);
+        let anomalies = detect(&mut state, &bucket(at, 60.0, 10_000.0), &config, now);
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].metric, AnomalyMetric::SuccessRate);
+        assert_eq!(anomalies[0].direction, AnomalyDirection::Drop);
+        assert_eq!(anomalies[0].severity, "critical");
+        assert!(anomalies[0].message().contains("success rate drop"));
+
+        // Already learned from, so scoring the same hour again is a no-op
+        assert!(detect(&mut state, &bucket(at, 60.0, 10_000.0), &config, now).is_empty());
+
+        // A better than usual success rate is not an anomaly worth reporting
+        let at = at + Duration::hours(1);
+        assert!(detect(&mut state, &bucket(at, 100.0, 10_000.0), &config, now).is_empty());
+    }
+
+    #[test]
+    fn test_thin_hours_skip_success_rate() {
+        let mut thin = bucket(Utc::now(), 50.0, 10.0);
+        thin.total_transactions = 3;
+        thin.successful_transactions = 3;
+        assert_eq!(metric_value(&thin, AnomalyMetric::SuccessRate, 20), None);
+        assert_eq!(metric_value(&thin, AnomalyMetric::Latency, 20), None);
+        assert_eq!(metric_value(&thin, AnomalyMetric::Volume, 20), Some(10.0));
+    }
+
+    #[tokio::test]
+    async fn test_silent_corridor_reads_as_volume_drop() {
+        let db = Arc::new(Database::new(
+            crate::test_support::migrated_pool().await.into(),
+        ));
+        let service = AnomalyDetectionService::new(Arc::clone(&db), AnomalyConfig::default());
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let until = start + Duration::days(14);
+
+        for i in 0..24 * 14 {
+            let hour = bucket(
+                start + Duration::hours(i),
+                97.0 + (i % 3) as f64,
+                10_000.0 + (i % 5) as f64 * 100.0,
+            );
+            db.upsert_hourly_corridor_metric(&HourlyCorridorMetrics {
+                id: Uuid::new_v4().to_string(),
+                corridor_key: hour.corridor_key,
+                asset_a_code: hour.asset_a_code,
+                asset_a_issuer: hour.asset_a_issuer,
+                asset_b_code: hour.asset_b_code,
+                asset_b_issuer: hour.asset_b_issuer,
+                hour_bucket: hour.bucket_start,
+                total_transactions: hour.total_transactions,
+                successful_transactions: hour.successful_transactions,
+                failed_transactions: hour.failed_transactions,
+                success_rate: hour.success_rate,
+                volume_usd: hour.volume_usd,
+                avg_slippage_bps: hour.avg_slippage_bps,
+                avg_settlement_latency_ms: hour.avg_settlement_latency_ms,
+                liquidity_depth_usd: hour.liquidity_depth_usd,
+            })
+            .await
+            .unwrap();
+        }
+
+        let stats = service.run(until, until).await.unwrap();
+        assert_eq!(stats.buckets_scored, 24 * 14);
+        assert_eq!(stats.anomalies_detected, 0);
+
+        // The corridor stops transacting: its next hours have no bucket at all
+        let until = until + Duration::hours(6);
+        let stats = service.run(until, until).await.unwrap();
+        assert_eq!(stats.buckets_scored, 6);
+        assert!(stats.anomalies_detected > 0);
+
+        let anomalies = db
+            .anomaly_db()
+            .list_anomalies("USDC:GA5Z->XLM:native", start, until, 10)
+            .await
+            .unwrap();
+        assert!(!anomalies.is_empty());
+        assert!(anomalies.iter().all(|anomaly| {
+            anomaly.metric == AnomalyMetric::Volume
+                && anomaly.direction == AnomalyDirection::Drop
+                && anomaly.observed == 0.0
+        }));
+    }
+
+    #[test]
+    fn test_rule_matching() {
+        let now = Utc::now();
+        let mut anomaly = CorridorAnomaly {
+            id: "a".to_string(),
+            corridor_key: "USDC:GA5Z->XLM:native".to_string(),
+            metric: AnomalyMetric::Latency,
+            bucket_start: now,
+            observed: 4000.0,
+            expected: 400.0,
+            z_score: 6.0,
+            direction: AnomalyDirection::Spike,
+            severity: "critical".to_string(),
+            detected_at: now,
+        };
+
+        assert!(rule_matches(
+            &rule("anomaly", "latency", None),
+            &anomaly,
+            now
+        ));
+        assert!(rule_matches(
+            &rule("anomaly", "latency", Some("USDC:GA5Z->XLM:native")),
+            &anomaly,
+            now
+        ));
+        assert!(!rule_matches(
+            &rule("anomaly", "latency", Some("other")),
+            &anomaly,
+            now
+        ));
+        assert!(!rule_matches(
+            &rule("above", "latency", None),
+            &anomaly,
+            now
+        ));
+        assert!(!rule_matches(
+            &rule("anomaly", "volume", None),
+            &anomaly,
+            now
+        ));
+
+        let mut snoozed = rule("anomaly", "latency", None);
+        snoozed.snoozed_until = Some(now + Duration::hours(1));
+        assert!(!rule_matches(&snoozed, &anomaly, now));
+
+        anomaly.z_score = 3.5;
+        assert!(!rule_matches(
+            &rule("anomaly", "latency", None),
+            &anomaly,
+            now
+        ));
+    }
+}
@@ -1,8 +1,9 @@
+use crate::database::DbPool;
+use crate::with_pool;
 use anyhow::{Context, Result};
 use chrono::Utc;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use sqlx::SqlitePool;
 use std::time::Duration;
 use tracing::{error, info, warn};
 use uuid::Uuid;
@@ -34,11 +35,11 @@ struct TomlInfo {
 
 pub struct AssetVerifier {
     http_client: Client,
-    pool: SqlitePool,
+    pool: DbPool,
 }
 
 impl AssetVerifier {
-    pub fn new(pool: SqlitePool) -> Result<Self> {
+    pub fn new(pool: DbPool) -> Result<Self> {
         let http_client = Client::builder()
             .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
             .user_agent("StellarInsights/1.0")
@@ -267,20 +268,22 @@ impl AssetVerifier {
         asset_code: &str,
         asset_issuer: &str,
     ) -> Result<Option<(i64, i64, f64)>> {
-        let result = sqlx::query_as::<_, (i64, i64, f64)>(
-            r#"
-            SELECT 
-                COALESCE(SUM(trustline_count), 0) as trustline_count,
-                COALESCE(COUNT(*), 0) as transaction_count,
-                COALESCE(SUM(total_volume_usd), 0.0) as total_volume
-            FROM payments
-            WHERE asset_code = $1 AND asset_issuer = $2
-            "#,
-        )
-        .bind(asset_code)
-        .bind(asset_issuer)
-        .fetch_optional(&self.pool)
-        .await?;
+        let result = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, (i64, i64, f64)>(
+                r#"
+                SELECT 
+                    COALESCE(SUM(trustline_count), 0) as trustline_count,
+                    COALESCE(COUNT(*), 0) as transaction_count,
+                    COALESCE(SUM(total_volume_usd), 0.0) as total_volume
+                FROM payments
+                WHERE asset_code = $1 AND asset_issuer = $2
+                "#,
+            )
+            .bind(asset_code)
+            .bind(asset_issuer)
+            .fetch_optional(pool)
+            .await
+        })?;
 
         Ok(result)
     }
@@ -426,58 +429,60 @@ impl AssetVerifier {
             .as_ref()
             .and_then(|d| d.logo_url.clone());
 
-        let verified_asset = sqlx::query_as::<_, VerifiedAsset>(
-            r#"
-            INSERT INTO verified_assets (
-                id, asset_code, asset_issuer, verification_status, reputation_score,
-                stellar_expert_verified, stellar_toml_verified, anchor_registry_verified,
-                trustline_count, transaction_count, total_volume_usd,
-                toml_home_domain, toml_name, toml_description, toml_org_name, toml_org_url, toml_logo_url,
-                last_verified_at, created_at, updated_at
+        let verified_asset = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, VerifiedAsset>(
+                r#"
+                INSERT INTO verified_assets (
+                    id, asset_code, asset_issuer, verification_status, reputation_score,
+                    stellar_expert_verified, stellar_toml_verified, anchor_registry_verified,
+                    trustline_count, transaction_count, total_volume_usd,
+                    toml_home_domain, toml_name, toml_description, toml_org_name, toml_org_url, toml_logo_url,
+                    last_verified_at, created_at, updated_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)
+                ON CONFLICT (asset_code, asset_issuer) DO UPDATE SET
+                    verification_status = EXCLUDED.verification_status,
+                    reputation_score = EXCLUDED.reputation_score,
+                    stellar_expert_verified = EXCLUDED.stellar_expert_verified,
+                    stellar_toml_verified = EXCLUDED.stellar_toml_verified,
+                    anchor_registry_verified = EXCLUDED.anchor_registry_verified,
+                    trustline_count = EXCLUDED.trustline_count,
+                    transaction_count = EXCLUDED.transaction_count,
+                    total_volume_usd = EXCLUDED.total_volume_usd,
+                    toml_home_domain = EXCLUDED.toml_home_domain,
+                    toml_name = EXCLUDED.toml_name,
+                    toml_description = EXCLUDED.toml_description,
+                    toml_org_name = EXCLUDED.toml_org_name,
+                    toml_org_url = EXCLUDED.toml_org_url,
+                    toml_logo_url = EXCLUDED.toml_logo_url,
+                    last_verified_at = EXCLUDED.last_verified_at,
+                    updated_at = EXCLUDED.updated_at
+                RETURNING *
+                "#,
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)
-            ON CONFLICT (asset_code, asset_issuer) DO UPDATE SET
-                verification_status = EXCLUDED.verification_status,
-                reputation_score = EXCLUDED.reputation_score,
-                stellar_expert_verified = EXCLUDED.stellar_expert_verified,
-                stellar_toml_verified = EXCLUDED.stellar_toml_verified,
-                anchor_registry_verified = EXCLUDED.anchor_registry_verified,
-                trustline_count = EXCLUDED.trustline_count,
-                transaction_count = EXCLUDED.transaction_count,
-                total_volume_usd = EXCLUDED.total_volume_usd,
-                toml_home_domain = EXCLUDED.toml_home_domain,
-                toml_name = EXCLUDED.toml_name,
-                toml_description = EXCLUDED.toml_description,
-                toml_org_name = EXCLUDED.toml_org_name,
-                toml_org_url = EXCLUDED.toml_org_url,
-                toml_logo_url = EXCLUDED.toml_logo_url,
-                last_verified_at = EXCLUDED.last_verified_at,
-                updated_at = EXCLUDED.updated_at
-            RETURNING *
-            "#,
-        )
-        .bind(&id)
-        .bind(asset_code)
-        .bind(asset_issuer)
-        .bind(status.as_str())
-        .bind(reputation_score)
-        .bind(result.stellar_expert_verified)
-        .bind(result.stellar_toml_verified)
-        .bind(result.anchor_registry_verified)
-        .bind(result.trustline_count)
-        .bind(result.transaction_count)
-        .bind(result.total_volume_usd)
-        .bind(toml_home_domain)
-        .bind(toml_name)
-        .bind(toml_description)
-        .bind(toml_org_name)
-        .bind(toml_org_url)
-        .bind(toml_logo_url)
-        .bind(now)
-        .bind(now)
-        .bind(now)
-        .fetch_one(&self.pool)
-        .await?;
+            .bind(&id)
+            .bind(asset_code)
+            .bind(asset_issuer)
+            .bind(status.as_str())
+            .bind(reputation_score)
+            .bind(result.stellar_expert_verified)
+            .bind(result.stellar_toml_verified)
+            .bind(result.anchor_registry_verified)
+            .bind(result.trustline_count)
+            .bind(result.transaction_count)
+            .bind(result.total_volume_usd)
+            .bind(toml_home_domain)
+            .bind(toml_name)
+            .bind(toml_description)
+            .bind(toml_org_name)
+            .bind(toml_org_url)
+            .bind(toml_logo_url)
+            .bind(now)
+            .bind(now)
+            .bind(now)
+            .fetch_one(pool)
+            .await
+        })?;
 
         // Record history
         self.record_verification_history(
@@ -512,26 +517,29 @@ impl AssetVerifier {
     ) -> Result<()> {
         let id = Uuid::new_v4().to_string();
 
-        sqlx::query(
-            r#"
-            INSERT INTO asset_verification_history (
-                id, asset_code, asset_issuer, previous_status, new_status,
-                previous_reputation_score, new_reputation_score, change_reason, created_at
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO asset_verification_history (
+                    id, asset_code, asset_issuer, previous_status, new_status,
+                    previous_reputation_score, new_reputation_score, change_reason, created_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                "#,
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-            "#,
-        )
-        .bind(&id)
-        .bind(asset_code)
-        .bind(asset_issuer)
-        .bind(previous_status)
-        .bind(new_status)
-        .bind(previous_score)
-        .bind(new_score)
-        .bind(reason)
-        .bind(Utc::now())
-        .execute(&self.pool)
-        .await?;
+            .bind(&id)
+            .bind(asset_code)
+            .bind(asset_issuer)
+            .bind(previous_status)
+            .bind(new_status)
+            .bind(previous_score)
+            .bind(new_score)
+            .bind(reason)
+            .bind(Utc::now())
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
 
         Ok(())
     }
@@ -542,16 +550,18 @@ impl AssetVerifier {
         asset_code: &str,
         asset_issuer: &str,
     ) -> Result<Option<VerifiedAsset>> {
-        let asset = sqlx::query_as::<_, VerifiedAsset>(
-            r#"
-            SELECT * FROM verified_assets
-            WHERE asset_code = $1 AND asset_issuer = $2
-            "#,
-        )
-        .bind(asset_code)
-        .bind(asset_issuer)
-        .fetch_optional(&self.pool)
-        .await?;
+        let asset = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, VerifiedAsset>(
+                r#"
+                SELECT * FROM verified_assets
+                WHERE asset_code = $1 AND asset_issuer = $2
+                "#,
+            )
+            .bind(asset_code)
+            .bind(asset_issuer)
+            .fetch_optional(pool)
+            .await
+        })?;
 
         Ok(asset)
     }
@@ -577,9 +587,11 @@ impl AssetVerifier {
         query.push_str(" ORDER BY reputation_score DESC, updated_at DESC");
         query.push_str(&format!(" LIMIT {} OFFSET {}", limit, offset));
 
-        let assets = sqlx::query_as::<_, VerifiedAsset>(&query)
-            .fetch_all(&self.pool)
-            .await?;
+        let assets = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, VerifiedAsset>(&query)
+                .fetch_all(pool)
+                .await
+        })?;
 
         Ok(assets)
     }
@@ -591,8 +603,8 @@ mod tests {
 
     #[test]
     fn test_calculate_reputation_score() {
-        let pool = SqlitePool::connect(":memory:").await.unwrap();
-        let verifier = AssetVerifier::new(pool).unwrap();
+        let pool = sqlx::SqlitePool::connect(":memory:").await.unwrap();
+        let verifier = AssetVerifier::new(pool.into()).unwrap();
 
         let result = VerificationResult {
             stellar_expert_verified: true,
@@ -611,8 +623,8 @@ mod tests {
 
     #[test]
     fn test_determine_status() {
-        let pool = SqlitePool::connect(":memory:").await.unwrap();
-        let verifier = AssetVerifier::new(pool).unwrap();
+        let pool = sqlx::SqlitePool::connect(":memory:").await.unwrap();
+        let verifier = AssetVerifier::new(pool.into()).unwrap();
 
         assert_eq!(
             verifier.determine_status(80.0, 0),
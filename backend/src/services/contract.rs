@@ -2,22 +2,55 @@
 //!
 //! This service handles:
 //! - Connecting to Soroban RPC endpoints
+//! - Building, simulating and signing `InvokeHostFunction` transactions
 //! - Submitting snapshot hashes on-chain
+//! - Source account sequence number tracking
+//! - Fee bumps when the network asks to try again later
 //! - Retry logic with exponential backoff
 //! - Comprehensive error handling and logging
 
 use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use ed25519_dalek::{Signer, SigningKey};
 use reqwest::Client;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+use std::sync::Arc;
 use std::time::Duration;
+use stellar_xdr::curr::{
+    AccountId, DecoratedSignature, FeeBumpTransaction, FeeBumpTransactionEnvelope,
+    FeeBumpTransactionExt, FeeBumpTransactionInnerTx, Hash, HostFunction, InvokeContractArgs,
+    InvokeHostFunctionOp, LedgerEntryData, LedgerKey, LedgerKeyAccount, Limits, Memo, MuxedAccount,
+    Operation, OperationBody, Preconditions, PublicKey, ReadXdr, ScAddress, ScBytes, ScString,
+    ScSymbol, ScVal, SequenceNumber, Signature, SignatureHint, SorobanAuthorizationEntry,
+    SorobanTransactionData, TimeBounds, TimePoint, Transaction, TransactionEnvelope,
+    TransactionExt, TransactionResult, TransactionResultResult, TransactionSignaturePayload,
+    TransactionSignaturePayloadTaggedTransaction, TransactionV1Envelope, Uint256, WriteXdr,
+};
+use tokio::sync::Mutex;
 use tracing::{debug, error, info, warn};
 
+use crate::vault::VaultClient;
+
 const MAX_RETRIES: u32 = 3;
 const INITIAL_BACKOFF_MS: u64 = 1000;
 const BACKOFF_MULTIPLIER: u64 = 2;
 const REQUEST_TIMEOUT_SECS: u64 = 30;
 
+/// Inclusion fee offered per operation, in stroops
+const BASE_FEE: u32 = 100;
+/// Transactions are only valid for this long after they are built
+const TRANSACTION_TIMEOUT_SECS: u64 = 300;
+/// Fee bumps attempted when the network answers `TRY_AGAIN_LATER`
+const MAX_FEE_BUMPS: u32 = 3;
+/// Each fee bump multiplies the inclusion fee by this factor
+const FEE_BUMP_MULTIPLIER: i64 = 4;
+/// Never offer more than this inclusion fee per operation, in stroops
+const MAX_INCLUSION_FEE: i64 = 1_000_000;
+
 /// Configuration for the contract service
 #[derive(Clone, Debug)]
 pub struct ContractConfig {
@@ -36,6 +69,10 @@ pub struct ContractConfig {
 pub struct ContractService {
     client: Client,
     config: ContractConfig,
+    /// Last sequence number used by the source account, or `None` when it
+    /// must be fetched from the network. Held while a transaction is built
+    /// and sent so concurrent submissions take consecutive numbers.
+    sequence: Arc<Mutex<Option<i64>>>,
 }
 
 /// RPC request structure for Soroban
@@ -50,6 +87,7 @@ struct JsonRpcRequest {
 /// RPC response structure
 /// Note: All fields required for JSON deserialization from Stellar RPC
 #[derive(Debug, Deserialize)]
+#[serde(bound(deserialize = "T: Deserialize<'de>"))]
 struct JsonRpcResponse<T> {
     #[allow(dead_code)] // Required for JSON deserialization
     jsonrpc: String,
@@ -81,6 +119,240 @@ impl std::fmt::Display for RpcError {
 
 impl std::error::Error for RpcError {}
 
+/// `simulateTransaction` result
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SimulateTransactionResult {
+    #[serde(default)]
+    error: Option<String>,
+    /// Base64 `SorobanTransactionData` with the footprint and resources
+    #[serde(default)]
+    transaction_data: Option<String>,
+    /// Resource fee in stroops, as a decimal string
+    #[serde(default)]
+    min_resource_fee: Option<String>,
+    #[serde(default)]
+    results: Vec<SimulateHostFunctionResult>,
+    /// Present when archived ledger entries must be restored first
+    #[serde(default)]
+    restore_preamble: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SimulateHostFunctionResult {
+    /// Base64 `SorobanAuthorizationEntry` values the invocation requires
+    #[serde(default)]
+    auth: Vec<String>,
+}
+
+/// `sendTransaction` result
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SendTransactionResult {
+    status: String,
+    hash: String,
+    /// Base64 `TransactionResult` when the status is `ERROR`
+    #[serde(default)]
+    error_result_xdr: Option<String>,
+}
+
+/// `getLedgerEntries` result
+#[derive(Debug, Deserialize)]
+struct LedgerEntriesResult {
+    #[serde(default)]
+    entries: Vec<LedgerEntryResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LedgerEntryResult {
+    /// Base64 `LedgerEntryData`
+    xdr: String,
+}
+
+/// Outcome of sending a signed transaction
+#[derive(Debug)]
+enum SendOutcome {
+    /// Accepted into the queue (or already known to it)
+    Pending(String),
+    /// Rejected with the given result code
+    Rejected { code: String, bad_sequence: bool },
+}
+
+fn encode_xdr(value: &impl WriteXdr) -> Result<String> {
+    Ok(BASE64.encode(value.to_xdr(Limits::none())?))
+}
+
+fn decode_xdr<T: ReadXdr>(encoded: &str) -> Result<T> {
+    let bytes = BASE64.decode(encoded).context("XDR is not base64")?;
+    Ok(T::from_xdr(bytes, Limits::none())?)
+}
+
+/// Decode a Stellar `S...` secret seed into a signing key
+fn parse_secret_key(secret: &str) -> Result<SigningKey> {
+    let seed = stellar_strkey::ed25519::PrivateKey::from_string(secret.trim())
+        .map_err(|e| anyhow::anyhow!("Invalid Stellar secret key: {:?}", e))?;
+    Ok(SigningKey::from_bytes(&seed.0))
+}
+
+fn source_account(key: &SigningKey) -> MuxedAccount {
+    MuxedAccount::Ed25519(Uint256(key.verifying_key().to_bytes()))
+}
+
+fn network_id(passphrase: &str) -> Hash {
+    Hash(Sha256::digest(passphrase.as_bytes()).into())
+}
+
+/// Sign a transaction payload the way stellar-core verifies it
+fn decorated_signature(
+    key: &SigningKey,
+    network_passphrase: &str,
+    tagged_transaction: TransactionSignaturePayloadTaggedTransaction,
+) -> Result<DecoratedSignature> {
+    let payload = TransactionSignaturePayload {
+        network_id: network_id(network_passphrase),
+        tagged_transaction,
+    };
+    let hash = Sha256::digest(payload.to_xdr(Limits::none())?);
+    let signature = key.sign(&hash);
+
+    let public_key = key.verifying_key().to_bytes();
+    let mut hint = [0u8; 4];
+    hint.copy_from_slice(&public_key[28..]);
+
+    Ok(DecoratedSignature {
+        hint: SignatureHint(hint),
+        signature: Signature(signature.to_bytes().to_vec().try_into()?),
+    })
+}
+
+/// Convert one `{type, value}` argument built by `build_invoke_args` to an `ScVal`
+fn to_sc_val(arg: &serde_json::Value) -> Result<ScVal> {
+    let kind = arg
+        .get("type")
+        .and_then(|t| t.as_str())
+        .context("Argument type missing")?;
+    let value = arg
+        .get("value")
+        .and_then(|v| v.as_str())
+        .context("Argument value missing")?;
+
+    Ok(match kind {
+        "bytes" => ScVal::Bytes(ScBytes(
+            hex::decode(value)
+                .context("Bytes argument is not hex")?
+                .try_into()?,
+        )),
+        "u64" => ScVal::U64(value.parse().context("Invalid u64 argument")?),
+        "u32" => ScVal::U32(value.parse().context("Invalid u32 argument")?),
+        "string" => ScVal::String(ScString(value.try_into()?)),
+        "symbol" => ScVal::Symbol(ScSymbol(value.try_into()?)),
+        "address" => ScVal::Address(
+            ScAddress::from_str(value).map_err(|e| anyhow::anyhow!("Invalid address: {}", e))?,
+        ),
+        other => return Err(anyhow::anyhow!("Unsupported argument type: {}", other)),
+    })
+}
+
+/// Convert the JSON invocation built by `build_invoke_args` to XDR
+fn invoke_contract_args(invoke_args: &serde_json::Value) -> Result<InvokeContractArgs> {
+    let contract_id = invoke_args
+        .get("contractId")
+        .and_then(|c| c.as_str())
+        .context("Contract ID missing")?;
+    let function = invoke_args
+        .get("function")
+        .and_then(|f| f.as_str())
+        .context("Function name missing")?;
+    let args = invoke_args
+        .get("args")
+        .and_then(|a| a.as_array())
+        .map(|args| args.iter().map(to_sc_val).collect::<Result<Vec<_>>>())
+        .transpose()?
+        .unwrap_or_default();
+
+    Ok(InvokeContractArgs {
+        contract_address: ScAddress::from_str(contract_id)
+            .map_err(|e| anyhow::anyhow!("Invalid contract ID {}: {}", contract_id, e))?,
+        function_name: ScSymbol(function.try_into()?),
+        args: args.try_into()?,
+    })
+}
+
+/// Apply a simulation to the transaction it was run against: attach the
+/// footprint and resources, fill in the authorization entries the invocation
+/// needs and add the resource fee to the inclusion fee
+fn assemble_transaction(
+    mut tx: Transaction,
+    simulated: &SimulateTransactionResult,
+) -> Result<Transaction> {
+    if let Some(error) = &simulated.error {
+        return Err(anyhow::anyhow!("Transaction simulation failed: {}", error));
+    }
+    if simulated.restore_preamble.is_some() {
+        return Err(anyhow::anyhow!(
+            "Contract state is archived and must be restored before submitting"
+        ));
+    }
+
+    let data: SorobanTransactionData = decode_xdr(
+        simulated
+            .transaction_data
+            .as_deref()
+            .context("Simulation returned no transaction data")?,
+    )
+    .context("Invalid simulated transaction data")?;
+    let resource_fee: u32 = simulated
+        .min_resource_fee
+        .as_deref()
+        .context("Simulation returned no resource fee")?
+        .parse()
+        .context("Invalid simulated resource fee")?;
+
+    let auth: Vec<SorobanAuthorizationEntry> = simulated
+        .results
+        .iter()
+        .flat_map(|result| &result.auth)
+        .map(|entry| decode_xdr(entry).context("Invalid simulated auth entry"))
+        .collect::<Result<_>>()?;
+
+    let mut operations = tx.operations.to_vec();
+    if let Some(OperationBody::InvokeHostFunction(op)) = operations.first_mut().map(|o| &mut o.body)
+    {
+        // Entries signed up front by the caller take precedence
+        if op.auth.is_empty() {
+            op.auth = auth.try_into()?;
+        }
+    }
+    tx.operations = operations.try_into()?;
+    tx.fee = tx
+        .fee
+        .checked_add(resource_fee)
+        .context("Transaction fee overflows")?;
+    tx.ext = TransactionExt::V1(data);
+
+    Ok(tx)
+}
+
+/// Resource fee reserved by a transaction's Soroban data
+fn resource_fee(tx: &Transaction) -> i64 {
+    match &tx.ext {
+        TransactionExt::V1(data) => data.resource_fee,
+        TransactionExt::V0 => 0,
+    }
+}
+
+/// Fee for a fee bump of `envelope` on bump `attempt` (starting at 1). Fee
+/// bumps pay the inclusion fee for the inner operations plus one, on top of
+/// the inner transaction's resource fee.
+fn fee_bump_fee(inner: &Transaction, attempt: u32) -> i64 {
+    let operations = inner.operations.len() as i64;
+    let inclusion_per_op = (i64::from(inner.fee) - resource_fee(inner)) / operations.max(1);
+    let bumped = (inclusion_per_op.max(i64::from(BASE_FEE)) * FEE_BUMP_MULTIPLIER.pow(attempt))
+        .min(MAX_INCLUSION_FEE);
+
+    resource_fee(inner) + bumped * (operations + 1)
+}
+
 /// Result of a successful snapshot submission
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct SubmissionResult {
@@ -107,7 +379,11 @@ impl ContractService {
             config.rpc_url, config.contract_id
         );
 
-        Ok(Self { client, config })
+        Ok(Self {
+            client,
+            config,
+            sequence: Arc::new(Mutex::new(None)),
+        })
     }
 
     /// Create from environment variables
@@ -126,6 +402,35 @@ impl ContractService {
         Self::new(config)
     }
 
+    /// Create from environment variables, reading the signing secret from
+    /// Vault when `STELLAR_SOURCE_SECRET_VAULT_PATH` is set. The secret is the
+    /// `STELLAR_SOURCE_SECRET_VAULT_FIELD` field of the secret (default
+    /// `secret_key`).
+    pub async fn from_env_with_vault(vault: &VaultClient) -> Result<Self> {
+        let Ok(path) = std::env::var("STELLAR_SOURCE_SECRET_VAULT_PATH") else {
+            return Self::from_env();
+        };
+        let field = std::env::var("STELLAR_SOURCE_SECRET_VAULT_FIELD")
+            .unwrap_or_else(|_| "secret_key".to_string());
+
+        let source_secret_key = vault
+            .read_secret(&path, Some(&field))
+            .await
+            .with_context(|| format!("Failed to read source secret key from Vault at {}", path))?;
+
+        let config = ContractConfig {
+            rpc_url: std::env::var("SOROBAN_RPC_URL")
+                .unwrap_or_else(|_| "https://soroban-testnet.stellar.org".to_string()),
+            contract_id: std::env::var("SNAPSHOT_CONTRACT_ID")
+                .context("SNAPSHOT_CONTRACT_ID environment variable not set")?,
+            network_passphrase: std::env::var("STELLAR_NETWORK_PASSPHRASE")
+                .unwrap_or_else(|_| "Test SDF Network ; September 2015".to_string()),
+            source_secret_key,
+        };
+
+        Self::new(config)
+    }
+
     /// Submit a snapshot hash to the on-chain contract
     ///
     /// This function will:
@@ -211,21 +516,48 @@ impl ContractService {
 
     /// Single attempt to submit snapshot (without retry logic)
     async fn try_submit_snapshot(&self, hash: [u8; 32], epoch: u64) -> Result<SubmissionResult> {
+        let signing_key = parse_secret_key(&self.config.source_secret_key)?;
+
         // Step 1: Build the contract invocation
         debug!("Building contract invocation for epoch {}", epoch);
         let invoke_args = self.build_invoke_args(hash, epoch)?;
 
+        // The sequence number stays reserved until the network has accepted
+        // or rejected the transaction
+        let mut sequence = self.sequence.lock().await;
+        let current = match *sequence {
+            Some(current) => current,
+            None => self.fetch_sequence(&signing_key).await?,
+        };
+        let tx = self.build_transaction(&signing_key, current + 1, &invoke_args)?;
+
         // Step 2: Simulate the transaction
         debug!("Simulating transaction");
-        let simulated = self.simulate_transaction(&invoke_args).await?;
+        let simulated = self.simulate_transaction(&tx).await?;
 
         // Step 3: Prepare and sign the transaction
         debug!("Preparing and signing transaction");
-        let signed_xdr = self.prepare_and_sign_transaction(&simulated)?;
+        let signed = self.prepare_and_sign_transaction(&signing_key, tx, &simulated)?;
 
-        // Step 4: Send the transaction
+        // Step 4: Send the transaction, bumping its fee while the network is
+        // too busy to accept it
         debug!("Sending transaction to network");
-        let tx_hash = self.send_transaction(&signed_xdr).await?;
+        let tx_hash = match self.send_with_fee_bumps(&signing_key, signed).await {
+            Ok(SendOutcome::Pending(tx_hash)) => {
+                *sequence = Some(current + 1);
+                tx_hash
+            }
+            Ok(SendOutcome::Rejected { code, bad_sequence }) => {
+                // Another client may have used the account; refetch next time
+                *sequence = if bad_sequence { None } else { Some(current) };
+                return Err(anyhow::anyhow!("Transaction rejected: {}", code));
+            }
+            Err(e) => {
+                *sequence = None;
+                return Err(e);
+            }
+        };
+        drop(sequence);
 
         // Step 5: Wait for transaction confirmation
         debug!("Waiting for transaction confirmation: {}", tx_hash);
@@ -234,41 +566,17 @@ impl ContractService {
         Ok(result)
     }
 
-    /// Build contract invocation arguments
-    fn build_invoke_args(&self, hash: [u8; 32], epoch: u64) -> Result<serde_json::Value> {
-        // Convert hash to hex for the contract call
-        let hash_hex = hex::encode(hash);
-
-        // Build Soroban contract invocation parameters
-        // Format: invoke contract_id submit_snapshot [hash_bytes, epoch_u64]
-        Ok(json!({
-            "contractId": self.config.contract_id,
-            "function": "submit_snapshot",
-            "args": [
-                {
-                    "type": "bytes",
-                    "value": hash_hex
-                },
-                {
-                    "type": "u64",
-                    "value": epoch.to_string()
-                }
-            ]
-        }))
-    }
-
-    /// Simulate the transaction to get resource estimates
-    async fn simulate_transaction(
+    /// Send a JSON-RPC request and decode its result
+    async fn rpc_call<T: DeserializeOwned>(
         &self,
-        invoke_args: &serde_json::Value,
-    ) -> Result<serde_json::Value> {
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<T> {
         let request = JsonRpcRequest {
             jsonrpc: "2.0".to_string(),
             id: 1,
-            method: "simulateTransaction".to_string(),
-            params: json!({
-                "transaction": invoke_args
-            }),
+            method: method.to_string(),
+            params,
         };
 
         let response = self
@@ -277,87 +585,242 @@ impl ContractService {
             .json(&request)
             .send()
             .await
-            .context("Failed to send simulation request")?;
+            .with_context(|| format!("Failed to send {} request", method))?;
 
         let status = response.status();
-        let body: JsonRpcResponse<serde_json::Value> = response
+        let body: JsonRpcResponse<T> = response
             .json()
             .await
-            .context("Failed to parse simulation response")?;
+            .with_context(|| format!("Failed to parse {} response", method))?;
 
         if let Some(error) = body.error {
             return Err(anyhow::anyhow!(
-                "Transaction simulation failed: {} (code: {})",
+                "{} failed: {} (code: {})",
+                method,
                 error.message,
                 error.code
             ));
         }
 
         body.result
-            .ok_or_else(|| anyhow::anyhow!("No simulation result returned (status: {})", status))
+            .ok_or_else(|| anyhow::anyhow!("No {} result returned (status: {})", method, status))
     }
 
-    /// Prepare and sign the transaction
-    fn prepare_and_sign_transaction(&self, _simulated: &serde_json::Value) -> Result<String> {
-        // In a real implementation, this would:
-        // 1. Extract the transaction envelope from simulation
-        // 2. Set appropriate fees and sequence number
-        // 3. Sign with the source account's secret key
-        // 4. Return the signed XDR
-
-        // For now, return a placeholder that would need stellar-sdk integration
-        // TODO: Integrate stellar-sdk for proper transaction signing
+    /// Current sequence number of the source account
+    async fn fetch_sequence(&self, signing_key: &SigningKey) -> Result<i64> {
+        let key = LedgerKey::Account(LedgerKeyAccount {
+            account_id: AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(
+                signing_key.verifying_key().to_bytes(),
+            ))),
+        });
 
-        warn!("Transaction signing not yet implemented - requires stellar-sdk integration");
-        Err(anyhow::anyhow!(
-            "Transaction signing requires stellar-sdk library integration"
-        ))
+        let result: LedgerEntriesResult = self
+            .rpc_call("getLedgerEntries", json!({ "keys": [encode_xdr(&key)?] }))
+            .await?;
+        let entry = result
+            .entries
+            .first()
+            .context("Source account not found on the network")?;
+
+        match decode_xdr::<LedgerEntryData>(&entry.xdr)? {
+            LedgerEntryData::Account(account) => {
+                debug!("Source account sequence is {}", account.seq_num.0);
+                Ok(account.seq_num.0)
+            }
+            _ => Err(anyhow::anyhow!(
+                "Unexpected ledger entry for source account"
+            )),
+        }
     }
 
-    /// Send the signed transaction to the network
-    async fn send_transaction(&self, signed_xdr: &str) -> Result<String> {
-        let request = JsonRpcRequest {
-            jsonrpc: "2.0".to_string(),
-            id: 1,
-            method: "sendTransaction".to_string(),
-            params: json!({
-                "transaction": signed_xdr
+    /// Build the unsigned `InvokeHostFunction` transaction for an invocation
+    fn build_transaction(
+        &self,
+        signing_key: &SigningKey,
+        sequence: i64,
+        invoke_args: &serde_json::Value,
+    ) -> Result<Transaction> {
+        let max_time = chrono::Utc::now().timestamp().max(0) as u64 + TRANSACTION_TIMEOUT_SECS;
+        let operation = Operation {
+            source_account: None,
+            body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+                host_function: HostFunction::InvokeContract(invoke_contract_args(invoke_args)?),
+                auth: Vec::new().try_into()?,
             }),
         };
 
-        let response = self
-            .client
-            .post(&self.config.rpc_url)
-            .json(&request)
-            .send()
-            .await
-            .context("Failed to send transaction")?;
+        Ok(Transaction {
+            source_account: source_account(signing_key),
+            fee: BASE_FEE,
+            seq_num: SequenceNumber(sequence),
+            cond: Preconditions::Time(TimeBounds {
+                min_time: TimePoint(0),
+                max_time: TimePoint(max_time),
+            }),
+            memo: Memo::None,
+            operations: vec![operation].try_into()?,
+            ext: TransactionExt::V0,
+        })
+    }
 
-        let body: JsonRpcResponse<serde_json::Value> = response
-            .json()
-            .await
-            .context("Failed to parse send transaction response")?;
+    /// Build contract invocation arguments
+    fn build_invoke_args(&self, hash: [u8; 32], epoch: u64) -> Result<serde_json::Value> {
+        // Convert hash to hex for the contract call
+        let hash_hex = hex::encode(hash);
 
-        if let Some(error) = body.error {
-            return Err(anyhow::anyhow!(
-                "Transaction submission failed: {} (code: {})",
-                error.message,
-                error.code
-            ));
-        }
+        // Build Soroban contract invocation parameters
+        // Format: invoke contract_id submit_snapshot [hash_bytes, epoch_u64]
+        Ok(json!({
+            "contractId": self.config.contract_id,
+            "function": "submit_snapshot",
+            "args": [
+                {
+                    "type": "bytes",
+                    "value": hash_hex
+                },
+                {
+                    "type": "u64",
+                    "value": epoch.to_string()
+                }
+            ]
+        }))
+    }
 
-        let result = body
-            .result
-            .ok_or_else(|| anyhow::anyhow!("No transaction hash returned"))?;
+    /// Simulate the transaction to get resource estimates
+    async fn simulate_transaction(&self, tx: &Transaction) -> Result<SimulateTransactionResult> {
+        let envelope = TransactionEnvelope::Tx(TransactionV1Envelope {
+            tx: tx.clone(),
+            signatures: Vec::new().try_into()?,
+        });
 
-        // Extract transaction hash from result
-        let tx_hash = result
-            .get("hash")
-            .and_then(|h| h.as_str())
-            .ok_or_else(|| anyhow::anyhow!("Transaction hash not found in response"))?
-            .to_string();
+        self.rpc_call(
+            "simulateTransaction",
+            json!({
+                "transaction": encode_xdr(&envelope)?
+            }),
+        )
+        .await
+        .context("Transaction simulation failed")
+    }
 
-        Ok(tx_hash)
+    /// Prepare and sign the transaction: apply the simulated footprint,
+    /// resource fee and auth entries, then sign with the source account key
+    fn prepare_and_sign_transaction(
+        &self,
+        signing_key: &SigningKey,
+        tx: Transaction,
+        simulated: &SimulateTransactionResult,
+    ) -> Result<TransactionV1Envelope> {
+        let tx = assemble_transaction(tx, simulated)?;
+        let signature = decorated_signature(
+            signing_key,
+            &self.config.network_passphrase,
+            TransactionSignaturePayloadTaggedTransaction::Tx(tx.clone()),
+        )?;
+
+        debug!(
+            "Prepared transaction with fee {} (resource fee {})",
+            tx.fee,
+            resource_fee(&tx)
+        );
+        Ok(TransactionV1Envelope {
+            tx,
+            signatures: vec![signature].try_into()?,
+        })
+    }
+
+    /// Wrap a signed transaction in a fee bump paid and signed by the source
+    /// account
+    fn fee_bump(
+        &self,
+        signing_key: &SigningKey,
+        inner: &TransactionV1Envelope,
+        attempt: u32,
+    ) -> Result<TransactionEnvelope> {
+        let tx = FeeBumpTransaction {
+            fee_source: source_account(signing_key),
+            fee: fee_bump_fee(&inner.tx, attempt),
+            inner_tx: FeeBumpTransactionInnerTx::Tx(inner.clone()),
+            ext: FeeBumpTransactionExt::V0,
+        };
+        let signature = decorated_signature(
+            signing_key,
+            &self.config.network_passphrase,
+            TransactionSignaturePayloadTaggedTransaction::TxFeeBump(tx.clone()),
+        )?;
+
+        Ok(TransactionEnvelope::TxFeeBump(FeeBumpTransactionEnvelope {
+            tx,
+            signatures: vec![signature].try_into()?,
+        }))
+    }
+
+    /// Send a signed transaction, re-sending it inside a fee bump with a
+    /// higher inclusion fee each time the network answers `TRY_AGAIN_LATER`
+    async fn send_with_fee_bumps(
+        &self,
+        signing_key: &SigningKey,
+        signed: TransactionV1Envelope,
+    ) -> Result<SendOutcome> {
+        let mut envelope = TransactionEnvelope::Tx(signed.clone());
+        let mut bumps = 0;
+
+        loop {
+            let result = self.send_transaction(&envelope).await?;
+            match result.status.as_str() {
+                "PENDING" | "DUPLICATE" => return Ok(SendOutcome::Pending(result.hash)),
+                "TRY_AGAIN_LATER" if bumps < MAX_FEE_BUMPS => {
+                    bumps += 1;
+                    envelope = self.fee_bump(signing_key, &signed, bumps)?;
+                    warn!(
+                        "Network asked to try again later; fee bump {}/{} offering {} stroops",
+                        bumps,
+                        MAX_FEE_BUMPS,
+                        fee_bump_fee(&signed.tx, bumps)
+                    );
+                    tokio::time::sleep(Duration::from_millis(INITIAL_BACKOFF_MS)).await;
+                }
+                "TRY_AGAIN_LATER" => {
+                    return Ok(SendOutcome::Rejected {
+                        code: "TRY_AGAIN_LATER".to_string(),
+                        bad_sequence: false,
+                    })
+                }
+                "ERROR" => {
+                    let result = result
+                        .error_result_xdr
+                        .as_deref()
+                        .map(decode_xdr::<TransactionResult>)
+                        .transpose()
+                        .context("Invalid transaction error result")?;
+                    let code = result
+                        .as_ref()
+                        .map_or("unknown error", |r| r.result.name())
+                        .to_string();
+                    let bad_sequence = matches!(
+                        result.map(|r| r.result),
+                        Some(TransactionResultResult::TxBadSeq)
+                    );
+                    return Ok(SendOutcome::Rejected { code, bad_sequence });
+                }
+                other => return Err(anyhow::anyhow!("Unknown sendTransaction status: {}", other)),
+            }
+        }
+    }
+
+    /// Send a signed transaction envelope to the network
+    async fn send_transaction(
+        &self,
+        envelope: &TransactionEnvelope,
+    ) -> Result<SendTransactionResult> {
+        self.rpc_call(
+            "sendTransaction",
+            json!({
+                "transaction": encode_xdr(envelope)?
+            }),
+        )
+        .await
+        .context("Transaction submission failed")
     }
 
     /// Wait for transaction to be confirmed and return the result
@@ -631,6 +1094,166 @@ mod tests {
         assert!(args["args"].is_array());
     }
 
+    fn test_service() -> (ContractService, SigningKey) {
+        let secret = stellar_strkey::ed25519::PrivateKey([7u8; 32]).to_string();
+        let config = ContractConfig {
+            rpc_url: "https://soroban-testnet.stellar.org".to_string(),
+            contract_id: stellar_strkey::Contract([1u8; 32]).to_string(),
+            network_passphrase: "Test SDF Network ; September 2015".to_string(),
+            source_secret_key: secret.to_string(),
+        };
+        let key = parse_secret_key(&secret).unwrap();
+        (ContractService::new(config).unwrap(), key)
+    }
+
+    fn test_simulation() -> SimulateTransactionResult {
+        let data = SorobanTransactionData {
+            ext: stellar_xdr::curr::ExtensionPoint::V0,
+            resources: stellar_xdr::curr::SorobanResources {
+                footprint: stellar_xdr::curr::LedgerFootprint {
+                    read_only: Vec::new().try_into().unwrap(),
+                    read_write: Vec::new().try_into().unwrap(),
+                },
+                instructions: 1_000_000,
+                read_bytes: 1_000,
+                write_bytes: 500,
+            },
+            resource_fee: 25_000,
+        };
+        let auth = SorobanAuthorizationEntry {
+            credentials: stellar_xdr::curr::SorobanCredentials::SourceAccount,
+            root_invocation: stellar_xdr::curr::SorobanAuthorizedInvocation {
+                function: stellar_xdr::curr::SorobanAuthorizedFunction::ContractFn(
+                    InvokeContractArgs {
+                        contract_address: ScAddress::Contract(Hash([1u8; 32])),
+                        function_name: ScSymbol("submit_snapshot".try_into().unwrap()),
+                        args: Vec::new().try_into().unwrap(),
+                    },
+                ),
+                sub_invocations: Vec::new().try_into().unwrap(),
+            },
+        };
+
+        SimulateTransactionResult {
+            transaction_data: Some(encode_xdr(&data).unwrap()),
+            min_resource_fee: Some("25000".to_string()),
+            results: vec![SimulateHostFunctionResult {
+                auth: vec![encode_xdr(&auth).unwrap()],
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn test_transaction(service: &ContractService, key: &SigningKey) -> Transaction {
+        let args = service.build_invoke_args([9u8; 32], 42).unwrap();
+        service.build_transaction(key, 101, &args).unwrap()
+    }
+
+    #[test]
+    fn test_invoke_contract_args_converts_arguments() {
+        let (service, _) = test_service();
+        let args = service.build_invoke_args([9u8; 32], 42).unwrap();
+        let invoke = invoke_contract_args(&args).unwrap();
+
+        assert_eq!(
+            invoke.contract_address,
+            ScAddress::Contract(Hash([1u8; 32]))
+        );
+        assert_eq!(
+            invoke.function_name.0.to_utf8_string_lossy(),
+            "submit_snapshot"
+        );
+        assert_eq!(invoke.args.len(), 2);
+        assert_eq!(
+            invoke.args[0],
+            ScVal::Bytes(ScBytes(vec![9u8; 32].try_into().unwrap()))
+        );
+        assert_eq!(invoke.args[1], ScVal::U64(42));
+
+        let bad = json!({
+            "contractId": service.config.contract_id,
+            "function": "submit_snapshot",
+            "args": [{"type": "map", "value": "x"}]
+        });
+        assert!(invoke_contract_args(&bad).is_err());
+    }
+
+    #[test]
+    fn test_assemble_applies_simulation() {
+        let (service, key) = test_service();
+        let tx = test_transaction(&service, &key);
+        assert_eq!(tx.seq_num, SequenceNumber(101));
+
+        let assembled = assemble_transaction(tx, &test_simulation()).unwrap();
+
+        assert_eq!(assembled.fee, BASE_FEE + 25_000);
+        assert_eq!(resource_fee(&assembled), 25_000);
+        let OperationBody::InvokeHostFunction(op) = &assembled.operations[0].body else {
+            panic!("expected InvokeHostFunction");
+        };
+        assert_eq!(op.auth.len(), 1);
+
+        let failed = SimulateTransactionResult {
+            error: Some("HostError".to_string()),
+            ..Default::default()
+        };
+        let tx = test_transaction(&service, &key);
+        assert!(assemble_transaction(tx, &failed).is_err());
+    }
+
+    #[test]
+    fn test_signature_verifies_against_source_account() {
+        let (service, key) = test_service();
+        let tx = test_transaction(&service, &key);
+        let envelope = service
+            .prepare_and_sign_transaction(&key, tx, &test_simulation())
+            .unwrap();
+
+        let signature = &envelope.signatures[0];
+        let public_key = key.verifying_key().to_bytes();
+        assert_eq!(signature.hint.0, public_key[28..]);
+
+        let payload = TransactionSignaturePayload {
+            network_id: network_id(&service.config.network_passphrase),
+            tagged_transaction: TransactionSignaturePayloadTaggedTransaction::Tx(
+                envelope.tx.clone(),
+            ),
+        };
+        let hash = Sha256::digest(payload.to_xdr(Limits::none()).unwrap());
+        let signature =
+            ed25519_dalek::Signature::from_slice(signature.signature.0.as_slice()).unwrap();
+        assert!(key.verifying_key().verify_strict(&hash, &signature).is_ok());
+    }
+
+    #[test]
+    fn test_fee_bump_raises_inclusion_fee() {
+        let (service, key) = test_service();
+        let tx = test_transaction(&service, &key);
+        let signed = service
+            .prepare_and_sign_transaction(&key, tx, &test_simulation())
+            .unwrap();
+
+        let TransactionEnvelope::TxFeeBump(bump) = service.fee_bump(&key, &signed, 1).unwrap()
+        else {
+            panic!("expected fee bump envelope");
+        };
+        // One inner operation plus the fee bump itself at four times the base fee
+        assert_eq!(bump.tx.fee, 25_000 + 400 * 2);
+        assert_eq!(bump.tx.fee_source, source_account(&key));
+        assert_eq!(bump.signatures.len(), 1);
+
+        // The inclusion fee is capped however often the network pushes back
+        assert_eq!(fee_bump_fee(&signed.tx, 20), 25_000 + MAX_INCLUSION_FEE * 2);
+    }
+
+    #[test]
+    fn test_parse_secret_key_rejects_invalid_keys() {
+        assert!(parse_secret_key("S...").is_err());
+        assert!(
+            parse_secret_key(&stellar_strkey::ed25519::PrivateKey([7u8; 32]).to_string()).is_ok()
+        );
+    }
+
     #[tokio::test]
     async fn test_health_check_with_mock() {
         // This would require a mock server setup
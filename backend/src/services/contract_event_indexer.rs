@@ -0,0 +1,497 @@
+//! Soroban contract event indexer.
+//!
+//! Polls RPC `getEvents` for the configured contracts, resuming from a cursor persisted
+//! in `ingestion_state`, decodes each event and stores it through `ContractEventDb`.
+//! The cursor is stored per filter set, so changing the contracts or topics starts a
+//! fresh scan instead of resuming past events the new filters would match. A cursor
+//! RPC no longer retains is dropped and the scan restarts from the start ledger.
+//! RPC accepts at most five filters of five contract ids each, so larger contract sets
+//! are rejected at configuration time rather than silently truncated.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+use crate::database::Database;
+use crate::ingestion::contract_events::{
+    decode_event, decode_sc_val, sc_val_to_json, symbol_topic, DecodedEvent, IndexedEvent,
+};
+use crate::rpc::error::RpcError;
+use crate::rpc::{GetEventsResult, RpcContractEvent, RpcEventFilter, StellarRpcClient};
+use crate::websocket::{WsMessage, WsState};
+
+/// Prefix of the ingestion cursors the indexer resumes from
+pub const CURSOR_TASK_PREFIX: &str = "soroban_events";
+
+const MAX_FILTERS: usize = 5;
+const MAX_CONTRACTS_PER_FILTER: usize = 5;
+
+#[derive(Debug, Clone)]
+pub struct ContractEventIndexerConfig {
+    /// Contracts whose events are indexed, as `C...` strkeys
+    pub contract_ids: Vec<String>,
+    /// Topic patterns an event must match one of; each segment is a symbol or `*`.
+    /// Empty matches every event of the contracts.
+    pub topics: Vec<Vec<String>>,
+    /// Ledger to start from when no cursor is stored; defaults to the oldest ledger
+    /// the RPC node retains
+    pub start_ledger: Option<u64>,
+    /// Events requested per `getEvents` call
+    pub page_size: u32,
+    /// Pages fetched per run
+    pub max_pages: usize,
+    /// Time between runs
+    pub poll_interval: std::time::Duration,
+}
+
+impl Default for ContractEventIndexerConfig {
+    fn default() -> Self {
+        Self {
+            contract_ids: Vec::new(),
+            topics: Vec::new(),
+            start_ledger: None,
+            page_size: 100,
+            max_pages: 10,
+            poll_interval: std::time::Duration::from_secs(30),
+        }
+    }
+}
+
+impl ContractEventIndexerConfig {
+    /// Reads `SOROBAN_EVENT_CONTRACT_IDS` (comma-separated), `SOROBAN_EVENT_TOPICS`
+    /// (patterns separated by `;`, segments by `,`), `SOROBAN_EVENT_START_LEDGER` and
+    /// `SOROBAN_EVENT_POLL_SECONDS`, falling back to the defaults.
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(ids) = std::env::var("SOROBAN_EVENT_CONTRACT_IDS") {
+            config.contract_ids = split_list(&ids, ',');
+        }
+        if let Ok(topics) = std::env::var("SOROBAN_EVENT_TOPICS") {
+            config.topics = split_list(&topics, ';')
+                .iter()
+                .map(|pattern| split_list(pattern, ','))
+                .filter(|pattern| !pattern.is_empty())
+                .collect();
+        }
+        config.start_ledger = env_parse::<u64>("SOROBAN_EVENT_START_LEDGER");
+        if let Some(seconds) = env_parse::<u64>("SOROBAN_EVENT_POLL_SECONDS").filter(|s| *s > 0) {
+            config.poll_interval = std::time::Duration::from_secs(seconds);
+        }
+        config
+    }
+
+    /// `getEvents` filters covering the configured contracts and topics
+    pub fn filters(&self) -> Result<Vec<RpcEventFilter>> {
+        if self.contract_ids.len() > MAX_FILTERS * MAX_CONTRACTS_PER_FILTER {
+            bail!(
+                "At most {} contract ids can be indexed, got {}",
+                MAX_FILTERS * MAX_CONTRACTS_PER_FILTER,
+                self.contract_ids.len()
+            );
+        }
+
+        let topics = self
+            .topics
+            .iter()
+            .map(|pattern| {
+                pattern
+                    .iter()
+                    .map(|segment| match segment.as_str() {
+                        "*" => Ok("*".to_string()),
+                        symbol => symbol_topic(symbol),
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(self
+            .contract_ids
+            .chunks(MAX_CONTRACTS_PER_FILTER)
+            .map(|ids| RpcEventFilter {
+                event_type: "contract".to_string(),
+                contract_ids: ids.to_vec(),
+                topics: topics.clone(),
+            })
+            .collect())
+    }
+}
+
+/// Ingestion cursor name for a filter set: the prefix and a hash of the filters
+pub fn cursor_task_name(filters: &[RpcEventFilter]) -> Result<String> {
+    let encoded = serde_json::to_vec(filters).context("Failed to encode event filters")?;
+    let digest = hex::encode(Sha256::digest(&encoded));
+    Ok(format!("{}:{}", CURSOR_TASK_PREFIX, &digest[..16]))
+}
+
+/// Whether RPC rejected the requested cursor or start ledger because it lies outside
+/// the ledgers the node retains
+fn is_out_of_range(error: &RpcError) -> bool {
+    match error {
+        RpcError::ServerError { message, .. } => {
+            let message = message.to_ascii_lowercase();
+            message.contains("must be between the oldest ledger")
+                || message.contains("out of range")
+        }
+        _ => false,
+    }
+}
+
+fn split_list(value: &str, separator: char) -> Vec<String> {
+    value
+        .split(separator)
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn env_parse<T: std::str::FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok().and_then(|v| v.parse().ok())
+}
+
+/// Outcome of one indexer run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexerRun {
+    /// Events returned by RPC
+    pub fetched: usize,
+    /// Events stored for the first time
+    pub stored: usize,
+    /// Events stored without a typed decoding because decoding failed
+    pub undecoded: usize,
+    pub latest_ledger: u64,
+}
+
+pub struct ContractEventIndexer {
+    db: Arc<Database>,
+    rpc_client: Arc<StellarRpcClient>,
+    config: ContractEventIndexerConfig,
+    ws_state: Option<Arc<WsState>>,
+}
+
+impl ContractEventIndexer {
+    pub fn new(
+        db: Arc<Database>,
+        rpc_client: Arc<StellarRpcClient>,
+        config: ContractEventIndexerConfig,
+    ) -> Self {
+        Self {
+            db,
+            rpc_client,
+            config,
+            ws_state: None,
+        }
+    }
+
+    /// Broadcast newly indexed snapshot submissions to WebSocket clients as
+    /// `SnapshotUpdate` messages
+    pub fn with_websocket(mut self, ws_state: Arc<WsState>) -> Self {
+        self.ws_state = Some(ws_state);
+        self
+    }
+
+    pub fn config(&self) -> &ContractEventIndexerConfig {
+        &self.config
+    }
+
+    /// Fetch and store new events, up to `max_pages` pages
+    pub async fn run_once(&self) -> Result<IndexerRun> {
+        let filters = self.config.filters()?;
+        let mut run = IndexerRun::default();
+        if filters.is_empty() {
+            return Ok(run);
+        }
+
+        let network = self.rpc_client.network_config().network.to_string();
+        let event_db = self.db.contract_event_db();
+        let task_name = cursor_task_name(&filters)?;
+        let mut cursor = self.db.get_ingestion_cursor(&task_name).await?;
+        let mut start_ledger = match (&cursor, self.config.start_ledger) {
+            (Some(_), _) => None,
+            (None, Some(ledger)) => Some(ledger),
+            (None, None) => Some(self.oldest_ledger().await?),
+        };
+
+        for _ in 0..self.config.max_pages {
+            let page = self
+                .fetch_page(&filters, &mut cursor, &mut start_ledger)
+                .await?;
+            run.latest_ledger = page.latest_ledger;
+            run.fetched += page.events.len();
+
+            for event in page.events.iter().filter(|e| e.in_successful_contract_call) {
+                let (indexed, decoded) = index_event(event)?;
+                if !decoded {
+                    run.undecoded += 1;
+                }
+                if event_db.store_event(&indexed, &network).await? {
+                    run.stored += 1;
+                    self.announce(&indexed);
+                }
+            }
+
+            let Some(next) = page.cursor else {
+                break;
+            };
+            self.db.update_ingestion_cursor(&task_name, &next).await?;
+            cursor = Some(next);
+            start_ledger = None;
+
+            if page.events.len() < self.config.page_size as usize {
+                break;
+            }
+        }
+
+        debug!(
+            "Indexed contract events: fetched={}, stored={}, undecoded={}",
+            run.fetched, run.stored, run.undecoded
+        );
+        Ok(run)
+    }
+
+    /// Fetch one page from `cursor`, or from `start_ledger` without one. A position RPC
+    /// rejects as out of range is replaced, with a warning: a cursor by the configured
+    /// start ledger, and a start ledger by the oldest retained ledger.
+    async fn fetch_page(
+        &self,
+        filters: &[RpcEventFilter],
+        cursor: &mut Option<String>,
+        start_ledger: &mut Option<u64>,
+    ) -> Result<GetEventsResult> {
+        loop {
+            let error = match self
+                .rpc_client
+                .fetch_contract_events(
+                    *start_ledger,
+                    cursor.as_deref(),
+                    filters,
+                    self.config.page_size,
+                )
+                .await
+            {
+                Ok(page) => return Ok(page),
+                Err(error) => error,
+            };
+            if !is_out_of_range(&error) {
+                return Err(error).context("Failed to fetch contract events");
+            }
+
+            let oldest = self.oldest_ledger().await?;
+            let restart = if cursor.take().is_some() {
+                self.config
+                    .start_ledger
+                    .filter(|ledger| *ledger >= oldest)
+                    .unwrap_or(oldest)
+            } else if *start_ledger != Some(oldest) {
+                oldest
+            } else {
+                return Err(error).context("Failed to fetch contract events");
+            };
+            warn!(
+                "RPC rejected the contract event position as out of range ({}), restarting from ledger {}",
+                error, restart
+            );
+            *start_ledger = Some(restart);
+        }
+    }
+
+    async fn oldest_ledger(&self) -> Result<u64> {
+        Ok(self
+            .rpc_client
+            .check_health()
+            .await
+            .context("Failed to read the oldest retained ledger")?
+            .oldest_ledger)
+    }
+
+    /// Tell WebSocket clients about a newly stored snapshot submission
+    fn announce(&self, indexed: &IndexedEvent) {
+        let (Some(ws_state), DecodedEvent::SnapshotSubmitted(snapshot)) =
+            (&self.ws_state, &indexed.event)
+        else {
+            return;
+        };
+        ws_state.broadcast(WsMessage::SnapshotUpdate {
+            snapshot_id: indexed.id.clone(),
+            epoch: snapshot.epoch as i64,
+            timestamp: indexed.closed_at.to_rfc3339(),
+            hash: snapshot.hash.clone(),
+        });
+    }
+}
+
+/// Decode an RPC event. Events a decoder rejects are kept as their raw rendering so
+/// the cursor can move past them; the flag reports whether decoding succeeded.
+fn index_event(event: &RpcContractEvent) -> Result<(IndexedEvent, bool)> {
+    let closed_at = DateTime::parse_from_rfc3339(&event.ledger_closed_at)
+        .with_context(|| format!("Invalid close time on event {}", event.id))?
+        .with_timezone(&Utc);
+
+    let (decoded, ok) = match decode_event(&event.topic, &event.value) {
+        Ok(decoded) => (decoded, true),
+        Err(e) => {
+            warn!("Failed to decode contract event {}: {:#}", event.id, e);
+            let render = |encoded: &String| {
+                decode_sc_val(encoded)
+                    .map(|val| sc_val_to_json(&val))
+                    .unwrap_or_else(|_| serde_json::Value::String(encoded.clone()))
+            };
+            (
+                DecodedEvent::Other {
+                    topics: event.topic.iter().map(render).collect(),
+                    value: render(&event.value),
+                },
+                false,
+            )
+        }
+    };
+
+    Ok((
+        IndexedEvent {
+            id: event.id.clone(),
+            contract_id: event.contract_id.clone(),
+            ledger_sequence: event.ledger,
+            transaction_hash: event.tx_hash.clone(),
+            closed_at,
+            event: decoded,
+        },
+        ok,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contract() -> String {
+        stellar_strkey::Contract([7u8; 32]).to_string()
+    }
+
+    async fn indexer(config: ContractEventIndexerConfig) -> ContractEventIndexer {
+        let pool = crate::test_support::migrated_pool().await;
+
+        ContractEventIndexer::new(
+            Arc::new(Database::new(pool.into())),
+            Arc::new(StellarRpcClient::new_with_defaults(true)),
+            config,
+        )
+    }
+
+    #[test]
+    fn test_filters_chunk_contracts_and_encode_topics() {
+        let config = ContractEventIndexerConfig {
+            contract_ids: (0..7).map(|i| format!("C{}", i)).collect(),
+            topics: vec![vec!["VOTE_CST".to_string(), "*".to_string()]],
+            ..Default::default()
+        };
+        let filters = config.filters().unwrap();
+        assert_eq!(filters.len(), 2);
+        assert_eq!(filters[0].contract_ids.len(), 5);
+        assert_eq!(filters[1].contract_ids.len(), 2);
+        assert_eq!(filters[0].topics[0][0], symbol_topic("VOTE_CST").unwrap());
+        assert_eq!(filters[0].topics[0][1], "*");
+
+        let too_many = ContractEventIndexerConfig {
+            contract_ids: (0..26).map(|i| format!("C{}", i)).collect(),
+            ..Default::default()
+        };
+        assert!(too_many.filters().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_once_stores_typed_events_and_resumes_from_cursor() {
+        let indexer = indexer(ContractEventIndexerConfig {
+            contract_ids: vec![contract()],
+            page_size: 2,
+            ..Default::default()
+        })
+        .await;
+
+        let run = indexer.run_once().await.unwrap();
+        assert_eq!(run.fetched, 3);
+        assert_eq!(run.stored, 3);
+        assert_eq!(run.undecoded, 0);
+
+        let contract = contract();
+        let event_db = indexer.db.contract_event_db();
+        let proposals = event_db
+            .list_proposal_events(Some(contract.as_str()), Some(1), 10)
+            .await
+            .unwrap();
+        assert_eq!(proposals.len(), 1);
+        assert_eq!(proposals[0].event_type, "created");
+
+        let votes = event_db.list_votes(None, Some(1), 10).await.unwrap();
+        assert_eq!(votes.len(), 1);
+        assert_eq!(votes[0].choice, "for");
+
+        let snapshots = event_db
+            .list_snapshot_submissions(Some(contract.as_str()), Some(7), 10)
+            .await
+            .unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].hash, "ab".repeat(32));
+
+        let rerun = indexer.run_once().await.unwrap();
+        assert_eq!(rerun.fetched, 0);
+        assert_eq!(rerun.stored, 0);
+        let task_name = cursor_task_name(&indexer.config.filters().unwrap()).unwrap();
+        assert_eq!(
+            indexer
+                .db
+                .get_ingestion_cursor(&task_name)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("2")
+        );
+    }
+
+    #[test]
+    fn test_cursor_task_name_follows_filters() {
+        let config = |topics: Vec<Vec<String>>| ContractEventIndexerConfig {
+            contract_ids: vec![contract()],
+            topics,
+            ..Default::default()
+        };
+        let name = |config: ContractEventIndexerConfig| {
+            cursor_task_name(&config.filters().unwrap()).unwrap()
+        };
+
+        let all = name(config(Vec::new()));
+        assert!(all.starts_with("soroban_events:"));
+        assert_eq!(all, name(config(Vec::new())));
+        assert_ne!(all, name(config(vec![vec!["VOTE_CST".to_string()]])));
+    }
+
+    #[tokio::test]
+    async fn test_run_once_restarts_when_cursor_is_out_of_range() {
+        let indexer = indexer(ContractEventIndexerConfig {
+            contract_ids: vec![contract()],
+            // Before the oldest retained ledger, so the restart uses the oldest one
+            start_ledger: Some(1),
+            ..Default::default()
+        })
+        .await;
+        let task_name = cursor_task_name(&indexer.config.filters().unwrap()).unwrap();
+        indexer
+            .db
+            .update_ingestion_cursor(&task_name, "0211569485234413568-0000000001")
+            .await
+            .unwrap();
+
+        let run = indexer.run_once().await.unwrap();
+        assert_eq!(run.fetched, 3);
+        assert_eq!(run.stored, 3);
+        assert_eq!(
+            indexer
+                .db
+                .get_ingestion_cursor(&task_name)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("2")
+        );
+    }
+}
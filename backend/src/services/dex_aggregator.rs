@@ -0,0 +1,794 @@
+//! Combined order book and liquidity pool depth per asset pair.
+//!
+//! The aggregator merges a pair's Horizon order book with the constant-product pools
+//! holding both assets into one depth curve: how much of the base asset can be sold or
+//! bought before the marginal price moves a given percentage away from the mid price,
+//! when the trade is spread across the book and every pool at once. Curves of the
+//! configured pairs and of the most traded pool pairs are snapshotted periodically.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::database::Database;
+use crate::models::corridor::Corridor;
+use crate::rpc::{Asset, HorizonLiquidityPool, OrderBook, StellarRpcClient};
+use crate::services::price_feed::PriceFeedClient;
+
+/// Price moves, in percent, the depth curve is evaluated at
+pub const SLIPPAGE_LEVELS_PCT: [f64; 4] = [0.1, 0.5, 1.0, 2.0];
+
+#[derive(Debug, Clone)]
+pub struct DexAggregatorConfig {
+    /// Time between snapshots
+    pub snapshot_interval: std::time::Duration,
+    /// Pairs snapshotted every run, as `(base, counter)` asset ids
+    pub pairs: Vec<(String, String)>,
+    /// Pairs snapshotted per run. Pool pairs with the most volume over the last day
+    /// fill the places left after the configured pairs.
+    pub max_pairs: usize,
+    /// Snapshots older than this are deleted
+    pub retention: Duration,
+    /// Price levels loaded per side of the order book
+    pub book_depth: u32,
+}
+
+impl Default for DexAggregatorConfig {
+    fn default() -> Self {
+        Self {
+            snapshot_interval: std::time::Duration::from_secs(300),
+            pairs: Vec::new(),
+            max_pairs: 20,
+            retention: Duration::days(30),
+            book_depth: 200,
+        }
+    }
+}
+
+impl DexAggregatorConfig {
+    /// Reads `DEX_SNAPSHOT_INTERVAL_SECONDS`, `DEX_SNAPSHOT_PAIRS`,
+    /// `DEX_SNAPSHOT_MAX_PAIRS` and `DEX_SNAPSHOT_RETENTION_DAYS`, falling back to the
+    /// defaults.
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Some(seconds) = env_parse::<u64>("DEX_SNAPSHOT_INTERVAL_SECONDS").filter(|s| *s > 0)
+        {
+            config.snapshot_interval = std::time::Duration::from_secs(seconds);
+        }
+        if let Ok(value) = std::env::var("DEX_SNAPSHOT_PAIRS") {
+            config.pairs = parse_pairs(&value);
+        }
+        if let Some(pairs) = env_parse::<usize>("DEX_SNAPSHOT_MAX_PAIRS").filter(|p| *p > 0) {
+            config.max_pairs = pairs;
+        }
+        if let Some(days) = env_parse::<i64>("DEX_SNAPSHOT_RETENTION_DAYS").filter(|d| *d > 0) {
+            config.retention = Duration::days(days);
+        }
+        config
+    }
+}
+
+fn env_parse<T: std::str::FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok().and_then(|v| v.parse().ok())
+}
+
+/// Parse comma-separated `BASE/COUNTER` pairs of asset ids, skipping invalid entries
+fn parse_pairs(value: &str) -> Vec<(String, String)> {
+    value
+        .split(',')
+        .filter(|entry| !entry.trim().is_empty())
+        .filter_map(|entry| {
+            let pair = entry
+                .split_once('/')
+                .map(|(base, counter)| (base.trim(), counter.trim()))
+                .filter(|(base, counter)| {
+                    base != counter
+                        && Asset::from_id(base).is_some()
+                        && Asset::from_id(counter).is_some()
+                });
+            if pair.is_none() {
+                tracing::warn!("Ignoring invalid DEX snapshot pair: {}", entry.trim());
+            }
+            pair.map(|(base, counter)| (base.to_string(), counter.to_string()))
+        })
+        .collect()
+}
+
+/// Offers at one price, in counter units per base unit, for `amount` base units
+#[derive(Debug, Clone, PartialEq)]
+pub struct DepthLevel {
+    pub price: f64,
+    pub amount: f64,
+}
+
+/// Reserves of a constant-product pool holding the base and counter assets
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolReserves {
+    pub pool_id: String,
+    pub reserve_base: f64,
+    pub reserve_counter: f64,
+    pub fee_bp: u32,
+}
+
+impl PoolReserves {
+    /// Share of the amount paid in that reaches the pool after its fee
+    fn retained(&self) -> f64 {
+        1.0 - f64::from(self.fee_bp) / 10_000.0
+    }
+
+    /// Counter units received per base unit for the first base unit sold
+    fn best_bid(&self) -> f64 {
+        self.retained() * self.reserve_counter / self.reserve_base
+    }
+
+    /// Counter units paid per base unit for the first base unit bought
+    fn best_ask(&self) -> f64 {
+        self.reserve_counter / (self.retained() * self.reserve_base)
+    }
+
+    /// Base units that can be sold before the marginal price falls to `limit`. Selling
+    /// `d` moves the base reserve to `x' = x + γd`, where the marginal price is `γk/x'²`.
+    fn sell_depth(&self, limit: f64) -> f64 {
+        let k = self.reserve_base * self.reserve_counter;
+        let gamma = self.retained();
+        let max_reserve = (gamma * k / limit).sqrt();
+        ((max_reserve - self.reserve_base) / gamma).max(0.0)
+    }
+
+    /// Base units that can be bought before the marginal price rises to `limit`. Buying
+    /// moves the base reserve down to `x'`, where the marginal price is `k/(γx'²)`.
+    fn buy_depth(&self, limit: f64) -> f64 {
+        let k = self.reserve_base * self.reserve_counter;
+        let min_reserve = (k / (self.retained() * limit)).sqrt();
+        (self.reserve_base - min_reserve).max(0.0)
+    }
+}
+
+/// Depth of a pair within one price move
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DepthPoint {
+    pub slippage_pct: f64,
+    /// Base units that can be sold before the price falls `slippage_pct` below mid
+    pub sell_depth: f64,
+    /// Base units that can be bought before the price rises `slippage_pct` above mid
+    pub buy_depth: f64,
+    /// Part of `sell_depth + buy_depth` provided by pools
+    pub pool_depth: f64,
+    /// Both sides in USD, when the base asset has a price
+    pub depth_usd: Option<f64>,
+}
+
+/// The order book and pools of one pair at one moment
+#[derive(Debug, Clone, Default)]
+pub struct MarketDepth {
+    /// `native` or `CODE:ISSUER`
+    pub base_asset: String,
+    pub counter_asset: String,
+    /// Best price first
+    pub bids: Vec<DepthLevel>,
+    /// Best price first
+    pub asks: Vec<DepthLevel>,
+    pub pools: Vec<PoolReserves>,
+    pub base_usd_price: Option<f64>,
+}
+
+impl MarketDepth {
+    /// Build from a Horizon order book with `base_asset` as its base (selling) asset
+    /// and the pools holding both assets. Horizon quotes bid amounts in the counter
+    /// asset; they are converted to base units here.
+    pub fn from_horizon(
+        base_asset: &str,
+        counter_asset: &str,
+        book: Option<&OrderBook>,
+        pools: &[HorizonLiquidityPool],
+    ) -> Self {
+        let levels = |entries: &[crate::rpc::OrderBookEntry], counter_amounts: bool| {
+            entries
+                .iter()
+                .filter_map(|entry| {
+                    let price = if entry.price_r.d > 0 {
+                        entry.price_r.n as f64 / entry.price_r.d as f64
+                    } else {
+                        entry.price.parse().ok()?
+                    };
+                    let amount: f64 = entry.amount.parse().ok()?;
+                    let amount = if counter_amounts {
+                        amount / price
+                    } else {
+                        amount
+                    };
+                    (price > 0.0 && amount > 0.0 && amount.is_finite())
+                        .then_some(DepthLevel { price, amount })
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let pools = pools
+            .iter()
+            .filter(|pool| pool.pool_type == "constant_product")
+            .filter_map(|pool| {
+                let reserve = |asset: &str| {
+                    pool.reserves
+                        .iter()
+                        .find(|r| r.asset == asset)
+                        .and_then(|r| r.amount.parse::<f64>().ok())
+                        .filter(|amount| *amount > 0.0)
+                };
+                Some(PoolReserves {
+                    pool_id: pool.id.clone(),
+                    reserve_base: reserve(base_asset)?,
+                    reserve_counter: reserve(counter_asset)?,
+                    fee_bp: pool.fee_bp,
+                })
+            })
+            .collect();
+
+        Self {
+            base_asset: base_asset.to_string(),
+            counter_asset: counter_asset.to_string(),
+            bids: book.map(|b| levels(&b.bids, true)).unwrap_or_default(),
+            asks: book.map(|b| levels(&b.asks, false)).unwrap_or_default(),
+            pools,
+            base_usd_price: None,
+        }
+    }
+
+    /// Best price a seller of the base asset gets, across the book and pools
+    pub fn best_bid(&self) -> Option<f64> {
+        self.bids
+            .first()
+            .map(|level| level.price)
+            .into_iter()
+            .chain(self.pools.iter().map(PoolReserves::best_bid))
+            .max_by(f64::total_cmp)
+    }
+
+    /// Best price a buyer of the base asset pays, across the book and pools
+    pub fn best_ask(&self) -> Option<f64> {
+        self.asks
+            .first()
+            .map(|level| level.price)
+            .into_iter()
+            .chain(self.pools.iter().map(PoolReserves::best_ask))
+            .min_by(f64::total_cmp)
+    }
+
+    /// Midpoint of the best bid and ask, or the only side quoted
+    pub fn mid_price(&self) -> Option<f64> {
+        match (self.best_bid(), self.best_ask()) {
+            (Some(bid), Some(ask)) => Some((bid + ask) / 2.0),
+            (bid, ask) => bid.or(ask),
+        }
+    }
+
+    pub fn spread_bps(&self) -> Option<f64> {
+        let (bid, ask) = (self.best_bid()?, self.best_ask()?);
+        Some((ask - bid) / ((bid + ask) / 2.0) * 10_000.0)
+    }
+
+    /// Depth within `slippage_pct` of the mid price, or `None` without any liquidity
+    pub fn depth_at(&self, slippage_pct: f64) -> Option<DepthPoint> {
+        let mid = self.mid_price()?;
+        let sell_limit = mid * (1.0 - slippage_pct / 100.0);
+        let buy_limit = mid * (1.0 + slippage_pct / 100.0);
+
+        let book_sell: f64 = self
+            .bids
+            .iter()
+            .take_while(|level| level.price >= sell_limit)
+            .map(|level| level.amount)
+            .sum();
+        let book_buy: f64 = self
+            .asks
+            .iter()
+            .take_while(|level| level.price <= buy_limit)
+            .map(|level| level.amount)
+            .sum();
+        let pool_sell: f64 = self.pools.iter().map(|p| p.sell_depth(sell_limit)).sum();
+        let pool_buy: f64 = self.pools.iter().map(|p| p.buy_depth(buy_limit)).sum();
+
+        let sell_depth = book_sell + pool_sell;
+        let buy_depth = book_buy + pool_buy;
+        Some(DepthPoint {
+            slippage_pct,
+            sell_depth,
+            buy_depth,
+            pool_depth: pool_sell + pool_buy,
+            depth_usd: self
+                .base_usd_price
+                .map(|price| (sell_depth + buy_depth) * price),
+        })
+    }
+
+    /// Depth at each of [`SLIPPAGE_LEVELS_PCT`]
+    pub fn curve(&self) -> Vec<DepthPoint> {
+        SLIPPAGE_LEVELS_PCT
+            .iter()
+            .filter_map(|pct| self.depth_at(*pct))
+            .collect()
+    }
+}
+
+/// A stored depth curve of one corridor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquiditySnapshot {
+    pub id: String,
+    /// Normalized corridor key; its first asset is the base
+    pub corridor_key: String,
+    pub base_asset: String,
+    pub counter_asset: String,
+    pub mid_price: f64,
+    pub spread_bps: Option<f64>,
+    pub base_usd_price: Option<f64>,
+    pub book_levels: i64,
+    pub pool_count: i64,
+    pub curve: Vec<DepthPoint>,
+    pub captured_at: DateTime<Utc>,
+}
+
+impl LiquiditySnapshot {
+    pub fn from_market(
+        corridor_key: &str,
+        market: &MarketDepth,
+        captured_at: DateTime<Utc>,
+    ) -> Option<Self> {
+        Some(Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            corridor_key: corridor_key.to_string(),
+            base_asset: market.base_asset.clone(),
+            counter_asset: market.counter_asset.clone(),
+            mid_price: market.mid_price()?,
+            spread_bps: market.spread_bps(),
+            base_usd_price: market.base_usd_price,
+            book_levels: (market.bids.len() + market.asks.len()) as i64,
+            pool_count: market.pools.len() as i64,
+            curve: market.curve(),
+            captured_at,
+        })
+    }
+
+    /// USD depth at one of [`SLIPPAGE_LEVELS_PCT`]
+    pub fn depth_usd_at(&self, slippage_pct: f64) -> Option<f64> {
+        self.curve
+            .iter()
+            .find(|point| (point.slippage_pct - slippage_pct).abs() < 1e-9)
+            .and_then(|point| point.depth_usd)
+    }
+}
+
+/// Asset id of a corridor asset, as used by Horizon pool reserves
+pub fn corridor_asset_id(code: &str, issuer: &str) -> String {
+    if issuer.eq_ignore_ascii_case("native") || (code == "XLM" && issuer.is_empty()) {
+        "native".to_string()
+    } else {
+        format!("{code}:{issuer}")
+    }
+}
+
+/// Corridor between two `native` or `CODE:ISSUER` asset ids
+fn corridor_for_pair(base: &str, counter: &str) -> Corridor {
+    let split = |id: &str| match id.split_once(':') {
+        Some((code, issuer)) => (code.to_string(), issuer.to_string()),
+        None => ("XLM".to_string(), "native".to_string()),
+    };
+    let (base_code, base_issuer) = split(base);
+    let (counter_code, counter_issuer) = split(counter);
+    Corridor::new(base_code, base_issuer, counter_code, counter_issuer)
+}
+
+pub struct DexAggregator {
+    db: Arc<Database>,
+    rpc_client: Arc<StellarRpcClient>,
+    price_feed: Arc<PriceFeedClient>,
+    config: DexAggregatorConfig,
+}
+
+impl DexAggregator {
+    pub fn new(
+        db: Arc<Database>,
+        rpc_client: Arc<StellarRpcClient>,
+        price_feed: Arc<PriceFeedClient>,
+        config: DexAggregatorConfig,
+    ) -> Self {
+        Self {
+            db,
+            rpc_client,
+            price_feed,
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &DexAggregatorConfig {
+        &self.config
+    }
+
+    /// Current book and pools of a pair, with the base asset priced in USD when the
+    /// price feed covers either asset
+    pub async fn market_depth(&self, base_asset: &str, counter_asset: &str) -> Result<MarketDepth> {
+        let base =
+            Asset::from_id(base_asset).with_context(|| format!("Invalid asset: {}", base_asset))?;
+        let counter = Asset::from_id(counter_asset)
+            .with_context(|| format!("Invalid asset: {}", counter_asset))?;
+
+        let reserves = [base.clone(), counter.clone()];
+        let (book, pools) = tokio::join!(
+            self.rpc_client
+                .fetch_order_book(&base, &counter, self.config.book_depth),
+            self.rpc_client
+                .fetch_liquidity_pools_for_reserves(&reserves, 200),
+        );
+        // A pair may trade only in pools or only on the book
+        let book = book
+            .map_err(|e| {
+                tracing::debug!("No order book for {}/{}: {}", base_asset, counter_asset, e)
+            })
+            .ok();
+        let pools = pools.unwrap_or_else(|e| {
+            tracing::debug!("No pools for {}/{}: {}", base_asset, counter_asset, e);
+            Vec::new()
+        });
+
+        let mut market =
+            MarketDepth::from_horizon(base_asset, counter_asset, book.as_ref(), &pools);
+        market.base_usd_price = match self.usd_price(base_asset).await {
+            Some(price) => Some(price),
+            None => match (self.usd_price(counter_asset).await, market.mid_price()) {
+                (Some(counter_price), Some(mid)) => Some(counter_price * mid),
+                _ => None,
+            },
+        };
+        Ok(market)
+    }
+
+    async fn usd_price(&self, asset: &str) -> Option<f64> {
+        let feed_id = if asset == "native" {
+            "XLM:native"
+        } else {
+            asset
+        };
+        self.price_feed
+            .get_price(feed_id)
+            .await
+            .ok()
+            .filter(|price| *price > 0.0 && price.is_finite())
+    }
+
+    /// Take and store a snapshot of one corridor. Returns `None` for same-asset
+    /// corridors and pairs without any liquidity.
+    pub async fn snapshot_corridor(
+        &self,
+        corridor: &Corridor,
+        now: DateTime<Utc>,
+    ) -> Result<Option<LiquiditySnapshot>> {
+        let base = corridor_asset_id(&corridor.asset_a_code, &corridor.asset_a_issuer);
+        let counter = corridor_asset_id(&corridor.asset_b_code, &corridor.asset_b_issuer);
+        if base == counter {
+            return Ok(None);
+        }
+
+        let market = self.market_depth(&base, &counter).await?;
+        let Some(snapshot) =
+            LiquiditySnapshot::from_market(&corridor.to_string_key(), &market, now)
+        else {
+            return Ok(None);
+        };
+        self.db.dex_db().insert_snapshot(&snapshot).await?;
+        Ok(Some(snapshot))
+    }
+
+    /// Corridors to snapshot: the configured pairs, then the pool pairs with the most
+    /// volume over the last day, up to `max_pairs`.
+    ///
+    /// Corridor rollups are not used for this: payments are recorded against
+    /// same-asset corridors, which have no market to measure.
+    pub async fn snapshot_pairs(&self) -> Result<Vec<Corridor>> {
+        let traded = self
+            .db
+            .dex_db()
+            .fetch_traded_pairs(self.config.max_pairs as i64)
+            .await?;
+
+        let mut corridors: Vec<Corridor> = Vec::new();
+        let configured = self.config.pairs.iter().map(|pair| (pair, true));
+        let traded = traded.iter().map(|pair| (pair, false));
+        for ((base, counter), is_configured) in configured.chain(traded) {
+            if !is_configured && corridors.len() >= self.config.max_pairs {
+                break;
+            }
+            let corridor = corridor_for_pair(base, counter);
+            if !corridors.contains(&corridor) {
+                corridors.push(corridor);
+            }
+        }
+        Ok(corridors)
+    }
+
+    /// Snapshot the configured and most traded pairs and prune old snapshots. Returns
+    /// the number of snapshots stored.
+    pub async fn take_snapshots(&self, now: DateTime<Utc>) -> Result<usize> {
+        let mut stored = 0;
+        for corridor in self.snapshot_pairs().await? {
+            match self.snapshot_corridor(&corridor, now).await {
+                Ok(Some(_)) => stored += 1,
+                Ok(None) => {}
+                Err(e) => tracing::warn!(
+                    "DEX liquidity snapshot failed for {}: {:#}",
+                    corridor.to_string_key(),
+                    e
+                ),
+            }
+        }
+
+        self.db
+            .dex_db()
+            .delete_snapshots_before(now - self.config.retention)
+            .await?;
+        Ok(stored)
+    }
+
+    /// Latest snapshot of a corridor no older than `max_age`, taking a fresh one if
+    /// there is none
+    pub async fn corridor_depth(
+        &self,
+        corridor: &Corridor,
+        max_age: Duration,
+    ) -> Result<Option<LiquiditySnapshot>> {
+        let now = Utc::now();
+        if let Some(snapshot) = self
+            .db
+            .dex_db()
+            .latest_snapshot(&corridor.to_string_key(), now - max_age)
+            .await?
+        {
+            return Ok(Some(snapshot));
+        }
+        self.snapshot_corridor(corridor, now).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(reserve_base: f64, reserve_counter: f64) -> PoolReserves {
+        PoolReserves {
+            pool_id: "pool".to_string(),
+            reserve_base,
+            reserve_counter,
+            fee_bp: 30,
+        }
+    }
+
+    fn market(bids: &[(f64, f64)], asks: &[(f64, f64)], pools: Vec<PoolReserves>) -> MarketDepth {
+        let levels = |levels: &[(f64, f64)]| {
+            levels
+                .iter()
+                .map(|&(price, amount)| DepthLevel { price, amount })
+                .collect()
+        };
+        MarketDepth {
+            base_asset: "USDC:GA".to_string(),
+            counter_asset: "native".to_string(),
+            bids: levels(bids),
+            asks: levels(asks),
+            pools,
+            base_usd_price: Some(1.0),
+        }
+    }
+
+    #[test]
+    fn test_book_depth_counts_levels_within_slippage() {
+        let market = market(
+            &[(0.99, 500.0), (0.98, 1_000.0), (0.95, 2_000.0)],
+            &[(1.01, 400.0), (1.02, 800.0), (1.06, 1_500.0)],
+            Vec::new(),
+        );
+        assert!((market.mid_price().unwrap() - 1.0).abs() < 1e-12);
+        assert!((market.spread_bps().unwrap() - 200.0).abs() < 1e-6);
+
+        let one = market.depth_at(1.0).unwrap();
+        assert_eq!((one.sell_depth, one.buy_depth), (500.0, 400.0));
+        let two = market.depth_at(2.0).unwrap();
+        assert_eq!((two.sell_depth, two.buy_depth), (1_500.0, 1_200.0));
+        assert_eq!(two.depth_usd, Some(2_700.0));
+        assert_eq!(two.pool_depth, 0.0);
+    }
+
+    #[test]
+    fn test_pool_depth_stops_at_marginal_price() {
+        let pool = pool(1_000_000.0, 1_000_000.0);
+        let limit = 0.99;
+        let sold = pool.sell_depth(limit);
+        // Marginal price after selling `sold` equals the limit
+        let x = pool.reserve_base + pool.retained() * sold;
+        let k = pool.reserve_base * pool.reserve_counter;
+        assert!((pool.retained() * k / (x * x) - limit).abs() < 1e-9);
+
+        let bought = pool.buy_depth(1.01);
+        let x = pool.reserve_base - bought;
+        assert!((k / (pool.retained() * x * x) - 1.01).abs() < 1e-9);
+
+        // The fee alone moves the price 0.3%, so nothing trades within 0.1%
+        assert_eq!(pool.sell_depth(0.999), 0.0);
+    }
+
+    #[test]
+    fn test_curve_merges_book_and_pools() {
+        let book_only = market(&[(0.995, 1_000.0)], &[(1.005, 1_000.0)], Vec::new());
+        let merged = market(
+            &[(0.995, 1_000.0)],
+            &[(1.005, 1_000.0)],
+            vec![pool(500_000.0, 500_000.0)],
+        );
+
+        let curve = merged.curve();
+        assert_eq!(curve.len(), SLIPPAGE_LEVELS_PCT.len());
+        for window in curve.windows(2) {
+            assert!(window[1].sell_depth >= window[0].sell_depth);
+            assert!(window[1].buy_depth >= window[0].buy_depth);
+        }
+
+        let book = book_only.depth_at(1.0).unwrap();
+        let both = merged.depth_at(1.0).unwrap();
+        assert!(both.pool_depth > 0.0);
+        assert!(
+            (both.sell_depth + both.buy_depth - book.sell_depth - book.buy_depth - both.pool_depth)
+                .abs()
+                < 1e-6
+        );
+        assert!(market(&[], &[], Vec::new()).depth_at(1.0).is_none());
+    }
+
+    #[test]
+    fn test_from_horizon_converts_bid_amounts_to_base() {
+        use crate::rpc::{HorizonPoolReserve, OrderBookEntry, Price};
+
+        let entry = |price: &str, amount: &str, n: i64, d: i64| OrderBookEntry {
+            price: price.to_string(),
+            amount: amount.to_string(),
+            price_r: Price { n, d },
+        };
+        let book = OrderBook {
+            bids: vec![entry("0.5", "100", 1, 2)],
+            asks: vec![entry("0.6", "100", 3, 5)],
+            base: Asset::from_id("USDC:GA").unwrap(),
+            counter: Asset::from_id("native").unwrap(),
+        };
+        let pools = vec![HorizonLiquidityPool {
+            id: "p1".to_string(),
+            fee_bp: 30,
+            pool_type: "constant_product".to_string(),
+            total_trustlines: 1,
+            total_shares: "1".to_string(),
+            reserves: vec![
+                HorizonPoolReserve {
+                    asset: "native".to_string(),
+                    amount: "2000".to_string(),
+                },
+                HorizonPoolReserve {
+                    asset: "USDC:GA".to_string(),
+                    amount: "1000".to_string(),
+                },
+            ],
+            paging_token: None,
+        }];
+
+        let market = MarketDepth::from_horizon("USDC:GA", "native", Some(&book), &pools);
+        assert_eq!(market.bids[0].amount, 200.0);
+        assert_eq!(market.asks[0].amount, 100.0);
+        assert_eq!(market.pools[0].reserve_base, 1_000.0);
+        assert_eq!(market.pools[0].reserve_counter, 2_000.0);
+    }
+
+    #[test]
+    fn test_corridor_asset_id() {
+        assert_eq!(corridor_asset_id("XLM", "native"), "native");
+        assert_eq!(corridor_asset_id("USDC", "GA"), "USDC:GA");
+    }
+
+    #[test]
+    fn test_parse_pairs_skips_invalid_entries() {
+        let pairs = parse_pairs("USDC:GA/native, EURC:GB / USDC:GA,native/native,USDC,");
+        assert_eq!(
+            pairs,
+            vec![
+                ("USDC:GA".to_string(), "native".to_string()),
+                ("EURC:GB".to_string(), "USDC:GA".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_pairs_ignore_same_asset_rollups() {
+        use crate::services::price_feed::PriceFeedConfig;
+
+        let pool = crate::test_support::migrated_pool().await;
+
+        // Hourly rollups as the aggregation job writes them: payments are grouped by
+        // their own asset, so every corridor has the same asset on both sides
+        for (id, asset, issuer, volume) in [
+            ("h1", "USDC", "GA", 90_000.0),
+            ("h2", "XLM", "native", 40_000.0),
+            ("h3", "EURC", "GB", 10_000.0),
+        ] {
+            sqlx::query(
+                "INSERT INTO corridor_metrics_hourly (
+                    id, corridor_key, asset_a_code, asset_a_issuer, asset_b_code,
+                    asset_b_issuer, hour_bucket, total_transactions, successful_transactions,
+                    volume_usd
+                ) VALUES ($1, $2, $3, $4, $3, $4, $5, 10, 10, $6)",
+            )
+            .bind(id)
+            .bind(format!("{asset}:{issuer}->{asset}:{issuer}"))
+            .bind(asset)
+            .bind(issuer)
+            .bind((Utc::now() - Duration::hours(1)).to_rfc3339())
+            .bind(volume)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        for (id, code_a, issuer_a, code_b, issuer_b, volume, trades) in [
+            ("p1", "XLM", None, "USDC", Some("GA"), 5_000.0, 40),
+            ("p2", "EURC", Some("GB"), "USDC", Some("GA"), 800.0, 12),
+            ("p3", "AQUA", Some("GC"), "XLM", None, 0.0, 0),
+        ] {
+            sqlx::query(
+                "INSERT INTO liquidity_pools (
+                    pool_id, reserve_a_asset_code, reserve_a_asset_issuer,
+                    reserve_b_asset_code, reserve_b_asset_issuer, volume_24h_usd,
+                    trade_count_24h
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            )
+            .bind(id)
+            .bind(code_a)
+            .bind(issuer_a)
+            .bind(code_b)
+            .bind(issuer_b)
+            .bind(volume)
+            .bind(trades)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let aggregator = |pairs: Vec<(String, String)>, max_pairs: usize| {
+            DexAggregator::new(
+                Arc::new(Database::new(pool.clone().into())),
+                Arc::new(StellarRpcClient::new_with_defaults(true)),
+                Arc::new(PriceFeedClient::new(
+                    PriceFeedConfig::default(),
+                    std::collections::HashMap::new(),
+                )),
+                DexAggregatorConfig {
+                    pairs,
+                    max_pairs,
+                    ..Default::default()
+                },
+            )
+        };
+        let keys = |corridors: Vec<Corridor>| {
+            corridors
+                .iter()
+                .map(Corridor::to_string_key)
+                .collect::<Vec<_>>()
+        };
+
+        let traded = aggregator(Vec::new(), 20).snapshot_pairs().await.unwrap();
+        assert_eq!(
+            keys(traded),
+            vec!["USDC:GA->XLM:native", "EURC:GB->USDC:GA"]
+        );
+
+        let configured = aggregator(vec![("native".to_string(), "EURC:GB".to_string())], 2)
+            .snapshot_pairs()
+            .await
+            .unwrap();
+        assert_eq!(
+            keys(configured),
+            vec!["EURC:GB->XLM:native", "USDC:GA->XLM:native"]
+        );
+    }
+}
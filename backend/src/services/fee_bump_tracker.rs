@@ -1,17 +1,20 @@
+use crate::database::DbPool;
+use crate::with_pool;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
-use sqlx::{Pool, Sqlite};
+
 use tracing::{info, warn};
 
+use crate::ingestion::xdr::DecodedLedger;
 use crate::models::{FeeBumpStats, FeeBumpTransaction};
 use crate::rpc::HorizonTransaction; // Changed from StellarRpcClient as we process data structs
 
 pub struct FeeBumpTrackerService {
-    pool: Pool<Sqlite>,
+    pool: DbPool,
 }
 
 impl FeeBumpTrackerService {
-    pub fn new(pool: Pool<Sqlite>) -> Self {
+    pub fn new(pool: DbPool) -> Self {
         Self { pool }
     }
 
@@ -77,64 +80,109 @@ impl FeeBumpTrackerService {
         Ok(count)
     }
 
+    /// Persist fee bump transactions from a ledger decoded from its close meta
+    pub async fn process_decoded_ledger(&self, ledger: &DecodedLedger) -> Result<u64> {
+        let mut count = 0;
+
+        for tx in &ledger.transactions {
+            let Some(fee_bump) = &tx.fee_bump else {
+                continue;
+            };
+
+            let fee_bump_tx = FeeBumpTransaction {
+                transaction_hash: tx.hash.clone(),
+                ledger_sequence: ledger.sequence as i64,
+                fee_source: fee_bump.fee_source.clone(),
+                fee_charged: tx.fee_charged,
+                max_fee: tx.max_fee,
+                inner_transaction_hash: fee_bump.inner_transaction_hash.clone(),
+                inner_max_fee: fee_bump.inner_max_fee,
+                signatures_count: fee_bump.signatures_count,
+                created_at: ledger.close_time,
+            };
+
+            if let Err(e) = self.persist_fee_bump(&fee_bump_tx).await {
+                warn!("Failed to persist fee bump transaction {}: {}", tx.hash, e);
+            } else {
+                count += 1;
+            }
+        }
+
+        if count > 0 {
+            info!(
+                "Processed {} fee bump transactions from ledger {}",
+                count, ledger.sequence
+            );
+        }
+
+        Ok(count)
+    }
+
     /// Persist a single fee bump transaction
     async fn persist_fee_bump(&self, tx: &FeeBumpTransaction) -> Result<()> {
-        sqlx::query(
-            r#"
-            INSERT INTO fee_bump_transactions (
-                transaction_hash, ledger_sequence, fee_source, fee_charged, max_fee,
-                inner_transaction_hash, inner_max_fee, signatures_count, created_at
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO fee_bump_transactions (
+                    transaction_hash, ledger_sequence, fee_source, fee_charged, max_fee,
+                    inner_transaction_hash, inner_max_fee, signatures_count, created_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                ON CONFLICT (transaction_hash) DO NOTHING
+                "#,
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-            ON CONFLICT (transaction_hash) DO NOTHING
-            "#,
-        )
-        .bind(&tx.transaction_hash)
-        .bind(tx.ledger_sequence)
-        .bind(&tx.fee_source)
-        .bind(tx.fee_charged)
-        .bind(tx.max_fee)
-        .bind(&tx.inner_transaction_hash)
-        .bind(tx.inner_max_fee)
-        .bind(tx.signatures_count)
-        .bind(tx.created_at)
-        .execute(&self.pool)
-        .await?;
+            .bind(&tx.transaction_hash)
+            .bind(tx.ledger_sequence)
+            .bind(&tx.fee_source)
+            .bind(tx.fee_charged)
+            .bind(tx.max_fee)
+            .bind(&tx.inner_transaction_hash)
+            .bind(tx.inner_max_fee)
+            .bind(tx.signatures_count)
+            .bind(tx.created_at)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
 
         Ok(())
     }
 
     /// Get recent fee bump transactions
     pub async fn get_recent_fee_bumps(&self, limit: i64) -> Result<Vec<FeeBumpTransaction>> {
-        let transactions = sqlx::query_as::<_, FeeBumpTransaction>(
-            r#"
-            SELECT * FROM fee_bump_transactions
-            ORDER BY created_at DESC
-            LIMIT $1
-            "#,
-        )
-        .bind(limit)
-        .fetch_all(&self.pool)
-        .await?;
+        let transactions = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, FeeBumpTransaction>(
+                r#"
+                SELECT * FROM fee_bump_transactions
+                ORDER BY created_at DESC
+                LIMIT $1
+                "#,
+            )
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+        })?;
 
         Ok(transactions)
     }
 
     /// Get fee bump statistics
     pub async fn get_fee_bump_stats(&self) -> Result<FeeBumpStats> {
-        let row: (i64, f64, i64, i64, i64) = sqlx::query_as(
-            r#"
-            SELECT 
-                COUNT(*) as total_count,
-                COALESCE(AVG(fee_charged), 0.0) as avg_fee,
-                COALESCE(MAX(fee_charged), 0) as max_fee,
-                COALESCE(MIN(fee_charged), 0) as min_fee,
-                COUNT(DISTINCT fee_source) as unique_sources
-            FROM fee_bump_transactions
-            "#,
-        )
-        .fetch_one(&self.pool)
-        .await?;
+        let row: (i64, f64, i64, i64, i64) = with_pool!(&self.pool, |pool| {
+            sqlx::query_as(
+                r#"
+                SELECT 
+                    COUNT(*) as total_count,
+                    COALESCE(CAST(AVG(fee_charged) AS DOUBLE PRECISION), 0.0) as avg_fee,
+                    COALESCE(MAX(fee_charged), 0) as max_fee,
+                    COALESCE(MIN(fee_charged), 0) as min_fee,
+                    COUNT(DISTINCT fee_source) as unique_sources
+                FROM fee_bump_transactions
+                "#,
+            )
+            .fetch_one(pool)
+            .await
+        })?;
 
         Ok(FeeBumpStats {
             total_fee_bumps: row.0,
@@ -0,0 +1,477 @@
+//! Volume and liquidity forecasts per corridor.
+//!
+//! Forecasts are fitted on demand from the corridor's hourly rollups with
+//! [`HoltWinters`]. Both metrics are modelled on a log scale, so forecasts stay
+//! non-negative and intervals are wider above the forecast than below it, as demand
+//! spikes are.
+//!
+//! Volume is a flow, so it is also forecast as a total over the horizon. Hourly errors
+//! are correlated and skewed, so the interval of the total is taken from simulated
+//! sample paths rather than by adding up hourly bounds. Liquidity depth is a level and
+//! has no total; its lowest expected hour is reported instead.
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::analytics::forecast::{season_length_for, HoltWinters, HoltWintersParams};
+use crate::db::rollups::RollupDb;
+use crate::services::rollup::{CorridorRollup, Resolution};
+
+/// Longest forecast served, in hours
+pub const MAX_HORIZON_HOURS: i64 = 7 * 24;
+/// Sample paths simulated for the interval of a forecast total
+const TOTAL_SIMULATION_PATHS: usize = 2_000;
+/// Fixed seed, so the same history always gives the same interval
+const TOTAL_SIMULATION_SEED: u64 = 0x5EED;
+
+#[derive(Debug, Clone)]
+pub struct ForecastConfig {
+    /// Hourly history the models are fitted on
+    pub history: Duration,
+    /// Coverage of the prediction intervals
+    pub confidence: f64,
+}
+
+impl Default for ForecastConfig {
+    fn default() -> Self {
+        Self {
+            history: Duration::days(28),
+            confidence: 0.95,
+        }
+    }
+}
+
+impl ForecastConfig {
+    /// Reads `FORECAST_HISTORY_DAYS`, falling back to the default.
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Some(days) = std::env::var("FORECAST_HISTORY_DAYS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .filter(|days| *days >= 2)
+        {
+            config.history = Duration::days(days);
+        }
+        config
+    }
+
+    /// Two-sided normal quantile for `confidence`
+    fn z_score(&self) -> f64 {
+        match self.confidence {
+            c if c >= 0.99 => 2.576,
+            c if c >= 0.95 => 1.96,
+            c if c >= 0.9 => 1.645,
+            _ => 1.282,
+        }
+    }
+}
+
+/// Forecast value for one future hour
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForecastPoint {
+    pub timestamp: DateTime<Utc>,
+    pub forecast: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+/// Total of a flow metric over the forecast horizon
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ForecastTotal {
+    /// Sum of the hourly forecasts
+    pub forecast: f64,
+    /// Bounds of the prediction interval of the total
+    pub lower: f64,
+    pub upper: f64,
+}
+
+/// Forecast of one metric
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricForecast {
+    /// Length of the seasonal cycle in hours (24 or 168)
+    pub season_length_hours: usize,
+    pub params: HoltWintersParams,
+    /// Hours of history the model was fitted on
+    pub observations: usize,
+    /// Hourly forecasts, soonest first
+    pub points: Vec<ForecastPoint>,
+    /// Total over the horizon; only set for flows such as volume
+    pub total: Option<ForecastTotal>,
+}
+
+impl MetricForecast {
+    /// Highest hourly upper bound, the demand to pre-position for
+    pub fn peak(&self) -> Option<&ForecastPoint> {
+        self.points
+            .iter()
+            .max_by(|a, b| a.upper.total_cmp(&b.upper))
+    }
+
+    /// Lowest hourly lower bound, the thinnest the metric is expected to get
+    pub fn trough(&self) -> Option<&ForecastPoint> {
+        self.points
+            .iter()
+            .min_by(|a, b| a.lower.total_cmp(&b.lower))
+    }
+}
+
+/// Volume and liquidity forecasts of one corridor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorridorForecast {
+    pub corridor_key: String,
+    pub generated_at: DateTime<Utc>,
+    /// Start of the first forecast hour, the current one, which is still being aggregated
+    pub forecast_start: DateTime<Utc>,
+    pub horizon_hours: i64,
+    /// Coverage of the `lower`/`upper` bounds
+    pub confidence: f64,
+    /// Hourly volume in USD; `None` without two days of history
+    pub volume_usd: Option<MetricForecast>,
+    /// Hourly liquidity depth in USD; `None` without two days of history
+    pub liquidity_depth_usd: Option<MetricForecast>,
+}
+
+/// Contiguous hourly volume and liquidity series from the first bucket up to `until`.
+/// Hours without payments had no volume; liquidity carries over from the previous hour.
+pub fn hourly_series(
+    buckets: &[CorridorRollup],
+    until: DateTime<Utc>,
+) -> Option<(DateTime<Utc>, Vec<f64>, Vec<f64>)> {
+    let first = buckets.iter().map(|b| b.bucket_start).min()?;
+    let last = buckets.iter().map(|b| b.bucket_start).max()?;
+    let hours = (until.max(last + Duration::hours(1)) - first).num_hours() as usize;
+
+    let mut volume = vec![0.0; hours];
+    let mut liquidity: Vec<Option<f64>> = vec![None; hours];
+    for bucket in buckets {
+        let index = (bucket.bucket_start - first).num_hours() as usize;
+        volume[index] += bucket.volume_usd;
+        liquidity[index] = Some(liquidity[index].unwrap_or(0.0) + bucket.liquidity_depth_usd);
+    }
+
+    let mut previous = 0.0;
+    let liquidity = liquidity
+        .into_iter()
+        .map(|value| {
+            previous = value.unwrap_or(previous);
+            previous
+        })
+        .collect();
+
+    Some((first, volume, liquidity))
+}
+
+/// Forecast `horizon` hours of a non-negative hourly series ending just before `start`
+pub fn forecast_series(
+    series: &[f64],
+    start: DateTime<Utc>,
+    horizon: usize,
+    z: f64,
+) -> Option<MetricForecast> {
+    let model = fit_log_series(series)?;
+    Some(metric_forecast(&model, start, horizon, z))
+}
+
+/// Like [`forecast_series`], for a flow whose total over the horizon is also forecast.
+/// The interval of the total holds `confidence` of the totals of simulated paths.
+pub fn forecast_flow_series(
+    series: &[f64],
+    start: DateTime<Utc>,
+    horizon: usize,
+    z: f64,
+    confidence: f64,
+) -> Option<MetricForecast> {
+    let model = fit_log_series(series)?;
+    let mut forecast = metric_forecast(&model, start, horizon, z);
+
+    let mut rng = StdRng::seed_from_u64(TOTAL_SIMULATION_SEED);
+    let mut totals: Vec<f64> = model
+        .simulate(horizon, TOTAL_SIMULATION_PATHS, &mut rng)
+        .iter()
+        .map(|path| path.iter().map(|v| v.exp_m1().max(0.0)).sum())
+        .collect();
+    totals.sort_by(f64::total_cmp);
+    let tail = (1.0 - confidence) / 2.0;
+    forecast.total = Some(ForecastTotal {
+        forecast: forecast.points.iter().map(|p| p.forecast).sum(),
+        lower: sorted_quantile(&totals, tail),
+        upper: sorted_quantile(&totals, 1.0 - tail),
+    });
+
+    Some(forecast)
+}
+
+fn fit_log_series(series: &[f64]) -> Option<HoltWinters> {
+    let season_length = season_length_for(series.len())?;
+    let log_series: Vec<f64> = series.iter().map(|v| v.max(0.0).ln_1p()).collect();
+    HoltWinters::fit(&log_series, season_length)
+}
+
+fn metric_forecast(
+    model: &HoltWinters,
+    start: DateTime<Utc>,
+    horizon: usize,
+    z: f64,
+) -> MetricForecast {
+    let points = model
+        .forecast(horizon, z)
+        .into_iter()
+        .enumerate()
+        .map(|(i, step)| ForecastPoint {
+            timestamp: start + Duration::hours(i as i64),
+            forecast: step.mean.exp_m1().max(0.0),
+            lower: step.lower.exp_m1().max(0.0),
+            upper: step.upper.exp_m1().max(0.0),
+        })
+        .collect();
+
+    MetricForecast {
+        season_length_hours: model.season_length,
+        params: model.params,
+        observations: model.observations,
+        points,
+        total: None,
+    }
+}
+
+/// Value at quantile `q` of an ascending, non-empty slice
+fn sorted_quantile(sorted: &[f64], q: f64) -> f64 {
+    let index = ((sorted.len() - 1) as f64 * q.clamp(0.0, 1.0)).round() as usize;
+    sorted[index]
+}
+
+/// Forecast the next `horizon_hours` of one corridor's volume and liquidity from its
+/// hourly rollups up to `now`
+pub async fn forecast_corridor(
+    db: &RollupDb,
+    corridor_key: &str,
+    horizon_hours: i64,
+    now: DateTime<Utc>,
+    config: &ForecastConfig,
+) -> Result<CorridorForecast> {
+    let horizon_hours = horizon_hours.clamp(1, MAX_HORIZON_HOURS);
+    let current_hour = now.duration_trunc(Duration::hours(1))?;
+
+    // The current hour is still being aggregated
+    let buckets = db
+        .fetch_buckets(
+            Resolution::Hour,
+            Some(corridor_key),
+            current_hour - config.history,
+            current_hour,
+        )
+        .await?;
+
+    let mut forecast = CorridorForecast {
+        corridor_key: corridor_key.to_string(),
+        generated_at: now,
+        forecast_start: current_hour,
+        horizon_hours,
+        confidence: config.confidence,
+        volume_usd: None,
+        liquidity_depth_usd: None,
+    };
+
+    if let Some((_, volume, liquidity)) = hourly_series(&buckets, current_hour) {
+        let horizon = horizon_hours as usize;
+        let z = config.z_score();
+        forecast.volume_usd =
+            forecast_flow_series(&volume, current_hour, horizon, z, config.confidence);
+        forecast.liquidity_depth_usd = forecast_series(&liquidity, current_hour, horizon, z);
+    }
+
+    Ok(forecast)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Database;
+    use crate::services::aggregation::HourlyCorridorMetrics;
+    use chrono::TimeZone;
+
+    fn bucket(start: DateTime<Utc>, volume_usd: f64, liquidity_depth_usd: f64) -> CorridorRollup {
+        CorridorRollup {
+            resolution: Resolution::Hour,
+            corridor_key: "USDC:GA->XLM:native".to_string(),
+            asset_a_code: "USDC".to_string(),
+            asset_a_issuer: "GA".to_string(),
+            asset_b_code: "XLM".to_string(),
+            asset_b_issuer: "native".to_string(),
+            bucket_start: start,
+            total_transactions: 10,
+            successful_transactions: 10,
+            failed_transactions: 0,
+            success_rate: 100.0,
+            volume_usd,
+            avg_slippage_bps: 0.0,
+            avg_settlement_latency_ms: None,
+            liquidity_depth_usd,
+            source_buckets: 1,
+        }
+    }
+
+    #[test]
+    fn test_hourly_series_fills_gaps() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let buckets = vec![
+            bucket(start, 100.0, 5_000.0),
+            bucket(start + Duration::hours(3), 50.0, 7_000.0),
+        ];
+
+        let (first, volume, liquidity) =
+            hourly_series(&buckets, start + Duration::hours(4)).unwrap();
+        assert_eq!(first, start);
+        assert_eq!(volume, vec![100.0, 0.0, 0.0, 50.0]);
+        assert_eq!(liquidity, vec![5_000.0, 5_000.0, 5_000.0, 7_000.0]);
+        assert!(hourly_series(&[], start).is_none());
+    }
+
+    #[test]
+    fn test_hourly_series_extends_to_until() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let buckets = vec![
+            bucket(start, 100.0, 5_000.0),
+            bucket(start + Duration::hours(1), 20.0, 6_000.0),
+        ];
+
+        // The corridor went quiet two hours before `until`
+        let (_, volume, liquidity) = hourly_series(&buckets, start + Duration::hours(4)).unwrap();
+        assert_eq!(volume, vec![100.0, 20.0, 0.0, 0.0]);
+        assert_eq!(liquidity, vec![5_000.0, 6_000.0, 6_000.0, 6_000.0]);
+    }
+
+    #[tokio::test]
+    async fn test_forecast_starts_at_current_hour_after_quiet_hours() {
+        let db = Database::new(crate::test_support::migrated_pool().await.into());
+        let now = Utc.with_ymd_and_hms(2024, 1, 10, 12, 30, 0).unwrap();
+        let current_hour = Utc.with_ymd_and_hms(2024, 1, 10, 12, 0, 0).unwrap();
+
+        // Three days of payments, then five hours without any
+        let last = current_hour - Duration::hours(6);
+        for i in 0..72 {
+            db.upsert_hourly_corridor_metric(&HourlyCorridorMetrics {
+                id: format!("hour-{i}"),
+                corridor_key: "USDC:GA->XLM:native".to_string(),
+                asset_a_code: "USDC".to_string(),
+                asset_a_issuer: "GA".to_string(),
+                asset_b_code: "XLM".to_string(),
+                asset_b_issuer: "native".to_string(),
+                hour_bucket: last - Duration::hours(i),
+                total_transactions: 10,
+                successful_transactions: 10,
+                failed_transactions: 0,
+                success_rate: 100.0,
+                volume_usd: 1_000.0,
+                avg_slippage_bps: 0.0,
+                avg_settlement_latency_ms: None,
+                liquidity_depth_usd: 50_000.0,
+            })
+            .await
+            .unwrap();
+        }
+
+        let forecast = forecast_corridor(
+            &db.rollup_db(),
+            "USDC:GA->XLM:native",
+            24,
+            now,
+            &ForecastConfig::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(forecast.forecast_start, current_hour);
+        let volume = forecast.volume_usd.unwrap();
+        assert_eq!(volume.observations, 77);
+        assert_eq!(volume.points[0].timestamp, current_hour);
+        let liquidity = forecast.liquidity_depth_usd.unwrap();
+        assert_eq!(liquidity.points[0].timestamp, current_hour);
+    }
+
+    #[test]
+    fn test_forecast_series_anticipates_daily_peak() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        // Quiet nights, busy afternoons
+        let series: Vec<f64> = (0..24 * 14)
+            .map(|h| {
+                if (12..18).contains(&(h % 24)) {
+                    10_000.0
+                } else {
+                    1_000.0
+                }
+            })
+            .collect();
+        let forecast_start = start + Duration::hours(series.len() as i64);
+
+        let forecast = forecast_flow_series(&series, forecast_start, 24, 1.96, 0.95).unwrap();
+        assert_eq!(forecast.season_length_hours, 24);
+        assert_eq!(forecast.points.len(), 24);
+        assert_eq!(forecast.points[0].timestamp, forecast_start);
+
+        let peak = forecast.peak().unwrap();
+        assert!((12..18).contains(&(peak.timestamp - start).num_hours().rem_euclid(24)));
+        for point in &forecast.points {
+            assert!(point.lower >= 0.0 && point.lower <= point.forecast);
+            assert!(point.forecast <= point.upper);
+        }
+
+        let total = forecast.total.unwrap();
+        assert!((total.forecast - 6.0 * 10_000.0 - 18.0 * 1_000.0).abs() < 0.1 * total.forecast);
+        assert!(total.lower <= total.forecast && total.forecast <= total.upper);
+    }
+
+    #[test]
+    fn test_forecast_total_interval_is_not_summed_hourly_bounds() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        // A noisy daily cycle, so hourly intervals are wide
+        let series: Vec<f64> = (0..24 * 14)
+            .map(|h: i64| {
+                let base = if (12..18).contains(&(h % 24)) {
+                    10_000.0
+                } else {
+                    1_000.0
+                };
+                base * (1.0 + 0.4 * ((h * 7919 % 13) as f64 / 6.0 - 1.0))
+            })
+            .collect();
+        let forecast_start = start + Duration::hours(series.len() as i64);
+
+        let forecast = forecast_flow_series(&series, forecast_start, 48, 1.96, 0.95).unwrap();
+        let total = forecast.total.unwrap();
+        let summed_lower: f64 = forecast.points.iter().map(|p| p.lower).sum();
+        let summed_upper: f64 = forecast.points.iter().map(|p| p.upper).sum();
+        // Hourly errors partly cancel out over the horizon
+        assert!(
+            total.lower > summed_lower,
+            "{} vs {}",
+            total.lower,
+            summed_lower
+        );
+        assert!(
+            total.upper < summed_upper,
+            "{} vs {}",
+            total.upper,
+            summed_upper
+        );
+        assert!(total.lower <= total.forecast && total.forecast <= total.upper);
+
+        // Simulation is seeded, so the interval is stable
+        let again = forecast_flow_series(&series, forecast_start, 48, 1.96, 0.95).unwrap();
+        assert_eq!(again.total, forecast.total);
+
+        // Levels are not summed
+        let liquidity = forecast_series(&series, forecast_start, 48, 1.96).unwrap();
+        assert!(liquidity.total.is_none());
+        let trough = liquidity.trough().unwrap();
+        assert!(liquidity.points.iter().all(|p| p.lower >= trough.lower));
+    }
+
+    #[test]
+    fn test_forecast_series_needs_two_days() {
+        let start = Utc::now();
+        assert!(forecast_series(&[1.0; 47], start, 24, 1.96).is_none());
+    }
+}
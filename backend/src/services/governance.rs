@@ -1,4 +1,5 @@
 use crate::database::Database;
+use crate::with_pool;
 use anyhow::{anyhow, Context, Result};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
@@ -93,24 +94,27 @@ impl GovernanceService {
             .proposal_type
             .unwrap_or_else(|| "contract_upgrade".to_string());
 
-        sqlx::query(
-            r#"
-            INSERT INTO governance_proposals
-            (id, title, description, proposal_type, target_contract, new_wasm_hash, status, created_by, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, 'draft', ?, ?, ?)
-            "#,
-        )
-        .bind(&id)
-        .bind(&request.title)
-        .bind(&request.description)
-        .bind(&proposal_type)
-        .bind(&request.target_contract)
-        .bind(&request.new_wasm_hash)
-        .bind(creator)
-        .bind(&now)
-        .bind(&now)
-        .execute(self.db.pool())
-        .await
+        with_pool!(self.db.pool(), |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO governance_proposals
+                (id, title, description, proposal_type, target_contract, new_wasm_hash, status, created_by, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, 'draft', $7, $8, $9)
+                "#,
+            )
+            .bind(&id)
+            .bind(&request.title)
+            .bind(&request.description)
+            .bind(&proposal_type)
+            .bind(&request.target_contract)
+            .bind(&request.new_wasm_hash)
+            .bind(creator)
+            .bind(&now)
+            .bind(&now)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .context("Failed to create proposal")?;
 
         info!("Created governance proposal {} by {}", id, creator);
@@ -146,21 +150,24 @@ impl GovernanceService {
         let now_str = now.to_rfc3339();
         let voting_ends_str = voting_ends_at.to_rfc3339();
 
-        let result = sqlx::query(
-            r#"
-            UPDATE governance_proposals
-            SET status = 'active', voting_ends_at = ?, updated_at = ?
-            WHERE id = ? AND status = 'draft'
-            "#,
-        )
-        .bind(&voting_ends_str)
-        .bind(&now_str)
-        .bind(proposal_id)
-        .execute(self.db.pool())
-        .await
+        let result = with_pool!(self.db.pool(), |pool| {
+            sqlx::query(
+                r#"
+                UPDATE governance_proposals
+                SET status = 'active', voting_ends_at = $1, updated_at = $2
+                WHERE id = $3 AND status = 'draft'
+                "#,
+            )
+            .bind(&voting_ends_str)
+            .bind(&now_str)
+            .bind(proposal_id)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .context("Failed to activate proposal")?;
 
-        if result.rows_affected() == 0 {
+        if result == 0 {
             return Err(anyhow!("Proposal not found or not in draft status"));
         }
 
@@ -174,38 +181,83 @@ impl GovernanceService {
         limit: i64,
         offset: i64,
     ) -> Result<ProposalsListResponse> {
-        let (rows, total) = if let Some(status) = status {
-            let rows = sqlx::query(
-                r#"
-                SELECT p.*,
-                    COALESCE(SUM(CASE WHEN v.choice = 'for' THEN 1 ELSE 0 END), 0) as votes_for,
-                    COALESCE(SUM(CASE WHEN v.choice = 'against' THEN 1 ELSE 0 END), 0) as votes_against,
-                    COALESCE(SUM(CASE WHEN v.choice = 'abstain' THEN 1 ELSE 0 END), 0) as votes_abstain
-                FROM governance_proposals p
-                LEFT JOIN governance_votes v ON p.id = v.proposal_id
-                WHERE p.status = ?
-                GROUP BY p.id
-                ORDER BY p.created_at DESC
-                LIMIT ? OFFSET ?
-                "#,
-            )
-            .bind(status)
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(self.db.pool())
-            .await
-            .context("Failed to list proposals")?;
-
-            let total: i64 =
-                sqlx::query_scalar("SELECT COUNT(*) FROM governance_proposals WHERE status = ?")
+        let (proposals, total) = if let Some(status) = status {
+            let proposals = with_pool!(self.db.pool(), |pool| {
+                sqlx::query(
+                    r#"
+                    SELECT p.*,
+                        COALESCE(SUM(CASE WHEN v.choice = 'for' THEN 1 ELSE 0 END), 0) as votes_for,
+                        COALESCE(SUM(CASE WHEN v.choice = 'against' THEN 1 ELSE 0 END), 0) as votes_against,
+                        COALESCE(SUM(CASE WHEN v.choice = 'abstain' THEN 1 ELSE 0 END), 0) as votes_abstain
+                    FROM governance_proposals p
+                    LEFT JOIN governance_votes v ON p.id = v.proposal_id
+                    WHERE p.status = $1
+                    GROUP BY p.id
+                    ORDER BY p.created_at DESC
+                    LIMIT $2 OFFSET $3
+                    "#,
+                )
+                .bind(status)
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(pool)
+                .await
+                .context("Failed to list proposals")?
+                .iter()
+                .map(proposal_from_row)
+                .collect::<Result<Vec<_>>>()
+            })?;
+
+            let total: i64 = with_pool!(self.db.pool(), |pool| {
+                sqlx::query_scalar("SELECT COUNT(*) FROM governance_proposals WHERE status = $1")
                     .bind(status)
-                    .fetch_one(self.db.pool())
+                    .fetch_one(pool)
                     .await
-                    .context("Failed to count proposals")?;
+            })
+            .context("Failed to count proposals")?;
 
-            (rows, total)
+            (proposals, total)
         } else {
-            let rows = sqlx::query(
+            let proposals = with_pool!(self.db.pool(), |pool| {
+                sqlx::query(
+                    r#"
+                    SELECT p.*,
+                        COALESCE(SUM(CASE WHEN v.choice = 'for' THEN 1 ELSE 0 END), 0) as votes_for,
+                        COALESCE(SUM(CASE WHEN v.choice = 'against' THEN 1 ELSE 0 END), 0) as votes_against,
+                        COALESCE(SUM(CASE WHEN v.choice = 'abstain' THEN 1 ELSE 0 END), 0) as votes_abstain
+                    FROM governance_proposals p
+                    LEFT JOIN governance_votes v ON p.id = v.proposal_id
+                    GROUP BY p.id
+                    ORDER BY p.created_at DESC
+                    LIMIT $1 OFFSET $2
+                    "#,
+                )
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(pool)
+                .await
+                .context("Failed to list proposals")?
+                .iter()
+                .map(proposal_from_row)
+                .collect::<Result<Vec<_>>>()
+            })?;
+
+            let total: i64 = with_pool!(self.db.pool(), |pool| {
+                sqlx::query_scalar("SELECT COUNT(*) FROM governance_proposals")
+                    .fetch_one(pool)
+                    .await
+            })
+            .context("Failed to count proposals")?;
+
+            (proposals, total)
+        };
+
+        Ok(ProposalsListResponse { proposals, total })
+    }
+
+    pub async fn get_proposal(&self, id: &str) -> Result<ProposalResponse> {
+        with_pool!(self.db.pool(), |pool| {
+            sqlx::query(
                 r#"
                 SELECT p.*,
                     COALESCE(SUM(CASE WHEN v.choice = 'for' THEN 1 ELSE 0 END), 0) as votes_for,
@@ -213,52 +265,16 @@ impl GovernanceService {
                     COALESCE(SUM(CASE WHEN v.choice = 'abstain' THEN 1 ELSE 0 END), 0) as votes_abstain
                 FROM governance_proposals p
                 LEFT JOIN governance_votes v ON p.id = v.proposal_id
+                WHERE p.id = $1
                 GROUP BY p.id
-                ORDER BY p.created_at DESC
-                LIMIT ? OFFSET ?
                 "#,
             )
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(self.db.pool())
+            .bind(id)
+            .fetch_one(pool)
             .await
-            .context("Failed to list proposals")?;
-
-            let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM governance_proposals")
-                .fetch_one(self.db.pool())
-                .await
-                .context("Failed to count proposals")?;
-
-            (rows, total)
-        };
-
-        let proposals = rows
-            .iter()
-            .map(|row| proposal_from_row(row))
-            .collect::<Result<Vec<_>>>()?;
-
-        Ok(ProposalsListResponse { proposals, total })
-    }
-
-    pub async fn get_proposal(&self, id: &str) -> Result<ProposalResponse> {
-        let row = sqlx::query(
-            r#"
-            SELECT p.*,
-                COALESCE(SUM(CASE WHEN v.choice = 'for' THEN 1 ELSE 0 END), 0) as votes_for,
-                COALESCE(SUM(CASE WHEN v.choice = 'against' THEN 1 ELSE 0 END), 0) as votes_against,
-                COALESCE(SUM(CASE WHEN v.choice = 'abstain' THEN 1 ELSE 0 END), 0) as votes_abstain
-            FROM governance_proposals p
-            LEFT JOIN governance_votes v ON p.id = v.proposal_id
-            WHERE p.id = ?
-            GROUP BY p.id
-            "#,
-        )
-        .bind(id)
-        .fetch_one(self.db.pool())
-        .await
-        .context("Proposal not found")?;
-
-        proposal_from_row(&row)
+            .context("Proposal not found")
+            .and_then(|row| proposal_from_row(&row))
+        })
     }
 
     pub async fn cast_vote(
@@ -268,12 +284,13 @@ impl GovernanceService {
         request: CastVoteRequest,
     ) -> Result<VoteResponse> {
         // Verify proposal is active
-        let status: String =
-            sqlx::query_scalar("SELECT status FROM governance_proposals WHERE id = ?")
+        let status: String = with_pool!(self.db.pool(), |pool| {
+            sqlx::query_scalar("SELECT status FROM governance_proposals WHERE id = $1")
                 .bind(proposal_id)
-                .fetch_one(self.db.pool())
+                .fetch_one(pool)
                 .await
-                .context("Proposal not found")?;
+        })
+        .context("Proposal not found")?;
 
         if status != "active" {
             return Err(anyhow!("Proposal is not active for voting"));
@@ -282,20 +299,23 @@ impl GovernanceService {
         let id = Uuid::new_v4().to_string();
         let now = Utc::now().to_rfc3339();
 
-        sqlx::query(
-            r#"
-            INSERT INTO governance_votes (id, proposal_id, voter_address, choice, tx_hash, voted_at)
-            VALUES (?, ?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(&id)
-        .bind(proposal_id)
-        .bind(voter_address)
-        .bind(&request.choice)
-        .bind(&request.tx_hash)
-        .bind(&now)
-        .execute(self.db.pool())
-        .await
+        with_pool!(self.db.pool(), |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO governance_votes (id, proposal_id, voter_address, choice, tx_hash, voted_at)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#,
+            )
+            .bind(&id)
+            .bind(proposal_id)
+            .bind(voter_address)
+            .bind(&request.choice)
+            .bind(&request.tx_hash)
+            .bind(&now)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .context("Failed to cast vote (may have already voted)")?;
 
         info!(
@@ -314,44 +334,46 @@ impl GovernanceService {
     }
 
     pub async fn get_votes(&self, proposal_id: &str, limit: i64) -> Result<Vec<VoteResponse>> {
-        let rows = sqlx::query(
-            r#"
-            SELECT id, proposal_id, voter_address, choice, tx_hash, voted_at
-            FROM governance_votes
-            WHERE proposal_id = ?
-            ORDER BY voted_at DESC
-            LIMIT ?
-            "#,
-        )
-        .bind(proposal_id)
-        .bind(limit)
-        .fetch_all(self.db.pool())
-        .await
-        .context("Failed to fetch votes")?;
-
-        let mut votes = Vec::new();
-        for row in rows {
-            votes.push(VoteResponse {
-                id: row.try_get::<String, _>("id")?,
-                proposal_id: row.try_get::<String, _>("proposal_id")?,
-                voter_address: row.try_get::<String, _>("voter_address")?,
-                choice: row.try_get::<String, _>("choice")?,
-                tx_hash: row.try_get("tx_hash").ok(),
-                voted_at: row.try_get::<String, _>("voted_at")?,
-            });
-        }
-
-        Ok(votes)
+        with_pool!(self.db.pool(), |pool| {
+            sqlx::query(
+                r#"
+                SELECT id, proposal_id, voter_address, choice, tx_hash, voted_at
+                FROM governance_votes
+                WHERE proposal_id = $1
+                ORDER BY voted_at DESC
+                LIMIT $2
+                "#,
+            )
+            .bind(proposal_id)
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+            .context("Failed to fetch votes")?
+            .iter()
+            .map(|row| {
+                Ok(VoteResponse {
+                    id: row.try_get::<String, _>("id")?,
+                    proposal_id: row.try_get::<String, _>("proposal_id")?,
+                    voter_address: row.try_get::<String, _>("voter_address")?,
+                    choice: row.try_get::<String, _>("choice")?,
+                    tx_hash: row.try_get("tx_hash").ok(),
+                    voted_at: row.try_get::<String, _>("voted_at")?,
+                })
+            })
+            .collect()
+        })
     }
 
     pub async fn has_voted(&self, proposal_id: &str, voter_address: &str) -> Result<bool> {
-        let count: i64 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM governance_votes WHERE proposal_id = ? AND voter_address = ?",
-        )
-        .bind(proposal_id)
-        .bind(voter_address)
-        .fetch_one(self.db.pool())
-        .await
+        let count: i64 = with_pool!(self.db.pool(), |pool| {
+            sqlx::query_scalar(
+                "SELECT COUNT(*) FROM governance_votes WHERE proposal_id = $1 AND voter_address = $2",
+            )
+            .bind(proposal_id)
+            .bind(voter_address)
+            .fetch_one(pool)
+            .await
+        })
         .context("Failed to check vote status")?;
 
         Ok(count > 0)
@@ -364,28 +386,33 @@ impl GovernanceService {
         request: AddCommentRequest,
     ) -> Result<CommentResponse> {
         // Verify proposal exists
-        let _: String = sqlx::query_scalar("SELECT id FROM governance_proposals WHERE id = ?")
-            .bind(proposal_id)
-            .fetch_one(self.db.pool())
-            .await
-            .context("Proposal not found")?;
+        let _: String = with_pool!(self.db.pool(), |pool| {
+            sqlx::query_scalar("SELECT id FROM governance_proposals WHERE id = $1")
+                .bind(proposal_id)
+                .fetch_one(pool)
+                .await
+        })
+        .context("Proposal not found")?;
 
         let id = Uuid::new_v4().to_string();
         let now = Utc::now().to_rfc3339();
 
-        sqlx::query(
-            r#"
-            INSERT INTO governance_comments (id, proposal_id, author_address, content, created_at)
-            VALUES (?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(&id)
-        .bind(proposal_id)
-        .bind(author)
-        .bind(&request.content)
-        .bind(&now)
-        .execute(self.db.pool())
-        .await
+        with_pool!(self.db.pool(), |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO governance_comments (id, proposal_id, author_address, content, created_at)
+                VALUES ($1, $2, $3, $4, $5)
+                "#,
+            )
+            .bind(&id)
+            .bind(proposal_id)
+            .bind(author)
+            .bind(&request.content)
+            .bind(&now)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .context("Failed to add comment")?;
 
         info!("Comment added to proposal {} by {}", proposal_id, author);
@@ -404,48 +431,52 @@ impl GovernanceService {
         proposal_id: &str,
         limit: i64,
     ) -> Result<Vec<CommentResponse>> {
-        let rows = sqlx::query(
-            r#"
-            SELECT id, proposal_id, author_address, content, created_at
-            FROM governance_comments
-            WHERE proposal_id = ?
-            ORDER BY created_at DESC
-            LIMIT ?
-            "#,
-        )
-        .bind(proposal_id)
-        .bind(limit)
-        .fetch_all(self.db.pool())
-        .await
-        .context("Failed to fetch comments")?;
-
-        let mut comments = Vec::new();
-        for row in rows {
-            comments.push(CommentResponse {
-                id: row.try_get::<String, _>("id")?,
-                proposal_id: row.try_get::<String, _>("proposal_id")?,
-                author_address: row.try_get::<String, _>("author_address")?,
-                content: row.try_get::<String, _>("content")?,
-                created_at: row.try_get::<String, _>("created_at")?,
-            });
-        }
-
-        Ok(comments)
+        with_pool!(self.db.pool(), |pool| {
+            sqlx::query(
+                r#"
+                SELECT id, proposal_id, author_address, content, created_at
+                FROM governance_comments
+                WHERE proposal_id = $1
+                ORDER BY created_at DESC
+                LIMIT $2
+                "#,
+            )
+            .bind(proposal_id)
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+            .context("Failed to fetch comments")?
+            .iter()
+            .map(|row| {
+                Ok(CommentResponse {
+                    id: row.try_get::<String, _>("id")?,
+                    proposal_id: row.try_get::<String, _>("proposal_id")?,
+                    author_address: row.try_get::<String, _>("author_address")?,
+                    content: row.try_get::<String, _>("content")?,
+                    created_at: row.try_get::<String, _>("created_at")?,
+                })
+            })
+            .collect()
+        })
     }
 
     pub async fn update_status(&self, proposal_id: &str, status: &str) -> Result<()> {
         let now = Utc::now().to_rfc3339();
 
-        let result =
-            sqlx::query("UPDATE governance_proposals SET status = ?, updated_at = ? WHERE id = ?")
-                .bind(status)
-                .bind(&now)
-                .bind(proposal_id)
-                .execute(self.db.pool())
-                .await
-                .context("Failed to update proposal status")?;
+        let result = with_pool!(self.db.pool(), |pool| {
+            sqlx::query(
+                "UPDATE governance_proposals SET status = $1, updated_at = $2 WHERE id = $3",
+            )
+            .bind(status)
+            .bind(&now)
+            .bind(proposal_id)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
+        .context("Failed to update proposal status")?;
 
-        if result.rows_affected() == 0 {
+        if result == 0 {
             return Err(anyhow!("Proposal not found"));
         }
 
@@ -454,7 +485,13 @@ impl GovernanceService {
     }
 }
 
-fn proposal_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<ProposalResponse> {
+fn proposal_from_row<R>(row: &R) -> Result<ProposalResponse>
+where
+    R: Row,
+    for<'a> &'a str: sqlx::ColumnIndex<R>,
+    for<'r> String: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+    for<'r> i64: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+{
     Ok(ProposalResponse {
         id: row.try_get::<String, _>("id")?,
         title: row.try_get::<String, _>("title")?,
@@ -1,6 +1,8 @@
+use crate::database::DbPool;
+use crate::with_pool;
 use anyhow::Result;
 use chrono::Utc;
-use sqlx::{Pool, Sqlite};
+
 use std::sync::Arc;
 use tracing::info;
 
@@ -8,12 +10,12 @@ use crate::models::{LiquidityPool, LiquidityPoolSnapshot, LiquidityPoolStats};
 use crate::rpc::StellarRpcClient;
 
 pub struct LiquidityPoolAnalyzer {
-    pool: Pool<Sqlite>,
+    pool: DbPool,
     rpc_client: Arc<StellarRpcClient>,
 }
 
 impl LiquidityPoolAnalyzer {
-    pub fn new(pool: Pool<Sqlite>, rpc_client: Arc<StellarRpcClient>) -> Self {
+    pub fn new(pool: DbPool, rpc_client: Arc<StellarRpcClient>) -> Self {
         Self { pool, rpc_client }
     }
 
@@ -37,8 +39,7 @@ impl LiquidityPoolAnalyzer {
             let reserve_a: f64 = hp.reserves[0].amount.parse().unwrap_or(0.0);
             let reserve_b: f64 = hp.reserves[1].amount.parse().unwrap_or(0.0);
 
-            // Estimate total value (simplified: assume both sides equivalent for AMM)
-            let total_value_usd = reserve_a + reserve_b; // Simplified valuation
+            let total_value_usd = Self::estimate_total_value(reserve_a, reserve_b);
 
             // Compute volume from recent trades
             let trades = self
@@ -56,16 +57,8 @@ impl LiquidityPoolAnalyzer {
 
             let trade_count_24h = trades.len() as i32;
 
-            // Compute fees earned (fee_bp basis points applied to volume)
-            let fee_rate = hp.fee_bp as f64 / 10_000.0;
-            let fees_earned_24h = volume_24h_usd * fee_rate;
-
-            // Compute APY: annualize daily fees relative to TVL
-            let apy = if total_value_usd > 0.0 {
-                (fees_earned_24h / total_value_usd) * 365.0 * 100.0
-            } else {
-                0.0
-            };
+            let fees_earned_24h = Self::compute_fees(volume_24h_usd, hp.fee_bp as i32);
+            let apy = Self::compute_apy(fees_earned_24h, total_value_usd);
 
             // Compute impermanent loss (requires initial reserves, use snapshot if available)
             let il = self
@@ -74,53 +67,56 @@ impl LiquidityPoolAnalyzer {
 
             let now = Utc::now();
 
-            sqlx::query(
-                r#"
-                INSERT INTO liquidity_pools (
-                    pool_id, pool_type, fee_bp, total_trustlines, total_shares,
-                    reserve_a_asset_code, reserve_a_asset_issuer, reserve_a_amount,
-                    reserve_b_asset_code, reserve_b_asset_issuer, reserve_b_amount,
-                    total_value_usd, volume_24h_usd, fees_earned_24h_usd, apy,
-                    impermanent_loss_pct, trade_count_24h, last_synced_at, created_at, updated_at
+            with_pool!(&self.pool, |db| {
+                sqlx::query(
+                    r#"
+                    INSERT INTO liquidity_pools (
+                        pool_id, pool_type, fee_bp, total_trustlines, total_shares,
+                        reserve_a_asset_code, reserve_a_asset_issuer, reserve_a_amount,
+                        reserve_b_asset_code, reserve_b_asset_issuer, reserve_b_amount,
+                        total_value_usd, volume_24h_usd, fees_earned_24h_usd, apy,
+                        impermanent_loss_pct, trade_count_24h, last_synced_at, created_at, updated_at
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)
+                    ON CONFLICT (pool_id) DO UPDATE SET
+                        total_trustlines = excluded.total_trustlines,
+                        total_shares = excluded.total_shares,
+                        reserve_a_amount = excluded.reserve_a_amount,
+                        reserve_b_amount = excluded.reserve_b_amount,
+                        total_value_usd = excluded.total_value_usd,
+                        volume_24h_usd = excluded.volume_24h_usd,
+                        fees_earned_24h_usd = excluded.fees_earned_24h_usd,
+                        apy = excluded.apy,
+                        impermanent_loss_pct = excluded.impermanent_loss_pct,
+                        trade_count_24h = excluded.trade_count_24h,
+                        last_synced_at = excluded.last_synced_at,
+                        updated_at = excluded.updated_at
+                    "#,
                 )
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)
-                ON CONFLICT (pool_id) DO UPDATE SET
-                    total_trustlines = excluded.total_trustlines,
-                    total_shares = excluded.total_shares,
-                    reserve_a_amount = excluded.reserve_a_amount,
-                    reserve_b_amount = excluded.reserve_b_amount,
-                    total_value_usd = excluded.total_value_usd,
-                    volume_24h_usd = excluded.volume_24h_usd,
-                    fees_earned_24h_usd = excluded.fees_earned_24h_usd,
-                    apy = excluded.apy,
-                    impermanent_loss_pct = excluded.impermanent_loss_pct,
-                    trade_count_24h = excluded.trade_count_24h,
-                    last_synced_at = excluded.last_synced_at,
-                    updated_at = excluded.updated_at
-                "#,
-            )
-            .bind(&hp.id)
-            .bind(&hp.pool_type)
-            .bind(hp.fee_bp as i32)
-            .bind(hp.total_trustlines as i32)
-            .bind(&hp.total_shares)
-            .bind(&asset_a_code)
-            .bind(&asset_a_issuer)
-            .bind(reserve_a)
-            .bind(&asset_b_code)
-            .bind(&asset_b_issuer)
-            .bind(reserve_b)
-            .bind(total_value_usd)
-            .bind(volume_24h_usd)
-            .bind(fees_earned_24h)
-            .bind(apy)
-            .bind(il)
-            .bind(trade_count_24h)
-            .bind(now)
-            .bind(now)
-            .bind(now)
-            .execute(&self.pool)
-            .await?;
+                .bind(&hp.id)
+                .bind(&hp.pool_type)
+                .bind(hp.fee_bp as i32)
+                .bind(hp.total_trustlines as i32)
+                .bind(&hp.total_shares)
+                .bind(&asset_a_code)
+                .bind(&asset_a_issuer)
+                .bind(reserve_a)
+                .bind(&asset_b_code)
+                .bind(&asset_b_issuer)
+                .bind(reserve_b)
+                .bind(total_value_usd)
+                .bind(volume_24h_usd)
+                .bind(fees_earned_24h)
+                .bind(apy)
+                .bind(il)
+                .bind(trade_count_24h)
+                .bind(now)
+                .bind(now)
+                .bind(now)
+                .execute(db)
+                .await
+                .map(|done| done.rows_affected())
+            })?;
 
             count += 1;
         }
@@ -139,27 +135,30 @@ impl LiquidityPoolAnalyzer {
         let now = Utc::now();
 
         for pool in &pools {
-            sqlx::query(
-                r#"
-                INSERT INTO liquidity_pool_snapshots (
-                    pool_id, reserve_a_amount, reserve_b_amount, total_value_usd,
-                    volume_usd, fees_usd, apy, impermanent_loss_pct, trade_count, snapshot_at
+            with_pool!(&self.pool, |db| {
+                sqlx::query(
+                    r#"
+                    INSERT INTO liquidity_pool_snapshots (
+                        pool_id, reserve_a_amount, reserve_b_amount, total_value_usd,
+                        volume_usd, fees_usd, apy, impermanent_loss_pct, trade_count, snapshot_at
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                    "#,
                 )
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
-                "#,
-            )
-            .bind(&pool.pool_id)
-            .bind(pool.reserve_a_amount)
-            .bind(pool.reserve_b_amount)
-            .bind(pool.total_value_usd)
-            .bind(pool.volume_24h_usd)
-            .bind(pool.fees_earned_24h_usd)
-            .bind(pool.apy)
-            .bind(pool.impermanent_loss_pct)
-            .bind(pool.trade_count_24h)
-            .bind(now)
-            .execute(&self.pool)
-            .await?;
+                .bind(&pool.pool_id)
+                .bind(pool.reserve_a_amount)
+                .bind(pool.reserve_b_amount)
+                .bind(pool.total_value_usd)
+                .bind(pool.volume_24h_usd)
+                .bind(pool.fees_earned_24h_usd)
+                .bind(pool.apy)
+                .bind(pool.impermanent_loss_pct)
+                .bind(pool.trade_count_24h)
+                .bind(now)
+                .execute(db)
+                .await
+                .map(|done| done.rows_affected())
+            })?;
             count += 1;
         }
 
@@ -175,11 +174,13 @@ impl LiquidityPoolAnalyzer {
 
     /// Get all pools from the database
     pub async fn get_all_pools(&self) -> Result<Vec<LiquidityPool>> {
-        let pools = sqlx::query_as::<_, LiquidityPool>(
-            "SELECT * FROM liquidity_pools ORDER BY total_value_usd DESC",
-        )
-        .fetch_all(&self.pool)
-        .await?;
+        let pools = with_pool!(&self.pool, |db| {
+            sqlx::query_as::<_, LiquidityPool>(
+                "SELECT * FROM liquidity_pools ORDER BY total_value_usd DESC",
+            )
+            .fetch_all(db)
+            .await
+        })?;
         Ok(pools)
     }
 
@@ -188,11 +189,12 @@ impl LiquidityPoolAnalyzer {
         &self,
         pool_id: &str,
     ) -> Result<(LiquidityPool, Vec<LiquidityPoolSnapshot>)> {
-        let pool =
+        let pool = with_pool!(&self.pool, |db| {
             sqlx::query_as::<_, LiquidityPool>("SELECT * FROM liquidity_pools WHERE pool_id = $1")
                 .bind(pool_id)
-                .fetch_one(&self.pool)
-                .await?;
+                .fetch_one(db)
+                .await
+        })?;
 
         let snapshots = self.get_pool_snapshots(pool_id, 100).await?;
 
@@ -205,18 +207,20 @@ impl LiquidityPoolAnalyzer {
         pool_id: &str,
         limit: i64,
     ) -> Result<Vec<LiquidityPoolSnapshot>> {
-        let snapshots = sqlx::query_as::<_, LiquidityPoolSnapshot>(
-            r#"
+        let snapshots = with_pool!(&self.pool, |db| {
+            sqlx::query_as::<_, LiquidityPoolSnapshot>(
+                r#"
             SELECT * FROM liquidity_pool_snapshots
             WHERE pool_id = $1
             ORDER BY snapshot_at DESC
             LIMIT $2
             "#,
-        )
-        .bind(pool_id)
-        .bind(limit)
-        .fetch_all(&self.pool)
-        .await?;
+            )
+            .bind(pool_id)
+            .bind(limit)
+            .fetch_all(db)
+            .await
+        })?;
         Ok(snapshots)
     }
 
@@ -236,30 +240,34 @@ impl LiquidityPoolAnalyzer {
             order_clause
         );
 
-        let pools = sqlx::query_as::<_, LiquidityPool>(&query)
-            .bind(limit)
-            .fetch_all(&self.pool)
-            .await?;
+        let pools = with_pool!(&self.pool, |db| {
+            sqlx::query_as::<_, LiquidityPool>(&query)
+                .bind(limit)
+                .fetch_all(db)
+                .await
+        })?;
 
         Ok(pools)
     }
 
     /// Get aggregate pool statistics
     pub async fn get_pool_stats(&self) -> Result<LiquidityPoolStats> {
-        let row: (i64, f64, f64, f64, f64, f64) = sqlx::query_as(
-            r#"
-            SELECT
-                COUNT(*) as total_pools,
-                COALESCE(SUM(total_value_usd), 0.0) as total_tvl,
-                COALESCE(SUM(volume_24h_usd), 0.0) as total_volume,
-                COALESCE(SUM(fees_earned_24h_usd), 0.0) as total_fees,
-                COALESCE(AVG(apy), 0.0) as avg_apy,
-                COALESCE(AVG(impermanent_loss_pct), 0.0) as avg_il
-            FROM liquidity_pools
-            "#,
-        )
-        .fetch_one(&self.pool)
-        .await?;
+        let row: (i64, f64, f64, f64, f64, f64) = with_pool!(&self.pool, |db| {
+            sqlx::query_as(
+                r#"
+                SELECT
+                    COUNT(*) as total_pools,
+                    COALESCE(SUM(total_value_usd), 0.0) as total_tvl,
+                    COALESCE(SUM(volume_24h_usd), 0.0) as total_volume,
+                    COALESCE(SUM(fees_earned_24h_usd), 0.0) as total_fees,
+                    COALESCE(AVG(apy), 0.0) as avg_apy,
+                    COALESCE(AVG(impermanent_loss_pct), 0.0) as avg_il
+                FROM liquidity_pools
+                "#,
+            )
+            .fetch_one(db)
+            .await
+        })?;
 
         Ok(LiquidityPoolStats {
             total_pools: row.0,
@@ -277,6 +285,25 @@ impl LiquidityPoolAnalyzer {
     // Computation Helpers
     // ========================================================================
 
+    /// Estimate pool value (simplified: assume both sides equivalent for AMM)
+    pub fn estimate_total_value(reserve_a: f64, reserve_b: f64) -> f64 {
+        reserve_a + reserve_b
+    }
+
+    /// Fees earned on `volume` at `fee_bp` basis points
+    pub fn compute_fees(volume: f64, fee_bp: i32) -> f64 {
+        volume * (fee_bp as f64 / 10_000.0)
+    }
+
+    /// APY: annualize daily fees relative to TVL
+    pub fn compute_apy(daily_fees: f64, total_value: f64) -> f64 {
+        if total_value > 0.0 {
+            (daily_fees / total_value) * 365.0 * 100.0
+        } else {
+            0.0
+        }
+    }
+
     /// Compute impermanent loss given initial and current reserves.
     /// IL = 2 * sqrt(price_ratio) / (1 + price_ratio) - 1
     /// where price_ratio = (current_a/current_b) / (initial_a/initial_b)
@@ -308,18 +335,20 @@ impl LiquidityPoolAnalyzer {
         current_a: f64,
         current_b: f64,
     ) -> f64 {
-        let initial = sqlx::query_as::<_, (f64, f64)>(
-            r#"
-            SELECT reserve_a_amount, reserve_b_amount
-            FROM liquidity_pool_snapshots
-            WHERE pool_id = $1
-            ORDER BY snapshot_at ASC
-            LIMIT 1
-            "#,
-        )
-        .bind(pool_id)
-        .fetch_optional(&self.pool)
-        .await
+        let initial = with_pool!(&self.pool, |db| {
+            sqlx::query_as::<_, (f64, f64)>(
+                r#"
+                SELECT reserve_a_amount, reserve_b_amount
+                FROM liquidity_pool_snapshots
+                WHERE pool_id = $1
+                ORDER BY snapshot_at ASC
+                LIMIT 1
+                "#,
+            )
+            .bind(pool_id)
+            .fetch_optional(db)
+            .await
+        })
         .ok()
         .flatten();
 
@@ -1,14 +1,21 @@
 pub mod account_merge_detector;
 pub mod aggregation;
 pub mod analytics;
+pub mod anomaly_detection;
 pub mod asset_verifier;
 pub mod contract;
+pub mod contract_event_indexer;
+pub mod dex_aggregator;
 pub mod fee_bump_tracker;
+pub mod forecasting;
 pub mod governance;
 pub mod indexing;
 pub mod liquidity_pool_analyzer;
+pub mod path_finder;
 pub mod price_feed;
 pub mod realtime_broadcaster;
+pub mod rollup;
+pub mod sep_transfer_recorder;
 pub mod slack_bot;
 pub mod snapshot;
 pub mod stellar_toml;
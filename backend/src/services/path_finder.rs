@@ -0,0 +1,863 @@
+//! Payment quotes across the Stellar DEX.
+//!
+//! A [`LiquidityGraph`] holds the constant-product pools from Horizon and the order
+//! books between the payment's assets and a few hub assets. Paths of up to
+//! [`MAX_HOPS`] hops are quoted the way a path payment executes: each hop crosses
+//! the book level by level or trades against the pool invariant, whichever delivers
+//! more, so quotes include the slippage of the requested amount.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::rpc::{Asset, HorizonLiquidityPool, OrderBook, PaymentPath, StellarRpcClient};
+
+/// Longest path quoted. Path payments allow more, but longer paths rarely win.
+pub const MAX_HOPS: usize = 3;
+
+/// Asset id of lumens
+pub const NATIVE: &str = "native";
+
+const USDC_ASSET: &str = "USDC:GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN";
+
+#[derive(Debug, Clone)]
+pub struct PathFinderConfig {
+    /// Assets whose order books with the payment's assets are loaded, as
+    /// `native` or `CODE:ISSUER`
+    pub hub_assets: Vec<String>,
+    /// Pages of 200 pools loaded from Horizon
+    pub pool_pages: u32,
+    /// Price levels loaded per order book
+    pub book_depth: u32,
+}
+
+impl Default for PathFinderConfig {
+    fn default() -> Self {
+        Self {
+            hub_assets: vec![NATIVE.to_string(), USDC_ASSET.to_string()],
+            pool_pages: 5,
+            book_depth: 50,
+        }
+    }
+}
+
+impl PathFinderConfig {
+    /// Reads `PATH_FINDER_HUB_ASSETS` and `PATH_FINDER_POOL_PAGES`, falling back to
+    /// the defaults.
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(value) = std::env::var("PATH_FINDER_HUB_ASSETS") {
+            let hubs: Vec<String> = value
+                .split(',')
+                .map(str::trim)
+                .filter(|id| Asset::from_id(id).is_some())
+                .map(str::to_string)
+                .collect();
+            if !hubs.is_empty() {
+                config.hub_assets = hubs;
+            }
+        }
+        if let Some(pages) = std::env::var("PATH_FINDER_POOL_PAGES")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .filter(|pages| *pages >= 1)
+        {
+            config.pool_pages = pages;
+        }
+        config
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VenueKind {
+    OrderBook,
+    LiquidityPool,
+}
+
+impl VenueKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::OrderBook => "order_book",
+            Self::LiquidityPool => "liquidity_pool",
+        }
+    }
+}
+
+/// Offers at one price, seen from the taker
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookLevel {
+    /// Units sold per unit received
+    pub price: f64,
+    /// Units available to receive
+    pub amount: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Venue {
+    OrderBook {
+        /// Best level first
+        levels: Vec<BookLevel>,
+        /// Midpoint of the best bid and ask, in units sold per unit received
+        mid_price: f64,
+    },
+    Pool {
+        id: String,
+        reserve_in: f64,
+        reserve_out: f64,
+        fee_bp: u32,
+    },
+}
+
+/// A venue selling `from` for `to`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Market {
+    pub from: String,
+    pub to: String,
+    pub venue: Venue,
+}
+
+impl Market {
+    pub fn kind(&self) -> VenueKind {
+        match self.venue {
+            Venue::OrderBook { .. } => VenueKind::OrderBook,
+            Venue::Pool { .. } => VenueKind::LiquidityPool,
+        }
+    }
+
+    fn pool_id(&self) -> Option<String> {
+        match &self.venue {
+            Venue::Pool { id, .. } => Some(id.clone()),
+            Venue::OrderBook { .. } => None,
+        }
+    }
+
+    /// Units received per unit sold at the mid price
+    pub fn mid_rate(&self) -> f64 {
+        match &self.venue {
+            Venue::OrderBook { mid_price, .. } => 1.0 / mid_price,
+            Venue::Pool {
+                reserve_in,
+                reserve_out,
+                ..
+            } => reserve_out / reserve_in,
+        }
+    }
+
+    /// Units received per unit sold for an infinitesimal trade, before fees
+    pub fn spot_rate(&self) -> f64 {
+        match &self.venue {
+            Venue::OrderBook { levels, .. } => levels.first().map_or(0.0, |l| 1.0 / l.price),
+            Venue::Pool { .. } => self.mid_rate(),
+        }
+    }
+
+    /// Share of the amount sold taken as a fee
+    pub fn fee(&self) -> f64 {
+        match &self.venue {
+            Venue::OrderBook { .. } => 0.0,
+            Venue::Pool { fee_bp, .. } => f64::from(*fee_bp) / 10_000.0,
+        }
+    }
+
+    /// Units received for selling `amount_in`, or `None` if the venue is too shallow
+    pub fn sell(&self, amount_in: f64) -> Option<f64> {
+        match &self.venue {
+            Venue::OrderBook { levels, .. } => {
+                let mut remaining = amount_in;
+                let mut received = 0.0;
+                for level in levels {
+                    let cost = level.amount * level.price;
+                    if remaining <= cost {
+                        return Some(received + remaining / level.price);
+                    }
+                    received += level.amount;
+                    remaining -= cost;
+                }
+                None
+            }
+            Venue::Pool {
+                reserve_in,
+                reserve_out,
+                ..
+            } => {
+                let effective_in = amount_in * (1.0 - self.fee());
+                Some(reserve_out * effective_in / (reserve_in + effective_in))
+            }
+        }
+    }
+
+    /// Units sold to receive `amount_out`, or `None` if the venue is too shallow
+    pub fn buy(&self, amount_out: f64) -> Option<f64> {
+        match &self.venue {
+            Venue::OrderBook { levels, .. } => {
+                let mut remaining = amount_out;
+                let mut spent = 0.0;
+                for level in levels {
+                    if remaining <= level.amount {
+                        return Some(spent + remaining * level.price);
+                    }
+                    spent += level.amount * level.price;
+                    remaining -= level.amount;
+                }
+                None
+            }
+            Venue::Pool {
+                reserve_in,
+                reserve_out,
+                ..
+            } => {
+                if amount_out >= *reserve_out {
+                    return None;
+                }
+                Some(reserve_in * amount_out / ((reserve_out - amount_out) * (1.0 - self.fee())))
+            }
+        }
+    }
+}
+
+/// One executed hop of a quoted path
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuotedHop {
+    pub from: String,
+    pub to: String,
+    pub venue: VenueKind,
+    pub pool_id: Option<String>,
+    pub amount_in: f64,
+    pub amount_out: f64,
+}
+
+/// A path quoted for a specific amount
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PathQuote {
+    pub hops: Vec<QuotedHop>,
+    pub source_amount: f64,
+    pub destination_amount: f64,
+    /// Destination units per source unit at the mid price of every hop
+    pub mid_rate: f64,
+    /// Destination units per source unit for an infinitesimal amount, before fees
+    pub spot_rate: f64,
+    /// Share of the amount left after every hop's fee
+    pub fee_factor: f64,
+}
+
+impl PathQuote {
+    fn new(hops: Vec<QuotedHop>, markets: &[&Market]) -> Option<Self> {
+        let source_amount = hops.first()?.amount_in;
+        let destination_amount = hops.last()?.amount_out;
+        Some(Self {
+            hops,
+            source_amount,
+            destination_amount,
+            mid_rate: markets.iter().map(|m| m.mid_rate()).product(),
+            spot_rate: markets.iter().map(|m| m.spot_rate()).product(),
+            fee_factor: markets.iter().map(|m| 1.0 - m.fee()).product(),
+        })
+    }
+
+    /// Assets along the path, source first
+    pub fn assets(&self) -> Vec<String> {
+        let mut assets: Vec<String> = self.hops.iter().map(|hop| hop.from.clone()).collect();
+        if let Some(last) = self.hops.last() {
+            assets.push(last.to.clone());
+        }
+        assets
+    }
+
+    /// Distance of the best prices from the mid prices
+    pub fn spread_bps(&self) -> f64 {
+        ((1.0 - self.spot_rate / self.mid_rate) * 10_000.0).max(0.0)
+    }
+
+    /// Shortfall of the executed amount against the best prices after fees
+    pub fn slippage_bps(&self) -> f64 {
+        let at_spot = self.source_amount * self.spot_rate * self.fee_factor;
+        if at_spot <= 0.0 {
+            return 0.0;
+        }
+        ((1.0 - self.destination_amount / at_spot) * 10_000.0).max(0.0)
+    }
+}
+
+/// Order books and pools available to a payment
+#[derive(Debug, Clone, Default)]
+pub struct LiquidityGraph {
+    markets: Vec<Market>,
+    /// Pool reserves held of each asset, to pick an issuer for bare asset codes
+    pool_reserves: HashMap<String, f64>,
+}
+
+impl LiquidityGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn markets(&self) -> &[Market] {
+        &self.markets
+    }
+
+    pub fn add_market(&mut self, market: Market) {
+        self.markets.push(market);
+    }
+
+    /// Add both directions of a constant-product pool
+    pub fn add_pool(&mut self, pool: &HorizonLiquidityPool) {
+        if pool.pool_type != "constant_product" || pool.reserves.len() != 2 {
+            return;
+        }
+        let reserves: Vec<(String, f64)> = pool
+            .reserves
+            .iter()
+            .filter_map(|r| Some((r.asset.clone(), r.amount.parse::<f64>().ok()?)))
+            .filter(|(_, amount)| *amount > 0.0)
+            .collect();
+        let [(asset_a, amount_a), (asset_b, amount_b)] = reserves.as_slice() else {
+            return;
+        };
+
+        for (asset, amount) in [(asset_a, amount_a), (asset_b, amount_b)] {
+            *self.pool_reserves.entry(asset.clone()).or_default() += amount;
+        }
+        for ((from, reserve_in), (to, reserve_out)) in [
+            ((asset_a, amount_a), (asset_b, amount_b)),
+            ((asset_b, amount_b), (asset_a, amount_a)),
+        ] {
+            self.markets.push(Market {
+                from: from.clone(),
+                to: to.clone(),
+                venue: Venue::Pool {
+                    id: pool.id.clone(),
+                    reserve_in: *reserve_in,
+                    reserve_out: *reserve_out,
+                    fee_bp: pool.fee_bp,
+                },
+            });
+        }
+    }
+
+    /// Add the asks of a Horizon order book: selling the counter asset for the base.
+    /// Empty books are skipped.
+    pub fn add_order_book(&mut self, book: &OrderBook) {
+        let levels: Vec<BookLevel> = book
+            .asks
+            .iter()
+            .filter_map(|entry| {
+                let price = entry_price(entry.price_r.n, entry.price_r.d, &entry.price)?;
+                let amount = entry.amount.parse::<f64>().ok().filter(|a| *a > 0.0)?;
+                Some(BookLevel { price, amount })
+            })
+            .collect();
+        let Some(best_ask) = levels.first().map(|l| l.price) else {
+            return;
+        };
+        let mid_price = book
+            .bids
+            .first()
+            .and_then(|bid| entry_price(bid.price_r.n, bid.price_r.d, &bid.price))
+            .filter(|bid| *bid <= best_ask)
+            .map_or(best_ask, |bid| (bid + best_ask) / 2.0);
+
+        self.markets.push(Market {
+            from: book.counter.id(),
+            to: book.base.id(),
+            venue: Venue::OrderBook { levels, mid_price },
+        });
+    }
+
+    /// Asset id for a currency: `native` for XLM, `CODE:ISSUER` as given, and for a
+    /// bare code the asset with that code holding the most pool reserves. USD and EUR
+    /// resolve to their stablecoins.
+    pub fn resolve_asset(&self, currency: &str, hub_assets: &[String]) -> Option<String> {
+        if let Some((code, issuer)) = currency.split_once(':') {
+            if issuer.eq_ignore_ascii_case(NATIVE) {
+                return code.eq_ignore_ascii_case("XLM").then(|| NATIVE.to_string());
+            }
+            return Asset::from_id(currency).map(|asset| asset.id());
+        }
+
+        let code = match currency {
+            "XLM" => return Some(NATIVE.to_string()),
+            "USD" => "USDC",
+            "EUR" => "EURC",
+            other => other,
+        };
+        let code_prefix = format!("{code}:");
+        self.pool_reserves
+            .iter()
+            .filter(|(id, _)| id.starts_with(&code_prefix))
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(id, _)| id.clone())
+            .or_else(|| {
+                hub_assets
+                    .iter()
+                    .find(|id| id.starts_with(&code_prefix))
+                    .cloned()
+            })
+    }
+
+    /// The path delivering the most `destination` for exactly `amount` of `source`
+    pub fn best_strict_send(
+        &self,
+        source: &str,
+        destination: &str,
+        amount: f64,
+        venues: &[VenueKind],
+        max_hops: usize,
+    ) -> Option<PathQuote> {
+        self.candidate_paths(source, destination, venues, max_hops)
+            .iter()
+            .filter_map(|path| self.quote_send(path, amount, venues))
+            .max_by(|a, b| a.destination_amount.total_cmp(&b.destination_amount))
+    }
+
+    /// The path needing the least `source` to deliver exactly `amount` of `destination`
+    pub fn best_strict_receive(
+        &self,
+        source: &str,
+        destination: &str,
+        amount: f64,
+        venues: &[VenueKind],
+        max_hops: usize,
+    ) -> Option<PathQuote> {
+        self.candidate_paths(source, destination, venues, max_hops)
+            .iter()
+            .filter_map(|path| self.quote_receive(path, amount, venues))
+            .min_by(|a, b| a.source_amount.total_cmp(&b.source_amount))
+    }
+
+    fn markets_between<'a>(
+        &'a self,
+        from: &'a str,
+        to: &'a str,
+        venues: &'a [VenueKind],
+    ) -> impl Iterator<Item = &'a Market> + 'a {
+        self.markets
+            .iter()
+            .filter(move |m| m.from == from && m.to == to && venues.contains(&m.kind()))
+    }
+
+    /// Asset sequences from `source` to `destination` without repeated assets
+    fn candidate_paths(
+        &self,
+        source: &str,
+        destination: &str,
+        venues: &[VenueKind],
+        max_hops: usize,
+    ) -> Vec<Vec<String>> {
+        let mut neighbours: HashMap<&str, HashSet<&str>> = HashMap::new();
+        for market in self.markets.iter().filter(|m| venues.contains(&m.kind())) {
+            neighbours
+                .entry(market.from.as_str())
+                .or_default()
+                .insert(market.to.as_str());
+        }
+
+        let mut paths = Vec::new();
+        let mut stack = vec![vec![source]];
+        while let Some(path) = stack.pop() {
+            let Some(next) = path.last().and_then(|last| neighbours.get(last)) else {
+                continue;
+            };
+            let hops = path.len();
+            for asset in next {
+                if *asset == destination {
+                    let mut complete: Vec<String> = path.iter().map(ToString::to_string).collect();
+                    complete.push(destination.to_string());
+                    paths.push(complete);
+                } else if hops < max_hops && !path.contains(asset) {
+                    let mut extended = path.clone();
+                    extended.push(asset);
+                    stack.push(extended);
+                }
+            }
+        }
+        paths
+    }
+
+    fn quote_send(&self, path: &[String], amount: f64, venues: &[VenueKind]) -> Option<PathQuote> {
+        let mut hops = Vec::with_capacity(path.len() - 1);
+        let mut markets = Vec::with_capacity(path.len() - 1);
+        let mut amount_in = amount;
+        for pair in path.windows(2) {
+            let (market, amount_out) = self
+                .markets_between(&pair[0], &pair[1], venues)
+                .filter_map(|m| Some((m, m.sell(amount_in)?)))
+                .max_by(|a, b| a.1.total_cmp(&b.1))?;
+            hops.push(hop(market, amount_in, amount_out));
+            markets.push(market);
+            amount_in = amount_out;
+        }
+        PathQuote::new(hops, &markets)
+    }
+
+    fn quote_receive(
+        &self,
+        path: &[String],
+        amount: f64,
+        venues: &[VenueKind],
+    ) -> Option<PathQuote> {
+        let mut hops = Vec::with_capacity(path.len() - 1);
+        let mut markets = Vec::with_capacity(path.len() - 1);
+        let mut amount_out = amount;
+        for pair in path.windows(2).rev() {
+            let (market, amount_in) = self
+                .markets_between(&pair[0], &pair[1], venues)
+                .filter_map(|m| Some((m, m.buy(amount_out)?)))
+                .min_by(|a, b| a.1.total_cmp(&b.1))?;
+            hops.push(hop(market, amount_in, amount_out));
+            markets.push(market);
+            amount_out = amount_in;
+        }
+        hops.reverse();
+        markets.reverse();
+        PathQuote::new(hops, &markets)
+    }
+}
+
+fn hop(market: &Market, amount_in: f64, amount_out: f64) -> QuotedHop {
+    QuotedHop {
+        from: market.from.clone(),
+        to: market.to.clone(),
+        venue: market.kind(),
+        pool_id: market.pool_id(),
+        amount_in,
+        amount_out,
+    }
+}
+
+/// Exact price from its fraction, falling back to the decimal string
+fn entry_price(n: i64, d: i64, decimal: &str) -> Option<f64> {
+    let price = if d > 0 {
+        n as f64 / d as f64
+    } else {
+        decimal.parse().ok()?
+    };
+    (price > 0.0 && price.is_finite()).then_some(price)
+}
+
+/// A path found by Horizon, for comparison with our own quotes
+#[derive(Debug, Clone, Serialize)]
+pub struct HorizonPathQuote {
+    pub source_amount: f64,
+    pub destination_amount: f64,
+    /// Assets along the path, source first
+    pub path: Vec<String>,
+}
+
+impl HorizonPathQuote {
+    fn from_path(path: &PaymentPath) -> Option<Self> {
+        let source = asset_id(
+            &path.source_asset_type,
+            &path.source_asset_code,
+            &path.source_asset_issuer,
+        );
+        let destination = asset_id(
+            &path.destination_asset_type,
+            &path.destination_asset_code,
+            &path.destination_asset_issuer,
+        );
+        let mut assets = vec![source];
+        assets.extend(path.path.iter().map(Asset::id));
+        assets.push(destination);
+
+        Some(Self {
+            source_amount: path.source_amount.parse().ok()?,
+            destination_amount: path.destination_amount.parse().ok()?,
+            path: assets,
+        })
+    }
+}
+
+fn asset_id(asset_type: &str, code: &Option<String>, issuer: &Option<String>) -> String {
+    Asset {
+        asset_type: asset_type.to_string(),
+        asset_code: code.clone(),
+        asset_issuer: issuer.clone(),
+    }
+    .id()
+}
+
+/// Loads market data from Horizon and quotes payments against it
+pub struct PathFinder {
+    rpc_client: Arc<StellarRpcClient>,
+    config: PathFinderConfig,
+}
+
+impl PathFinder {
+    pub fn new(rpc_client: Arc<StellarRpcClient>, config: PathFinderConfig) -> Self {
+        Self { rpc_client, config }
+    }
+
+    pub fn config(&self) -> &PathFinderConfig {
+        &self.config
+    }
+
+    /// Graph of the current constant-product pools
+    pub async fn load_pools(&self) -> Result<LiquidityGraph> {
+        let mut graph = LiquidityGraph::new();
+        let mut cursor: Option<String> = None;
+        for _ in 0..self.config.pool_pages {
+            let pools = self
+                .rpc_client
+                .fetch_liquidity_pools(200, cursor.as_deref())
+                .await
+                .context("Failed to fetch liquidity pools")?;
+            for pool in &pools {
+                graph.add_pool(pool);
+            }
+            cursor = pools.last().and_then(|pool| pool.paging_token.clone());
+            if pools.len() < 200 || cursor.is_none() {
+                break;
+            }
+        }
+        Ok(graph)
+    }
+
+    /// Add the order books in both directions between every pair of `assets` and the
+    /// hub assets. Books Horizon can't serve are skipped.
+    pub async fn load_order_books(&self, graph: &mut LiquidityGraph, assets: &[&str]) {
+        let mut ids: Vec<&str> = assets.to_vec();
+        for hub in &self.config.hub_assets {
+            if !ids.contains(&hub.as_str()) {
+                ids.push(hub);
+            }
+        }
+        let pairs: Vec<(Asset, Asset)> = ids
+            .iter()
+            .flat_map(|a| ids.iter().map(move |b| (*a, *b)))
+            .filter(|(a, b)| a != b)
+            .filter_map(|(a, b)| Some((Asset::from_id(a)?, Asset::from_id(b)?)))
+            .collect();
+
+        let books = futures::future::join_all(pairs.iter().map(|(base, counter)| {
+            self.rpc_client
+                .fetch_order_book(base, counter, self.config.book_depth)
+        }))
+        .await;
+
+        for ((base, counter), book) in pairs.iter().zip(books) {
+            match book {
+                Ok(book) => graph.add_order_book(&book),
+                Err(e) => tracing::debug!(
+                    "Skipping order book {} / {}: {}",
+                    base.id(),
+                    counter.id(),
+                    e
+                ),
+            }
+        }
+    }
+
+    /// Horizon's best strict-send path, if it finds one
+    pub async fn horizon_strict_send(
+        &self,
+        source: &str,
+        amount: f64,
+        destination: &str,
+    ) -> Option<HorizonPathQuote> {
+        let source_asset = Asset::from_id(source)?;
+        let destination_asset = Asset::from_id(destination)?;
+        let paths = self
+            .rpc_client
+            .fetch_strict_send_paths(
+                &source_asset,
+                &format!("{:.7}", amount),
+                &[destination_asset],
+            )
+            .await
+            .map_err(|e| tracing::warn!("Horizon strict-send path finding failed: {}", e))
+            .ok()?;
+
+        paths
+            .iter()
+            .filter_map(HorizonPathQuote::from_path)
+            .max_by(|a, b| a.destination_amount.total_cmp(&b.destination_amount))
+    }
+
+    /// Horizon's best strict-receive path, if it finds one
+    pub async fn horizon_strict_receive(
+        &self,
+        source: &str,
+        destination: &str,
+        amount: f64,
+    ) -> Option<HorizonPathQuote> {
+        let source_asset = Asset::from_id(source)?;
+        let destination_asset = Asset::from_id(destination)?;
+        let paths = self
+            .rpc_client
+            .fetch_strict_receive_paths(
+                &[source_asset],
+                &destination_asset,
+                &format!("{:.7}", amount),
+            )
+            .await
+            .map_err(|e| tracing::warn!("Horizon strict-receive path finding failed: {}", e))
+            .ok()?;
+
+        paths
+            .iter()
+            .filter_map(HorizonPathQuote::from_path)
+            .min_by(|a, b| a.source_amount.total_cmp(&b.source_amount))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::{HorizonPoolReserve, OrderBookEntry, Price};
+
+    const USDC: &str = "USDC:GA";
+    const EURC: &str = "EURC:GB";
+
+    fn pool(id: &str, a: (&str, f64), b: (&str, f64)) -> HorizonLiquidityPool {
+        HorizonLiquidityPool {
+            id: id.to_string(),
+            fee_bp: 30,
+            pool_type: "constant_product".to_string(),
+            total_trustlines: 1,
+            total_shares: "1".to_string(),
+            reserves: vec![
+                HorizonPoolReserve {
+                    asset: a.0.to_string(),
+                    amount: a.1.to_string(),
+                },
+                HorizonPoolReserve {
+                    asset: b.0.to_string(),
+                    amount: b.1.to_string(),
+                },
+            ],
+            paging_token: None,
+        }
+    }
+
+    fn book(from: &str, to: &str, mid_price: f64, levels: &[(f64, f64)]) -> Market {
+        Market {
+            from: from.to_string(),
+            to: to.to_string(),
+            venue: Venue::OrderBook {
+                levels: levels
+                    .iter()
+                    .map(|&(price, amount)| BookLevel { price, amount })
+                    .collect(),
+                mid_price,
+            },
+        }
+    }
+
+    #[test]
+    fn test_order_book_walks_levels() {
+        let market = book(USDC, NATIVE, 0.1, &[(0.1, 100.0), (0.2, 100.0)]);
+        // 10 buys the first level, the next 10 half of the second
+        assert_eq!(market.sell(10.0), Some(100.0));
+        assert!((market.sell(20.0).unwrap() - 150.0).abs() < 1e-9);
+        assert!((market.buy(150.0).unwrap() - 20.0).abs() < 1e-9);
+        assert_eq!(market.sell(40.0), None);
+        assert_eq!(market.buy(250.0), None);
+    }
+
+    #[test]
+    fn test_pool_applies_invariant_and_fee() {
+        let mut graph = LiquidityGraph::new();
+        graph.add_pool(&pool("p1", (USDC, 1_000.0), (NATIVE, 10_000.0)));
+        let market = &graph.markets()[0];
+
+        let out = market.sell(100.0).unwrap();
+        let effective_in = 100.0 * 0.997;
+        assert!((out - 10_000.0 * effective_in / (1_000.0 + effective_in)).abs() < 1e-9);
+        assert!((market.buy(out).unwrap() - 100.0).abs() < 1e-9);
+        assert_eq!(market.buy(10_000.0), None);
+    }
+
+    #[test]
+    fn test_best_path_uses_hub_when_direct_market_is_shallow() {
+        let mut graph = LiquidityGraph::new();
+        graph.add_pool(&pool("direct", (USDC, 100.0), (EURC, 92.0)));
+        graph.add_pool(&pool("usdc-xlm", (USDC, 100_000.0), (NATIVE, 1_000_000.0)));
+        graph.add_pool(&pool("xlm-eurc", (NATIVE, 1_000_000.0), (EURC, 92_000.0)));
+        let venues = [VenueKind::LiquidityPool];
+
+        let direct = graph
+            .best_strict_send(USDC, EURC, 50.0, &venues, 1)
+            .unwrap();
+        let best = graph
+            .best_strict_send(USDC, EURC, 50.0, &venues, MAX_HOPS)
+            .unwrap();
+        assert_eq!(best.assets(), vec![USDC, NATIVE, EURC]);
+        assert!(best.destination_amount > direct.destination_amount);
+        assert!(direct.slippage_bps() > best.slippage_bps());
+
+        let receive = graph
+            .best_strict_receive(USDC, EURC, best.destination_amount, &venues, MAX_HOPS)
+            .unwrap();
+        assert!((receive.source_amount - 50.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_quote_decomposes_spread_fees_and_slippage() {
+        let mut graph = LiquidityGraph::new();
+        graph.add_market(book(
+            USDC,
+            NATIVE,
+            0.1,
+            &[(0.101, 1_000.0), (0.102, 1_000.0)],
+        ));
+        graph.add_pool(&pool("xlm-eurc", (NATIVE, 1_000_000.0), (EURC, 92_000.0)));
+        let venues = [VenueKind::OrderBook, VenueKind::LiquidityPool];
+
+        let quote = graph
+            .best_strict_send(USDC, EURC, 150.0, &venues, MAX_HOPS)
+            .unwrap();
+        assert_eq!(quote.hops[0].venue, VenueKind::OrderBook);
+        assert_eq!(quote.hops[1].pool_id.as_deref(), Some("xlm-eurc"));
+        assert!((quote.mid_rate - 10.0 * 0.092).abs() < 1e-12);
+        assert!((quote.spread_bps() - (1.0 - 0.1 / 0.101) * 10_000.0).abs() < 1e-6);
+        assert!((quote.fee_factor - 0.997).abs() < 1e-12);
+        assert!(quote.slippage_bps() > 0.0);
+        assert!(
+            quote.destination_amount < quote.source_amount * quote.spot_rate * quote.fee_factor
+        );
+    }
+
+    #[test]
+    fn test_order_book_asks_sell_counter_for_base() {
+        let entry = |price: &str, n: i64, d: i64| OrderBookEntry {
+            price: price.to_string(),
+            amount: "100.0".to_string(),
+            price_r: Price { n, d },
+        };
+        let mut graph = LiquidityGraph::new();
+        graph.add_order_book(&OrderBook {
+            bids: vec![entry("0.09", 9, 100)],
+            asks: vec![entry("0.11", 11, 100)],
+            base: Asset::from_id(NATIVE).unwrap(),
+            counter: Asset::from_id(USDC).unwrap(),
+        });
+
+        let market = &graph.markets()[0];
+        assert_eq!((market.from.as_str(), market.to.as_str()), (USDC, NATIVE));
+        assert!((market.mid_rate() - 10.0).abs() < 1e-9);
+        assert!((market.sell(11.0).unwrap() - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_resolve_asset() {
+        let mut graph = LiquidityGraph::new();
+        graph.add_pool(&pool("a", ("NGNC:GSMALL", 10.0), (NATIVE, 10.0)));
+        graph.add_pool(&pool("b", ("NGNC:GLARGE", 1_000.0), (NATIVE, 10.0)));
+        let hubs = vec![NATIVE.to_string(), USDC.to_string()];
+
+        assert_eq!(graph.resolve_asset("XLM", &hubs).as_deref(), Some(NATIVE));
+        assert_eq!(
+            graph.resolve_asset("XLM:native", &hubs).as_deref(),
+            Some(NATIVE)
+        );
+        assert_eq!(
+            graph.resolve_asset("NGNC", &hubs).as_deref(),
+            Some("NGNC:GLARGE")
+        );
+        assert_eq!(graph.resolve_asset("USD", &hubs).as_deref(), Some(USDC));
+        assert_eq!(graph.resolve_asset("EURC:GB", &hubs).as_deref(), Some(EURC));
+        assert_eq!(graph.resolve_asset("NGN", &hubs), None);
+    }
+}
@@ -1,24 +1,131 @@
 use crate::cache::CacheManager;
 use crate::database::Database;
-use crate::models::corridor::CorridorMetrics;
+use crate::models::corridor::{Corridor, CorridorMetrics};
 use crate::models::{AnchorMetrics, AnchorStatus, PaymentRecord};
-use crate::rpc::StellarRpcClient;
+use crate::rpc::{AccountPayment, HorizonStream, StellarRpcClient, StreamEvent};
 use crate::websocket::{WsMessage, WsState};
+use chrono::{DateTime, Timelike, Utc};
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
+/// Minimum spacing between streamed `CorridorUpdate`s for one corridor
+const CORRIDOR_UPDATE_MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Running totals for a corridor over the current UTC hour, built from the
+/// live payment stream
+#[derive(Debug, Clone)]
+struct LiveCorridorStats {
+    corridor: Corridor,
+    hour: DateTime<Utc>,
+    total: u64,
+    successful: u64,
+    volume: f64,
+    last_broadcast: Option<Instant>,
+}
+
+impl LiveCorridorStats {
+    fn new(corridor: Corridor, hour: DateTime<Utc>) -> Self {
+        Self {
+            corridor,
+            hour,
+            total: 0,
+            successful: 0,
+            volume: 0.0,
+            last_broadcast: None,
+        }
+    }
+
+    fn record(&mut self, at: DateTime<Utc>, amount: f64, successful: bool) {
+        let hour = truncate_to_hour(at);
+        if hour > self.hour {
+            *self = Self::new(self.corridor.clone(), hour);
+        }
+        self.total += 1;
+        if successful {
+            self.successful += 1;
+            self.volume += amount;
+        }
+    }
+
+    fn success_rate(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.successful as f64 / self.total as f64 * 100.0
+        }
+    }
+
+    /// Whether enough time has passed since the last update was sent
+    fn should_broadcast(&mut self, now: Instant) -> bool {
+        match self.last_broadcast {
+            Some(last) if now.duration_since(last) < CORRIDOR_UPDATE_MIN_INTERVAL => false,
+            _ => {
+                self.last_broadcast = Some(now);
+                true
+            }
+        }
+    }
+}
+
+/// Live stats of every corridor with payments in the current UTC hour.
+///
+/// Corridors are dropped once the stream moves past the hour they were last
+/// active in, so the table only holds corridors seen this hour.
+#[derive(Debug, Default)]
+struct LiveCorridorTable {
+    hour: Option<DateTime<Utc>>,
+    corridors: HashMap<String, LiveCorridorStats>,
+}
+
+impl LiveCorridorTable {
+    /// Stats for `corridor` after recording a payment made at `at`, or `None`
+    /// for a late payment from an hour the table has already moved past
+    fn record(
+        &mut self,
+        corridor: Corridor,
+        at: DateTime<Utc>,
+        amount: f64,
+        successful: bool,
+    ) -> Option<&mut LiveCorridorStats> {
+        let hour = truncate_to_hour(at);
+        match self.hour {
+            Some(current) if hour < current => return None,
+            Some(current) if hour == current => {}
+            _ => {
+                self.hour = Some(hour);
+                self.corridors.retain(|_, stats| stats.hour >= hour);
+            }
+        }
+
+        let stats = self
+            .corridors
+            .entry(corridor.to_string_key())
+            .or_insert_with(|| LiveCorridorStats::new(corridor, hour));
+        stats.record(at, amount, successful);
+        Some(stats)
+    }
+}
+
+fn truncate_to_hour(at: DateTime<Utc>) -> DateTime<Utc> {
+    at.with_minute(0)
+        .and_then(|t| t.with_second(0))
+        .and_then(|t| t.with_nanosecond(0))
+        .unwrap_or(at)
+}
+
 /// Real-time broadcaster service for WebSocket updates
 pub struct RealtimeBroadcaster {
     /// WebSocket state for managing connections
     ws_state: Arc<WsState>,
     /// Database for fetching data
     db: Arc<Database>,
-    /// RPC client for fetching data (reserved for future real-time data fetching)
-    _rpc_client: Arc<StellarRpcClient>,
+    /// RPC client for the live Horizon payment stream
+    rpc_client: Arc<StellarRpcClient>,
     /// Cache manager for data access (reserved for future caching optimizations)
     _cache: Arc<CacheManager>,
     /// Per-connection subscriptions
@@ -79,7 +186,7 @@ impl RealtimeBroadcaster {
         Self {
             ws_state,
             db,
-            _rpc_client: rpc_client,
+            rpc_client,
             _cache: cache,
             subscriptions: Arc::new(DashMap::new()),
             shutdown_rx: Some(shutdown_rx),
@@ -96,8 +203,16 @@ impl RealtimeBroadcaster {
             .take()
             .expect("Shutdown receiver already taken");
 
-        // Start corridor metrics broadcasting task
-        let corridor_task = self.start_corridor_broadcast_task();
+        // Push corridor updates from the Horizon payment stream, or fall back
+        // to periodic polling when streaming is disabled
+        let streaming_enabled = std::env::var("HORIZON_STREAMING_ENABLED")
+            .map(|v| v != "false" && v != "0")
+            .unwrap_or(true);
+        let corridor_task = if streaming_enabled {
+            self.start_payment_stream_task()
+        } else {
+            self.start_corridor_broadcast_task()
+        };
 
         // Start subscription management task
         let subscription_task = self.start_subscription_management_task();
@@ -157,6 +272,96 @@ impl RealtimeBroadcaster {
         })
     }
 
+    /// Start the task relaying streamed Horizon payments as `NewPayment` and
+    /// `CorridorUpdate` messages
+    fn start_payment_stream_task(&self) -> tokio::task::JoinHandle<()> {
+        let ws_state = Arc::clone(&self.ws_state);
+        let mut payments = self.rpc_client.stream(HorizonStream::Payments, None);
+
+        tokio::spawn(async move {
+            info!("Payment stream broadcast task started");
+            let mut live_stats = LiveCorridorTable::default();
+
+            while let Some(event) = payments.recv().await {
+                if let StreamEvent::Payment(payment) = event {
+                    Self::publish_streamed_payment(&ws_state, &mut live_stats, &payment).await;
+                }
+            }
+
+            warn!("Horizon payment stream ended");
+        })
+    }
+
+    async fn publish_streamed_payment(
+        ws_state: &WsState,
+        live_stats: &mut LiveCorridorTable,
+        record: &AccountPayment,
+    ) {
+        let corridor = Self::payment_corridor(record);
+        let corridor_key = corridor.to_string_key();
+        let channel = format!("corridor:{}", corridor_key);
+        let amount = record.payment.get_amount().parse::<f64>().unwrap_or(0.0);
+        let successful = record.is_successful();
+        let timestamp = DateTime::parse_from_rfc3339(&record.payment.created_at)
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now());
+
+        ws_state
+            .broadcast_to_channel(
+                &channel,
+                WsMessage::NewPayment {
+                    corridor_id: corridor_key.clone(),
+                    amount,
+                    successful,
+                    timestamp: timestamp.to_rfc3339(),
+                },
+            )
+            .await;
+
+        let Some(stats) = live_stats.record(corridor, timestamp, amount, successful) else {
+            return;
+        };
+
+        if stats.should_broadcast(Instant::now()) {
+            let message = WsMessage::CorridorUpdate {
+                corridor_key,
+                asset_a_code: stats.corridor.asset_a_code.clone(),
+                asset_a_issuer: stats.corridor.asset_a_issuer.clone(),
+                asset_b_code: stats.corridor.asset_b_code.clone(),
+                asset_b_issuer: stats.corridor.asset_b_issuer.clone(),
+                success_rate: Some(stats.success_rate()),
+                // Partial-hour stream totals are not enough for a health score
+                health_score: None,
+                last_updated: Some(timestamp.to_rfc3339()),
+            };
+            ws_state.broadcast_to_channel(&channel, message).await;
+        }
+    }
+
+    /// Corridor a payment moves value along. Path payments go from the source
+    /// asset to the delivered asset; plain payments stay within one asset.
+    fn payment_corridor(record: &AccountPayment) -> Corridor {
+        let payment = &record.payment;
+        let destination = (
+            payment
+                .get_asset_code()
+                .unwrap_or_else(|| "XLM".to_string()),
+            payment
+                .get_asset_issuer()
+                .unwrap_or_else(|| "native".to_string()),
+        );
+        let source = match payment.source_asset_type.as_deref() {
+            Some("native") => ("XLM".to_string(), "native".to_string()),
+            Some(_) => (
+                payment.source_asset_code.clone().unwrap_or_default(),
+                payment.source_asset_issuer.clone().unwrap_or_default(),
+            ),
+            None => destination.clone(),
+        };
+
+        Corridor::new(source.0, source.1, destination.0, destination.1)
+    }
+
     /// Start the subscription management task
     fn start_subscription_management_task(&self) -> tokio::task::JoinHandle<()> {
         let ws_state = Arc::clone(&self.ws_state);
@@ -325,6 +530,7 @@ impl RealtimeBroadcaster {
         message: BroadcastMessage,
     ) {
         let ws_message = WsMessage::from_broadcast_message(message);
+        ws_state.publish(ws_message.clone());
 
         // Find all connections subscribed to this channel
         let mut target_connections = Vec::new();
@@ -432,6 +638,88 @@ impl WsMessage {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_payment_corridor_uses_path_payment_assets() {
+        let record: AccountPayment = serde_json::from_value(json!({
+            "id": "1",
+            "paging_token": "1",
+            "transaction_hash": "abc",
+            "transaction_successful": true,
+            "source_account": "GSOURCE",
+            "type": "path_payment_strict_send",
+            "created_at": "2026-01-22T10:15:00Z",
+            "asset_type": "credit_alphanum4",
+            "asset_code": "EURC",
+            "asset_issuer": "GEURC",
+            "amount": "91.0000000",
+            "source_asset_type": "native",
+            "source_amount": "1000.0000000"
+        }))
+        .unwrap();
+
+        let corridor = RealtimeBroadcaster::payment_corridor(&record);
+        assert_eq!(corridor.to_string_key(), "EURC:GEURC->XLM:native");
+    }
+
+    #[test]
+    fn test_live_corridor_stats_reset_each_hour() {
+        let corridor = Corridor::new(
+            "USDC".to_string(),
+            "GUSDC".to_string(),
+            "EURC".to_string(),
+            "GEURC".to_string(),
+        );
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        let mut stats =
+            LiveCorridorStats::new(corridor, truncate_to_hour(at("2026-01-22T10:05:00Z")));
+
+        stats.record(at("2026-01-22T10:05:00Z"), 100.0, true);
+        stats.record(at("2026-01-22T10:45:00Z"), 50.0, false);
+        assert_eq!(stats.total, 2);
+        assert!((stats.success_rate() - 50.0).abs() < f64::EPSILON);
+        assert!((stats.volume - 100.0).abs() < f64::EPSILON);
+
+        stats.record(at("2026-01-22T11:01:00Z"), 10.0, true);
+        assert_eq!(stats.total, 1);
+        assert_eq!(stats.hour, at("2026-01-22T11:00:00Z"));
+
+        let now = Instant::now();
+        assert!(stats.should_broadcast(now));
+        assert!(!stats.should_broadcast(now));
+    }
+
+    #[test]
+    fn test_live_corridor_table_drops_past_hours() {
+        let corridor = |code: &str| {
+            Corridor::new(
+                code.to_string(),
+                format!("G{}", code),
+                code.to_string(),
+                format!("G{}", code),
+            )
+        };
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        let mut table = LiveCorridorTable::default();
+
+        table.record(corridor("USDC"), at("2026-01-22T10:05:00Z"), 1.0, true);
+        table.record(corridor("EURC"), at("2026-01-22T10:10:00Z"), 1.0, true);
+        assert_eq!(table.corridors.len(), 2);
+
+        // A late payment from the previous hour is ignored
+        table.record(corridor("USDC"), at("2026-01-22T11:00:00Z"), 1.0, true);
+        assert!(table
+            .record(corridor("EURC"), at("2026-01-22T10:59:00Z"), 1.0, true)
+            .is_none());
+        assert_eq!(table.corridors.len(), 1);
+
+        table.record(corridor("NGNC"), at("2026-01-22T12:00:00Z"), 1.0, true);
+        assert_eq!(table.corridors.len(), 1);
+        assert!(table
+            .corridors
+            .contains_key(&corridor("NGNC").to_string_key()));
+    }
 
     #[test]
     fn test_subscription_management() {
@@ -0,0 +1,883 @@
+//! Multi-resolution corridor metric rollups.
+//!
+//! Hourly corridor metrics are produced by [`AggregationService`]. This module folds them
+//! into coarser buckets (hour → day → week, day → month), each derived from the next
+//! finer resolution so every level agrees with the one below it. Progress per resolution
+//! is tracked with a watermark, so a run only touches buckets that closed since the last
+//! run, and each resolution has its own retention window. Latency and payment size
+//! sketches follow the same pipeline, merged bucket by bucket alongside the metrics.
+//!
+//! [`AggregationService`]: crate::services::aggregation::AggregationService
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::{debug, info};
+
+use crate::analytics::quantile_sketch::{DistributionSummary, QuantileSketch};
+use crate::database::Database;
+use crate::db::rollups::RollupDb;
+
+/// Upper bound on points returned by a series query when no granularity is requested
+pub const DEFAULT_MAX_SERIES_POINTS: i64 = 500;
+
+/// Bucket width of a corridor metrics series.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Resolution {
+    Hour,
+    Day,
+    Week,
+    Month,
+}
+
+impl Resolution {
+    /// All resolutions, finest first
+    pub const ALL: [Resolution; 4] = [
+        Resolution::Hour,
+        Resolution::Day,
+        Resolution::Week,
+        Resolution::Month,
+    ];
+
+    /// Resolutions stored in `corridor_metrics_rollups`, in the order they must be rolled up
+    pub const ROLLED_UP: [Resolution; 3] = [Resolution::Day, Resolution::Week, Resolution::Month];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Resolution::Hour => "hour",
+            Resolution::Day => "day",
+            Resolution::Week => "week",
+            Resolution::Month => "month",
+        }
+    }
+
+    /// The finer resolution this one is derived from.
+    ///
+    /// Months are built from days rather than weeks because ISO weeks straddle month
+    /// boundaries.
+    pub fn source(&self) -> Option<Resolution> {
+        match self {
+            Resolution::Hour => None,
+            Resolution::Day => Some(Resolution::Hour),
+            Resolution::Week | Resolution::Month => Some(Resolution::Day),
+        }
+    }
+
+    /// Nominal bucket width, used to match a requested granularity. Months count as 30 days.
+    pub fn nominal_width(&self) -> Duration {
+        match self {
+            Resolution::Hour => Duration::hours(1),
+            Resolution::Day => Duration::days(1),
+            Resolution::Week => Duration::weeks(1),
+            Resolution::Month => Duration::days(30),
+        }
+    }
+
+    /// Start of the bucket containing `ts`. Weeks start on Monday (ISO 8601).
+    pub fn bucket_start(&self, ts: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            Resolution::Hour => ts
+                .with_minute(0)
+                .and_then(|t| t.with_second(0))
+                .and_then(|t| t.with_nanosecond(0))
+                .unwrap_or(ts),
+            Resolution::Day => start_of_day(ts.date_naive()),
+            Resolution::Week => {
+                let date = ts.date_naive();
+                start_of_day(date - Duration::days(date.weekday().num_days_from_monday() as i64))
+            }
+            Resolution::Month => {
+                start_of_day(NaiveDate::from_ymd_opt(ts.year(), ts.month(), 1).unwrap_or_default())
+            }
+        }
+    }
+
+    /// Start of the bucket following the one that starts at `bucket_start`
+    pub fn next_bucket(&self, bucket_start: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            Resolution::Hour | Resolution::Day | Resolution::Week => {
+                bucket_start + self.nominal_width()
+            }
+            Resolution::Month => {
+                let (year, month) = if bucket_start.month() == 12 {
+                    (bucket_start.year() + 1, 1)
+                } else {
+                    (bucket_start.year(), bucket_start.month() + 1)
+                };
+                start_of_day(NaiveDate::from_ymd_opt(year, month, 1).unwrap_or_default())
+            }
+        }
+    }
+
+    /// The coarsest resolution whose buckets are no wider than `granularity`.
+    ///
+    /// Granularities finer than an hour fall back to hourly buckets, the finest stored.
+    pub fn for_granularity(granularity: Duration) -> Resolution {
+        Resolution::ALL
+            .iter()
+            .rev()
+            .copied()
+            .find(|r| r.nominal_width() <= granularity)
+            .unwrap_or(Resolution::Hour)
+    }
+
+    /// Picks the resolution for a series query over `[start, end)`.
+    ///
+    /// Without an explicit granularity, the finest resolution that keeps the range within
+    /// [`DEFAULT_MAX_SERIES_POINTS`] buckets is used.
+    pub fn for_range(
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        granularity: Option<Duration>,
+    ) -> Resolution {
+        match granularity {
+            Some(granularity) => Resolution::for_granularity(granularity),
+            None => {
+                let min_width = (end - start) / DEFAULT_MAX_SERIES_POINTS as i32;
+                Resolution::ALL
+                    .iter()
+                    .copied()
+                    .find(|r| r.nominal_width() >= min_width)
+                    .unwrap_or(Resolution::Month)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Resolution {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "hour" => Ok(Resolution::Hour),
+            "day" => Ok(Resolution::Day),
+            "week" => Ok(Resolution::Week),
+            "month" => Ok(Resolution::Month),
+            other => anyhow::bail!("unknown rollup resolution '{}'", other),
+        }
+    }
+}
+
+fn start_of_day(date: NaiveDate) -> DateTime<Utc> {
+    Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap_or_default())
+}
+
+/// Parses a granularity such as `15m`, `6h`, `1d`, `2w` or `1mo` (30 days).
+///
+/// Returns `None` for malformed or non-positive values.
+pub fn parse_granularity(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit())?;
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().ok().filter(|n| *n > 0)?;
+
+    match unit {
+        "m" => Some(Duration::minutes(amount)),
+        "h" => Some(Duration::hours(amount)),
+        "d" => Some(Duration::days(amount)),
+        "w" => Some(Duration::weeks(amount)),
+        "mo" => Some(Duration::days(amount * 30)),
+        _ => None,
+    }
+}
+
+/// Corridor metrics for one bucket at any resolution.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CorridorRollup {
+    pub resolution: Resolution,
+    pub corridor_key: String,
+    pub asset_a_code: String,
+    pub asset_a_issuer: String,
+    pub asset_b_code: String,
+    pub asset_b_issuer: String,
+    pub bucket_start: DateTime<Utc>,
+    pub total_transactions: i64,
+    pub successful_transactions: i64,
+    pub failed_transactions: i64,
+    pub success_rate: f64,
+    pub volume_usd: f64,
+    pub avg_slippage_bps: f64,
+    pub avg_settlement_latency_ms: Option<i32>,
+    pub liquidity_depth_usd: f64,
+    /// Number of hourly buckets folded into this one, used to time-weight liquidity
+    pub source_buckets: i64,
+}
+
+/// Folds finer buckets into one `resolution` bucket per corridor.
+///
+/// Counters and volume are summed and the success rate is recomputed from the summed
+/// counters. Slippage and latency are weighted by transaction count, liquidity by the
+/// number of hours each child covers, so the result is the same whichever intermediate
+/// resolution it was built from.
+pub fn combine_buckets(
+    resolution: Resolution,
+    bucket_start: DateTime<Utc>,
+    children: &[CorridorRollup],
+) -> Vec<CorridorRollup> {
+    let mut by_corridor: HashMap<&str, Vec<&CorridorRollup>> = HashMap::new();
+    for child in children {
+        by_corridor
+            .entry(child.corridor_key.as_str())
+            .or_default()
+            .push(child);
+    }
+
+    let mut combined: Vec<CorridorRollup> = by_corridor
+        .into_values()
+        .map(|group| {
+            let first = group[0];
+            let total_transactions: i64 = group.iter().map(|c| c.total_transactions).sum();
+            let successful_transactions: i64 =
+                group.iter().map(|c| c.successful_transactions).sum();
+            let failed_transactions: i64 = group.iter().map(|c| c.failed_transactions).sum();
+            let source_buckets: i64 = group.iter().map(|c| c.source_buckets.max(1)).sum();
+
+            let success_rate = if total_transactions > 0 {
+                successful_transactions as f64 / total_transactions as f64 * 100.0
+            } else {
+                0.0
+            };
+
+            let avg_slippage_bps = if total_transactions > 0 {
+                group
+                    .iter()
+                    .map(|c| c.avg_slippage_bps * c.total_transactions as f64)
+                    .sum::<f64>()
+                    / total_transactions as f64
+            } else {
+                group.iter().map(|c| c.avg_slippage_bps).sum::<f64>() / group.len() as f64
+            };
+
+            let (latency_sum, latency_weight) = group
+                .iter()
+                .filter_map(|c| {
+                    c.avg_settlement_latency_ms
+                        .map(|l| (l as f64, c.total_transactions.max(1) as f64))
+                })
+                .fold((0.0, 0.0), |(sum, weight), (latency, w)| {
+                    (sum + latency * w, weight + w)
+                });
+            let avg_settlement_latency_ms =
+                (latency_weight > 0.0).then(|| (latency_sum / latency_weight).round() as i32);
+
+            let liquidity_depth_usd = group
+                .iter()
+                .map(|c| c.liquidity_depth_usd * c.source_buckets.max(1) as f64)
+                .sum::<f64>()
+                / source_buckets as f64;
+
+            CorridorRollup {
+                resolution,
+                corridor_key: first.corridor_key.clone(),
+                asset_a_code: first.asset_a_code.clone(),
+                asset_a_issuer: first.asset_a_issuer.clone(),
+                asset_b_code: first.asset_b_code.clone(),
+                asset_b_issuer: first.asset_b_issuer.clone(),
+                bucket_start,
+                total_transactions,
+                successful_transactions,
+                failed_transactions,
+                success_rate,
+                volume_usd: group.iter().map(|c| c.volume_usd).sum(),
+                avg_slippage_bps,
+                avg_settlement_latency_ms,
+                liquidity_depth_usd,
+                source_buckets,
+            }
+        })
+        .collect();
+
+    combined.sort_by(|a, b| a.corridor_key.cmp(&b.corridor_key));
+    combined
+}
+
+/// Latency and payment size distributions of one corridor for one bucket.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CorridorSketches {
+    pub resolution: Resolution,
+    pub corridor_key: String,
+    pub bucket_start: DateTime<Utc>,
+    /// Settlement latency of successful payments, in milliseconds
+    pub latency_ms: QuantileSketch,
+    /// Amounts of successful payments
+    pub amount: QuantileSketch,
+}
+
+impl CorridorSketches {
+    pub fn new(resolution: Resolution, corridor_key: String, bucket_start: DateTime<Utc>) -> Self {
+        Self {
+            resolution,
+            corridor_key,
+            bucket_start,
+            latency_ms: QuantileSketch::default(),
+            amount: QuantileSketch::default(),
+        }
+    }
+
+    /// Fold the distributions of `other` into this one
+    pub fn merge(&mut self, other: &CorridorSketches) -> Result<()> {
+        self.latency_ms.merge(&other.latency_ms)?;
+        self.amount.merge(&other.amount)
+    }
+}
+
+/// Merges finer sketches into one `resolution` bucket per corridor
+pub fn merge_sketches(
+    resolution: Resolution,
+    bucket_start: DateTime<Utc>,
+    children: &[CorridorSketches],
+) -> Result<Vec<CorridorSketches>> {
+    let mut by_corridor: HashMap<&str, CorridorSketches> = HashMap::new();
+    for child in children {
+        by_corridor
+            .entry(child.corridor_key.as_str())
+            .or_insert_with(|| {
+                CorridorSketches::new(resolution, child.corridor_key.clone(), bucket_start)
+            })
+            .merge(child)?;
+    }
+
+    let mut merged: Vec<CorridorSketches> = by_corridor.into_values().collect();
+    merged.sort_by(|a, b| a.corridor_key.cmp(&b.corridor_key));
+    Ok(merged)
+}
+
+#[derive(Debug, Clone)]
+pub struct RollupConfig {
+    /// How long to keep buckets of each resolution; `None` keeps them forever
+    pub retention: HashMap<Resolution, Option<Duration>>,
+    /// Maximum number of closed buckets rolled up per resolution and run, so a long
+    /// backfill is spread over several runs
+    pub max_buckets_per_run: usize,
+}
+
+impl Default for RollupConfig {
+    fn default() -> Self {
+        Self {
+            retention: HashMap::from([
+                // Hour buckets are the aggregation's own `corridor_metrics_hourly` rows, which
+                // replay verification, anomaly detection and forecasting read; keep them
+                // unless a retention is configured
+                (Resolution::Hour, None),
+                (Resolution::Day, Some(Duration::days(730))),
+                (Resolution::Week, Some(Duration::days(5 * 365))),
+                (Resolution::Month, None),
+            ]),
+            max_buckets_per_run: 500,
+        }
+    }
+}
+
+impl RollupConfig {
+    /// Reads `ROLLUP_RETENTION_<RESOLUTION>_DAYS` (0 keeps buckets forever) and
+    /// `ROLLUP_MAX_BUCKETS_PER_RUN`, falling back to the defaults.
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+
+        for resolution in Resolution::ALL {
+            let key = format!(
+                "ROLLUP_RETENTION_{}_DAYS",
+                resolution.as_str().to_uppercase()
+            );
+            if let Some(days) = std::env::var(key).ok().and_then(|v| v.parse::<i64>().ok()) {
+                let retention = (days > 0).then(|| Duration::days(days));
+                config.retention.insert(resolution, retention);
+            }
+        }
+
+        if let Some(max) = std::env::var("ROLLUP_MAX_BUCKETS_PER_RUN")
+            .ok()
+            .and_then(|v| v.parse().ok())
+        {
+            config.max_buckets_per_run = max;
+        }
+
+        config
+    }
+
+    pub fn retention_for(&self, resolution: Resolution) -> Option<Duration> {
+        self.retention.get(&resolution).copied().flatten()
+    }
+}
+
+/// Outcome of one rollup run
+#[derive(Debug, Clone, Default)]
+pub struct RollupRunStats {
+    /// Closed buckets finalized, per resolution
+    pub buckets_finalized: HashMap<Resolution, usize>,
+    /// Rows deleted by retention, per resolution
+    pub rows_pruned: HashMap<Resolution, u64>,
+}
+
+pub struct RollupService {
+    db: Arc<Database>,
+    config: RollupConfig,
+}
+
+impl RollupService {
+    pub fn new(db: Arc<Database>, config: RollupConfig) -> Self {
+        Self { db, config }
+    }
+
+    fn rollup_db(&self) -> RollupDb {
+        self.db.rollup_db()
+    }
+
+    /// Rolls hourly metrics up through every coarser resolution and applies retention.
+    ///
+    /// `hourly_complete_until` is the start of the first hour that may still receive new
+    /// payments; hours before it are treated as final.
+    pub async fn run(
+        &self,
+        now: DateTime<Utc>,
+        hourly_complete_until: DateTime<Utc>,
+    ) -> Result<RollupRunStats> {
+        let mut stats = RollupRunStats::default();
+
+        for resolution in Resolution::ROLLED_UP {
+            let finalized = self
+                .roll_up(resolution, now, hourly_complete_until)
+                .await
+                .with_context(|| format!("Failed to roll up {} buckets", resolution))?;
+            stats.buckets_finalized.insert(resolution, finalized);
+        }
+
+        for resolution in Resolution::ALL {
+            let pruned = self
+                .apply_retention(resolution, now)
+                .await
+                .with_context(|| format!("Failed to apply {} retention", resolution))?;
+            stats.rows_pruned.insert(resolution, pruned);
+        }
+
+        info!(
+            "Corridor rollups complete: finalized {:?}, pruned {:?}",
+            stats.buckets_finalized, stats.rows_pruned
+        );
+
+        Ok(stats)
+    }
+
+    /// Point up to which the source of `resolution` is final
+    async fn source_complete_until(
+        &self,
+        resolution: Resolution,
+        hourly_complete_until: DateTime<Utc>,
+    ) -> Result<Option<DateTime<Utc>>> {
+        match resolution.source() {
+            None => Ok(None),
+            Some(Resolution::Hour) => Ok(Some(hourly_complete_until)),
+            Some(source) => self.rollup_db().get_watermark(source).await,
+        }
+    }
+
+    /// Finalizes closed `resolution` buckets after the watermark, then refreshes the
+    /// bucket that is still open so recent data shows up before it closes.
+    async fn roll_up(
+        &self,
+        resolution: Resolution,
+        now: DateTime<Utc>,
+        hourly_complete_until: DateTime<Utc>,
+    ) -> Result<usize> {
+        let Some(source) = resolution.source() else {
+            return Ok(0);
+        };
+        let Some(complete_until) = self
+            .source_complete_until(resolution, hourly_complete_until)
+            .await?
+        else {
+            debug!(
+                "No finalized {} buckets yet; skipping {} rollup",
+                source, resolution
+            );
+            return Ok(0);
+        };
+
+        let db = self.rollup_db();
+        let limit = resolution.bucket_start(complete_until);
+        let mut watermark = db.get_watermark(resolution).await?;
+        let mut finalized = 0;
+
+        while finalized < self.config.max_buckets_per_run {
+            // Jump straight to the next bucket that has source data, so gaps cost nothing
+            let next_source = db.earliest_bucket_from(source, watermark).await?;
+            let bucket = match next_source.map(|ts| resolution.bucket_start(ts)) {
+                Some(bucket) if bucket < limit => bucket,
+                _ => {
+                    if watermark.is_none_or(|w| w < limit) {
+                        db.set_watermark(resolution, limit).await?;
+                        watermark = Some(limit);
+                    }
+                    break;
+                }
+            };
+
+            let next = resolution.next_bucket(bucket);
+            self.rebuild_bucket(&db, resolution, source, bucket, next)
+                .await?;
+            db.set_watermark(resolution, next).await?;
+            watermark = Some(next);
+            finalized += 1;
+        }
+
+        // Only refresh the open bucket once the backlog has been worked off
+        if watermark.is_some_and(|w| w >= limit) {
+            let open = resolution.bucket_start(now);
+            if open >= limit {
+                self.rebuild_bucket(&db, resolution, source, open, resolution.next_bucket(open))
+                    .await?;
+            }
+        }
+
+        Ok(finalized)
+    }
+
+    async fn rebuild_bucket(
+        &self,
+        db: &RollupDb,
+        resolution: Resolution,
+        source: Resolution,
+        bucket_start: DateTime<Utc>,
+        bucket_end: DateTime<Utc>,
+    ) -> Result<()> {
+        let children = db
+            .fetch_buckets(source, None, bucket_start, bucket_end)
+            .await?;
+
+        for rollup in combine_buckets(resolution, bucket_start, &children) {
+            db.upsert_rollup(&rollup).await?;
+        }
+
+        let child_sketches = db
+            .fetch_sketches(source, None, bucket_start, bucket_end)
+            .await?;
+        for sketches in merge_sketches(resolution, bucket_start, &child_sketches)? {
+            db.upsert_sketches(&sketches).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Deletes buckets older than the retention window of `resolution`.
+    ///
+    /// Buckets a coarser resolution has not consumed yet are kept regardless, so a
+    /// lagging rollup never loses its input.
+    async fn apply_retention(&self, resolution: Resolution, now: DateTime<Utc>) -> Result<u64> {
+        let Some(retention) = self.config.retention_for(resolution) else {
+            return Ok(0);
+        };
+
+        let db = self.rollup_db();
+        let mut cutoff = resolution.bucket_start(now - retention);
+
+        for consumer in Resolution::ROLLED_UP
+            .iter()
+            .filter(|r| r.source() == Some(resolution))
+        {
+            match db.get_watermark(*consumer).await? {
+                Some(watermark) => cutoff = cutoff.min(watermark),
+                None => return Ok(0),
+            }
+        }
+
+        db.delete_buckets_before(resolution, cutoff).await
+    }
+}
+
+/// A corridor metrics series at the resolution picked for the requested range.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorridorSeries {
+    pub corridor_key: String,
+    pub resolution: Resolution,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub points: Vec<CorridorRollup>,
+}
+
+/// Loads the metrics of one corridor over `[start, end)` from the coarsest resolution
+/// that still satisfies `granularity`.
+pub async fn fetch_corridor_series(
+    db: &RollupDb,
+    corridor_key: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    granularity: Option<Duration>,
+) -> Result<CorridorSeries> {
+    let resolution = Resolution::for_range(start, end, granularity);
+    let points = db
+        .fetch_buckets(
+            resolution,
+            Some(corridor_key),
+            resolution.bucket_start(start),
+            end,
+        )
+        .await?;
+
+    Ok(CorridorSeries {
+        corridor_key: corridor_key.to_string(),
+        resolution,
+        start,
+        end,
+        points,
+    })
+}
+
+/// Percentile summaries of a corridor over a time range.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorridorDistribution {
+    pub corridor_key: String,
+    /// Resolution of the buckets merged to answer the query
+    pub resolution: Resolution,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Settlement latency in milliseconds; `None` when no latency was recorded
+    pub latency_ms: Option<DistributionSummary>,
+    /// Payment size; `None` when there were no successful payments
+    pub amount: Option<DistributionSummary>,
+}
+
+/// Merges the sketches of one corridor over `[start, end)` into a single pair of
+/// distributions.
+///
+/// Buckets are read at the finest resolution that keeps the range within
+/// [`DEFAULT_MAX_SERIES_POINTS`] buckets, so the range is effectively widened to the
+/// bucket boundaries of that resolution.
+pub async fn fetch_corridor_sketches(
+    db: &RollupDb,
+    corridor_key: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<CorridorSketches> {
+    let resolution = Resolution::for_range(start, end, None);
+    let bucket_start = resolution.bucket_start(start);
+    let buckets = db
+        .fetch_sketches(resolution, Some(corridor_key), bucket_start, end)
+        .await?;
+
+    let mut merged = CorridorSketches::new(resolution, corridor_key.to_string(), bucket_start);
+    for bucket in &buckets {
+        merged.merge(bucket)?;
+    }
+    Ok(merged)
+}
+
+/// Latency and payment size percentiles of one corridor over `[start, end)`
+pub async fn fetch_corridor_distribution(
+    db: &RollupDb,
+    corridor_key: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<CorridorDistribution> {
+    let sketches = fetch_corridor_sketches(db, corridor_key, start, end).await?;
+
+    Ok(CorridorDistribution {
+        corridor_key: corridor_key.to_string(),
+        resolution: sketches.resolution,
+        start,
+        end,
+        latency_ms: DistributionSummary::from_sketch(&sketches.latency_ms),
+        amount: DistributionSummary::from_sketch(&sketches.amount),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ts(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn hour(key: &str, at: &str, total: i64, ok: i64, liquidity: f64) -> CorridorRollup {
+        CorridorRollup {
+            resolution: Resolution::Hour,
+            corridor_key: key.to_string(),
+            asset_a_code: "USDC".to_string(),
+            asset_a_issuer: "GA".to_string(),
+            asset_b_code: "EURC".to_string(),
+            asset_b_issuer: "GB".to_string(),
+            bucket_start: ts(at),
+            total_transactions: total,
+            successful_transactions: ok,
+            failed_transactions: total - ok,
+            success_rate: ok as f64 / total as f64 * 100.0,
+            volume_usd: total as f64 * 10.0,
+            avg_slippage_bps: total as f64,
+            avg_settlement_latency_ms: Some(1000),
+            liquidity_depth_usd: liquidity,
+            source_buckets: 1,
+        }
+    }
+
+    #[test]
+    fn test_bucket_boundaries() {
+        let t = ts("2024-02-29T17:45:12Z"); // Thursday, leap day
+        assert_eq!(Resolution::Hour.bucket_start(t), ts("2024-02-29T17:00:00Z"));
+        assert_eq!(Resolution::Day.bucket_start(t), ts("2024-02-29T00:00:00Z"));
+        assert_eq!(Resolution::Week.bucket_start(t), ts("2024-02-26T00:00:00Z"));
+        assert_eq!(
+            Resolution::Month.bucket_start(t),
+            ts("2024-02-01T00:00:00Z")
+        );
+
+        assert_eq!(
+            Resolution::Month.next_bucket(ts("2024-02-01T00:00:00Z")),
+            ts("2024-03-01T00:00:00Z")
+        );
+        assert_eq!(
+            Resolution::Month.next_bucket(ts("2024-12-01T00:00:00Z")),
+            ts("2025-01-01T00:00:00Z")
+        );
+        assert_eq!(
+            Resolution::Week.next_bucket(ts("2024-02-26T00:00:00Z")),
+            ts("2024-03-04T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_resolution_selection() {
+        assert_eq!(
+            Resolution::for_granularity(Duration::minutes(5)),
+            Resolution::Hour
+        );
+        assert_eq!(
+            Resolution::for_granularity(Duration::hours(6)),
+            Resolution::Hour
+        );
+        assert_eq!(
+            Resolution::for_granularity(Duration::days(3)),
+            Resolution::Day
+        );
+        assert_eq!(
+            Resolution::for_granularity(Duration::days(7)),
+            Resolution::Week
+        );
+        assert_eq!(
+            Resolution::for_granularity(Duration::days(90)),
+            Resolution::Month
+        );
+
+        let end = ts("2024-06-01T00:00:00Z");
+        assert_eq!(
+            Resolution::for_range(end - Duration::days(7), end, None),
+            Resolution::Hour
+        );
+        assert_eq!(
+            Resolution::for_range(end - Duration::days(365), end, None),
+            Resolution::Day
+        );
+        assert_eq!(
+            Resolution::for_range(end - Duration::days(5 * 365), end, None),
+            Resolution::Week
+        );
+        assert_eq!(
+            Resolution::for_range(end - Duration::days(20 * 365), end, None),
+            Resolution::Month
+        );
+    }
+
+    #[test]
+    fn test_parse_granularity() {
+        assert_eq!(parse_granularity("15m"), Some(Duration::minutes(15)));
+        assert_eq!(parse_granularity("6h"), Some(Duration::hours(6)));
+        assert_eq!(parse_granularity("1d"), Some(Duration::days(1)));
+        assert_eq!(parse_granularity("2w"), Some(Duration::weeks(2)));
+        assert_eq!(parse_granularity("1mo"), Some(Duration::days(30)));
+        assert_eq!(parse_granularity("0h"), None);
+        assert_eq!(parse_granularity("h"), None);
+        assert_eq!(parse_granularity("3y"), None);
+    }
+
+    #[test]
+    fn test_combine_buckets_weights_metrics() {
+        let day = ts("2024-01-01T00:00:00Z");
+        let children = vec![
+            hour("A", "2024-01-01T00:00:00Z", 10, 10, 100.0),
+            hour("A", "2024-01-01T01:00:00Z", 30, 15, 300.0),
+            hour("B", "2024-01-01T00:00:00Z", 5, 5, 50.0),
+        ];
+
+        let combined = combine_buckets(Resolution::Day, day, &children);
+        assert_eq!(combined.len(), 2);
+
+        let a = &combined[0];
+        assert_eq!(a.corridor_key, "A");
+        assert_eq!(a.resolution, Resolution::Day);
+        assert_eq!(a.bucket_start, day);
+        assert_eq!(a.total_transactions, 40);
+        assert_eq!(a.successful_transactions, 25);
+        assert_eq!(a.failed_transactions, 15);
+        assert_eq!(a.success_rate, 62.5);
+        assert_eq!(a.volume_usd, 400.0);
+        // (10 * 10 + 30 * 30) / 40
+        assert_eq!(a.avg_slippage_bps, 25.0);
+        assert_eq!(a.avg_settlement_latency_ms, Some(1000));
+        assert_eq!(a.liquidity_depth_usd, 200.0);
+        assert_eq!(a.source_buckets, 2);
+    }
+
+    #[test]
+    fn test_merge_sketches_per_corridor() {
+        let day = ts("2024-01-01T00:00:00Z");
+        let sketch = |key: &str, at: &str, latencies: &[f64]| {
+            let mut s = CorridorSketches::new(Resolution::Hour, key.to_string(), ts(at));
+            for l in latencies {
+                s.latency_ms.insert(*l);
+                s.amount.insert(100.0);
+            }
+            s
+        };
+        let children = vec![
+            sketch("A", "2024-01-01T00:00:00Z", &[100.0, 200.0]),
+            sketch("A", "2024-01-01T01:00:00Z", &[5000.0]),
+            sketch("B", "2024-01-01T00:00:00Z", &[300.0]),
+        ];
+
+        let merged = merge_sketches(Resolution::Day, day, &children).unwrap();
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].corridor_key, "A");
+        assert_eq!(merged[0].resolution, Resolution::Day);
+        assert_eq!(merged[0].bucket_start, day);
+        assert_eq!(merged[0].latency_ms.count(), 3);
+        assert_eq!(merged[0].latency_ms.max(), Some(5000.0));
+        assert_eq!(merged[0].amount.count(), 3);
+        assert_eq!(merged[1].latency_ms.count(), 1);
+    }
+
+    #[test]
+    fn test_combine_is_independent_of_intermediate_resolution() {
+        let hours = vec![
+            hour("A", "2024-01-01T00:00:00Z", 10, 9, 100.0),
+            hour("A", "2024-01-01T05:00:00Z", 20, 20, 200.0),
+            hour("A", "2024-01-02T00:00:00Z", 30, 10, 600.0),
+        ];
+        let month = ts("2024-01-01T00:00:00Z");
+
+        let direct = combine_buckets(Resolution::Month, month, &hours);
+
+        let days: Vec<CorridorRollup> = [&hours[..2], &hours[2..]]
+            .iter()
+            .flat_map(|chunk| combine_buckets(Resolution::Day, chunk[0].bucket_start, chunk))
+            .collect();
+        let via_days = combine_buckets(Resolution::Month, month, &days);
+
+        let (direct, via_days) = (&direct[0], &via_days[0]);
+        assert_eq!(direct.total_transactions, via_days.total_transactions);
+        assert_eq!(direct.success_rate, via_days.success_rate);
+        assert_eq!(direct.source_buckets, via_days.source_buckets);
+        assert!((direct.avg_slippage_bps - via_days.avg_slippage_bps).abs() < 1e-9);
+        assert!((direct.liquidity_depth_usd - via_days.liquidity_depth_usd).abs() < 1e-9);
+        assert!((via_days.liquidity_depth_usd - 300.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,187 @@
+//! Records anchor-reported SEP-24/SEP-31 transfer timestamps from proxied responses.
+//!
+//! The SEP proxies accept any transfer server the caller names, so a response is
+//! only recorded when its server is the one a registered anchor declares in its
+//! stellar.toml (`TRANSFER_SERVER_SEP0024` for SEP-24, `DIRECT_PAYMENT_SERVER` for
+//! SEP-31). Records are stored under that anchor's account and only feed that
+//! anchor's settlement metrics.
+
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
+use crate::database::Database;
+use crate::ingestion::settlement::{parse_sep_transactions, PROTOCOL_SEP24, PROTOCOL_SEP31};
+use crate::services::stellar_toml::{StellarToml, StellarTomlClient};
+
+/// How long the transfer server index is used before anchors are re-read
+const INDEX_TTL: Duration = Duration::from_secs(60 * 60);
+/// Upper bound on anchors whose stellar.toml is read when building the index
+const MAX_INDEXED_ANCHORS: i64 = 1_000;
+
+/// `(protocol, normalized server URL)` -> anchor account
+type ServerIndex = HashMap<(String, String), String>;
+
+pub struct SepTransferRecorder {
+    db: Arc<Database>,
+    toml_client: Arc<StellarTomlClient>,
+    index: RwLock<Option<(ServerIndex, Instant)>>,
+}
+
+impl SepTransferRecorder {
+    pub fn new(db: Arc<Database>, toml_client: Arc<StellarTomlClient>) -> Self {
+        Self {
+            db,
+            toml_client,
+            index: RwLock::new(None),
+        }
+    }
+
+    /// Store the transfer timestamps in a proxied `/transaction(s)` response if
+    /// `transfer_server` belongs to a registered anchor
+    pub async fn record(&self, protocol: &str, transfer_server: &str, body: &Value) {
+        let transfers = parse_sep_transactions(protocol, body);
+        if transfers.is_empty() {
+            return;
+        }
+
+        let anchor_account = match self.resolve_anchor(protocol, transfer_server).await {
+            Ok(Some(account)) => account,
+            Ok(None) => {
+                debug!(
+                    "{} server {} is not declared by a registered anchor, not recording transfers",
+                    protocol, transfer_server
+                );
+                return;
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to resolve {} server {}: {}",
+                    protocol, transfer_server, e
+                );
+                return;
+            }
+        };
+
+        if let Err(e) = self
+            .db
+            .record_sep_transfers(&anchor_account, &transfers)
+            .await
+        {
+            warn!("Failed to record {} transfer timestamps: {}", protocol, e);
+        }
+    }
+
+    /// Account of the registered anchor whose stellar.toml declares
+    /// `transfer_server` for `protocol`
+    pub async fn resolve_anchor(
+        &self,
+        protocol: &str,
+        transfer_server: &str,
+    ) -> Result<Option<String>> {
+        let key = (protocol.to_string(), normalize_server_url(transfer_server));
+
+        if let Some((index, built_at)) = self.index.read().await.as_ref() {
+            if built_at.elapsed() < INDEX_TTL {
+                return Ok(index.get(&key).cloned());
+            }
+        }
+
+        let index = self.build_index().await?;
+        let account = index.get(&key).cloned();
+        *self.index.write().await = Some((index, Instant::now()));
+        Ok(account)
+    }
+
+    async fn build_index(&self) -> Result<ServerIndex> {
+        let anchors = self.db.list_anchors(MAX_INDEXED_ANCHORS, 0).await?;
+
+        let mut tomls = Vec::new();
+        for anchor in anchors {
+            let Some(domain) = anchor.home_domain.as_deref() else {
+                continue;
+            };
+            match self.toml_client.fetch_toml(domain).await {
+                Ok(toml) => tomls.push((anchor.stellar_account, toml)),
+                Err(e) => debug!("Skipping anchor {}: no stellar.toml ({})", domain, e),
+            }
+        }
+
+        Ok(index_servers(&tomls))
+    }
+}
+
+/// Index the SEP-24 and SEP-31 servers declared by each anchor's stellar.toml
+fn index_servers(anchors: &[(String, StellarToml)]) -> ServerIndex {
+    let mut index = HashMap::new();
+    for (account, toml) in anchors {
+        let declared = [
+            (PROTOCOL_SEP24, toml.transfer_server_sep0024.as_deref()),
+            (PROTOCOL_SEP31, toml.direct_payment_server.as_deref()),
+        ];
+        for (protocol, server) in declared {
+            if let Some(server) = server {
+                index
+                    .entry((protocol.to_string(), normalize_server_url(server)))
+                    .or_insert_with(|| account.clone());
+            }
+        }
+    }
+    index
+}
+
+fn normalize_server_url(url: &str) -> String {
+    url.trim().trim_end_matches('/').to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn toml(sep24: Option<&str>, sep31: Option<&str>) -> StellarToml {
+        let client = StellarTomlClient::new(Arc::new(RwLock::new(None)), None).unwrap();
+        let mut toml = client.parse_toml("", "anchor.example").unwrap();
+        toml.transfer_server_sep0024 = sep24.map(str::to_string);
+        toml.direct_payment_server = sep31.map(str::to_string);
+        toml
+    }
+
+    #[test]
+    fn test_index_matches_declared_servers_only() {
+        let index = index_servers(&[
+            (
+                "GANCHOR1".to_string(),
+                toml(Some("https://api.anchor1.example/sep24/"), None),
+            ),
+            (
+                "GANCHOR2".to_string(),
+                toml(None, Some("https://anchor2.example/sep31")),
+            ),
+        ]);
+
+        let lookup = |protocol: &str, url: &str| {
+            index
+                .get(&(protocol.to_string(), normalize_server_url(url)))
+                .cloned()
+        };
+
+        assert_eq!(
+            lookup(PROTOCOL_SEP24, "https://API.anchor1.example/sep24"),
+            Some("GANCHOR1".to_string())
+        );
+        assert_eq!(
+            lookup(PROTOCOL_SEP31, "https://anchor2.example/sep31/"),
+            Some("GANCHOR2".to_string())
+        );
+        // A server is only trusted for the protocol its anchor declared it for
+        assert_eq!(
+            lookup(PROTOCOL_SEP31, "https://api.anchor1.example/sep24"),
+            None
+        );
+        assert_eq!(lookup(PROTOCOL_SEP24, "https://evil.example/sep24"), None);
+    }
+}
@@ -2,6 +2,7 @@ use crate::database::Database;
 use crate::snapshot::schema::{
     AnalyticsSnapshot, SnapshotAnchorMetrics, SnapshotCorridorMetrics, SCHEMA_VERSION,
 };
+use crate::with_pool;
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde::Serialize;
@@ -182,51 +183,54 @@ impl SnapshotService {
             ORDER BY id
         "#;
 
-        let rows = sqlx::query(query)
-            .fetch_all(self.db.pool())
-            .await
-            .context("Failed to fetch anchor data")?;
-
-        let mut metrics = Vec::new();
-
-        for row in rows {
-            let total_transactions: i64 = row.get("total_transactions");
-            let successful_transactions: i64 = row.get("successful_transactions");
-            let failed_transactions: i64 = row.get("failed_transactions");
-
-            let success_rate = if total_transactions > 0 {
-                successful_transactions as f64 / total_transactions as f64
-            } else {
-                0.0
-            };
-
-            let failure_rate = if total_transactions > 0 {
-                failed_transactions as f64 / total_transactions as f64
-            } else {
-                0.0
-            };
-
-            let anchor_metrics = SnapshotAnchorMetrics {
-                id: Uuid::parse_str(&row.get::<String, _>("id"))
-                    .context("Invalid anchor ID format")?,
-                name: row.get("name"),
-                stellar_account: row.get("stellar_account"),
-                success_rate,
-                failure_rate,
-                reliability_score: row.get("reliability_score"),
-                total_transactions,
-                successful_transactions,
-                failed_transactions,
-                avg_settlement_time_ms: row.get("avg_settlement_time_ms"),
-                volume_usd: row.get("total_volume_usd"),
-                status: row.get("status"),
-            };
-
-            metrics.push(anchor_metrics);
-        }
+        with_pool!(self.db.pool(), |pool| {
+            let rows = sqlx::query(query)
+                .bind(Utc::now() - chrono::Duration::days(1))
+                .fetch_all(pool)
+                .await
+                .context("Failed to fetch anchor data")?;
+
+            let mut metrics = Vec::new();
+
+            for row in rows {
+                let total_transactions: i64 = row.get("total_transactions");
+                let successful_transactions: i64 = row.get("successful_transactions");
+                let failed_transactions: i64 = row.get("failed_transactions");
+
+                let success_rate = if total_transactions > 0 {
+                    successful_transactions as f64 / total_transactions as f64
+                } else {
+                    0.0
+                };
+
+                let failure_rate = if total_transactions > 0 {
+                    failed_transactions as f64 / total_transactions as f64
+                } else {
+                    0.0
+                };
+
+                let anchor_metrics = SnapshotAnchorMetrics {
+                    id: Uuid::parse_str(&row.get::<String, _>("id"))
+                        .context("Invalid anchor ID format")?,
+                    name: row.get("name"),
+                    stellar_account: row.get("stellar_account"),
+                    success_rate,
+                    failure_rate,
+                    reliability_score: row.get("reliability_score"),
+                    total_transactions,
+                    successful_transactions,
+                    failed_transactions,
+                    avg_settlement_time_ms: row.get("avg_settlement_time_ms"),
+                    volume_usd: row.get("total_volume_usd"),
+                    status: row.get("status"),
+                };
+
+                metrics.push(anchor_metrics);
+            }
 
-        debug!("Aggregated {} anchor metrics", metrics.len());
-        Ok(metrics)
+            debug!("Aggregated {} anchor metrics", metrics.len());
+            Ok(metrics)
+        })
     }
 
     /// Aggregate corridor metrics from database
@@ -247,42 +251,45 @@ impl SnapshotService {
                 cm.avg_settlement_latency_ms,
                 cm.liquidity_depth_usd
             FROM corridor_metrics cm
-            WHERE cm.date >= datetime('now', '-1 day')
+            WHERE cm.date >= $1
             GROUP BY cm.corridor_key
             HAVING cm.date = MAX(cm.date)
             ORDER BY cm.corridor_key
         "#;
 
-        let rows = sqlx::query(query)
-            .fetch_all(self.db.pool())
-            .await
-            .context("Failed to fetch corridor metrics")?;
-
-        let mut metrics = Vec::new();
-
-        for row in rows {
-            let corridor_metrics = SnapshotCorridorMetrics {
-                id: Uuid::parse_str(&row.get::<String, _>("id"))
-                    .context("Invalid corridor metrics ID format")?,
-                corridor_key: row.get("corridor_key"),
-                asset_a_code: row.get("asset_a_code"),
-                asset_a_issuer: row.get("asset_a_issuer"),
-                asset_b_code: row.get("asset_b_code"),
-                asset_b_issuer: row.get("asset_b_issuer"),
-                total_transactions: row.get("total_transactions"),
-                successful_transactions: row.get("successful_transactions"),
-                failed_transactions: row.get("failed_transactions"),
-                success_rate: row.get("success_rate"),
-                volume_usd: row.get("volume_usd"),
-                avg_settlement_latency_ms: row.get("avg_settlement_latency_ms"),
-                liquidity_depth_usd: row.get("liquidity_depth_usd"),
-            };
-
-            metrics.push(corridor_metrics);
-        }
+        with_pool!(self.db.pool(), |pool| {
+            let rows = sqlx::query(query)
+                .bind(Utc::now() - chrono::Duration::days(1))
+                .fetch_all(pool)
+                .await
+                .context("Failed to fetch corridor metrics")?;
+
+            let mut metrics = Vec::new();
+
+            for row in rows {
+                let corridor_metrics = SnapshotCorridorMetrics {
+                    id: Uuid::parse_str(&row.get::<String, _>("id"))
+                        .context("Invalid corridor metrics ID format")?,
+                    corridor_key: row.get("corridor_key"),
+                    asset_a_code: row.get("asset_a_code"),
+                    asset_a_issuer: row.get("asset_a_issuer"),
+                    asset_b_code: row.get("asset_b_code"),
+                    asset_b_issuer: row.get("asset_b_issuer"),
+                    total_transactions: row.get("total_transactions"),
+                    successful_transactions: row.get("successful_transactions"),
+                    failed_transactions: row.get("failed_transactions"),
+                    success_rate: row.get("success_rate"),
+                    volume_usd: row.get("volume_usd"),
+                    avg_settlement_latency_ms: row.get("avg_settlement_latency_ms"),
+                    liquidity_depth_usd: row.get("liquidity_depth_usd"),
+                };
+
+                metrics.push(corridor_metrics);
+            }
 
-        debug!("Aggregated {} corridor metrics", metrics.len());
-        Ok(metrics)
+            debug!("Aggregated {} corridor metrics", metrics.len());
+            Ok(metrics)
+        })
     }
 
     /// Store snapshot and hash in database
@@ -297,21 +304,24 @@ impl SnapshotService {
         let query = r#"
             INSERT INTO snapshots (
                 id, entity_id, entity_type, data, hash, epoch, timestamp, created_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
         "#;
 
-        sqlx::query(query)
-            .bind(&snapshot_id)
-            .bind("system") // entity_id for system-wide snapshots
-            .bind("analytics_snapshot") // entity_type
-            .bind(canonical_json)
-            .bind(hash)
-            .bind(snapshot.epoch as i64)
-            .bind(snapshot.timestamp)
-            .bind(Utc::now())
-            .execute(self.db.pool())
-            .await
-            .context("Failed to insert snapshot record")?;
+        with_pool!(self.db.pool(), |pool| {
+            sqlx::query(query)
+                .bind(&snapshot_id)
+                .bind("system") // entity_id for system-wide snapshots
+                .bind("analytics_snapshot") // entity_type
+                .bind(canonical_json)
+                .bind(hash)
+                .bind(snapshot.epoch as i64)
+                .bind(snapshot.timestamp)
+                .bind(Utc::now())
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+        })
+        .context("Failed to insert snapshot record")?;
 
         Ok(snapshot_id)
     }
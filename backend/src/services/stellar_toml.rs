@@ -64,6 +64,13 @@ pub struct StellarToml {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub network_passphrase: Option<String>,
 
+    // Service endpoints
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transfer_server_sep0024: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direct_payment_server: Option<String>,
+
     // Currencies
     #[serde(skip_serializing_if = "Option::is_none")]
     pub currencies: Option<Vec<CurrencyInfo>>,
@@ -453,6 +460,17 @@ impl StellarTomlClient {
             }
         }
 
+        // Extract SEP-24 and SEP-31 endpoints
+        let transfer_server_sep0024 = parsed
+            .get("TRANSFER_SERVER_SEP0024")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+
+        let direct_payment_server = parsed
+            .get("DIRECT_PAYMENT_SERVER")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+
         // Parse currencies
         let currencies = self.parse_currencies(&parsed)?;
 
@@ -476,6 +494,8 @@ impl StellarTomlClient {
             organization_official_email,
             organization_support_email,
             network_passphrase,
+            transfer_server_sep0024,
+            direct_payment_server,
             currencies,
             principals,
             documentation,
@@ -737,6 +757,26 @@ NETWORK_PASSPHRASE = "Test SDF Network ; September 2015"
         assert_eq!(toml.domain, "test.com");
     }
 
+    #[test]
+    fn test_parse_toml_service_endpoints() {
+        let client = StellarTomlClient::new(Arc::new(RwLock::new(None)), None).unwrap();
+
+        let toml_content = r#"
+TRANSFER_SERVER_SEP0024 = "https://api.test.com/sep24"
+DIRECT_PAYMENT_SERVER = "https://api.test.com/sep31"
+        "#;
+
+        let toml = client.parse_toml(toml_content, "test.com").unwrap();
+        assert_eq!(
+            toml.transfer_server_sep0024.as_deref(),
+            Some("https://api.test.com/sep24")
+        );
+        assert_eq!(
+            toml.direct_payment_server.as_deref(),
+            Some("https://api.test.com/sep31")
+        );
+    }
+
     #[test]
     fn test_parse_toml_with_currencies() {
         let client = StellarTomlClient::new(Arc::new(RwLock::new(None)), None).unwrap();
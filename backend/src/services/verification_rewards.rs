@@ -4,6 +4,7 @@
 //! Users earn points for successfully verifying that snapshot hashes match backend data.
 
 use crate::database::Database;
+use crate::with_pool;
 use anyhow::{anyhow, Context, Result};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
@@ -99,7 +100,7 @@ impl VerificationRewardsService {
             let mut points = BASE_REWARD_POINTS;
 
             // Add early verification bonus
-            if self.is_early_verification(&snapshot.created_at).await? {
+            if self.is_early_verification(snapshot.created_at).await? {
                 points += EARLY_VERIFICATION_BONUS;
                 debug!(
                     "Early verification bonus applied: +{}",
@@ -156,76 +157,80 @@ impl VerificationRewardsService {
 
     /// Get user reward statistics
     pub async fn get_user_stats(&self, user_id: &str) -> Result<UserRewardStats> {
-        let row = sqlx::query(
-            r#"
-            SELECT 
-                u.id,
-                u.username,
-                COALESCE(ur.total_points, 0) as total_points,
-                COALESCE(ur.successful_verifications, 0) as successful_verifications,
-                COALESCE(ur.failed_verifications, 0) as failed_verifications,
-                ur.last_verification_at
-            FROM users u
-            LEFT JOIN user_rewards ur ON u.id = ur.user_id
-            WHERE u.id = ?
-            "#,
-        )
-        .bind(user_id)
-        .fetch_one(self.db.pool())
-        .await
-        .context("Failed to fetch user stats")?;
-
-        let successful: i32 = row.try_get("successful_verifications")?;
-        let failed: i32 = row.try_get("failed_verifications")?;
-        let total_attempts = successful + failed;
-        let success_rate = if total_attempts > 0 {
-            (successful as f64 / total_attempts as f64) * 100.0
-        } else {
-            0.0
-        };
-
-        Ok(UserRewardStats {
-            user_id: row.try_get::<String, _>("id")?,
-            username: row.try_get::<String, _>("username")?,
-            total_points: row.try_get::<i32, _>("total_points")?,
-            successful_verifications: successful,
-            failed_verifications: failed,
-            success_rate,
-            last_verification_at: row.try_get("last_verification_at").ok(),
+        with_pool!(self.db.pool(), |pool| {
+            let row = sqlx::query(
+                r#"
+                SELECT 
+                    u.id,
+                    u.username,
+                    COALESCE(ur.total_points, 0) as total_points,
+                    COALESCE(ur.successful_verifications, 0) as successful_verifications,
+                    COALESCE(ur.failed_verifications, 0) as failed_verifications,
+                    ur.last_verification_at
+                FROM users u
+                LEFT JOIN user_rewards ur ON u.id = ur.user_id
+                WHERE u.id = $1
+                "#,
+            )
+            .bind(user_id)
+            .fetch_one(pool)
+            .await
+            .context("Failed to fetch user stats")?;
+
+            let successful: i32 = row.try_get("successful_verifications")?;
+            let failed: i32 = row.try_get("failed_verifications")?;
+            let total_attempts = successful + failed;
+            let success_rate = if total_attempts > 0 {
+                (successful as f64 / total_attempts as f64) * 100.0
+            } else {
+                0.0
+            };
+
+            Ok(UserRewardStats {
+                user_id: row.try_get::<String, _>("id")?,
+                username: row.try_get::<String, _>("username")?,
+                total_points: row.try_get::<i32, _>("total_points")?,
+                successful_verifications: successful,
+                failed_verifications: failed,
+                success_rate,
+                last_verification_at: row.try_get("last_verification_at").ok(),
+            })
         })
     }
 
     /// Get leaderboard of top verifiers
     pub async fn get_leaderboard(&self, limit: i32) -> Result<Vec<LeaderboardEntry>> {
-        let rows = sqlx::query(
-            r#"
-            SELECT 
-                username,
-                total_points,
-                successful_verifications,
-                CAST(successful_verifications AS REAL) / 
-                    NULLIF(successful_verifications + failed_verifications, 0) * 100 AS success_rate
-            FROM verification_leaderboard
-            LIMIT ?
-            "#,
-        )
-        .bind(limit)
-        .fetch_all(self.db.pool())
-        .await
-        .context("Failed to fetch leaderboard")?;
-
-        let mut leaderboard = Vec::new();
-        for (rank, row) in rows.iter().enumerate() {
-            leaderboard.push(LeaderboardEntry {
-                rank: (rank + 1) as i32,
-                username: row.try_get::<String, _>("username")?,
-                total_points: row.try_get::<i32, _>("total_points")?,
-                successful_verifications: row.try_get::<i32, _>("successful_verifications")?,
-                success_rate: row.try_get("success_rate").unwrap_or(0.0),
-            });
-        }
+        with_pool!(self.db.pool(), |pool| {
+            let rows = sqlx::query(
+                r#"
+                SELECT 
+                    username,
+                    total_points,
+                    successful_verifications,
+                    CAST(successful_verifications AS DOUBLE PRECISION) / 
+                        NULLIF(successful_verifications + failed_verifications, 0) * 100 AS success_rate
+                FROM verification_leaderboard
+                LIMIT $1
+                "#,
+            )
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+            .context("Failed to fetch leaderboard")?;
+
+            let mut leaderboard = Vec::new();
+            for (rank, row) in rows.iter().enumerate() {
+                leaderboard.push(LeaderboardEntry {
+                    rank: (rank + 1) as i32,
+                    username: row.try_get::<String, _>("username")?,
+                    total_points: row.try_get::<i32, _>("total_points")?,
+                    successful_verifications: row.try_get::<i32, _>("successful_verifications")?,
+                    success_rate: row.try_get("success_rate").unwrap_or(0.0),
+                });
+            }
 
-        Ok(leaderboard)
+            Ok(leaderboard)
+        })
     }
 
     /// Get recent verifications for a user
@@ -234,60 +239,65 @@ impl VerificationRewardsService {
         user_id: &str,
         limit: i32,
     ) -> Result<Vec<VerificationRecord>> {
-        let rows = sqlx::query(
-            r#"
-            SELECT 
-                id,
-                snapshot_id,
-                epoch,
-                submitted_hash,
-                expected_hash,
-                is_match,
-                reward_points,
-                verified_at
-            FROM snapshot_verifications
-            WHERE user_id = ?
-            ORDER BY verified_at DESC
-            LIMIT ?
-            "#,
-        )
-        .bind(user_id)
-        .bind(limit)
-        .fetch_all(self.db.pool())
-        .await
-        .context("Failed to fetch user verifications")?;
-
-        let mut verifications = Vec::new();
-        for row in rows {
-            verifications.push(VerificationRecord {
-                id: row.try_get::<String, _>("id")?,
-                snapshot_id: row.try_get::<String, _>("snapshot_id")?,
-                epoch: row.try_get::<i64, _>("epoch")?,
-                submitted_hash: row.try_get::<String, _>("submitted_hash")?,
-                expected_hash: row.try_get::<String, _>("expected_hash")?,
-                is_match: row.try_get::<bool, _>("is_match")?,
-                reward_points: row.try_get::<i32, _>("reward_points")?,
-                verified_at: row.try_get::<String, _>("verified_at")?,
-            });
-        }
+        with_pool!(self.db.pool(), |pool| {
+            let rows = sqlx::query(
+                r#"
+                SELECT 
+                    id,
+                    snapshot_id,
+                    epoch,
+                    submitted_hash,
+                    expected_hash,
+                    is_match,
+                    reward_points,
+                    verified_at
+                FROM snapshot_verifications
+                WHERE user_id = $1
+                ORDER BY verified_at DESC
+                LIMIT $2
+                "#,
+            )
+            .bind(user_id)
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+            .context("Failed to fetch user verifications")?;
+
+            let mut verifications = Vec::new();
+            for row in rows {
+                verifications.push(VerificationRecord {
+                    id: row.try_get::<String, _>("id")?,
+                    snapshot_id: row.try_get::<String, _>("snapshot_id")?,
+                    epoch: row.try_get::<i64, _>("epoch")?,
+                    submitted_hash: row.try_get::<String, _>("submitted_hash")?,
+                    expected_hash: row.try_get::<String, _>("expected_hash")?,
+                    is_match: row.try_get::<bool, _>("is_match")?,
+                    reward_points: row.try_get::<i32, _>("reward_points")?,
+                    verified_at: row.try_get::<String, _>("verified_at")?,
+                });
+            }
 
-        Ok(verifications)
+            Ok(verifications)
+        })
     }
 
     // Private helper methods
 
     async fn check_daily_limit(&self, user_id: &str) -> Result<()> {
-        let count: i32 = sqlx::query_scalar(
-            r#"
-            SELECT COUNT(*)
-            FROM snapshot_verifications
-            WHERE user_id = ?
-            AND DATE(verified_at) = DATE('now')
-            "#,
-        )
-        .bind(user_id)
-        .fetch_one(self.db.pool())
-        .await
+        let count: i32 = with_pool!(self.db.pool(), |pool| {
+            sqlx::query_scalar(
+                r#"
+                SELECT CAST(COUNT(*) AS INTEGER)
+                FROM snapshot_verifications
+                WHERE user_id = $1
+                AND SUBSTR(verified_at, 1, 10) = $2
+                "#,
+            )
+            .bind(user_id)
+            .bind(Utc::now().format("%Y-%m-%d").to_string())
+            .fetch_one(pool)
+            .await
+        })
         .context("Failed to check daily limit")?;
 
         if count >= MAX_VERIFICATIONS_PER_DAY {
@@ -302,37 +312,35 @@ impl VerificationRewardsService {
     }
 
     async fn fetch_snapshot(&self, snapshot_id: &str) -> Result<SnapshotRecord> {
-        let row = sqlx::query(
-            r#"
-            SELECT id, hash, epoch, created_at
-            FROM snapshots
-            WHERE id = ?
-            "#,
-        )
-        .bind(snapshot_id)
-        .fetch_one(self.db.pool())
-        .await
-        .context("Snapshot not found")?;
-
-        Ok(SnapshotRecord {
-            id: row.try_get("id")?,
-            hash: row.try_get("hash")?,
-            epoch: row.try_get("epoch")?,
-            created_at: row.try_get("created_at")?,
+        with_pool!(self.db.pool(), |pool| {
+            let row = sqlx::query(
+                r#"
+                SELECT id, hash, epoch, created_at
+                FROM snapshots
+                WHERE id = $1
+                "#,
+            )
+            .bind(snapshot_id)
+            .fetch_one(pool)
+            .await
+            .context("Snapshot not found")?;
+
+            Ok(SnapshotRecord {
+                id: row.try_get("id")?,
+                hash: row.try_get("hash")?,
+                epoch: row.try_get("epoch")?,
+                created_at: row.try_get("created_at")?,
+            })
         })
     }
 
-    async fn is_early_verification(&self, snapshot_created_at: &str) -> Result<bool> {
-        // Parse the timestamp and check if verification is within 1 hour
-        let created = chrono::DateTime::parse_from_rfc3339(snapshot_created_at)
-            .or_else(|_| {
-                chrono::NaiveDateTime::parse_from_str(snapshot_created_at, "%Y-%m-%d %H:%M:%S")
-                    .map(|dt| dt.and_utc().fixed_offset())
-            })
-            .context("Failed to parse snapshot timestamp")?;
-
+    async fn is_early_verification(
+        &self,
+        snapshot_created_at: chrono::DateTime<Utc>,
+    ) -> Result<bool> {
+        // Check if verification is within 1 hour of the snapshot
         let now = Utc::now();
-        let duration = now.signed_duration_since(created);
+        let duration = now.signed_duration_since(snapshot_created_at);
 
         Ok(duration.num_hours() < 1)
     }
@@ -348,24 +356,27 @@ impl VerificationRewardsService {
         is_match: bool,
         reward_points: i32,
     ) -> Result<()> {
-        sqlx::query(
-            r#"
-            INSERT INTO snapshot_verifications 
-            (id, user_id, snapshot_id, epoch, submitted_hash, expected_hash, is_match, reward_points, verified_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(verification_id)
-        .bind(user_id)
-        .bind(snapshot_id)
-        .bind(epoch)
-        .bind(submitted_hash)
-        .bind(expected_hash)
-        .bind(is_match)
-        .bind(reward_points)
-        .bind(Utc::now().to_rfc3339())
-        .execute(self.db.pool())
-        .await
+        with_pool!(self.db.pool(), |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO snapshot_verifications 
+                (id, user_id, snapshot_id, epoch, submitted_hash, expected_hash, is_match, reward_points, verified_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                "#,
+            )
+            .bind(verification_id)
+            .bind(user_id)
+            .bind(snapshot_id)
+            .bind(epoch)
+            .bind(submitted_hash)
+            .bind(expected_hash)
+            .bind(is_match)
+            .bind(reward_points)
+            .bind(Utc::now().to_rfc3339())
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .context("Failed to record verification")?;
 
         Ok(())
@@ -378,44 +389,49 @@ impl VerificationRewardsService {
         reward_points: i32,
     ) -> Result<i32> {
         // Insert or update user rewards
-        sqlx::query(
-            r#"
-            INSERT INTO user_rewards (user_id, total_points, successful_verifications, failed_verifications, last_verification_at, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?)
-            ON CONFLICT(user_id) DO UPDATE SET
-                total_points = total_points + ?,
-                successful_verifications = successful_verifications + ?,
-                failed_verifications = failed_verifications + ?,
-                last_verification_at = ?,
-                updated_at = ?
-            "#,
-        )
-        .bind(user_id)
-        .bind(reward_points)
-        .bind(if is_match { 1 } else { 0 })
-        .bind(if is_match { 0 } else { 1 })
-        .bind(Utc::now().to_rfc3339())
-        .bind(Utc::now().to_rfc3339())
-        .bind(reward_points)
-        .bind(if is_match { 1 } else { 0 })
-        .bind(if is_match { 0 } else { 1 })
-        .bind(Utc::now().to_rfc3339())
-        .bind(Utc::now().to_rfc3339())
-        .execute(self.db.pool())
-        .await
+        with_pool!(self.db.pool(), |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO user_rewards (user_id, total_points, successful_verifications, failed_verifications, last_verification_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT(user_id) DO UPDATE SET
+                    total_points = user_rewards.total_points + $7,
+                    successful_verifications = user_rewards.successful_verifications + $8,
+                    failed_verifications = user_rewards.failed_verifications + $9,
+                    last_verification_at = $10,
+                    updated_at = $11
+                "#,
+            )
+            .bind(user_id)
+            .bind(reward_points)
+            .bind(if is_match { 1 } else { 0 })
+            .bind(if is_match { 0 } else { 1 })
+            .bind(Utc::now().to_rfc3339())
+            .bind(Utc::now().to_rfc3339())
+            .bind(reward_points)
+            .bind(if is_match { 1 } else { 0 })
+            .bind(if is_match { 0 } else { 1 })
+            .bind(Utc::now().to_rfc3339())
+            .bind(Utc::now().to_rfc3339())
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .context("Failed to update user rewards")?;
 
         // Fetch updated total
-        let total: i32 = sqlx::query_scalar(
-            r#"
-            SELECT total_points
-            FROM user_rewards
-            WHERE user_id = ?
-            "#,
-        )
-        .bind(user_id)
-        .fetch_one(self.db.pool())
-        .await
+        let total: i32 = with_pool!(self.db.pool(), |pool| {
+            sqlx::query_scalar(
+                r#"
+                SELECT total_points
+                FROM user_rewards
+                WHERE user_id = $1
+                "#,
+            )
+            .bind(user_id)
+            .fetch_one(pool)
+            .await
+        })
         .context("Failed to fetch updated total")?;
 
         Ok(total)
@@ -427,7 +443,7 @@ struct SnapshotRecord {
     id: String,
     hash: String,
     epoch: i64,
-    created_at: String,
+    created_at: chrono::DateTime<Utc>,
 }
 
 #[derive(Debug, Serialize)]
@@ -1,35 +1,144 @@
+use crate::database::DbPool;
 /// Webhook Dispatcher Service
 /// Processes webhook events and sends them to registered webhooks with retry logic
 use anyhow::Result;
-use reqwest::Client;
-use sqlx::SqlitePool;
-use std::time::Duration;
-use uuid::Uuid;
+use rand::Rng;
+use reqwest::{Client, Response};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
 
-use crate::webhooks::{WebhookEventEnvelope, WebhookService, WebhookSignature};
+use crate::webhooks::delivery::{AttemptOutcome, DueEvent, RESPONSE_SNIPPET_BYTES};
+use crate::webhooks::{Webhook, WebhookEventEnvelope, WebhookService, WebhookSignature};
+
+/// Delivery schedule and limits of the dispatcher
+#[derive(Debug, Clone)]
+pub struct WebhookDispatcherConfig {
+    /// Time between checks for due events
+    pub poll_interval: Duration,
+    /// Due events fetched per check
+    pub batch_size: usize,
+    /// Attempts per event before it is dead-lettered
+    pub max_attempts: i32,
+    /// Delay after the first failed attempt; doubled after each further failure
+    pub base_retry_delay: Duration,
+    /// Longest delay between attempts
+    pub max_retry_delay: Duration,
+    /// Requests in flight to the same host
+    pub per_host_concurrency: usize,
+    /// An endpoint is disabled after this many failed attempts in a row...
+    pub disable_after_failures: i32,
+    /// ...spanning at least this long
+    pub disable_after: Duration,
+    pub request_timeout: Duration,
+}
+
+impl Default for WebhookDispatcherConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(5),
+            batch_size: 100,
+            max_attempts: 10,
+            base_retry_delay: Duration::from_secs(30),
+            max_retry_delay: Duration::from_secs(6 * 3600),
+            per_host_concurrency: 4,
+            disable_after_failures: 20,
+            disable_after: Duration::from_secs(24 * 3600),
+            request_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl WebhookDispatcherConfig {
+    /// Reads `WEBHOOK_POLL_SECONDS`, `WEBHOOK_BATCH_SIZE`, `WEBHOOK_MAX_ATTEMPTS`,
+    /// `WEBHOOK_RETRY_BASE_SECONDS`, `WEBHOOK_RETRY_MAX_SECONDS`,
+    /// `WEBHOOK_HOST_CONCURRENCY`, `WEBHOOK_DISABLE_AFTER_FAILURES`,
+    /// `WEBHOOK_DISABLE_AFTER_HOURS` and `WEBHOOK_TIMEOUT_SECONDS`, falling back to the
+    /// defaults.
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Some(seconds) = env_parse::<u64>("WEBHOOK_POLL_SECONDS").filter(|s| *s > 0) {
+            config.poll_interval = Duration::from_secs(seconds);
+        }
+        if let Some(size) = env_parse::<usize>("WEBHOOK_BATCH_SIZE").filter(|s| *s > 0) {
+            config.batch_size = size;
+        }
+        if let Some(attempts) = env_parse::<i32>("WEBHOOK_MAX_ATTEMPTS").filter(|a| *a > 0) {
+            config.max_attempts = attempts;
+        }
+        if let Some(seconds) = env_parse::<u64>("WEBHOOK_RETRY_BASE_SECONDS") {
+            config.base_retry_delay = Duration::from_secs(seconds);
+        }
+        if let Some(seconds) = env_parse::<u64>("WEBHOOK_RETRY_MAX_SECONDS") {
+            config.max_retry_delay = Duration::from_secs(seconds);
+        }
+        if let Some(limit) = env_parse::<usize>("WEBHOOK_HOST_CONCURRENCY").filter(|l| *l > 0) {
+            config.per_host_concurrency = limit;
+        }
+        if let Some(failures) = env_parse::<i32>("WEBHOOK_DISABLE_AFTER_FAILURES") {
+            config.disable_after_failures = failures;
+        }
+        if let Some(hours) = env_parse::<u64>("WEBHOOK_DISABLE_AFTER_HOURS") {
+            config.disable_after = Duration::from_secs(hours * 3600);
+        }
+        if let Some(seconds) = env_parse::<u64>("WEBHOOK_TIMEOUT_SECONDS").filter(|s| *s > 0) {
+            config.request_timeout = Duration::from_secs(seconds);
+        }
+        config
+    }
+
+    /// Delay before the next attempt of an event that has failed `failed_attempts`
+    /// times: exponential in the attempts, capped, with the upper half jittered so
+    /// events that failed together do not retry together
+    pub fn retry_delay(&self, failed_attempts: i32) -> Duration {
+        let exponent = failed_attempts.saturating_sub(1).clamp(0, 30) as u32;
+        let delay = self
+            .base_retry_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_retry_delay);
+        delay.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+    }
+}
+
+fn env_parse<T: std::str::FromStr>(name: &str) -> Option<T> {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+}
 
 /// Webhook dispatcher - sends events to webhooks asynchronously
 pub struct WebhookDispatcher {
-    db: SqlitePool,
+    db: DbPool,
     http_client: Client,
+    config: WebhookDispatcherConfig,
+    /// Limits concurrent requests per destination host, so one slow integrator does
+    /// not hold up deliveries to the others
+    host_slots: Mutex<HashMap<String, Arc<Semaphore>>>,
 }
 
 impl WebhookDispatcher {
     /// Create new webhook dispatcher
-    pub fn new(db: SqlitePool) -> Self {
+    pub fn new(db: DbPool, config: WebhookDispatcherConfig) -> Self {
         let http_client = Client::builder()
-            .timeout(Duration::from_secs(10))
+            .timeout(config.request_timeout)
             .build()
             .unwrap_or_else(|_| Client::new());
 
-        Self { db, http_client }
+        Self {
+            db,
+            http_client,
+            config,
+            host_slots: Mutex::new(HashMap::new()),
+        }
     }
 
     /// Run dispatcher loop - processes pending webhook events
     pub async fn run(&self) -> Result<()> {
         tracing::info!("Starting webhook dispatcher");
 
-        let mut interval = tokio::time::interval(Duration::from_secs(5));
+        let mut interval = tokio::time::interval(self.config.poll_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
 
         loop {
             interval.tick().await;
@@ -40,164 +149,418 @@ impl WebhookDispatcher {
         }
     }
 
-    /// Process all pending webhook events
-    async fn process_pending_events(&self) -> Result<()> {
+    /// Attempt every due event, concurrently up to the per-host limit. Returns the
+    /// number of events attempted.
+    pub async fn process_pending_events(&self) -> Result<usize> {
         let service = WebhookService::new(self.db.clone());
+        let events = service.due_events(self.config.batch_size).await?;
+        let count = events.len();
 
-        // Fetch pending events (max 10 per run)
-        let events = service.get_pending_events(10).await?;
-
-        for (event_id, webhook_id, event_type, payload_str) in events {
-            // Get webhook details
-            let webhook = match service.get_webhook(&webhook_id).await? {
-                Some(w) => w,
-                None => {
-                    // Webhook was deleted, mark event as failed
-                    let _ = service
-                        .update_event_status(&event_id, "failed", Some("webhook_deleted"), 0)
-                        .await;
-                    continue;
+        futures::future::join_all(events.into_iter().map(|event| {
+            let service = &service;
+            async move {
+                let event_id = event.id.clone();
+                if let Err(e) = self.process_event(service, event).await {
+                    tracing::error!("Failed to process webhook event {}: {}", event_id, e);
                 }
-            };
+            }
+        }))
+        .await;
 
-            if !webhook.is_active {
-                let _ = service
-                    .update_event_status(&event_id, "failed", Some("webhook_inactive"), 0)
+        Ok(count)
+    }
+
+    async fn process_event(&self, service: &WebhookService, event: DueEvent) -> Result<()> {
+        let webhook = match service.get_webhook(&event.webhook_id).await? {
+            Some(w) if w.is_active && w.disabled_at.is_none() => w,
+            Some(w) => {
+                let reason = if w.is_active {
+                    "webhook_disabled"
+                } else {
+                    "webhook_inactive"
+                };
+                return service
+                    .dead_letter_event(&event, event.retries, reason)
                     .await;
-                continue;
             }
-
-            // Attempt delivery
-            match self
-                .deliver_webhook(&webhook.url, &payload_str, &webhook.secret, &event_type)
-                .await
-            {
-                Ok(_) => {
-                    // Success
-                    let _ = service
-                        .update_event_status(&event_id, "delivered", None, 0)
-                        .await;
-
-                    // Update webhook's last_fired_at
-                    let _ = service.update_last_fired(&webhook_id).await;
-
-                    tracing::info!(
-                        "Webhook delivered successfully: webhook_id={}, event={}",
-                        webhook_id,
-                        event_type
-                    );
-                }
-                Err(e) => {
-                    // Determine retry count from event
-                    let current_retries = self.get_event_retries(&event_id).await.unwrap_or(0);
-
-                    if current_retries < 3 {
-                        // Retry later
-                        let _ = service
-                            .update_event_status(
-                                &event_id,
-                                "pending",
-                                Some(&e.to_string()),
-                                current_retries + 1,
-                            )
-                            .await;
-
-                        tracing::warn!(
-                            "Webhook delivery failed (will retry): webhook_id={}, error={}, retries={}",
-                            webhook_id,
-                            e,
-                            current_retries + 1
-                        );
-                    } else {
-                        // Max retries exceeded
-                        let _ = service
-                            .update_event_status(&event_id, "failed", Some(&e.to_string()), 3)
-                            .await;
-
-                        tracing::error!(
-                            "Webhook delivery failed (max retries): webhook_id={}, error={}",
-                            webhook_id,
-                            e
-                        );
-                    }
-                }
+            None => {
+                return service
+                    .dead_letter_event(&event, event.retries, "webhook_deleted")
+                    .await;
             }
+        };
+
+        let attempt = event.retries + 1;
+        let outcome = {
+            let slot = self.host_slot(&webhook.url);
+            let _permit = slot.acquire().await?;
+            self.deliver_webhook(&webhook, &event, attempt).await
+        };
+        service
+            .record_delivery_attempt(&event, attempt, &outcome)
+            .await?;
+
+        let Some(error) = outcome.error else {
+            service.mark_event_delivered(&event.id, attempt).await?;
+            service.record_endpoint_success(&webhook.id).await?;
+            tracing::info!(
+                "Webhook delivered successfully: webhook_id={}, event={}, attempt={}",
+                webhook.id,
+                event.event_type,
+                attempt
+            );
+            return Ok(());
+        };
+
+        if attempt >= self.config.max_attempts {
+            service.dead_letter_event(&event, attempt, &error).await?;
+            tracing::error!(
+                "Webhook delivery failed (dead-lettered): webhook_id={}, event_id={}, error={}, attempts={}",
+                webhook.id,
+                event.id,
+                error,
+                attempt
+            );
+        } else {
+            let delay = self.config.retry_delay(attempt);
+            let next_attempt_at = chrono::Utc::now() + chrono::Duration::from_std(delay)?;
+            service
+                .schedule_event_retry(&event.id, attempt, &error, next_attempt_at)
+                .await?;
+            tracing::warn!(
+                "Webhook delivery failed (will retry in {:?}): webhook_id={}, error={}, attempts={}",
+                delay,
+                webhook.id,
+                error,
+                attempt
+            );
+        }
+
+        let disabled = service
+            .record_endpoint_failure(
+                &webhook.id,
+                self.config.disable_after_failures,
+                chrono::Duration::from_std(self.config.disable_after)?,
+                &format!("Deliveries kept failing; last error: {}", error),
+            )
+            .await?;
+        if disabled {
+            tracing::warn!(
+                "Webhook disabled after persistent delivery failures: webhook_id={}",
+                webhook.id
+            );
         }
 
         Ok(())
     }
 
+    /// Semaphore shared by every delivery to the host of `url`
+    fn host_slot(&self, url: &str) -> Arc<Semaphore> {
+        let host = url::Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string))
+            .unwrap_or_else(|| url.to_string());
+        let mut slots = self.host_slots.lock().unwrap();
+        Arc::clone(
+            slots
+                .entry(host)
+                .or_insert_with(|| Arc::new(Semaphore::new(self.config.per_host_concurrency))),
+        )
+    }
+
     /// Deliver webhook to URL
     async fn deliver_webhook(
         &self,
-        url: &str,
-        payload: &str,
-        secret: &str,
-        event_type: &str,
-    ) -> Result<()> {
-        let delivery_id = Uuid::new_v4().to_string();
+        webhook: &Webhook,
+        event: &DueEvent,
+        attempt: i32,
+    ) -> AttemptOutcome {
         let timestamp = chrono::Utc::now().timestamp();
 
         // Create envelope
+        let data = match serde_json::from_str(&event.payload) {
+            Ok(data) => data,
+            Err(e) => {
+                return AttemptOutcome {
+                    error: Some(format!("Invalid event payload: {}", e)),
+                    ..Default::default()
+                }
+            }
+        };
         let envelope = WebhookEventEnvelope {
-            id: delivery_id.clone(),
-            event: event_type.to_string(),
+            id: event.id.clone(),
+            event: event.event_type.clone(),
             timestamp,
-            data: serde_json::from_str(payload)?,
+            data,
         };
 
-        let body = serde_json::to_string(&envelope)?;
-        let signature = WebhookSignature::sign(&body, secret);
+        let body = match serde_json::to_string(&envelope) {
+            Ok(body) => body,
+            Err(e) => {
+                return AttemptOutcome {
+                    error: Some(format!("Failed to encode event: {}", e)),
+                    ..Default::default()
+                }
+            }
+        };
+        let signature = WebhookSignature::sign(&body, timestamp, &webhook.signing_secrets());
 
         tracing::debug!(
-            "Sending webhook to {}: delivery_id={}, signature={}...",
-            url,
-            delivery_id,
-            &signature[..20]
+            "Sending webhook to {}: delivery_id={}, attempt={}",
+            webhook.url,
+            event.id,
+            attempt
         );
 
-        let response = self
+        let started = Instant::now();
+        let result = self
             .http_client
-            .post(url)
-            .header("X-Zapier-Event", event_type)
+            .post(&webhook.url)
+            .header("X-Zapier-Event", &event.event_type)
             .header("X-Zapier-Signature", signature)
             .header("X-Zapier-Timestamp", timestamp.to_string())
-            .header("X-Zapier-Delivery-ID", delivery_id)
+            .header("X-Zapier-Delivery-ID", &event.id)
+            .header("X-Zapier-Attempt", attempt.to_string())
             .header("Content-Type", "application/json")
             .body(body)
             .send()
-            .await?;
+            .await;
+        let latency_ms = started.elapsed().as_millis() as i64;
 
-        if response.status().is_success() {
-            Ok(())
-        } else {
-            anyhow::bail!(
-                "Webhook failed with status {}: {}",
-                response.status(),
-                response.text().await.unwrap_or_default()
-            )
+        match result {
+            Ok(response) => {
+                let status = response.status();
+                AttemptOutcome {
+                    status_code: Some(status.as_u16()),
+                    latency_ms,
+                    response_snippet: read_snippet(response).await,
+                    error: (!status.is_success()).then(|| format!("HTTP {}", status)),
+                }
+            }
+            Err(e) => AttemptOutcome {
+                latency_ms,
+                error: Some(e.to_string()),
+                ..Default::default()
+            },
         }
     }
+}
 
-    /// Get current retry count for an event
-    async fn get_event_retries(&self, event_id: &str) -> Result<i32> {
-        let retries: Option<i64> =
-            sqlx::query_scalar("SELECT retries FROM webhook_events WHERE id = ?")
-                .bind(event_id)
-                .fetch_optional(&self.db)
-                .await?;
-
-        Ok(retries.unwrap_or(0) as i32)
+/// Start of the response body, without downloading more of it than is kept
+async fn read_snippet(mut response: Response) -> Option<String> {
+    let mut body = Vec::new();
+    while body.len() < RESPONSE_SNIPPET_BYTES {
+        match response.chunk().await {
+            Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+            _ => break,
+        }
     }
+    body.truncate(RESPONSE_SNIPPET_BYTES);
+    (!body.is_empty()).then(|| String::from_utf8_lossy(&body).into_owned())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::webhooks::signature::DEFAULT_TOLERANCE_SECONDS;
+    use axum::{
+        http::{HeaderMap, StatusCode},
+        routing::post,
+        Router,
+    };
 
     #[test]
     fn test_webhook_dispatcher_creation() {
         // This is a smoke test for basic creation
         // Full tests would require mocking the database and HTTP client
     }
+
+    #[test]
+    fn test_retry_delay_grows_with_jitter_and_cap() {
+        let config = WebhookDispatcherConfig {
+            base_retry_delay: Duration::from_secs(30),
+            max_retry_delay: Duration::from_secs(600),
+            ..Default::default()
+        };
+        for _ in 0..20 {
+            let first = config.retry_delay(1);
+            assert!(first >= Duration::from_secs(15) && first <= Duration::from_secs(30));
+            let third = config.retry_delay(3);
+            assert!(third >= Duration::from_secs(60) && third <= Duration::from_secs(120));
+            let capped = config.retry_delay(40);
+            assert!(capped >= Duration::from_secs(300) && capped <= Duration::from_secs(600));
+        }
+    }
+
+    /// Signature header and body of each request the test server received
+    type Received = Arc<Mutex<Vec<(String, String)>>>;
+
+    /// Dispatcher over an in-memory database with one webhook pointing at a local
+    /// server that answers `status` with `body`
+    async fn setup(
+        status: StatusCode,
+        body: &'static str,
+        config: WebhookDispatcherConfig,
+    ) -> (WebhookDispatcher, WebhookService, String, Received) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let received = Received::default();
+        let requests = Arc::clone(&received);
+        let app = Router::new().route(
+            "/hook",
+            post(move |headers: HeaderMap, request: String| async move {
+                let signature = headers
+                    .get("X-Zapier-Signature")
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or_default()
+                    .to_string();
+                requests.lock().unwrap().push((signature, request));
+                (status, body)
+            }),
+        );
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let pool = crate::test_support::migrated_pool().await;
+        sqlx::query("INSERT INTO users (id, username) VALUES ('u1', 'alice')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let service = WebhookService::new(pool.clone().into());
+        let webhook = service
+            .register_webhook(
+                "u1",
+                crate::webhooks::CreateWebhookRequest {
+                    url: format!("http://{}/hook", addr),
+                    event_types: vec!["payment.created".to_string()],
+                    filters: None,
+                },
+            )
+            .await
+            .unwrap();
+        service
+            .queue_event(
+                &crate::webhooks::WebhookEventType::PaymentCreated,
+                serde_json::json!({"payment_id": "p1"}),
+            )
+            .await
+            .unwrap();
+
+        (
+            WebhookDispatcher::new(pool.into(), config),
+            service,
+            webhook.id,
+            received,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_failed_delivery_is_recorded_and_rescheduled() {
+        let (dispatcher, service, webhook_id, _) = setup(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "maintenance",
+            WebhookDispatcherConfig::default(),
+        )
+        .await;
+
+        assert_eq!(dispatcher.process_pending_events().await.unwrap(), 1);
+        // Not due again until the backoff has passed
+        assert_eq!(dispatcher.process_pending_events().await.unwrap(), 0);
+
+        let deliveries = service
+            .list_deliveries(&webhook_id, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(deliveries[0].status, "pending");
+        assert_eq!(deliveries[0].attempts, 1);
+        assert!(deliveries[0].next_attempt_at.is_some());
+
+        let attempts = service
+            .list_delivery_attempts(&webhook_id, &deliveries[0].id)
+            .await
+            .unwrap();
+        assert_eq!(attempts.len(), 1);
+        assert_eq!(attempts[0].status_code, Some(503));
+        assert_eq!(attempts[0].response_snippet.as_deref(), Some("maintenance"));
+
+        let webhook = service.get_webhook(&webhook_id).await.unwrap().unwrap();
+        assert_eq!(webhook.consecutive_failures, 1);
+    }
+
+    #[tokio::test]
+    async fn test_last_failed_attempt_dead_letters_and_disables() {
+        let config = WebhookDispatcherConfig {
+            max_attempts: 1,
+            disable_after_failures: 1,
+            disable_after: Duration::ZERO,
+            ..Default::default()
+        };
+        let (dispatcher, service, webhook_id, _) =
+            setup(StatusCode::INTERNAL_SERVER_ERROR, "", config).await;
+
+        dispatcher.process_pending_events().await.unwrap();
+
+        let dead_letters = service.list_dead_letters(&webhook_id, 10).await.unwrap();
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(dead_letters[0].reason, "HTTP 500 Internal Server Error");
+        let webhook = service.get_webhook(&webhook_id).await.unwrap().unwrap();
+        assert!(webhook.disabled_at.is_some());
+
+        // Redelivered events of a disabled endpoint go straight back to dead letters
+        service
+            .redeliver_event(&webhook_id, &dead_letters[0].event_id)
+            .await
+            .unwrap();
+        dispatcher.process_pending_events().await.unwrap();
+        let dead_letters = service.list_dead_letters(&webhook_id, 10).await.unwrap();
+        assert_eq!(dead_letters[0].reason, "webhook_disabled");
+    }
+
+    #[tokio::test]
+    async fn test_successful_delivery() {
+        let (dispatcher, service, webhook_id, _) =
+            setup(StatusCode::OK, "ok", WebhookDispatcherConfig::default()).await;
+
+        dispatcher.process_pending_events().await.unwrap();
+
+        let deliveries = service
+            .list_deliveries(&webhook_id, Some("delivered"), 10)
+            .await
+            .unwrap();
+        assert_eq!(deliveries.len(), 1);
+        assert!(deliveries[0].delivered_at.is_some());
+        let webhook = service.get_webhook(&webhook_id).await.unwrap().unwrap();
+        assert!(webhook.last_fired_at.is_some());
+        assert_eq!(webhook.consecutive_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_deliveries_are_signed_with_current_and_previous_secret() {
+        let (dispatcher, service, webhook_id, received) =
+            setup(StatusCode::OK, "ok", WebhookDispatcherConfig::default()).await;
+        let original = service
+            .get_webhook(&webhook_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .secret;
+        let rotated = service
+            .rotate_secret(&webhook_id, "u1", chrono::Duration::hours(1))
+            .await
+            .unwrap()
+            .unwrap();
+
+        dispatcher.process_pending_events().await.unwrap();
+
+        let (signature, body) = received.lock().unwrap().pop().unwrap();
+        for secret in [original.as_str(), rotated.secret.as_str()] {
+            assert!(WebhookSignature::verify(
+                &signature,
+                &body,
+                &[secret],
+                DEFAULT_TOLERANCE_SECONDS
+            )
+            .is_ok());
+        }
+    }
 }
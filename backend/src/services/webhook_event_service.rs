@@ -2,7 +2,8 @@
 /// Triggers webhook events for corridor events, anchor status changes, and alerts
 use anyhow::Result;
 use serde_json::json;
-use sqlx::SqlitePool;
+use crate::with_pool;
+use crate::database::DbPool;
 use std::sync::Arc;
 
 use crate::webhooks::{
@@ -17,7 +18,7 @@ pub struct WebhookEventService {
 }
 
 impl WebhookEventService {
-    pub fn new(db: SqlitePool) -> Self {
+    pub fn new(db: DbPool) -> Self {
         Self {
             webhook_service: Arc::new(WebhookService::new(db)),
         }
@@ -149,12 +150,14 @@ impl WebhookEventService {
     async fn get_webhooks_for_event(&self, event_type: &str) -> Result<Vec<crate::webhooks::Webhook>> {
         // For now, we'll get all active webhooks and filter in memory
         // In a production system, you might want to optimize this with a better query
-        let all_webhooks = sqlx::query_as::<_, crate::webhooks::Webhook>(
-            "SELECT id, user_id, url, event_types, filters, secret, is_active, created_at, last_fired_at 
-             FROM webhooks WHERE is_active = 1"
-        )
-        .fetch_all(&self.webhook_service.db)
-        .await?;
+        let all_webhooks = with_pool!(&self.webhook_service.db, |pool| {
+            sqlx::query_as::<_, crate::webhooks::Webhook>(
+                "SELECT id, user_id, url, event_types, filters, secret, is_active, created_at, last_fired_at 
+                 FROM webhooks WHERE is_active = TRUE"
+            )
+            .fetch_all(pool)
+            .await
+        })?;
 
         let matching_webhooks: Vec<crate::webhooks::Webhook> = all_webhooks
             .into_iter()
@@ -193,7 +196,7 @@ mod tests {
 
     #[test]
     fn test_filter_application() {
-        let service = WebhookEventService::new(sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap());
+        let service = WebhookEventService::new(crate::database::DbPool::connect("sqlite::memory:").await.unwrap());
         
         let payload = json!({
             "corridor_key": "USDC:GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN->XLM:native",
@@ -167,7 +167,7 @@ pub async fn shutdown_background_tasks(
 /// Gracefully close database connection pool
 ///
 /// Attempts to close the database pool within the timeout period.
-pub async fn shutdown_database(pool: sqlx::SqlitePool, timeout_duration: Duration) {
+pub async fn shutdown_database(pool: crate::database::DbPool, timeout_duration: Duration) {
     info!("Closing database connections");
 
     let close_future = async {
@@ -1,8 +1,10 @@
-use sqlx::SqlitePool;
+use crate::database::DbPool;
+use crate::with_pool;
+use chrono::Utc;
 use uuid::Uuid;
 
 pub struct SubscriptionService {
-    pool: SqlitePool,
+    pool: DbPool,
 }
 
 #[derive(Debug, Clone, sqlx::FromRow)]
@@ -19,7 +21,7 @@ pub struct TelegramSubscription {
 }
 
 impl SubscriptionService {
-    pub fn new(pool: SqlitePool) -> Self {
+    pub fn new(pool: DbPool) -> Self {
         Self { pool }
     }
 
@@ -30,77 +32,95 @@ impl SubscriptionService {
         chat_title: Option<&str>,
         username: Option<&str>,
     ) -> anyhow::Result<bool> {
-        let existing: Option<TelegramSubscription> =
-            sqlx::query_as("SELECT * FROM telegram_subscriptions WHERE chat_id = ?")
+        let existing: Option<TelegramSubscription> = with_pool!(&self.pool, |pool| {
+            sqlx::query_as("SELECT * FROM telegram_subscriptions WHERE chat_id = $1")
                 .bind(chat_id)
-                .fetch_optional(&self.pool)
-                .await?;
+                .fetch_optional(pool)
+                .await
+        })?;
 
         if let Some(sub) = existing {
             if sub.is_active == 1 {
                 return Ok(false); // already subscribed
             }
             // Re-activate
-            sqlx::query("UPDATE telegram_subscriptions SET is_active = 1, subscribed_at = datetime('now') WHERE chat_id = ?")
-                .bind(chat_id)
-                .execute(&self.pool)
-                .await?;
+            with_pool!(&self.pool, |pool| {
+                sqlx::query("UPDATE telegram_subscriptions SET is_active = 1, subscribed_at = $1 WHERE chat_id = $2")
+                    .bind(Utc::now().to_rfc3339())
+                    .bind(chat_id)
+                    .execute(pool)
+                    .await
+                    .map(|done| done.rows_affected())
+            })?;
             return Ok(true);
         }
 
         let id = Uuid::new_v4().to_string();
-        sqlx::query(
-            "INSERT INTO telegram_subscriptions (id, chat_id, chat_type, chat_title, username) VALUES (?, ?, ?, ?, ?)"
-        )
-            .bind(&id)
-            .bind(chat_id)
-            .bind(chat_type)
-            .bind(chat_title)
-            .bind(username)
-            .execute(&self.pool)
-            .await?;
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                "INSERT INTO telegram_subscriptions (id, chat_id, chat_type, chat_title, username) VALUES ($1, $2, $3, $4, $5)"
+            )
+                .bind(&id)
+                .bind(chat_id)
+                .bind(chat_type)
+                .bind(chat_title)
+                .bind(username)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+        })?;
 
         Ok(true)
     }
 
     pub async fn unsubscribe(&self, chat_id: i64) -> anyhow::Result<bool> {
-        let result = sqlx::query(
-            "UPDATE telegram_subscriptions SET is_active = 0 WHERE chat_id = ? AND is_active = 1",
-        )
-        .bind(chat_id)
-        .execute(&self.pool)
-        .await?;
+        let result = with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                "UPDATE telegram_subscriptions SET is_active = 0 WHERE chat_id = $1 AND is_active = 1",
+            )
+            .bind(chat_id)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
 
-        Ok(result.rows_affected() > 0)
+        Ok(result > 0)
     }
 
     pub async fn get_active_chat_ids(&self) -> anyhow::Result<Vec<i64>> {
-        let rows: Vec<(i64,)> =
+        let rows: Vec<(i64,)> = with_pool!(&self.pool, |pool| {
             sqlx::query_as("SELECT chat_id FROM telegram_subscriptions WHERE is_active = 1")
-                .fetch_all(&self.pool)
-                .await?;
+                .fetch_all(pool)
+                .await
+        })?;
 
         Ok(rows.into_iter().map(|(id,)| id).collect())
     }
 
     pub async fn is_subscribed(&self, chat_id: i64) -> anyhow::Result<bool> {
-        let row: Option<(i64,)> = sqlx::query_as(
-            "SELECT COUNT(*) FROM telegram_subscriptions WHERE chat_id = ? AND is_active = 1",
-        )
-        .bind(chat_id)
-        .fetch_optional(&self.pool)
-        .await?;
+        let row: Option<(i64,)> = with_pool!(&self.pool, |pool| {
+            sqlx::query_as(
+                "SELECT COUNT(*) FROM telegram_subscriptions WHERE chat_id = $1 AND is_active = 1",
+            )
+            .bind(chat_id)
+            .fetch_optional(pool)
+            .await
+        })?;
 
         Ok(row.map(|(c,)| c > 0).unwrap_or(false))
     }
 
     pub async fn update_last_alert_sent(&self, chat_id: i64) -> anyhow::Result<()> {
-        sqlx::query(
-            "UPDATE telegram_subscriptions SET last_alert_sent_at = datetime('now') WHERE chat_id = ?",
-        )
-        .bind(chat_id)
-        .execute(&self.pool)
-        .await?;
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                "UPDATE telegram_subscriptions SET last_alert_sent_at = $1 WHERE chat_id = $2",
+            )
+            .bind(Utc::now().to_rfc3339())
+            .bind(chat_id)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
 
         Ok(())
     }
@@ -0,0 +1,60 @@
+//! Shared fixtures for unit tests
+
+use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
+use std::path::PathBuf;
+
+use crate::database::DbPool;
+
+/// Indexes in `migrations/` on columns their tables lack at that point. SQLite aborts the
+/// migration at these statements, so they are dropped before it runs.
+const INDEXES_ON_MISSING_COLUMNS: [&str; 3] = [
+    "idx_snapshots_ledger",
+    "idx_corridors_pair",
+    "idx_assets_chain",
+];
+
+impl DbPool {
+    /// The SQLite pool a test built its service on
+    pub fn sqlite(&self) -> &SqlitePool {
+        match self {
+            DbPool::Sqlite(pool) => pool,
+            DbPool::Postgres(_) => panic!("test pool is not SQLite"),
+        }
+    }
+}
+
+/// In-memory SQLite database with every migration in `migrations/` applied in order
+pub async fn migrated_pool() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("migrations");
+    let mut files: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "sql"))
+        .collect();
+    files.sort();
+
+    for path in files {
+        let sql = without_broken_indexes(std::fs::read_to_string(&path).unwrap());
+        if let Err(e) = sqlx::raw_sql(&sql).execute(&pool).await {
+            panic!("migration {} failed: {}", path.display(), e);
+        }
+    }
+    pool
+}
+
+fn without_broken_indexes(mut sql: String) -> String {
+    for index in INDEXES_ON_MISSING_COLUMNS {
+        let Some(start) = sql.find(&format!("CREATE INDEX IF NOT EXISTS {}", index)) else {
+            continue;
+        };
+        let end = sql[start..].find(';').map_or(sql.len(), |i| start + i + 1);
+        sql.replace_range(start..end, "");
+    }
+    sql
+}
@@ -0,0 +1,572 @@
+//! Delivery bookkeeping for queued webhook events: the retry schedule, the attempt
+//! history shown to integrators and the dead-letter store.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::WebhookService;
+use crate::with_pool;
+
+/// Waiting for its next attempt
+pub const EVENT_PENDING: &str = "pending";
+pub const EVENT_DELIVERED: &str = "delivered";
+/// Out of attempts or its endpoint was disabled; kept until redelivered
+pub const EVENT_DEAD_LETTERED: &str = "dead_lettered";
+
+/// Bytes of a response body kept with a delivery attempt
+pub const RESPONSE_SNIPPET_BYTES: usize = 512;
+
+/// Event whose next attempt is due
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct DueEvent {
+    pub id: String,
+    pub webhook_id: String,
+    pub event_type: String,
+    pub payload: String,
+    /// Attempts made so far
+    pub retries: i32,
+}
+
+/// Queued event as shown to the webhook owner
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub event_type: String,
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: Option<String>,
+    pub delivered_at: Option<String>,
+    pub last_error: Option<String>,
+    pub created_at: String,
+}
+
+/// One HTTP request made for an event
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct DeliveryAttempt {
+    pub id: String,
+    pub event_id: String,
+    pub attempt: i32,
+    /// None when no response was received
+    pub status_code: Option<i32>,
+    pub latency_ms: i64,
+    pub response_snippet: Option<String>,
+    pub error: Option<String>,
+    pub attempted_at: String,
+}
+
+/// Result of a request, before it is recorded
+#[derive(Debug, Clone, Default)]
+pub struct AttemptOutcome {
+    pub status_code: Option<u16>,
+    pub latency_ms: i64,
+    pub response_snippet: Option<String>,
+    /// None only for a 2xx response
+    pub error: Option<String>,
+}
+
+/// Entry of the dead-letter store
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct DeadLetter {
+    pub event_id: String,
+    pub event_type: String,
+    pub reason: String,
+    pub attempts: i32,
+    pub created_at: String,
+    pub dead_lettered_at: String,
+}
+
+impl WebhookService {
+    /// Pending events whose next attempt is due, oldest first
+    pub async fn due_events(&self, limit: usize) -> anyhow::Result<Vec<DueEvent>> {
+        let events = with_pool!(&self.db, |pool| {
+            sqlx::query_as::<_, DueEvent>(
+                "SELECT id, webhook_id, event_type, payload, retries FROM webhook_events
+                 WHERE status = $1 AND (next_attempt_at IS NULL OR next_attempt_at <= $2)
+                 ORDER BY created_at ASC
+                 LIMIT $3",
+            )
+            .bind(EVENT_PENDING)
+            .bind(Utc::now().to_rfc3339())
+            .bind(limit as i64)
+            .fetch_all(pool)
+            .await
+        })?;
+
+        Ok(events)
+    }
+
+    /// Store the outcome of attempt number `attempt` of `event`
+    pub async fn record_delivery_attempt(
+        &self,
+        event: &DueEvent,
+        attempt: i32,
+        outcome: &AttemptOutcome,
+    ) -> anyhow::Result<()> {
+        with_pool!(&self.db, |pool| {
+            sqlx::query(
+                "INSERT INTO webhook_delivery_attempts (id, event_id, webhook_id, attempt, status_code,
+                 latency_ms, response_snippet, error, attempted_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(&event.id)
+            .bind(&event.webhook_id)
+            .bind(attempt)
+            .bind(outcome.status_code.map(i32::from))
+            .bind(outcome.latency_ms)
+            .bind(outcome.response_snippet.as_deref())
+            .bind(outcome.error.as_deref())
+            .bind(Utc::now().to_rfc3339())
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
+
+        Ok(())
+    }
+
+    pub async fn mark_event_delivered(&self, event_id: &str, attempts: i32) -> anyhow::Result<()> {
+        with_pool!(&self.db, |pool| {
+            sqlx::query(
+                "UPDATE webhook_events SET status = $1, retries = $2, last_error = NULL,
+                 next_attempt_at = NULL, delivered_at = $3 WHERE id = $4",
+            )
+            .bind(EVENT_DELIVERED)
+            .bind(attempts)
+            .bind(Utc::now().to_rfc3339())
+            .bind(event_id)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
+
+        Ok(())
+    }
+
+    /// Keep the event pending until `next_attempt_at`
+    pub async fn schedule_event_retry(
+        &self,
+        event_id: &str,
+        attempts: i32,
+        error: &str,
+        next_attempt_at: DateTime<Utc>,
+    ) -> anyhow::Result<()> {
+        with_pool!(&self.db, |pool| {
+            sqlx::query(
+                "UPDATE webhook_events SET status = $1, retries = $2, last_error = $3,
+                 next_attempt_at = $4 WHERE id = $5",
+            )
+            .bind(EVENT_PENDING)
+            .bind(attempts)
+            .bind(error)
+            .bind(next_attempt_at.to_rfc3339())
+            .bind(event_id)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
+
+        Ok(())
+    }
+
+    /// Stop retrying the event and move it to the dead-letter store
+    pub async fn dead_letter_event(
+        &self,
+        event: &DueEvent,
+        attempts: i32,
+        reason: &str,
+    ) -> anyhow::Result<()> {
+        let now = Utc::now().to_rfc3339();
+        with_pool!(&self.db, |pool| {
+            let mut tx = pool.begin().await?;
+
+            sqlx::query(
+                "UPDATE webhook_events SET status = $1, retries = $2, last_error = $3,
+                 next_attempt_at = NULL WHERE id = $4",
+            )
+            .bind(EVENT_DEAD_LETTERED)
+            .bind(attempts)
+            .bind(reason)
+            .bind(&event.id)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                "INSERT INTO webhook_dead_letters (event_id, webhook_id, reason, attempts, dead_lettered_at)
+                 VALUES ($1, $2, $3, $4, $5)
+                 ON CONFLICT (event_id) DO UPDATE SET reason = excluded.reason,
+                 attempts = excluded.attempts, dead_lettered_at = excluded.dead_lettered_at",
+            )
+            .bind(&event.id)
+            .bind(&event.webhook_id)
+            .bind(reason)
+            .bind(attempts)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+        });
+        Ok(())
+    }
+
+    /// Reset the failure streak of the endpoint after a successful delivery
+    pub async fn record_endpoint_success(&self, webhook_id: &str) -> anyhow::Result<()> {
+        with_pool!(&self.db, |pool| {
+            sqlx::query(
+                "UPDATE webhooks SET consecutive_failures = 0, failing_since = NULL,
+                 last_fired_at = $1 WHERE id = $2",
+            )
+            .bind(Utc::now().to_rfc3339())
+            .bind(webhook_id)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
+
+        Ok(())
+    }
+
+    /// Count a failed attempt against the endpoint and disable it once it has failed at
+    /// least `max_failures` times in a row over at least `max_failing_for`. Returns
+    /// whether this call disabled it.
+    pub async fn record_endpoint_failure(
+        &self,
+        webhook_id: &str,
+        max_failures: i32,
+        max_failing_for: chrono::Duration,
+        reason: &str,
+    ) -> anyhow::Result<bool> {
+        let now = Utc::now();
+
+        with_pool!(&self.db, |pool| {
+            sqlx::query(
+                "UPDATE webhooks SET consecutive_failures = consecutive_failures + 1,
+                 failing_since = COALESCE(failing_since, $1) WHERE id = $2",
+            )
+            .bind(now.to_rfc3339())
+            .bind(webhook_id)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
+
+        let result = with_pool!(&self.db, |pool| {
+            sqlx::query(
+                "UPDATE webhooks SET disabled_at = $1, disabled_reason = $2
+                 WHERE id = $3 AND disabled_at IS NULL
+                 AND consecutive_failures >= $4 AND failing_since <= $5",
+            )
+            .bind(now.to_rfc3339())
+            .bind(reason)
+            .bind(webhook_id)
+            .bind(max_failures)
+            .bind((now - max_failing_for).to_rfc3339())
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
+
+        Ok(result > 0)
+    }
+
+    /// Resume deliveries to a disabled webhook. Dead-lettered events stay where they
+    /// are until redelivered.
+    pub async fn enable_webhook(&self, webhook_id: &str, user_id: &str) -> anyhow::Result<bool> {
+        let result = with_pool!(&self.db, |pool| {
+            sqlx::query(
+                "UPDATE webhooks SET disabled_at = NULL, disabled_reason = NULL,
+                 consecutive_failures = 0, failing_since = NULL
+                 WHERE id = $1 AND user_id = $2 AND is_active = TRUE",
+            )
+            .bind(webhook_id)
+            .bind(user_id)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
+
+        Ok(result > 0)
+    }
+
+    /// Most recent events of a webhook, optionally only those with `status`
+    pub async fn list_deliveries(
+        &self,
+        webhook_id: &str,
+        status: Option<&str>,
+        limit: i64,
+    ) -> anyhow::Result<Vec<WebhookDelivery>> {
+        let deliveries = with_pool!(&self.db, |pool| {
+            sqlx::query_as::<_, WebhookDelivery>(
+                "SELECT id, event_type, status, retries AS attempts, next_attempt_at, delivered_at,
+                 last_error, created_at FROM webhook_events
+                 WHERE webhook_id = $1 AND (CAST($2 AS TEXT) IS NULL OR status = $2)
+                 ORDER BY created_at DESC
+                 LIMIT $3",
+            )
+            .bind(webhook_id)
+            .bind(status)
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+        })?;
+
+        Ok(deliveries)
+    }
+
+    pub async fn get_delivery(
+        &self,
+        webhook_id: &str,
+        event_id: &str,
+    ) -> anyhow::Result<Option<WebhookDelivery>> {
+        let delivery = with_pool!(&self.db, |pool| {
+            sqlx::query_as::<_, WebhookDelivery>(
+                "SELECT id, event_type, status, retries AS attempts, next_attempt_at, delivered_at,
+                 last_error, created_at FROM webhook_events
+                 WHERE id = $1 AND webhook_id = $2",
+            )
+            .bind(event_id)
+            .bind(webhook_id)
+            .fetch_optional(pool)
+            .await
+        })?;
+
+        Ok(delivery)
+    }
+
+    /// Attempts made for an event, first to last
+    pub async fn list_delivery_attempts(
+        &self,
+        webhook_id: &str,
+        event_id: &str,
+    ) -> anyhow::Result<Vec<DeliveryAttempt>> {
+        let attempts = with_pool!(&self.db, |pool| {
+            sqlx::query_as::<_, DeliveryAttempt>(
+                "SELECT id, event_id, attempt, status_code, latency_ms, response_snippet, error,
+                 attempted_at FROM webhook_delivery_attempts
+                 WHERE event_id = $1 AND webhook_id = $2
+                 ORDER BY attempted_at ASC",
+            )
+            .bind(event_id)
+            .bind(webhook_id)
+            .fetch_all(pool)
+            .await
+        })?;
+
+        Ok(attempts)
+    }
+
+    /// Dead-lettered events of a webhook, most recent first
+    pub async fn list_dead_letters(
+        &self,
+        webhook_id: &str,
+        limit: i64,
+    ) -> anyhow::Result<Vec<DeadLetter>> {
+        let dead_letters = with_pool!(&self.db, |pool| {
+            sqlx::query_as::<_, DeadLetter>(
+                "SELECT dl.event_id, we.event_type, dl.reason, dl.attempts, we.created_at,
+                 dl.dead_lettered_at
+                 FROM webhook_dead_letters dl
+                 JOIN webhook_events we ON we.id = dl.event_id
+                 WHERE dl.webhook_id = $1
+                 ORDER BY dl.dead_lettered_at DESC
+                 LIMIT $2",
+            )
+            .bind(webhook_id)
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+        })?;
+
+        Ok(dead_letters)
+    }
+
+    /// Queue an event for delivery again with a fresh set of attempts, taking it out of
+    /// the dead-letter store. Delivered events can be replayed the same way. Returns
+    /// false if the webhook has no such event.
+    pub async fn redeliver_event(&self, webhook_id: &str, event_id: &str) -> anyhow::Result<bool> {
+        with_pool!(&self.db, |pool| {
+            let mut tx = pool.begin().await?;
+
+            let result = sqlx::query(
+                "UPDATE webhook_events SET status = $1, retries = 0, last_error = NULL,
+                 delivered_at = NULL, next_attempt_at = $2 WHERE id = $3 AND webhook_id = $4",
+            )
+            .bind(EVENT_PENDING)
+            .bind(Utc::now().to_rfc3339())
+            .bind(event_id)
+            .bind(webhook_id)
+            .execute(&mut *tx)
+            .await?;
+            if result.rows_affected() == 0 {
+                return Ok(false);
+            }
+
+            sqlx::query("DELETE FROM webhook_dead_letters WHERE event_id = $1")
+                .bind(event_id)
+                .execute(&mut *tx)
+                .await?;
+
+            tx.commit().await?;
+        });
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webhooks::WebhookEventType;
+
+    async fn service() -> (WebhookService, String) {
+        let pool = crate::test_support::migrated_pool().await;
+        sqlx::query("INSERT INTO users (id, username) VALUES ('u1', 'alice')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let service = WebhookService::new(pool.into());
+        let webhook = service
+            .register_webhook(
+                "u1",
+                crate::webhooks::CreateWebhookRequest {
+                    url: "https://example.com/hook".to_string(),
+                    event_types: vec!["anchor.status_changed".to_string()],
+                    filters: None,
+                },
+            )
+            .await
+            .unwrap();
+        (service, webhook.id)
+    }
+
+    async fn queue_one(service: &WebhookService) -> DueEvent {
+        let queued = service
+            .queue_event(
+                &WebhookEventType::AnchorStatusChanged,
+                serde_json::json!({"anchor_id": "a1"}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(queued, 1);
+        service.due_events(10).await.unwrap().remove(0)
+    }
+
+    #[tokio::test]
+    async fn test_retry_schedule_hides_event_until_due() {
+        let (service, webhook_id) = service().await;
+        let event = queue_one(&service).await;
+
+        let outcome = AttemptOutcome {
+            status_code: Some(503),
+            latency_ms: 42,
+            response_snippet: Some("unavailable".to_string()),
+            error: Some("HTTP 503 Service Unavailable".to_string()),
+        };
+        service
+            .record_delivery_attempt(&event, 1, &outcome)
+            .await
+            .unwrap();
+        service
+            .schedule_event_retry(
+                &event.id,
+                1,
+                "HTTP 503 Service Unavailable",
+                Utc::now() + chrono::Duration::minutes(5),
+            )
+            .await
+            .unwrap();
+        assert!(service.due_events(10).await.unwrap().is_empty());
+
+        let delivery = service
+            .get_delivery(&webhook_id, &event.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(delivery.status, EVENT_PENDING);
+        assert_eq!(delivery.attempts, 1);
+        let attempts = service
+            .list_delivery_attempts(&webhook_id, &event.id)
+            .await
+            .unwrap();
+        assert_eq!(attempts.len(), 1);
+        assert_eq!(attempts[0].status_code, Some(503));
+        assert_eq!(attempts[0].response_snippet.as_deref(), Some("unavailable"));
+    }
+
+    #[tokio::test]
+    async fn test_dead_letter_and_redeliver() {
+        let (service, webhook_id) = service().await;
+        let event = queue_one(&service).await;
+
+        service
+            .dead_letter_event(&event, 10, "max_attempts_exceeded")
+            .await
+            .unwrap();
+        assert!(service.due_events(10).await.unwrap().is_empty());
+        let dead_letters = service.list_dead_letters(&webhook_id, 10).await.unwrap();
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(dead_letters[0].event_type, "anchor.status_changed");
+        assert_eq!(dead_letters[0].attempts, 10);
+
+        assert!(!service
+            .redeliver_event("other-webhook", &event.id)
+            .await
+            .unwrap());
+        assert!(service
+            .redeliver_event(&webhook_id, &event.id)
+            .await
+            .unwrap());
+        assert!(service
+            .list_dead_letters(&webhook_id, 10)
+            .await
+            .unwrap()
+            .is_empty());
+        let due = service.due_events(10).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].retries, 0);
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_disabled_after_persistent_failures() {
+        let (service, webhook_id) = service().await;
+
+        // Enough failures, but not for long enough
+        for _ in 0..3 {
+            assert!(!service
+                .record_endpoint_failure(&webhook_id, 3, chrono::Duration::hours(1), "down")
+                .await
+                .unwrap());
+        }
+        // A success resets the streak
+        service.record_endpoint_success(&webhook_id).await.unwrap();
+        assert!(!service
+            .record_endpoint_failure(&webhook_id, 2, chrono::Duration::zero(), "down")
+            .await
+            .unwrap());
+        assert!(service
+            .record_endpoint_failure(&webhook_id, 2, chrono::Duration::zero(), "down")
+            .await
+            .unwrap());
+
+        let webhook = service.get_webhook(&webhook_id).await.unwrap().unwrap();
+        assert!(webhook.disabled_at.is_some());
+        assert_eq!(webhook.disabled_reason.as_deref(), Some("down"));
+        // Disabled endpoints are not queued for
+        let queued = service
+            .queue_event(
+                &WebhookEventType::AnchorStatusChanged,
+                serde_json::json!({}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(queued, 0);
+
+        assert!(!service.enable_webhook(&webhook_id, "u2").await.unwrap());
+        assert!(service.enable_webhook(&webhook_id, "u1").await.unwrap());
+        let webhook = service.get_webhook(&webhook_id).await.unwrap().unwrap();
+        assert!(webhook.disabled_at.is_none());
+        assert_eq!(webhook.consecutive_failures, 0);
+    }
+}
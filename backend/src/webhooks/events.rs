@@ -44,6 +44,19 @@ pub struct CorridorLiquidityDroppedEvent {
     pub severity: String,        // "warning" | "critical"
 }
 
+/// Corridor Anomaly Detected Event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorridorAnomalyDetectedEvent {
+    pub corridor_key: String,
+    pub metric: String, // "success_rate" | "volume" | "latency" | "liquidity"
+    pub bucket_start: String, // RFC 3339 start of the anomalous hour
+    pub observed: f64,
+    pub expected: f64,
+    pub z_score: f64,
+    pub direction: String, // "spike" | "drop"
+    pub severity: String,  // "warning" | "critical"
+}
+
 /// Corridor Metrics snapshot
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CorridorMetrics {
@@ -1,33 +1,15 @@
 /// Webhooks module for Zapier integration
 /// Manages webhook registrations, event definitions, and dispatching
+pub mod delivery;
 pub mod events;
+pub mod signature;
 
-use hmac::{Hmac, Mac};
+use crate::database::DbPool;
+use crate::with_pool;
 use serde::{Deserialize, Serialize};
-use sha2::Sha256;
-use sqlx::SqlitePool;
 use uuid::Uuid;
 
-type HmacSha256 = Hmac<Sha256>;
-
-/// Webhook signature - for verifying webhook requests
-pub struct WebhookSignature;
-
-impl WebhookSignature {
-    /// Generate HMAC-SHA256 signature for webhook payload
-    pub fn sign(payload: &str, secret: &str) -> String {
-        let mut mac =
-            HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
-        mac.update(payload.as_bytes());
-        format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
-    }
-
-    /// Verify webhook signature
-    pub fn verify(payload: &str, secret: &str, signature: &str) -> bool {
-        let expected = Self::sign(payload, secret);
-        signature == expected
-    }
-}
+pub use signature::{SignatureError, WebhookSignature};
 
 /// Webhook Configuration
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -41,6 +23,32 @@ pub struct Webhook {
     pub is_active: bool,
     pub created_at: String,
     pub last_fired_at: Option<String>,
+    /// Failed delivery attempts since the last success
+    pub consecutive_failures: i32,
+    pub failing_since: Option<String>,
+    /// Set when the endpoint was disabled for failing persistently
+    pub disabled_at: Option<String>,
+    pub disabled_reason: Option<String>,
+    /// Secret replaced by the last rotation, still used for signing until it expires
+    pub previous_secret: Option<String>,
+    pub previous_secret_expires_at: Option<String>,
+}
+
+impl Webhook {
+    /// Secrets deliveries are signed with: the current one and, during the grace
+    /// period of a rotation, the previous one
+    pub fn signing_secrets(&self) -> Vec<&str> {
+        let mut secrets = vec![self.secret.as_str()];
+        let previous_valid = self
+            .previous_secret_expires_at
+            .as_deref()
+            .and_then(|expires| chrono::DateTime::parse_from_rfc3339(expires).ok())
+            .is_some_and(|expires| expires > chrono::Utc::now());
+        if let (Some(previous), true) = (self.previous_secret.as_deref(), previous_valid) {
+            secrets.push(previous);
+        }
+        secrets
+    }
 }
 
 /// Webhook creation request
@@ -60,12 +68,26 @@ pub struct WebhookResponse {
     pub filters: Option<serde_json::Value>,
     pub is_active: bool,
     pub created_at: String,
+    /// Deliveries are paused until the webhook is re-enabled
+    pub disabled_at: Option<String>,
+    pub disabled_reason: Option<String>,
+    /// Signing secret, only returned when the webhook is registered
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+}
+
+/// New signing secret issued by a rotation
+#[derive(Debug, Serialize)]
+pub struct RotatedSecret {
+    pub secret: String,
+    /// Until then deliveries are also signed with the previous secret
+    pub previous_secret_expires_at: String,
 }
 
 /// Webhook event envelope
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WebhookEventEnvelope {
-    pub id: String, // Delivery ID for idempotency
+    pub id: String, // Event ID, the same on every attempt, for idempotency
     pub event: String,
     pub timestamp: i64,
     pub data: serde_json::Value,
@@ -78,6 +100,7 @@ pub enum WebhookEventType {
     AnchorStatusChanged,
     PaymentCreated,
     CorridorLiquidityDropped,
+    CorridorAnomalyDetected,
 }
 
 impl WebhookEventType {
@@ -87,6 +110,7 @@ impl WebhookEventType {
             Self::AnchorStatusChanged => "anchor.status_changed",
             Self::PaymentCreated => "payment.created",
             Self::CorridorLiquidityDropped => "corridor.liquidity_dropped",
+            Self::CorridorAnomalyDetected => "corridor.anomaly_detected",
         }
     }
 
@@ -96,6 +120,7 @@ impl WebhookEventType {
             "anchor.status_changed" => Some(Self::AnchorStatusChanged),
             "payment.created" => Some(Self::PaymentCreated),
             "corridor.liquidity_dropped" => Some(Self::CorridorLiquidityDropped),
+            "corridor.anomaly_detected" => Some(Self::CorridorAnomalyDetected),
             _ => None,
         }
     }
@@ -103,12 +128,12 @@ impl WebhookEventType {
 
 /// Webhook service - manages webhook operations
 pub struct WebhookService {
-    db: SqlitePool,
+    db: DbPool,
     encryption_key: String,
 }
 
 impl WebhookService {
-    pub fn new(db: SqlitePool) -> Self {
+    pub fn new(db: DbPool) -> Self {
         let encryption_key = std::env::var("ENCRYPTION_KEY").unwrap_or_else(|_| {
             "0000000000000000000000000000000000000000000000000000000000000000".to_string()
         });
@@ -130,22 +155,25 @@ impl WebhookService {
         let encrypted_secret = crate::crypto::encrypt_data(&secret, &self.encryption_key)
             .unwrap_or_else(|_| secret.clone());
 
-        sqlx::query(
-            r#"
-            INSERT INTO webhooks (id, user_id, url, event_types, filters, secret, is_active, created_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(&id)
-        .bind(user_id)
-        .bind(&request.url)
-        .bind(&event_types_str)
-        .bind(filters_str.as_deref())
-        .bind(&encrypted_secret)
-        .bind(true)
-        .bind(&now)
-        .execute(&self.db)
-        .await?;
+        with_pool!(&self.db, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO webhooks (id, user_id, url, event_types, filters, secret, is_active, created_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                "#,
+            )
+            .bind(&id)
+            .bind(user_id)
+            .bind(&request.url)
+            .bind(&event_types_str)
+            .bind(filters_str.as_deref())
+            .bind(&encrypted_secret)
+            .bind(true)
+            .bind(&now)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
 
         Ok(WebhookResponse {
             id,
@@ -154,21 +182,25 @@ impl WebhookService {
             filters: request.filters,
             is_active: true,
             created_at: now,
+            disabled_at: None,
+            disabled_reason: None,
+            secret: Some(secret),
         })
     }
 
     /// Get webhook by ID
     pub async fn get_webhook(&self, webhook_id: &str) -> anyhow::Result<Option<Webhook>> {
-        let mut webhook = sqlx::query_as::<_, Webhook>(
-            "SELECT id, user_id, url, event_types, filters, secret, is_active, created_at, last_fired_at FROM webhooks WHERE id = ?"
-        )
-        .bind(webhook_id)
-        .fetch_optional(&self.db)
-        .await?;
+        let mut webhook = with_pool!(&self.db, |pool| {
+            sqlx::query_as::<_, Webhook>(
+                "SELECT id, user_id, url, event_types, filters, secret, is_active, created_at, last_fired_at, consecutive_failures, failing_since, disabled_at, disabled_reason, previous_secret, previous_secret_expires_at FROM webhooks WHERE id = $1"
+            )
+            .bind(webhook_id)
+            .fetch_optional(pool)
+            .await
+        })?;
 
         if let Some(ref mut w) = webhook {
-            w.secret = crate::crypto::decrypt_data(&w.secret, &self.encryption_key)
-                .unwrap_or_else(|_| w.secret.clone());
+            self.decrypt_secrets(w);
         }
 
         Ok(webhook)
@@ -176,30 +208,78 @@ impl WebhookService {
 
     /// List webhooks for a user
     pub async fn list_webhooks(&self, user_id: &str) -> anyhow::Result<Vec<Webhook>> {
-        let mut webhooks = sqlx::query_as::<_, Webhook>(
-            "SELECT id, user_id, url, event_types, filters, secret, is_active, created_at, last_fired_at FROM webhooks WHERE user_id = ? AND is_active = 1 ORDER BY created_at DESC"
-        )
-        .bind(user_id)
-        .fetch_all(&self.db)
-        .await?;
+        let mut webhooks = with_pool!(&self.db, |pool| {
+            sqlx::query_as::<_, Webhook>(
+                "SELECT id, user_id, url, event_types, filters, secret, is_active, created_at, last_fired_at, consecutive_failures, failing_since, disabled_at, disabled_reason, previous_secret, previous_secret_expires_at FROM webhooks WHERE user_id = $1 AND is_active = TRUE ORDER BY created_at DESC"
+            )
+            .bind(user_id)
+            .fetch_all(pool)
+            .await
+        })?;
 
         for w in &mut webhooks {
-            w.secret = crate::crypto::decrypt_data(&w.secret, &self.encryption_key)
-                .unwrap_or_else(|_| w.secret.clone());
+            self.decrypt_secrets(w);
         }
 
         Ok(webhooks)
     }
 
-    /// Delete/deactivate webhook
-    pub async fn delete_webhook(&self, webhook_id: &str, user_id: &str) -> anyhow::Result<bool> {
-        let result = sqlx::query("UPDATE webhooks SET is_active = 0 WHERE id = ? AND user_id = ?")
+    fn decrypt_secrets(&self, webhook: &mut Webhook) {
+        webhook.secret = crate::crypto::decrypt_data(&webhook.secret, &self.encryption_key)
+            .unwrap_or_else(|_| webhook.secret.clone());
+        if let Some(previous) = webhook.previous_secret.as_mut() {
+            *previous = crate::crypto::decrypt_data(previous, &self.encryption_key)
+                .unwrap_or_else(|_| previous.clone());
+        }
+    }
+
+    /// Replace the signing secret of a webhook. Deliveries are signed with both the new
+    /// and the old secret for `grace_period`, so receivers can switch without
+    /// rejecting events; a secret replaced by an earlier rotation stops being used.
+    /// Returns None if the caller has no such active webhook.
+    pub async fn rotate_secret(
+        &self,
+        webhook_id: &str,
+        user_id: &str,
+        grace_period: chrono::Duration,
+    ) -> anyhow::Result<Option<RotatedSecret>> {
+        let secret = Uuid::new_v4().to_string();
+        let encrypted_secret = crate::crypto::encrypt_data(&secret, &self.encryption_key)
+            .unwrap_or_else(|_| secret.clone());
+        let expires_at = (chrono::Utc::now() + grace_period).to_rfc3339();
+
+        let result = with_pool!(&self.db, |pool| {
+            sqlx::query(
+                "UPDATE webhooks SET previous_secret = secret, previous_secret_expires_at = $1,
+                 secret = $2 WHERE id = $3 AND user_id = $4 AND is_active = TRUE",
+            )
+            .bind(&expires_at)
+            .bind(&encrypted_secret)
             .bind(webhook_id)
             .bind(user_id)
-            .execute(&self.db)
-            .await?;
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
+
+        Ok((result > 0).then_some(RotatedSecret {
+            secret,
+            previous_secret_expires_at: expires_at,
+        }))
+    }
 
-        Ok(result.rows_affected() > 0)
+    /// Delete/deactivate webhook
+    pub async fn delete_webhook(&self, webhook_id: &str, user_id: &str) -> anyhow::Result<bool> {
+        let result = with_pool!(&self.db, |pool| {
+            sqlx::query("UPDATE webhooks SET is_active = FALSE WHERE id = $1 AND user_id = $2")
+                .bind(webhook_id)
+                .bind(user_id)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+        })?;
+
+        Ok(result > 0)
     }
 
     /// Record webhook event for delivery
@@ -213,89 +293,76 @@ impl WebhookService {
         let payload_str = payload.to_string();
         let now = chrono::Utc::now().to_rfc3339();
 
-        sqlx::query(
-            "INSERT INTO webhook_events (id, webhook_id, event_type, payload, status, retries, created_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?)"
-        )
-        .bind(id.clone())
-        .bind(webhook_id)
-        .bind(event_type)
-        .bind(payload_str)
-        .bind("pending")
-        .bind(0)
-        .bind(now)
-        .execute(&self.db)
-        .await?;
+        with_pool!(&self.db, |pool| {
+            sqlx::query(
+                "INSERT INTO webhook_events (id, webhook_id, event_type, payload, status, retries, created_at, next_attempt_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $7)"
+            )
+            .bind(id.clone())
+            .bind(webhook_id)
+            .bind(event_type)
+            .bind(payload_str)
+            .bind(delivery::EVENT_PENDING)
+            .bind(0)
+            .bind(now)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })?;
 
         Ok(id)
     }
 
-    /// Get pending webhook events
-    pub async fn get_pending_events(
+    /// Queue `payload` for every active, enabled webhook subscribed to `event_type` whose
+    /// filters match it. Returns the number of deliveries queued.
+    pub async fn queue_event(
         &self,
-        limit: usize,
-    ) -> anyhow::Result<Vec<(String, String, String, String)>> {
-        let query_limit = limit as i64;
-
-        let rows = sqlx::query(
-            "SELECT we.id, we.webhook_id, we.event_type, we.payload
-             FROM webhook_events we
-             WHERE we.status = 'pending' AND we.retries < 3
-             ORDER BY we.created_at ASC
-             LIMIT ?",
-        )
-        .bind(query_limit)
-        .fetch_all(&self.db)
-        .await?;
-
-        let events: Vec<(String, String, String, String)> = rows
-            .into_iter()
-            .map(|row| {
-                use sqlx::Row;
-                (
-                    row.get::<String, _>(0),
-                    row.get::<String, _>(1),
-                    row.get::<String, _>(2),
-                    row.get::<String, _>(3),
-                )
-            })
-            .collect();
-
-        Ok(events)
-    }
+        event_type: &WebhookEventType,
+        payload: serde_json::Value,
+    ) -> anyhow::Result<usize> {
+        let webhooks = with_pool!(&self.db, |pool| {
+            sqlx::query_as::<_, Webhook>(
+                "SELECT id, user_id, url, event_types, filters, secret, is_active, created_at, last_fired_at, consecutive_failures, failing_since, disabled_at, disabled_reason, previous_secret, previous_secret_expires_at FROM webhooks WHERE is_active = TRUE AND disabled_at IS NULL"
+            )
+            .fetch_all(pool)
+            .await
+        })?;
+
+        let mut queued = 0;
+        for webhook in webhooks {
+            if !webhook
+                .event_types
+                .split(',')
+                .any(|et| et.trim() == event_type.as_str())
+            {
+                continue;
+            }
+            let filters = webhook
+                .filters
+                .as_deref()
+                .and_then(|f| serde_json::from_str::<serde_json::Value>(f).ok());
+            if let Some(filters) = filters {
+                if !filters_match(&payload, &filters) {
+                    continue;
+                }
+            }
+
+            self.create_webhook_event(&webhook.id, event_type.as_str(), payload.clone())
+                .await?;
+            queued += 1;
+        }
 
-    /// Update webhook event status
-    pub async fn update_event_status(
-        &self,
-        event_id: &str,
-        status: &str,
-        error: Option<&str>,
-        retries: i32,
-    ) -> anyhow::Result<()> {
-        sqlx::query(
-            "UPDATE webhook_events SET status = ?, last_error = ?, retries = ? WHERE id = ?",
-        )
-        .bind(status)
-        .bind(error)
-        .bind(retries)
-        .bind(event_id)
-        .execute(&self.db)
-        .await?;
-
-        Ok(())
+        Ok(queued)
     }
+}
 
-    /// Update webhook's last_fired_at timestamp
-    pub async fn update_last_fired(&self, webhook_id: &str) -> anyhow::Result<()> {
-        let now = chrono::Utc::now().to_rfc3339();
-        sqlx::query("UPDATE webhooks SET last_fired_at = ? WHERE id = ?")
-            .bind(now)
-            .bind(webhook_id)
-            .execute(&self.db)
-            .await?;
-
-        Ok(())
-    }
+/// Whether `payload` has every top-level field of the `filters` object with an equal value
+pub fn filters_match(payload: &serde_json::Value, filters: &serde_json::Value) -> bool {
+    filters.as_object().is_none_or(|filters| {
+        filters
+            .iter()
+            .all(|(key, expected)| payload.get(key) == Some(expected))
+    })
 }
 
 #[cfg(test)]
@@ -303,12 +370,31 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_webhook_signature() {
-        let payload = r#"{"event":"test"}"#;
-        let secret = "my-secret";
-
-        let signature = WebhookSignature::sign(payload, secret);
-        assert!(WebhookSignature::verify(payload, secret, &signature));
+    fn test_previous_secret_signs_until_it_expires() {
+        let mut webhook = Webhook {
+            id: "w1".to_string(),
+            user_id: "u1".to_string(),
+            url: "https://example.com/hook".to_string(),
+            event_types: "payment.created".to_string(),
+            filters: None,
+            secret: "new".to_string(),
+            is_active: true,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            last_fired_at: None,
+            consecutive_failures: 0,
+            failing_since: None,
+            disabled_at: None,
+            disabled_reason: None,
+            previous_secret: Some("old".to_string()),
+            previous_secret_expires_at: Some(
+                (chrono::Utc::now() + chrono::Duration::hours(1)).to_rfc3339(),
+            ),
+        };
+        assert_eq!(webhook.signing_secrets(), vec!["new", "old"]);
+
+        webhook.previous_secret_expires_at =
+            Some((chrono::Utc::now() - chrono::Duration::seconds(1)).to_rfc3339());
+        assert_eq!(webhook.signing_secrets(), vec!["new"]);
     }
 
     #[test]
@@ -319,5 +405,31 @@ mod tests {
             WebhookEventType::from_str("corridor.health_degraded"),
             Some(WebhookEventType::CorridorHealthDegraded)
         );
+        assert_eq!(
+            WebhookEventType::from_str("corridor.anomaly_detected"),
+            Some(WebhookEventType::CorridorAnomalyDetected)
+        );
+    }
+
+    #[test]
+    fn test_filters_match() {
+        let payload = serde_json::json!({
+            "corridor_key": "USDC:GA5Z->XLM:native",
+            "severity": "warning"
+        });
+
+        assert!(filters_match(
+            &payload,
+            &serde_json::json!({"severity": "warning"})
+        ));
+        assert!(!filters_match(
+            &payload,
+            &serde_json::json!({"severity": "critical"})
+        ));
+        assert!(!filters_match(
+            &payload,
+            &serde_json::json!({"metric": "volume"})
+        ));
+        assert!(filters_match(&payload, &serde_json::json!(null)));
     }
 }
@@ -0,0 +1,190 @@
+//! Versioned, timestamped webhook signatures.
+//!
+//! Deliveries carry `X-Zapier-Signature: t=<unix seconds>,v1=<hex>[,v1=<hex>...]`.
+//! Each `v1` value is the HMAC-SHA256 of `"<t>.<body>"` under one of the webhook's
+//! active secrets; there is more than one while a rotated secret is in its grace
+//! period. Because the timestamp is signed, a receiver that checks it against its own
+//! clock rejects captured deliveries replayed later. Unknown keys are ignored so
+//! later versions can be sent alongside `v1`.
+//!
+//! Receivers written in Rust can use [`WebhookSignature::verify`] directly; others
+//! follow the same steps, described in `WEBHOOKS.md`.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const SIGNATURE_VERSION: &str = "v1";
+
+/// Largest accepted difference between the signed timestamp and the receiver's clock
+pub const DEFAULT_TOLERANCE_SECONDS: i64 = 300;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SignatureError {
+    #[error("Signature header has no valid timestamp")]
+    Malformed,
+    #[error("Signature header has no v1 signature")]
+    NoSignature,
+    #[error("Signature timestamp is outside the tolerance")]
+    TimestampOutsideTolerance,
+    #[error("No signature matches the payload")]
+    Mismatch,
+}
+
+/// Webhook signature - for signing deliveries and verifying them on receipt
+pub struct WebhookSignature;
+
+impl WebhookSignature {
+    /// Signature header for `body` sent at `timestamp` (unix seconds), with one `v1`
+    /// value per secret
+    pub fn sign(body: &str, timestamp: i64, secrets: &[&str]) -> String {
+        let mut header = format!("t={}", timestamp);
+        for secret in secrets {
+            let signature = signed_payload_mac(secret, timestamp, body).finalize();
+            header.push_str(&format!(
+                ",{}={}",
+                SIGNATURE_VERSION,
+                hex::encode(signature.into_bytes())
+            ));
+        }
+        header
+    }
+
+    /// Check a signature header against the secrets the receiver holds, rejecting
+    /// timestamps more than `tolerance_seconds` from the current time. Returns the
+    /// signed timestamp.
+    pub fn verify(
+        header: &str,
+        body: &str,
+        secrets: &[&str],
+        tolerance_seconds: i64,
+    ) -> Result<i64, SignatureError> {
+        Self::verify_at(
+            header,
+            body,
+            secrets,
+            tolerance_seconds,
+            chrono::Utc::now().timestamp(),
+        )
+    }
+
+    /// [`Self::verify`] with the receiver's clock given as `now` (unix seconds)
+    pub fn verify_at(
+        header: &str,
+        body: &str,
+        secrets: &[&str],
+        tolerance_seconds: i64,
+        now: i64,
+    ) -> Result<i64, SignatureError> {
+        let mut timestamp = None;
+        let mut signatures = Vec::new();
+        for part in header.split(',') {
+            match part.trim().split_once('=') {
+                Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+                Some((SIGNATURE_VERSION, value)) => signatures.push(value),
+                _ => {}
+            }
+        }
+
+        let timestamp = timestamp.ok_or(SignatureError::Malformed)?;
+        if signatures.is_empty() {
+            return Err(SignatureError::NoSignature);
+        }
+        if (now - timestamp).abs() > tolerance_seconds {
+            return Err(SignatureError::TimestampOutsideTolerance);
+        }
+
+        let matches = signatures
+            .iter()
+            .filter_map(|signature| hex::decode(signature).ok())
+            .any(|signature| {
+                secrets.iter().any(|secret| {
+                    signed_payload_mac(secret, timestamp, body)
+                        .verify_slice(&signature)
+                        .is_ok()
+                })
+            });
+        if matches {
+            Ok(timestamp)
+        } else {
+            Err(SignatureError::Mismatch)
+        }
+    }
+}
+
+fn signed_payload_mac(secret: &str, timestamp: i64, body: &str) -> HmacSha256 {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &str = r#"{"event":"test"}"#;
+    const NOW: i64 = 1_792_224_000;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let header = WebhookSignature::sign(BODY, NOW, &["my-secret"]);
+        assert!(header.starts_with("t=1792224000,v1="));
+        assert_eq!(
+            WebhookSignature::verify_at(&header, BODY, &["my-secret"], 300, NOW + 10),
+            Ok(NOW)
+        );
+        assert_eq!(
+            WebhookSignature::verify_at(&header, r#"{"event":"other"}"#, &["my-secret"], 300, NOW),
+            Err(SignatureError::Mismatch)
+        );
+        assert_eq!(
+            WebhookSignature::verify_at(&header, BODY, &["wrong"], 300, NOW),
+            Err(SignatureError::Mismatch)
+        );
+    }
+
+    #[test]
+    fn test_timestamp_is_signed_and_checked() {
+        let header = WebhookSignature::sign(BODY, NOW, &["my-secret"]);
+        assert_eq!(
+            WebhookSignature::verify_at(&header, BODY, &["my-secret"], 300, NOW + 301),
+            Err(SignatureError::TimestampOutsideTolerance)
+        );
+
+        // Moving the timestamp forward to pass the tolerance breaks the signature
+        let replayed = header.replace("t=1792224000", "t=1792229000");
+        assert_eq!(
+            WebhookSignature::verify_at(&replayed, BODY, &["my-secret"], 300, NOW + 5000),
+            Err(SignatureError::Mismatch)
+        );
+    }
+
+    #[test]
+    fn test_any_secret_during_rotation() {
+        let header = WebhookSignature::sign(BODY, NOW, &["new-secret", "old-secret"]);
+        assert_eq!(header.matches(",v1=").count(), 2);
+        // Receivers that have not switched yet and those that have both verify
+        assert!(WebhookSignature::verify_at(&header, BODY, &["old-secret"], 300, NOW).is_ok());
+        assert!(WebhookSignature::verify_at(&header, BODY, &["new-secret"], 300, NOW).is_ok());
+    }
+
+    #[test]
+    fn test_malformed_headers() {
+        assert_eq!(
+            WebhookSignature::verify_at("sha256=abcd", BODY, &["s"], 300, NOW),
+            Err(SignatureError::Malformed)
+        );
+        assert_eq!(
+            WebhookSignature::verify_at("t=1792224000,v0=abcd", BODY, &["s"], 300, NOW),
+            Err(SignatureError::NoSignature)
+        );
+        assert_eq!(
+            WebhookSignature::verify_at("t=1792224000,v1=not-hex", BODY, &["s"], 300, NOW),
+            Err(SignatureError::Mismatch)
+        );
+    }
+}
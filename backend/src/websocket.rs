@@ -23,27 +23,46 @@ pub struct WsState {
     pub subscriptions: DashMap<Uuid, HashSet<String>>,
     ///Broadcast channel for sending messages to all connections
     pub tx: broadcast::Sender<WsMessage>,
+    /// Every message delivered to WebSocket clients, broadcast or channel-scoped, for
+    /// in-process listeners such as GraphQL subscriptions
+    pub events: broadcast::Sender<WsMessage>,
 }
 
 impl WsState {
     pub fn new() -> Self {
         let (tx, _rx) = broadcast::channel(100);
+        let (events, _rx) = broadcast::channel(256);
         Self {
             connections: DashMap::new(),
             subscriptions: DashMap::new(),
             tx,
+            events,
         }
     }
 
     /// Broadcast a message to all connected clients
     pub fn broadcast(&self, message: WsMessage) {
+        self.publish(message.clone());
         if let Err(e) = self.tx.send(message) {
             warn!("Failed to broadcast message: {}", e);
         }
     }
 
+    /// Hand a message to in-process listeners without sending it to any connection
+    pub fn publish(&self, message: WsMessage) {
+        // Having no listeners is the normal case, not an error
+        let _ = self.events.send(message);
+    }
+
+    /// Receive every message published through this state
+    pub fn subscribe_events(&self) -> broadcast::Receiver<WsMessage> {
+        self.events.subscribe()
+    }
+
     /// Broadcast a message to clients subscribed to a specific channel
     pub async fn broadcast_to_channel(&self, channel: &str, message: WsMessage) {
+        self.publish(message.clone());
+
         let mut target_connections = Vec::new();
 
         // Find connections subscribed to this channel
@@ -237,7 +256,7 @@ pub async fn ws_handler(
 }
 
 /// Validate authentication token
-fn validate_token(token: &str) -> bool {
+pub(crate) fn validate_token(token: &str) -> bool {
     // For now, implement basic token validation
     // In production, use JWT or other robust auth mechanism
 
@@ -10,7 +10,7 @@ use tower::util::ServiceExt;
 #[sqlx::test]
 async fn test_account_merge_detector_process_and_stats(pool: SqlitePool) {
     let rpc_client = Arc::new(StellarRpcClient::new_with_defaults(true));
-    let detector = AccountMergeDetector::new(pool.clone(), rpc_client);
+    let detector = AccountMergeDetector::new(pool.clone().into(), rpc_client);
 
     sqlx::query(
         "INSERT INTO ledgers (sequence, hash, close_time, transaction_count, operation_count) VALUES (200, 'ledger_hash_200', '2026-01-22T10:30:00Z', 0, 0)",
@@ -43,7 +43,7 @@ async fn test_account_merge_detector_process_and_stats(pool: SqlitePool) {
 #[sqlx::test]
 async fn test_account_merge_detector_is_idempotent(pool: SqlitePool) {
     let rpc_client = Arc::new(StellarRpcClient::new_with_defaults(true));
-    let detector = AccountMergeDetector::new(pool.clone(), rpc_client);
+    let detector = AccountMergeDetector::new(pool.clone().into(), rpc_client);
 
     sqlx::query(
         "INSERT INTO ledgers (sequence, hash, close_time, transaction_count, operation_count) VALUES (201, 'ledger_hash_201', '2026-01-22T10:31:00Z', 0, 0)",
@@ -65,7 +65,7 @@ async fn test_account_merge_detector_is_idempotent(pool: SqlitePool) {
 #[sqlx::test]
 async fn test_account_merge_routes(pool: SqlitePool) {
     let rpc_client = Arc::new(StellarRpcClient::new_with_defaults(true));
-    let detector = Arc::new(AccountMergeDetector::new(pool.clone(), rpc_client));
+    let detector = Arc::new(AccountMergeDetector::new(pool.clone().into(), rpc_client));
 
     sqlx::query(
         "INSERT INTO ledgers (sequence, hash, close_time, transaction_count, operation_count) VALUES (202, 'ledger_hash_202', '2026-01-22T10:32:00Z', 0, 0)",
@@ -46,7 +46,7 @@ fn create_test_router(db: Arc<Database>) -> Router {
 #[tokio::test]
 async fn test_list_corridors_success() {
     let pool = setup_test_db().await;
-    let db = Arc::new(Database::new(pool));
+    let db = Arc::new(Database::new(pool.into()));
 
     let app = create_test_router(db);
 
@@ -73,7 +73,7 @@ async fn test_list_corridors_success() {
 #[tokio::test]
 async fn test_get_corridor_detail_success() {
     let pool = setup_test_db().await;
-    let db = Arc::new(Database::new(pool));
+    let db = Arc::new(Database::new(pool.into()));
 
     let app = create_test_router(db);
 
@@ -93,7 +93,7 @@ async fn test_get_corridor_detail_success() {
 #[tokio::test]
 async fn test_get_corridor_detail_not_found() {
     let pool = setup_test_db().await;
-    let db = Arc::new(Database::new(pool));
+    let db = Arc::new(Database::new(pool.into()));
 
     let app = create_test_router(db);
 
@@ -111,7 +111,7 @@ async fn test_get_corridor_detail_not_found() {
 #[tokio::test]
 async fn test_get_corridor_detail_invalid_format() {
     let pool = setup_test_db().await;
-    let db = Arc::new(Database::new(pool));
+    let db = Arc::new(Database::new(pool.into()));
     let app = create_test_router(db);
 
     let invalid_key = "INVALID_FORMAT";
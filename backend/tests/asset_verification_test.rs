@@ -82,7 +82,7 @@ async fn create_test_db() -> Result<SqlitePool> {
 #[tokio::test]
 async fn test_reputation_score_calculation() -> Result<()> {
     let pool = create_test_db().await?;
-    let verifier = AssetVerifier::new(pool)?;
+    let verifier = AssetVerifier::new(pool.into())?;
 
     // Test case 1: All verifications passed
     let result = stellar_insights_backend::services::asset_verifier::VerificationResult {
@@ -132,7 +132,7 @@ async fn test_reputation_score_calculation() -> Result<()> {
 #[tokio::test]
 async fn test_status_determination() -> Result<()> {
     let pool = create_test_db().await?;
-    let verifier = AssetVerifier::new(pool)?;
+    let verifier = AssetVerifier::new(pool.into())?;
 
     // Test case 1: Verified status
     let status = verifier.determine_status(80.0, 0);
@@ -160,7 +160,7 @@ async fn test_status_determination() -> Result<()> {
 #[tokio::test]
 async fn test_save_and_retrieve_verification() -> Result<()> {
     let pool = create_test_db().await?;
-    let verifier = AssetVerifier::new(pool.clone())?;
+    let verifier = AssetVerifier::new(pool.clone().into())?;
 
     let asset_code = "USDC";
     let asset_issuer = "GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN";
@@ -215,7 +215,7 @@ async fn test_save_and_retrieve_verification() -> Result<()> {
 #[tokio::test]
 async fn test_list_verified_assets() -> Result<()> {
     let pool = create_test_db().await?;
-    let verifier = AssetVerifier::new(pool.clone())?;
+    let verifier = AssetVerifier::new(pool.clone().into())?;
 
     // Insert test assets
     for i in 0..5 {
@@ -259,7 +259,7 @@ async fn test_list_verified_assets() -> Result<()> {
 #[tokio::test]
 async fn test_unique_constraint() -> Result<()> {
     let pool = create_test_db().await?;
-    let verifier = AssetVerifier::new(pool.clone())?;
+    let verifier = AssetVerifier::new(pool.clone().into())?;
 
     let asset_code = "USDC";
     let asset_issuer = "GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN";
@@ -311,7 +311,7 @@ async fn test_concurrent_verification() -> Result<()> {
         .map(|i| {
             let pool_clone = pool.clone();
             tokio::spawn(async move {
-                let verifier = AssetVerifier::new(pool_clone).unwrap();
+                let verifier = AssetVerifier::new(pool_clone.into()).unwrap();
                 let asset_code = format!("CONCURRENT{}", i);
                 let asset_issuer =
                     format!("G{}SEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN", i);
@@ -340,7 +340,7 @@ async fn test_concurrent_verification() -> Result<()> {
     }
 
     // Verify all assets were saved
-    let verifier = AssetVerifier::new(pool)?;
+    let verifier = AssetVerifier::new(pool.into())?;
     let assets = verifier.list_verified_assets(None, None, 10, 0).await?;
     assert_eq!(assets.len(), 5);
 
@@ -350,7 +350,7 @@ async fn test_concurrent_verification() -> Result<()> {
 #[tokio::test]
 async fn test_similar_asset_codes() -> Result<()> {
     let pool = create_test_db().await?;
-    let verifier = AssetVerifier::new(pool.clone())?;
+    let verifier = AssetVerifier::new(pool.clone().into())?;
 
     // Create assets with similar codes but different issuers
     let assets = vec![
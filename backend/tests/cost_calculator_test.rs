@@ -3,6 +3,7 @@ use std::sync::Arc;
 
 use axum::body::{to_bytes, Body};
 use axum::http::{header::IF_NONE_MATCH, HeaderValue, Request, StatusCode};
+use stellar_insights_backend::rpc::StellarRpcClient;
 use stellar_insights_backend::services::price_feed::{PriceFeedClient, PriceFeedConfig};
 use tower::util::ServiceExt;
 
@@ -11,7 +12,8 @@ fn test_app() -> axum::Router {
         PriceFeedConfig::default(),
         HashMap::new(),
     ));
-    stellar_insights_backend::api::cost_calculator::routes(price_feed)
+    let rpc_client = Arc::new(StellarRpcClient::new_with_defaults(true));
+    stellar_insights_backend::api::cost_calculator::routes(price_feed, rpc_client)
 }
 
 #[tokio::test]
@@ -50,6 +52,9 @@ async fn estimate_returns_cost_breakdown_and_comparison() {
     assert!(payload["routes"].is_array());
     assert_eq!(payload["routes"].as_array().unwrap().len(), 3);
     assert!(payload["routes"][0]["breakdown"]["total_fees_source"].is_number());
+    assert_eq!(payload["quote_source"], "reference_rates");
+    assert!(payload["source_usd_rate"].is_number());
+    assert!(payload["destination_usd_rate"].is_number());
 }
 
 #[tokio::test]
@@ -101,3 +106,78 @@ async fn estimate_supports_conditional_etag_requests() {
 
     assert_eq!(second_response.status(), StatusCode::NOT_MODIFIED);
 }
+
+#[tokio::test]
+async fn estimate_quotes_on_chain_pairs_from_market() {
+    let app = test_app();
+
+    let request_body = serde_json::json!({
+        "source_currency": "USD",
+        "destination_currency": "EURC",
+        "source_amount": 1000.0,
+        "destination_amount": 1500.0
+    });
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/estimate")
+                .header("content-type", "application/json")
+                .body(Body::from(request_body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let payload: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(payload["quote_source"], "market");
+    assert!(payload["routes"][0]["path"].is_array());
+    assert_eq!(
+        payload["destination_asset"]
+            .as_str()
+            .unwrap()
+            .split(':')
+            .next(),
+        Some("EURC")
+    );
+    assert!(
+        payload["best_route"]["breakdown"]["additional_source_required"]
+            .as_f64()
+            .unwrap()
+            > 0.0
+    );
+    assert!(payload["source_usd_rate"].is_number());
+    assert!(payload["destination_usd_rate"].is_number());
+    assert!(payload["horizon"]["strict_send"]["destination_amount"].is_number());
+    assert!(payload["horizon"]["strict_receive"]["source_amount"].is_number());
+}
+
+#[tokio::test]
+async fn estimate_rejects_unknown_currency() {
+    let app = test_app();
+
+    let request_body = serde_json::json!({
+        "source_currency": "USDC",
+        "destination_currency": "ZZZ",
+        "source_amount": 100.0
+    });
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/estimate")
+                .header("content-type", "application/json")
+                .body(Body::from(request_body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
@@ -6,7 +6,7 @@ use stellar_insights_backend::services::fee_bump_tracker::FeeBumpTrackerService;
 #[sqlx::test]
 async fn test_fee_bump_tracker_process_transactions(pool: SqlitePool) {
     // Initialize service
-    let service = FeeBumpTrackerService::new(pool.clone());
+    let service = FeeBumpTrackerService::new(pool.clone().into());
 
     // Create mock transactions
     let tx1 = HorizonTransaction {
@@ -27,7 +27,7 @@ async fn test_snapshot_storage_with_hash_and_epoch() {
     .await
     .unwrap();
 
-    let db = Database::new(pool);
+    let db = Database::new(pool.into());
 
     // Test 1: Create snapshot with hash and epoch
     let snapshot_data = serde_json::json!({
@@ -120,7 +120,7 @@ async fn test_snapshot_without_hash_and_epoch() {
     .await
     .unwrap();
 
-    let db = Database::new(pool);
+    let db = Database::new(pool.into());
 
     // Create snapshot without hash and epoch (backward compatibility)
     let snapshot_data = serde_json::json!({"test": "data"});
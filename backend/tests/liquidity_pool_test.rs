@@ -7,7 +7,7 @@ use stellar_insights_backend::services::liquidity_pool_analyzer::LiquidityPoolAn
 async fn test_liquidity_pool_sync_and_query(pool: SqlitePool) {
     // Create a mock RPC client
     let rpc_client = Arc::new(StellarRpcClient::new_with_defaults(true));
-    let analyzer = LiquidityPoolAnalyzer::new(pool.clone(), rpc_client);
+    let analyzer = LiquidityPoolAnalyzer::new(pool.clone().into(), rpc_client);
 
     // Sync pools from mock Horizon data
     let count = analyzer.sync_pools().await.unwrap();
@@ -34,7 +34,7 @@ async fn test_liquidity_pool_sync_and_query(pool: SqlitePool) {
 #[sqlx::test]
 async fn test_liquidity_pool_rankings(pool: SqlitePool) {
     let rpc_client = Arc::new(StellarRpcClient::new_with_defaults(true));
-    let analyzer = LiquidityPoolAnalyzer::new(pool.clone(), rpc_client);
+    let analyzer = LiquidityPoolAnalyzer::new(pool.clone().into(), rpc_client);
 
     // Sync first
     analyzer.sync_pools().await.unwrap();
@@ -55,7 +55,7 @@ async fn test_liquidity_pool_rankings(pool: SqlitePool) {
 #[sqlx::test]
 async fn test_liquidity_pool_snapshots(pool: SqlitePool) {
     let rpc_client = Arc::new(StellarRpcClient::new_with_defaults(true));
-    let analyzer = LiquidityPoolAnalyzer::new(pool.clone(), rpc_client);
+    let analyzer = LiquidityPoolAnalyzer::new(pool.clone().into(), rpc_client);
 
     // Sync pools first
     analyzer.sync_pools().await.unwrap();
@@ -77,7 +77,7 @@ async fn test_liquidity_pool_snapshots(pool: SqlitePool) {
 #[sqlx::test]
 async fn test_liquidity_pool_detail(pool: SqlitePool) {
     let rpc_client = Arc::new(StellarRpcClient::new_with_defaults(true));
-    let analyzer = LiquidityPoolAnalyzer::new(pool.clone(), rpc_client);
+    let analyzer = LiquidityPoolAnalyzer::new(pool.clone().into(), rpc_client);
 
     // Sync and snapshot
     analyzer.sync_pools().await.unwrap();
@@ -5,11 +5,12 @@ use stellar_insights_backend::database::Database;
 use stellar_insights_backend::rpc::StellarRpcClient;
 use stellar_insights_backend::services::aggregation::{AggregationConfig, AggregationService};
 use stellar_insights_backend::services::indexing::IndexingService;
+use stellar_insights_backend::with_pool;
 
 async fn setup_test_db() -> Arc<Database> {
     let pool = SqlitePool::connect(":memory:").await.unwrap();
     sqlx::migrate!("./migrations").run(&pool).await.unwrap();
-    Arc::new(Database::new(pool))
+    Arc::new(Database::new(pool.into()))
 }
 
 #[tokio::test]
@@ -21,10 +22,12 @@ async fn test_payment_ingestion_pipeline_end_to_end() {
     let indexing = IndexingService::new(Arc::clone(&rpc_client), Arc::clone(&db));
     indexing.run_payment_ingestion().await.unwrap();
 
-    let stored_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM payments")
-        .fetch_one(db.pool())
-        .await
-        .unwrap();
+    let stored_count: i64 = with_pool!(db.pool(), |pool| {
+        sqlx::query_scalar("SELECT COUNT(*) FROM payments")
+            .fetch_one(pool)
+            .await
+    })
+    .unwrap();
     assert!(
         stored_count > 0,
         "expected ingested payments to be persisted"
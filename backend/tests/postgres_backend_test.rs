@@ -0,0 +1,944 @@
+//! Runs the data layer against a real PostgreSQL server.
+//!
+//! Skipped unless `TEST_DATABASE_URL` points at a database the test may freely
+//! migrate, e.g. `TEST_DATABASE_URL=postgres://postgres@localhost/stellar_insights_test`.
+
+use chrono::{Duration, DurationRound, TimeZone, Utc};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::Arc;
+use stellar_insights_backend::database::{run_migrations, Database, DbPool, PoolConfig};
+use stellar_insights_backend::ingestion::contract_events::{
+    DecodedEvent, IndexedEvent, SnapshotSubmittedEvent, VoteCastEvent, VoteChoice,
+};
+use stellar_insights_backend::ml::{
+    payment_corridor_key, CorridorContext, ModelScore, ModelStatus, PaymentSuccessModel,
+    PredictionFeatures, TrainingConfig, TrainingExample,
+};
+use stellar_insights_backend::models::alerts::CreateAlertRuleRequest;
+use stellar_insights_backend::models::{CreateAnchorRequest, PaymentRecord};
+use stellar_insights_backend::replay::{
+    checkpoint::CheckpointManager,
+    config::{ReplayConfig, ReplayMode, ReplayRange},
+    engine::ReplayEngine,
+    event_processor::{CompositeEventProcessor, SnapshotEventProcessor},
+    metrics_processors::{
+        AnchorMetricsProcessor, CorridorMetricsProcessor, LiquidityPoolSnapshotProcessor,
+    },
+    state_builder::StateBuilder,
+    storage::{EventStorage, ReplayStorage},
+    verification::{MismatchKind, VerificationReport},
+    ReplayStatus,
+};
+use stellar_insights_backend::services::aggregation::{
+    AggregationConfig, AggregationService, HourlyCorridorMetrics,
+};
+use stellar_insights_backend::services::anomaly_detection::{
+    AnomalyConfig, AnomalyDetectionService,
+};
+use stellar_insights_backend::services::dex_aggregator::{
+    DepthLevel, LiquiditySnapshot, MarketDepth,
+};
+use stellar_insights_backend::services::rollup::{
+    fetch_corridor_distribution, fetch_corridor_series, Resolution, RollupConfig, RollupService,
+};
+use uuid::Uuid;
+
+async fn setup() -> Option<Database> {
+    let url = match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => url,
+        Err(_) => {
+            eprintln!("TEST_DATABASE_URL not set; skipping PostgreSQL backend test");
+            return None;
+        }
+    };
+
+    let pool: DbPool = PoolConfig::default()
+        .create_pool(&url)
+        .await
+        .expect("connect to TEST_DATABASE_URL");
+    run_migrations(&pool)
+        .await
+        .expect("apply postgres migrations");
+
+    // Re-running must be a no-op
+    run_migrations(&pool)
+        .await
+        .expect("re-apply postgres migrations");
+
+    Some(Database::new(pool))
+}
+
+/// The PostgreSQL pool behind `db`, for statements the data layer does not expose
+fn pg(db: &Database) -> &PgPool {
+    match db.pool() {
+        DbPool::Postgres(pool) => pool,
+        DbPool::Sqlite(_) => panic!("TEST_DATABASE_URL is not a PostgreSQL URL"),
+    }
+}
+
+fn unique(prefix: &str) -> String {
+    format!("{}{}", prefix, Uuid::new_v4().simple())
+}
+
+#[tokio::test]
+async fn test_anchor_and_asset_queries() {
+    let Some(db) = setup().await else { return };
+
+    let anchor = db
+        .create_anchor(CreateAnchorRequest {
+            name: "PG Anchor".to_string(),
+            stellar_account: unique("G"),
+            home_domain: Some("anchor.example".to_string()),
+        })
+        .await
+        .unwrap();
+
+    let anchor_id = Uuid::parse_str(&anchor.id).unwrap();
+    db.create_asset(anchor_id, "USDC".to_string(), unique("GISSUER"))
+        .await
+        .unwrap();
+    db.create_asset(anchor_id, "EURC".to_string(), unique("GISSUER"))
+        .await
+        .unwrap();
+
+    let fetched = db.get_anchor_by_id(anchor_id).await.unwrap().unwrap();
+    assert_eq!(fetched.name, "PG Anchor");
+
+    let assets = db.get_assets_by_anchors(&[anchor_id]).await.unwrap();
+    assert_eq!(assets.get(&anchor.id).map(Vec::len), Some(2));
+    assert_eq!(db.count_assets_by_anchor(anchor_id).await.unwrap(), 2);
+}
+
+#[tokio::test]
+async fn test_payments_round_trip() {
+    let Some(db) = setup().await else { return };
+
+    let created_at = Utc::now().duration_trunc(Duration::seconds(1)).unwrap();
+    let payment = PaymentRecord {
+        id: Uuid::new_v4().to_string(),
+        transaction_hash: unique("tx"),
+        source_account: unique("GSRC"),
+        destination_account: unique("GDST"),
+        asset_type: "credit_alphanum4".to_string(),
+        asset_code: Some("USDC".to_string()),
+        asset_issuer: Some(unique("GISSUER")),
+        source_asset_code: String::new(),
+        source_asset_issuer: String::new(),
+        destination_asset_code: String::new(),
+        destination_asset_issuer: String::new(),
+        amount: 42.5,
+        successful: true,
+        timestamp: None,
+        submission_time: None,
+        confirmation_time: None,
+        created_at,
+    };
+    let payment_id = payment.id.clone();
+    db.save_payments(vec![payment.clone()]).await.unwrap();
+    // Duplicate ids are ignored
+    db.save_payments(vec![payment]).await.unwrap();
+
+    let fetched = db
+        .fetch_payments_by_timerange(
+            created_at - Duration::seconds(1),
+            created_at + Duration::seconds(1),
+            1000,
+        )
+        .await
+        .unwrap();
+    let record = fetched
+        .iter()
+        .find(|p| p.id.to_string() == payment_id)
+        .expect("stored payment is returned");
+    assert_eq!(record.amount, 42.5);
+    assert_eq!(record.timestamp, created_at);
+}
+
+#[tokio::test]
+async fn test_hourly_metrics_upsert_accumulates() {
+    let Some(db) = setup().await else { return };
+
+    let hour_bucket = Utc::now().duration_trunc(Duration::hours(1)).unwrap() - Duration::days(400);
+    let metric = HourlyCorridorMetrics {
+        id: Uuid::new_v4().to_string(),
+        corridor_key: unique("USDC:GA->EURC:"),
+        asset_a_code: "USDC".to_string(),
+        asset_a_issuer: "GA".to_string(),
+        asset_b_code: "EURC".to_string(),
+        asset_b_issuer: "GB".to_string(),
+        hour_bucket,
+        total_transactions: 10,
+        successful_transactions: 9,
+        failed_transactions: 1,
+        success_rate: 90.0,
+        volume_usd: 1000.0,
+        avg_slippage_bps: 4.0,
+        avg_settlement_latency_ms: Some(1200),
+        liquidity_depth_usd: 50_000.0,
+    };
+    db.upsert_hourly_corridor_metric(&metric).await.unwrap();
+    db.upsert_hourly_corridor_metric(&HourlyCorridorMetrics {
+        id: Uuid::new_v4().to_string(),
+        ..metric.clone()
+    })
+    .await
+    .unwrap();
+
+    let stored: Vec<_> = db
+        .fetch_hourly_metrics_by_timerange(hour_bucket, hour_bucket)
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|m| m.corridor_key == metric.corridor_key)
+        .collect();
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].total_transactions, 20);
+    assert_eq!(stored[0].successful_transactions, 18);
+    assert_eq!(stored[0].volume_usd, 2000.0);
+    assert_eq!(stored[0].hour_bucket, hour_bucket);
+}
+
+#[tokio::test]
+async fn test_rollups_derive_coarser_buckets() {
+    let Some(db) = setup().await else { return };
+    let db = Arc::new(db);
+
+    // Start from scratch so buckets from earlier runs do not hide this test's data
+    sqlx::query("DELETE FROM rollup_watermarks")
+        .execute(pg(&db))
+        .await
+        .unwrap();
+
+    let now = Utc::now();
+    let month = Resolution::Month.bucket_start(now - Duration::days(70));
+    let corridor_key = unique("USDC:GA->EURC:");
+    for (offset_hours, total) in [(0, 10), (5, 20), (24, 30)] {
+        db.upsert_hourly_corridor_metric(&HourlyCorridorMetrics {
+            id: Uuid::new_v4().to_string(),
+            corridor_key: corridor_key.clone(),
+            asset_a_code: "USDC".to_string(),
+            asset_a_issuer: "GA".to_string(),
+            asset_b_code: "EURC".to_string(),
+            asset_b_issuer: "GB".to_string(),
+            hour_bucket: month + Duration::hours(offset_hours),
+            total_transactions: total,
+            successful_transactions: total / 2,
+            failed_transactions: total - total / 2,
+            success_rate: 50.0,
+            volume_usd: total as f64,
+            avg_slippage_bps: 1.0,
+            avg_settlement_latency_ms: Some(1000),
+            liquidity_depth_usd: 100.0,
+        })
+        .await
+        .unwrap();
+    }
+
+    let config = RollupConfig {
+        retention: HashMap::new(),
+        max_buckets_per_run: 10_000,
+    };
+    let rollups = RollupService::new(Arc::clone(&db), config);
+    let hourly_complete_until = Resolution::Hour.bucket_start(now);
+    rollups.run(now, hourly_complete_until).await.unwrap();
+    // A second run has nothing left to finalize and leaves the buckets unchanged
+    let stats = rollups.run(now, hourly_complete_until).await.unwrap();
+    assert_eq!(stats.buckets_finalized[&Resolution::Day], 0);
+
+    let rollup_db = db.rollup_db();
+    let end = Resolution::Month.next_bucket(month);
+    let days = fetch_corridor_series(
+        &rollup_db,
+        &corridor_key,
+        month,
+        end,
+        Some(Duration::days(1)),
+    )
+    .await
+    .unwrap();
+    assert_eq!(days.resolution, Resolution::Day);
+    let totals: Vec<i64> = days.points.iter().map(|p| p.total_transactions).collect();
+    assert_eq!(totals, vec![30, 30]);
+
+    let months = fetch_corridor_series(
+        &rollup_db,
+        &corridor_key,
+        month,
+        end,
+        Some(Duration::days(31)),
+    )
+    .await
+    .unwrap();
+    assert_eq!(months.resolution, Resolution::Month);
+    assert_eq!(months.points.len(), 1);
+    assert_eq!(months.points[0].total_transactions, 60);
+    assert_eq!(months.points[0].successful_transactions, 30);
+    assert_eq!(months.points[0].source_buckets, 3);
+}
+
+#[tokio::test]
+async fn test_aggregation_records_distribution_sketches() {
+    let Some(db) = setup().await else { return };
+    let db = Arc::new(db);
+
+    let issuer = unique("GISSUER");
+    let created_at = Utc::now() - Duration::minutes(30);
+    let payments: Vec<PaymentRecord> = [10.0, 20.0, 1000.0]
+        .into_iter()
+        .map(|amount| PaymentRecord {
+            id: Uuid::new_v4().to_string(),
+            transaction_hash: unique("tx"),
+            source_account: unique("GSRC"),
+            destination_account: unique("GDST"),
+            asset_type: "credit_alphanum4".to_string(),
+            asset_code: Some("USDC".to_string()),
+            asset_issuer: Some(issuer.clone()),
+            source_asset_code: String::new(),
+            source_asset_issuer: String::new(),
+            destination_asset_code: String::new(),
+            destination_asset_issuer: String::new(),
+            amount,
+            successful: true,
+            timestamp: None,
+            submission_time: None,
+            confirmation_time: None,
+            created_at,
+        })
+        .collect();
+    db.save_payments(payments).await.unwrap();
+
+    AggregationService::new(Arc::clone(&db), AggregationConfig::default())
+        .run_hourly_aggregation()
+        .await
+        .unwrap();
+
+    let corridor_key = format!("USDC:{}->USDC:{}", issuer, issuer);
+    let now = Utc::now();
+    let distribution = fetch_corridor_distribution(
+        &db.rollup_db(),
+        &corridor_key,
+        now - Duration::hours(3),
+        now,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(distribution.resolution, Resolution::Hour);
+    let amount = distribution.amount.expect("amount distribution");
+    assert_eq!(amount.count, 3);
+    assert_eq!(amount.min, 10.0);
+    assert_eq!(amount.max, 1000.0);
+    assert!((amount.p50 - 20.0).abs() <= 0.2);
+    // Payments carry no submission/confirmation times, so there is no latency to report
+    assert!(distribution.latency_ms.is_none());
+}
+
+#[tokio::test]
+async fn test_aggregation_job_lifecycle() {
+    let Some(db) = setup().await else { return };
+
+    let job_id = Uuid::new_v4().to_string();
+    db.create_aggregation_job(&job_id, "hourly").await.unwrap();
+    db.update_aggregation_job_status(&job_id, "running", None)
+        .await
+        .unwrap();
+    db.increment_job_retry_count(&job_id).await.unwrap();
+    db.increment_job_retry_count(&job_id).await.unwrap();
+    assert_eq!(db.get_job_retry_count(&job_id).await.unwrap(), 2);
+
+    db.update_last_processed_hour(&job_id, &Utc::now().to_rfc3339())
+        .await
+        .unwrap();
+    db.update_aggregation_job_status(&job_id, "completed", None)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_corridor_summary_stats_types() {
+    let Some(db) = setup().await else { return };
+
+    // Far-future window so rows from other tests never fall inside it
+    let day = chrono::NaiveDate::from_ymd_opt(2999, 1, 1).unwrap();
+    let stats = db
+        .corridor_aggregates()
+        .get_corridor_summary_stats(day, day)
+        .await
+        .unwrap();
+    assert_eq!(stats.total_corridors, 0);
+    assert_eq!(stats.total_transactions, None);
+}
+
+#[tokio::test]
+async fn test_anomaly_detection_alerts_subscribed_users() {
+    let Some(db) = setup().await else { return };
+    let db = Arc::new(db);
+
+    // Start from scratch so baselines from earlier runs do not skip this test's history
+    sqlx::query("DELETE FROM corridor_anomaly_baselines")
+        .execute(pg(&db))
+        .await
+        .unwrap();
+
+    let corridor_key = unique("USDC:GA->BRL:");
+    let user_id = Uuid::new_v4().to_string();
+    sqlx::query("INSERT INTO users (id, username) VALUES ($1, $2)")
+        .bind(&user_id)
+        .bind(unique("anomaly-user-"))
+        .execute(pg(&db))
+        .await
+        .unwrap();
+    db.create_alert_rule(
+        &user_id,
+        CreateAlertRuleRequest {
+            corridor_id: Some(corridor_key.clone()),
+            metric_type: "success_rate".to_string(),
+            condition: "anomaly".to_string(),
+            threshold: 3.0,
+            notify_email: false,
+            notify_webhook: false,
+            notify_in_app: true,
+        },
+    )
+    .await
+    .unwrap();
+
+    // Eight steady days, then an hour where half the payments fail
+    let now = Utc::now();
+    let complete_until = Resolution::Hour.bucket_start(now);
+    let hours = 24 * 8;
+    for i in 0..=hours {
+        let success_rate = if i == hours {
+            50.0
+        } else {
+            97.0 + (i % 3) as f64
+        };
+        db.upsert_hourly_corridor_metric(&HourlyCorridorMetrics {
+            id: Uuid::new_v4().to_string(),
+            corridor_key: corridor_key.clone(),
+            asset_a_code: "USDC".to_string(),
+            asset_a_issuer: "GA".to_string(),
+            asset_b_code: "BRL".to_string(),
+            asset_b_issuer: "GB".to_string(),
+            hour_bucket: complete_until - Duration::hours(hours + 1 - i),
+            total_transactions: 100,
+            successful_transactions: success_rate as i64,
+            failed_transactions: 100 - success_rate as i64,
+            success_rate,
+            volume_usd: 10_000.0,
+            avg_slippage_bps: 1.0,
+            avg_settlement_latency_ms: Some(400),
+            liquidity_depth_usd: 50_000.0,
+        })
+        .await
+        .unwrap();
+    }
+
+    let service = AnomalyDetectionService::new(Arc::clone(&db), AnomalyConfig::default());
+    service.run(now, complete_until).await.unwrap();
+    // Nothing new to learn from, and the anomaly is not reported twice
+    service.run(now, complete_until).await.unwrap();
+
+    let anomalies = db
+        .anomaly_db()
+        .list_anomalies(&corridor_key, complete_until - Duration::days(9), now, 10)
+        .await
+        .unwrap();
+    assert_eq!(anomalies.len(), 1);
+    assert_eq!(anomalies[0].metric.as_str(), "success_rate");
+    assert_eq!(
+        anomalies[0].bucket_start,
+        complete_until - Duration::hours(1)
+    );
+    assert!(anomalies[0].z_score < -3.0);
+
+    let history = db.get_alert_history_for_user(&user_id, 10).await.unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(
+        history[0].corridor_id.as_deref(),
+        Some(corridor_key.as_str())
+    );
+    assert_eq!(history[0].condition, "anomaly");
+}
+
+#[tokio::test]
+async fn test_ml_training_data_and_model_registry() {
+    let Some(db) = setup().await else { return };
+    let ml_db = db.ml_db();
+
+    let issuer = unique("GISSUER");
+    let created_at =
+        Utc::now().duration_trunc(Duration::seconds(1)).unwrap() - Duration::minutes(5);
+    let failed_tx = unique("tx");
+    let ledger = (Uuid::new_v4().as_u128() % 1_000_000_000) as i64 + 1_000_000_000_000;
+    sqlx::query(
+        "INSERT INTO ledgers (sequence, hash, close_time) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING",
+    )
+    .bind(ledger)
+    .bind(unique("ledger"))
+    .bind(created_at)
+    .execute(pg(&db))
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO transactions (hash, ledger_sequence, successful) VALUES ($1, $2, FALSE)",
+    )
+    .bind(&failed_tx)
+    .bind(ledger)
+    .execute(pg(&db))
+    .await
+    .unwrap();
+
+    let payment = |transaction_hash: String, offset: i64| PaymentRecord {
+        id: Uuid::new_v4().to_string(),
+        transaction_hash,
+        source_account: unique("GSRC"),
+        destination_account: unique("GDST"),
+        asset_type: "credit_alphanum4".to_string(),
+        asset_code: Some("USDC".to_string()),
+        asset_issuer: Some(issuer.clone()),
+        source_asset_code: String::new(),
+        source_asset_issuer: String::new(),
+        destination_asset_code: String::new(),
+        destination_asset_issuer: String::new(),
+        amount: 10.0,
+        successful: true,
+        timestamp: None,
+        submission_time: None,
+        confirmation_time: None,
+        created_at: created_at + Duration::seconds(offset),
+    };
+    let successful_tx = unique("tx");
+    db.save_payments(vec![
+        payment(failed_tx.clone(), 0),
+        payment(successful_tx.clone(), 1),
+    ])
+    .await
+    .unwrap();
+
+    // Payments without an indexed transaction count as successful
+    let corridor_key = payment_corridor_key("USDC", &issuer);
+    let labelled: Vec<bool> = ml_db
+        .fetch_labelled_payments(created_at - Duration::seconds(1), Utc::now(), 100_000)
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|p| p.corridor_key == corridor_key)
+        .map(|p| p.successful)
+        .collect();
+    assert_eq!(labelled, vec![false, true]);
+
+    let outcomes = ml_db
+        .count_recent_outcomes("USDC", &issuer, created_at - Duration::hours(1), Utc::now())
+        .await
+        .unwrap();
+    assert_eq!(outcomes, (2, 1));
+
+    let start = Utc::now() - Duration::days(1);
+    let examples: Vec<TrainingExample> = (0..400)
+        .map(|i| TrainingExample {
+            features: PredictionFeatures::new(
+                f64::from(i),
+                start + Duration::minutes(i64::from(i)),
+                &CorridorContext::default(),
+            ),
+            successful: i < 200,
+            timestamp: start + Duration::minutes(i64::from(i)),
+        })
+        .collect();
+    let model = PaymentSuccessModel::train(&examples, &TrainingConfig::default()).unwrap();
+
+    let model_name = unique("test_model");
+    assert!(ml_db.load_model(&model_name, 1).await.unwrap().is_none());
+    assert_eq!(ml_db.save_model(&model_name, &model).await.unwrap(), 1);
+    assert_eq!(ml_db.save_model(&model_name, &model).await.unwrap(), 2);
+
+    // Promoting a version retires the previously active one
+    let now = Utc::now();
+    assert!(ml_db
+        .set_model_status(&model_name, 1, ModelStatus::Active, now)
+        .await
+        .unwrap());
+    assert!(ml_db
+        .set_model_status(&model_name, 2, ModelStatus::Shadow, now)
+        .await
+        .unwrap());
+    assert!(!ml_db
+        .set_model_status(&model_name, 3, ModelStatus::Active, now)
+        .await
+        .unwrap());
+    let serving: Vec<(ModelStatus, i64)> = ml_db
+        .load_serving_models(&model_name)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|(status, model)| (status, model.version))
+        .collect();
+    assert_eq!(
+        serving,
+        vec![(ModelStatus::Shadow, 2), (ModelStatus::Active, 1)]
+    );
+    assert!(ml_db
+        .set_model_status(&model_name, 2, ModelStatus::Active, now)
+        .await
+        .unwrap());
+    let statuses: Vec<(i64, ModelStatus)> = ml_db
+        .list_models(&model_name)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|m| (m.version, m.status))
+        .collect();
+    assert_eq!(
+        statuses,
+        vec![(2, ModelStatus::Active), (1, ModelStatus::Retired)]
+    );
+
+    // Logged predictions are labelled directly or once their transaction is ingested
+    let features = examples[0].features.clone();
+    let scores = [
+        ModelScore {
+            version: 2,
+            role: ModelStatus::Active,
+            probability: 0.8,
+        },
+        ModelScore {
+            version: 1,
+            role: ModelStatus::Shadow,
+            probability: 0.4,
+        },
+    ];
+    let reported = unique("prediction");
+    let pending = unique("prediction");
+    for prediction_id in [&reported, &pending] {
+        ml_db
+            .log_prediction(
+                prediction_id,
+                &model_name,
+                "USDC",
+                &issuer,
+                10.0,
+                &features,
+                &scores,
+                now,
+            )
+            .await
+            .unwrap();
+    }
+    assert_eq!(
+        ml_db
+            .record_transaction_hash(&reported, &successful_tx)
+            .await
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        ml_db
+            .record_transaction_hash(&pending, &failed_tx)
+            .await
+            .unwrap(),
+        2
+    );
+    assert!(ml_db.resolve_outcomes(now).await.unwrap() >= 4);
+    // Resolved predictions keep their outcome
+    assert_eq!(
+        ml_db
+            .record_transaction_hash(&reported, &failed_tx)
+            .await
+            .unwrap(),
+        0
+    );
+
+    let mut logged = ml_db
+        .fetch_logged_predictions(&model_name, now - Duration::seconds(1))
+        .await
+        .unwrap();
+    logged.sort_by(|a, b| (a.0, a.2).cmp(&(b.0, b.2)));
+    assert_eq!(
+        logged,
+        vec![
+            (1, 0.4, Some(false)),
+            (1, 0.4, Some(true)),
+            (2, 0.8, Some(false)),
+            (2, 0.8, Some(true)),
+        ]
+    );
+
+    let loaded = ml_db.load_model(&model_name, 2).await.unwrap().unwrap();
+    assert_eq!(loaded.version, 2);
+    assert_eq!(loaded.feature_names, model.feature_names);
+    assert_eq!(loaded.metrics.samples, model.metrics.samples);
+    // JSON round-trips floats to within an ulp
+    for example in &examples {
+        let features = example.features.to_vec();
+        let difference =
+            loaded.model.predict_proba(&features) - model.model.predict_proba(&features);
+        assert!(difference.abs() < 1e-12);
+    }
+}
+
+#[tokio::test]
+async fn test_dex_liquidity_snapshots_round_trip() {
+    let Some(db) = setup().await else { return };
+    let dex_db = db.dex_db();
+
+    let corridor_key = format!("{}->XLM:native", unique("USDC:G"));
+    let market = MarketDepth {
+        base_asset: corridor_key.split("->").next().unwrap().to_string(),
+        counter_asset: "native".to_string(),
+        bids: vec![DepthLevel {
+            price: 9.95,
+            amount: 1_000.0,
+        }],
+        asks: vec![DepthLevel {
+            price: 10.05,
+            amount: 800.0,
+        }],
+        pools: Vec::new(),
+        base_usd_price: Some(1.0),
+    };
+    let now = Utc::now().duration_trunc(Duration::seconds(1)).unwrap();
+    let older =
+        LiquiditySnapshot::from_market(&corridor_key, &market, now - Duration::hours(2)).unwrap();
+    let latest = LiquiditySnapshot::from_market(&corridor_key, &market, now).unwrap();
+    dex_db.insert_snapshot(&older).await.unwrap();
+    dex_db.insert_snapshot(&latest).await.unwrap();
+
+    let loaded = dex_db
+        .latest_snapshot(&corridor_key, now - Duration::hours(1))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(loaded.id, latest.id);
+    assert_eq!(loaded.captured_at, now);
+    assert_eq!(loaded.curve, latest.curve);
+    assert_eq!(loaded.depth_usd_at(1.0), Some(1_800.0));
+
+    let listed: Vec<String> = dex_db
+        .list_snapshots(
+            &corridor_key,
+            now - Duration::days(1),
+            now + Duration::hours(1),
+        )
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|s| s.id)
+        .collect();
+    assert_eq!(listed, vec![older.id.clone(), latest.id.clone()]);
+
+    assert!(
+        dex_db
+            .delete_snapshots_before(now - Duration::hours(1))
+            .await
+            .unwrap()
+            >= 1
+    );
+    let remaining = dex_db
+        .list_snapshots(
+            &corridor_key,
+            now - Duration::days(1),
+            now + Duration::hours(1),
+        )
+        .await
+        .unwrap();
+    assert_eq!(remaining.len(), 1);
+}
+
+#[tokio::test]
+async fn test_contract_events_round_trip() {
+    let Some(db) = setup().await else { return };
+    let event_db = db.contract_event_db();
+
+    let contract_id = unique("C");
+    let closed_at = Utc::now().duration_trunc(Duration::seconds(1)).unwrap();
+    let vote = IndexedEvent {
+        id: unique("vote"),
+        contract_id: contract_id.clone(),
+        ledger_sequence: 100,
+        transaction_hash: unique("tx"),
+        closed_at,
+        event: DecodedEvent::VoteCast(VoteCastEvent {
+            proposal_id: 4,
+            voter: unique("G"),
+            choice: VoteChoice::Against,
+        }),
+    };
+    let snapshot = IndexedEvent {
+        id: unique("snapshot"),
+        contract_id: contract_id.clone(),
+        ledger_sequence: 101,
+        transaction_hash: unique("tx"),
+        closed_at,
+        event: DecodedEvent::SnapshotSubmitted(SnapshotSubmittedEvent {
+            epoch: 12,
+            hash: "cd".repeat(32),
+            ledger_timestamp: 1_734_032_457,
+            submitter: None,
+        }),
+    };
+    assert!(event_db.store_event(&vote, "testnet").await.unwrap());
+    assert!(event_db.store_event(&snapshot, "testnet").await.unwrap());
+    assert!(!event_db.store_event(&vote, "testnet").await.unwrap());
+
+    let votes = event_db
+        .list_votes(Some(&contract_id), Some(4), 10)
+        .await
+        .unwrap();
+    assert_eq!(votes.len(), 1);
+    assert_eq!(votes[0].choice, "against");
+    assert_eq!(votes[0].closed_at, closed_at.to_rfc3339());
+
+    let submissions = event_db
+        .list_snapshot_submissions(Some(&contract_id), None, 10)
+        .await
+        .unwrap();
+    assert_eq!(submissions.len(), 1);
+    assert_eq!(submissions[0].epoch, 12);
+    assert_eq!(submissions[0].submitter, None);
+}
+
+async fn run_replay(pool: &DbPool, mode: ReplayMode, start: u64, end: u64) -> String {
+    let processor = CompositeEventProcessor::new()
+        .add_processor(Arc::new(SnapshotEventProcessor::new(pool.clone())))
+        .add_processor(Arc::new(CorridorMetricsProcessor::hourly(pool.clone())))
+        .add_processor(Arc::new(CorridorMetricsProcessor::daily(pool.clone())))
+        .add_processor(Arc::new(AnchorMetricsProcessor::new(pool.clone())))
+        .add_processor(Arc::new(LiquidityPoolSnapshotProcessor::new(pool.clone())));
+
+    let engine = ReplayEngine::new(
+        ReplayConfig::new()
+            .with_mode(mode)
+            .with_range(ReplayRange::FromTo { start, end }),
+        Arc::new(EventStorage::new(pool.clone())),
+        Arc::new(ReplayStorage::new(pool.clone())),
+        Arc::new(CheckpointManager::new(pool.clone())),
+        Arc::new(processor),
+        Arc::new(tokio::sync::RwLock::new(StateBuilder::new(pool.clone()))),
+    )
+    .unwrap();
+
+    let metadata = engine.start().await.unwrap();
+    assert!(
+        matches!(
+            metadata.status,
+            ReplayStatus::Completed {
+                events_failed: 0,
+                ..
+            }
+        ),
+        "unexpected status: {}",
+        metadata.status
+    );
+    metadata.session_id
+}
+
+async fn run_verification(pool: &DbPool, start: u64, end: u64) -> VerificationReport {
+    let session_id = run_replay(pool, ReplayMode::Verification, start, end).await;
+    ReplayStorage::new(pool.clone())
+        .load_verification_report(&session_id)
+        .await
+        .unwrap()
+        .expect("verification report stored")
+}
+
+#[tokio::test]
+async fn test_verification_replay_compares_live_tables() {
+    let Some(db) = setup().await else { return };
+    let pool = db.pool().clone();
+    let pg = pg(&db);
+
+    // An hour and ledger range no other test writes to
+    let seed = Uuid::new_v4().as_u128();
+    let hour = Utc.with_ymd_and_hms(2001, 1, 1, 0, 0, 0).unwrap()
+        + Duration::hours((seed % 100_000) as i64);
+    let first = ((seed >> 64) % 1_000_000_000) as i64 * 10 + 2_000_000_000_000;
+    let issuer = unique("GISSUER");
+    let anchor_account = unique("GANCHOR");
+    let corridor_key = format!("USDC:{issuer}->USDC:{issuer}");
+
+    sqlx::query("INSERT INTO anchors (id, name, stellar_account) VALUES ($1, 'Replay Anchor', $2)")
+        .bind(unique("anchor-"))
+        .bind(&anchor_account)
+        .execute(pg)
+        .await
+        .unwrap();
+
+    // A successful anchor payment and a failed one in the same hour
+    for (offset, successful, amount) in [(0, true, 100.0), (1, false, 50.0)] {
+        let sequence = first + offset;
+        let close_time = hour + Duration::minutes(15 + 30 * offset);
+        let tx = unique("tx");
+        sqlx::query("INSERT INTO ledgers (sequence, hash, close_time) VALUES ($1, $2, $3)")
+            .bind(sequence)
+            .bind(unique("ledger"))
+            .bind(close_time)
+            .execute(pg)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO transactions (hash, ledger_sequence, successful) VALUES ($1, $2, $3)",
+        )
+        .bind(&tx)
+        .bind(sequence)
+        .bind(successful)
+        .execute(pg)
+        .await
+        .unwrap();
+        sqlx::query(
+            r#"
+            INSERT INTO ledger_payments
+                (ledger_sequence, transaction_hash, operation_type, source_account, destination, asset_code, asset_issuer, amount)
+            VALUES ($1, $2, 'payment', $3, 'GUSER', 'USDC', $4, $5)
+            "#,
+        )
+        .bind(sequence)
+        .bind(&tx)
+        .bind(&anchor_account)
+        .bind(&issuer)
+        .bind(format!("{:.7}", amount))
+        .execute(pg)
+        .await
+        .unwrap();
+        sqlx::query(
+            r#"
+            INSERT INTO payments
+                (id, transaction_hash, source_account, destination_account, asset_type, asset_code, asset_issuer, amount, created_at)
+            VALUES ($1, $2, $3, 'GUSER', 'credit_alphanum4', 'USDC', $4, $5, $6)
+            "#,
+        )
+        .bind(unique("op-"))
+        .bind(&tx)
+        .bind(&anchor_account)
+        .bind(&issuer)
+        .bind(amount)
+        .bind(close_time)
+        .execute(pg)
+        .await
+        .unwrap();
+    }
+
+    let (start, end) = (first as u64, first as u64 + 1);
+    run_replay(&pool, ReplayMode::Full, start, end).await;
+
+    let report = run_verification(&pool, start, end).await;
+    assert!(report.is_consistent(), "unexpected drift: {:?}", report);
+    // One hourly and one daily corridor row and one anchor entry
+    assert_eq!(report.rows_compared, 3);
+
+    sqlx::query(
+        "UPDATE corridor_metrics_hourly SET volume_usd = volume_usd + 5 WHERE corridor_key = $1",
+    )
+    .bind(&corridor_key)
+    .execute(pg)
+    .await
+    .unwrap();
+
+    let report = run_verification(&pool, start, end).await;
+    assert_eq!(report.rows_mismatched, 1);
+    assert_eq!(report.mismatches[0].table, "corridor_metrics_hourly");
+    assert_eq!(report.mismatches[0].key, corridor_key);
+    assert_eq!(report.mismatches[0].kind, MismatchKind::Drifted);
+    assert_eq!(report.first_diverging_ledger, Some(start));
+}
@@ -17,9 +17,15 @@ use stellar_insights_backend::replay::{
     checkpoint::{Checkpoint, CheckpointManager},
     config::{ReplayConfig, ReplayMode, ReplayRange},
     engine::ReplayEngine,
-    event_processor::{CompositeEventProcessor, ProcessingContext, SnapshotEventProcessor},
+    event_processor::{
+        CompositeEventProcessor, EventProcessor, ProcessingContext, SnapshotEventProcessor,
+    },
+    metrics_processors::{
+        AnchorMetricsProcessor, CorridorMetricsProcessor, LiquidityPoolSnapshotProcessor,
+    },
     state_builder::{ApplicationState, StateBuilder},
     storage::{EventStorage, ReplayStorage},
+    verification::{MismatchKind, VerificationReport},
     ContractEvent, EventFilter,
 };
 
@@ -68,6 +74,17 @@ async fn setup_test_db() -> SqlitePool {
             updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
         );
 
+        CREATE TABLE replay_verification_reports (
+            session_id TEXT PRIMARY KEY,
+            start_ledger INTEGER NOT NULL,
+            end_ledger INTEGER NOT NULL,
+            rows_compared INTEGER NOT NULL,
+            rows_mismatched INTEGER NOT NULL,
+            first_diverging_ledger INTEGER,
+            report TEXT NOT NULL,
+            created_at TIMESTAMP NOT NULL
+        );
+
         CREATE TABLE processed_events (
             event_id TEXT PRIMARY KEY,
             ledger_sequence INTEGER NOT NULL,
@@ -116,7 +133,7 @@ fn create_test_events(count: usize, start_ledger: u64) -> Vec<ContractEvent> {
 #[tokio::test]
 async fn test_event_storage_and_retrieval() {
     let pool = setup_test_db().await;
-    let storage = EventStorage::new(pool);
+    let storage = EventStorage::new(pool.into());
 
     // Store events
     let events = create_test_events(10, 1000);
@@ -138,7 +155,7 @@ async fn test_event_storage_and_retrieval() {
 #[tokio::test]
 async fn test_event_filtering() {
     let pool = setup_test_db().await;
-    let storage = EventStorage::new(pool);
+    let storage = EventStorage::new(pool.into());
 
     // Store events with different contracts
     let mut events = create_test_events(5, 1000);
@@ -170,7 +187,7 @@ async fn test_event_filtering() {
 #[tokio::test]
 async fn test_checkpoint_creation_and_restoration() {
     let pool = setup_test_db().await;
-    let manager = CheckpointManager::new(pool);
+    let manager = CheckpointManager::new(pool.into());
 
     // Create checkpoint
     let checkpoint = Checkpoint::new("session-1".to_string(), 1000)
@@ -191,7 +208,7 @@ async fn test_checkpoint_creation_and_restoration() {
 #[tokio::test]
 async fn test_checkpoint_latest() {
     let pool = setup_test_db().await;
-    let manager = CheckpointManager::new(pool);
+    let manager = CheckpointManager::new(pool.into());
 
     // Create multiple checkpoints
     for i in 0..3 {
@@ -209,7 +226,7 @@ async fn test_checkpoint_latest() {
 #[tokio::test]
 async fn test_state_builder() {
     let pool = setup_test_db().await;
-    let mut builder = StateBuilder::new(pool);
+    let mut builder = StateBuilder::new(pool.into());
 
     // Apply events
     let events = create_test_events(5, 1000);
@@ -227,7 +244,7 @@ async fn test_state_builder() {
 #[tokio::test]
 async fn test_state_idempotency() {
     let pool = setup_test_db().await;
-    let mut builder = StateBuilder::new(pool);
+    let mut builder = StateBuilder::new(pool.into());
 
     // Apply same event twice
     let event = create_test_events(1, 1000)[0].clone();
@@ -245,7 +262,7 @@ async fn test_state_idempotency() {
 #[tokio::test]
 async fn test_state_persistence_and_verification() {
     let pool = setup_test_db().await;
-    let mut builder = StateBuilder::new(pool.clone());
+    let mut builder = StateBuilder::new(pool.clone().into());
 
     // Build state
     let events = create_test_events(5, 1000);
@@ -257,7 +274,7 @@ async fn test_state_persistence_and_verification() {
     builder.persist_state().await.unwrap();
 
     // Load state in new builder
-    let mut new_builder = StateBuilder::new(pool);
+    let mut new_builder = StateBuilder::new(pool.into());
     let loaded = new_builder.load_state(1004).await.unwrap();
 
     assert!(loaded);
@@ -272,8 +289,8 @@ async fn test_state_persistence_and_verification() {
 #[tokio::test]
 async fn test_state_hash_consistency() {
     let pool = setup_test_db().await;
-    let mut builder1 = StateBuilder::new(pool.clone());
-    let mut builder2 = StateBuilder::new(pool);
+    let mut builder1 = StateBuilder::new(pool.clone().into());
+    let mut builder2 = StateBuilder::new(pool.into());
 
     // Apply same events to both builders
     let events = create_test_events(5, 1000);
@@ -302,7 +319,7 @@ async fn test_processing_context() {
 #[tokio::test]
 async fn test_event_processor_idempotency() {
     let pool = setup_test_db().await;
-    let processor = SnapshotEventProcessor::new(pool);
+    let processor = SnapshotEventProcessor::new(pool.into());
 
     let event = create_test_events(1, 1000)[0].clone();
 
@@ -349,7 +366,7 @@ async fn test_replay_range() {
 #[tokio::test]
 async fn test_replay_storage() {
     let pool = setup_test_db().await;
-    let storage = ReplayStorage::new(pool);
+    let storage = ReplayStorage::new(pool.into());
 
     // Create metadata
     let metadata = stellar_insights_backend::replay::ReplayMetadata {
@@ -377,7 +394,7 @@ async fn test_replay_storage() {
 #[tokio::test]
 async fn test_event_ordering() {
     let pool = setup_test_db().await;
-    let storage = EventStorage::new(pool);
+    let storage = EventStorage::new(pool.into());
 
     // Store events out of order
     let mut events = create_test_events(5, 1000);
@@ -401,7 +418,7 @@ async fn test_event_ordering() {
 #[tokio::test]
 async fn test_checkpoint_cleanup() {
     let pool = setup_test_db().await;
-    let manager = CheckpointManager::new(pool);
+    let manager = CheckpointManager::new(pool.into());
 
     // Create old checkpoint
     let old_checkpoint = Checkpoint {
@@ -426,7 +443,7 @@ async fn test_checkpoint_cleanup() {
 #[tokio::test]
 async fn test_concurrent_event_processing() {
     let pool = setup_test_db().await;
-    let storage = Arc::new(EventStorage::new(pool));
+    let storage = Arc::new(EventStorage::new(pool.into()));
 
     // Store events concurrently
     let events = create_test_events(100, 1000);
@@ -456,7 +473,7 @@ async fn test_concurrent_event_processing() {
 #[tokio::test]
 async fn test_state_corruption_detection() {
     let pool = setup_test_db().await;
-    let mut builder = StateBuilder::new(pool.clone());
+    let mut builder = StateBuilder::new(pool.clone().into());
 
     // Build and persist state
     let events = create_test_events(5, 1000);
@@ -472,8 +489,483 @@ async fn test_state_corruption_detection() {
         .unwrap();
 
     // Try to load - should detect corruption
-    let mut new_builder = StateBuilder::new(pool);
+    let mut new_builder = StateBuilder::new(pool.into());
     let result = new_builder.load_state(1004).await;
 
     assert!(result.is_err());
 }
+
+/// Setup the ingestion and analytics tables the metrics processors read and rebuild
+async fn setup_metrics_tables(pool: &SqlitePool) {
+    sqlx::query(
+        r#"
+        CREATE TABLE ledgers (
+            sequence INTEGER PRIMARY KEY,
+            hash TEXT NOT NULL,
+            close_time TEXT NOT NULL,
+            transaction_count INTEGER DEFAULT 0,
+            operation_count INTEGER DEFAULT 0
+        );
+
+        CREATE TABLE transactions (
+            hash TEXT PRIMARY KEY,
+            ledger_sequence INTEGER NOT NULL,
+            successful INTEGER
+        );
+
+        CREATE TABLE ledger_payments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            ledger_sequence INTEGER NOT NULL,
+            transaction_hash TEXT NOT NULL,
+            operation_type TEXT,
+            source_account TEXT,
+            destination TEXT,
+            asset_code TEXT,
+            asset_issuer TEXT,
+            amount TEXT
+        );
+
+        CREATE TABLE payments (
+            id TEXT PRIMARY KEY,
+            transaction_hash TEXT NOT NULL,
+            source_account TEXT NOT NULL,
+            destination_account TEXT NOT NULL,
+            asset_type TEXT NOT NULL,
+            asset_code TEXT,
+            asset_issuer TEXT,
+            amount REAL NOT NULL,
+            created_at TEXT NOT NULL
+        );
+
+        CREATE TABLE anchor_sep_transactions (
+            stellar_transaction_id TEXT PRIMARY KEY,
+            started_at TEXT,
+            completed_at TEXT
+        );
+
+        CREATE TABLE corridor_metrics_hourly (
+            id TEXT PRIMARY KEY,
+            corridor_key TEXT NOT NULL,
+            asset_a_code TEXT NOT NULL,
+            asset_a_issuer TEXT NOT NULL,
+            asset_b_code TEXT NOT NULL,
+            asset_b_issuer TEXT NOT NULL,
+            hour_bucket TEXT NOT NULL,
+            total_transactions INTEGER DEFAULT 0,
+            successful_transactions INTEGER DEFAULT 0,
+            failed_transactions INTEGER DEFAULT 0,
+            success_rate REAL DEFAULT 0,
+            volume_usd REAL DEFAULT 0,
+            avg_slippage_bps REAL DEFAULT 0,
+            avg_settlement_latency_ms INTEGER,
+            liquidity_depth_usd REAL DEFAULT 0,
+            UNIQUE(corridor_key, hour_bucket)
+        );
+
+        CREATE TABLE corridor_metrics (
+            corridor_key TEXT NOT NULL,
+            asset_a_code TEXT NOT NULL,
+            asset_a_issuer TEXT NOT NULL,
+            asset_b_code TEXT NOT NULL,
+            asset_b_issuer TEXT NOT NULL,
+            date TEXT NOT NULL,
+            total_transactions INTEGER DEFAULT 0,
+            successful_transactions INTEGER DEFAULT 0,
+            failed_transactions INTEGER DEFAULT 0,
+            success_rate REAL DEFAULT 0,
+            volume_usd REAL DEFAULT 0,
+            PRIMARY KEY (corridor_key, date)
+        );
+
+        CREATE TABLE anchors (
+            id TEXT PRIMARY KEY,
+            stellar_account TEXT NOT NULL
+        );
+
+        CREATE TABLE anchor_metrics_history (
+            id TEXT PRIMARY KEY,
+            anchor_id TEXT NOT NULL,
+            timestamp TEXT NOT NULL,
+            success_rate REAL NOT NULL,
+            failure_rate REAL NOT NULL,
+            reliability_score REAL NOT NULL,
+            total_transactions INTEGER NOT NULL,
+            successful_transactions INTEGER NOT NULL,
+            failed_transactions INTEGER NOT NULL,
+            avg_settlement_time_ms INTEGER,
+            volume_usd REAL
+        );
+
+        CREATE TABLE liquidity_pools (
+            pool_id TEXT PRIMARY KEY,
+            fee_bp INTEGER NOT NULL
+        );
+
+        CREATE TABLE liquidity_pool_snapshots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            pool_id TEXT NOT NULL,
+            reserve_a_amount REAL NOT NULL,
+            reserve_b_amount REAL NOT NULL,
+            total_value_usd REAL NOT NULL DEFAULT 0.0,
+            volume_usd REAL NOT NULL DEFAULT 0.0,
+            fees_usd REAL NOT NULL DEFAULT 0.0,
+            apy REAL NOT NULL DEFAULT 0.0,
+            impermanent_loss_pct REAL NOT NULL DEFAULT 0.0,
+            trade_count INTEGER NOT NULL DEFAULT 0,
+            snapshot_at DATETIME NOT NULL
+        );
+        "#,
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+fn at(s: &str) -> chrono::DateTime<Utc> {
+    chrono::DateTime::parse_from_rfc3339(s)
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+/// Three ledgers across two hours: a successful anchor payment and a failed
+/// payment in USDC, then an EURC payment in the next hour. The same payments
+/// are in `payments` as the Horizon indexer stores them, without a result.
+async fn seed_ledger_history(pool: &SqlitePool) {
+    for (sequence, close_time) in [
+        (100, "2026-01-22T10:15:00Z"),
+        (101, "2026-01-22T10:50:00Z"),
+        (102, "2026-01-22T11:05:00Z"),
+    ] {
+        sqlx::query("INSERT INTO ledgers (sequence, hash, close_time) VALUES ($1, $2, $3)")
+            .bind(sequence)
+            .bind(format!("hash-{}", sequence))
+            .bind(at(close_time))
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO transactions (hash, ledger_sequence, successful)
+        VALUES ('tx-a', 100, 1), ('tx-b', 101, 0);
+
+        INSERT INTO ledger_payments
+            (ledger_sequence, transaction_hash, operation_type, source_account, destination, asset_code, asset_issuer, amount)
+        VALUES
+            (100, 'tx-a', 'payment', 'GANCHOR', 'GUSER', 'USDC', 'GISSUER', '100.0000000'),
+            (101, 'tx-b', 'payment', 'GUSER', 'GOTHER', 'USDC', 'GISSUER', '50.0000000'),
+            (102, 'tx-c', 'payment', 'GUSER', 'GOTHER', 'EURC', 'GISSUER', '20.0000000');
+
+        INSERT INTO anchors (id, stellar_account) VALUES ('anchor-1', 'GANCHOR');
+        INSERT INTO liquidity_pools (pool_id, fee_bp) VALUES ('pool-1', 30);
+        "#,
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+
+    for (id, hash, asset_code, amount, created_at) in [
+        ("op-a", "tx-a", "USDC", 100.0, "2026-01-22T10:15:00Z"),
+        ("op-b", "tx-b", "USDC", 50.0, "2026-01-22T10:50:00Z"),
+        ("op-c", "tx-c", "EURC", 20.0, "2026-01-22T11:05:00Z"),
+    ] {
+        sqlx::query(
+            r#"
+            INSERT INTO payments
+                (id, transaction_hash, source_account, destination_account, asset_type, asset_code, asset_issuer, amount, created_at)
+            VALUES ($1, $2, 'GUSER', 'GOTHER', 'credit_alphanum4', $3, 'GISSUER', $4, $5)
+            "#,
+        )
+        .bind(id)
+        .bind(hash)
+        .bind(asset_code)
+        .bind(amount)
+        .bind(at(created_at))
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    for (reserve_a, snapshot_at) in [
+        (100.0, "2026-01-22T10:20:00Z"),
+        (121.0, "2026-01-22T10:40:00Z"),
+    ] {
+        sqlx::query(
+            r#"
+            INSERT INTO liquidity_pool_snapshots (pool_id, reserve_a_amount, reserve_b_amount, volume_usd, snapshot_at)
+            VALUES ('pool-1', $1, 100.0, 1000.0, $2)
+            "#,
+        )
+        .bind(reserve_a)
+        .bind(at(snapshot_at))
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+}
+
+async fn run_full_replay(pool: &SqlitePool, start: u64, end: u64) {
+    run_replay(pool, ReplayMode::Full, start, end).await;
+}
+
+async fn run_replay(pool: &SqlitePool, mode: ReplayMode, start: u64, end: u64) -> String {
+    let processor = CompositeEventProcessor::new()
+        .add_processor(Arc::new(SnapshotEventProcessor::new(pool.clone().into())))
+        .add_processor(Arc::new(CorridorMetricsProcessor::hourly(
+            pool.clone().into(),
+        )))
+        .add_processor(Arc::new(CorridorMetricsProcessor::daily(
+            pool.clone().into(),
+        )))
+        .add_processor(Arc::new(AnchorMetricsProcessor::new(pool.clone().into())))
+        .add_processor(Arc::new(LiquidityPoolSnapshotProcessor::new(
+            pool.clone().into(),
+        )));
+
+    let engine = ReplayEngine::new(
+        ReplayConfig::new()
+            .with_mode(mode)
+            .with_range(ReplayRange::FromTo { start, end }),
+        Arc::new(EventStorage::new(pool.clone().into())),
+        Arc::new(ReplayStorage::new(pool.clone().into())),
+        Arc::new(CheckpointManager::new(pool.clone().into())),
+        Arc::new(processor),
+        Arc::new(RwLock::new(StateBuilder::new(pool.clone().into()))),
+    )
+    .unwrap();
+
+    let metadata = engine.start().await.unwrap();
+    assert!(
+        matches!(
+            metadata.status,
+            stellar_insights_backend::replay::ReplayStatus::Completed {
+                events_failed: 0,
+                ..
+            }
+        ),
+        "unexpected status: {}",
+        metadata.status
+    );
+    metadata.session_id
+}
+
+async fn run_verification(pool: &SqlitePool, start: u64, end: u64) -> VerificationReport {
+    let session_id = run_replay(pool, ReplayMode::Verification, start, end).await;
+    ReplayStorage::new(pool.clone().into())
+        .load_verification_report(&session_id)
+        .await
+        .unwrap()
+        .expect("verification report stored")
+}
+
+#[tokio::test]
+async fn test_ledger_events_synthesized_in_order() {
+    let pool = setup_test_db().await;
+    setup_metrics_tables(&pool).await;
+    seed_ledger_history(&pool).await;
+
+    let storage = EventStorage::new(pool.into());
+    let events = storage
+        .get_ledger_events_in_range(100, 101, &EventFilter::default())
+        .await
+        .unwrap();
+
+    let kinds: Vec<&str> = events.iter().map(|e| e.event_type.as_str()).collect();
+    assert_eq!(
+        kinds,
+        vec!["ledger_closed", "payment", "ledger_closed", "payment"]
+    );
+    assert_eq!(events[3].data["successful"], false);
+    assert_eq!(events[3].timestamp, at("2026-01-22T10:50:00Z"));
+    assert_eq!(
+        storage.get_latest_ingested_ledger().await.unwrap(),
+        Some(102)
+    );
+}
+
+#[tokio::test]
+async fn test_full_replay_rebuilds_metrics_deterministically() {
+    let pool = setup_test_db().await;
+    setup_metrics_tables(&pool).await;
+    seed_ledger_history(&pool).await;
+
+    // A stale row from an earlier formula that no payment supports any more
+    sqlx::query(
+        r#"
+        INSERT INTO corridor_metrics_hourly
+            (id, corridor_key, asset_a_code, asset_a_issuer, asset_b_code, asset_b_issuer, hour_bucket, total_transactions)
+        VALUES ('stale', 'OLD:G->OLD:G', 'OLD', 'G', 'OLD', 'G', $1, 99)
+        "#,
+    )
+    .bind(at("2026-01-22T10:00:00Z").to_rfc3339())
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    for _ in 0..2 {
+        run_full_replay(&pool, 100, 102).await;
+
+        // Corridor rows come from `payments` like the aggregation service's,
+        // which has no result column, so the failed USDC payment counts as
+        // successful
+        let hourly: Vec<(String, String, i64, i64, f64)> = sqlx::query_as(
+            r#"
+            SELECT hour_bucket, corridor_key, total_transactions, successful_transactions, volume_usd
+            FROM corridor_metrics_hourly ORDER BY hour_bucket, corridor_key
+            "#,
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            hourly,
+            vec![
+                (
+                    at("2026-01-22T10:00:00Z").to_rfc3339(),
+                    "USDC:GISSUER->USDC:GISSUER".to_string(),
+                    2,
+                    2,
+                    150.0
+                ),
+                (
+                    at("2026-01-22T11:00:00Z").to_rfc3339(),
+                    "EURC:GISSUER->EURC:GISSUER".to_string(),
+                    1,
+                    1,
+                    20.0
+                ),
+            ]
+        );
+
+        let daily: Vec<(String, i64, f64)> = sqlx::query_as(
+            "SELECT corridor_key, total_transactions, success_rate FROM corridor_metrics ORDER BY corridor_key",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(daily.len(), 2);
+        assert_eq!(daily[1].1, 2);
+        assert!((daily[1].2 - 100.0).abs() < f64::EPSILON);
+
+        let anchor: Vec<(String, i64, f64)> = sqlx::query_as(
+            "SELECT timestamp, total_transactions, volume_usd FROM anchor_metrics_history WHERE anchor_id = 'anchor-1'",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            anchor,
+            vec![(at("2026-01-22T10:00:00Z").to_rfc3339(), 1, 100.0)]
+        );
+
+        let snapshots: Vec<(f64, f64, f64)> = sqlx::query_as(
+            "SELECT total_value_usd, fees_usd, impermanent_loss_pct FROM liquidity_pool_snapshots ORDER BY id",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert!((snapshots[0].0 - 200.0).abs() < 1e-9);
+        assert!((snapshots[0].1 - 3.0).abs() < 1e-9);
+        assert_eq!(snapshots[0].2, 0.0);
+        assert!(snapshots[1].2 > 0.0);
+    }
+}
+
+#[tokio::test]
+async fn test_partial_replay_keeps_payments_outside_range() {
+    let pool = setup_test_db().await;
+    setup_metrics_tables(&pool).await;
+    seed_ledger_history(&pool).await;
+
+    // Ledger 101 shares its hour with ledger 100, which is not replayed
+    run_full_replay(&pool, 101, 101).await;
+
+    let hourly: Vec<(String, i64)> =
+        sqlx::query_as("SELECT corridor_key, total_transactions FROM corridor_metrics_hourly")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(hourly, vec![("USDC:GISSUER->USDC:GISSUER".to_string(), 2)]);
+}
+
+#[tokio::test]
+async fn test_verification_replay_matches_rebuilt_tables() {
+    let pool = setup_test_db().await;
+    setup_metrics_tables(&pool).await;
+    seed_ledger_history(&pool).await;
+    run_full_replay(&pool, 100, 102).await;
+
+    let report = run_verification(&pool, 100, 102).await;
+    assert!(report.is_consistent(), "unexpected drift: {:?}", report);
+    assert_eq!(report.first_diverging_ledger, None);
+    // Two hourly and two daily corridor rows, one anchor entry, two snapshots
+    assert_eq!(report.rows_compared, 7);
+}
+
+#[tokio::test]
+async fn test_verification_replay_reports_drift_without_writing() {
+    let pool = setup_test_db().await;
+    setup_metrics_tables(&pool).await;
+    seed_ledger_history(&pool).await;
+    run_full_replay(&pool, 100, 102).await;
+
+    // Corrupt the live tables: drifted volume in the second hour, a lost
+    // anchor entry in the first and a row no payment supports
+    sqlx::query(
+        r#"
+        UPDATE corridor_metrics_hourly SET volume_usd = 25.0 WHERE corridor_key = 'EURC:GISSUER->EURC:GISSUER';
+        DELETE FROM anchor_metrics_history;
+        INSERT INTO corridor_metrics_hourly
+            (id, corridor_key, asset_a_code, asset_a_issuer, asset_b_code, asset_b_issuer, hour_bucket, total_transactions)
+        VALUES ('bogus', 'OLD:G->OLD:G', 'OLD', 'G', 'OLD', 'G', $1, 3);
+        "#,
+    )
+    .bind(at("2026-01-22T11:00:00Z").to_rfc3339())
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let report = run_verification(&pool, 100, 102).await;
+    assert_eq!(report.rows_mismatched, 3);
+    assert_eq!(report.first_diverging_ledger, Some(100));
+
+    let kinds: Vec<(&str, MismatchKind, Option<u64>)> = report
+        .mismatches
+        .iter()
+        .map(|m| (m.table.as_str(), m.kind, m.first_ledger))
+        .collect();
+    assert_eq!(
+        kinds[0],
+        ("anchor_metrics_history", MismatchKind::Missing, Some(100))
+    );
+    assert!(kinds.contains(&("corridor_metrics_hourly", MismatchKind::Drifted, Some(102))));
+    assert!(kinds.contains(&(
+        "corridor_metrics_hourly",
+        MismatchKind::Unexpected,
+        Some(102)
+    )));
+
+    let hourly = report
+        .tables
+        .iter()
+        .find(|t| t.table == "corridor_metrics_hourly")
+        .unwrap();
+    assert_eq!(hourly.drifted, 1);
+    assert_eq!(hourly.unexpected, 1);
+    assert_eq!(hourly.max_drift["volume_usd"], 5.0);
+
+    // The live tables are left as they were
+    let volume: f64 = sqlx::query_scalar(
+        "SELECT volume_usd FROM corridor_metrics_hourly WHERE corridor_key = 'EURC:GISSUER->EURC:GISSUER'",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(volume, 25.0);
+    let anchor_rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM anchor_metrics_history")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(anchor_rows, 0);
+}
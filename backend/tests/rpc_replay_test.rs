@@ -0,0 +1,232 @@
+// Offline tests replaying the Horizon fixtures in tests/fixtures/rpc.
+// The fixtures are synthetic; see tests/fixtures/rpc/README.md.
+
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use stellar_insights_backend::database::Database;
+use stellar_insights_backend::rpc::error::RpcError;
+use stellar_insights_backend::rpc::{ReplayTransport, StellarRpcClient};
+use stellar_insights_backend::services::aggregation::{AggregationConfig, AggregationService};
+use stellar_insights_backend::services::indexing::IndexingService;
+use stellar_insights_backend::services::liquidity_pool_analyzer::LiquidityPoolAnalyzer;
+use stellar_insights_backend::services::rollup::Resolution;
+
+const USDC_ISSUER: &str = "GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN";
+const EURC_ISSUER: &str = "GDHU6WRG4IEQXM5NZ4BMPKOXHW76MZM4Y2IEMFDVXBSDP6FJY4ITNPP2";
+
+fn replay_client() -> StellarRpcClient {
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/rpc");
+    let transport = ReplayTransport::from_dir(fixtures).expect("fixtures load");
+    StellarRpcClient::new_with_defaults(false).with_transport(Arc::new(transport))
+}
+
+/// In-memory database with only the given migrations applied
+async fn pool_with(migrations: &[&str]) -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    for migration in migrations {
+        sqlx::raw_sql(migration).execute(&pool).await.unwrap();
+    }
+    pool
+}
+
+async fn payments_pool() -> SqlitePool {
+    pool_with(&[
+        include_str!("../migrations/003_create_ingestion_and_payments.sql"),
+        include_str!("../migrations/005_create_corridor_aggregates.sql"),
+        include_str!("../migrations/029_create_corridor_metrics_rollups.sql"),
+        include_str!("../migrations/030_create_corridor_metric_sketches.sql"),
+    ])
+    .await
+}
+
+#[tokio::test]
+async fn test_replay_latest_ledger() {
+    let client = replay_client();
+    let ledger = client.fetch_latest_ledger().await.unwrap();
+
+    assert_eq!(ledger.sequence, 52_487_812);
+    assert_eq!(ledger.transaction_count, 249);
+    assert_eq!(ledger.base_fee, 100);
+}
+
+#[tokio::test]
+async fn test_replay_payments_parse_path_payments() {
+    let client = replay_client();
+    let payments = client.fetch_payments(3, None).await.unwrap();
+
+    assert_eq!(payments.len(), 3);
+    assert_eq!(payments[0].get_asset_code().as_deref(), Some("USDC"));
+    assert_eq!(payments[0].get_amount(), "1250.0000000");
+
+    let path_payment = &payments[1];
+    assert_eq!(
+        path_payment.operation_type.as_deref(),
+        Some("path_payment_strict_send")
+    );
+    assert_eq!(path_payment.source_asset_code.as_deref(), Some("USDC"));
+    assert_eq!(path_payment.get_asset_code().as_deref(), Some("EURC"));
+    assert!(path_payment.get_destination().is_some());
+
+    assert_eq!(payments[2].asset_type, "native");
+}
+
+#[tokio::test]
+async fn test_replay_unrecorded_request_fails_fast() {
+    let client = replay_client();
+    let result = client.fetch_trades(10, None).await;
+
+    assert!(matches!(
+        result,
+        Err(RpcError::ServerError { status: 404, .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_replay_payment_ingestion() {
+    let pool = payments_pool().await;
+    let db = Arc::new(Database::new(pool.clone().into()));
+    let indexing = IndexingService::new(Arc::new(replay_client()), Arc::clone(&db));
+
+    indexing.run_payment_ingestion().await.unwrap();
+
+    let stored: Vec<(String, Option<String>, f64)> =
+        sqlx::query_as("SELECT id, asset_code, amount FROM payments ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(stored.len(), 5);
+    assert_eq!(
+        stored[0],
+        (
+            "225432143441985537".to_string(),
+            Some("USDC".to_string()),
+            1250.0
+        )
+    );
+    // Path payments are stored with the asset that was delivered
+    assert_eq!(stored[1].1.as_deref(), Some("EURC"));
+    assert_eq!(
+        db.get_ingestion_cursor("payment_ingestion").await.unwrap(),
+        Some("225432143441985539".to_string())
+    );
+
+    // The next page is empty, so a second run stores nothing and keeps the cursor
+    indexing.run_payment_ingestion().await.unwrap();
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM payments")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 5);
+    assert_eq!(
+        db.get_ingestion_cursor("payment_ingestion").await.unwrap(),
+        Some("225432143441985539".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_replay_corridor_aggregation() {
+    let pool = payments_pool().await;
+    let db = Arc::new(Database::new(pool.clone().into()));
+    IndexingService::new(Arc::new(replay_client()), Arc::clone(&db))
+        .run_payment_ingestion()
+        .await
+        .unwrap();
+
+    // The fixtures are from July 2024, so look back far enough to cover them
+    let aggregation = AggregationService::new(
+        Arc::clone(&db),
+        AggregationConfig {
+            lookback_hours: 24 * 365 * 20,
+            ..AggregationConfig::default()
+        },
+    );
+    aggregation.run_hourly_aggregation().await.unwrap();
+
+    let corridors: Vec<(String, i64, f64)> = sqlx::query_as(
+        r#"
+        SELECT corridor_key, SUM(total_transactions), SUM(volume_usd)
+        FROM corridor_metrics_hourly
+        GROUP BY corridor_key
+        ORDER BY corridor_key
+        "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(corridors.len(), 3);
+
+    let corridor = |key: &str| {
+        corridors
+            .iter()
+            .find(|c| c.0 == key)
+            .unwrap_or_else(|| panic!("corridor {} aggregated", key))
+    };
+    let usdc = corridor(&format!("USDC:{0}->USDC:{0}", USDC_ISSUER));
+    assert_eq!(usdc.1, 2);
+    assert!((usdc.2 - 1730.25).abs() < 1e-9);
+    assert_eq!(corridor("XLM:native->XLM:native").1, 2);
+    assert_eq!(corridor(&format!("EURC:{0}->EURC:{0}", EURC_ISSUER)).1, 1);
+}
+
+#[tokio::test]
+async fn test_replay_aggregation_reruns_replace_hourly_sketches() {
+    let pool = payments_pool().await;
+    let db = Arc::new(Database::new(pool.clone().into()));
+    IndexingService::new(Arc::new(replay_client()), Arc::clone(&db))
+        .run_payment_ingestion()
+        .await
+        .unwrap();
+
+    let aggregation = AggregationService::new(
+        Arc::clone(&db),
+        AggregationConfig {
+            lookback_hours: 24 * 365 * 20,
+            ..AggregationConfig::default()
+        },
+    );
+
+    // Overlapping runs see the same payments; each hour's sketch is replaced
+    for _ in 0..2 {
+        aggregation.run_hourly_aggregation().await.unwrap();
+
+        let sketches = db
+            .rollup_db()
+            .fetch_sketches(
+                Resolution::Hour,
+                None,
+                chrono::DateTime::UNIX_EPOCH,
+                chrono::Utc::now() + chrono::Duration::hours(1),
+            )
+            .await
+            .unwrap();
+        let payments: u64 = sketches.iter().map(|s| s.amount.count()).sum();
+        assert_eq!(payments, 5);
+    }
+}
+
+#[tokio::test]
+async fn test_replay_liquidity_pool_sync() {
+    let pool = pool_with(&[include_str!("../migrations/009_create_liquidity_pools.sql")]).await;
+    let analyzer = LiquidityPoolAnalyzer::new(pool.clone().into(), Arc::new(replay_client()));
+
+    let count = analyzer.sync_pools().await.unwrap();
+    assert_eq!(count, 2);
+
+    let pools = analyzer.get_all_pools().await.unwrap();
+    let xlm_usdc = pools
+        .iter()
+        .find(|p| p.pool_id.starts_with("a468d41d"))
+        .expect("XLM/USDC pool synced");
+
+    assert_eq!(xlm_usdc.fee_bp, 30);
+    assert_eq!(xlm_usdc.trade_count_24h, 3);
+    assert!((xlm_usdc.reserve_a_amount - 18_420_551.334_010_2).abs() < 1e-6);
+
+    let stats = analyzer.get_pool_stats().await.unwrap();
+    assert_eq!(stats.total_pools, 2);
+}
@@ -8,15 +8,14 @@
 //! 5. Submit to smart contract ✅ (mocked)
 //! 6. Verify submission success ✅ (mocked)
 
-use sqlx::Row;
 use std::sync::Arc;
 use stellar_insights_backend::database::Database;
 use stellar_insights_backend::services::snapshot::SnapshotService;
 use stellar_insights_backend::snapshot::schema::AnalyticsSnapshot;
+use stellar_insights_backend::with_pool;
 
 async fn setup_test_database() -> Arc<Database> {
     let pool = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
-    let db = Database::new(pool);
 
     // Create test tables
     let _: sqlx::sqlite::SqliteQueryResult = sqlx::query(
@@ -35,7 +34,7 @@ async fn setup_test_database() -> Arc<Database> {
         )
     "#,
     )
-    .execute(db.pool())
+    .execute(&pool)
     .await
     .unwrap();
 
@@ -59,7 +58,7 @@ async fn setup_test_database() -> Arc<Database> {
         )
     "#,
     )
-    .execute(db.pool())
+    .execute(&pool)
     .await
     .unwrap();
 
@@ -77,7 +76,7 @@ async fn setup_test_database() -> Arc<Database> {
         )
     "#,
     )
-    .execute(db.pool())
+    .execute(&pool)
     .await
     .unwrap();
 
@@ -87,16 +86,16 @@ async fn setup_test_database() -> Arc<Database> {
         VALUES 
         ('00000000-0000-0000-0000-000000000001', 'Test Anchor 1', 'GTEST1', 1000, 950, 50, 100000.0, 500, 0.95, 'green'),
         ('00000000-0000-0000-0000-000000000002', 'Test Anchor 2', 'GTEST2', 2000, 1900, 100, 200000.0, 600, 0.95, 'green')
-    "#).execute(db.pool()).await.unwrap();
+    "#).execute(&pool).await.unwrap();
 
     let _: sqlx::sqlite::SqliteQueryResult = sqlx::query(r#"
         INSERT INTO corridor_metrics (id, corridor_key, asset_a_code, asset_a_issuer, asset_b_code, asset_b_issuer, date, total_transactions, successful_transactions, failed_transactions, success_rate, volume_usd, avg_settlement_latency_ms, liquidity_depth_usd)
         VALUES 
         ('00000000-0000-0000-0000-000000000003', 'USDC:ISSUER1->EURC:ISSUER2', 'USDC', 'ISSUER1', 'EURC', 'ISSUER2', datetime('now'), 500, 475, 25, 95.0, 50000.0, 250, 100000.0),
         ('00000000-0000-0000-0000-000000000004', 'USDC:ISSUER1->GBPC:ISSUER3', 'USDC', 'ISSUER1', 'GBPC', 'ISSUER3', datetime('now'), 300, 285, 15, 95.0, 30000.0, 300, 75000.0)
-    "#).execute(db.pool()).await.unwrap();
+    "#).execute(&pool).await.unwrap();
 
-    Arc::new(db)
+    Arc::new(Database::new(pool.into()))
 }
 
 #[tokio::test]
@@ -196,16 +195,14 @@ async fn test_acceptance_criteria_4_store_hash_in_database() {
     let result = service.generate_and_submit_snapshot(4).await.unwrap();
 
     // Verify stored in database
-    let stored: sqlx::sqlite::SqliteRow =
-        sqlx::query("SELECT id, hash, epoch, data FROM snapshots WHERE id = ?")
-            .bind(&result.snapshot_id)
-            .fetch_one(db.pool())
-            .await
-            .unwrap();
-
-    let stored_hash: String = stored.get("hash");
-    let stored_epoch: i64 = stored.get("epoch");
-    let stored_data: String = stored.get("data");
+    let (stored_hash, stored_epoch, stored_data): (String, i64, String) =
+        with_pool!(db.pool(), |pool| {
+            sqlx::query_as("SELECT hash, epoch, data FROM snapshots WHERE id = ?")
+                .bind(&result.snapshot_id)
+                .fetch_one(pool)
+                .await
+        })
+        .unwrap();
 
     assert_eq!(stored_hash, result.hash);
     assert_eq!(stored_epoch, 4);
@@ -274,12 +271,13 @@ async fn test_complete_workflow() {
     assert_eq!(json1, json2);
 
     // Verify database storage
-    let stored: sqlx::sqlite::SqliteRow = sqlx::query("SELECT hash FROM snapshots WHERE id = ?")
-        .bind(&result.snapshot_id)
-        .fetch_one(db.pool())
-        .await
-        .unwrap();
-    let stored_hash: String = stored.get("hash");
+    let stored_hash: String = with_pool!(db.pool(), |pool| {
+        sqlx::query_scalar("SELECT hash FROM snapshots WHERE id = ?")
+            .bind(&result.snapshot_id)
+            .fetch_one(pool)
+            .await
+    })
+    .unwrap();
     assert_eq!(stored_hash, result.hash);
 
     println!("✅ Complete workflow verified:");
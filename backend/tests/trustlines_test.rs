@@ -7,7 +7,7 @@ use stellar_insights_backend::services::trustline_analyzer::TrustlineAnalyzer;
 async fn test_trustlines_sync_and_query(pool: SqlitePool) {
     // Create a mock RPC client
     let rpc_client = Arc::new(StellarRpcClient::new_with_defaults(true));
-    let analyzer = TrustlineAnalyzer::new(pool.clone(), rpc_client);
+    let analyzer = TrustlineAnalyzer::new(pool.clone().into(), rpc_client);
 
     // Sync assets from mock Horizon data
     let count = analyzer.sync_assets().await.unwrap();
@@ -27,7 +27,7 @@ async fn test_trustlines_sync_and_query(pool: SqlitePool) {
 #[sqlx::test]
 async fn test_trustlines_snapshots(pool: SqlitePool) {
     let rpc_client = Arc::new(StellarRpcClient::new_with_defaults(true));
-    let analyzer = TrustlineAnalyzer::new(pool.clone(), rpc_client);
+    let analyzer = TrustlineAnalyzer::new(pool.clone().into(), rpc_client);
 
     // Sync and snapshot
     analyzer.sync_assets().await.unwrap();
@@ -47,7 +47,7 @@ async fn create_test_snapshot(
 #[tokio::test]
 async fn test_successful_verification() -> Result<()> {
     let pool = setup_test_db().await?;
-    let db = Arc::new(Database::new(pool.clone()));
+    let db = Arc::new(Database::new(pool.clone().into()));
     let service = VerificationRewardsService::new(db);
 
     let user_id = "test-user-1";
@@ -74,7 +74,7 @@ async fn test_successful_verification() -> Result<()> {
 #[tokio::test]
 async fn test_failed_verification() -> Result<()> {
     let pool = setup_test_db().await?;
-    let db = Arc::new(Database::new(pool.clone()));
+    let db = Arc::new(Database::new(pool.clone().into()));
     let service = VerificationRewardsService::new(db);
 
     let user_id = "test-user-2";
@@ -102,7 +102,7 @@ async fn test_failed_verification() -> Result<()> {
 #[tokio::test]
 async fn test_user_stats() -> Result<()> {
     let pool = setup_test_db().await?;
-    let db = Arc::new(Database::new(pool.clone()));
+    let db = Arc::new(Database::new(pool.clone().into()));
     let service = VerificationRewardsService::new(db);
 
     let user_id = "test-user-3";
@@ -134,7 +134,7 @@ async fn test_user_stats() -> Result<()> {
 #[tokio::test]
 async fn test_leaderboard() -> Result<()> {
     let pool = setup_test_db().await?;
-    let db = Arc::new(Database::new(pool.clone()));
+    let db = Arc::new(Database::new(pool.clone().into()));
     let service = VerificationRewardsService::new(db);
 
     // Create multiple users with verifications
@@ -164,7 +164,7 @@ async fn test_leaderboard() -> Result<()> {
 #[tokio::test]
 async fn test_daily_limit() -> Result<()> {
     let pool = setup_test_db().await?;
-    let db = Arc::new(Database::new(pool.clone()));
+    let db = Arc::new(Database::new(pool.clone().into()));
     let service = VerificationRewardsService::new(db);
 
     let user_id = "test-user-limit";
@@ -200,7 +200,7 @@ async fn test_daily_limit() -> Result<()> {
 #[tokio::test]
 async fn test_verification_history() -> Result<()> {
     let pool = setup_test_db().await?;
-    let db = Arc::new(Database::new(pool.clone()));
+    let db = Arc::new(Database::new(pool.clone().into()));
     let service = VerificationRewardsService::new(db);
 
     let user_id = "test-user-history";